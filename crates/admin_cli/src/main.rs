@@ -1,5 +1,6 @@
 use std::{error::Error, io::Write};
 
+use chrono::{Duration, NaiveDate, NaiveTime, Utc};
 use clap::{Args, Parser, Subcommand};
 use crossterm::{
     cursor,
@@ -9,9 +10,13 @@ use crossterm::{
     terminal,
     terminal::ClearType,
 };
-use engine::{Currency, Engine};
+use engine::{
+    CsvImportCmd, CsvMapping, Currency, Engine, ImportPreview, ImportVaultCmd, Money,
+    StatementFormat, StatementImportCmd, VaultExport,
+};
 use migration::MigratorTrait;
-use sea_orm::{Database, DatabaseConnection, EntityTrait, Set};
+use sea_orm::{ActiveModelTrait, Database, DatabaseConnection, EntityTrait, Set};
+use uuid::Uuid;
 
 mod users {
     use sea_orm::entity::prelude::*;
@@ -24,6 +29,7 @@ mod users {
         pub password: String,
         pub telegram_id: Option<String>,
         pub pair_code: Option<String>,
+        pub pair_code_expires_at: Option<DateTimeUtc>,
     }
 
     #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -34,7 +40,7 @@ mod users {
 
 #[derive(Parser, Debug)]
 #[command(name = "sparagne_admin")]
-#[command(about = "Admin utilities for Sparagne (bootstrap users/vaults)")]
+#[command(about = "Admin utilities for Sparagne (bootstrap users/vaults, imports)")]
 struct Cli {
     /// Database connection string (also read from `DATABASE_URL`).
     #[arg(
@@ -52,6 +58,7 @@ struct Cli {
 enum Command {
     User(User),
     Vault(Vault),
+    Import(Import),
 }
 
 #[derive(Args, Debug)]
@@ -63,6 +70,10 @@ struct User {
 #[derive(Subcommand, Debug)]
 enum UserCommand {
     Create(UserCreateArgs),
+    /// Replace a user's password (prompted, stored as an Argon2id hash).
+    SetPassword(UserSetPasswordArgs),
+    /// Generate a one-time Telegram pair code, replacing any previous one.
+    PairCode(UserPairCodeArgs),
 }
 
 #[derive(Args, Debug)]
@@ -71,8 +82,21 @@ struct UserCreateArgs {
     username: String,
     #[arg(long)]
     telegram_id: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct UserSetPasswordArgs {
+    #[arg(long)]
+    username: String,
+}
+
+#[derive(Args, Debug)]
+struct UserPairCodeArgs {
     #[arg(long)]
-    pair_code: Option<String>,
+    username: String,
+    /// Minutes before the code expires.
+    #[arg(long, default_value_t = 15)]
+    ttl_minutes: u32,
 }
 
 #[derive(Args, Debug)]
@@ -84,6 +108,17 @@ struct Vault {
 #[derive(Subcommand, Debug)]
 enum VaultCommand {
     Create(VaultCreateArgs),
+    /// Write a full JSON export of a vault.
+    Export(VaultExportArgs),
+    /// Recreate a vault from a JSON export.
+    Import(VaultImportArgs),
+    /// Lock every transaction up to a date against creates, edits and voids.
+    CloseBooks(VaultCloseBooksArgs),
+    /// Remove the books lock of a vault.
+    ReopenBooks(VaultReopenBooksArgs),
+    /// Check stored balances against the ledger; exits with status 1 if
+    /// anything is off.
+    Check(VaultCheckArgs),
 }
 
 #[derive(Args, Debug)]
@@ -96,13 +131,142 @@ struct VaultCreateArgs {
     currency: String,
 }
 
-fn parse_currency(raw: &str) -> Result<Currency, String> {
-    match raw {
-        "EUR" | "eur" => Ok(Currency::Eur),
-        other => Err(format!("unsupported currency: {other}")),
+#[derive(Args, Debug)]
+struct VaultExportArgs {
+    #[arg(long)]
+    vault_id: String,
+    /// Vault owner.
+    #[arg(long)]
+    owner: String,
+    /// Output file (default: stdout).
+    #[arg(long)]
+    output: Option<std::path::PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct VaultImportArgs {
+    /// JSON export to import.
+    #[arg(long)]
+    file: std::path::PathBuf,
+    /// Owner of the imported vault (default: the exported owner).
+    #[arg(long)]
+    owner: Option<String>,
+    /// Vault name (default: the exported name).
+    #[arg(long)]
+    name: Option<String>,
+    /// Assign new ids, e.g. to copy a vault within the same instance.
+    #[arg(long)]
+    new_ids: bool,
+}
+
+#[derive(Args, Debug)]
+struct VaultCloseBooksArgs {
+    #[arg(long)]
+    vault_id: String,
+    /// Vault owner.
+    #[arg(long)]
+    owner: String,
+    /// Last closed day (`YYYY-MM-DD`, UTC), included.
+    #[arg(long)]
+    until: NaiveDate,
+}
+
+#[derive(Args, Debug)]
+struct VaultReopenBooksArgs {
+    #[arg(long)]
+    vault_id: String,
+    /// Vault owner.
+    #[arg(long)]
+    owner: String,
+}
+
+#[derive(Args, Debug)]
+struct VaultCheckArgs {
+    #[arg(long)]
+    vault_id: String,
+    /// Vault owner.
+    #[arg(long)]
+    owner: String,
+}
+
+#[derive(Args, Debug)]
+struct Import {
+    #[command(subcommand)]
+    command: ImportCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum ImportCommand {
+    /// Import a bank CSV statement (dry run unless `--commit` is given).
+    Csv(ImportCsvArgs),
+    /// Import an OFX, QIF or CAMT.053 statement (dry run unless `--commit`
+    /// is given).
+    Statement(ImportStatementArgs),
+}
+
+#[derive(Args, Debug)]
+struct ImportCsvArgs {
+    #[arg(long)]
+    vault_id: String,
+    /// User the transactions are booked as (needs write access).
+    #[arg(long)]
+    user: String,
+    /// CSV statement to import.
+    #[arg(long)]
+    file: std::path::PathBuf,
+    /// JSON mapping profile (`engine::CsvMapping`).
+    #[arg(long)]
+    mapping: std::path::PathBuf,
+    #[arg(long)]
+    wallet_id: Option<Uuid>,
+    #[arg(long)]
+    flow_id: Option<Uuid>,
+    /// Book the rows instead of printing a preview.
+    #[arg(long)]
+    commit: bool,
+    /// Also book rows that look already booked.
+    #[arg(long)]
+    include_duplicates: bool,
+}
+
+#[derive(Args, Debug)]
+struct ImportStatementArgs {
+    #[arg(long)]
+    vault_id: String,
+    /// User the transactions are booked as (needs write access).
+    #[arg(long)]
+    user: String,
+    /// Statement to import.
+    #[arg(long)]
+    file: std::path::PathBuf,
+    /// `ofx`, `qif` or `camt053`.
+    #[arg(long, value_parser = parse_statement_format)]
+    format: StatementFormat,
+    #[arg(long)]
+    wallet_id: Option<Uuid>,
+    #[arg(long)]
+    flow_id: Option<Uuid>,
+    /// Book the rows instead of printing a preview.
+    #[arg(long)]
+    commit: bool,
+    /// Also book rows that look already booked but carry a new bank reference.
+    #[arg(long)]
+    include_duplicates: bool,
+}
+
+fn parse_statement_format(raw: &str) -> Result<StatementFormat, String> {
+    match raw.to_ascii_lowercase().as_str() {
+        "ofx" => Ok(StatementFormat::Ofx),
+        "qif" => Ok(StatementFormat::Qif),
+        "camt053" | "camt.053" | "camt" => Ok(StatementFormat::Camt053),
+        other => Err(format!("unknown statement format: {other}")),
     }
 }
 
+fn parse_currency(raw: &str) -> Result<Currency, String> {
+    Currency::try_from(raw).map_err(|err| err.to_string())
+}
+
 struct RawModeGuard;
 
 impl RawModeGuard {
@@ -206,6 +370,33 @@ async fn connect_db(
     Ok(db)
 }
 
+/// Prints an import dry run; exits with status 1 if any row failed to parse.
+fn print_import_preview(preview: &ImportPreview) {
+    for row in &preview.rows {
+        let meta = row.draft.meta();
+        let duplicate = match row.duplicate_of {
+            Some(id) if row.already_imported => format!(" [already imported as {id}]"),
+            Some(id) => format!(" [duplicate of {id}]"),
+            None => String::new(),
+        };
+        println!(
+            "{:>5}  {}  {:>16}  {}  {}{duplicate}",
+            row.line,
+            meta.occurred_at.format("%Y-%m-%d"),
+            Money::new(row.draft.signed_amount_minor()).format(preview.currency),
+            meta.category.as_deref().unwrap_or("-"),
+            meta.note.as_deref().unwrap_or(""),
+        );
+    }
+    for err in &preview.errors {
+        eprintln!("line {}: {}", err.line, err.message);
+    }
+    println!("dry run: pass --commit to import");
+    if !preview.errors.is_empty() {
+        std::process::exit(1);
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     let cli = Cli::parse();
@@ -229,14 +420,54 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
 
             let user = users::ActiveModel {
                 username: Set(args.username.clone()),
-                password: Set(password),
+                password: Set(engine::hash_password(&password)?),
                 telegram_id: Set(args.telegram_id),
-                pair_code: Set(args.pair_code),
+                pair_code: Set(None),
+                pair_code_expires_at: Set(None),
             };
             users::Entity::insert(user).exec(&db).await?;
 
             println!("created user: {}", args.username);
         }
+        Command::User(User {
+            command: UserCommand::SetPassword(args),
+        }) => {
+            let Some(user) = users::Entity::find_by_id(args.username.clone())
+                .one(&db)
+                .await?
+            else {
+                eprintln!("user not found: {}", args.username);
+                std::process::exit(1);
+            };
+
+            let password = prompt_password_twice()?;
+            let mut user: users::ActiveModel = user.into();
+            user.password = Set(engine::hash_password(&password)?);
+            user.update(&db).await?;
+
+            println!("updated password for user: {}", args.username);
+        }
+        Command::User(User {
+            command: UserCommand::PairCode(args),
+        }) => {
+            let Some(user) = users::Entity::find_by_id(args.username.clone())
+                .one(&db)
+                .await?
+            else {
+                eprintln!("user not found: {}", args.username);
+                std::process::exit(1);
+            };
+
+            let code = engine::generate_pair_code();
+            let expires_at = Utc::now() + Duration::minutes(i64::from(args.ttl_minutes));
+            let mut user: users::ActiveModel = user.into();
+            user.pair_code = Set(Some(engine::hash_pair_code(&code)));
+            user.pair_code_expires_at = Set(Some(expires_at));
+            user.update(&db).await?;
+
+            println!("pair code for {}: {code}", args.username);
+            println!("expires at {}", expires_at.to_rfc3339());
+        }
         Command::Vault(Vault {
             command: VaultCommand::Create(args),
         }) => {
@@ -263,6 +494,130 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
                 .await?;
             println!("created vault: {} ({vault_id})", args.name);
         }
+        Command::Vault(Vault {
+            command: VaultCommand::Export(args),
+        }) => {
+            let engine = Engine::builder().database(db.clone()).build().await?;
+            let export = engine.export_vault(&args.vault_id, &args.owner).await?;
+            let json = serde_json::to_string_pretty(&export)?;
+            match args.output {
+                Some(path) => {
+                    std::fs::write(&path, json)?;
+                    eprintln!(
+                        "exported {} transactions to {}",
+                        export.transactions.len(),
+                        path.display()
+                    );
+                }
+                None => println!("{json}"),
+            }
+        }
+        Command::Vault(Vault {
+            command: VaultCommand::Import(args),
+        }) => {
+            let export: VaultExport = serde_json::from_str(&std::fs::read_to_string(&args.file)?)?;
+            let mut cmd = ImportVaultCmd::new(export).new_ids(args.new_ids);
+            cmd.owner = args.owner;
+            cmd.name = args.name;
+
+            let engine = Engine::builder().database(db.clone()).build().await?;
+            let report = engine.import_vault(cmd).await?;
+            for user in &report.skipped_members {
+                eprintln!("skipped member (unknown user): {user}");
+            }
+            println!("imported vault: {}", report.vault_id);
+        }
+        Command::Vault(Vault {
+            command: VaultCommand::CloseBooks(args),
+        }) => {
+            // The engine locks transactions before an instant: the day after
+            // the last closed one, at midnight UTC.
+            let Some(until) = args.until.succ_opt() else {
+                eprintln!("invalid date: {}", args.until);
+                std::process::exit(2);
+            };
+            let until = until.and_time(NaiveTime::MIN).and_utc();
+
+            let engine = Engine::builder().database(db.clone()).build().await?;
+            engine
+                .set_books_closed_until(&args.vault_id, Some(until), &args.owner)
+                .await?;
+            println!("books closed through {}", args.until);
+        }
+        Command::Vault(Vault {
+            command: VaultCommand::ReopenBooks(args),
+        }) => {
+            let engine = Engine::builder().database(db.clone()).build().await?;
+            engine
+                .set_books_closed_until(&args.vault_id, None, &args.owner)
+                .await?;
+            println!("books reopened");
+        }
+        Command::Vault(Vault {
+            command: VaultCommand::Check(args),
+        }) => {
+            let engine = Engine::builder().database(db.clone()).build().await?;
+            let report = engine.verify_vault(&args.vault_id, &args.owner).await?;
+            for issue in &report.issues {
+                println!("{issue}");
+            }
+            println!(
+                "checked {} wallets, {} flows, {} legs: {} issues",
+                report.wallets_checked,
+                report.flows_checked,
+                report.legs_checked,
+                report.issues.len()
+            );
+            if !report.is_clean() {
+                std::process::exit(1);
+            }
+        }
+        Command::Import(Import {
+            command: ImportCommand::Csv(args),
+        }) => {
+            let mapping: CsvMapping =
+                serde_json::from_str(&std::fs::read_to_string(&args.mapping)?)?;
+            let data = std::fs::read_to_string(&args.file)?;
+            let mut cmd = CsvImportCmd::new(&args.vault_id, &args.user, mapping, data)
+                .include_duplicates(args.include_duplicates);
+            cmd.wallet_id = args.wallet_id;
+            cmd.flow_id = args.flow_id;
+
+            let engine = Engine::builder().database(db.clone()).build().await?;
+            if args.commit {
+                let report = engine.import_csv(cmd).await?;
+                println!(
+                    "imported {} transactions, skipped {} duplicates",
+                    report.created.len(),
+                    report.skipped_duplicates
+                );
+            } else {
+                let preview = engine.preview_csv_import(cmd).await?;
+                print_import_preview(&preview);
+            }
+        }
+        Command::Import(Import {
+            command: ImportCommand::Statement(args),
+        }) => {
+            let data = std::fs::read_to_string(&args.file)?;
+            let mut cmd = StatementImportCmd::new(&args.vault_id, &args.user, args.format, data)
+                .include_duplicates(args.include_duplicates);
+            cmd.wallet_id = args.wallet_id;
+            cmd.flow_id = args.flow_id;
+
+            let engine = Engine::builder().database(db.clone()).build().await?;
+            if args.commit {
+                let report = engine.import_statement(cmd).await?;
+                println!(
+                    "imported {} transactions, skipped {} duplicates",
+                    report.created.len(),
+                    report.skipped_duplicates
+                );
+            } else {
+                let preview = engine.preview_statement_import(cmd).await?;
+                print_import_preview(&preview);
+            }
+        }
     }
 
     Ok(())
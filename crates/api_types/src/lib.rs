@@ -1,18 +1,232 @@
 use chrono::{DateTime, FixedOffset};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// ISO 4217 currency code (mirrors `engine::Currency`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Currency {
     #[default]
     Eur,
+    Usd,
+    Gbp,
+    Chf,
+    Jpy,
+    Cad,
+    Aud,
+    Nzd,
+    Sek,
+    Nok,
+    Dkk,
+    Pln,
+    Czk,
+    Huf,
+    Ron,
+    Cny,
+    Inr,
+    Krw,
+    Brl,
+    Mxn,
+    Bhd,
+    Kwd,
+    Omr,
+    Jod,
+    Tnd,
+}
+
+impl Currency {
+    /// All supported currencies.
+    pub const ALL: [Currency; 25] = [
+        Currency::Eur,
+        Currency::Usd,
+        Currency::Gbp,
+        Currency::Chf,
+        Currency::Jpy,
+        Currency::Cad,
+        Currency::Aud,
+        Currency::Nzd,
+        Currency::Sek,
+        Currency::Nok,
+        Currency::Dkk,
+        Currency::Pln,
+        Currency::Czk,
+        Currency::Huf,
+        Currency::Ron,
+        Currency::Cny,
+        Currency::Inr,
+        Currency::Krw,
+        Currency::Brl,
+        Currency::Mxn,
+        Currency::Bhd,
+        Currency::Kwd,
+        Currency::Omr,
+        Currency::Jod,
+        Currency::Tnd,
+    ];
+
+    /// Canonical currency code.
+    #[must_use]
+    pub const fn code(self) -> &'static str {
+        match self {
+            Currency::Eur => "EUR",
+            Currency::Usd => "USD",
+            Currency::Gbp => "GBP",
+            Currency::Chf => "CHF",
+            Currency::Jpy => "JPY",
+            Currency::Cad => "CAD",
+            Currency::Aud => "AUD",
+            Currency::Nzd => "NZD",
+            Currency::Sek => "SEK",
+            Currency::Nok => "NOK",
+            Currency::Dkk => "DKK",
+            Currency::Pln => "PLN",
+            Currency::Czk => "CZK",
+            Currency::Huf => "HUF",
+            Currency::Ron => "RON",
+            Currency::Cny => "CNY",
+            Currency::Inr => "INR",
+            Currency::Krw => "KRW",
+            Currency::Brl => "BRL",
+            Currency::Mxn => "MXN",
+            Currency::Bhd => "BHD",
+            Currency::Kwd => "KWD",
+            Currency::Omr => "OMR",
+            Currency::Jod => "JOD",
+            Currency::Tnd => "TND",
+        }
+    }
+}
+
+/// A list in a query string, written as `a,b,c` (OpenAPI `style: form`,
+/// `explode: false`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommaSeparated<T>(pub Vec<T>);
+
+impl<T: Serialize> Serialize for CommaSeparated<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut items = Vec::with_capacity(self.0.len());
+        for item in &self.0 {
+            match serde_json::to_value(item).map_err(serde::ser::Error::custom)? {
+                serde_json::Value::String(item) => items.push(item),
+                other => items.push(other.to_string()),
+            }
+        }
+        serializer.serialize_str(&items.join(","))
+    }
+}
+
+impl<'de, T: serde::de::DeserializeOwned> Deserialize<'de> for CommaSeparated<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{IntoDeserializer, value::Error};
+
+        let raw = String::deserialize(deserializer)?;
+        raw.split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| T::deserialize(IntoDeserializer::<Error>::into_deserializer(item)))
+            .collect::<Result<_, _>>()
+            .map(Self)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl<T: schemars::JsonSchema> schemars::JsonSchema for CommaSeparated<T> {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        format!("CommaSeparated_{}", T::schema_name()).into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "array",
+            "items": generator.subschema_for::<T>(),
+        })
+    }
+}
+
+pub mod error {
+    use super::*;
+
+    /// Machine-readable reason of a failed request.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ErrorCode {
+        /// A flow cap would be exceeded; see [`ErrorDetails::FlowLimit`].
+        MaxBalanceReached,
+        /// A flow balance would go negative; see [`ErrorDetails::FlowLimit`].
+        InsufficientFunds,
+        NotFound,
+        AlreadyExists,
+        InvalidAmount,
+        InvalidName,
+        InvalidId,
+        InvalidCursor,
+        InvalidFlow,
+        InvalidRole,
+        CurrencyMismatch,
+        InvalidExport,
+        InvalidRule,
+        InvalidPassword,
+        /// The transaction is reconciled; unlock it before changing it.
+        TransactionLocked,
+        /// The change touches a period whose books the owner has closed.
+        BooksClosed,
+        /// The pair code does not belong to any user, or has expired.
+        InvalidPairCode,
+        /// Too many failed attempts; retry later.
+        TooManyRequests,
+        /// Missing or rejected credentials.
+        Unauthorized,
+        /// Authenticated, but not allowed to do this.
+        Forbidden,
+        /// The request could not be parsed or is missing arguments.
+        BadRequest,
+        PayloadTooLarge,
+        UnsupportedMediaType,
+        MethodNotAllowed,
+        Internal,
+    }
+
+    /// Structured context of an error, by kind.
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(tag = "kind", rename_all = "snake_case")]
+    pub enum ErrorDetails {
+        /// The flow whose cap or balance check failed, in its currency's
+        /// minor units.
+        FlowLimit {
+            flow_id: Uuid,
+            flow_name: String,
+            currency: Currency,
+            /// The cap, or `0` for `insufficient_funds`.
+            limit_minor: i64,
+            /// Balance (income total, for income-capped flows) the request
+            /// would have left the flow with.
+            attempted_minor: i64,
+            /// How far past the limit the request would go.
+            excess_minor: i64,
+        },
+    }
+
+    /// Body of every error response.
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    pub struct ErrorResponse {
+        pub code: ErrorCode,
+        /// English description, for logs and as a fallback.
+        pub message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub details: Option<ErrorDetails>,
+    }
 }
 
 pub mod cash_flow {
     use super::*;
+    use chrono::NaiveDate;
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CashFlowGet {
         pub vault_id: String,
         /// Cash flow id (UUID).
@@ -22,6 +236,23 @@ pub mod cash_flow {
         /// Cash flow name (legacy convenience).
         pub name: Option<String>,
     }
+
+    /// A cash flow with its cap state, amounts in vault currency minor units.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct CashFlowView {
+        pub id: Uuid,
+        pub name: String,
+        pub balance: i64,
+        /// Cap, for capped flows.
+        pub max_balance: Option<i64>,
+        /// Income counted against the cap, for income-capped flows.
+        pub income_balance: Option<i64>,
+        pub currency: Currency,
+        pub archived: bool,
+        /// Savings goal, if any.
+        pub target_minor: Option<i64>,
+        pub target_date: Option<NaiveDate>,
+    }
 }
 
 pub mod wallet {
@@ -31,38 +262,80 @@ pub mod wallet {
     ///
     /// `opening_balance_minor` is applied as an "opening" transaction at
     /// `occurred_at`.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct WalletNew {
         pub vault_id: String,
         pub name: String,
-        /// Initial balance in minor units (can be negative).
+        /// Wallet currency (defaults to the vault currency).
+        #[serde(default)]
+        pub currency: Option<Currency>,
+        /// Initial balance in minor units of the wallet currency (can be
+        /// negative).
         pub opening_balance_minor: i64,
+        /// Rate from the wallet currency to the vault currency, required for
+        /// a non-zero opening balance of a foreign-currency wallet.
+        #[serde(default)]
+        pub exchange_rate: Option<String>,
         /// RFC3339 timestamp, including timezone offset (local user time).
         pub occurred_at: DateTime<FixedOffset>,
     }
 
     /// Response body for wallet creation.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct WalletCreated {
         pub id: Uuid,
     }
 
     /// Patch an existing wallet.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct WalletUpdate {
         pub vault_id: String,
         pub name: Option<String>,
         pub archived: Option<bool>,
     }
+
+    /// Reconcile a wallet against a bank statement.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct WalletReconcile {
+        pub vault_id: String,
+        /// End of the statement: transactions that occurred before it are
+        /// covered.
+        pub statement_date: DateTime<FixedOffset>,
+        /// Closing balance printed on the statement, in wallet currency.
+        pub statement_balance_minor: i64,
+    }
+
+    /// Cleared balance of a wallet compared with a statement.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct ReconciliationView {
+        pub wallet_id: Uuid,
+        pub currency: Currency,
+        pub statement_balance_minor: i64,
+        /// Sum of the cleared and reconciled transactions before the
+        /// statement date.
+        pub cleared_balance_minor: i64,
+        /// Statement balance minus cleared balance: zero when the wallet
+        /// reconciles.
+        pub difference_minor: i64,
+        /// Cleared transactions covered by the statement, reconciled on
+        /// commit.
+        pub cleared_count: usize,
+        /// Transactions before the statement date that are not cleared yet.
+        pub uncleared_count: usize,
+        pub uncleared_minor: i64,
+        /// Transactions already reconciled by earlier statements.
+        pub reconciled_count: usize,
+    }
 }
 
 pub mod flow {
     use super::*;
+    use chrono::NaiveDate;
 
     /// How a flow enforces an upper bound.
     ///
     /// Amounts are expressed in integer minor units (e.g. cents for EUR).
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
     #[serde(tag = "mode", rename_all = "snake_case")]
     pub enum FlowMode {
         Unlimited,
@@ -70,15 +343,30 @@ pub mod flow {
         IncomeCapped { cap_minor: i64 },
     }
 
+    /// Savings goal of a flow, in vault currency minor units.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(tag = "goal", rename_all = "snake_case")]
+    pub enum FlowGoal {
+        /// No goal.
+        Unset,
+        /// Reach `target_minor`, optionally by `target_date`.
+        Target {
+            target_minor: i64,
+            target_date: Option<NaiveDate>,
+        },
+    }
+
     /// Create a new flow in a vault.
     ///
     /// `opening_balance_minor` is modeled as an opening allocation transfer
     /// from Unallocated → this flow at `occurred_at`.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct FlowNew {
         pub vault_id: String,
         pub name: String,
         pub mode: FlowMode,
+        /// Savings goal (default: none).
+        pub goal: Option<FlowGoal>,
         /// Initial allocation for this flow (must be >= 0).
         pub opening_balance_minor: i64,
         /// RFC3339 timestamp, including timezone offset (local user time).
@@ -86,31 +374,33 @@ pub mod flow {
     }
 
     /// Response body for flow creation.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct FlowCreated {
         pub id: Uuid,
     }
 
     /// Patch an existing flow.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct FlowUpdate {
         pub vault_id: String,
         pub name: Option<String>,
         pub archived: Option<bool>,
         pub mode: Option<FlowMode>,
+        pub goal: Option<FlowGoal>,
     }
 }
 
 pub mod vault {
     use super::*;
+    use chrono::NaiveDate;
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct VaultNew {
         pub name: String,
         pub currency: Option<Currency>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct Vault {
         pub id: Option<String>,
         pub name: Option<String>,
@@ -121,7 +411,7 @@ pub mod vault {
     ///
     /// This is a single "read model" response that includes wallets and flows,
     /// so clients don't need DB access or multiple ad-hoc queries.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct VaultSnapshot {
         pub id: String,
         pub name: String,
@@ -131,21 +421,46 @@ pub mod vault {
         pub unallocated_flow_id: Uuid,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct WalletView {
         pub id: Uuid,
         pub name: String,
+        /// Balance in minor units of the wallet `currency`.
         pub balance_minor: i64,
+        pub currency: Currency,
         pub archived: bool,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct FlowView {
         pub id: Uuid,
         pub name: String,
         pub balance_minor: i64,
         pub archived: bool,
         pub is_unallocated: bool,
+        /// Progress towards the savings goal, for flows that have one.
+        pub goal: Option<FlowGoalView>,
+    }
+
+    /// Progress of a flow towards its savings goal, in vault currency minor
+    /// units.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct FlowGoalView {
+        pub target_minor: i64,
+        pub target_date: Option<NaiveDate>,
+        /// Current balance, floored at zero.
+        pub saved_minor: i64,
+        /// Amount still missing, floored at zero.
+        pub remaining_minor: i64,
+        /// Saved share of the target in basis points (10000 when reached).
+        pub progress_bps: u32,
+        /// Average net monthly change of the flow over the last 3 months.
+        pub monthly_contribution_minor: i64,
+        /// Monthly amount needed to reach the target by `target_date`.
+        pub required_monthly_minor: Option<i64>,
+        /// Date the target is reached at the current monthly contribution;
+        /// absent once reached or when the flow is not growing.
+        pub projected_completion: Option<NaiveDate>,
     }
 }
 
@@ -153,15 +468,22 @@ pub mod category {
     use super::*;
 
     /// List categories for a vault.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryList {
         pub vault_id: String,
         /// If true, includes archived categories (default: false).
         pub include_archived: Option<bool>,
     }
 
+    /// Query string of `GET /v2/vaults/{vault_id}/categories`.
+    #[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+    pub struct CategoryListQuery {
+        /// If true, includes archived categories (default: false).
+        pub include_archived: Option<bool>,
+    }
+
     /// A category entry for clients.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryView {
         pub id: Uuid,
         pub name: String,
@@ -169,26 +491,26 @@ pub mod category {
         pub is_system: bool,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryListResponse {
         pub categories: Vec<CategoryView>,
     }
 
     /// Create a new category in a vault.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryCreate {
         pub vault_id: String,
         pub name: String,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryCreated {
         pub id: Uuid,
         pub name: String,
     }
 
     /// Patch a category.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryUpdate {
         pub vault_id: String,
         pub name: Option<String>,
@@ -196,64 +518,64 @@ pub mod category {
     }
 
     /// List aliases for a category.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryAliasList {
         pub vault_id: String,
     }
 
     /// Alias entry.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryAliasView {
         pub id: Uuid,
         pub alias: String,
         pub category_id: Uuid,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryAliasListResponse {
         pub aliases: Vec<CategoryAliasView>,
     }
 
     /// Create a category alias.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryAliasCreate {
         pub vault_id: String,
         pub alias: String,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryAliasCreated {
         pub id: Uuid,
         pub alias: String,
     }
 
     /// Delete a category alias.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryAliasDelete {
         pub vault_id: String,
     }
 
     /// Merge a category into another category.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryMerge {
         pub vault_id: String,
         pub into_category_id: Uuid,
     }
 
     /// Preview merge conflicts for a category merge.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryMergePreview {
         pub vault_id: String,
         pub into_category_id: Uuid,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryMergeConflict {
         pub kind: String,
         pub value: String,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct CategoryMergePreviewResponse {
         pub ok: bool,
         pub conflicts: Vec<CategoryMergeConflict>,
@@ -263,11 +585,21 @@ pub mod category {
 pub mod user {
     use super::*;
 
-    #[derive(Debug, Serialize, Deserialize)]
+    /// Pair a Telegram account using the one-time code set for the user.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct PairUser {
         pub code: String,
         pub telegram_id: String,
     }
+
+    /// Credential minted for a paired Telegram account. The bot stores the
+    /// secret and sends it as `Authorization: Bearer <secret>` on behalf of
+    /// that account; it is never shown again.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct PairUserResponse {
+        pub username: String,
+        pub secret: String,
+    }
 }
 
 pub mod membership {
@@ -279,7 +611,7 @@ pub mod membership {
     /// - `owner`: full access and can manage members.
     /// - `editor`: can write but cannot manage members.
     /// - `viewer`: read-only.
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
     #[serde(rename_all = "snake_case")]
     pub enum MembershipRole {
         Owner,
@@ -299,20 +631,20 @@ pub mod membership {
     }
 
     /// Request body for adding/updating a member.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct MemberUpsert {
         pub username: String,
         pub role: MembershipRole,
     }
 
     /// Response body for listing members.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct MembersResponse {
         pub members: Vec<MemberView>,
     }
 
     /// A member with their role.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct MemberView {
         pub username: String,
         pub role: MembershipRole,
@@ -322,7 +654,7 @@ pub mod membership {
 pub mod stats {
     use super::*;
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct Statistic {
         pub currency: Currency,
         pub balance_minor: i64,
@@ -334,7 +666,7 @@ pub mod stats {
 pub mod transaction {
     use super::*;
 
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
     #[serde(rename_all = "snake_case")]
     pub enum TransactionKind {
         Income,
@@ -344,7 +676,19 @@ pub mod transaction {
         Refund,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    /// Whether a transaction has been matched against a bank statement.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ClearingStatus {
+        #[default]
+        Uncleared,
+        /// Seen on a statement, not reconciled yet.
+        Cleared,
+        /// Part of a reconciled statement: locked until unlocked.
+        Reconciled,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct TransactionList {
         pub vault_id: String,
         pub flow_id: Option<Uuid>,
@@ -364,7 +708,26 @@ pub mod transaction {
         pub include_transfers: Option<bool>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    /// Query string of `GET /v2/vaults/{vault_id}/transactions`; the fields
+    /// of [`TransactionList`] without the vault.
+    #[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+    pub struct TransactionListQuery {
+        pub flow_id: Option<Uuid>,
+        pub wallet_id: Option<Uuid>,
+        pub limit: Option<u64>,
+        /// `next_cursor` of the previous page.
+        pub cursor: Option<String>,
+        /// Inclusive start of the time range filter (RFC3339, with offset).
+        pub from: Option<DateTime<FixedOffset>>,
+        /// Exclusive end of the time range filter (RFC3339, with offset).
+        pub to: Option<DateTime<FixedOffset>>,
+        /// Optional allow-list of kinds to include, e.g. `income,expense`.
+        pub kinds: Option<CommaSeparated<TransactionKind>>,
+        pub include_voided: Option<bool>,
+        pub include_transfers: Option<bool>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct TransactionView {
         pub id: Uuid,
         pub kind: TransactionKind,
@@ -372,42 +735,81 @@ pub mod transaction {
         pub occurred_at: DateTime<FixedOffset>,
         /// Signed amount for the selected target (wallet/flow).
         pub amount_minor: i64,
+        /// Currency of `amount_minor` (the wallet currency when listing a wallet).
+        pub currency: Currency,
         pub category_id: Uuid,
         pub category: Option<String>,
         pub note: Option<String>,
         pub voided: bool,
+        #[serde(default)]
+        pub clearing_status: ClearingStatus,
+        /// Lines of a split expense (empty otherwise).
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub splits: Vec<TransactionSplitView>,
+        /// Expense paid back by this refund.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub refunded_transaction_id: Option<Uuid>,
+        /// How much of this expense was refunded (expenses only).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub refund_status: Option<RefundStatusView>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    /// "Refunded X of Y" for an expense, in vault currency.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct RefundStatusView {
+        /// Sum of the non-voided refunds linked to the expense.
+        pub refunded_minor: i64,
+        /// Expense amount.
+        pub total_minor: i64,
+        pub currency: Currency,
+    }
+
+    /// One line of a split expense, in vault currency.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct TransactionSplitView {
+        pub flow_id: Uuid,
+        /// Positive amount charged to the flow.
+        pub amount_minor: i64,
+        pub category_id: Uuid,
+        pub note: Option<String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct TransactionListResponse {
         pub transactions: Vec<TransactionView>,
         /// Opaque cursor for fetching the next page (older items).
         pub next_cursor: Option<String>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct TransactionGet {
         pub vault_id: String,
         pub id: Uuid,
     }
 
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
     #[serde(tag = "target", rename_all = "snake_case")]
     pub enum LegTarget {
         Wallet { wallet_id: Uuid },
         Flow { flow_id: Uuid },
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct TransactionLegView {
         #[serde(flatten)]
         pub target: LegTarget,
         pub amount_minor: i64,
         pub attributed_user_id: Option<String>,
         pub currency: Currency,
+        /// Category of a split expense line.
+        #[serde(default)]
+        pub category_id: Option<Uuid>,
+        /// Note of a split expense line.
+        #[serde(default)]
+        pub note: Option<String>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct TransactionHeaderView {
         pub id: Uuid,
         pub kind: TransactionKind,
@@ -416,29 +818,53 @@ pub mod transaction {
         /// Positive absolute amount of the transaction.
         pub amount_minor: i64,
         pub currency: Currency,
+        /// Amount as entered, when a currency conversion was applied.
+        pub original_amount_minor: Option<i64>,
+        pub original_currency: Option<Currency>,
+        /// Decimal rate from `original_currency` to the other currency.
+        pub exchange_rate: Option<String>,
         pub category_id: Uuid,
         pub category: Option<String>,
         pub note: Option<String>,
         pub voided: bool,
+        #[serde(default)]
+        pub clearing_status: ClearingStatus,
+        /// Expense paid back by this refund.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub refunded_transaction_id: Option<Uuid>,
+        /// How much of this expense was refunded (expenses only).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub refund_status: Option<RefundStatusView>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct TransactionDetailResponse {
         pub transaction: TransactionHeaderView,
         pub legs: Vec<TransactionLegView>,
+        /// Lines of a split expense (empty otherwise).
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub splits: Vec<TransactionSplitView>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct TransactionCreated {
         pub id: Uuid,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct IncomeNew {
         pub vault_id: String,
         pub amount_minor: i64,
         pub flow_id: Option<Uuid>,
         pub wallet_id: Option<Uuid>,
+        /// Currency `amount_minor` is expressed in (defaults to the wallet
+        /// currency).
+        #[serde(default)]
+        pub currency: Option<Currency>,
+        /// Decimal exchange rate from `currency` to the other currency
+        /// involved (wallet or vault), e.g. `"0.92"`.
+        #[serde(default)]
+        pub exchange_rate: Option<String>,
         /// Optional: use a canonical category id instead of a name.
         pub category_id: Option<Uuid>,
         pub category: Option<String>,
@@ -450,12 +876,20 @@ pub mod transaction {
         pub occurred_at: DateTime<FixedOffset>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct ExpenseNew {
         pub vault_id: String,
         pub amount_minor: i64,
         pub flow_id: Option<Uuid>,
         pub wallet_id: Option<Uuid>,
+        /// Currency `amount_minor` is expressed in (defaults to the wallet
+        /// currency).
+        #[serde(default)]
+        pub currency: Option<Currency>,
+        /// Decimal exchange rate from `currency` to the other currency
+        /// involved (wallet or vault), e.g. `"0.92"`.
+        #[serde(default)]
+        pub exchange_rate: Option<String>,
         /// Optional: use a canonical category id instead of a name.
         pub category_id: Option<Uuid>,
         pub category: Option<String>,
@@ -467,13 +901,61 @@ pub mod transaction {
         pub occurred_at: DateTime<FixedOffset>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct SplitLineNew {
+        /// Must be > 0, in the currency of the expense.
+        pub amount_minor: i64,
+        pub flow_id: Option<Uuid>,
+        /// Optional: use a canonical category id instead of a name.
+        pub category_id: Option<Uuid>,
+        pub category: Option<String>,
+        pub note: Option<String>,
+    }
+
+    /// Expense paid from one wallet and split across categories and flows.
+    ///
+    /// The lines must sum to `amount_minor`.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct SplitExpenseNew {
+        pub vault_id: String,
+        pub amount_minor: i64,
+        pub wallet_id: Option<Uuid>,
+        /// Currency `amount_minor` is expressed in (defaults to the wallet
+        /// currency).
+        #[serde(default)]
+        pub currency: Option<Currency>,
+        /// Decimal exchange rate from `currency` to the other currency
+        /// involved (wallet or vault), e.g. `"0.92"`.
+        #[serde(default)]
+        pub exchange_rate: Option<String>,
+        pub splits: Vec<SplitLineNew>,
+        pub note: Option<String>,
+        /// Optional idempotency key for safely retrying the same create
+        /// request.
+        pub idempotency_key: Option<String>,
+        /// RFC3339 timestamp, including timezone offset (local user time).
+        pub occurred_at: DateTime<FixedOffset>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct Refund {
         pub vault_id: String,
         /// Must be > 0. The kind defines the sign of the legs.
         pub amount_minor: i64,
+        /// Expense being refunded. Its wallet, flow and category are used
+        /// when not given, and the refunds of an expense cannot exceed it.
+        #[serde(default)]
+        pub refunded_transaction_id: Option<Uuid>,
         pub flow_id: Option<Uuid>,
         pub wallet_id: Option<Uuid>,
+        /// Currency `amount_minor` is expressed in (defaults to the wallet
+        /// currency).
+        #[serde(default)]
+        pub currency: Option<Currency>,
+        /// Decimal exchange rate from `currency` to the other currency
+        /// involved (wallet or vault), e.g. `"0.92"`.
+        #[serde(default)]
+        pub exchange_rate: Option<String>,
         /// Optional: use a canonical category id instead of a name.
         pub category_id: Option<Uuid>,
         pub category: Option<String>,
@@ -485,12 +967,16 @@ pub mod transaction {
         pub occurred_at: DateTime<FixedOffset>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct TransferWalletNew {
         pub vault_id: String,
         pub amount_minor: i64,
         pub from_wallet_id: Uuid,
         pub to_wallet_id: Uuid,
+        /// Decimal exchange rate from the source to the destination wallet
+        /// currency (required when they differ).
+        #[serde(default)]
+        pub exchange_rate: Option<String>,
         pub note: Option<String>,
         /// Optional idempotency key for safely retrying the same create
         /// request.
@@ -499,7 +985,7 @@ pub mod transaction {
         pub occurred_at: DateTime<FixedOffset>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct TransferFlowNew {
         pub vault_id: String,
         pub amount_minor: i64,
@@ -513,11 +999,16 @@ pub mod transaction {
         pub occurred_at: DateTime<FixedOffset>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct TransactionUpdate {
         pub vault_id: String,
         /// If present, updates the transaction amount (must be `> 0`).
+        ///
+        /// Expressed in the original currency for converted transactions.
         pub amount_minor: Option<i64>,
+        /// If present, replaces the exchange rate of a converted transaction.
+        #[serde(default)]
+        pub exchange_rate: Option<String>,
         /// If present, updates the wallet target (Income/Expense/Refund).
         pub wallet_id: Option<Uuid>,
         /// If present, updates the flow target (Income/Expense/Refund).
@@ -537,10 +1028,789 @@ pub mod transaction {
         pub occurred_at: Option<DateTime<FixedOffset>>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
     pub struct TransactionVoid {
         pub vault_id: String,
         /// Optional: if absent, server uses now().
         pub voided_at: Option<DateTime<FixedOffset>>,
     }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct TransactionRestore {
+        pub vault_id: String,
+    }
+
+    /// Mark a transaction as cleared, or back as uncleared.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct TransactionClear {
+        pub vault_id: String,
+        pub cleared: bool,
+    }
+
+    /// Unlock a reconciled transaction so it can be changed again.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct TransactionUnlock {
+        pub vault_id: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct TransactionRevisionList {
+        pub vault_id: String,
+    }
+
+    /// A previous state of a transaction, stored by an update.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct TransactionRevisionView {
+        /// 1-based revision number; `1` is the state before the first update.
+        pub revision: u32,
+        pub occurred_at: DateTime<FixedOffset>,
+        pub amount_minor: i64,
+        pub currency: Currency,
+        pub original_amount_minor: Option<i64>,
+        pub original_currency: Option<Currency>,
+        pub exchange_rate: Option<String>,
+        pub category_id: Uuid,
+        pub category: Option<String>,
+        pub note: Option<String>,
+        pub legs: Vec<TransactionLegView>,
+        /// User whose update replaced this state.
+        pub replaced_by: String,
+        pub replaced_at: DateTime<FixedOffset>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct TransactionRevisionListResponse {
+        /// Oldest first.
+        pub revisions: Vec<TransactionRevisionView>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct TransactionRevert {
+        pub vault_id: String,
+        /// Revision to restore (see `TransactionRevisionView::revision`).
+        pub revision: u32,
+    }
+}
+
+pub mod recurring {
+    use super::*;
+    use crate::transaction::TransactionKind;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum RecurrenceFrequency {
+        Weekly,
+        Monthly,
+        Yearly,
+    }
+
+    /// List the recurring rules of a vault.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct RecurringRuleList {
+        pub vault_id: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct RecurringRuleView {
+        pub id: Uuid,
+        pub kind: TransactionKind,
+        pub amount_minor: i64,
+        pub wallet_id: Option<Uuid>,
+        pub flow_id: Option<Uuid>,
+        pub from_flow_id: Option<Uuid>,
+        pub to_flow_id: Option<Uuid>,
+        pub category: Option<String>,
+        pub note: Option<String>,
+        pub frequency: RecurrenceFrequency,
+        pub interval: u32,
+        pub day_of_month: Option<u32>,
+        pub starts_at: DateTime<FixedOffset>,
+        pub ends_at: Option<DateTime<FixedOffset>>,
+        pub max_occurrences: Option<u32>,
+        /// Number of occurrences already posted.
+        pub occurrences: u32,
+        /// `None` once the schedule is exhausted.
+        pub next_occurrence_at: Option<DateTime<FixedOffset>>,
+        pub active: bool,
+        /// Why the scheduler paused the rule; cleared when it is resumed.
+        pub last_error: Option<String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct RecurringRuleListResponse {
+        pub rules: Vec<RecurringRuleView>,
+    }
+
+    /// Create a recurring rule (`income`, `expense` or `transfer_flow`).
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct RecurringRuleNew {
+        pub vault_id: String,
+        pub kind: TransactionKind,
+        /// Amount in minor units of the vault currency.
+        pub amount_minor: i64,
+        /// Income/Expense wallet (defaults to the only active wallet); it must
+        /// be in the vault currency.
+        pub wallet_id: Option<Uuid>,
+        /// Income/Expense flow (defaults to Unallocated).
+        pub flow_id: Option<Uuid>,
+        pub from_flow_id: Option<Uuid>,
+        pub to_flow_id: Option<Uuid>,
+        pub category: Option<String>,
+        pub note: Option<String>,
+        pub frequency: RecurrenceFrequency,
+        /// Repeat every N periods (default: 1).
+        pub interval: Option<u32>,
+        /// Monthly rules only: day of month (clamped to short months).
+        pub day_of_month: Option<u32>,
+        /// First occurrence; later ones keep its time of day.
+        pub starts_at: DateTime<FixedOffset>,
+        /// Inclusive end of the schedule.
+        pub ends_at: Option<DateTime<FixedOffset>>,
+        pub max_occurrences: Option<u32>,
+    }
+
+    /// Patch a recurring rule. The cadence is immutable.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct RecurringRuleUpdate {
+        pub vault_id: String,
+        pub amount_minor: Option<i64>,
+        pub wallet_id: Option<Uuid>,
+        pub flow_id: Option<Uuid>,
+        pub from_flow_id: Option<Uuid>,
+        pub to_flow_id: Option<Uuid>,
+        pub category: Option<String>,
+        pub note: Option<String>,
+        pub ends_at: Option<DateTime<FixedOffset>>,
+        /// Remove the end date; `ends_at` must be unset.
+        #[serde(default)]
+        pub clear_ends_at: bool,
+        /// Pause (`false`) or resume (`true`); resuming skips missed
+        /// occurrences.
+        pub active: Option<bool>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct RecurringRuleDelete {
+        pub vault_id: String,
+    }
+}
+
+pub mod budget {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum BudgetPeriod {
+        Weekly,
+        Monthly,
+        Yearly,
+    }
+
+    /// List the budgets of a vault with their status on a given day.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct BudgetList {
+        pub vault_id: String,
+        /// Day whose period is reported (default: today, UTC).
+        pub on: Option<NaiveDate>,
+    }
+
+    /// Query string of `GET /v2/vaults/{vault_id}/budgets`.
+    #[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+    pub struct BudgetListQuery {
+        /// Day whose period is reported (default: today, UTC).
+        pub on: Option<NaiveDate>,
+    }
+
+    /// Budget with spent vs. limit for the period containing the requested
+    /// day. Amounts are in vault currency minor units.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct BudgetView {
+        pub id: Uuid,
+        pub category_id: Uuid,
+        pub amount_minor: i64,
+        pub period: BudgetPeriod,
+        pub starts_on: NaiveDate,
+        pub rollover: bool,
+        pub period_start: NaiveDate,
+        /// Exclusive end of the period.
+        pub period_end: NaiveDate,
+        pub spent_minor: i64,
+        /// Leftover (or overspending, negative) of the previous periods.
+        pub carried_minor: i64,
+        pub available_minor: i64,
+        /// Negative when over budget.
+        pub remaining_minor: i64,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct BudgetListResponse {
+        pub budgets: Vec<BudgetView>,
+    }
+
+    /// Create a budget for a category (one per category).
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct BudgetNew {
+        pub vault_id: String,
+        pub category_id: Uuid,
+        pub amount_minor: i64,
+        pub period: BudgetPeriod,
+        /// First day of the first period; later periods keep its weekday or
+        /// day of month.
+        pub starts_on: NaiveDate,
+        #[serde(default)]
+        pub rollover: bool,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct BudgetUpdate {
+        pub vault_id: String,
+        pub amount_minor: Option<i64>,
+        pub period: Option<BudgetPeriod>,
+        pub starts_on: Option<NaiveDate>,
+        pub rollover: Option<bool>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct BudgetDelete {
+        pub vault_id: String,
+    }
+}
+
+pub mod category_rule {
+    use super::*;
+    use crate::transaction::TransactionKind;
+
+    /// Conditions a transaction must meet; unset conditions always hold.
+    #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    pub struct CategoryRuleConditions {
+        /// Case-insensitive substring of the note.
+        #[serde(default)]
+        pub note_contains: Option<String>,
+        /// Regular expression searched in the note.
+        #[serde(default)]
+        pub note_regex: Option<String>,
+        /// Inclusive bounds, in vault currency minor units.
+        #[serde(default)]
+        pub min_amount_minor: Option<i64>,
+        #[serde(default)]
+        pub max_amount_minor: Option<i64>,
+        #[serde(default)]
+        pub wallet_id: Option<Uuid>,
+        #[serde(default)]
+        pub flow_id: Option<Uuid>,
+        /// `income`, `expense` or `refund`.
+        #[serde(default)]
+        pub kind: Option<TransactionKind>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct CategoryRuleList {
+        pub vault_id: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct CategoryRuleView {
+        pub id: Uuid,
+        pub category_id: Uuid,
+        /// Lower values are tried first.
+        pub priority: i32,
+        #[serde(flatten)]
+        pub conditions: CategoryRuleConditions,
+        pub created_at: DateTime<FixedOffset>,
+    }
+
+    /// Rules in evaluation order.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct CategoryRuleListResponse {
+        pub rules: Vec<CategoryRuleView>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct CategoryRuleNew {
+        pub vault_id: String,
+        pub category_id: Uuid,
+        #[serde(default)]
+        pub priority: i32,
+        #[serde(flatten)]
+        pub conditions: CategoryRuleConditions,
+    }
+
+    /// Update a rule; `conditions`, when given, replaces all of them.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct CategoryRuleUpdate {
+        pub vault_id: String,
+        pub category_id: Option<Uuid>,
+        pub priority: Option<i32>,
+        pub conditions: Option<CategoryRuleConditions>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct CategoryRuleDelete {
+        pub vault_id: String,
+    }
+
+    /// Match the rules against booked transactions, as a dry run or for
+    /// real. Only uncategorized transactions are considered unless
+    /// `include_categorized` is set.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct CategoryRuleApply {
+        pub vault_id: String,
+        pub from: Option<DateTime<FixedOffset>>,
+        /// Exclusive upper bound.
+        pub to: Option<DateTime<FixedOffset>>,
+        #[serde(default)]
+        pub include_categorized: bool,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct CategoryRuleHitView {
+        pub transaction_id: Uuid,
+        pub occurred_at: DateTime<FixedOffset>,
+        pub kind: TransactionKind,
+        pub amount_minor: i64,
+        pub note: Option<String>,
+        pub previous_category_id: Uuid,
+        pub rule_id: Uuid,
+        pub category_id: Uuid,
+        /// Set when the transaction is left unchanged.
+        pub skipped: Option<CategoryRuleSkip>,
+    }
+
+    /// Why a rule hit is left unchanged.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum CategoryRuleSkip {
+        /// The transaction is reconciled.
+        Reconciled,
+        /// The transaction occurred in a closed period.
+        BooksClosed,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct CategoryRuleApplyResponse {
+        /// Whether the categories were written.
+        pub applied: bool,
+        pub hits: Vec<CategoryRuleHitView>,
+    }
+}
+
+pub mod import {
+    use super::*;
+    use crate::transaction::TransactionKind;
+
+    /// A CSV column, by 0-based position or by header name.
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(untagged)]
+    pub enum CsvColumn {
+        Index(usize),
+        Name(String),
+    }
+
+    /// Where the amount of a row is read from.
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(tag = "mode", rename_all = "snake_case")]
+    pub enum CsvAmount {
+        /// One signed column: positive values are incomes, negative expenses.
+        Signed { column: CsvColumn },
+        /// Separate debit (expense) and credit (income) columns.
+        DebitCredit { debit: CsvColumn, credit: CsvColumn },
+    }
+
+    /// Mapping profile describing the layout of a bank CSV export.
+    #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct CsvMapping {
+        /// Field delimiter (default: `,`).
+        pub delimiter: Option<char>,
+        /// Whether the first record is a header row (default: true).
+        pub has_headers: Option<bool>,
+        pub date_column: CsvColumn,
+        /// `chrono` format string, e.g. `%d/%m/%Y`.
+        pub date_format: String,
+        pub amount: CsvAmount,
+        /// `.` (default) or `,`.
+        pub decimal_separator: Option<char>,
+        pub note_column: Option<CsvColumn>,
+        pub category_column: Option<CsvColumn>,
+    }
+
+    /// Preview or commit a CSV statement import into one wallet.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct CsvImport {
+        pub vault_id: String,
+        /// Defaults to the only active wallet.
+        pub wallet_id: Option<Uuid>,
+        /// Defaults to Unallocated.
+        pub flow_id: Option<Uuid>,
+        pub mapping: CsvMapping,
+        /// Raw CSV content.
+        pub data: String,
+        /// Also book rows that look already booked (default: false).
+        #[serde(default)]
+        pub include_duplicates: bool,
+    }
+
+    /// Structured statement formats.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum StatementFormat {
+        Ofx,
+        Qif,
+        Camt053,
+    }
+
+    /// Preview or commit an OFX, QIF or CAMT.053 statement import into one
+    /// wallet.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct StatementImport {
+        pub vault_id: String,
+        /// Defaults to the only active wallet.
+        pub wallet_id: Option<Uuid>,
+        /// Defaults to Unallocated.
+        pub flow_id: Option<Uuid>,
+        pub format: StatementFormat,
+        /// Raw statement content.
+        pub data: String,
+        /// Also book rows that look already booked but carry a new bank
+        /// reference (default: false). Rows already imported are always
+        /// skipped.
+        #[serde(default)]
+        pub include_duplicates: bool,
+    }
+
+    /// A parsed statement row.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct ImportRowView {
+        /// 1-based line in the source file.
+        pub line: u64,
+        /// `income` or `expense`.
+        pub kind: TransactionKind,
+        /// Positive amount in wallet currency minor units.
+        pub amount_minor: i64,
+        pub occurred_at: DateTime<FixedOffset>,
+        pub category: Option<String>,
+        pub note: Option<String>,
+        /// Existing transaction this row appears to duplicate.
+        pub duplicate_of: Option<Uuid>,
+        /// Whether `duplicate_of` was booked from the same bank reference
+        /// (such rows are never booked again).
+        #[serde(default)]
+        pub already_imported: bool,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct ImportRowError {
+        pub line: u64,
+        pub message: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct ImportPreviewResponse {
+        pub rows: Vec<ImportRowView>,
+        pub errors: Vec<ImportRowError>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct ImportCommitResponse {
+        pub created: Vec<Uuid>,
+        pub skipped_duplicates: usize,
+    }
+}
+
+pub mod report {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ReportGroupBy {
+        Category,
+        Flow,
+        Wallet,
+        Day,
+        Week,
+        Month,
+        Year,
+    }
+
+    /// Aggregate the non-voided incomes, expenses and refunds of a vault.
+    /// Transfers are never included.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct ReportQuery {
+        pub vault_id: String,
+        /// Dimensions of the rows, at most one period (none: a single total
+        /// row).
+        #[serde(default)]
+        pub group_by: Vec<ReportGroupBy>,
+        /// Inclusive start of the time range filter (RFC3339, with offset).
+        pub from: Option<DateTime<FixedOffset>>,
+        /// Exclusive end of the time range filter (RFC3339, with offset).
+        pub to: Option<DateTime<FixedOffset>>,
+        /// Optional allow-list of `income`, `expense` and `refund`.
+        pub kinds: Option<Vec<transaction::TransactionKind>>,
+        /// Offset from UTC, in minutes, the periods are computed in (default:
+        /// 0). Weeks start on Monday.
+        #[serde(default)]
+        pub utc_offset_minutes: Option<i32>,
+    }
+
+    /// Query string of `GET /v2/vaults/{vault_id}/reports`; the fields of
+    /// [`ReportQuery`] without the vault.
+    #[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+    pub struct ReportQueryParams {
+        /// Dimensions of the rows, e.g. `month,category`.
+        pub group_by: Option<CommaSeparated<ReportGroupBy>>,
+        /// Inclusive start of the time range filter (RFC3339, with offset).
+        pub from: Option<DateTime<FixedOffset>>,
+        /// Exclusive end of the time range filter (RFC3339, with offset).
+        pub to: Option<DateTime<FixedOffset>>,
+        /// Optional allow-list of `income`, `expense` and `refund`.
+        pub kinds: Option<CommaSeparated<transaction::TransactionKind>>,
+        /// Offset from UTC, in minutes, the periods are computed in (default:
+        /// 0).
+        pub utc_offset_minutes: Option<i32>,
+    }
+
+    /// Totals of one group, in vault currency minor units. Dimensions the
+    /// report is not grouped by are `null`.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct ReportRowView {
+        /// First day of the period.
+        pub period_start: Option<NaiveDate>,
+        pub category_id: Option<Uuid>,
+        pub category: Option<String>,
+        pub flow_id: Option<Uuid>,
+        pub flow: Option<String>,
+        pub wallet_id: Option<Uuid>,
+        pub wallet: Option<String>,
+        pub income_minor: i64,
+        /// Expenses as a positive amount.
+        pub expense_minor: i64,
+        pub refund_minor: i64,
+        /// `income_minor - expense_minor + refund_minor`.
+        pub net_minor: i64,
+        pub transactions: u64,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct ReportResponse {
+        pub currency: Currency,
+        pub group_by: Vec<ReportGroupBy>,
+        /// Sorted by period, then by expense (largest first), then by income.
+        pub rows: Vec<ReportRowView>,
+    }
+}
+
+pub mod balance {
+    use super::*;
+    use chrono::NaiveDate;
+
+    /// Spacing of the points of a balance series.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum BalanceInterval {
+        Day,
+        Week,
+        Month,
+    }
+
+    /// Balance of a wallet, a flow or, with neither, the whole vault (the
+    /// sum of its flows, in vault currency) as of `at`.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct BalanceQuery {
+        pub vault_id: String,
+        pub wallet_id: Option<Uuid>,
+        pub flow_id: Option<Uuid>,
+        /// RFC3339 timestamp, including timezone offset; only transactions
+        /// that occurred before it count.
+        pub at: DateTime<FixedOffset>,
+    }
+
+    /// Query string of `GET /v2/vaults/{vault_id}/balances`; the fields of
+    /// [`BalanceQuery`] without the vault.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct BalanceQueryParams {
+        pub wallet_id: Option<Uuid>,
+        pub flow_id: Option<Uuid>,
+        /// RFC3339 timestamp, including timezone offset.
+        pub at: DateTime<FixedOffset>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct BalanceResponse {
+        /// Wallet currency for a wallet, vault currency otherwise.
+        pub currency: Currency,
+        pub balance_minor: i64,
+    }
+
+    /// Closing balances of a wallet, a flow or, with neither, the whole
+    /// vault for each period of a range.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct BalanceHistoryQuery {
+        pub vault_id: String,
+        pub wallet_id: Option<Uuid>,
+        pub flow_id: Option<Uuid>,
+        /// Inclusive start of the range (RFC3339, with offset).
+        pub from: DateTime<FixedOffset>,
+        /// Exclusive end of the range (RFC3339, with offset).
+        pub to: DateTime<FixedOffset>,
+        pub interval: BalanceInterval,
+        /// Offset from UTC, in minutes, the periods are computed in (default:
+        /// 0). Weeks start on Monday.
+        #[serde(default)]
+        pub utc_offset_minutes: Option<i32>,
+    }
+
+    /// Query string of `GET /v2/vaults/{vault_id}/balances/history`; the
+    /// fields of [`BalanceHistoryQuery`] without the vault.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct BalanceHistoryQueryParams {
+        pub wallet_id: Option<Uuid>,
+        pub flow_id: Option<Uuid>,
+        /// Inclusive start of the range (RFC3339, with offset).
+        pub from: DateTime<FixedOffset>,
+        /// Exclusive end of the range (RFC3339, with offset).
+        pub to: DateTime<FixedOffset>,
+        pub interval: BalanceInterval,
+        /// Offset from UTC, in minutes, the periods are computed in (default:
+        /// 0).
+        pub utc_offset_minutes: Option<i32>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct BalancePointView {
+        /// First day of the period.
+        pub period_start: NaiveDate,
+        /// Balance at the end of the period (at `to` for the last one).
+        pub balance_minor: i64,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct BalanceHistoryResponse {
+        pub currency: Currency,
+        pub interval: BalanceInterval,
+        /// Balance at `from`.
+        pub opening_minor: i64,
+        /// One point per period overlapping the range, oldest first.
+        pub points: Vec<BalancePointView>,
+    }
+}
+
+pub mod access_token {
+    use super::*;
+
+    /// What a personal access token may do.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum AccessTokenScope {
+        /// Read endpoints only.
+        Read,
+        /// Read and write endpoints.
+        Write,
+    }
+
+    /// Create a personal access token for the authenticated user.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct AccessTokenNew {
+        pub name: String,
+        pub scope: AccessTokenScope,
+        /// Limit the token to one vault.
+        pub vault_id: Option<String>,
+        pub expires_at: Option<DateTime<FixedOffset>>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct AccessTokenView {
+        pub id: Uuid,
+        pub name: String,
+        pub scope: AccessTokenScope,
+        pub vault_id: Option<String>,
+        pub created_at: DateTime<FixedOffset>,
+        pub expires_at: Option<DateTime<FixedOffset>>,
+    }
+
+    /// A new token and its secret. The secret is never shown again: send it
+    /// as `Authorization: Bearer <secret>`.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct AccessTokenCreated {
+        pub token: AccessTokenView,
+        pub secret: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct AccessTokenListResponse {
+        pub tokens: Vec<AccessTokenView>,
+    }
+}
+
+pub mod audit {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum AuditAction {
+        Create,
+        Update,
+        Delete,
+        Void,
+        Restore,
+        /// `before` is the merged category, `after` the category it was
+        /// merged into.
+        Merge,
+        Import,
+        /// A transaction was reconciled against a wallet statement.
+        Reconcile,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum AuditEntity {
+        Vault,
+        Wallet,
+        Flow,
+        Transaction,
+        Category,
+        CategoryAlias,
+        CategoryRule,
+        Budget,
+        RecurringRule,
+        VaultMember,
+        FlowMember,
+        /// A personal access token that can act on the vault.
+        AccessToken,
+    }
+
+    /// Query string of `GET /vault/{vault_id}/audit` (owner-only).
+    #[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+    pub struct AuditQuery {
+        /// Only changes made by this username.
+        pub actor: Option<String>,
+        pub entity: Option<AuditEntity>,
+        pub entity_id: Option<String>,
+        /// Page size (default: 50).
+        pub limit: Option<u64>,
+        /// `next_cursor` of the previous page.
+        pub cursor: Option<String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct AuditEntryView {
+        pub id: Uuid,
+        pub actor: String,
+        pub action: AuditAction,
+        pub entity: AuditEntity,
+        pub entity_id: String,
+        /// Row snapshot before the change (absent on create).
+        pub before: Option<serde_json::Value>,
+        /// Row snapshot after the change (absent on delete).
+        pub after: Option<serde_json::Value>,
+        pub created_at: DateTime<FixedOffset>,
+    }
+
+    /// Audit entries, newest first.
+    #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    pub struct AuditListResponse {
+        pub entries: Vec<AuditEntryView>,
+        pub next_cursor: Option<String>,
+    }
 }
@@ -1,5 +1,6 @@
 use migration::{Migrator, MigratorTrait};
 use settings::Database;
+use std::time::Duration;
 
 mod settings;
 
@@ -33,6 +34,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                     return;
                 }
             };
+            let interval = server.recurring_interval_secs.unwrap_or(60).max(1);
+            tokio::spawn(run_recurring_scheduler(
+                engine.clone(),
+                Duration::from_secs(interval),
+            ));
+
             let bind = server.bind.unwrap_or_else(|| "127.0.0.1".to_string());
             let addr = format!("{}:{}", bind, server.port);
             let listener = match tokio::net::TcpListener::bind(addr).await {
@@ -53,7 +60,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
             tracing::info!("Found telegram settings...");
             match telegram_bot::Bot::builder()
                 .token(&telegram.token)
-                .server(&telegram.server, &telegram.username, &telegram.password)
+                .server(&telegram.server)
                 .build()
             {
                 Ok(bot) => bot.run().await,
@@ -69,12 +76,40 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     Ok(())
 }
 
+/// Posts due recurring rules. The first tick fires immediately, so
+/// occurrences missed while the app was down are caught up at startup.
+async fn run_recurring_scheduler(engine: engine::Engine, period: Duration) {
+    let mut ticker = tokio::time::interval(period);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        match engine.run_recurring_rules(chrono::Utc::now()).await {
+            Ok(report) => {
+                if report.posted > 0 {
+                    tracing::info!("posted {} recurring transactions", report.posted);
+                }
+                for failure in report.failures {
+                    tracing::warn!(
+                        "recurring rule {} (vault {}) failed at {}: {}",
+                        failure.rule_id,
+                        failure.vault_id,
+                        failure.occurrence_at,
+                        failure.error
+                    );
+                }
+            }
+            Err(err) => tracing::error!("recurring rules scheduler failed: {err}"),
+        }
+    }
+}
+
 async fn parse_database(
     config: &settings::Database,
 ) -> Result<sea_orm::DatabaseConnection, Box<dyn std::error::Error + Send + Sync>> {
     let url = match config {
         Database::Memory => String::from("sqlite::memory"),
         Database::Sqlite(path) => format!("sqlite:{}?mode=rwc", path),
+        Database::Postgres(url) => url.clone(),
     };
 
     let database = sea_orm::Database::connect(url).await?;
@@ -0,0 +1,193 @@
+//! Personal access tokens.
+//!
+//! A token authenticates API requests on behalf of its owner without the
+//! owner's password. Only a SHA-256 digest of the secret is stored; the
+//! secret itself is returned once, when the token is created. Each token has
+//! a [`TokenScope`] and may be limited to a single vault.
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveValue, entity::prelude::*};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{EngineError, ResultEngine};
+
+/// What a token may do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
+pub enum TokenScope {
+    /// Read endpoints only.
+    #[sea_orm(string_value = "read")]
+    Read,
+    /// Read and write endpoints.
+    #[sea_orm(string_value = "write")]
+    Write,
+}
+
+/// A personal access token, without its secret.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct AccessToken {
+    pub id: Uuid,
+    /// Owner username.
+    pub user_id: String,
+    pub name: String,
+    pub scope: TokenScope,
+    /// When set, the token only works on this vault.
+    pub vault_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl AccessToken {
+    /// Returns `true` once `expires_at` has passed.
+    #[must_use]
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// Returns `true` if the token may serve a request that writes (when
+    /// `write`) on `vault_id` (`None` for requests not tied to a vault).
+    ///
+    /// Vault ids are compared as UUIDs, so their spelling (case, hyphens)
+    /// does not matter; an id that is not a UUID never matches.
+    #[must_use]
+    pub fn allows(&self, write: bool, vault_id: Option<&str>) -> bool {
+        if write && self.scope == TokenScope::Read {
+            return false;
+        }
+        match &self.vault_id {
+            Some(limit) => {
+                let requested = vault_id.and_then(|id| Uuid::parse_str(id).ok());
+                requested.is_some() && requested == Uuid::parse_str(limit).ok()
+            }
+            None => true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "access_tokens")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub username: String,
+    pub name: String,
+    #[sea_orm(unique)]
+    pub token_hash: String,
+    pub scope: TokenScope,
+    pub vault_id: Option<Uuid>,
+    pub created_at: DateTimeUtc,
+    pub expires_at: Option<DateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::Username",
+        to = "super::users::Column::Username",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl AccessToken {
+    pub(crate) fn to_active_model(&self, token_hash: String) -> ResultEngine<ActiveModel> {
+        let vault_id = self
+            .vault_id
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| EngineError::KeyNotFound("vault not exists".to_string()))?;
+        Ok(ActiveModel {
+            id: ActiveValue::Set(self.id),
+            username: ActiveValue::Set(self.user_id.clone()),
+            name: ActiveValue::Set(self.name.clone()),
+            token_hash: ActiveValue::Set(token_hash),
+            scope: ActiveValue::Set(self.scope),
+            vault_id: ActiveValue::Set(vault_id),
+            created_at: ActiveValue::Set(self.created_at),
+            expires_at: ActiveValue::Set(self.expires_at),
+        })
+    }
+}
+
+impl From<Model> for AccessToken {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            user_id: model.username,
+            name: model.name,
+            scope: model.scope,
+            vault_id: model.vault_id.map(|id| id.to_string()),
+            created_at: model.created_at,
+            expires_at: model.expires_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use chrono::Duration;
+
+    use super::*;
+
+    fn token(scope: TokenScope, vault_id: Option<&str>) -> AccessToken {
+        AccessToken {
+            id: Uuid::new_v4(),
+            user_id: "alice".to_string(),
+            name: "ci".to_string(),
+            scope,
+            vault_id: vault_id.map(ToString::to_string),
+            created_at: Utc::now(),
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn scopes_limit_writes_and_vaults() {
+        let v1 = Uuid::new_v4().to_string();
+        let v2 = Uuid::new_v4().to_string();
+        let read = token(TokenScope::Read, None);
+        assert!(read.allows(false, Some(&v1)));
+        assert!(read.allows(false, None));
+        assert!(!read.allows(true, Some(&v1)));
+
+        let vault_write = token(TokenScope::Write, Some(&v1));
+        assert!(vault_write.allows(true, Some(&v1)));
+        assert!(!vault_write.allows(true, Some(&v2)));
+        assert!(!vault_write.allows(false, None));
+    }
+
+    #[test]
+    fn vault_ids_are_compared_as_uuids() {
+        let vault_id = Uuid::new_v4();
+        let vault_write = token(TokenScope::Write, Some(&vault_id.to_string()));
+        assert!(vault_write.allows(true, Some(&vault_id.to_string().to_uppercase())));
+        assert!(vault_write.allows(true, Some(&vault_id.simple().to_string())));
+        assert!(!vault_write.allows(true, Some("not-a-vault")));
+    }
+
+    #[test]
+    fn expires_at_is_exclusive() {
+        let now = Utc::now();
+        let mut token = token(TokenScope::Write, None);
+        assert!(!token.is_expired(now));
+        token.expires_at = Some(now + Duration::minutes(1));
+        assert!(!token.is_expired(now));
+        token.expires_at = Some(now);
+        assert!(token.is_expired(now));
+    }
+}
@@ -0,0 +1,194 @@
+//! Append-only audit log of vault mutations.
+//!
+//! Every write operation records one entry per changed entity, in the same
+//! database transaction as the change itself, with JSON snapshots of the row
+//! before and after the change. Entries are never updated or deleted, and
+//! they outlive the rows (and vaults) they describe.
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveValue, entity::prelude::*};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// What happened to the audited entity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    #[sea_orm(string_value = "create")]
+    Create,
+    #[sea_orm(string_value = "update")]
+    Update,
+    #[sea_orm(string_value = "delete")]
+    Delete,
+    /// A transaction was voided.
+    #[sea_orm(string_value = "void")]
+    Void,
+    /// A voided transaction was restored.
+    #[sea_orm(string_value = "restore")]
+    Restore,
+    /// A category was merged into another one: `before` is the merged
+    /// category, `after` the category it was merged into.
+    #[sea_orm(string_value = "merge")]
+    Merge,
+    /// A vault export was imported.
+    #[sea_orm(string_value = "import")]
+    Import,
+    /// A transaction was reconciled against a wallet statement.
+    #[sea_orm(string_value = "reconcile")]
+    Reconcile,
+}
+
+/// Kind of the audited entity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEntity {
+    #[sea_orm(string_value = "vault")]
+    Vault,
+    #[sea_orm(string_value = "wallet")]
+    Wallet,
+    #[sea_orm(string_value = "flow")]
+    Flow,
+    #[sea_orm(string_value = "transaction")]
+    Transaction,
+    #[sea_orm(string_value = "category")]
+    Category,
+    #[sea_orm(string_value = "category_alias")]
+    CategoryAlias,
+    #[sea_orm(string_value = "category_rule")]
+    CategoryRule,
+    #[sea_orm(string_value = "budget")]
+    Budget,
+    #[sea_orm(string_value = "recurring_rule")]
+    RecurringRule,
+    #[sea_orm(string_value = "vault_member")]
+    VaultMember,
+    #[sea_orm(string_value = "flow_member")]
+    FlowMember,
+    /// A personal access token that can act on the vault.
+    #[sea_orm(string_value = "access_token")]
+    AccessToken,
+}
+
+/// One audit log entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditEntry {
+    /// Time-ordered (UUID v7) entry id.
+    pub id: Uuid,
+    pub vault_id: String,
+    /// Username of the user who made the change.
+    pub actor: String,
+    pub action: AuditAction,
+    pub entity: AuditEntity,
+    pub entity_id: String,
+    /// Entity snapshot before the change (`None` on create).
+    pub before: Option<serde_json::Value>,
+    /// Entity snapshot after the change (`None` on delete).
+    pub after: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A change to record, before it is attached to a vault and an actor.
+pub(crate) struct AuditChange {
+    pub(crate) action: AuditAction,
+    pub(crate) entity: AuditEntity,
+    pub(crate) entity_id: String,
+    pub(crate) before: Option<serde_json::Value>,
+    pub(crate) after: Option<serde_json::Value>,
+}
+
+impl AuditChange {
+    pub(crate) fn created(
+        entity: AuditEntity,
+        entity_id: impl ToString,
+        after: Option<serde_json::Value>,
+    ) -> Self {
+        Self::new(AuditAction::Create, entity, entity_id, None, after)
+    }
+
+    pub(crate) fn updated(
+        entity: AuditEntity,
+        entity_id: impl ToString,
+        before: Option<serde_json::Value>,
+        after: Option<serde_json::Value>,
+    ) -> Self {
+        Self::new(AuditAction::Update, entity, entity_id, before, after)
+    }
+
+    pub(crate) fn deleted(
+        entity: AuditEntity,
+        entity_id: impl ToString,
+        before: Option<serde_json::Value>,
+    ) -> Self {
+        Self::new(AuditAction::Delete, entity, entity_id, before, None)
+    }
+
+    pub(crate) fn new(
+        action: AuditAction,
+        entity: AuditEntity,
+        entity_id: impl ToString,
+        before: Option<serde_json::Value>,
+        after: Option<serde_json::Value>,
+    ) -> Self {
+        Self {
+            action,
+            entity,
+            entity_id: entity_id.to_string(),
+            before,
+            after,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "audit_log")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub vault_id: Uuid,
+    pub actor: String,
+    pub action: AuditAction,
+    pub entity_kind: AuditEntity,
+    pub entity_id: String,
+    pub before: Option<Json>,
+    pub after: Option<Json>,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    pub(crate) fn new(vault_id: Uuid, actor: &str, change: AuditChange) -> Self {
+        Self {
+            id: ActiveValue::Set(Uuid::now_v7()),
+            vault_id: ActiveValue::Set(vault_id),
+            actor: ActiveValue::Set(actor.to_string()),
+            action: ActiveValue::Set(change.action),
+            entity_kind: ActiveValue::Set(change.entity),
+            entity_id: ActiveValue::Set(change.entity_id),
+            before: ActiveValue::Set(change.before),
+            after: ActiveValue::Set(change.after),
+            created_at: ActiveValue::Set(Utc::now()),
+        }
+    }
+}
+
+impl From<Model> for AuditEntry {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            vault_id: model.vault_id.to_string(),
+            actor: model.actor,
+            action: model.action,
+            entity: model.entity_kind,
+            entity_id: model.entity_id,
+            before: model.before,
+            after: model.after,
+            created_at: model.created_at,
+        }
+    }
+}
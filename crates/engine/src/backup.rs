@@ -0,0 +1,321 @@
+//! Portable vault backups.
+//!
+//! A [`VaultExport`] is a self-contained, versioned JSON document holding
+//! everything needed to recreate a vault on another instance: wallets, flows
+//! with their modes, categories and aliases, memberships, budgets, recurring
+//! rules, categorization rules and every transaction with its legs, void and refund link.
+//!
+//! Balances are exported for verification only: an import replays the ledger
+//! and rejects the document if the recomputed balances differ.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    BudgetPeriod, CategoryRuleConditions, ClearingStatus, Currency, EngineError, LegTarget,
+    RecurrenceFrequency, ResultEngine, TransactionKind,
+};
+
+/// Current version of the [`VaultExport`] format. Bump it whenever a field is
+/// added, so that older builds reject documents they would only partly read.
+///
+/// - 1: initial format.
+/// - 2: categorization rules, split lines, clearing status, books closed date
+///   and savings goals.
+pub const VAULT_EXPORT_VERSION: u32 = 2;
+
+/// Full export of a vault.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VaultExport {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub vault: VaultRecord,
+    pub wallets: Vec<WalletRecord>,
+    pub flows: Vec<FlowRecord>,
+    pub categories: Vec<CategoryRecord>,
+    #[serde(default)]
+    pub category_aliases: Vec<CategoryAliasRecord>,
+    /// Vault members other than the owner.
+    #[serde(default)]
+    pub members: Vec<MemberRecord>,
+    #[serde(default)]
+    pub flow_members: Vec<FlowMemberRecord>,
+    #[serde(default)]
+    pub budgets: Vec<BudgetRecord>,
+    #[serde(default)]
+    pub recurring_rules: Vec<RecurringRuleRecord>,
+    #[serde(default)]
+    pub category_rules: Vec<CategoryRuleRecord>,
+    /// Transactions in chronological order.
+    pub transactions: Vec<TransactionRecord>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultRecord {
+    pub id: Uuid,
+    pub name: String,
+    pub owner: String,
+    pub currency: Currency,
+    #[serde(default)]
+    pub books_closed_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletRecord {
+    pub id: Uuid,
+    pub name: String,
+    pub currency: Currency,
+    pub archived: bool,
+    /// Balance at export time, in wallet currency minor units.
+    pub balance_minor: i64,
+}
+
+/// Upper bound of a flow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum FlowModeRecord {
+    Unlimited,
+    NetCapped { cap_minor: i64 },
+    IncomeCapped { cap_minor: i64 },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlowRecord {
+    pub id: Uuid,
+    pub name: String,
+    /// Whether this is the system "Unallocated" flow.
+    #[serde(default)]
+    pub unallocated: bool,
+    #[serde(flatten)]
+    pub mode: FlowModeRecord,
+    pub archived: bool,
+    /// Balance at export time, in vault currency minor units.
+    pub balance_minor: i64,
+    /// Savings goal, in vault currency minor units.
+    #[serde(default)]
+    pub target_minor: Option<i64>,
+    #[serde(default)]
+    pub target_date: Option<NaiveDate>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryRecord {
+    pub id: Uuid,
+    pub name: String,
+    pub archived: bool,
+    pub is_system: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryAliasRecord {
+    pub id: Uuid,
+    pub category_id: Uuid,
+    pub alias: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemberRecord {
+    pub user_id: String,
+    pub role: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlowMemberRecord {
+    pub flow_id: Uuid,
+    pub user_id: String,
+    pub role: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BudgetRecord {
+    pub id: Uuid,
+    pub category_id: Uuid,
+    pub amount_minor: i64,
+    pub period: BudgetPeriod,
+    pub starts_on: NaiveDate,
+    pub rollover: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecurringRuleRecord {
+    pub id: Uuid,
+    pub kind: TransactionKind,
+    pub amount_minor: i64,
+    pub wallet_id: Option<Uuid>,
+    pub flow_id: Option<Uuid>,
+    pub from_flow_id: Option<Uuid>,
+    pub to_flow_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub note: Option<String>,
+    pub frequency: RecurrenceFrequency,
+    pub interval: u32,
+    pub day_of_month: Option<u32>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub max_occurrences: Option<u32>,
+    pub occurrences: u32,
+    pub next_occurrence_at: Option<DateTime<Utc>>,
+    pub active: bool,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    pub created_by: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryRuleRecord {
+    pub id: Uuid,
+    pub category_id: Uuid,
+    pub priority: i32,
+    #[serde(flatten)]
+    pub conditions: CategoryRuleConditions,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionRecord {
+    pub id: Uuid,
+    pub kind: TransactionKind,
+    pub occurred_at: DateTime<Utc>,
+    pub amount_minor: i64,
+    pub currency: Currency,
+    pub original_amount_minor: Option<i64>,
+    pub original_currency: Option<Currency>,
+    pub exchange_rate: Option<String>,
+    pub category_id: Uuid,
+    pub category: Option<String>,
+    pub note: Option<String>,
+    pub idempotency_key: Option<String>,
+    pub created_by: String,
+    pub voided_at: Option<DateTime<Utc>>,
+    pub voided_by: Option<String>,
+    #[serde(default)]
+    pub clearing_status: ClearingStatus,
+    pub refunded_transaction_id: Option<Uuid>,
+    pub legs: Vec<LegRecord>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LegRecord {
+    pub id: Uuid,
+    #[serde(flatten)]
+    pub target: LegTarget,
+    pub amount_minor: i64,
+    pub currency: Currency,
+    pub attributed_user_id: Option<String>,
+    /// Category of a split line.
+    #[serde(default)]
+    pub category_id: Option<Uuid>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Outcome of a vault import.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultImportReport {
+    pub vault_id: String,
+    /// Members (vault or flow) skipped because the user does not exist on
+    /// this instance.
+    pub skipped_members: Vec<String>,
+}
+
+/// Translates exported ids to the ids used on import.
+///
+/// Every id declared by the export is registered first, so references to
+/// unknown ids (e.g. a leg on a flow missing from the document) are rejected.
+pub(crate) struct IdMap {
+    fresh: bool,
+    ids: HashMap<Uuid, Uuid>,
+}
+
+impl IdMap {
+    pub(crate) fn new(fresh: bool) -> Self {
+        Self {
+            fresh,
+            ids: HashMap::new(),
+        }
+    }
+
+    /// Registers an exported id, minting a new one in `fresh` mode.
+    pub(crate) fn declare(&mut self, id: Uuid) -> ResultEngine<()> {
+        let mapped = if self.fresh { Uuid::new_v4() } else { id };
+        if self.ids.insert(id, mapped).is_some() {
+            return Err(EngineError::InvalidExport(format!("duplicate id {id}")));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn get(&self, id: Uuid) -> ResultEngine<Uuid> {
+        self.ids
+            .get(&id)
+            .copied()
+            .ok_or_else(|| EngineError::InvalidExport(format!("unknown id {id}")))
+    }
+
+    pub(crate) fn get_opt(&self, id: Option<Uuid>) -> ResultEngine<Option<Uuid>> {
+        id.map(|id| self.get(id)).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn id_map_rejects_duplicates_and_unknown_ids() {
+        let id = Uuid::new_v4();
+        let mut ids = IdMap::new(false);
+        ids.declare(id).unwrap();
+        assert_eq!(ids.get(id).unwrap(), id);
+        assert!(matches!(
+            ids.declare(id),
+            Err(EngineError::InvalidExport(_))
+        ));
+        assert!(matches!(
+            ids.get(Uuid::new_v4()),
+            Err(EngineError::InvalidExport(_))
+        ));
+
+        let mut fresh = IdMap::new(true);
+        fresh.declare(id).unwrap();
+        assert_ne!(fresh.get(id).unwrap(), id);
+        assert_eq!(fresh.get_opt(None).unwrap(), None);
+    }
+
+    #[test]
+    fn flow_mode_and_leg_target_are_flattened() {
+        let flow = FlowRecord {
+            id: Uuid::nil(),
+            name: "Trips".to_string(),
+            unallocated: false,
+            mode: FlowModeRecord::NetCapped { cap_minor: 500 },
+            archived: false,
+            balance_minor: 0,
+            target_minor: None,
+            target_date: None,
+        };
+        let json = serde_json::to_value(&flow).unwrap();
+        assert_eq!(json["mode"], "net_capped");
+        assert_eq!(json["cap_minor"], 500);
+        assert_eq!(serde_json::from_value::<FlowRecord>(json).unwrap(), flow);
+
+        let leg = LegRecord {
+            id: Uuid::nil(),
+            target: LegTarget::Wallet {
+                wallet_id: Uuid::nil(),
+            },
+            amount_minor: -100,
+            currency: Currency::Eur,
+            attributed_user_id: None,
+            category_id: None,
+            note: None,
+        };
+        let json = serde_json::to_value(&leg).unwrap();
+        assert_eq!(json["target"], "wallet");
+        assert_eq!(serde_json::from_value::<LegRecord>(json).unwrap(), leg);
+    }
+}
@@ -0,0 +1,244 @@
+//! Balances as of a point in time.
+//!
+//! Wallets and flows only store their current balance. Past balances are
+//! rebuilt from the legs of non-voided transactions, ordered by
+//! `occurred_at`: the balance at an instant is the sum of the legs that
+//! occurred strictly before it. A series samples that balance at the end of
+//! each day, ISO week (starting on Monday) or month, computed in the requested
+//! UTC offset.
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{Currency, EngineError, ResultEngine, util::CalendarPeriod};
+
+/// Longest series a single query may return.
+const MAX_POINTS: usize = 5000;
+
+/// What a balance is computed for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "target", rename_all = "snake_case")]
+pub enum BalanceTarget {
+    /// The sum of every flow of the vault, in vault currency: its net worth.
+    Vault,
+    Wallet {
+        wallet_id: Uuid,
+    },
+    Flow {
+        flow_id: Uuid,
+    },
+}
+
+/// Spacing of the points of a balance series.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceInterval {
+    Day,
+    Week,
+    Month,
+}
+
+impl BalanceInterval {
+    fn calendar_period(self) -> CalendarPeriod {
+        match self {
+            Self::Day => CalendarPeriod::Day,
+            Self::Week => CalendarPeriod::Week,
+            Self::Month => CalendarPeriod::Month,
+        }
+    }
+
+    /// First day of the period containing `date`.
+    #[must_use]
+    pub fn period_start(self, date: NaiveDate) -> Option<NaiveDate> {
+        self.calendar_period().start(date)
+    }
+
+    /// First day of the period after the one starting on `start`.
+    fn next_start(self, start: NaiveDate) -> Option<NaiveDate> {
+        self.calendar_period().next_start(start)
+    }
+}
+
+/// Balance of a target at one instant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Balance {
+    pub currency: Currency,
+    pub balance_minor: i64,
+}
+
+/// Closing balance of one period.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BalancePoint {
+    pub period_start: NaiveDate,
+    /// Balance at the end of the period, or at the end of the range for the
+    /// last one.
+    pub balance_minor: i64,
+}
+
+/// Balance of a target over a time range.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BalanceHistory {
+    pub currency: Currency,
+    pub interval: BalanceInterval,
+    /// Balance at the start of the range.
+    pub opening_minor: i64,
+    /// One point per period overlapping the range, oldest first.
+    pub points: Vec<BalancePoint>,
+}
+
+fn midnight(date: NaiveDate, offset: FixedOffset) -> ResultEngine<DateTime<Utc>> {
+    date.and_time(NaiveTime::MIN)
+        .and_local_timezone(offset)
+        .single()
+        .map(|at| at.with_timezone(&Utc))
+        .ok_or_else(|| EngineError::InvalidAmount("date out of range".to_string()))
+}
+
+/// Checks that `[from, to)` is a non-empty range.
+pub(crate) fn validate_range(from: DateTime<Utc>, to: DateTime<Utc>) -> ResultEngine<()> {
+    if from >= to {
+        return Err(EngineError::InvalidAmount(
+            "invalid range: from must be < to".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Samples the running balance at the end of each period of `[from, to)`.
+///
+/// `changes` are the signed leg amounts in the range, sorted by time;
+/// `opening_minor` is the balance at `from`.
+pub(crate) fn series(
+    interval: BalanceInterval,
+    offset: FixedOffset,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    opening_minor: i64,
+    changes: &[(DateTime<Utc>, i64)],
+) -> ResultEngine<Vec<BalancePoint>> {
+    validate_range(from, to)?;
+    let out_of_range = || EngineError::InvalidAmount("date out of range".to_string());
+
+    let mut points = Vec::new();
+    let mut balance = opening_minor;
+    let mut changes = changes.iter().peekable();
+    let mut start = interval
+        .period_start(from.with_timezone(&offset).date_naive())
+        .ok_or_else(out_of_range)?;
+    loop {
+        if points.len() == MAX_POINTS {
+            return Err(EngineError::InvalidAmount(
+                "range too long for the interval".to_string(),
+            ));
+        }
+        let next = interval.next_start(start).ok_or_else(out_of_range)?;
+        let end = midnight(next, offset)?.min(to);
+        while let Some((_, amount_minor)) = changes.next_if(|(at, _)| *at < end) {
+            balance = balance
+                .checked_add(*amount_minor)
+                .ok_or_else(|| EngineError::InvalidAmount("amount overflow".to_string()))?;
+        }
+        points.push(BalancePoint {
+            period_start: start,
+            balance_minor: balance,
+        });
+        if end >= to {
+            return Ok(points);
+        }
+        start = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn periods_start_on_monday_and_first_of_month() {
+        // 2026-03-12 is a Thursday.
+        let date = day(2026, 3, 12);
+        assert_eq!(BalanceInterval::Day.period_start(date), Some(date));
+        assert_eq!(
+            BalanceInterval::Week.period_start(date),
+            Some(day(2026, 3, 9))
+        );
+        assert_eq!(
+            BalanceInterval::Month.period_start(date),
+            Some(day(2026, 3, 1))
+        );
+        assert_eq!(
+            BalanceInterval::Month.next_start(day(2026, 1, 1)),
+            Some(day(2026, 2, 1))
+        );
+    }
+
+    #[test]
+    fn series_closes_each_period_in_the_given_offset() {
+        let from = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2026, 3, 15, 0, 0, 0).unwrap();
+        let changes = [
+            (Utc.with_ymd_and_hms(2026, 1, 10, 12, 0, 0).unwrap(), 5000),
+            // 23:30 UTC on Jan 31st is already February at +01:00.
+            (Utc.with_ymd_and_hms(2026, 1, 31, 23, 30, 0).unwrap(), -1200),
+            (Utc.with_ymd_and_hms(2026, 3, 14, 9, 0, 0).unwrap(), 300),
+        ];
+
+        let points = series(
+            BalanceInterval::Month,
+            FixedOffset::east_opt(0).unwrap(),
+            from,
+            to,
+            100,
+            &changes,
+        )
+        .unwrap();
+        let balances: Vec<_> = points
+            .iter()
+            .map(|point| (point.period_start, point.balance_minor))
+            .collect();
+        assert_eq!(
+            balances,
+            [
+                (day(2026, 1, 1), 3900),
+                (day(2026, 2, 1), 3900),
+                (day(2026, 3, 1), 4200),
+            ]
+        );
+
+        let points = series(
+            BalanceInterval::Month,
+            FixedOffset::east_opt(3600).unwrap(),
+            from,
+            to,
+            100,
+            &changes,
+        )
+        .unwrap();
+        let balances: Vec<_> = points.iter().map(|point| point.balance_minor).collect();
+        assert_eq!(balances, [5100, 3900, 4200]);
+    }
+
+    #[test]
+    fn series_rejects_empty_and_oversized_ranges() {
+        let from = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let offset = FixedOffset::east_opt(0).unwrap();
+        assert!(series(BalanceInterval::Day, offset, from, from, 0, &[]).is_err());
+        let to = Utc.with_ymd_and_hms(2046, 1, 1, 0, 0, 0).unwrap();
+        assert!(series(BalanceInterval::Day, offset, from, to, 0, &[]).is_err());
+        assert_eq!(
+            series(BalanceInterval::Month, offset, from, to, 0, &[])
+                .unwrap()
+                .len(),
+            240
+        );
+    }
+}
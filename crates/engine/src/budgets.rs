@@ -0,0 +1,338 @@
+//! Periodic spending budgets per category.
+//!
+//! A [`Budget`] caps the spending of one category per period. Periods are
+//! anchored on `starts_on`: weekly budgets restart on its weekday, monthly
+//! budgets on its day of month (clamped to shorter months) and yearly budgets
+//! on its date. Spending is read from the flow legs of expenses and refunds,
+//! so amounts are always in the vault currency.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use sea_orm::{ActiveValue, entity::prelude::*};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{EngineError, ResultEngine, util};
+
+/// Length of a budget period.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    #[sea_orm(string_value = "weekly")]
+    Weekly,
+    #[sea_orm(string_value = "monthly")]
+    Monthly,
+    #[sea_orm(string_value = "yearly")]
+    Yearly,
+}
+
+/// Spending limit of a category.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Budget {
+    pub id: Uuid,
+    pub vault_id: String,
+    pub category_id: Uuid,
+    /// Limit per period, in vault currency minor units.
+    pub amount_minor: i64,
+    pub period: BudgetPeriod,
+    /// First day of the first period.
+    pub starts_on: NaiveDate,
+    /// Carry unspent (or overspent) amounts into the following periods.
+    pub rollover: bool,
+}
+
+impl Budget {
+    /// Returns the first day of the `index`-th period (0-based).
+    #[must_use]
+    pub fn period_start(&self, index: u32) -> Option<NaiveDate> {
+        match self.period {
+            BudgetPeriod::Weekly => self
+                .starts_on
+                .checked_add_signed(Duration::weeks(i64::from(index))),
+            BudgetPeriod::Monthly => {
+                util::add_months_on_day(self.starts_on, index, self.starts_on.day())
+            }
+            BudgetPeriod::Yearly => util::add_months_on_day(
+                self.starts_on,
+                index.checked_mul(12)?,
+                self.starts_on.day(),
+            ),
+        }
+    }
+
+    /// Returns the index of the period containing `date`, or `None` if `date`
+    /// is before `starts_on`.
+    #[must_use]
+    pub fn period_index(&self, date: NaiveDate) -> Option<u32> {
+        if date < self.starts_on {
+            return None;
+        }
+        let estimate = match self.period {
+            BudgetPeriod::Weekly => {
+                return u32::try_from((date - self.starts_on).num_weeks()).ok();
+            }
+            BudgetPeriod::Monthly => month_index(date) - month_index(self.starts_on),
+            BudgetPeriod::Yearly => i64::from(date.year() - self.starts_on.year()),
+        };
+        let estimate = u32::try_from(estimate).ok()?;
+        // Clamped anchors (e.g. the 31st) can start later in the month than
+        // `date`; step back one period in that case.
+        if self.period_start(estimate)? > date {
+            estimate.checked_sub(1)
+        } else {
+            Some(estimate)
+        }
+    }
+
+    /// Returns the index, first day and exclusive end of the period
+    /// containing `on`; dates before `starts_on` map to the first period.
+    pub(crate) fn period_containing(
+        &self,
+        on: NaiveDate,
+    ) -> ResultEngine<(u32, NaiveDate, NaiveDate)> {
+        let invalid = || EngineError::InvalidAmount("budget period out of range".to_string());
+        let index = self.period_index(on).unwrap_or(0);
+        let start = self.period_start(index).ok_or_else(invalid)?;
+        let end = index
+            .checked_add(1)
+            .and_then(|next| self.period_start(next))
+            .ok_or_else(invalid)?;
+        Ok((index, start, end))
+    }
+
+    pub(crate) fn validate(&self) -> ResultEngine<()> {
+        if self.amount_minor <= 0 {
+            return Err(EngineError::InvalidAmount(
+                "amount_minor must be > 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn month_index(date: NaiveDate) -> i64 {
+    i64::from(date.year()) * 12 + i64::from(date.month0())
+}
+
+/// Spending of a budget in the period containing a given date.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BudgetStatus {
+    pub budget: Budget,
+    pub period_start: NaiveDate,
+    /// Exclusive end of the period.
+    pub period_end: NaiveDate,
+    /// Net spending in the period (expenses minus refunds).
+    pub spent_minor: i64,
+    /// Leftover of the previous periods when rollover is enabled (negative
+    /// after overspending), otherwise zero.
+    pub carried_minor: i64,
+    /// `amount_minor + carried_minor`.
+    pub available_minor: i64,
+    /// `available_minor - spent_minor`; negative when over budget.
+    pub remaining_minor: i64,
+}
+
+impl BudgetStatus {
+    /// Builds the status of the period containing `on` from the spending
+    /// entries (`(date, spent_minor)`) since `starts_on`. Dates before
+    /// `starts_on` report the first period.
+    pub(crate) fn compute(
+        budget: Budget,
+        on: NaiveDate,
+        entries: &[(NaiveDate, i64)],
+    ) -> ResultEngine<Self> {
+        let (index, period_start, period_end) = budget.period_containing(on)?;
+
+        let mut spent_minor = 0i64;
+        let mut spent_before = 0i64;
+        for (date, amount) in entries {
+            if *date >= period_end || *date < budget.starts_on {
+                continue;
+            }
+            if *date >= period_start {
+                spent_minor += amount;
+            } else {
+                spent_before += amount;
+            }
+        }
+
+        let carried_minor = if budget.rollover {
+            budget.amount_minor * i64::from(index) - spent_before
+        } else {
+            0
+        };
+        let available_minor = budget.amount_minor + carried_minor;
+        Ok(Self {
+            budget,
+            period_start,
+            period_end,
+            spent_minor,
+            carried_minor,
+            available_minor,
+            remaining_minor: available_minor - spent_minor,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "budgets")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub vault_id: Uuid,
+    pub category_id: Uuid,
+    pub amount_minor: i64,
+    pub period: BudgetPeriod,
+    pub starts_on: Date,
+    pub rollover: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::vault::Entity",
+        from = "Column::VaultId",
+        to = "super::vault::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Vault,
+    #[sea_orm(
+        belongs_to = "super::categories::Entity",
+        from = "Column::CategoryId",
+        to = "super::categories::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Category,
+}
+
+impl Related<super::vault::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vault.def()
+    }
+}
+
+impl Related<super::categories::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Category.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl TryFrom<&Budget> for ActiveModel {
+    type Error = EngineError;
+
+    fn try_from(budget: &Budget) -> Result<Self, Self::Error> {
+        let vault_id = Uuid::parse_str(&budget.vault_id)
+            .map_err(|_| EngineError::KeyNotFound("vault not exists".to_string()))?;
+        Ok(Self {
+            id: ActiveValue::Set(budget.id),
+            vault_id: ActiveValue::Set(vault_id),
+            category_id: ActiveValue::Set(budget.category_id),
+            amount_minor: ActiveValue::Set(budget.amount_minor),
+            period: ActiveValue::Set(budget.period),
+            starts_on: ActiveValue::Set(budget.starts_on),
+            rollover: ActiveValue::Set(budget.rollover),
+        })
+    }
+}
+
+impl From<Model> for Budget {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            vault_id: model.vault_id.to_string(),
+            category_id: model.category_id,
+            amount_minor: model.amount_minor,
+            period: model.period,
+            starts_on: model.starts_on,
+            rollover: model.rollover,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn budget(period: BudgetPeriod, starts_on: NaiveDate, rollover: bool) -> Budget {
+        Budget {
+            id: Uuid::new_v4(),
+            vault_id: Uuid::new_v4().to_string(),
+            category_id: Uuid::new_v4(),
+            amount_minor: 40_000,
+            period,
+            starts_on,
+            rollover,
+        }
+    }
+
+    #[test]
+    fn weekly_periods_start_on_the_anchor_weekday() {
+        let b = budget(BudgetPeriod::Weekly, day(2026, 1, 7), false);
+        assert_eq!(b.period_index(day(2026, 1, 6)), None);
+        assert_eq!(b.period_index(day(2026, 1, 13)), Some(0));
+        assert_eq!(b.period_index(day(2026, 1, 14)), Some(1));
+        assert_eq!(b.period_start(1), Some(day(2026, 1, 14)));
+    }
+
+    #[test]
+    fn monthly_periods_clamp_the_anchor_day() {
+        let b = budget(BudgetPeriod::Monthly, day(2026, 1, 31), false);
+        assert_eq!(b.period_start(1), Some(day(2026, 2, 28)));
+        assert_eq!(b.period_start(2), Some(day(2026, 3, 31)));
+        assert_eq!(b.period_index(day(2026, 3, 15)), Some(1));
+        assert_eq!(b.period_index(day(2026, 3, 31)), Some(2));
+
+        let b = budget(BudgetPeriod::Monthly, day(2026, 1, 15), false);
+        assert_eq!(b.period_index(day(2026, 2, 14)), Some(0));
+        assert_eq!(b.period_index(day(2026, 2, 15)), Some(1));
+    }
+
+    #[test]
+    fn yearly_periods_start_on_the_anchor_date() {
+        let b = budget(BudgetPeriod::Yearly, day(2025, 4, 6), false);
+        assert_eq!(b.period_index(day(2026, 4, 5)), Some(0));
+        assert_eq!(b.period_index(day(2026, 4, 6)), Some(1));
+    }
+
+    #[test]
+    fn status_without_rollover_ignores_previous_periods() {
+        let b = budget(BudgetPeriod::Monthly, day(2026, 1, 1), false);
+        let entries = [(day(2026, 1, 10), 10_000), (day(2026, 2, 3), 15_000)];
+        let status = BudgetStatus::compute(b, day(2026, 2, 20), &entries).unwrap();
+        assert_eq!(status.period_start, day(2026, 2, 1));
+        assert_eq!(status.period_end, day(2026, 3, 1));
+        assert_eq!(status.spent_minor, 15_000);
+        assert_eq!(status.carried_minor, 0);
+        assert_eq!(status.remaining_minor, 25_000);
+    }
+
+    #[test]
+    fn status_with_rollover_carries_leftover_and_overspending() {
+        let b = budget(BudgetPeriod::Monthly, day(2026, 1, 1), true);
+        // January: 100 spent (300 left); February: 500 spent (100 over).
+        let entries = [
+            (day(2026, 1, 10), 10_000),
+            (day(2026, 2, 3), 50_000),
+            (day(2026, 3, 2), 50_00),
+        ];
+        let status = BudgetStatus::compute(b.clone(), day(2026, 2, 20), &entries).unwrap();
+        assert_eq!(status.carried_minor, 30_000);
+        assert_eq!(status.available_minor, 70_000);
+        assert_eq!(status.remaining_minor, 20_000);
+
+        let status = BudgetStatus::compute(b, day(2026, 3, 5), &entries).unwrap();
+        assert_eq!(status.carried_minor, 20_000);
+        assert_eq!(status.spent_minor, 50_00);
+        assert_eq!(status.remaining_minor, 55_000);
+    }
+}
@@ -1,19 +1,21 @@
 //! Cash flows.
 
+use chrono::NaiveDate;
 use sea_orm::entity::{ActiveValue, prelude::*};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
-    Currency, EngineError, ResultEngine,
+    Currency, EngineError, FlowLimit, ResultEngine,
     util::{ensure_vault_currency, validate_flow_mode_fields},
 };
 
 pub(crate) const UNALLOCATED_INTERNAL_NAME: &str = "unallocated";
 
 /// Identifies special system-managed flows.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, EnumIter, DeriveActiveEnum)]
 #[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
 pub enum SystemFlowKind {
     #[sea_orm(string_value = "unallocated")]
     Unallocated,
@@ -109,6 +111,14 @@ pub struct CashFlow {
     pub income_balance: Option<i64>,
     pub currency: Currency,
     pub archived: bool,
+    /// Savings goal: the balance to reach, see [`GoalProgress`].
+    ///
+    /// [`GoalProgress`]: crate::GoalProgress
+    #[serde(default)]
+    pub target_minor: Option<i64>,
+    /// Date the goal should be reached by.
+    #[serde(default)]
+    pub target_date: Option<NaiveDate>,
 }
 
 impl CashFlow {
@@ -162,9 +172,21 @@ impl CashFlow {
             income_balance,
             currency,
             archived: false,
+            target_minor: None,
+            target_date: None,
         })
     }
 
+    pub(crate) fn limit(&self, limit_minor: i64, attempted_minor: i64) -> FlowLimit {
+        FlowLimit {
+            flow_id: self.id,
+            flow_name: self.name.clone(),
+            currency: self.currency,
+            limit_minor,
+            attempted_minor,
+        }
+    }
+
     pub fn apply_leg_change(
         &mut self,
         old_amount_minor: i64,
@@ -175,14 +197,16 @@ impl CashFlow {
         let new_balance = self.balance - old_amount_minor + new_amount_minor;
 
         if !is_unallocated && new_balance < 0 {
-            return Err(EngineError::InsufficientFunds(self.name.clone()));
+            return Err(EngineError::InsufficientFunds(self.limit(0, new_balance)));
         }
 
         match mode {
             FlowMode::Unlimited => {}
             FlowMode::NetCapped { cap_minor } => {
                 if new_balance > cap_minor {
-                    return Err(EngineError::MaxBalanceReached(self.name.clone()));
+                    return Err(EngineError::MaxBalanceReached(
+                        self.limit(cap_minor, new_balance),
+                    ));
                 }
             }
             FlowMode::IncomeCapped {
@@ -193,7 +217,9 @@ impl CashFlow {
                     - income_contribution_minor(old_amount_minor)
                     + income_contribution_minor(new_amount_minor);
                 if new_income_total > cap_minor {
-                    return Err(EngineError::MaxBalanceReached(self.name.clone()));
+                    return Err(EngineError::MaxBalanceReached(
+                        self.limit(cap_minor, new_income_total),
+                    ));
                 }
                 self.income_balance = Some(new_income_total);
             }
@@ -202,9 +228,29 @@ impl CashFlow {
         self.balance = new_balance;
         Ok(())
     }
+
+    /// Like [`Self::apply_leg_change`], but applies the change even when it
+    /// breaks the flow limits, and returns the broken limit.
+    pub(crate) fn force_leg_change(
+        &mut self,
+        old_amount_minor: i64,
+        new_amount_minor: i64,
+    ) -> Option<EngineError> {
+        let err = self
+            .apply_leg_change(old_amount_minor, new_amount_minor)
+            .err()?;
+        self.balance = self.balance - old_amount_minor + new_amount_minor;
+        if let Some(income_total_minor) = self.income_balance {
+            self.income_balance = Some(
+                income_total_minor - income_contribution_minor(old_amount_minor)
+                    + income_contribution_minor(new_amount_minor),
+            );
+        }
+        Some(err)
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "cash_flows")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -217,6 +263,8 @@ pub struct Model {
     pub currency: Currency,
     pub archived: bool,
     pub vault_id: Uuid,
+    pub target_minor: Option<i64>,
+    pub target_date: Option<Date>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -255,6 +303,8 @@ impl TryFrom<(Model, Currency)> for CashFlow {
             income_balance: model.income_balance,
             currency: model.currency,
             archived: model.archived,
+            target_minor: model.target_minor,
+            target_date: model.target_date,
         })
     }
 }
@@ -270,6 +320,8 @@ impl From<&CashFlow> for ActiveModel {
             income_balance: ActiveValue::Set(flow.income_balance),
             currency: ActiveValue::Set(flow.currency),
             archived: ActiveValue::Set(flow.archived),
+            target_minor: ActiveValue::Set(flow.target_minor),
+            target_date: ActiveValue::Set(flow.target_date),
             vault_id: ActiveValue::NotSet,
         }
     }
@@ -338,7 +390,7 @@ mod tests {
     fn fail_net_capped_add_income_over_cap() {
         let mut flow = net_capped();
         let err = flow.apply_leg_change(0, 2044).unwrap_err();
-        assert_eq!(err, EngineError::MaxBalanceReached("Cash".to_string()));
+        assert_eq!(err, EngineError::MaxBalanceReached(flow.limit(1000, 2044)));
     }
 
     #[test]
@@ -346,14 +398,15 @@ mod tests {
         let mut flow = net_capped();
         flow.apply_leg_change(0, 123).unwrap();
         let err = flow.apply_leg_change(123, 2000).unwrap_err();
-        assert_eq!(err, EngineError::MaxBalanceReached("Cash".to_string()));
+        assert_eq!(err, EngineError::MaxBalanceReached(flow.limit(1000, 2000)));
     }
 
     #[test]
     fn fail_non_unallocated_negative_balance() {
         let mut flow = unbounded();
         let err = flow.apply_leg_change(0, -1).unwrap_err();
-        assert_eq!(err, EngineError::InsufficientFunds("Cash".to_string()));
+        assert_eq!(err, EngineError::InsufficientFunds(flow.limit(0, -1)));
+        assert_eq!(flow.limit(0, -1).excess_minor(), 1);
     }
 
     #[test]
@@ -376,4 +429,24 @@ mod tests {
         flow.apply_leg_change(-50, 0).unwrap();
         assert_eq!(flow.income_balance, Some(100));
     }
+
+    #[test]
+    fn force_leg_change_applies_and_reports_the_broken_limit() {
+        let mut flow = income_capped();
+        let err = flow.force_leg_change(0, 1500);
+        assert_eq!(
+            err,
+            Some(EngineError::MaxBalanceReached(flow.limit(1000, 1500)))
+        );
+        assert_eq!(flow.balance, 1500);
+        assert_eq!(flow.income_balance, Some(1500));
+
+        let err = flow.force_leg_change(0, -1600);
+        assert_eq!(
+            err,
+            Some(EngineError::InsufficientFunds(flow.limit(0, -100)))
+        );
+        assert_eq!(flow.balance, -100);
+        assert_eq!(flow.income_balance, Some(1500));
+    }
 }
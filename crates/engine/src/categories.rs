@@ -1,6 +1,7 @@
 //! Category registry per vault.
 
 use sea_orm::entity::prelude::*;
+use serde::Serialize;
 use uuid::Uuid;
 
 /// Canonical category entry exposed to clients.
@@ -12,7 +13,7 @@ pub struct Category {
     pub is_system: bool,
 }
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "categories")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -1,6 +1,7 @@
 //! Category aliases per vault.
 
 use sea_orm::entity::prelude::*;
+use serde::Serialize;
 use uuid::Uuid;
 
 /// Alias entry exposed to clients.
@@ -11,7 +12,7 @@ pub struct CategoryAlias {
     pub category_id: Uuid,
 }
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "category_aliases")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -0,0 +1,424 @@
+//! Auto-categorization rules.
+//!
+//! A [`CategoryRule`] assigns its category to incomes, expenses and refunds
+//! created (or imported) without one. Rules are tried by ascending `priority`,
+//! then creation order; the first rule whose conditions all hold wins. A rule
+//! without conditions matches every transaction.
+
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexBuilder};
+use sea_orm::{ActiveValue, entity::prelude::*};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{EngineError, ResultEngine, TransactionKind, util::normalize_optional_text};
+
+/// Upper bound on the compiled size of a note regex.
+const REGEX_SIZE_LIMIT: usize = 1 << 16;
+
+/// Conditions a transaction must meet for a rule to apply. Unset conditions
+/// always hold.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryRuleConditions {
+    /// Case-insensitive substring of the note.
+    #[serde(default)]
+    pub note_contains: Option<String>,
+    /// Regular expression searched in the note (prefix with `(?i)` to ignore
+    /// case).
+    #[serde(default)]
+    pub note_regex: Option<String>,
+    /// Inclusive lower bound of the amount, in vault currency minor units.
+    #[serde(default)]
+    pub min_amount_minor: Option<i64>,
+    /// Inclusive upper bound of the amount, in vault currency minor units.
+    #[serde(default)]
+    pub max_amount_minor: Option<i64>,
+    #[serde(default)]
+    pub wallet_id: Option<Uuid>,
+    #[serde(default)]
+    pub flow_id: Option<Uuid>,
+    /// `income`, `expense` or `refund`.
+    #[serde(default)]
+    pub kind: Option<TransactionKind>,
+}
+
+impl CategoryRuleConditions {
+    /// Trims the note conditions and checks bounds, kind and regex.
+    pub(crate) fn normalize(mut self) -> ResultEngine<Self> {
+        self.note_contains = normalize_optional_text(self.note_contains.as_deref());
+        self.note_regex = normalize_optional_text(self.note_regex.as_deref());
+        if self.min_amount_minor.is_some_and(|min| min < 0)
+            || self.max_amount_minor.is_some_and(|max| max < 0)
+        {
+            return Err(EngineError::InvalidRule(
+                "amount bounds must be >= 0".to_string(),
+            ));
+        }
+        if matches!(
+            (self.min_amount_minor, self.max_amount_minor),
+            (Some(min), Some(max)) if min > max
+        ) {
+            return Err(EngineError::InvalidRule(
+                "min_amount_minor must be <= max_amount_minor".to_string(),
+            ));
+        }
+        if self.kind.is_some_and(|kind| !is_categorizable(kind)) {
+            return Err(EngineError::InvalidRule(
+                "kind must be income, expense or refund".to_string(),
+            ));
+        }
+        if let Some(pattern) = &self.note_regex {
+            compile_regex(pattern)?;
+        }
+        Ok(self)
+    }
+}
+
+/// Whether rules apply to transactions of `kind` (transfers carry no
+/// category).
+pub(crate) fn is_categorizable(kind: TransactionKind) -> bool {
+    matches!(
+        kind,
+        TransactionKind::Income | TransactionKind::Expense | TransactionKind::Refund
+    )
+}
+
+fn compile_regex(pattern: &str) -> ResultEngine<Regex> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|err| EngineError::InvalidRule(format!("invalid note_regex: {err}")))
+}
+
+/// Rule assigning a category to matching transactions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CategoryRule {
+    pub id: Uuid,
+    pub vault_id: String,
+    pub category_id: Uuid,
+    /// Lower values are tried first.
+    pub priority: i32,
+    pub conditions: CategoryRuleConditions,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A transaction that a rule would (re)categorize.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CategoryRuleHit {
+    pub transaction_id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    pub kind: TransactionKind,
+    /// Amount in vault currency minor units.
+    pub amount_minor: i64,
+    pub note: Option<String>,
+    pub previous_category_id: Uuid,
+    pub rule_id: Uuid,
+    pub category_id: Uuid,
+    /// Set when the transaction cannot be changed; applying leaves it as is.
+    pub skipped: Option<CategoryRuleSkip>,
+}
+
+/// Why a rule hit is left unchanged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CategoryRuleSkip {
+    /// The transaction is reconciled: unlock it first.
+    Reconciled,
+    /// The transaction occurred in a closed period.
+    BooksClosed,
+}
+
+/// What rules are matched against.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RuleSubject<'a> {
+    pub(crate) kind: TransactionKind,
+    /// Amount in vault currency minor units (positive).
+    pub(crate) amount_minor: i64,
+    pub(crate) wallet_id: Option<Uuid>,
+    pub(crate) flow_id: Option<Uuid>,
+    pub(crate) note: Option<&'a str>,
+}
+
+/// A rule with its note matchers prepared.
+pub(crate) struct CompiledRule {
+    pub(crate) rule: CategoryRule,
+    note_contains: Option<String>,
+    note_regex: Option<Regex>,
+}
+
+impl CompiledRule {
+    pub(crate) fn new(rule: CategoryRule) -> ResultEngine<Self> {
+        let note_regex = rule
+            .conditions
+            .note_regex
+            .as_deref()
+            .map(compile_regex)
+            .transpose()?;
+        Ok(Self {
+            note_contains: rule
+                .conditions
+                .note_contains
+                .as_deref()
+                .map(str::to_lowercase),
+            note_regex,
+            rule,
+        })
+    }
+
+    pub(crate) fn matches(&self, subject: &RuleSubject<'_>) -> bool {
+        let conditions = &self.rule.conditions;
+        if !is_categorizable(subject.kind)
+            || conditions.kind.is_some_and(|kind| kind != subject.kind)
+            || conditions
+                .min_amount_minor
+                .is_some_and(|min| subject.amount_minor < min)
+            || conditions
+                .max_amount_minor
+                .is_some_and(|max| subject.amount_minor > max)
+            || conditions
+                .wallet_id
+                .is_some_and(|id| subject.wallet_id != Some(id))
+            || conditions
+                .flow_id
+                .is_some_and(|id| subject.flow_id != Some(id))
+        {
+            return false;
+        }
+        let note = subject.note.unwrap_or_default();
+        let contains = self
+            .note_contains
+            .as_ref()
+            .is_none_or(|needle| note.to_lowercase().contains(needle));
+        contains
+            && self
+                .note_regex
+                .as_ref()
+                .is_none_or(|regex| regex.is_match(note))
+    }
+}
+
+/// Returns the first rule (in priority order) matching `subject`.
+pub(crate) fn first_match<'a>(
+    rules: &'a [CompiledRule],
+    subject: &RuleSubject<'_>,
+) -> Option<&'a CategoryRule> {
+    rules
+        .iter()
+        .find(|rule| rule.matches(subject))
+        .map(|rule| &rule.rule)
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "category_rules")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub vault_id: Uuid,
+    pub category_id: Uuid,
+    pub priority: i32,
+    pub note_contains: Option<String>,
+    pub note_regex: Option<String>,
+    pub min_amount_minor: Option<i64>,
+    pub max_amount_minor: Option<i64>,
+    pub wallet_id: Option<Uuid>,
+    pub flow_id: Option<Uuid>,
+    pub kind: Option<TransactionKind>,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::vault::Entity",
+        from = "Column::VaultId",
+        to = "super::vault::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Vault,
+    #[sea_orm(
+        belongs_to = "super::categories::Entity",
+        from = "Column::CategoryId",
+        to = "super::categories::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Category,
+}
+
+impl Related<super::vault::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vault.def()
+    }
+}
+
+impl Related<super::categories::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Category.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl TryFrom<&CategoryRule> for ActiveModel {
+    type Error = EngineError;
+
+    fn try_from(rule: &CategoryRule) -> Result<Self, Self::Error> {
+        let vault_id = Uuid::parse_str(&rule.vault_id)
+            .map_err(|_| EngineError::KeyNotFound("vault not exists".to_string()))?;
+        let conditions = &rule.conditions;
+        Ok(Self {
+            id: ActiveValue::Set(rule.id),
+            vault_id: ActiveValue::Set(vault_id),
+            category_id: ActiveValue::Set(rule.category_id),
+            priority: ActiveValue::Set(rule.priority),
+            note_contains: ActiveValue::Set(conditions.note_contains.clone()),
+            note_regex: ActiveValue::Set(conditions.note_regex.clone()),
+            min_amount_minor: ActiveValue::Set(conditions.min_amount_minor),
+            max_amount_minor: ActiveValue::Set(conditions.max_amount_minor),
+            wallet_id: ActiveValue::Set(conditions.wallet_id),
+            flow_id: ActiveValue::Set(conditions.flow_id),
+            kind: ActiveValue::Set(conditions.kind),
+            created_at: ActiveValue::Set(rule.created_at),
+        })
+    }
+}
+
+impl From<Model> for CategoryRule {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            vault_id: model.vault_id.to_string(),
+            category_id: model.category_id,
+            priority: model.priority,
+            conditions: CategoryRuleConditions {
+                note_contains: model.note_contains,
+                note_regex: model.note_regex,
+                min_amount_minor: model.min_amount_minor,
+                max_amount_minor: model.max_amount_minor,
+                wallet_id: model.wallet_id,
+                flow_id: model.flow_id,
+                kind: model.kind,
+            },
+            created_at: model.created_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    fn rule(priority: i32, conditions: CategoryRuleConditions) -> CompiledRule {
+        CompiledRule::new(CategoryRule {
+            id: Uuid::new_v4(),
+            vault_id: Uuid::new_v4().to_string(),
+            category_id: Uuid::new_v4(),
+            priority,
+            conditions: conditions.normalize().unwrap(),
+            created_at: Utc::now(),
+        })
+        .unwrap()
+    }
+
+    fn subject(note: &str, amount_minor: i64) -> RuleSubject<'_> {
+        RuleSubject {
+            kind: TransactionKind::Expense,
+            amount_minor,
+            wallet_id: None,
+            flow_id: None,
+            note: Some(note),
+        }
+    }
+
+    #[test]
+    fn conditions_must_all_hold() {
+        let wallet = Uuid::new_v4();
+        let groceries = rule(
+            0,
+            CategoryRuleConditions {
+                note_contains: Some(" esselunga ".to_string()),
+                max_amount_minor: Some(20_000),
+                wallet_id: Some(wallet),
+                kind: Some(TransactionKind::Expense),
+                ..CategoryRuleConditions::default()
+            },
+        );
+        let mut hit = subject("ESSELUNGA Milano", 4_510);
+        hit.wallet_id = Some(wallet);
+        assert!(groceries.matches(&hit));
+        assert!(!groceries.matches(&RuleSubject {
+            amount_minor: 25_000,
+            ..hit
+        }));
+        assert!(!groceries.matches(&RuleSubject {
+            wallet_id: None,
+            ..hit
+        }));
+        assert!(!groceries.matches(&RuleSubject {
+            kind: TransactionKind::Income,
+            ..hit
+        }));
+        assert!(!groceries.matches(&RuleSubject { note: None, ..hit }));
+    }
+
+    #[test]
+    fn first_match_follows_slice_order() {
+        let rules = [
+            rule(
+                0,
+                CategoryRuleConditions {
+                    note_regex: Some(r"(?i)^uber\s+eats".to_string()),
+                    ..CategoryRuleConditions::default()
+                },
+            ),
+            rule(
+                1,
+                CategoryRuleConditions {
+                    note_contains: Some("uber".to_string()),
+                    ..CategoryRuleConditions::default()
+                },
+            ),
+            rule(2, CategoryRuleConditions::default()),
+        ];
+        let id = |note| first_match(&rules, &subject(note, 1_000)).map(|rule| rule.id);
+        assert_eq!(id("Uber Eats order"), Some(rules[0].rule.id));
+        assert_eq!(id("UBER trip"), Some(rules[1].rule.id));
+        assert_eq!(id("Bakery"), Some(rules[2].rule.id));
+
+        // Transfers are never categorized.
+        let transfer = RuleSubject {
+            kind: TransactionKind::TransferWallet,
+            ..subject("Bakery", 1_000)
+        };
+        assert!(first_match(&rules, &transfer).is_none());
+    }
+
+    #[test]
+    fn invalid_conditions_are_rejected() {
+        let invalid = |conditions: CategoryRuleConditions| {
+            matches!(conditions.normalize(), Err(EngineError::InvalidRule(_)))
+        };
+        assert!(invalid(CategoryRuleConditions {
+            note_regex: Some("(unclosed".to_string()),
+            ..CategoryRuleConditions::default()
+        }));
+        assert!(invalid(CategoryRuleConditions {
+            min_amount_minor: Some(500),
+            max_amount_minor: Some(100),
+            ..CategoryRuleConditions::default()
+        }));
+        assert!(invalid(CategoryRuleConditions {
+            kind: Some(TransactionKind::TransferFlow),
+            ..CategoryRuleConditions::default()
+        }));
+        let normalized = CategoryRuleConditions {
+            note_contains: Some("  ".to_string()),
+            ..CategoryRuleConditions::default()
+        }
+        .normalize()
+        .unwrap();
+        assert_eq!(normalized.note_contains, None);
+    }
+}
@@ -1,12 +1,19 @@
 //! Command structs for engine operations.
 //!
 //! These types group parameters for write operations
-//! (income/expense/transfer/update), keeping call sites readable and avoiding
-//! long argument lists.
+//! (income/expense/transfer/update/recurring rules/budgets/imports/backups/
+//! access tokens) and reads such as reports and the audit log, keeping call sites
+//! readable and avoiding long argument lists.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset, NaiveDate, Offset, Utc};
 use uuid::Uuid;
 
+use crate::{
+    AuditEntity, BalanceInterval, BalanceTarget, BudgetPeriod, CategoryRuleConditions, CsvMapping,
+    Currency, ExchangeRate, Recurrence, ReportGroupBy, StatementFormat, TokenScope,
+    TransactionKind, VaultExport,
+};
+
 /// Common metadata for transaction creation.
 #[derive(Clone, Debug)]
 pub struct TxMeta {
@@ -60,9 +67,15 @@ impl TxMeta {
 #[derive(Clone, Debug)]
 pub struct IncomeCmd {
     pub vault_id: String,
+    /// Amount in `currency` minor units (wallet currency when unset).
     pub amount_minor: i64,
     pub flow_id: Option<Uuid>,
     pub wallet_id: Option<Uuid>,
+    /// Currency the amount was paid in (defaults to the wallet currency).
+    pub currency: Option<Currency>,
+    /// Rate from `currency` to the other currency involved, required when the
+    /// amount, wallet and vault currencies differ.
+    pub exchange_rate: Option<ExchangeRate>,
     pub meta: TxMeta,
     pub user_id: String,
 }
@@ -80,6 +93,8 @@ impl IncomeCmd {
             amount_minor,
             flow_id: None,
             wallet_id: None,
+            currency: None,
+            exchange_rate: None,
             meta: TxMeta::new(occurred_at),
             user_id: user_id.into(),
         }
@@ -97,6 +112,18 @@ impl IncomeCmd {
         self
     }
 
+    #[must_use]
+    pub fn currency(mut self, currency: Currency) -> Self {
+        self.currency = Some(currency);
+        self
+    }
+
+    #[must_use]
+    pub fn exchange_rate(mut self, rate: ExchangeRate) -> Self {
+        self.exchange_rate = Some(rate);
+        self
+    }
+
     #[must_use]
     pub fn meta(mut self, meta: TxMeta) -> Self {
         self.meta = meta;
@@ -132,9 +159,15 @@ impl IncomeCmd {
 #[derive(Clone, Debug)]
 pub struct ExpenseCmd {
     pub vault_id: String,
+    /// Amount in `currency` minor units (wallet currency when unset).
     pub amount_minor: i64,
     pub flow_id: Option<Uuid>,
     pub wallet_id: Option<Uuid>,
+    /// Currency the amount was paid in (defaults to the wallet currency).
+    pub currency: Option<Currency>,
+    /// Rate from `currency` to the other currency involved, required when the
+    /// amount, wallet and vault currencies differ.
+    pub exchange_rate: Option<ExchangeRate>,
     pub meta: TxMeta,
     pub user_id: String,
 }
@@ -152,6 +185,8 @@ impl ExpenseCmd {
             amount_minor,
             flow_id: None,
             wallet_id: None,
+            currency: None,
+            exchange_rate: None,
             meta: TxMeta::new(occurred_at),
             user_id: user_id.into(),
         }
@@ -169,6 +204,18 @@ impl ExpenseCmd {
         self
     }
 
+    #[must_use]
+    pub fn currency(mut self, currency: Currency) -> Self {
+        self.currency = Some(currency);
+        self
+    }
+
+    #[must_use]
+    pub fn exchange_rate(mut self, rate: ExchangeRate) -> Self {
+        self.exchange_rate = Some(rate);
+        self
+    }
+
     #[must_use]
     pub fn meta(mut self, meta: TxMeta) -> Self {
         self.meta = meta;
@@ -204,9 +251,18 @@ impl ExpenseCmd {
 #[derive(Clone, Debug)]
 pub struct RefundCmd {
     pub vault_id: String,
+    /// Amount in `currency` minor units (wallet currency when unset).
     pub amount_minor: i64,
+    /// Expense being refunded; its wallet, flow and category become the
+    /// defaults.
+    pub refunded_transaction_id: Option<Uuid>,
     pub flow_id: Option<Uuid>,
     pub wallet_id: Option<Uuid>,
+    /// Currency the amount was paid in (defaults to the wallet currency).
+    pub currency: Option<Currency>,
+    /// Rate from `currency` to the other currency involved, required when the
+    /// amount, wallet and vault currencies differ.
+    pub exchange_rate: Option<ExchangeRate>,
     pub meta: TxMeta,
     pub user_id: String,
 }
@@ -222,13 +278,22 @@ impl RefundCmd {
         Self {
             vault_id: vault_id.into(),
             amount_minor,
+            refunded_transaction_id: None,
             flow_id: None,
             wallet_id: None,
+            currency: None,
+            exchange_rate: None,
             meta: TxMeta::new(occurred_at),
             user_id: user_id.into(),
         }
     }
 
+    #[must_use]
+    pub fn refunded_transaction_id(mut self, transaction_id: Uuid) -> Self {
+        self.refunded_transaction_id = Some(transaction_id);
+        self
+    }
+
     #[must_use]
     pub fn flow_id(mut self, flow_id: Uuid) -> Self {
         self.flow_id = Some(flow_id);
@@ -241,6 +306,18 @@ impl RefundCmd {
         self
     }
 
+    #[must_use]
+    pub fn currency(mut self, currency: Currency) -> Self {
+        self.currency = Some(currency);
+        self
+    }
+
+    #[must_use]
+    pub fn exchange_rate(mut self, rate: ExchangeRate) -> Self {
+        self.exchange_rate = Some(rate);
+        self
+    }
+
     #[must_use]
     pub fn meta(mut self, meta: TxMeta) -> Self {
         self.meta = meta;
@@ -272,13 +349,146 @@ impl RefundCmd {
     }
 }
 
+/// One line of a split expense.
+#[derive(Clone, Debug)]
+pub struct SplitLine {
+    /// Amount in the expense currency minor units.
+    pub amount_minor: i64,
+    /// Flow the line is charged to (defaults to Unallocated).
+    pub flow_id: Option<Uuid>,
+    /// Optional canonical category id (takes precedence over `category`).
+    pub category_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub note: Option<String>,
+}
+
+impl SplitLine {
+    #[must_use]
+    pub fn new(amount_minor: i64) -> Self {
+        Self {
+            amount_minor,
+            flow_id: None,
+            category_id: None,
+            category: None,
+            note: None,
+        }
+    }
+
+    #[must_use]
+    pub fn flow_id(mut self, flow_id: Uuid) -> Self {
+        self.flow_id = Some(flow_id);
+        self
+    }
+
+    #[must_use]
+    pub fn category_id(mut self, category_id: Uuid) -> Self {
+        self.category_id = Some(category_id);
+        self
+    }
+
+    #[must_use]
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    #[must_use]
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+}
+
+/// Create an expense paid from one wallet and split across categories and
+/// flows.
+///
+/// The lines must sum to `amount_minor`. Categories are set per line, so
+/// `meta.category`/`meta.category_id` must be unset.
+#[derive(Clone, Debug)]
+pub struct SplitExpenseCmd {
+    pub vault_id: String,
+    /// Total amount in `currency` minor units (wallet currency when unset).
+    pub amount_minor: i64,
+    pub wallet_id: Option<Uuid>,
+    /// Currency the amount was paid in (defaults to the wallet currency).
+    pub currency: Option<Currency>,
+    /// Rate from `currency` to the other currency involved, required when the
+    /// amount, wallet and vault currencies differ.
+    pub exchange_rate: Option<ExchangeRate>,
+    pub splits: Vec<SplitLine>,
+    pub meta: TxMeta,
+    pub user_id: String,
+}
+
+impl SplitExpenseCmd {
+    #[must_use]
+    pub fn new(
+        vault_id: impl Into<String>,
+        user_id: impl Into<String>,
+        amount_minor: i64,
+        occurred_at: DateTime<Utc>,
+        splits: Vec<SplitLine>,
+    ) -> Self {
+        Self {
+            vault_id: vault_id.into(),
+            amount_minor,
+            wallet_id: None,
+            currency: None,
+            exchange_rate: None,
+            splits,
+            meta: TxMeta::new(occurred_at),
+            user_id: user_id.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn wallet_id(mut self, wallet_id: Uuid) -> Self {
+        self.wallet_id = Some(wallet_id);
+        self
+    }
+
+    #[must_use]
+    pub fn currency(mut self, currency: Currency) -> Self {
+        self.currency = Some(currency);
+        self
+    }
+
+    #[must_use]
+    pub fn exchange_rate(mut self, rate: ExchangeRate) -> Self {
+        self.exchange_rate = Some(rate);
+        self
+    }
+
+    #[must_use]
+    pub fn meta(mut self, meta: TxMeta) -> Self {
+        self.meta = meta;
+        self
+    }
+
+    #[must_use]
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        self.meta.note = Some(note.into());
+        self
+    }
+
+    #[must_use]
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.meta.idempotency_key = Some(key.into());
+        self
+    }
+}
+
 /// Create a wallet-to-wallet transfer transaction.
 #[derive(Clone, Debug)]
 pub struct TransferWalletCmd {
     pub vault_id: String,
+    /// Amount leaving `from_wallet_id`, in that wallet's currency.
     pub amount_minor: i64,
     pub from_wallet_id: Uuid,
     pub to_wallet_id: Uuid,
+    /// Rate from the source to the destination wallet currency (required when
+    /// they differ).
+    pub exchange_rate: Option<ExchangeRate>,
     pub note: Option<String>,
     pub idempotency_key: Option<String>,
     pub occurred_at: DateTime<Utc>,
@@ -300,6 +510,7 @@ impl TransferWalletCmd {
             amount_minor,
             from_wallet_id,
             to_wallet_id,
+            exchange_rate: None,
             note: None,
             idempotency_key: None,
             occurred_at,
@@ -307,6 +518,12 @@ impl TransferWalletCmd {
         }
     }
 
+    #[must_use]
+    pub fn exchange_rate(mut self, rate: ExchangeRate) -> Self {
+        self.exchange_rate = Some(rate);
+        self
+    }
+
     #[must_use]
     pub fn note(mut self, note: impl Into<String>) -> Self {
         self.note = Some(note.into());
@@ -375,7 +592,12 @@ pub struct UpdateTransactionCmd {
     pub transaction_id: Uuid,
     pub user_id: String,
 
+    /// New amount, expressed in the original currency for transactions that
+    /// were recorded with an exchange rate.
     pub amount_minor: Option<i64>,
+    /// New exchange rate for transactions spanning two currencies. The stored
+    /// rate is kept unless the currency pair changes.
+    pub exchange_rate: Option<ExchangeRate>,
 
     // Income/Expense/Refund retargeting.
     pub wallet_id: Option<Uuid>,
@@ -407,6 +629,7 @@ impl UpdateTransactionCmd {
             transaction_id,
             user_id: user_id.into(),
             amount_minor: None,
+            exchange_rate: None,
             wallet_id: None,
             flow_id: None,
             from_wallet_id: None,
@@ -426,6 +649,12 @@ impl UpdateTransactionCmd {
         self
     }
 
+    #[must_use]
+    pub fn exchange_rate(mut self, rate: ExchangeRate) -> Self {
+        self.exchange_rate = Some(rate);
+        self
+    }
+
     #[must_use]
     pub fn wallet_id(mut self, wallet_id: Uuid) -> Self {
         self.wallet_id = Some(wallet_id);
@@ -487,3 +716,786 @@ impl UpdateTransactionCmd {
         self
     }
 }
+
+/// Create a recurring rule.
+#[derive(Clone, Debug)]
+pub struct CreateRecurringRuleCmd {
+    pub vault_id: String,
+    pub user_id: String,
+    /// One of `Income`, `Expense` or `TransferFlow`.
+    pub kind: TransactionKind,
+    pub amount_minor: i64,
+    pub recurrence: Recurrence,
+
+    // Income/Expense targets (resolved to the defaults when unset).
+    pub wallet_id: Option<Uuid>,
+    pub flow_id: Option<Uuid>,
+
+    // TransferFlow targets.
+    pub from_flow_id: Option<Uuid>,
+    pub to_flow_id: Option<Uuid>,
+
+    pub category: Option<String>,
+    pub note: Option<String>,
+}
+
+impl CreateRecurringRuleCmd {
+    #[must_use]
+    pub fn new(
+        vault_id: impl Into<String>,
+        user_id: impl Into<String>,
+        kind: TransactionKind,
+        amount_minor: i64,
+        recurrence: Recurrence,
+    ) -> Self {
+        Self {
+            vault_id: vault_id.into(),
+            user_id: user_id.into(),
+            kind,
+            amount_minor,
+            recurrence,
+            wallet_id: None,
+            flow_id: None,
+            from_flow_id: None,
+            to_flow_id: None,
+            category: None,
+            note: None,
+        }
+    }
+
+    #[must_use]
+    pub fn wallet_id(mut self, wallet_id: Uuid) -> Self {
+        self.wallet_id = Some(wallet_id);
+        self
+    }
+
+    #[must_use]
+    pub fn flow_id(mut self, flow_id: Uuid) -> Self {
+        self.flow_id = Some(flow_id);
+        self
+    }
+
+    #[must_use]
+    pub fn from_flow_id(mut self, flow_id: Uuid) -> Self {
+        self.from_flow_id = Some(flow_id);
+        self
+    }
+
+    #[must_use]
+    pub fn to_flow_id(mut self, flow_id: Uuid) -> Self {
+        self.to_flow_id = Some(flow_id);
+        self
+    }
+
+    #[must_use]
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    #[must_use]
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+}
+
+/// Update a recurring rule.
+///
+/// The schedule itself is immutable (occurrence indexes feed the idempotency
+/// keys); only its end can be moved. Create a new rule to change cadence.
+#[derive(Clone, Debug)]
+pub struct UpdateRecurringRuleCmd {
+    pub vault_id: String,
+    pub rule_id: Uuid,
+    pub user_id: String,
+
+    pub amount_minor: Option<i64>,
+    pub wallet_id: Option<Uuid>,
+    pub flow_id: Option<Uuid>,
+    pub from_flow_id: Option<Uuid>,
+    pub to_flow_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub note: Option<String>,
+    /// New end of the schedule; `Some(None)` removes it.
+    pub ends_at: Option<Option<DateTime<Utc>>>,
+    pub active: Option<bool>,
+}
+
+impl UpdateRecurringRuleCmd {
+    #[must_use]
+    pub fn new(vault_id: impl Into<String>, rule_id: Uuid, user_id: impl Into<String>) -> Self {
+        Self {
+            vault_id: vault_id.into(),
+            rule_id,
+            user_id: user_id.into(),
+            amount_minor: None,
+            wallet_id: None,
+            flow_id: None,
+            from_flow_id: None,
+            to_flow_id: None,
+            category: None,
+            note: None,
+            ends_at: None,
+            active: None,
+        }
+    }
+
+    #[must_use]
+    pub fn amount_minor(mut self, amount_minor: i64) -> Self {
+        self.amount_minor = Some(amount_minor);
+        self
+    }
+
+    #[must_use]
+    pub fn wallet_id(mut self, wallet_id: Uuid) -> Self {
+        self.wallet_id = Some(wallet_id);
+        self
+    }
+
+    #[must_use]
+    pub fn flow_id(mut self, flow_id: Uuid) -> Self {
+        self.flow_id = Some(flow_id);
+        self
+    }
+
+    #[must_use]
+    pub fn from_flow_id(mut self, flow_id: Uuid) -> Self {
+        self.from_flow_id = Some(flow_id);
+        self
+    }
+
+    #[must_use]
+    pub fn to_flow_id(mut self, flow_id: Uuid) -> Self {
+        self.to_flow_id = Some(flow_id);
+        self
+    }
+
+    #[must_use]
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    #[must_use]
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    #[must_use]
+    pub fn ends_at(mut self, ends_at: DateTime<Utc>) -> Self {
+        self.ends_at = Some(Some(ends_at));
+        self
+    }
+
+    /// Lets the schedule run without an end date.
+    #[must_use]
+    pub fn clear_ends_at(mut self) -> Self {
+        self.ends_at = Some(None);
+        self
+    }
+
+    #[must_use]
+    pub fn active(mut self, active: bool) -> Self {
+        self.active = Some(active);
+        self
+    }
+}
+
+/// Create a budget for a category.
+#[derive(Clone, Debug)]
+pub struct CreateBudgetCmd {
+    pub vault_id: String,
+    pub user_id: String,
+    pub category_id: Uuid,
+    /// Limit per period, in vault currency minor units.
+    pub amount_minor: i64,
+    pub period: BudgetPeriod,
+    /// First day of the first period; later periods keep its weekday/day.
+    pub starts_on: NaiveDate,
+    pub rollover: bool,
+}
+
+impl CreateBudgetCmd {
+    #[must_use]
+    pub fn new(
+        vault_id: impl Into<String>,
+        user_id: impl Into<String>,
+        category_id: Uuid,
+        amount_minor: i64,
+        period: BudgetPeriod,
+        starts_on: NaiveDate,
+    ) -> Self {
+        Self {
+            vault_id: vault_id.into(),
+            user_id: user_id.into(),
+            category_id,
+            amount_minor,
+            period,
+            starts_on,
+            rollover: false,
+        }
+    }
+
+    #[must_use]
+    pub fn rollover(mut self, rollover: bool) -> Self {
+        self.rollover = rollover;
+        self
+    }
+}
+
+/// Update a budget.
+#[derive(Clone, Debug)]
+pub struct UpdateBudgetCmd {
+    pub vault_id: String,
+    pub budget_id: Uuid,
+    pub user_id: String,
+
+    pub amount_minor: Option<i64>,
+    pub period: Option<BudgetPeriod>,
+    pub starts_on: Option<NaiveDate>,
+    pub rollover: Option<bool>,
+}
+
+impl UpdateBudgetCmd {
+    #[must_use]
+    pub fn new(vault_id: impl Into<String>, budget_id: Uuid, user_id: impl Into<String>) -> Self {
+        Self {
+            vault_id: vault_id.into(),
+            budget_id,
+            user_id: user_id.into(),
+            amount_minor: None,
+            period: None,
+            starts_on: None,
+            rollover: None,
+        }
+    }
+
+    #[must_use]
+    pub fn amount_minor(mut self, amount_minor: i64) -> Self {
+        self.amount_minor = Some(amount_minor);
+        self
+    }
+
+    #[must_use]
+    pub fn period(mut self, period: BudgetPeriod) -> Self {
+        self.period = Some(period);
+        self
+    }
+
+    #[must_use]
+    pub fn starts_on(mut self, starts_on: NaiveDate) -> Self {
+        self.starts_on = Some(starts_on);
+        self
+    }
+
+    #[must_use]
+    pub fn rollover(mut self, rollover: bool) -> Self {
+        self.rollover = Some(rollover);
+        self
+    }
+}
+
+/// Create an auto-categorization rule.
+#[derive(Clone, Debug)]
+pub struct CreateCategoryRuleCmd {
+    pub vault_id: String,
+    pub user_id: String,
+    pub category_id: Uuid,
+    /// Lower values are tried first (default: 0).
+    pub priority: i32,
+    pub conditions: CategoryRuleConditions,
+}
+
+impl CreateCategoryRuleCmd {
+    #[must_use]
+    pub fn new(
+        vault_id: impl Into<String>,
+        user_id: impl Into<String>,
+        category_id: Uuid,
+        conditions: CategoryRuleConditions,
+    ) -> Self {
+        Self {
+            vault_id: vault_id.into(),
+            user_id: user_id.into(),
+            category_id,
+            priority: 0,
+            conditions,
+        }
+    }
+
+    #[must_use]
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Update an auto-categorization rule.
+#[derive(Clone, Debug)]
+pub struct UpdateCategoryRuleCmd {
+    pub vault_id: String,
+    pub rule_id: Uuid,
+    pub user_id: String,
+
+    pub category_id: Option<Uuid>,
+    pub priority: Option<i32>,
+    /// Replaces every condition of the rule.
+    pub conditions: Option<CategoryRuleConditions>,
+}
+
+impl UpdateCategoryRuleCmd {
+    #[must_use]
+    pub fn new(vault_id: impl Into<String>, rule_id: Uuid, user_id: impl Into<String>) -> Self {
+        Self {
+            vault_id: vault_id.into(),
+            rule_id,
+            user_id: user_id.into(),
+            category_id: None,
+            priority: None,
+            conditions: None,
+        }
+    }
+
+    #[must_use]
+    pub fn category_id(mut self, category_id: Uuid) -> Self {
+        self.category_id = Some(category_id);
+        self
+    }
+
+    #[must_use]
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    #[must_use]
+    pub fn conditions(mut self, conditions: CategoryRuleConditions) -> Self {
+        self.conditions = Some(conditions);
+        self
+    }
+}
+
+/// Run the categorization rules of a vault against booked transactions.
+#[derive(Clone, Debug)]
+pub struct ApplyCategoryRulesCmd {
+    pub vault_id: String,
+    pub user_id: String,
+    /// Inclusive lower bound on `occurred_at`.
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `occurred_at`.
+    pub to: Option<DateTime<Utc>>,
+    /// Also re-evaluate transactions that already have a category (default:
+    /// only uncategorized ones). Transactions no rule matches keep theirs.
+    pub include_categorized: bool,
+}
+
+impl ApplyCategoryRulesCmd {
+    #[must_use]
+    pub fn new(vault_id: impl Into<String>, user_id: impl Into<String>) -> Self {
+        Self {
+            vault_id: vault_id.into(),
+            user_id: user_id.into(),
+            from: None,
+            to: None,
+            include_categorized: false,
+        }
+    }
+
+    #[must_use]
+    pub fn from(mut self, from: DateTime<Utc>) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    #[must_use]
+    pub fn to(mut self, to: DateTime<Utc>) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    #[must_use]
+    pub fn include_categorized(mut self, include_categorized: bool) -> Self {
+        self.include_categorized = include_categorized;
+        self
+    }
+}
+
+/// Aggregate the booked transactions of a vault.
+#[derive(Clone, Debug)]
+pub struct ReportCmd {
+    pub vault_id: String,
+    pub user_id: String,
+    /// Dimensions of the rows (none: a single total row).
+    pub group_by: Vec<ReportGroupBy>,
+    /// Inclusive lower bound on `occurred_at`.
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `occurred_at`.
+    pub to: Option<DateTime<Utc>>,
+    /// Allow-list of incomes, expenses and refunds (default: all three).
+    pub kinds: Option<Vec<TransactionKind>>,
+    /// Offset the periods are computed in (default: UTC).
+    pub utc_offset: FixedOffset,
+}
+
+impl ReportCmd {
+    #[must_use]
+    pub fn new(
+        vault_id: impl Into<String>,
+        user_id: impl Into<String>,
+        group_by: Vec<ReportGroupBy>,
+    ) -> Self {
+        Self {
+            vault_id: vault_id.into(),
+            user_id: user_id.into(),
+            group_by,
+            from: None,
+            to: None,
+            kinds: None,
+            utc_offset: Utc.fix(),
+        }
+    }
+
+    #[must_use]
+    pub fn from(mut self, from: DateTime<Utc>) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    #[must_use]
+    pub fn to(mut self, to: DateTime<Utc>) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    #[must_use]
+    pub fn kinds(mut self, kinds: Vec<TransactionKind>) -> Self {
+        self.kinds = Some(kinds);
+        self
+    }
+
+    #[must_use]
+    pub fn utc_offset(mut self, utc_offset: FixedOffset) -> Self {
+        self.utc_offset = utc_offset;
+        self
+    }
+}
+
+/// Sample the balance of a wallet, a flow or the whole vault over a range.
+#[derive(Clone, Debug)]
+pub struct BalanceHistoryCmd {
+    pub vault_id: String,
+    pub user_id: String,
+    pub target: BalanceTarget,
+    /// Inclusive start of the range.
+    pub from: DateTime<Utc>,
+    /// Exclusive end of the range.
+    pub to: DateTime<Utc>,
+    pub interval: BalanceInterval,
+    /// Offset the periods are computed in (default: UTC).
+    pub utc_offset: FixedOffset,
+}
+
+impl BalanceHistoryCmd {
+    #[must_use]
+    pub fn new(
+        vault_id: impl Into<String>,
+        user_id: impl Into<String>,
+        target: BalanceTarget,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        interval: BalanceInterval,
+    ) -> Self {
+        Self {
+            vault_id: vault_id.into(),
+            user_id: user_id.into(),
+            target,
+            from,
+            to,
+            interval,
+            utc_offset: Utc.fix(),
+        }
+    }
+
+    #[must_use]
+    pub fn utc_offset(mut self, utc_offset: FixedOffset) -> Self {
+        self.utc_offset = utc_offset;
+        self
+    }
+}
+
+/// Reconcile a wallet against a bank statement.
+#[derive(Clone, Debug)]
+pub struct ReconcileWalletCmd {
+    pub vault_id: String,
+    pub user_id: String,
+    pub wallet_id: Uuid,
+    /// End of the statement: transactions that occurred before it are
+    /// covered.
+    pub statement_date: DateTime<Utc>,
+    /// Closing balance printed on the statement, in wallet currency.
+    pub statement_balance_minor: i64,
+}
+
+impl ReconcileWalletCmd {
+    #[must_use]
+    pub fn new(
+        vault_id: impl Into<String>,
+        user_id: impl Into<String>,
+        wallet_id: Uuid,
+        statement_date: DateTime<Utc>,
+        statement_balance_minor: i64,
+    ) -> Self {
+        Self {
+            vault_id: vault_id.into(),
+            user_id: user_id.into(),
+            wallet_id,
+            statement_date,
+            statement_balance_minor,
+        }
+    }
+}
+
+/// Import a CSV statement into one wallet.
+#[derive(Clone, Debug)]
+pub struct CsvImportCmd {
+    pub vault_id: String,
+    pub user_id: String,
+    /// Wallet the statement belongs to (defaults to the only active wallet).
+    pub wallet_id: Option<Uuid>,
+    /// Flow every row is booked on (defaults to Unallocated).
+    pub flow_id: Option<Uuid>,
+    pub mapping: CsvMapping,
+    pub data: String,
+    /// Also book rows that look already booked (default: skip them).
+    pub include_duplicates: bool,
+}
+
+impl CsvImportCmd {
+    #[must_use]
+    pub fn new(
+        vault_id: impl Into<String>,
+        user_id: impl Into<String>,
+        mapping: CsvMapping,
+        data: impl Into<String>,
+    ) -> Self {
+        Self {
+            vault_id: vault_id.into(),
+            user_id: user_id.into(),
+            wallet_id: None,
+            flow_id: None,
+            mapping,
+            data: data.into(),
+            include_duplicates: false,
+        }
+    }
+
+    #[must_use]
+    pub fn wallet_id(mut self, wallet_id: Uuid) -> Self {
+        self.wallet_id = Some(wallet_id);
+        self
+    }
+
+    #[must_use]
+    pub fn flow_id(mut self, flow_id: Uuid) -> Self {
+        self.flow_id = Some(flow_id);
+        self
+    }
+
+    #[must_use]
+    pub fn include_duplicates(mut self, include_duplicates: bool) -> Self {
+        self.include_duplicates = include_duplicates;
+        self
+    }
+}
+
+/// Import an OFX, QIF or CAMT.053 statement into one wallet.
+#[derive(Clone, Debug)]
+pub struct StatementImportCmd {
+    pub vault_id: String,
+    pub user_id: String,
+    /// Wallet the statement belongs to (defaults to the only active wallet).
+    pub wallet_id: Option<Uuid>,
+    /// Flow every row is booked on (defaults to Unallocated).
+    pub flow_id: Option<Uuid>,
+    pub format: StatementFormat,
+    pub data: String,
+    /// Also book rows that look already booked but carry a new bank
+    /// reference (default: skip them). Rows already imported are always
+    /// skipped.
+    pub include_duplicates: bool,
+}
+
+impl StatementImportCmd {
+    #[must_use]
+    pub fn new(
+        vault_id: impl Into<String>,
+        user_id: impl Into<String>,
+        format: StatementFormat,
+        data: impl Into<String>,
+    ) -> Self {
+        Self {
+            vault_id: vault_id.into(),
+            user_id: user_id.into(),
+            wallet_id: None,
+            flow_id: None,
+            format,
+            data: data.into(),
+            include_duplicates: false,
+        }
+    }
+
+    #[must_use]
+    pub fn wallet_id(mut self, wallet_id: Uuid) -> Self {
+        self.wallet_id = Some(wallet_id);
+        self
+    }
+
+    #[must_use]
+    pub fn flow_id(mut self, flow_id: Uuid) -> Self {
+        self.flow_id = Some(flow_id);
+        self
+    }
+
+    #[must_use]
+    pub fn include_duplicates(mut self, include_duplicates: bool) -> Self {
+        self.include_duplicates = include_duplicates;
+        self
+    }
+}
+
+/// Recreate a vault from a [`VaultExport`](crate::VaultExport).
+#[derive(Clone, Debug)]
+pub struct ImportVaultCmd {
+    pub export: VaultExport,
+    /// Owner of the imported vault (defaults to the exported owner).
+    pub owner: Option<String>,
+    /// Vault name (defaults to the exported name).
+    pub name: Option<String>,
+    /// Assign new UUIDs to every record, e.g. to import a copy of a vault
+    /// that still exists on this instance.
+    pub new_ids: bool,
+}
+
+impl ImportVaultCmd {
+    #[must_use]
+    pub fn new(export: VaultExport) -> Self {
+        Self {
+            export,
+            owner: None,
+            name: None,
+            new_ids: false,
+        }
+    }
+
+    #[must_use]
+    pub fn owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    #[must_use]
+    pub fn new_ids(mut self, new_ids: bool) -> Self {
+        self.new_ids = new_ids;
+        self
+    }
+}
+
+/// Create a personal access token.
+#[derive(Clone, Debug)]
+pub struct CreateAccessTokenCmd {
+    /// Owner of the token.
+    pub user_id: String,
+    pub name: String,
+    pub scope: TokenScope,
+    /// Limit the token to one vault the owner can access.
+    pub vault_id: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl CreateAccessTokenCmd {
+    #[must_use]
+    pub fn new(user_id: impl Into<String>, name: impl Into<String>, scope: TokenScope) -> Self {
+        Self {
+            user_id: user_id.into(),
+            name: name.into(),
+            scope,
+            vault_id: None,
+            expires_at: None,
+        }
+    }
+
+    #[must_use]
+    pub fn vault_id(mut self, vault_id: impl Into<String>) -> Self {
+        self.vault_id = Some(vault_id.into());
+        self
+    }
+
+    #[must_use]
+    pub fn expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+}
+
+/// List the audit log of a vault, newest first.
+#[derive(Clone, Debug)]
+pub struct AuditListCmd {
+    pub vault_id: String,
+    pub user_id: String,
+    /// Only entries recorded for this user.
+    pub actor: Option<String>,
+    /// Only entries about this kind of entity.
+    pub entity: Option<AuditEntity>,
+    /// Only entries about this entity id.
+    pub entity_id: Option<String>,
+    pub limit: u64,
+    /// Cursor returned with the previous page.
+    pub cursor: Option<String>,
+}
+
+impl AuditListCmd {
+    #[must_use]
+    pub fn new(vault_id: impl Into<String>, user_id: impl Into<String>, limit: u64) -> Self {
+        Self {
+            vault_id: vault_id.into(),
+            user_id: user_id.into(),
+            actor: None,
+            entity: None,
+            entity_id: None,
+            limit,
+            cursor: None,
+        }
+    }
+
+    #[must_use]
+    pub fn actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = Some(actor.into());
+        self
+    }
+
+    #[must_use]
+    pub fn entity(mut self, entity: AuditEntity) -> Self {
+        self.entity = Some(entity);
+        self
+    }
+
+    #[must_use]
+    pub fn entity_id(mut self, entity_id: impl Into<String>) -> Self {
+        self.entity_id = Some(entity_id.into());
+        self
+    }
+
+    #[must_use]
+    pub fn cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+}
@@ -0,0 +1,130 @@
+//! Password and access token hashing for user credentials.
+//!
+//! Passwords are stored as Argon2id PHC strings (`$argon2id$v=19$...`) with
+//! the crate default cost parameters and a random salt per hash, so the
+//! stored value carries everything needed to verify it later.
+//!
+//! Access token secrets are 256 random bits, so a plain SHA-256 digest is
+//! enough to store them and lets the server look a token up by its digest.
+//! Telegram pair codes are stored the same way, from 128 random bits.
+
+use argon2::{
+    Argon2,
+    password_hash::{
+        PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+        rand_core::{OsRng, RngCore},
+    },
+};
+use base64::Engine as _;
+use sha2::{Digest, Sha256};
+
+use crate::{EngineError, ResultEngine};
+
+/// Hashes `password` with Argon2id and a fresh random salt.
+///
+/// Empty passwords are rejected.
+pub fn hash_password(password: &str) -> ResultEngine<String> {
+    if password.is_empty() {
+        return Err(EngineError::InvalidPassword(
+            "password must not be empty".to_string(),
+        ));
+    }
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|err| EngineError::InvalidPassword(err.to_string()))
+}
+
+/// Checks `password` against a stored PHC hash.
+///
+/// A malformed stored hash never verifies.
+#[must_use]
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash).is_ok_and(|parsed| {
+        Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok()
+    })
+}
+
+/// Prefix of every access token secret, to make leaked tokens easy to spot.
+const ACCESS_TOKEN_PREFIX: &str = "spg_";
+
+/// Generates a new access token secret.
+pub(crate) fn generate_access_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    format!(
+        "{ACCESS_TOKEN_PREFIX}{}",
+        base64::prelude::BASE64_URL_SAFE_NO_PAD.encode(bytes)
+    )
+}
+
+/// Hex SHA-256 digest of an access token secret, as stored in the database.
+pub(crate) fn hash_access_token(secret: &str) -> String {
+    format!("{:x}", Sha256::digest(secret.as_bytes()))
+}
+
+/// Generates a one-time Telegram pair code. It only uses characters allowed
+/// in a `/start` deep link.
+#[must_use]
+pub fn generate_pair_code() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    base64::prelude::BASE64_URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Hex SHA-256 digest of a pair code, as stored in `users.pair_code`.
+#[must_use]
+pub fn hash_pair_code(code: &str) -> String {
+    hash_access_token(code)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn hashes_are_salted_argon2id_and_verify_exactly() {
+        let first = hash_password("correct horse").unwrap();
+        let second = hash_password("correct horse").unwrap();
+        assert!(first.starts_with("$argon2id$"));
+        assert_ne!(first, second);
+        assert!(verify_password("correct horse", &first));
+        assert!(verify_password("correct horse", &second));
+        assert!(!verify_password("correct", &first));
+        assert!(!verify_password("correct horse ", &first));
+        assert!(!verify_password("correct horse", "correct horse"));
+        assert!(hash_password("").is_err());
+    }
+
+    #[test]
+    fn access_tokens_are_random_and_hashed_deterministically() {
+        let first = generate_access_token();
+        let second = generate_access_token();
+        assert!(first.starts_with(ACCESS_TOKEN_PREFIX));
+        assert_eq!(first.len(), ACCESS_TOKEN_PREFIX.len() + 43);
+        assert_ne!(first, second);
+        assert_eq!(hash_access_token(&first), hash_access_token(&first));
+        assert_ne!(hash_access_token(&first), hash_access_token(&second));
+        assert_eq!(hash_access_token(&first).len(), 64);
+    }
+
+    #[test]
+    fn pair_codes_carry_128_random_bits() {
+        let first = generate_pair_code();
+        let second = generate_pair_code();
+        assert_eq!(first.len(), 22);
+        assert!(
+            first
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        );
+        assert_ne!(first, second);
+        assert_eq!(hash_pair_code(&first), hash_pair_code(&first));
+        assert_ne!(hash_pair_code(&first), first);
+    }
+}
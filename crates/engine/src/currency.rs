@@ -1,12 +1,14 @@
-use sea_orm::{DeriveActiveEnum, EnumIter};
+use sea_orm::{DeriveActiveEnum, EnumIter, Iterable};
 use serde::{Deserialize, Serialize};
 
 use crate::EngineError;
 
-/// ISO-like currency code used by a vault and its money values.
+/// ISO 4217 currency code used by vaults, wallets and money values.
 ///
-/// Today Sparagne is effectively mono-currency (default `EUR`), but the engine
-/// models currency explicitly to keep the data model future-proof.
+/// A vault has a single *vault currency* (default `EUR`) used by its flows and
+/// reports. Wallets may be denominated in any supported currency; transactions
+/// touching a foreign-currency wallet record an exchange rate so the
+/// vault-currency legs stay balanced.
 ///
 /// ## Minor units
 ///
@@ -16,7 +18,8 @@ use crate::EngineError;
 /// - major units (human input/output, e.g. `10.50 EUR`)
 /// - minor units (stored integers, e.g. `1050`)
 ///
-/// Example: EUR has 2 minor units, so `10.50 EUR` ⇄ `1050`.
+/// Example: EUR has 2 minor units, so `10.50 EUR` ⇄ `1050`; JPY has none, so
+/// `1050 JPY` ⇄ `1050`; BHD has 3, so `1.050 BHD` ⇄ `1050`.
 #[derive(
     Clone,
     Copy,
@@ -36,6 +39,54 @@ pub enum Currency {
     #[default]
     #[sea_orm(string_value = "EUR")]
     Eur,
+    #[sea_orm(string_value = "USD")]
+    Usd,
+    #[sea_orm(string_value = "GBP")]
+    Gbp,
+    #[sea_orm(string_value = "CHF")]
+    Chf,
+    #[sea_orm(string_value = "JPY")]
+    Jpy,
+    #[sea_orm(string_value = "CAD")]
+    Cad,
+    #[sea_orm(string_value = "AUD")]
+    Aud,
+    #[sea_orm(string_value = "NZD")]
+    Nzd,
+    #[sea_orm(string_value = "SEK")]
+    Sek,
+    #[sea_orm(string_value = "NOK")]
+    Nok,
+    #[sea_orm(string_value = "DKK")]
+    Dkk,
+    #[sea_orm(string_value = "PLN")]
+    Pln,
+    #[sea_orm(string_value = "CZK")]
+    Czk,
+    #[sea_orm(string_value = "HUF")]
+    Huf,
+    #[sea_orm(string_value = "RON")]
+    Ron,
+    #[sea_orm(string_value = "CNY")]
+    Cny,
+    #[sea_orm(string_value = "INR")]
+    Inr,
+    #[sea_orm(string_value = "KRW")]
+    Krw,
+    #[sea_orm(string_value = "BRL")]
+    Brl,
+    #[sea_orm(string_value = "MXN")]
+    Mxn,
+    #[sea_orm(string_value = "BHD")]
+    Bhd,
+    #[sea_orm(string_value = "KWD")]
+    Kwd,
+    #[sea_orm(string_value = "OMR")]
+    Omr,
+    #[sea_orm(string_value = "JOD")]
+    Jod,
+    #[sea_orm(string_value = "TND")]
+    Tnd,
 }
 
 impl Currency {
@@ -44,16 +95,43 @@ impl Currency {
     pub const fn code(self) -> &'static str {
         match self {
             Currency::Eur => "EUR",
+            Currency::Usd => "USD",
+            Currency::Gbp => "GBP",
+            Currency::Chf => "CHF",
+            Currency::Jpy => "JPY",
+            Currency::Cad => "CAD",
+            Currency::Aud => "AUD",
+            Currency::Nzd => "NZD",
+            Currency::Sek => "SEK",
+            Currency::Nok => "NOK",
+            Currency::Dkk => "DKK",
+            Currency::Pln => "PLN",
+            Currency::Czk => "CZK",
+            Currency::Huf => "HUF",
+            Currency::Ron => "RON",
+            Currency::Cny => "CNY",
+            Currency::Inr => "INR",
+            Currency::Krw => "KRW",
+            Currency::Brl => "BRL",
+            Currency::Mxn => "MXN",
+            Currency::Bhd => "BHD",
+            Currency::Kwd => "KWD",
+            Currency::Omr => "OMR",
+            Currency::Jod => "JOD",
+            Currency::Tnd => "TND",
         }
     }
 
     /// Number of fraction digits used when formatting/parsing amounts.
     ///
-    /// Example: EUR uses 2 fraction digits (cents).
+    /// Follows ISO 4217: EUR uses 2 fraction digits (cents), JPY/KRW use none,
+    /// and BHD/KWD/OMR/JOD/TND use 3.
     #[must_use]
     pub const fn minor_units(self) -> u8 {
         match self {
-            Currency::Eur => 2,
+            Currency::Jpy | Currency::Krw => 0,
+            Currency::Bhd | Currency::Kwd | Currency::Omr | Currency::Jod | Currency::Tnd => 3,
+            _ => 2,
         }
     }
 }
@@ -68,11 +146,9 @@ impl TryFrom<&str> for Currency {
     type Error = EngineError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value.trim().to_ascii_uppercase().as_str() {
-            "EUR" => Ok(Currency::Eur),
-            other => Err(EngineError::CurrencyMismatch(format!(
-                "unsupported currency: {other}"
-            ))),
-        }
+        let code = value.trim().to_ascii_uppercase();
+        Currency::iter()
+            .find(|currency| currency.code() == code)
+            .ok_or_else(|| EngineError::CurrencyMismatch(format!("unsupported currency: {code}")))
     }
 }
@@ -8,16 +8,49 @@
 //!  [`MaxBalanceReached`]: EngineError::MaxBalanceReached
 //!  [`KeyNotFound`]: EngineError::KeyNotFound
 //!  [`CashFlow`]: super::cash_flows::CashFlow
+use std::fmt;
+
 use sea_orm::DbErr;
 use thiserror::Error;
+use uuid::Uuid;
+
+use crate::Currency;
+
+/// The flow and amounts behind [`EngineError::MaxBalanceReached`] and
+/// [`EngineError::InsufficientFunds`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlowLimit {
+    pub flow_id: Uuid,
+    pub flow_name: String,
+    pub currency: Currency,
+    /// The cap, or `0` for [`EngineError::InsufficientFunds`].
+    pub limit_minor: i64,
+    /// Balance (income total, for income-capped flows) the operation would
+    /// have left the flow with.
+    pub attempted_minor: i64,
+}
+
+impl FlowLimit {
+    /// How far past the limit the operation would go.
+    #[must_use]
+    pub fn excess_minor(&self) -> i64 {
+        (self.attempted_minor - self.limit_minor).abs()
+    }
+}
+
+impl fmt::Display for FlowLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.flow_name)
+    }
+}
 
 /// Engine custom errors.
 #[derive(Error, Debug)]
 pub enum EngineError {
     #[error("Max balance reached!")]
-    MaxBalanceReached(String),
+    MaxBalanceReached(FlowLimit),
     #[error("Insufficient funds: {0}")]
-    InsufficientFunds(String),
+    InsufficientFunds(FlowLimit),
     #[error("\"{0}\" key not found!")]
     KeyNotFound(String),
     #[error("\"{0}\" already present!")]
@@ -40,8 +73,23 @@ pub enum EngineError {
     InvalidRole(String),
     #[error("Currency mismatch: {0}")]
     CurrencyMismatch(String),
+    /// Vault export parsing/validation failed.
+    #[error("Invalid export: {0}")]
+    InvalidExport(String),
+    /// Categorization rule validation failed.
+    #[error("Invalid rule: {0}")]
+    InvalidRule(String),
+    /// Password validation or hashing failed.
+    #[error("Invalid password: {0}")]
+    InvalidPassword(String),
     #[error("Forbidden: {0}")]
     Forbidden(String),
+    /// The transaction is reconciled and must be unlocked before changing.
+    #[error("Transaction locked: {0}")]
+    TransactionLocked(String),
+    /// The change touches a period the vault owner has closed.
+    #[error("Books closed: {0}")]
+    BooksClosed(String),
     #[error(transparent)]
     Database(#[from] DbErr),
 }
@@ -60,7 +108,12 @@ impl PartialEq for EngineError {
             (Self::InvalidFlow(a), Self::InvalidFlow(b)) => a == b,
             (Self::InvalidRole(a), Self::InvalidRole(b)) => a == b,
             (Self::CurrencyMismatch(a), Self::CurrencyMismatch(b)) => a == b,
+            (Self::InvalidExport(a), Self::InvalidExport(b)) => a == b,
+            (Self::InvalidRule(a), Self::InvalidRule(b)) => a == b,
+            (Self::InvalidPassword(a), Self::InvalidPassword(b)) => a == b,
             (Self::Forbidden(a), Self::Forbidden(b)) => a == b,
+            (Self::TransactionLocked(a), Self::TransactionLocked(b)) => a == b,
+            (Self::BooksClosed(a), Self::BooksClosed(b)) => a == b,
             (Self::Database(a), Self::Database(b)) => a.to_string() == b.to_string(),
             _ => false,
         }
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Currency, EngineError, ResultEngine};
+
+/// Maximum number of fractional digits accepted in an exchange rate.
+const MAX_RATE_DECIMALS: u32 = 10;
+
+/// Exchange rate between two currencies, stored as an exact decimal.
+///
+/// A rate `r` converts one major unit of the *source* currency into `r` major
+/// units of the *target* currency (e.g. `1 USD = 0.92 EUR` is the rate
+/// `0.92` from USD to EUR).
+///
+/// Rates are kept as `mantissa / 10^scale` to avoid floating-point drift; the
+/// canonical string form (see `Display`) is what gets persisted.
+///
+/// # Examples
+///
+/// ```rust
+/// use engine::{Currency, ExchangeRate};
+///
+/// let rate = ExchangeRate::parse("0,92").unwrap();
+/// assert_eq!(rate.to_string(), "0.92");
+/// // 50.00 USD -> 46.00 EUR
+/// assert_eq!(
+///     rate.convert(50_00, Currency::Usd, Currency::Eur).unwrap(),
+///     46_00
+/// );
+/// // 1000 JPY at 0.0061 -> 6.10 EUR
+/// let rate = ExchangeRate::parse("0.0061").unwrap();
+/// assert_eq!(rate.convert(1000, Currency::Jpy, Currency::Eur).unwrap(), 6_10);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ExchangeRate {
+    mantissa: i64,
+    scale: u32,
+}
+
+impl ExchangeRate {
+    /// Parses a positive decimal rate.
+    ///
+    /// Accepts `.` or `,` as decimal separator and at most 10 fractional
+    /// digits. Trailing zeros are dropped so equal rates compare equal.
+    pub fn parse(input: &str) -> ResultEngine<Self> {
+        let invalid = || EngineError::InvalidAmount("invalid exchange rate".to_string());
+
+        let trimmed = input.trim().replace(',', ".");
+        let mut parts = trimmed.split('.');
+        let major_str = parts.next().ok_or_else(invalid)?;
+        let frac_str = parts.next().unwrap_or_default();
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        if major_str.is_empty()
+            || !major_str.chars().all(|c| c.is_ascii_digit())
+            || !frac_str.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let frac_str = frac_str.trim_end_matches('0');
+        let scale = u32::try_from(frac_str.len()).map_err(|_| invalid())?;
+        if scale > MAX_RATE_DECIMALS {
+            return Err(EngineError::InvalidAmount(
+                "exchange rate has too many decimals".to_string(),
+            ));
+        }
+
+        let mantissa: i64 = format!("{major_str}{frac_str}")
+            .parse()
+            .map_err(|_| invalid())?;
+        if mantissa <= 0 {
+            return Err(EngineError::InvalidAmount(
+                "exchange rate must be > 0".to_string(),
+            ));
+        }
+
+        Ok(Self { mantissa, scale })
+    }
+
+    /// Converts `amount_minor` (in `from` minor units) into `to` minor units.
+    ///
+    /// The result is rounded half away from zero to the target currency's
+    /// minor units.
+    pub fn convert(self, amount_minor: i64, from: Currency, to: Currency) -> ResultEngine<i64> {
+        let overflow = || EngineError::InvalidAmount("converted amount too large".to_string());
+
+        let numerator = i128::from(amount_minor)
+            .checked_mul(i128::from(self.mantissa))
+            .and_then(|v| v.checked_mul(10i128.pow(u32::from(to.minor_units()))))
+            .ok_or_else(overflow)?;
+        let denominator = 10i128.pow(self.scale) * 10i128.pow(u32::from(from.minor_units()));
+
+        let quotient = numerator / denominator;
+        let remainder = numerator % denominator;
+        let rounded = if remainder.abs() * 2 >= denominator {
+            quotient + numerator.signum()
+        } else {
+            quotient
+        };
+
+        i64::try_from(rounded).map_err(|_| overflow())
+    }
+}
+
+impl core::fmt::Display for ExchangeRate {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.scale == 0 {
+            return write!(f, "{}", self.mantissa);
+        }
+        let scale = 10i64.pow(self.scale);
+        write!(
+            f,
+            "{}.{:0width$}",
+            self.mantissa / scale,
+            self.mantissa % scale,
+            width = self.scale as usize
+        )
+    }
+}
+
+impl TryFrom<String> for ExchangeRate {
+    type Error = EngineError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<ExchangeRate> for String {
+    fn from(value: ExchangeRate) -> Self {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn parse_normalizes_representation() {
+        assert_eq!(ExchangeRate::parse("1").unwrap().to_string(), "1");
+        assert_eq!(ExchangeRate::parse("1.0500").unwrap().to_string(), "1.05");
+        assert_eq!(ExchangeRate::parse("0,0061").unwrap().to_string(), "0.0061");
+        assert_eq!(
+            ExchangeRate::parse("1.05").unwrap(),
+            ExchangeRate::parse("1.050").unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_rejects_invalid_rates() {
+        assert!(ExchangeRate::parse("").is_err());
+        assert!(ExchangeRate::parse("0").is_err());
+        assert!(ExchangeRate::parse("0.000").is_err());
+        assert!(ExchangeRate::parse("-1.2").is_err());
+        assert!(ExchangeRate::parse("1.2.3").is_err());
+        assert!(ExchangeRate::parse("abc").is_err());
+        assert!(ExchangeRate::parse("0.00000000001").is_err());
+    }
+
+    #[test]
+    fn convert_respects_minor_units_and_rounds() {
+        let rate = ExchangeRate::parse("1.05").unwrap();
+        assert_eq!(
+            rate.convert(10_000, Currency::Chf, Currency::Eur).unwrap(),
+            10_500
+        );
+        assert_eq!(
+            rate.convert(-10_000, Currency::Chf, Currency::Eur).unwrap(),
+            -10_500
+        );
+
+        // 1.005 EUR rounds half away from zero to 1.01 EUR.
+        let rate = ExchangeRate::parse("0.5").unwrap();
+        assert_eq!(
+            rate.convert(2_01, Currency::Usd, Currency::Eur).unwrap(),
+            1_01
+        );
+        assert_eq!(
+            rate.convert(-2_01, Currency::Usd, Currency::Eur).unwrap(),
+            -1_01
+        );
+
+        // JPY has no minor units, BHD has three.
+        let rate = ExchangeRate::parse("160").unwrap();
+        assert_eq!(
+            rate.convert(10_00, Currency::Eur, Currency::Jpy).unwrap(),
+            1600
+        );
+        let rate = ExchangeRate::parse("0.41").unwrap();
+        assert_eq!(
+            rate.convert(10_00, Currency::Eur, Currency::Bhd).unwrap(),
+            4_100
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Savings goals on cash flows.
+//!
+//! A flow with a `target_minor` is saving towards that balance, optionally by
+//! a `target_date`. [`GoalProgress`] compares the current balance with the
+//! target, spreads what is missing over the months left, and projects when
+//! the goal will be reached if the flow keeps growing at its average net
+//! monthly change over the last [`CONTRIBUTION_WINDOW_MONTHS`] months.
+
+use chrono::{Datelike, Months, NaiveDate};
+use uuid::Uuid;
+
+use crate::{CashFlow, EngineError, ResultEngine};
+
+/// Months of history averaged into the monthly contribution.
+pub const CONTRIBUTION_WINDOW_MONTHS: u32 = 3;
+
+/// Progress of a flow towards its savings goal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GoalProgress {
+    pub flow_id: Uuid,
+    pub target_minor: i64,
+    pub target_date: Option<NaiveDate>,
+    /// Current balance, floored at zero.
+    pub saved_minor: i64,
+    /// `target_minor - saved_minor`, floored at zero.
+    pub remaining_minor: i64,
+    /// Saved share of the target in basis points, up to 10000.
+    pub progress_bps: u32,
+    /// Average net change of the balance per month over the last
+    /// [`CONTRIBUTION_WINDOW_MONTHS`] months.
+    pub monthly_contribution_minor: i64,
+    /// Monthly amount still needed to reach the target by `target_date`: the
+    /// whole remainder once the date is in the current month or past. `None`
+    /// without a target date.
+    pub required_monthly_minor: Option<i64>,
+    /// Date the target is reached at the current monthly contribution; `None`
+    /// once reached, or when the flow is not growing.
+    pub projected_completion: Option<NaiveDate>,
+}
+
+impl GoalProgress {
+    /// Builds the progress of `flow` on `on`, given the net change of its
+    /// balance over the contribution window ending on `on`. Returns `None` for
+    /// flows without a goal.
+    pub(crate) fn compute(
+        flow: &CashFlow,
+        on: NaiveDate,
+        window_change_minor: i64,
+    ) -> Option<Self> {
+        let target_minor = flow.target_minor.filter(|target| *target > 0)?;
+        let saved_minor = flow.balance.clamp(0, target_minor);
+        let remaining_minor = target_minor - saved_minor;
+        let progress_bps =
+            u32::try_from(i128::from(saved_minor) * 10_000 / i128::from(target_minor)).ok()?;
+        let monthly_contribution_minor =
+            window_change_minor / i64::from(CONTRIBUTION_WINDOW_MONTHS);
+
+        let required_monthly_minor = flow.target_date.map(|target_date| {
+            let months = months_until(on, target_date).max(1);
+            // Round up: paying the quotient every month must be enough.
+            div_ceil(remaining_minor, months)
+        });
+
+        let projected_completion = if remaining_minor == 0 || monthly_contribution_minor <= 0 {
+            None
+        } else {
+            let months = div_ceil(remaining_minor, monthly_contribution_minor);
+            u32::try_from(months)
+                .ok()
+                .and_then(|months| on.checked_add_months(Months::new(months)))
+        };
+
+        Some(Self {
+            flow_id: flow.id,
+            target_minor,
+            target_date: flow.target_date,
+            saved_minor,
+            remaining_minor,
+            progress_bps,
+            monthly_contribution_minor,
+            required_monthly_minor,
+            projected_completion,
+        })
+    }
+}
+
+/// `numerator / denominator` rounded up, for a non-negative numerator and a
+/// positive denominator. Unlike `(n + d - 1) / d`, it cannot overflow near
+/// `i64::MAX`.
+fn div_ceil(numerator: i64, denominator: i64) -> i64 {
+    let quotient = numerator
+        .unsigned_abs()
+        .div_ceil(denominator.unsigned_abs());
+    i64::try_from(quotient).unwrap_or(i64::MAX)
+}
+
+/// Monthly installments left from `on` to `target`: the current month
+/// counts, and so does the month of `target` when its day is still ahead.
+fn months_until(on: NaiveDate, target: NaiveDate) -> i64 {
+    let month_index = |date: NaiveDate| i64::from(date.year()) * 12 + i64::from(date.month0());
+    let mut months = month_index(target) - month_index(on);
+    if target.day() > on.day() {
+        months += 1;
+    }
+    months
+}
+
+/// Checks a goal: a positive target, and a date only together with a target.
+pub(crate) fn validate_goal(
+    target_minor: Option<i64>,
+    target_date: Option<NaiveDate>,
+) -> ResultEngine<()> {
+    match (target_minor, target_date) {
+        (Some(target_minor), _) if target_minor <= 0 => Err(EngineError::InvalidFlow(
+            "goal target must be > 0".to_string(),
+        )),
+        (None, Some(_)) => Err(EngineError::InvalidFlow(
+            "goal date requires a target amount".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+    use crate::Currency;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn goal(balance: i64, target_minor: i64, target_date: Option<NaiveDate>) -> CashFlow {
+        let mut flow = CashFlow::new("Holiday".to_string(), 0, None, None, Currency::Eur).unwrap();
+        flow.balance = balance;
+        flow.target_minor = Some(target_minor);
+        flow.target_date = target_date;
+        flow
+    }
+
+    #[test]
+    fn flows_without_a_goal_have_no_progress() {
+        let flow = CashFlow::new("Food".to_string(), 0, None, None, Currency::Eur).unwrap();
+        assert_eq!(GoalProgress::compute(&flow, day(2026, 1, 15), 0), None);
+    }
+
+    #[test]
+    fn progress_spreads_the_remainder_and_projects_from_contributions() {
+        // 1,200.00 saved of 3,000.00 by June 1st; 900.00 added in 3 months.
+        let flow = goal(120_000, 300_000, Some(day(2026, 6, 1)));
+        let progress = GoalProgress::compute(&flow, day(2026, 1, 15), 90_000).unwrap();
+        assert_eq!(progress.saved_minor, 120_000);
+        assert_eq!(progress.remaining_minor, 180_000);
+        assert_eq!(progress.progress_bps, 4000);
+        assert_eq!(progress.monthly_contribution_minor, 30_000);
+        // January to May: 5 installments.
+        assert_eq!(progress.required_monthly_minor, Some(36_000));
+        assert_eq!(progress.projected_completion, Some(day(2026, 7, 15)));
+    }
+
+    #[test]
+    fn reached_overdue_and_shrinking_goals() {
+        let reached = goal(350_000, 300_000, Some(day(2026, 6, 1)));
+        let progress = GoalProgress::compute(&reached, day(2026, 1, 15), 0).unwrap();
+        assert_eq!(progress.progress_bps, 10_000);
+        assert_eq!(progress.remaining_minor, 0);
+        assert_eq!(progress.required_monthly_minor, Some(0));
+        assert_eq!(progress.projected_completion, None);
+
+        let overdue = goal(100_000, 300_000, Some(day(2026, 1, 1)));
+        let progress = GoalProgress::compute(&overdue, day(2026, 1, 15), -3).unwrap();
+        assert_eq!(progress.required_monthly_minor, Some(200_000));
+        assert_eq!(progress.monthly_contribution_minor, -1);
+        assert_eq!(progress.projected_completion, None);
+
+        let undated = goal(-500, 300_000, None);
+        let progress = GoalProgress::compute(&undated, day(2026, 1, 15), 0).unwrap();
+        assert_eq!(progress.saved_minor, 0);
+        assert_eq!(progress.required_monthly_minor, None);
+    }
+
+    #[test]
+    fn huge_targets_do_not_overflow() {
+        let flow = goal(0, i64::MAX, Some(day(2026, 3, 1)));
+        let progress = GoalProgress::compute(&flow, day(2026, 1, 15), 3).unwrap();
+        assert_eq!(progress.remaining_minor, i64::MAX);
+        assert_eq!(progress.required_monthly_minor, Some(i64::MAX / 2 + 1));
+        // Too many months away to be a date.
+        assert_eq!(progress.projected_completion, None);
+    }
+
+    #[test]
+    fn months_count_the_current_and_target_month() {
+        assert_eq!(months_until(day(2026, 1, 15), day(2026, 1, 31)), 1);
+        assert_eq!(months_until(day(2026, 1, 15), day(2026, 2, 15)), 1);
+        assert_eq!(months_until(day(2026, 1, 15), day(2026, 2, 16)), 2);
+        assert_eq!(months_until(day(2026, 1, 15), day(2025, 12, 1)), -1);
+    }
+
+    #[test]
+    fn goals_need_a_positive_target() {
+        assert!(validate_goal(None, None).is_ok());
+        assert!(validate_goal(Some(1), Some(day(2026, 6, 1))).is_ok());
+        assert!(validate_goal(Some(0), None).is_err());
+        assert!(validate_goal(None, Some(day(2026, 6, 1))).is_err());
+    }
+}
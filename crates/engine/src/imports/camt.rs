@@ -0,0 +1,262 @@
+//! ISO 20022 `camt.053` (bank-to-customer statement) documents.
+//!
+//! Elements are matched by local name, so every `camt.053.001.xx` version
+//! is accepted. Each booked `<Ntry>` becomes one draft; pending and
+//! informational entries are skipped.
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use roxmltree::{Document, Node};
+
+use crate::{EngineError, ResultEngine, TxMeta};
+
+use super::{
+    DraftTarget, ImportDraft, ImportRowError, ParsedStatement, StatementFormat,
+    ensure_statement_currency, join_note, parse_amount,
+};
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|child| child.is_element() && child.tag_name().name() == name)
+}
+
+/// Follows a path of child element names.
+fn path<'a, 'input>(node: Node<'a, 'input>, names: &[&str]) -> Option<Node<'a, 'input>> {
+    names.iter().try_fold(node, |node, name| child(node, name))
+}
+
+fn text<'a>(node: Option<Node<'a, '_>>) -> Option<&'a str> {
+    node.and_then(|node| node.text())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+}
+
+fn descendants<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.descendants()
+        .filter(move |node| node.is_element() && node.tag_name().name() == name)
+}
+
+/// Entry status: plain text up to version 8, `<Sts><Cd>` afterwards.
+fn status<'a>(entry: Node<'a, '_>) -> Option<&'a str> {
+    let status = child(entry, "Sts")?;
+    text(child(status, "Cd")).or_else(|| text(Some(status)))
+}
+
+/// Booking (or else value) date, as `<Dt>` or `<DtTm>`.
+fn entry_date(entry: Node<'_, '_>) -> Option<DateTime<Utc>> {
+    let date = child(entry, "BookgDt").or_else(|| child(entry, "ValDt"))?;
+    if let Some(raw) = text(child(date, "DtTm")) {
+        return DateTime::parse_from_rfc3339(raw)
+            .map(|at| at.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                chrono::NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S")
+                    .ok()
+                    .map(|at| at.and_utc())
+            });
+    }
+    let raw = text(child(date, "Dt"))?;
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .ok()
+        .map(|date| date.and_time(NaiveTime::MIN).and_utc())
+}
+
+fn parse_entry(entry: Node<'_, '_>, target: &DraftTarget) -> ResultEngine<ImportDraft> {
+    let missing = |name| EngineError::InvalidAmount(format!("missing {name}"));
+
+    let reference = text(child(entry, "AcctSvcrRef"))
+        .or_else(|| text(child(entry, "NtryRef")))
+        .ok_or_else(|| missing("AcctSvcrRef/NtryRef"))?;
+    let occurred_at = entry_date(entry).ok_or_else(|| missing("booking date"))?;
+
+    let amount = child(entry, "Amt").ok_or_else(|| missing("Amt"))?;
+    if let Some(currency) = amount.attribute("Ccy") {
+        ensure_statement_currency(currency, target)?;
+    }
+    let minor = parse_amount(text(Some(amount)).unwrap_or_default(), '.', target.currency)?
+        .ok_or_else(|| missing("Amt"))?
+        .abs();
+    let credit = match text(child(entry, "CdtDbtInd")) {
+        Some("CRDT") => true,
+        Some("DBIT") => false,
+        _ => return Err(missing("CdtDbtInd")),
+    };
+
+    // Counterparty: the debtor of a credit, the creditor of a debit.
+    let party = if credit { "Dbtr" } else { "Cdtr" };
+    let details = path(entry, &["NtryDtls", "TxDtls"]);
+    let counterparty = details.and_then(|details| {
+        text(path(details, &["RltdPties", party, "Nm"]))
+            .or_else(|| text(path(details, &["RltdPties", party, "Pty", "Nm"])))
+    });
+    let remittance = details.and_then(|details| {
+        let lines: Vec<&str> = descendants(details, "Ustrd")
+            .filter_map(|node| text(Some(node)))
+            .collect();
+        (!lines.is_empty()).then(|| lines.join(" "))
+    });
+
+    let mut meta = TxMeta::new(occurred_at);
+    meta.note = join_note([
+        counterparty,
+        remittance
+            .as_deref()
+            .or_else(|| text(child(entry, "AddtlNtryInf"))),
+    ]);
+    meta.idempotency_key = Some(target.idempotency_key(StatementFormat::Camt053, reference));
+
+    target
+        .draft(if credit { minor } else { -minor }, meta)
+        .ok_or_else(|| EngineError::InvalidAmount("amount is zero".to_string()))
+}
+
+/// Parses a `camt.053` document into drafts keyed by the entry reference.
+///
+/// A document holding statements of more than one account is rejected.
+pub(crate) fn parse_camt053(data: &str, target: &DraftTarget) -> ResultEngine<ParsedStatement> {
+    let doc = Document::parse(data)
+        .map_err(|err| EngineError::InvalidAmount(format!("invalid camt.053 XML: {err}")))?;
+    let Some(root) = descendants(doc.root(), "BkToCstmrStmt").next() else {
+        return Err(EngineError::InvalidAmount(
+            "not a camt.053 document".to_string(),
+        ));
+    };
+
+    let statements: Vec<Node<'_, '_>> = descendants(root, "Stmt").collect();
+    let mut accounts: Vec<&str> = statements
+        .iter()
+        .filter_map(|stmt| {
+            let id = path(*stmt, &["Acct", "Id"])?;
+            text(child(id, "IBAN")).or_else(|| text(path(id, &["Othr", "Id"])))
+        })
+        .collect();
+    accounts.sort_unstable();
+    accounts.dedup();
+    if accounts.len() > 1 {
+        return Err(EngineError::InvalidAmount(format!(
+            "statement covers {} accounts; import them one at a time",
+            accounts.len()
+        )));
+    }
+
+    let mut drafts = Vec::new();
+    let mut errors = Vec::new();
+    for stmt in statements {
+        if let Some(currency) = text(path(stmt, &["Acct", "Ccy"])) {
+            ensure_statement_currency(currency, target)?;
+        }
+        for entry in stmt
+            .children()
+            .filter(|node| node.is_element() && node.tag_name().name() == "Ntry")
+        {
+            if matches!(status(entry), Some("PDNG" | "INFO")) {
+                continue;
+            }
+            let line = u64::from(doc.text_pos_at(entry.range().start).row);
+            match parse_entry(entry, target) {
+                Ok(draft) => drafts.push((line, draft)),
+                Err(err) => errors.push(ImportRowError {
+                    line,
+                    message: err.to_string(),
+                }),
+            }
+        }
+    }
+    Ok((drafts, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use crate::TransactionKind;
+
+    use super::{super::test_target, *};
+
+    const STATEMENT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02">
+  <BkToCstmrStmt>
+    <GrpHdr><MsgId>MSG1</MsgId></GrpHdr>
+    <Stmt>
+      <Id>STMT1</Id>
+      <Acct><Id><IBAN>IT60X0542811101000000123456</IBAN></Id><Ccy>EUR</Ccy></Acct>
+      <Ntry>
+        <Amt Ccy="EUR">45.10</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <Sts>BOOK</Sts>
+        <BookgDt><Dt>2026-02-03</Dt></BookgDt>
+        <AcctSvcrRef>REF-1</AcctSvcrRef>
+        <NtryDtls><TxDtls>
+          <RltdPties><Cdtr><Nm>Supermarket</Nm></Cdtr></RltdPties>
+          <RmtInf><Ustrd>Card 1234</Ustrd></RmtInf>
+        </TxDtls></NtryDtls>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="EUR">2000.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Sts><Cd>BOOK</Cd></Sts>
+        <BookgDt><DtTm>2026-02-04T09:30:00+01:00</DtTm></BookgDt>
+        <NtryRef>N-2</NtryRef>
+        <AddtlNtryInf>Salary</AddtlNtryInf>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="EUR">1.00</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <Sts>PDNG</Sts>
+        <BookgDt><Dt>2026-02-05</Dt></BookgDt>
+        <AcctSvcrRef>REF-3</AcctSvcrRef>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="EUR">1.00</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <Sts>BOOK</Sts>
+        <BookgDt><Dt>2026-02-05</Dt></BookgDt>
+      </Ntry>
+    </Stmt>
+  </BkToCstmrStmt>
+</Document>"#;
+
+    #[test]
+    fn booked_entries() {
+        let target = test_target();
+        let (drafts, errors) = parse_camt053(STATEMENT, &target).unwrap();
+        assert_eq!(drafts.len(), 2);
+
+        let (line, expense) = &drafts[0];
+        assert_eq!(*line, 8);
+        assert_eq!(expense.kind(), TransactionKind::Expense);
+        assert_eq!(expense.amount_minor(), 4510);
+        assert_eq!(
+            expense.meta().note.as_deref(),
+            Some("Supermarket - Card 1234")
+        );
+        assert_eq!(
+            expense.meta().idempotency_key,
+            Some(format!("camt:{}:REF-1", target.wallet_id))
+        );
+
+        let income = &drafts[1].1;
+        assert_eq!(income.signed_amount_minor(), 200_000);
+        assert_eq!(
+            income.meta().occurred_at.to_rfc3339(),
+            "2026-02-04T08:30:00+00:00"
+        );
+        assert_eq!(income.meta().note.as_deref(), Some("Salary"));
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("AcctSvcrRef"));
+    }
+
+    #[test]
+    fn rejects_foreign_currency_and_other_documents() {
+        let usd = STATEMENT.replace("<Ccy>EUR</Ccy>", "<Ccy>USD</Ccy>");
+        assert!(matches!(
+            parse_camt053(&usd, &test_target()),
+            Err(EngineError::CurrencyMismatch(_))
+        ));
+        assert!(parse_camt053("<Document/>", &test_target()).is_err());
+        assert!(parse_camt053("not xml", &test_target()).is_err());
+    }
+}
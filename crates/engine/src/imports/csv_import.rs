@@ -0,0 +1,360 @@
+//! CSV statements with a user-defined column mapping.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{EngineError, ResultEngine, TxMeta};
+
+use super::{DraftTarget, ImportDraft, ImportRowError, ParsedStatement, parse_amount};
+
+/// A CSV column, by 0-based position or by header name.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CsvColumn {
+    Index(usize),
+    Name(String),
+}
+
+/// Where the amount of a row is read from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum CsvAmount {
+    /// One signed column: positive values are incomes, negative expenses.
+    Signed { column: CsvColumn },
+    /// Separate debit (expense) and credit (income) columns; the unused one is
+    /// left empty.
+    DebitCredit { debit: CsvColumn, credit: CsvColumn },
+}
+
+/// Mapping profile describing the layout of a bank CSV export.
+///
+/// # Examples
+///
+/// ```rust
+/// use engine::{CsvAmount, CsvColumn, CsvMapping};
+///
+/// let mapping: CsvMapping = serde_json::from_str(
+///     r#"{
+///         "delimiter": ";",
+///         "date_column": "Data",
+///         "date_format": "%d/%m/%Y",
+///         "amount": { "mode": "signed", "column": "Importo" },
+///         "decimal_separator": ",",
+///         "note_column": 2
+///     }"#,
+/// )
+/// .unwrap();
+/// assert_eq!(mapping.note_column, Some(CsvColumn::Index(2)));
+/// assert!(mapping.has_headers);
+/// assert!(matches!(mapping.amount, CsvAmount::Signed { .. }));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CsvMapping {
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    /// Whether the first record is a header row (required to map columns by
+    /// name).
+    #[serde(default = "default_has_headers")]
+    pub has_headers: bool,
+    pub date_column: CsvColumn,
+    /// `chrono` format string, e.g. `%d/%m/%Y` or `%Y-%m-%d %H:%M`.
+    pub date_format: String,
+    pub amount: CsvAmount,
+    /// `.` or `,`; the other one is treated as thousands separator.
+    #[serde(default = "default_decimal_separator")]
+    pub decimal_separator: char,
+    #[serde(default)]
+    pub note_column: Option<CsvColumn>,
+    #[serde(default)]
+    pub category_column: Option<CsvColumn>,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+fn default_has_headers() -> bool {
+    true
+}
+
+fn default_decimal_separator() -> char {
+    '.'
+}
+
+impl CsvMapping {
+    fn validate(&self) -> ResultEngine<()> {
+        if !self.delimiter.is_ascii() {
+            return Err(EngineError::InvalidAmount(
+                "delimiter must be an ASCII character".to_string(),
+            ));
+        }
+        if self.decimal_separator != '.' && self.decimal_separator != ',' {
+            return Err(EngineError::InvalidAmount(
+                "decimal_separator must be '.' or ','".to_string(),
+            ));
+        }
+        if self.date_format.trim().is_empty() {
+            return Err(EngineError::InvalidAmount(
+                "date_format must not be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Column positions resolved against the header row.
+struct Columns {
+    date: usize,
+    amount: ResolvedAmount,
+    note: Option<usize>,
+    category: Option<usize>,
+}
+
+enum ResolvedAmount {
+    Signed(usize),
+    DebitCredit { debit: usize, credit: usize },
+}
+
+fn resolve_column(
+    column: &CsvColumn,
+    headers: Option<&::csv::StringRecord>,
+) -> ResultEngine<usize> {
+    match column {
+        CsvColumn::Index(index) => Ok(*index),
+        CsvColumn::Name(name) => {
+            let headers = headers.ok_or_else(|| {
+                EngineError::InvalidAmount(format!(
+                    "column '{name}' is mapped by name but the file has no header row"
+                ))
+            })?;
+            headers
+                .iter()
+                .position(|header| header.trim().eq_ignore_ascii_case(name.trim()))
+                .ok_or_else(|| EngineError::InvalidAmount(format!("column '{name}' not found")))
+        }
+    }
+}
+
+fn resolve_columns(
+    mapping: &CsvMapping,
+    headers: Option<&::csv::StringRecord>,
+) -> ResultEngine<Columns> {
+    let optional = |column: &Option<CsvColumn>| {
+        column
+            .as_ref()
+            .map(|column| resolve_column(column, headers))
+            .transpose()
+    };
+    Ok(Columns {
+        date: resolve_column(&mapping.date_column, headers)?,
+        amount: match &mapping.amount {
+            CsvAmount::Signed { column } => {
+                ResolvedAmount::Signed(resolve_column(column, headers)?)
+            }
+            CsvAmount::DebitCredit { debit, credit } => ResolvedAmount::DebitCredit {
+                debit: resolve_column(debit, headers)?,
+                credit: resolve_column(credit, headers)?,
+            },
+        },
+        note: optional(&mapping.note_column)?,
+        category: optional(&mapping.category_column)?,
+    })
+}
+
+/// Parses a date (or date and time) in UTC.
+fn parse_date(raw: &str, format: &str) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    NaiveDateTime::parse_from_str(raw, format)
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(raw, format)
+                .ok()
+                .map(|date| date.and_time(NaiveTime::MIN))
+        })
+        .map(|at| at.and_utc())
+}
+
+fn cell(record: &::csv::StringRecord, index: usize) -> ResultEngine<&str> {
+    record
+        .get(index)
+        .ok_or_else(|| EngineError::InvalidAmount(format!("missing column {index}")))
+}
+
+fn parse_record(
+    record: &::csv::StringRecord,
+    columns: &Columns,
+    mapping: &CsvMapping,
+    target: &DraftTarget,
+) -> ResultEngine<ImportDraft> {
+    let raw_date = cell(record, columns.date)?;
+    let occurred_at = parse_date(raw_date, &mapping.date_format).ok_or_else(|| {
+        EngineError::InvalidAmount(format!(
+            "invalid date '{raw_date}' (expected {})",
+            mapping.date_format
+        ))
+    })?;
+
+    let parse = |index| {
+        cell(record, index)
+            .and_then(|raw| parse_amount(raw, mapping.decimal_separator, target.currency))
+    };
+    let signed_minor = match columns.amount {
+        ResolvedAmount::Signed(index) => parse(index)?.unwrap_or(0),
+        ResolvedAmount::DebitCredit { debit, credit } => {
+            match (parse(debit)?.map(i64::abs), parse(credit)?.map(i64::abs)) {
+                (Some(debit), Some(credit)) if debit != 0 && credit != 0 => {
+                    return Err(EngineError::InvalidAmount(
+                        "both debit and credit are set".to_string(),
+                    ));
+                }
+                (debit, credit) => credit.unwrap_or(0) - debit.unwrap_or(0),
+            }
+        }
+    };
+
+    let mut meta = TxMeta::new(occurred_at);
+    if let Some(index) = columns.note {
+        meta.note = Some(cell(record, index)?.trim().to_string()).filter(|s| !s.is_empty());
+    }
+    if let Some(index) = columns.category {
+        meta.category = Some(cell(record, index)?.trim().to_string()).filter(|s| !s.is_empty());
+    }
+
+    target
+        .draft(signed_minor, meta)
+        .ok_or_else(|| EngineError::InvalidAmount("amount is zero".to_string()))
+}
+
+/// Parses a CSV statement into drafts. Rows that cannot be parsed are
+/// reported by line; a broken mapping fails the whole file.
+pub(crate) fn parse_csv(
+    data: &str,
+    mapping: &CsvMapping,
+    target: &DraftTarget,
+) -> ResultEngine<ParsedStatement> {
+    mapping.validate()?;
+    let mut reader = ::csv::ReaderBuilder::new()
+        .delimiter(mapping.delimiter as u8)
+        .has_headers(mapping.has_headers)
+        .flexible(true)
+        .from_reader(data.as_bytes());
+    let headers = if mapping.has_headers {
+        Some(
+            reader
+                .headers()
+                .map_err(|err| EngineError::InvalidAmount(format!("invalid CSV header: {err}")))?
+                .clone(),
+        )
+    } else {
+        None
+    };
+    let columns = resolve_columns(mapping, headers.as_ref())?;
+
+    let mut drafts = Vec::new();
+    let mut errors = Vec::new();
+    for result in reader.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(err) => {
+                let line = err.position().map_or(0, ::csv::Position::line);
+                errors.push(ImportRowError {
+                    line,
+                    message: err.to_string(),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map_or(0, ::csv::Position::line);
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        match parse_record(&record, &columns, mapping, target) {
+            Ok(draft) => drafts.push((line, draft)),
+            Err(err) => errors.push(ImportRowError {
+                line,
+                message: err.to_string(),
+            }),
+        }
+    }
+    Ok((drafts, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::{super::test_target as target, *};
+
+    #[test]
+    fn signed_column_with_headers() {
+        let mapping = CsvMapping {
+            delimiter: ';',
+            has_headers: true,
+            date_column: CsvColumn::Name("Data".to_string()),
+            date_format: "%d/%m/%Y".to_string(),
+            amount: CsvAmount::Signed {
+                column: CsvColumn::Name("Importo".to_string()),
+            },
+            decimal_separator: ',',
+            note_column: Some(CsvColumn::Name("Descrizione".to_string())),
+            category_column: None,
+        };
+        let data = "Data;Descrizione;Importo\n\
+                    03/02/2026;Supermarket;-45,10\n\
+                    04/02/2026;Salary;2.000,00\n\
+                    05/02/2026;Broken;abc\n";
+        let (drafts, errors) = parse_csv(data, &mapping, &target()).unwrap();
+        assert_eq!(drafts.len(), 2);
+        assert_eq!(drafts[0].0, 2);
+        assert!(matches!(drafts[0].1, ImportDraft::Expense(_)));
+        assert_eq!(drafts[0].1.amount_minor(), 4510);
+        assert_eq!(drafts[0].1.meta().note.as_deref(), Some("Supermarket"));
+        assert!(matches!(drafts[1].1, ImportDraft::Income(_)));
+        assert_eq!(drafts[1].1.amount_minor(), 200_000);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 4);
+    }
+
+    #[test]
+    fn debit_credit_columns_by_index() {
+        let mapping = CsvMapping {
+            delimiter: ',',
+            has_headers: false,
+            date_column: CsvColumn::Index(0),
+            date_format: "%Y-%m-%d".to_string(),
+            amount: CsvAmount::DebitCredit {
+                debit: CsvColumn::Index(1),
+                credit: CsvColumn::Index(2),
+            },
+            decimal_separator: '.',
+            note_column: None,
+            category_column: Some(CsvColumn::Index(3)),
+        };
+        let data = "2026-02-01,12.50,,food\n2026-02-02,,100,\n2026-02-03,1,1,x\n";
+        let (drafts, errors) = parse_csv(data, &mapping, &target()).unwrap();
+        assert_eq!(drafts.len(), 2);
+        assert_eq!(drafts[0].1.signed_amount_minor(), -1250);
+        assert_eq!(drafts[0].1.meta().category.as_deref(), Some("food"));
+        assert_eq!(drafts[1].1.signed_amount_minor(), 10_000);
+        assert_eq!(drafts[1].1.meta().category, None);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn unknown_header_fails_the_mapping() {
+        let mapping = CsvMapping {
+            delimiter: ',',
+            has_headers: true,
+            date_column: CsvColumn::Name("When".to_string()),
+            date_format: "%Y-%m-%d".to_string(),
+            amount: CsvAmount::Signed {
+                column: CsvColumn::Index(1),
+            },
+            decimal_separator: '.',
+            note_column: None,
+            category_column: None,
+        };
+        let err = parse_csv("Date,Amount\n", &mapping, &target()).unwrap_err();
+        assert!(matches!(err, EngineError::InvalidAmount(_)));
+    }
+}
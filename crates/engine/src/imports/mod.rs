@@ -0,0 +1,338 @@
+//! Bank statement import.
+//!
+//! Parsers turn a statement into [`ImportDraft`]s (plain [`IncomeCmd`] /
+//! [`ExpenseCmd`] values targeting one wallet). The engine previews drafts
+//! against the ledger to flag rows already booked, then commits them in a
+//! single DB transaction.
+//!
+//! Structured formats (OFX, QIF, CAMT.053) also carry a per-row bank
+//! reference, stored as the transaction idempotency key: rows whose key is
+//! already booked in the vault are never booked again.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    Currency, EngineError, ExpenseCmd, IncomeCmd, Money, ResultEngine, TransactionKind, TxMeta,
+};
+
+pub use csv_import::{CsvAmount, CsvColumn, CsvMapping};
+
+mod camt;
+mod csv_import;
+mod ofx;
+mod qif;
+
+pub(crate) use csv_import::parse_csv;
+
+/// Structured statement formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementFormat {
+    /// OFX 1.x (SGML) or 2.x (XML); FITID is the row reference.
+    Ofx,
+    /// Quicken Interchange Format (bank, cash and credit card accounts).
+    Qif,
+    /// ISO 20022 `camt.053` bank-to-customer statement; the entry
+    /// `AcctSvcrRef` (or `NtryRef`) is the row reference.
+    Camt053,
+}
+
+impl StatementFormat {
+    /// Prefix of the idempotency keys of imported rows.
+    fn key_prefix(self) -> &'static str {
+        match self {
+            Self::Ofx => "ofx",
+            Self::Qif => "qif",
+            Self::Camt053 => "camt",
+        }
+    }
+}
+
+/// Parses a structured statement.
+pub(crate) fn parse_statement(
+    format: StatementFormat,
+    data: &str,
+    target: &DraftTarget,
+) -> ResultEngine<ParsedStatement> {
+    match format {
+        StatementFormat::Ofx => ofx::parse_ofx(data, target),
+        StatementFormat::Qif => qif::parse_qif(data, target),
+        StatementFormat::Camt053 => camt::parse_camt053(data, target),
+    }
+}
+
+/// A statement row ready to be booked.
+#[derive(Clone, Debug)]
+pub enum ImportDraft {
+    Income(IncomeCmd),
+    Expense(ExpenseCmd),
+}
+
+impl ImportDraft {
+    #[must_use]
+    pub fn kind(&self) -> TransactionKind {
+        match self {
+            Self::Income(_) => TransactionKind::Income,
+            Self::Expense(_) => TransactionKind::Expense,
+        }
+    }
+
+    /// Amount in wallet currency minor units (always positive).
+    #[must_use]
+    pub fn amount_minor(&self) -> i64 {
+        match self {
+            Self::Income(cmd) => cmd.amount_minor,
+            Self::Expense(cmd) => cmd.amount_minor,
+        }
+    }
+
+    /// Amount as seen by the wallet: positive for incomes, negative for
+    /// expenses.
+    #[must_use]
+    pub fn signed_amount_minor(&self) -> i64 {
+        match self {
+            Self::Income(cmd) => cmd.amount_minor,
+            Self::Expense(cmd) => -cmd.amount_minor,
+        }
+    }
+
+    #[must_use]
+    pub fn meta(&self) -> &TxMeta {
+        match self {
+            Self::Income(cmd) => &cmd.meta,
+            Self::Expense(cmd) => &cmd.meta,
+        }
+    }
+
+    pub(crate) fn meta_mut(&mut self) -> &mut TxMeta {
+        match self {
+            Self::Income(cmd) => &mut cmd.meta,
+            Self::Expense(cmd) => &mut cmd.meta,
+        }
+    }
+
+    pub(crate) fn date(&self) -> NaiveDate {
+        self.meta().occurred_at.date_naive()
+    }
+}
+
+/// Drafts with their 1-based source line, plus the rows that failed to parse.
+pub(crate) type ParsedStatement = (Vec<(u64, ImportDraft)>, Vec<ImportRowError>);
+
+/// Wallet (and optional flow) every draft of an import is booked on.
+#[derive(Clone, Debug)]
+pub(crate) struct DraftTarget {
+    pub(crate) vault_id: String,
+    pub(crate) user_id: String,
+    pub(crate) wallet_id: Uuid,
+    pub(crate) flow_id: Option<Uuid>,
+    /// Wallet currency, used to scale statement amounts.
+    pub(crate) currency: Currency,
+}
+
+impl DraftTarget {
+    /// Idempotency key of a statement row: bank references are only unique
+    /// per account, so keys are scoped to the target wallet.
+    pub(crate) fn idempotency_key(&self, format: StatementFormat, reference: &str) -> String {
+        format!(
+            "{}:{}:{}",
+            format.key_prefix(),
+            self.wallet_id,
+            reference.trim()
+        )
+    }
+
+    /// Builds an income (positive amount) or expense (negative amount) draft.
+    pub(crate) fn draft(&self, signed_minor: i64, meta: TxMeta) -> Option<ImportDraft> {
+        let amount_minor = signed_minor.checked_abs()?;
+        if amount_minor == 0 {
+            return None;
+        }
+        Some(if signed_minor > 0 {
+            ImportDraft::Income(IncomeCmd {
+                vault_id: self.vault_id.clone(),
+                amount_minor,
+                flow_id: self.flow_id,
+                wallet_id: Some(self.wallet_id),
+                currency: None,
+                exchange_rate: None,
+                meta,
+                user_id: self.user_id.clone(),
+            })
+        } else {
+            ImportDraft::Expense(ExpenseCmd {
+                vault_id: self.vault_id.clone(),
+                amount_minor,
+                flow_id: self.flow_id,
+                wallet_id: Some(self.wallet_id),
+                currency: None,
+                exchange_rate: None,
+                meta,
+                user_id: self.user_id.clone(),
+            })
+        })
+    }
+}
+
+/// A parsed statement row.
+#[derive(Clone, Debug)]
+pub struct ImportRow {
+    /// 1-based line in the source file.
+    pub line: u64,
+    pub draft: ImportDraft,
+    /// Existing transaction this row duplicates: the one booked with the same
+    /// bank reference or, failing that, with the same amount, day and note in
+    /// the same wallet.
+    pub duplicate_of: Option<Uuid>,
+    /// Whether `duplicate_of` was booked from the same bank reference. Such
+    /// rows are always skipped.
+    pub already_imported: bool,
+}
+
+/// A statement row that could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportRowError {
+    pub line: u64,
+    pub message: String,
+}
+
+/// Dry-run result of an import.
+#[derive(Clone, Debug)]
+pub struct ImportPreview {
+    /// Currency of the target wallet (the unit of every draft amount).
+    pub currency: Currency,
+    pub rows: Vec<ImportRow>,
+    pub errors: Vec<ImportRowError>,
+}
+
+/// Outcome of a committed import.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub created: Vec<Uuid>,
+    pub skipped_duplicates: usize,
+}
+
+/// Rejects statements denominated in another currency than the wallet.
+fn ensure_statement_currency(code: &str, target: &DraftTarget) -> ResultEngine<()> {
+    match Currency::try_from(code.trim()) {
+        Ok(currency) if currency == target.currency => Ok(()),
+        _ => Err(EngineError::CurrencyMismatch(format!(
+            "statement currency is {}, wallet currency is {}",
+            code.trim(),
+            target.currency.code()
+        ))),
+    }
+}
+
+/// Joins the non-empty descriptive fields of a row (e.g. payee and memo) into
+/// a note.
+fn join_note<'a>(parts: impl IntoIterator<Item = Option<&'a str>>) -> Option<String> {
+    let mut note = String::new();
+    for part in parts.into_iter().flatten() {
+        let part = part.trim();
+        if part.is_empty() || note.contains(part) {
+            continue;
+        }
+        if !note.is_empty() {
+            note.push_str(" - ");
+        }
+        note.push_str(part);
+    }
+    (!note.is_empty()).then_some(note)
+}
+
+/// Parses a statement amount into signed minor units; `None` for an empty
+/// cell.
+///
+/// Accepts thousands separators, currency symbols, `(12.34)` and trailing
+/// `-` negatives.
+fn parse_amount(
+    raw: &str,
+    decimal_separator: char,
+    currency: Currency,
+) -> ResultEngine<Option<i64>> {
+    let mut value = raw.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let mut negative = false;
+    if let Some(inner) = value.strip_prefix('(').and_then(|v| v.strip_suffix(')')) {
+        negative = true;
+        value = inner;
+    }
+    if let Some(inner) = value.strip_suffix('-') {
+        negative = !negative;
+        value = inner;
+    }
+
+    let thousands = if decimal_separator == ',' { '.' } else { ',' };
+    let mut cleaned = String::with_capacity(value.len());
+    for ch in value.chars() {
+        if ch.is_ascii_digit() || ch == '-' || ch == '+' {
+            cleaned.push(ch);
+        } else if ch == decimal_separator {
+            cleaned.push('.');
+        } else if ch == thousands || ch.is_whitespace() || ch == '\'' {
+            continue;
+        } else if !ch.is_alphabetic() && !matches!(ch, '€' | '$' | '£' | '¥') {
+            return Err(EngineError::InvalidAmount(format!(
+                "invalid amount '{raw}'"
+            )));
+        }
+    }
+    if cleaned.is_empty() {
+        return Err(EngineError::InvalidAmount(format!(
+            "invalid amount '{raw}'"
+        )));
+    }
+
+    let minor = Money::parse_major(&cleaned, currency)
+        .map_err(|err| EngineError::InvalidAmount(format!("invalid amount '{raw}': {err}")))?
+        .minor();
+    Ok(Some(if negative { -minor } else { minor }))
+}
+
+#[cfg(test)]
+fn test_target() -> DraftTarget {
+    DraftTarget {
+        vault_id: Uuid::new_v4().to_string(),
+        user_id: "alice".to_string(),
+        wallet_id: Uuid::new_v4(),
+        flow_id: None,
+        currency: Currency::Eur,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn amounts_accept_bank_formats() {
+        let eur = Currency::Eur;
+        assert_eq!(parse_amount("1.234,56", ',', eur).unwrap(), Some(123_456));
+        assert_eq!(parse_amount("-1,234.56", '.', eur).unwrap(), Some(-123_456));
+        assert_eq!(parse_amount("(12.00)", '.', eur).unwrap(), Some(-1200));
+        assert_eq!(parse_amount("12,00-", ',', eur).unwrap(), Some(-1200));
+        assert_eq!(parse_amount("€ 3,5", ',', eur).unwrap(), Some(350));
+        assert_eq!(parse_amount("  ", ',', eur).unwrap(), None);
+        assert!(parse_amount("12#00", '.', eur).is_err());
+    }
+
+    #[test]
+    fn notes_join_distinct_parts() {
+        assert_eq!(
+            join_note([Some("ACME"), None, Some(" Card 1234 ")]).as_deref(),
+            Some("ACME - Card 1234")
+        );
+        assert_eq!(
+            join_note([Some("ACME"), Some("ACME")]).as_deref(),
+            Some("ACME")
+        );
+        assert_eq!(join_note([Some(" "), None]), None);
+    }
+}
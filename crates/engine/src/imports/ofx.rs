@@ -0,0 +1,231 @@
+//! OFX statements, both the SGML (1.x) and the XML (2.x) flavour.
+//!
+//! Only `<STMTTRN>` aggregates are read. Tags are matched case-insensitively
+//! and elements may be left unclosed, as SGML allows.
+
+use std::ops::Range;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+
+use crate::{EngineError, ResultEngine, TxMeta};
+
+use super::{
+    DraftTarget, ImportDraft, ImportRowError, ParsedStatement, StatementFormat,
+    ensure_statement_currency, join_note, parse_amount,
+};
+
+/// An OFX document with an uppercased copy for tag lookups (ASCII
+/// uppercasing keeps byte offsets aligned).
+struct Document<'a> {
+    data: &'a str,
+    upper: String,
+}
+
+impl Document<'_> {
+    /// Text of the first `<tag>` element within `range`, up to the next tag.
+    fn element(&self, range: Range<usize>, tag: &str) -> Option<String> {
+        let open = format!("<{tag}>");
+        let start = self.upper[range.clone()].find(&open)? + range.start + open.len();
+        let end = self.upper[start..range.end]
+            .find('<')
+            .map_or(range.end, |offset| start + offset);
+        let text = decode_entities(self.data[start..end].trim());
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// Byte ranges of the `<STMTTRN>` aggregates.
+    fn transactions(&self) -> Vec<Range<usize>> {
+        const OPEN: &str = "<STMTTRN>";
+        const CLOSE: &str = "</STMTTRN>";
+        let mut ranges = Vec::new();
+        let mut from = 0;
+        while let Some(offset) = self.upper[from..].find(OPEN) {
+            let start = from + offset;
+            let body = start + OPEN.len();
+            let end = [CLOSE, OPEN]
+                .iter()
+                .filter_map(|tag| self.upper[body..].find(tag))
+                .min()
+                .map_or(self.upper.len(), |offset| body + offset);
+            ranges.push(start..end);
+            from = end;
+        }
+        ranges
+    }
+
+    /// 1-based line of a byte offset.
+    fn line_at(&self, offset: usize) -> u64 {
+        self.data[..offset].matches('\n').count() as u64 + 1
+    }
+}
+
+fn decode_entities(raw: &str) -> String {
+    raw.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Parses an OFX date (`YYYYMMDD[HHMMSS[.XXX]][[offset:TZ]]`). The time zone
+/// suffix is ignored, like CSV dates.
+fn parse_ofx_date(raw: &str) -> Option<DateTime<Utc>> {
+    let digits: String = raw.chars().take_while(char::is_ascii_digit).collect();
+    let date = NaiveDate::parse_from_str(digits.get(..8)?, "%Y%m%d").ok()?;
+    let time = digits
+        .get(8..14)
+        .and_then(|time| NaiveTime::parse_from_str(time, "%H%M%S").ok())
+        .unwrap_or(NaiveTime::MIN);
+    Some(date.and_time(time).and_utc())
+}
+
+fn parse_transaction(
+    doc: &Document<'_>,
+    range: Range<usize>,
+    target: &DraftTarget,
+) -> ResultEngine<ImportDraft> {
+    let field = |tag| doc.element(range.clone(), tag);
+    let missing = |tag| EngineError::InvalidAmount(format!("missing {tag}"));
+
+    let fitid = field("FITID").ok_or_else(|| missing("FITID"))?;
+    let raw_date = field("DTPOSTED").ok_or_else(|| missing("DTPOSTED"))?;
+    let occurred_at = parse_ofx_date(&raw_date)
+        .ok_or_else(|| EngineError::InvalidAmount(format!("invalid date '{raw_date}'")))?;
+    let raw_amount = field("TRNAMT").ok_or_else(|| missing("TRNAMT"))?;
+    // Some European banks write decimal commas.
+    let decimal_separator = if raw_amount.contains(',') && !raw_amount.contains('.') {
+        ','
+    } else {
+        '.'
+    };
+    let signed_minor = parse_amount(&raw_amount, decimal_separator, target.currency)?.unwrap_or(0);
+
+    let mut meta = TxMeta::new(occurred_at);
+    meta.note = join_note([field("NAME").as_deref(), field("MEMO").as_deref()]);
+    meta.idempotency_key = Some(target.idempotency_key(StatementFormat::Ofx, &fitid));
+
+    target
+        .draft(signed_minor, meta)
+        .ok_or_else(|| EngineError::InvalidAmount("amount is zero".to_string()))
+}
+
+/// Parses an OFX statement into drafts keyed by FITID.
+pub(crate) fn parse_ofx(data: &str, target: &DraftTarget) -> ResultEngine<ParsedStatement> {
+    let doc = Document {
+        data,
+        upper: data.to_ascii_uppercase(),
+    };
+    if !doc.upper.contains("<OFX>") {
+        return Err(EngineError::InvalidAmount(
+            "not an OFX document".to_string(),
+        ));
+    }
+    if let Some(currency) = doc.element(0..data.len(), "CURDEF") {
+        ensure_statement_currency(&currency, target)?;
+    }
+
+    let mut drafts = Vec::new();
+    let mut errors = Vec::new();
+    for range in doc.transactions() {
+        let line = doc.line_at(range.start);
+        match parse_transaction(&doc, range, target) {
+            Ok(draft) => drafts.push((line, draft)),
+            Err(err) => errors.push(ImportRowError {
+                line,
+                message: err.to_string(),
+            }),
+        }
+    }
+    Ok((drafts, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use crate::TransactionKind;
+
+    use super::{super::test_target, *};
+
+    const SGML: &str = "OFXHEADER:100\n\
+DATA:OFXSGML\n\
+\n\
+<OFX>\n\
+<BANKMSGSRSV1><STMTTRNRS><STMTRS>\n\
+<CURDEF>EUR\n\
+<BANKTRANLIST>\n\
+<STMTTRN>\n\
+<TRNTYPE>DEBIT\n\
+<DTPOSTED>20260203120000.000[-5:EST]\n\
+<TRNAMT>-45.10\n\
+<FITID>2026020301\n\
+<NAME>Supermarket &amp; Co\n\
+<MEMO>Card 1234\n\
+</STMTTRN>\n\
+<STMTTRN>\n\
+<TRNTYPE>CREDIT\n\
+<DTPOSTED>20260204\n\
+<TRNAMT>2000,00\n\
+<FITID>2026020402\n\
+<NAME>Salary\n\
+</STMTTRN>\n\
+<STMTTRN>\n\
+<DTPOSTED>20260205\n\
+<TRNAMT>-1.00\n\
+</STMTTRN>\n\
+</BANKTRANLIST>\n\
+</STMTRS></STMTTRNRS></BANKMSGSRSV1>\n\
+</OFX>\n";
+
+    #[test]
+    fn sgml_statement() {
+        let target = test_target();
+        let (drafts, errors) = parse_ofx(SGML, &target).unwrap();
+        assert_eq!(drafts.len(), 2);
+
+        let (line, expense) = &drafts[0];
+        assert_eq!(*line, 8);
+        assert_eq!(expense.kind(), TransactionKind::Expense);
+        assert_eq!(expense.amount_minor(), 4510);
+        assert_eq!(
+            expense.meta().note.as_deref(),
+            Some("Supermarket & Co - Card 1234")
+        );
+        assert_eq!(
+            expense.meta().occurred_at.to_rfc3339(),
+            "2026-02-03T12:00:00+00:00"
+        );
+        assert_eq!(
+            expense.meta().idempotency_key,
+            Some(format!("ofx:{}:2026020301", target.wallet_id))
+        );
+        assert_eq!(drafts[1].1.signed_amount_minor(), 200_000);
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 23);
+        assert!(errors[0].message.contains("missing FITID"));
+    }
+
+    #[test]
+    fn xml_statement_and_currency_check() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<?OFX OFXHEADER="200" VERSION="220"?>
+<OFX><BANKMSGSRSV1><STMTTRNRS><STMTRS><CURDEF>EUR</CURDEF><BANKTRANLIST>
+<STMTTRN><TRNTYPE>DEBIT</TRNTYPE><DTPOSTED>20260210</DTPOSTED><TRNAMT>-3.50</TRNAMT><FITID>A1</FITID><NAME>Coffee</NAME></STMTTRN>
+</BANKTRANLIST></STMTRS></STMTTRNRS></BANKMSGSRSV1></OFX>"#;
+        let (drafts, errors) = parse_ofx(xml, &test_target()).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts[0].0, 4);
+        assert_eq!(drafts[0].1.signed_amount_minor(), -350);
+        assert_eq!(drafts[0].1.meta().note.as_deref(), Some("Coffee"));
+
+        let usd = xml.replace("<CURDEF>EUR", "<CURDEF>USD");
+        assert!(matches!(
+            parse_ofx(&usd, &test_target()),
+            Err(EngineError::CurrencyMismatch(_))
+        ));
+        assert!(parse_ofx("Date,Amount\n", &test_target()).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+//! QIF (Quicken Interchange Format) statements.
+//!
+//! Only bank-like account sections (`!Type:Bank`, `Cash`, `CCard`, `Oth A`,
+//! `Oth L`) are read; split lines are ignored and the record total is booked.
+//! Dates are month first, as written by Quicken (`01/31/2026`,
+//! `1/31'26`), or ISO `2026-01-31`.
+//!
+//! QIF has no transaction ids, so the row reference is derived from date,
+//! amount, check number and payee, plus an occurrence counter for identical
+//! records within the same file.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+
+use crate::{EngineError, ResultEngine, TxMeta};
+
+use super::{
+    DraftTarget, ImportDraft, ImportRowError, ParsedStatement, StatementFormat, join_note,
+    parse_amount,
+};
+
+const ACCOUNT_TYPES: [&str; 5] = ["bank", "cash", "ccard", "oth a", "oth l"];
+
+/// Fields of one `^`-terminated record.
+#[derive(Default)]
+struct Record {
+    line: u64,
+    date: Option<String>,
+    amount: Option<String>,
+    payee: Option<String>,
+    memo: Option<String>,
+    number: Option<String>,
+    category: Option<String>,
+}
+
+fn parse_qif_date(raw: &str) -> Option<DateTime<Utc>> {
+    let cleaned: String = raw
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .map(|ch| if ch == '\'' || ch == '-' { '/' } else { ch })
+        .collect();
+    let mut parts = cleaned.split('/').map(str::parse::<u32>);
+    let (a, b, c) = (
+        parts.next()?.ok()?,
+        parts.next()?.ok()?,
+        parts.next()?.ok()?,
+    );
+    if parts.next().is_some() {
+        return None;
+    }
+    let date = if a > 999 {
+        NaiveDate::from_ymd_opt(i32::try_from(a).ok()?, b, c)?
+    } else {
+        let year = if c < 100 { c + 2000 } else { c };
+        NaiveDate::from_ymd_opt(i32::try_from(year).ok()?, a, b)?
+    };
+    Some(date.and_time(NaiveTime::MIN).and_utc())
+}
+
+/// Category of an `L` field; `[Account]` transfers carry none.
+fn category(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    if raw.starts_with('[') {
+        return None;
+    }
+    // `Category/Class`: the class is not a category.
+    let name = raw.split('/').next().unwrap_or_default().trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn parse_record(
+    record: &Record,
+    target: &DraftTarget,
+    seen: &mut HashMap<String, u32>,
+) -> ResultEngine<ImportDraft> {
+    let raw_date = record
+        .date
+        .as_deref()
+        .ok_or_else(|| EngineError::InvalidAmount("missing date".to_string()))?;
+    let occurred_at = parse_qif_date(raw_date)
+        .ok_or_else(|| EngineError::InvalidAmount(format!("invalid date '{raw_date}'")))?;
+    let raw_amount = record
+        .amount
+        .as_deref()
+        .ok_or_else(|| EngineError::InvalidAmount("missing amount".to_string()))?;
+    let signed_minor = parse_amount(raw_amount, '.', target.currency)?.unwrap_or(0);
+
+    let payee = record.payee.as_deref().map(str::trim).unwrap_or_default();
+    let reference = format!(
+        "{}:{signed_minor}:{}:{}",
+        occurred_at.format("%Y%m%d"),
+        record.number.as_deref().map(str::trim).unwrap_or_default(),
+        payee.to_lowercase()
+    );
+    let occurrence = seen.entry(reference.clone()).or_default();
+    *occurrence += 1;
+
+    let mut meta = TxMeta::new(occurred_at);
+    meta.note = join_note([record.payee.as_deref(), record.memo.as_deref()]);
+    meta.category = record.category.as_deref().and_then(category);
+    meta.idempotency_key =
+        Some(target.idempotency_key(StatementFormat::Qif, &format!("{reference}#{occurrence}")));
+
+    target
+        .draft(signed_minor, meta)
+        .ok_or_else(|| EngineError::InvalidAmount("amount is zero".to_string()))
+}
+
+/// Parses a QIF statement into drafts.
+pub(crate) fn parse_qif(data: &str, target: &DraftTarget) -> ResultEngine<ParsedStatement> {
+    let mut drafts = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen = HashMap::new();
+    let mut in_account = false;
+    let mut saw_header = false;
+    let mut record: Option<Record> = None;
+
+    for (index, raw_line) in data.lines().enumerate() {
+        let line = index as u64 + 1;
+        let raw_line = raw_line.trim_end();
+        if raw_line.trim().is_empty() {
+            continue;
+        }
+        if let Some(header) = raw_line.strip_prefix('!') {
+            saw_header = true;
+            in_account = header
+                .strip_prefix("Type:")
+                .or_else(|| header.strip_prefix("type:"))
+                .is_some_and(|kind| ACCOUNT_TYPES.contains(&kind.trim().to_lowercase().as_str()));
+            record = None;
+            continue;
+        }
+        if !in_account {
+            continue;
+        }
+        let (code, value) = raw_line.split_at(raw_line.chars().next().map_or(0, char::len_utf8));
+        if code == "^" {
+            if let Some(record) = record.take() {
+                match parse_record(&record, target, &mut seen) {
+                    Ok(draft) => drafts.push((record.line, draft)),
+                    Err(err) => errors.push(ImportRowError {
+                        line: record.line,
+                        message: err.to_string(),
+                    }),
+                }
+            }
+            continue;
+        }
+
+        let record = record.get_or_insert_with(|| Record {
+            line,
+            ..Record::default()
+        });
+        let value = Some(value.trim().to_string());
+        match code {
+            "D" => record.date = value,
+            // `T` is the total; older files only carry `U`.
+            "T" => record.amount = value,
+            "U" if record.amount.is_none() => record.amount = value,
+            "P" => record.payee = value,
+            "M" => record.memo = value,
+            "N" => record.number = value,
+            "L" => record.category = value,
+            _ => {}
+        }
+    }
+
+    if !saw_header {
+        return Err(EngineError::InvalidAmount(
+            "not a QIF document (missing !Type header)".to_string(),
+        ));
+    }
+    Ok((drafts, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use crate::TransactionKind;
+
+    use super::{super::test_target, *};
+
+    #[test]
+    fn dates_accept_quicken_variants() {
+        let day = |raw| parse_qif_date(raw).map(|at| at.date_naive().to_string());
+        assert_eq!(day("01/31/2026").as_deref(), Some("2026-01-31"));
+        assert_eq!(day(" 1/31'26").as_deref(), Some("2026-01-31"));
+        assert_eq!(day("2026-01-31").as_deref(), Some("2026-01-31"));
+        assert_eq!(day("31/01/2026"), None);
+    }
+
+    #[test]
+    fn bank_section_records() {
+        let data = "!Type:Cat\n\
+NGroceries\n\
+^\n\
+!Type:Bank\n\
+D02/03/2026\n\
+T-45.10\n\
+PSupermarket\n\
+MWeekly shopping\n\
+LFood:Groceries/Home\n\
+^\n\
+D02/03/2026\n\
+T-45.10\n\
+PSupermarket\n\
+^\n\
+D02/04/2026\n\
+U2,000.00\n\
+PSalary\n\
+L[Savings]\n\
+^\n\
+D02/05/2026\n\
+Tabc\n\
+^\n";
+        let target = test_target();
+        let (drafts, errors) = parse_qif(data, &target).unwrap();
+        assert_eq!(drafts.len(), 3);
+
+        let (line, expense) = &drafts[0];
+        assert_eq!(*line, 5);
+        assert_eq!(expense.kind(), TransactionKind::Expense);
+        assert_eq!(expense.amount_minor(), 4510);
+        assert_eq!(
+            expense.meta().note.as_deref(),
+            Some("Supermarket - Weekly shopping")
+        );
+        assert_eq!(expense.meta().category.as_deref(), Some("Food:Groceries"));
+
+        // Identical records get distinct keys.
+        let key = |index: usize| drafts[index].1.meta().idempotency_key.clone().unwrap();
+        assert_eq!(
+            key(0),
+            format!("qif:{}:20260203:-4510::supermarket#1", target.wallet_id)
+        );
+        assert!(key(1).ends_with("#2"));
+
+        assert_eq!(drafts[2].1.signed_amount_minor(), 200_000);
+        assert_eq!(drafts[2].1.meta().category, None);
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 20);
+        assert!(parse_qif("Date,Amount\n", &target).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Ledger integrity checks.
+//!
+//! Wallet and flow balances are denormalized from the legs. An
+//! [`IntegrityReport`] lists every way a vault's stored state disagrees with
+//! its ledger: balances that drifted from the legs, legs without a
+//! transaction or on a wallet/flow that no longer exists, amounts in the wrong
+//! currency, and flow caps or non-negativity broken at some point of history
+//! (replayed against the current caps, like
+//! [`Engine::recompute_balances`](crate::Engine::recompute_balances)).
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{Currency, FlowLimit, LegTarget};
+
+/// One inconsistency found by [`Engine::verify_vault`](crate::Engine::verify_vault).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IntegrityIssue {
+    /// The stored wallet balance differs from the sum of its legs.
+    WalletBalanceMismatch {
+        wallet_id: Uuid,
+        stored_minor: i64,
+        ledger_minor: i64,
+    },
+    /// The stored flow balance differs from the sum of its legs.
+    FlowBalanceMismatch {
+        flow_id: Uuid,
+        stored_minor: i64,
+        ledger_minor: i64,
+    },
+    /// The stored income total of an income-capped flow differs from the
+    /// sum of its positive legs.
+    FlowIncomeMismatch {
+        flow_id: Uuid,
+        stored_minor: i64,
+        ledger_minor: i64,
+    },
+    /// A leg on a wallet or flow of the vault whose transaction does not
+    /// exist.
+    OrphanLeg { leg_id: Uuid, transaction_id: Uuid },
+    /// A leg on a wallet or flow that does not exist in the vault.
+    MissingTarget {
+        leg_id: Uuid,
+        transaction_id: Uuid,
+        target: LegTarget,
+    },
+    /// A leg in another currency than its wallet (or than the vault, for
+    /// flow legs).
+    LegCurrencyMismatch {
+        leg_id: Uuid,
+        transaction_id: Uuid,
+        expected: Currency,
+        found: Currency,
+    },
+    /// A flow in another currency than its vault.
+    FlowCurrencyMismatch {
+        flow_id: Uuid,
+        expected: Currency,
+        found: Currency,
+    },
+    /// A transaction pushed a flow past its cap.
+    CapExceeded {
+        transaction_id: Uuid,
+        occurred_at: DateTime<Utc>,
+        limit: FlowLimit,
+    },
+    /// A transaction left a flow (other than Unallocated) negative.
+    NegativeBalance {
+        transaction_id: Uuid,
+        occurred_at: DateTime<Utc>,
+        limit: FlowLimit,
+    },
+}
+
+impl fmt::Display for IntegrityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WalletBalanceMismatch {
+                wallet_id,
+                stored_minor,
+                ledger_minor,
+            } => write!(
+                f,
+                "wallet {wallet_id}: stored balance {stored_minor}, ledger {ledger_minor}"
+            ),
+            Self::FlowBalanceMismatch {
+                flow_id,
+                stored_minor,
+                ledger_minor,
+            } => write!(
+                f,
+                "flow {flow_id}: stored balance {stored_minor}, ledger {ledger_minor}"
+            ),
+            Self::FlowIncomeMismatch {
+                flow_id,
+                stored_minor,
+                ledger_minor,
+            } => write!(
+                f,
+                "flow {flow_id}: stored income total {stored_minor}, ledger {ledger_minor}"
+            ),
+            Self::OrphanLeg {
+                leg_id,
+                transaction_id,
+            } => write!(f, "leg {leg_id}: transaction {transaction_id} not found"),
+            Self::MissingTarget {
+                leg_id,
+                transaction_id,
+                target,
+            } => match target {
+                LegTarget::Wallet { wallet_id } => write!(
+                    f,
+                    "leg {leg_id} of transaction {transaction_id}: wallet {wallet_id} not found"
+                ),
+                LegTarget::Flow { flow_id } => write!(
+                    f,
+                    "leg {leg_id} of transaction {transaction_id}: flow {flow_id} not found"
+                ),
+            },
+            Self::LegCurrencyMismatch {
+                leg_id,
+                transaction_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "leg {leg_id} of transaction {transaction_id}: currency {}, expected {}",
+                found.code(),
+                expected.code()
+            ),
+            Self::FlowCurrencyMismatch {
+                flow_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "flow {flow_id}: currency {}, expected {}",
+                found.code(),
+                expected.code()
+            ),
+            Self::CapExceeded {
+                transaction_id,
+                occurred_at,
+                limit,
+            } => write!(
+                f,
+                "transaction {transaction_id} ({}): flow '{limit}' over its cap of {} by {}",
+                occurred_at.to_rfc3339(),
+                limit.limit_minor,
+                limit.excess_minor()
+            ),
+            Self::NegativeBalance {
+                transaction_id,
+                occurred_at,
+                limit,
+            } => write!(
+                f,
+                "transaction {transaction_id} ({}): flow '{limit}' negative by {}",
+                occurred_at.to_rfc3339(),
+                limit.excess_minor()
+            ),
+        }
+    }
+}
+
+/// Outcome of [`Engine::verify_vault`](crate::Engine::verify_vault).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    pub wallets_checked: usize,
+    pub flows_checked: usize,
+    pub legs_checked: usize,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    /// Whether no issue was found.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
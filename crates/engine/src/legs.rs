@@ -8,6 +8,9 @@
 //! - negative values decrease the target balance
 //!
 //! In the engine, *every* change to balances happens via legs.
+//!
+//! The flow legs of a split expense are its split lines: each one carries its
+//! own category and note.
 
 use sea_orm::{ActiveValue, entity::prelude::*};
 use serde::{Deserialize, Serialize};
@@ -64,9 +67,17 @@ pub struct Leg {
     pub amount_minor: i64,
     pub currency: Currency,
     pub attributed_user_id: Option<String>,
+    /// Category of a split line (flow legs of split expenses only).
+    #[serde(default)]
+    pub category_id: Option<Uuid>,
+    /// Note of a split line.
+    #[serde(default)]
+    pub note: Option<String>,
 }
 
 impl Leg {
+    /// Leg ids are time-ordered, so legs sorted by id keep their creation
+    /// order (e.g. the lines of a split expense).
     pub fn new(
         transaction_id: Uuid,
         target: LegTarget,
@@ -74,12 +85,14 @@ impl Leg {
         currency: Currency,
     ) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: Uuid::now_v7(),
             transaction_id,
             target,
             amount_minor,
             currency,
             attributed_user_id: None,
+            category_id: None,
+            note: None,
         }
     }
 
@@ -98,7 +111,7 @@ impl Leg {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "legs")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -109,6 +122,8 @@ pub struct Model {
     pub amount_minor: i64,
     pub currency: Currency,
     pub attributed_user_id: Option<String>,
+    pub category_id: Option<Uuid>,
+    pub note: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -141,6 +156,8 @@ impl From<&Leg> for ActiveModel {
             amount_minor: ActiveValue::Set(leg.amount_minor),
             currency: ActiveValue::Set(leg.currency),
             attributed_user_id: ActiveValue::Set(leg.attributed_user_id.clone()),
+            category_id: ActiveValue::Set(leg.category_id),
+            note: ActiveValue::Set(leg.note.clone()),
         }
     }
 }
@@ -165,6 +182,8 @@ impl TryFrom<Model> for Leg {
             amount_minor: model.amount_minor,
             currency: model.currency,
             attributed_user_id: model.attributed_user_id,
+            category_id: model.category_id,
+            note: model.note,
         })
     }
 }
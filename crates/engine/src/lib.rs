@@ -3,21 +3,53 @@
 //! This crate exposes the domain model, commands, and the main [`Engine`]
 //! facade used by the server and bots.
 
+/// Personal access tokens and their scopes.
+pub use access_tokens::{AccessToken, TokenScope};
+/// Audit log of vault mutations.
+pub use audit::{AuditAction, AuditEntity, AuditEntry};
+/// Versioned vault export format.
+pub use backup::{
+    BudgetRecord, CategoryAliasRecord, CategoryRecord, CategoryRuleRecord, FlowMemberRecord,
+    FlowModeRecord, FlowRecord, LegRecord, MemberRecord, RecurringRuleRecord, TransactionRecord,
+    VAULT_EXPORT_VERSION, VaultExport, VaultImportReport, VaultRecord, WalletRecord,
+};
+/// Point-in-time balances and balance series.
+pub use balances::{Balance, BalanceHistory, BalanceInterval, BalancePoint, BalanceTarget};
+/// Category budgets and their period status.
+pub use budgets::{Budget, BudgetPeriod, BudgetStatus};
 /// Cash flow aggregate model.
 pub use cash_flows::CashFlow;
 /// Category entries for transactions.
 pub use categories::Category;
 /// Category aliases for normalized lookups.
 pub use category_aliases::CategoryAlias;
+/// Auto-categorization rules.
+pub use category_rules::{CategoryRule, CategoryRuleConditions, CategoryRuleHit, CategoryRuleSkip};
 /// Command inputs for transaction operations.
 pub use commands::{
-    ExpenseCmd, IncomeCmd, RefundCmd, TransferFlowCmd, TransferWalletCmd, TxMeta,
-    UpdateTransactionCmd,
+    ApplyCategoryRulesCmd, AuditListCmd, BalanceHistoryCmd, CreateAccessTokenCmd, CreateBudgetCmd,
+    CreateCategoryRuleCmd, CreateRecurringRuleCmd, CsvImportCmd, ExpenseCmd, ImportVaultCmd,
+    IncomeCmd, ReconcileWalletCmd, RefundCmd, ReportCmd, SplitExpenseCmd, SplitLine,
+    StatementImportCmd, TransferFlowCmd, TransferWalletCmd, TxMeta, UpdateBudgetCmd,
+    UpdateCategoryRuleCmd, UpdateRecurringRuleCmd, UpdateTransactionCmd,
 };
+/// Argon2id password hashing and pair codes for user credentials.
+pub use credentials::{generate_pair_code, hash_pair_code, hash_password, verify_password};
 /// Currency codes and helpers.
 pub use currency::Currency;
 /// Engine error type.
-pub use error::EngineError;
+pub use error::{EngineError, FlowLimit};
+/// Exact decimal exchange rates.
+pub use exchange_rate::ExchangeRate;
+/// Savings goals on cash flows.
+pub use goals::{CONTRIBUTION_WINDOW_MONTHS, GoalProgress};
+/// Statement import: formats, mapping profiles, drafts and previews.
+pub use imports::{
+    CsvAmount, CsvColumn, CsvMapping, ImportDraft, ImportPreview, ImportReport, ImportRow,
+    ImportRowError, StatementFormat,
+};
+/// Ledger integrity checks.
+pub use integrity::{IntegrityIssue, IntegrityReport};
 /// Transaction leg primitives.
 pub use legs::{Leg, LegTarget};
 /// Money parsing and formatting helper.
@@ -26,23 +58,48 @@ pub use money::Money;
 pub use ops::{CategoryMergeConflict, CategoryMergeConflictKind, CategoryMergePreview};
 /// Engine facade, builder, and transaction listing filters.
 pub use ops::{Engine, EngineBuilder, TransactionListFilter};
+/// Recurring rule outcomes reported by the scheduler.
+pub use ops::{RecurringFailure, RecurringRunReport};
+/// Wallet reconciliation against bank statements.
+pub use reconciliation::Reconciliation;
+/// Recurring rules and their schedules.
+pub use recurring_rules::{Recurrence, RecurrenceFrequency, RecurringRule};
+/// Aggregated reports.
+pub use reports::{Report, ReportGroupBy, ReportRow};
+/// Transaction edit history.
+pub use transaction_revisions::TransactionRevision;
 /// Transaction models and kinds.
-pub use transactions::{Transaction, TransactionKind, TransactionNew};
+pub use transactions::{ClearingStatus, Transaction, TransactionKind, TransactionNew};
 /// Vault aggregate model.
 pub use vault::Vault;
 /// Wallet aggregate model.
 pub use wallets::Wallet;
 
+mod access_tokens;
+mod audit;
+mod backup;
+mod balances;
+mod budgets;
 mod cash_flows;
 mod categories;
 mod category_aliases;
+mod category_rules;
 mod commands;
+mod credentials;
 mod currency;
 mod error;
+mod exchange_rate;
 mod flow_memberships;
+mod goals;
+mod imports;
+mod integrity;
 mod legs;
 mod money;
 mod ops;
+mod reconciliation;
+mod recurring_rules;
+mod reports;
+mod transaction_revisions;
 mod transactions;
 mod users;
 mod util;
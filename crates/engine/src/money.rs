@@ -20,8 +20,8 @@ use crate::{Currency, EngineError};
 /// assert_eq!(amount.format(Currency::Eur), "12.34 EUR");
 /// ```
 ///
-/// Parsing from user input (accepts `.` or `,` as decimal separator; rejects >
-/// 2 decimals):
+/// Parsing from user input (accepts `.` or `,` as decimal separator; rejects
+/// more decimals than the currency's minor units):
 ///
 /// ```rust
 /// use engine::{Currency, Money};
@@ -35,6 +35,11 @@ use crate::{Currency, EngineError};
 ///     1050
 /// );
 /// assert!(Money::parse_major("12.345", Currency::Eur).is_err());
+/// assert_eq!(
+///     Money::parse_major("12.345", Currency::Bhd).unwrap().minor(),
+///     12345
+/// );
+/// assert!(Money::parse_major("1.5", Currency::Jpy).is_err());
 /// ```
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
@@ -55,7 +60,8 @@ impl Money {
 
     /// Formats the amount according to `currency.minor_units()`.
     ///
-    /// Output format: `<sign><major>.<minor> <CODE>`, e.g. `-12.34 EUR`.
+    /// Output format: `<sign><major>.<minor> <CODE>`, e.g. `-12.34 EUR`, or
+    /// `<sign><major> <CODE>` for currencies without minor units (`1200 JPY`).
     #[must_use]
     pub fn format(self, currency: Currency) -> String {
         let sign = if self.0 < 0 { "-" } else { "" };
@@ -206,4 +212,30 @@ mod tests {
         assert!(Money::parse_major("12.345", Currency::Eur).is_err());
         assert!(Money::parse_major("0.001", Currency::Eur).is_err());
     }
+
+    #[test]
+    fn format_handles_zero_and_three_minor_units() {
+        assert_eq!(Money::new(1200).format(Currency::Jpy), "1200 JPY");
+        assert_eq!(Money::new(-5).format(Currency::Jpy), "-5 JPY");
+        assert_eq!(Money::new(1050).format(Currency::Bhd), "1.050 BHD");
+        assert_eq!(Money::new(-1).format(Currency::Bhd), "-0.001 BHD");
+    }
+
+    #[test]
+    fn parse_uses_currency_minor_units() {
+        assert_eq!(
+            Money::parse_major("1200", Currency::Jpy).unwrap().minor(),
+            1200
+        );
+        assert!(Money::parse_major("12.5", Currency::Jpy).is_err());
+        assert_eq!(
+            Money::parse_major("1,05", Currency::Bhd).unwrap().minor(),
+            1050
+        );
+        assert_eq!(
+            Money::parse_major("0.001", Currency::Kwd).unwrap().minor(),
+            1
+        );
+        assert!(Money::parse_major("0.0001", Currency::Bhd).is_err());
+    }
 }
@@ -2,8 +2,8 @@ use sea_orm::{DatabaseTransaction, QueryFilter, prelude::*, sea_query::Expr};
 use uuid::Uuid;
 
 use crate::{
-    EngineError, ResultEngine, cash_flows, flow_memberships, users, util::normalize_required_name,
-    vault, vault_memberships, wallets,
+    Currency, EngineError, ResultEngine, cash_flows, flow_memberships, users,
+    util::normalize_required_name, vault, vault_memberships, wallets,
 };
 
 use super::{Engine, parse_vault_uuid};
@@ -335,6 +335,22 @@ impl Engine {
         Ok(model.id)
     }
 
+    /// Currency a wallet of the vault is denominated in.
+    pub(super) async fn wallet_currency(
+        &self,
+        db: &DatabaseTransaction,
+        vault_id: &str,
+        wallet_id: Uuid,
+    ) -> ResultEngine<Currency> {
+        let vault_uuid = parse_vault_uuid(vault_id)?;
+        let model = wallets::Entity::find_by_id(wallet_id)
+            .filter(wallets::Column::VaultId.eq(vault_uuid))
+            .one(db)
+            .await?
+            .ok_or_else(|| EngineError::KeyNotFound("wallet not exists".to_string()))?;
+        Ok(model.currency)
+    }
+
     pub(super) async fn resolve_flow_id(
         &self,
         db: &DatabaseTransaction,
@@ -0,0 +1,167 @@
+use chrono::Utc;
+use sea_orm::{DatabaseTransaction, QueryFilter, QueryOrder, QuerySelect, prelude::*};
+use uuid::Uuid;
+
+use crate::{
+    AccessToken, AuditEntity, CreateAccessTokenCmd, EngineError, ResultEngine, TokenScope,
+    access_tokens,
+    audit::AuditChange,
+    credentials::{generate_access_token, hash_access_token},
+    util::normalize_required_name,
+    vault_memberships,
+};
+
+use super::{
+    Engine,
+    audit::{record_audit, snapshot},
+    parse_vault_uuid,
+};
+
+/// Records `change` in the audit log of every vault `token` can act on: the
+/// vault it is limited to, or else each vault its owner is a member of.
+async fn record_token_audit(
+    db_tx: &DatabaseTransaction,
+    token: &AccessToken,
+    change: impl Fn() -> AuditChange,
+) -> ResultEngine<()> {
+    let vault_ids: Vec<Uuid> = match &token.vault_id {
+        Some(vault_id) => vec![parse_vault_uuid(vault_id)?],
+        None => {
+            vault_memberships::Entity::find()
+                .select_only()
+                .column(vault_memberships::Column::VaultId)
+                .filter(vault_memberships::Column::UserId.eq(token.user_id.as_str()))
+                .into_tuple()
+                .all(db_tx)
+                .await?
+        }
+    };
+    for vault_id in vault_ids {
+        record_audit(db_tx, vault_id, &token.user_id, change()).await?;
+    }
+    Ok(())
+}
+
+impl Engine {
+    /// Creates a personal access token and returns it with its secret.
+    ///
+    /// The secret is only available here: the database keeps its digest.
+    /// A token limited to a vault requires the owner to have read access to
+    /// it (write access for write tokens).
+    pub async fn create_access_token(
+        &self,
+        cmd: CreateAccessTokenCmd,
+    ) -> ResultEngine<(AccessToken, String)> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine.require_user_exists(db_tx, &cmd.user_id).await?;
+                let name = normalize_required_name(&cmd.name, "token")?;
+                let now = Utc::now();
+                if cmd.expires_at.is_some_and(|expires_at| expires_at <= now) {
+                    return Err(EngineError::InvalidAmount(
+                        "expires_at must be in the future".to_string(),
+                    ));
+                }
+                if let Some(vault_id) = &cmd.vault_id {
+                    match cmd.scope {
+                        TokenScope::Read => {
+                            engine
+                                .require_vault_by_id(db_tx, vault_id, &cmd.user_id)
+                                .await?;
+                        }
+                        TokenScope::Write => {
+                            engine
+                                .require_vault_by_id_write(db_tx, vault_id, &cmd.user_id)
+                                .await?;
+                        }
+                    }
+                }
+
+                let token = AccessToken {
+                    id: Uuid::new_v4(),
+                    user_id: cmd.user_id,
+                    name,
+                    scope: cmd.scope,
+                    vault_id: cmd.vault_id,
+                    created_at: now,
+                    expires_at: cmd.expires_at,
+                };
+                let secret = generate_access_token();
+                // Return the stored row: Postgres keeps microsecond timestamps.
+                let stored = token
+                    .to_active_model(hash_access_token(&secret))?
+                    .insert(db_tx)
+                    .await?;
+                let token = AccessToken::from(stored);
+                record_token_audit(db_tx, &token, || {
+                    AuditChange::created(AuditEntity::AccessToken, token.id, snapshot(&token))
+                })
+                .await?;
+                Ok((token, secret))
+            })
+        })
+        .await
+    }
+
+    /// Lists the access tokens of a user, expired ones included.
+    pub async fn list_access_tokens(&self, user_id: &str) -> ResultEngine<Vec<AccessToken>> {
+        let user_id = user_id.to_string();
+        self.with_tx(|_engine, db_tx| {
+            Box::pin(async move {
+                Ok(access_tokens::Entity::find()
+                    .filter(access_tokens::Column::Username.eq(user_id))
+                    .order_by_asc(access_tokens::Column::CreatedAt)
+                    .order_by_asc(access_tokens::Column::Id)
+                    .all(db_tx)
+                    .await?
+                    .into_iter()
+                    .map(AccessToken::from)
+                    .collect())
+            })
+        })
+        .await
+    }
+
+    /// Revokes (deletes) one of the user's access tokens.
+    pub async fn revoke_access_token(&self, token_id: Uuid, user_id: &str) -> ResultEngine<()> {
+        let user_id = user_id.to_string();
+        self.with_tx(|_engine, db_tx| {
+            Box::pin(async move {
+                let token = access_tokens::Entity::find_by_id(token_id)
+                    .filter(access_tokens::Column::Username.eq(user_id))
+                    .one(db_tx)
+                    .await?
+                    .map(AccessToken::from)
+                    .ok_or_else(|| EngineError::KeyNotFound("token not exists".to_string()))?;
+                access_tokens::Entity::delete_by_id(token_id)
+                    .exec(db_tx)
+                    .await?;
+                record_token_audit(db_tx, &token, || {
+                    AuditChange::deleted(AuditEntity::AccessToken, token.id, snapshot(&token))
+                })
+                .await
+            })
+        })
+        .await
+    }
+
+    /// Resolves a token secret to its token. Returns `None` for unknown or
+    /// expired tokens.
+    pub async fn authenticate_access_token(
+        &self,
+        secret: &str,
+    ) -> ResultEngine<Option<AccessToken>> {
+        let token_hash = hash_access_token(secret);
+        self.with_tx(|_engine, db_tx| {
+            Box::pin(async move {
+                let token = access_tokens::Entity::find()
+                    .filter(access_tokens::Column::TokenHash.eq(token_hash))
+                    .one(db_tx)
+                    .await?
+                    .map(AccessToken::from);
+                Ok(token.filter(|token| !token.is_expired(Utc::now())))
+            })
+        })
+        .await
+    }
+}
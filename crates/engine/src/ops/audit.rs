@@ -0,0 +1,140 @@
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use sea_orm::{DatabaseTransaction, QueryFilter, QueryOrder, QuerySelect, prelude::*};
+
+use crate::{
+    AuditEntry, AuditListCmd, EngineError, ResultEngine,
+    audit::{self, AuditChange},
+    legs, transactions,
+};
+
+use super::{Engine, parse_vault_uuid};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct AuditCursor {
+    entry_id: Uuid,
+}
+
+impl AuditCursor {
+    fn encode(&self) -> ResultEngine<String> {
+        let bytes = serde_json::to_vec(self)
+            .map_err(|_| EngineError::InvalidCursor("invalid audit cursor".to_string()))?;
+        Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes))
+    }
+
+    fn decode(input: &str) -> ResultEngine<Self> {
+        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(input.as_bytes())
+            .map_err(|_| EngineError::InvalidCursor("invalid audit cursor".to_string()))?;
+        serde_json::from_slice::<Self>(&bytes)
+            .map_err(|_| EngineError::InvalidCursor("invalid audit cursor".to_string()))
+    }
+}
+
+/// JSON snapshot of a row for the audit log.
+pub(super) fn snapshot<T: Serialize>(model: &T) -> Option<Json> {
+    serde_json::to_value(model).ok()
+}
+
+/// JSON snapshot of the row with primary key `id`, if it exists.
+pub(super) async fn entity_snapshot<E>(
+    db_tx: &DatabaseTransaction,
+    id: Uuid,
+) -> ResultEngine<Option<Json>>
+where
+    E: EntityTrait,
+    E::Model: Serialize,
+    Uuid: Into<<E::PrimaryKey as PrimaryKeyTrait>::ValueType>,
+{
+    Ok(E::find_by_id(id)
+        .one(db_tx)
+        .await?
+        .and_then(|model| snapshot(&model)))
+}
+
+/// JSON snapshot of a transaction with its legs, if it exists.
+pub(super) async fn transaction_snapshot(
+    db_tx: &DatabaseTransaction,
+    transaction_id: Uuid,
+) -> ResultEngine<Option<Json>> {
+    let Some(mut tx) = entity_snapshot::<transactions::Entity>(db_tx, transaction_id).await? else {
+        return Ok(None);
+    };
+    let legs = legs::Entity::find()
+        .filter(legs::Column::TransactionId.eq(transaction_id))
+        .order_by_asc(legs::Column::Id)
+        .all(db_tx)
+        .await?;
+    if let (Json::Object(fields), Some(legs)) = (&mut tx, snapshot(&legs)) {
+        fields.insert("legs".to_string(), legs);
+    }
+    Ok(Some(tx))
+}
+
+/// Appends an entry to the audit log of `vault_id`.
+///
+/// Call it inside the `with_tx` of the change, so the entry is committed (or
+/// rolled back) with it.
+pub(super) async fn record_audit(
+    db_tx: &DatabaseTransaction,
+    vault_id: Uuid,
+    actor: &str,
+    change: AuditChange,
+) -> ResultEngine<()> {
+    audit::ActiveModel::new(vault_id, actor, change)
+        .insert(db_tx)
+        .await?;
+    Ok(())
+}
+
+impl Engine {
+    /// Lists the audit log of a vault, newest first, with cursor-based
+    /// pagination. Only the vault owner can read it.
+    pub async fn list_audit(
+        &self,
+        cmd: AuditListCmd,
+    ) -> ResultEngine<(Vec<AuditEntry>, Option<String>)> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_owner(db_tx, &cmd.vault_id, &cmd.user_id)
+                    .await?;
+                let vault_uuid = parse_vault_uuid(&cmd.vault_id)?;
+
+                let mut query = audit::Entity::find()
+                    .filter(audit::Column::VaultId.eq(vault_uuid))
+                    .order_by_desc(audit::Column::Id)
+                    .limit(cmd.limit.saturating_add(1));
+                if let Some(actor) = cmd.actor {
+                    query = query.filter(audit::Column::Actor.eq(actor));
+                }
+                if let Some(entity) = cmd.entity {
+                    query = query.filter(audit::Column::EntityKind.eq(entity));
+                }
+                if let Some(entity_id) = cmd.entity_id {
+                    query = query.filter(audit::Column::EntityId.eq(entity_id));
+                }
+                if let Some(cursor) = cmd.cursor.as_deref() {
+                    let cursor = AuditCursor::decode(cursor)?;
+                    query = query.filter(audit::Column::Id.lt(cursor.entry_id));
+                }
+
+                let rows = query.all(db_tx).await?;
+                let has_more = rows.len() > cmd.limit as usize;
+                let entries: Vec<AuditEntry> = rows
+                    .into_iter()
+                    .take(cmd.limit as usize)
+                    .map(AuditEntry::from)
+                    .collect();
+                let next_cursor = match entries.last() {
+                    Some(last) if has_more => Some(AuditCursor { entry_id: last.id }.encode()?),
+                    _ => None,
+                };
+                Ok((entries, next_cursor))
+            })
+        })
+        .await
+    }
+}
@@ -0,0 +1,699 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sea_orm::{
+    ActiveValue, DatabaseTransaction, JoinType, QueryFilter, QueryOrder, QuerySelect, prelude::*,
+};
+use uuid::Uuid;
+
+use crate::{
+    AuditAction, AuditEntity, BudgetRecord, CategoryAliasRecord, CategoryRecord, CategoryRule,
+    CategoryRuleConditions, CategoryRuleRecord, EngineError, FlowMemberRecord, FlowModeRecord,
+    FlowRecord, ImportVaultCmd, Leg, LegRecord, LegTarget, MemberRecord, RecurringRuleRecord,
+    ResultEngine, TransactionRecord, VAULT_EXPORT_VERSION, VaultExport, VaultImportReport,
+    VaultRecord, WalletRecord,
+    audit::AuditChange,
+    backup::IdMap,
+    budgets, cash_flows, categories, category_aliases, category_rules, flow_memberships, goals,
+    legs, recurring_rules, transactions, users,
+    util::{normalize_category_key, normalize_required_name},
+    vault, vault_memberships, wallets,
+};
+
+use super::{
+    Engine,
+    access::MembershipRole,
+    audit::{record_audit, snapshot},
+    parse_vault_uuid,
+};
+
+fn to_u32(value: i32, label: &str) -> ResultEngine<u32> {
+    u32::try_from(value).map_err(|_| EngineError::InvalidExport(format!("invalid {label}")))
+}
+
+fn to_i32(value: u32, label: &str) -> ResultEngine<i32> {
+    i32::try_from(value).map_err(|_| EngineError::InvalidExport(format!("invalid {label}")))
+}
+
+fn flow_mode(model: &cash_flows::Model) -> FlowModeRecord {
+    match (model.max_balance, model.income_balance) {
+        (None, _) => FlowModeRecord::Unlimited,
+        (Some(cap_minor), None) => FlowModeRecord::NetCapped { cap_minor },
+        (Some(cap_minor), Some(_)) => FlowModeRecord::IncomeCapped { cap_minor },
+    }
+}
+
+fn rule_record(model: recurring_rules::Model) -> ResultEngine<RecurringRuleRecord> {
+    Ok(RecurringRuleRecord {
+        id: model.id,
+        kind: model.kind,
+        amount_minor: model.amount_minor,
+        wallet_id: model.wallet_id,
+        flow_id: model.flow_id,
+        from_flow_id: model.from_flow_id,
+        to_flow_id: model.to_flow_id,
+        category: model.category,
+        note: model.note,
+        frequency: model.frequency,
+        interval: to_u32(model.interval, "interval")?,
+        day_of_month: model
+            .day_of_month
+            .map(|day| to_u32(day, "day_of_month"))
+            .transpose()?,
+        starts_at: model.starts_at,
+        ends_at: model.ends_at,
+        max_occurrences: model
+            .max_occurrences
+            .map(|max| to_u32(max, "max_occurrences"))
+            .transpose()?,
+        occurrences: to_u32(model.occurrences, "occurrences")?,
+        next_occurrence_at: model.next_occurrence_at,
+        active: model.active,
+        last_error: model.last_error,
+        created_by: model.created_by,
+    })
+}
+
+impl Engine {
+    /// Exports a whole vault as a [`VaultExport`] document.
+    ///
+    /// Authorization: owner-only.
+    pub async fn export_vault(&self, vault_id: &str, user_id: &str) -> ResultEngine<VaultExport> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let vault_model = engine
+                    .require_vault_owner(db_tx, &vault_id, &user_id)
+                    .await?;
+                engine.export_vault_in_tx(db_tx, vault_model).await
+            })
+        })
+        .await
+    }
+
+    async fn export_vault_in_tx(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_model: vault::Model,
+    ) -> ResultEngine<VaultExport> {
+        let vault_uuid = vault_model.id;
+
+        let wallets = wallets::Entity::find()
+            .filter(wallets::Column::VaultId.eq(vault_uuid))
+            .order_by_asc(wallets::Column::Name)
+            .all(db_tx)
+            .await?
+            .into_iter()
+            .map(|model| WalletRecord {
+                id: model.id,
+                name: model.name,
+                currency: model.currency,
+                archived: model.archived,
+                balance_minor: model.balance,
+            })
+            .collect();
+
+        let flows = cash_flows::Entity::find()
+            .filter(cash_flows::Column::VaultId.eq(vault_uuid))
+            .order_by_asc(cash_flows::Column::Name)
+            .all(db_tx)
+            .await?
+            .into_iter()
+            .map(|model| FlowRecord {
+                id: model.id,
+                mode: flow_mode(&model),
+                unallocated: model.system_kind == Some(cash_flows::SystemFlowKind::Unallocated),
+                name: model.name,
+                archived: model.archived,
+                balance_minor: model.balance,
+                target_minor: model.target_minor,
+                target_date: model.target_date,
+            })
+            .collect();
+
+        let categories = categories::Entity::find()
+            .filter(categories::Column::VaultId.eq(vault_uuid))
+            .order_by_asc(categories::Column::NameNorm)
+            .all(db_tx)
+            .await?
+            .into_iter()
+            .map(|model| CategoryRecord {
+                id: model.id,
+                name: model.name,
+                archived: model.archived,
+                is_system: model.is_system,
+            })
+            .collect();
+
+        let category_aliases = category_aliases::Entity::find()
+            .filter(category_aliases::Column::VaultId.eq(vault_uuid))
+            .order_by_asc(category_aliases::Column::AliasNorm)
+            .all(db_tx)
+            .await?
+            .into_iter()
+            .map(|model| CategoryAliasRecord {
+                id: model.id,
+                category_id: model.category_id,
+                alias: model.alias,
+            })
+            .collect();
+
+        let members = vault_memberships::Entity::find()
+            .filter(vault_memberships::Column::VaultId.eq(vault_uuid))
+            .filter(vault_memberships::Column::UserId.ne(vault_model.user_id.clone()))
+            .order_by_asc(vault_memberships::Column::UserId)
+            .all(db_tx)
+            .await?
+            .into_iter()
+            .map(|model| MemberRecord {
+                user_id: model.user_id,
+                role: model.role,
+            })
+            .collect();
+
+        let flow_members = flow_memberships::Entity::find()
+            .join(
+                JoinType::InnerJoin,
+                flow_memberships::Relation::CashFlows.def(),
+            )
+            .filter(cash_flows::Column::VaultId.eq(vault_uuid))
+            .order_by_asc(flow_memberships::Column::FlowId)
+            .order_by_asc(flow_memberships::Column::UserId)
+            .all(db_tx)
+            .await?
+            .into_iter()
+            .map(|model| FlowMemberRecord {
+                flow_id: model.flow_id,
+                user_id: model.user_id,
+                role: model.role,
+            })
+            .collect();
+
+        let budgets = budgets::Entity::find()
+            .filter(budgets::Column::VaultId.eq(vault_uuid))
+            .order_by_asc(budgets::Column::StartsOn)
+            .order_by_asc(budgets::Column::Id)
+            .all(db_tx)
+            .await?
+            .into_iter()
+            .map(|model| BudgetRecord {
+                id: model.id,
+                category_id: model.category_id,
+                amount_minor: model.amount_minor,
+                period: model.period,
+                starts_on: model.starts_on,
+                rollover: model.rollover,
+            })
+            .collect();
+
+        let recurring_rules = recurring_rules::Entity::find()
+            .filter(recurring_rules::Column::VaultId.eq(vault_uuid))
+            .order_by_asc(recurring_rules::Column::StartsAt)
+            .order_by_asc(recurring_rules::Column::Id)
+            .all(db_tx)
+            .await?
+            .into_iter()
+            .map(rule_record)
+            .collect::<ResultEngine<_>>()?;
+
+        let category_rules = category_rules::Entity::find()
+            .filter(category_rules::Column::VaultId.eq(vault_uuid))
+            .order_by_asc(category_rules::Column::Priority)
+            .order_by_asc(category_rules::Column::CreatedAt)
+            .order_by_asc(category_rules::Column::Id)
+            .all(db_tx)
+            .await?
+            .into_iter()
+            .map(|model| {
+                let rule = CategoryRule::from(model);
+                CategoryRuleRecord {
+                    id: rule.id,
+                    category_id: rule.category_id,
+                    priority: rule.priority,
+                    conditions: rule.conditions,
+                    created_at: rule.created_at,
+                }
+            })
+            .collect();
+
+        let mut legs_by_tx: HashMap<Uuid, Vec<LegRecord>> = HashMap::new();
+        let leg_models = legs::Entity::find()
+            .join(JoinType::InnerJoin, legs::Relation::Transactions.def())
+            .filter(transactions::Column::VaultId.eq(vault_uuid))
+            .order_by_asc(legs::Column::Id)
+            .all(db_tx)
+            .await?;
+        for model in leg_models {
+            let leg = Leg::try_from(model)?;
+            legs_by_tx
+                .entry(leg.transaction_id)
+                .or_default()
+                .push(LegRecord {
+                    id: leg.id,
+                    target: leg.target,
+                    amount_minor: leg.amount_minor,
+                    currency: leg.currency,
+                    attributed_user_id: leg.attributed_user_id,
+                    category_id: leg.category_id,
+                    note: leg.note,
+                });
+        }
+
+        let transactions = transactions::Entity::find()
+            .filter(transactions::Column::VaultId.eq(vault_uuid))
+            .order_by_asc(transactions::Column::OccurredAt)
+            .order_by_asc(transactions::Column::Id)
+            .all(db_tx)
+            .await?
+            .into_iter()
+            .map(|model| TransactionRecord {
+                legs: legs_by_tx.remove(&model.id).unwrap_or_default(),
+                id: model.id,
+                kind: model.kind,
+                occurred_at: model.occurred_at,
+                amount_minor: model.amount_minor,
+                currency: model.currency,
+                original_amount_minor: model.original_amount_minor,
+                original_currency: model.original_currency,
+                exchange_rate: model.exchange_rate,
+                category_id: model.category_id,
+                category: model.category,
+                note: model.note,
+                idempotency_key: model.idempotency_key,
+                created_by: model.created_by,
+                voided_at: model.voided_at,
+                voided_by: model.voided_by,
+                clearing_status: model.clearing_status,
+                refunded_transaction_id: model.refunded_transaction_id,
+            })
+            .collect();
+
+        Ok(VaultExport {
+            format_version: VAULT_EXPORT_VERSION,
+            exported_at: Utc::now(),
+            vault: VaultRecord {
+                id: vault_uuid,
+                name: vault_model.name,
+                owner: vault_model.user_id,
+                currency: vault_model.currency,
+                books_closed_until: vault_model.books_closed_until,
+            },
+            wallets,
+            flows,
+            categories,
+            category_aliases,
+            members,
+            flow_members,
+            budgets,
+            recurring_rules,
+            category_rules,
+            transactions,
+        })
+    }
+
+    /// Recreates a vault from a [`VaultExport`], then replays its ledger and
+    /// checks the recomputed balances against the exported ones.
+    ///
+    /// Everything happens in one DB transaction: an inconsistent document
+    /// leaves no trace. Members whose user does not exist on this instance are
+    /// skipped and reported.
+    ///
+    /// Authorization: the target owner must exist (admin operation).
+    pub async fn import_vault(&self, cmd: ImportVaultCmd) -> ResultEngine<VaultImportReport> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move { engine.import_vault_in_tx(db_tx, cmd).await })
+        })
+        .await
+    }
+
+    async fn import_vault_in_tx(
+        &self,
+        db_tx: &DatabaseTransaction,
+        cmd: ImportVaultCmd,
+    ) -> ResultEngine<VaultImportReport> {
+        let export = cmd.export;
+        // Older versions only lack fields that default to empty.
+        if !(1..=VAULT_EXPORT_VERSION).contains(&export.format_version) {
+            return Err(EngineError::InvalidExport(format!(
+                "unsupported format version {} (supported up to {VAULT_EXPORT_VERSION})",
+                export.format_version
+            )));
+        }
+        if export.flows.iter().filter(|flow| flow.unallocated).count() != 1 {
+            return Err(EngineError::InvalidExport(
+                "exactly one unallocated flow is required".to_string(),
+            ));
+        }
+        if !export.categories.iter().any(|category| category.is_system) {
+            return Err(EngineError::InvalidExport(
+                "missing system category".to_string(),
+            ));
+        }
+
+        let owner = cmd.owner.unwrap_or(export.vault.owner);
+        self.require_user_exists(db_tx, &owner).await?;
+        let name =
+            normalize_required_name(cmd.name.as_deref().unwrap_or(&export.vault.name), "vault")?;
+        self.ensure_vault_name_available(db_tx, &owner, &name)
+            .await?;
+
+        let mut ids = IdMap::new(cmd.new_ids);
+        ids.declare(export.vault.id)?;
+        for id in export
+            .wallets
+            .iter()
+            .map(|w| w.id)
+            .chain(export.flows.iter().map(|f| f.id))
+            .chain(export.categories.iter().map(|c| c.id))
+            .chain(export.category_aliases.iter().map(|a| a.id))
+            .chain(export.budgets.iter().map(|b| b.id))
+            .chain(export.recurring_rules.iter().map(|r| r.id))
+            .chain(export.category_rules.iter().map(|r| r.id))
+            .chain(export.transactions.iter().map(|t| t.id))
+            .chain(
+                export
+                    .transactions
+                    .iter()
+                    .flat_map(|t| t.legs.iter().map(|l| l.id)),
+            )
+        {
+            ids.declare(id)?;
+        }
+
+        let vault_uuid = ids.get(export.vault.id)?;
+        let currency = export.vault.currency;
+        let vault_model = vault::ActiveModel {
+            id: ActiveValue::Set(vault_uuid),
+            name: ActiveValue::Set(name),
+            user_id: ActiveValue::Set(owner.clone()),
+            currency: ActiveValue::Set(currency),
+            books_closed_until: ActiveValue::Set(export.vault.books_closed_until),
+        }
+        .insert(db_tx)
+        .await?;
+        vault_memberships::ActiveModel {
+            vault_id: ActiveValue::Set(vault_uuid),
+            user_id: ActiveValue::Set(owner.clone()),
+            role: ActiveValue::Set("owner".to_string()),
+        }
+        .insert(db_tx)
+        .await?;
+
+        for wallet in &export.wallets {
+            wallets::ActiveModel {
+                id: ActiveValue::Set(ids.get(wallet.id)?),
+                name: ActiveValue::Set(wallet.name.clone()),
+                balance: ActiveValue::Set(0),
+                currency: ActiveValue::Set(wallet.currency),
+                archived: ActiveValue::Set(wallet.archived),
+                vault_id: ActiveValue::Set(vault_uuid),
+            }
+            .insert(db_tx)
+            .await?;
+        }
+        for flow in &export.flows {
+            goals::validate_goal(flow.target_minor, flow.target_date)?;
+            let (max_balance, income_balance) = match flow.mode {
+                FlowModeRecord::Unlimited => (None, None),
+                FlowModeRecord::NetCapped { cap_minor } => (Some(cap_minor), None),
+                FlowModeRecord::IncomeCapped { cap_minor } => (Some(cap_minor), Some(0)),
+            };
+            cash_flows::ActiveModel {
+                id: ActiveValue::Set(ids.get(flow.id)?),
+                name: ActiveValue::Set(flow.name.clone()),
+                system_kind: ActiveValue::Set(
+                    flow.unallocated
+                        .then_some(cash_flows::SystemFlowKind::Unallocated),
+                ),
+                balance: ActiveValue::Set(0),
+                max_balance: ActiveValue::Set(max_balance),
+                income_balance: ActiveValue::Set(income_balance),
+                currency: ActiveValue::Set(currency),
+                archived: ActiveValue::Set(flow.archived),
+                vault_id: ActiveValue::Set(vault_uuid),
+                target_minor: ActiveValue::Set(flow.target_minor),
+                target_date: ActiveValue::Set(flow.target_date),
+            }
+            .insert(db_tx)
+            .await?;
+        }
+        for category in &export.categories {
+            categories::ActiveModel {
+                id: ActiveValue::Set(ids.get(category.id)?),
+                vault_id: ActiveValue::Set(vault_uuid),
+                name: ActiveValue::Set(category.name.clone()),
+                name_norm: ActiveValue::Set(normalize_category_key(&category.name)?),
+                archived: ActiveValue::Set(category.archived),
+                is_system: ActiveValue::Set(category.is_system),
+            }
+            .insert(db_tx)
+            .await?;
+        }
+        for alias in &export.category_aliases {
+            category_aliases::ActiveModel {
+                id: ActiveValue::Set(ids.get(alias.id)?),
+                vault_id: ActiveValue::Set(vault_uuid),
+                category_id: ActiveValue::Set(ids.get(alias.category_id)?),
+                alias: ActiveValue::Set(alias.alias.clone()),
+                alias_norm: ActiveValue::Set(normalize_category_key(&alias.alias)?),
+            }
+            .insert(db_tx)
+            .await?;
+        }
+
+        let mut skipped_members = Vec::new();
+        for member in &export.members {
+            MembershipRole::try_from(member.role.as_str())?;
+            if member.user_id == owner {
+                continue;
+            }
+            if users::Entity::find_by_id(member.user_id.clone())
+                .one(db_tx)
+                .await?
+                .is_none()
+            {
+                skipped_members.push(member.user_id.clone());
+                continue;
+            }
+            vault_memberships::ActiveModel {
+                vault_id: ActiveValue::Set(vault_uuid),
+                user_id: ActiveValue::Set(member.user_id.clone()),
+                role: ActiveValue::Set(member.role.clone()),
+            }
+            .insert(db_tx)
+            .await?;
+        }
+        for member in &export.flow_members {
+            MembershipRole::try_from(member.role.as_str())?;
+            if users::Entity::find_by_id(member.user_id.clone())
+                .one(db_tx)
+                .await?
+                .is_none()
+            {
+                if !skipped_members.contains(&member.user_id) {
+                    skipped_members.push(member.user_id.clone());
+                }
+                continue;
+            }
+            flow_memberships::ActiveModel {
+                flow_id: ActiveValue::Set(ids.get(member.flow_id)?),
+                user_id: ActiveValue::Set(member.user_id.clone()),
+                role: ActiveValue::Set(member.role.clone()),
+            }
+            .insert(db_tx)
+            .await?;
+        }
+
+        for tx in &export.transactions {
+            let tx_id = ids.get(tx.id)?;
+            transactions::ActiveModel {
+                id: ActiveValue::Set(tx_id),
+                vault_id: ActiveValue::Set(vault_uuid),
+                kind: ActiveValue::Set(tx.kind),
+                occurred_at: ActiveValue::Set(tx.occurred_at),
+                amount_minor: ActiveValue::Set(tx.amount_minor),
+                idempotency_key: ActiveValue::Set(tx.idempotency_key.clone()),
+                currency: ActiveValue::Set(tx.currency),
+                category: ActiveValue::Set(tx.category.clone()),
+                note: ActiveValue::Set(tx.note.clone()),
+                created_by: ActiveValue::Set(tx.created_by.clone()),
+                voided_at: ActiveValue::Set(tx.voided_at),
+                voided_by: ActiveValue::Set(tx.voided_by.clone()),
+                clearing_status: ActiveValue::Set(tx.clearing_status),
+                refunded_transaction_id: ActiveValue::Set(ids.get_opt(tx.refunded_transaction_id)?),
+                category_id: ActiveValue::Set(ids.get(tx.category_id)?),
+                original_amount_minor: ActiveValue::Set(tx.original_amount_minor),
+                original_currency: ActiveValue::Set(tx.original_currency),
+                exchange_rate: ActiveValue::Set(tx.exchange_rate.clone()),
+            }
+            .insert(db_tx)
+            .await?;
+            for leg in &tx.legs {
+                let target = match leg.target {
+                    LegTarget::Wallet { wallet_id } => LegTarget::Wallet {
+                        wallet_id: ids.get(wallet_id)?,
+                    },
+                    LegTarget::Flow { flow_id } => LegTarget::Flow {
+                        flow_id: ids.get(flow_id)?,
+                    },
+                };
+                let model = legs::ActiveModel::from(&Leg {
+                    id: ids.get(leg.id)?,
+                    transaction_id: tx_id,
+                    target,
+                    amount_minor: leg.amount_minor,
+                    currency: leg.currency,
+                    attributed_user_id: leg.attributed_user_id.clone(),
+                    category_id: ids.get_opt(leg.category_id)?,
+                    note: leg.note.clone(),
+                });
+                model.insert(db_tx).await?;
+            }
+        }
+
+        for budget in &export.budgets {
+            budgets::ActiveModel {
+                id: ActiveValue::Set(ids.get(budget.id)?),
+                vault_id: ActiveValue::Set(vault_uuid),
+                category_id: ActiveValue::Set(ids.get(budget.category_id)?),
+                amount_minor: ActiveValue::Set(budget.amount_minor),
+                period: ActiveValue::Set(budget.period),
+                starts_on: ActiveValue::Set(budget.starts_on),
+                rollover: ActiveValue::Set(budget.rollover),
+            }
+            .insert(db_tx)
+            .await?;
+        }
+        for rule in &export.recurring_rules {
+            recurring_rules::ActiveModel {
+                id: ActiveValue::Set(ids.get(rule.id)?),
+                vault_id: ActiveValue::Set(vault_uuid),
+                kind: ActiveValue::Set(rule.kind),
+                amount_minor: ActiveValue::Set(rule.amount_minor),
+                wallet_id: ActiveValue::Set(ids.get_opt(rule.wallet_id)?),
+                flow_id: ActiveValue::Set(ids.get_opt(rule.flow_id)?),
+                from_flow_id: ActiveValue::Set(ids.get_opt(rule.from_flow_id)?),
+                to_flow_id: ActiveValue::Set(ids.get_opt(rule.to_flow_id)?),
+                category: ActiveValue::Set(rule.category.clone()),
+                note: ActiveValue::Set(rule.note.clone()),
+                frequency: ActiveValue::Set(rule.frequency),
+                interval: ActiveValue::Set(to_i32(rule.interval, "interval")?),
+                day_of_month: ActiveValue::Set(
+                    rule.day_of_month
+                        .map(|day| to_i32(day, "day_of_month"))
+                        .transpose()?,
+                ),
+                starts_at: ActiveValue::Set(rule.starts_at),
+                ends_at: ActiveValue::Set(rule.ends_at),
+                max_occurrences: ActiveValue::Set(
+                    rule.max_occurrences
+                        .map(|max| to_i32(max, "max_occurrences"))
+                        .transpose()?,
+                ),
+                occurrences: ActiveValue::Set(to_i32(rule.occurrences, "occurrences")?),
+                next_occurrence_at: ActiveValue::Set(rule.next_occurrence_at),
+                active: ActiveValue::Set(rule.active),
+                last_error: ActiveValue::Set(rule.last_error.clone()),
+                created_by: ActiveValue::Set(rule.created_by.clone()),
+            }
+            .insert(db_tx)
+            .await?;
+        }
+
+        for rule in &export.category_rules {
+            let conditions = CategoryRuleConditions {
+                wallet_id: ids.get_opt(rule.conditions.wallet_id)?,
+                flow_id: ids.get_opt(rule.conditions.flow_id)?,
+                ..rule.conditions.clone()
+            }
+            .normalize()
+            .map_err(|err| EngineError::InvalidExport(err.to_string()))?;
+            category_rules::ActiveModel::try_from(&CategoryRule {
+                id: ids.get(rule.id)?,
+                vault_id: vault_uuid.to_string(),
+                category_id: ids.get(rule.category_id)?,
+                priority: rule.priority,
+                conditions,
+                created_at: rule.created_at,
+            })?
+            .insert(db_tx)
+            .await?;
+        }
+
+        let vault_id = vault_uuid.to_string();
+        self.recompute_balances_in_tx(db_tx, &vault_id, currency)
+            .await?;
+        self.check_imported_balances(db_tx, &vault_id, &export.wallets, &export.flows, &ids)
+            .await?;
+        // Imports are admin operations: the new owner is recorded as actor.
+        record_audit(
+            db_tx,
+            vault_uuid,
+            &owner,
+            AuditChange::new(
+                AuditAction::Import,
+                AuditEntity::Vault,
+                vault_uuid,
+                None,
+                snapshot(&vault_model),
+            ),
+        )
+        .await?;
+
+        Ok(VaultImportReport {
+            vault_id,
+            skipped_members,
+        })
+    }
+
+    /// Compares recomputed balances with the ones recorded in the export.
+    async fn check_imported_balances(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_id: &str,
+        wallets: &[WalletRecord],
+        flows: &[FlowRecord],
+        ids: &IdMap,
+    ) -> ResultEngine<()> {
+        let vault_uuid = parse_vault_uuid(vault_id)?;
+        let wallet_balances: HashMap<Uuid, i64> = wallets::Entity::find()
+            .select_only()
+            .column(wallets::Column::Id)
+            .column(wallets::Column::Balance)
+            .filter(wallets::Column::VaultId.eq(vault_uuid))
+            .into_tuple::<(Uuid, i64)>()
+            .all(db_tx)
+            .await?
+            .into_iter()
+            .collect();
+        let flow_balances: HashMap<Uuid, i64> = cash_flows::Entity::find()
+            .select_only()
+            .column(cash_flows::Column::Id)
+            .column(cash_flows::Column::Balance)
+            .filter(cash_flows::Column::VaultId.eq(vault_uuid))
+            .into_tuple::<(Uuid, i64)>()
+            .all(db_tx)
+            .await?
+            .into_iter()
+            .collect();
+
+        let expected = wallets
+            .iter()
+            .map(|w| ("wallet", &w.name, w.id, w.balance_minor, &wallet_balances))
+            .chain(
+                flows
+                    .iter()
+                    .map(|f| ("flow", &f.name, f.id, f.balance_minor, &flow_balances)),
+            );
+        for (label, name, id, balance_minor, actual) in expected {
+            let recomputed = actual.get(&ids.get(id)?).copied().unwrap_or_default();
+            if recomputed != balance_minor {
+                return Err(EngineError::InvalidExport(format!(
+                    "{label} {name}: ledger balance is {recomputed}, export says {balance_minor}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
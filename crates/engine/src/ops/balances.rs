@@ -1,15 +1,46 @@
 use std::collections::HashMap;
 
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use sea_orm::{ActiveValue, JoinType, QueryFilter, QueryOrder, QuerySelect, prelude::*};
+use sea_orm::{
+    ActiveValue, DatabaseTransaction, JoinType, QueryFilter, QueryOrder, QuerySelect, Select,
+    prelude::*,
+};
 
 use crate::{
-    CashFlow, EngineError, Leg, LegTarget, ResultEngine, Wallet, cash_flows, legs, transactions,
-    util::ensure_vault_currency, wallets,
+    AuditEntity, Balance, BalanceHistory, BalanceHistoryCmd, BalanceTarget, CashFlow, Currency,
+    EngineError, Leg, LegTarget, ResultEngine, Wallet,
+    audit::AuditChange,
+    balances, cash_flows,
+    legs::{self, LegTargetKind},
+    transactions,
+    util::ensure_vault_currency,
+    vault, wallets,
+};
+
+use super::{
+    Engine,
+    audit::{record_audit, snapshot},
+    parse_vault_uuid, sum_minor,
 };
 
-use super::{Engine, parse_vault_uuid};
+/// Non-voided legs of a vault that move the balance of `target`.
+fn target_legs(vault_uuid: Uuid, target: BalanceTarget) -> Select<legs::Entity> {
+    let query = legs::Entity::find()
+        .join(JoinType::InnerJoin, legs::Relation::Transactions.def())
+        .filter(transactions::Column::VaultId.eq(vault_uuid))
+        .filter(transactions::Column::VoidedAt.is_null());
+    match target {
+        BalanceTarget::Vault => query.filter(legs::Column::TargetKind.eq(LegTargetKind::Flow)),
+        BalanceTarget::Wallet { wallet_id } => query
+            .filter(legs::Column::TargetKind.eq(LegTargetKind::Wallet))
+            .filter(legs::Column::TargetId.eq(wallet_id)),
+        BalanceTarget::Flow { flow_id } => query
+            .filter(legs::Column::TargetKind.eq(LegTargetKind::Flow))
+            .filter(legs::Column::TargetId.eq(flow_id)),
+    }
+}
 
 impl Engine {
     /// Recomputes denormalized balances for wallets and flows from the ledger
@@ -18,6 +49,7 @@ impl Engine {
     /// - Ignores voided transactions.
     /// - Validates flow invariants while replaying legs in chronological order.
     /// - Refreshes the in-memory vault state from DB models post-commit.
+    /// - Audits each wallet/flow whose stored balance was off.
     pub async fn recompute_balances(&self, vault_id: &str, user_id: &str) -> ResultEngine<()> {
         let vault_id = vault_id.to_string();
         let user_id = user_id.to_string();
@@ -26,90 +58,271 @@ impl Engine {
                 let vault_model = engine
                     .require_vault_by_id_write(db_tx, vault_id.as_str(), user_id.as_str())
                     .await?;
-                let currency = vault_model.currency;
-                let vault_uuid = parse_vault_uuid(vault_id.as_str())?;
-
-                // Load all wallets/flows from DB (including archived) to avoid stale RAM
-                // issues.
-                let wallet_models: Vec<wallets::Model> = wallets::Entity::find()
-                    .filter(wallets::Column::VaultId.eq(vault_uuid))
-                    .all(db_tx)
+                let before = balance_snapshots(db_tx, vault_model.id).await?;
+                engine
+                    .recompute_balances_in_tx(db_tx, &vault_id, vault_model.currency)
                     .await?;
-                let flow_models: Vec<cash_flows::Model> = cash_flows::Entity::find()
-                    .filter(cash_flows::Column::VaultId.eq(vault_uuid))
-                    .all(db_tx)
-                    .await?;
-
-                let mut wallets_by_id: HashMap<Uuid, Wallet> = HashMap::new();
-                for model in wallet_models {
-                    let mut wallet = Wallet::try_from((model, currency))?;
-                    wallet.balance = 0;
-                    wallets_by_id.insert(wallet.id, wallet);
-                }
-
-                let mut flows: HashMap<Uuid, CashFlow> = HashMap::new();
-                for model in flow_models {
-                    let mut flow = CashFlow::try_from((model, currency))?;
-                    flow.balance = 0;
-                    if flow.income_balance.is_some() {
-                        flow.income_balance = Some(0);
+                let after = balance_snapshots(db_tx, vault_model.id).await?;
+                for ((entity, id, before), (_, _, after)) in before.into_iter().zip(after) {
+                    if before != after {
+                        record_audit(
+                            db_tx,
+                            vault_model.id,
+                            &user_id,
+                            AuditChange::updated(entity, id, before, after),
+                        )
+                        .await?;
                     }
-                    flows.insert(flow.id, flow);
                 }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Balance of a wallet, a flow or the whole vault as of `at`: the sum of
+    /// the non-voided legs that occurred before it.
+    ///
+    /// Authorization: requires vault read access, or flow membership for a
+    /// flow.
+    pub async fn balance_at(
+        &self,
+        vault_id: &str,
+        target: BalanceTarget,
+        at: DateTime<Utc>,
+        user_id: &str,
+    ) -> ResultEngine<Balance> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let currency = engine
+                    .require_balance_target(db_tx, &vault_id, target, &user_id)
+                    .await?;
+                let balance_minor =
+                    balance_before(db_tx, parse_vault_uuid(&vault_id)?, target, at).await?;
+                Ok(Balance {
+                    currency,
+                    balance_minor,
+                })
+            })
+        })
+        .await
+    }
 
-                // Replay all non-voided legs in chronological order to validate invariants.
-                let leg_models: Vec<legs::Model> = legs::Entity::find()
-                    .join(JoinType::InnerJoin, legs::Relation::Transactions.def())
-                    .filter(transactions::Column::VaultId.eq(vault_uuid))
-                    .filter(transactions::Column::VoidedAt.is_null())
+    /// Closing balances of a wallet, a flow or the whole vault for each day,
+    /// week or month of a range.
+    ///
+    /// Authorization: requires vault read access, or flow membership for a
+    /// flow.
+    pub async fn balance_history(&self, cmd: BalanceHistoryCmd) -> ResultEngine<BalanceHistory> {
+        balances::validate_range(cmd.from, cmd.to)?;
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let currency = engine
+                    .require_balance_target(db_tx, &cmd.vault_id, cmd.target, &cmd.user_id)
+                    .await?;
+                let vault_uuid = parse_vault_uuid(&cmd.vault_id)?;
+                let opening_minor = balance_before(db_tx, vault_uuid, cmd.target, cmd.from).await?;
+                let changes: Vec<(DateTime<Utc>, i64)> = target_legs(vault_uuid, cmd.target)
+                    .select_only()
+                    .column(transactions::Column::OccurredAt)
+                    .column(legs::Column::AmountMinor)
+                    .filter(transactions::Column::OccurredAt.gte(cmd.from))
+                    .filter(transactions::Column::OccurredAt.lt(cmd.to))
                     .order_by_asc(transactions::Column::OccurredAt)
                     .order_by_asc(legs::Column::Id)
+                    .into_tuple()
                     .all(db_tx)
                     .await?;
+                let points = balances::series(
+                    cmd.interval,
+                    cmd.utc_offset,
+                    cmd.from,
+                    cmd.to,
+                    opening_minor,
+                    &changes,
+                )?;
+                Ok(BalanceHistory {
+                    currency,
+                    interval: cmd.interval,
+                    opening_minor,
+                    points,
+                })
+            })
+        })
+        .await
+    }
+
+    /// Checks that `user_id` may read the balance of `target` and returns
+    /// the currency it is kept in.
+    async fn require_balance_target(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_id: &str,
+        target: BalanceTarget,
+        user_id: &str,
+    ) -> ResultEngine<Currency> {
+        match target {
+            BalanceTarget::Vault => Ok(self
+                .require_vault_by_id(db_tx, vault_id, user_id)
+                .await?
+                .currency),
+            BalanceTarget::Wallet { wallet_id } => {
+                self.require_vault_by_id(db_tx, vault_id, user_id).await?;
+                self.wallet_currency(db_tx, vault_id, wallet_id).await
+            }
+            BalanceTarget::Flow { flow_id } => {
+                self.require_flow_read(db_tx, vault_id, flow_id, user_id)
+                    .await?;
+                let vault_model = vault::Entity::find_by_id(parse_vault_uuid(vault_id)?)
+                    .one(db_tx)
+                    .await?
+                    .ok_or_else(|| EngineError::KeyNotFound("vault not exists".to_string()))?;
+                Ok(vault_model.currency)
+            }
+        }
+    }
 
-                for leg_model in leg_models {
-                    let leg = Leg::try_from(leg_model)?;
-                    ensure_vault_currency(currency, leg.currency)?;
+    /// Replays the ledger of a vault inside `db_tx` and persists the
+    /// recomputed wallet/flow balances.
+    pub(super) async fn recompute_balances_in_tx(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_id: &str,
+        currency: Currency,
+    ) -> ResultEngine<()> {
+        let vault_uuid = parse_vault_uuid(vault_id)?;
+
+        // Load all wallets/flows from DB (including archived) to avoid stale RAM
+        // issues.
+        let wallet_models: Vec<wallets::Model> = wallets::Entity::find()
+            .filter(wallets::Column::VaultId.eq(vault_uuid))
+            .all(db_tx)
+            .await?;
+        let flow_models: Vec<cash_flows::Model> = cash_flows::Entity::find()
+            .filter(cash_flows::Column::VaultId.eq(vault_uuid))
+            .all(db_tx)
+            .await?;
+
+        let mut wallets_by_id: HashMap<Uuid, Wallet> = HashMap::new();
+        for model in wallet_models {
+            let mut wallet = Wallet::from(model);
+            wallet.balance = 0;
+            wallets_by_id.insert(wallet.id, wallet);
+        }
+
+        let mut flows: HashMap<Uuid, CashFlow> = HashMap::new();
+        for model in flow_models {
+            let mut flow = CashFlow::try_from((model, currency))?;
+            flow.balance = 0;
+            if flow.income_balance.is_some() {
+                flow.income_balance = Some(0);
+            }
+            flows.insert(flow.id, flow);
+        }
 
-                    match leg.target {
-                        LegTarget::Wallet { wallet_id } => {
-                            let wallet = wallets_by_id.get_mut(&wallet_id).ok_or_else(|| {
-                                EngineError::KeyNotFound("wallet not exists".to_string())
-                            })?;
-                            wallet.balance += leg.amount_minor;
-                        }
-                        LegTarget::Flow { flow_id } => {
-                            let flow = flows.get_mut(&flow_id).ok_or_else(|| {
-                                EngineError::KeyNotFound("cash_flow not exists".to_string())
-                            })?;
-                            flow.apply_leg_change(0, leg.amount_minor)?;
-                        }
+        // Replay all non-voided legs in chronological order to validate invariants.
+        let leg_models: Vec<legs::Model> = legs::Entity::find()
+            .join(JoinType::InnerJoin, legs::Relation::Transactions.def())
+            .filter(transactions::Column::VaultId.eq(vault_uuid))
+            .filter(transactions::Column::VoidedAt.is_null())
+            .order_by_asc(transactions::Column::OccurredAt)
+            .order_by_asc(legs::Column::Id)
+            .all(db_tx)
+            .await?;
+
+        for leg_model in leg_models {
+            let leg = Leg::try_from(leg_model)?;
+
+            match leg.target {
+                LegTarget::Wallet { wallet_id } => {
+                    let wallet = wallets_by_id
+                        .get_mut(&wallet_id)
+                        .ok_or_else(|| EngineError::KeyNotFound("wallet not exists".to_string()))?;
+                    if wallet.currency != leg.currency {
+                        return Err(EngineError::CurrencyMismatch(format!(
+                            "wallet currency is {}, got {}",
+                            wallet.currency.code(),
+                            leg.currency.code()
+                        )));
                     }
+                    wallet.balance += leg.amount_minor;
                 }
-
-                // Persist denormalized balances.
-                for (wallet_id, wallet) in &wallets_by_id {
-                    let wallet_model = wallets::ActiveModel {
-                        id: ActiveValue::Set(*wallet_id),
-                        balance: ActiveValue::Set(wallet.balance),
-                        ..Default::default()
-                    };
-                    wallet_model.update(db_tx).await?;
+                LegTarget::Flow { flow_id } => {
+                    ensure_vault_currency(currency, leg.currency)?;
+                    let flow = flows.get_mut(&flow_id).ok_or_else(|| {
+                        EngineError::KeyNotFound("cash_flow not exists".to_string())
+                    })?;
+                    flow.apply_leg_change(0, leg.amount_minor)?;
                 }
+            }
+        }
 
-                for (flow_id, flow) in &flows {
-                    let flow_model = cash_flows::ActiveModel {
-                        id: ActiveValue::Set(*flow_id),
-                        balance: ActiveValue::Set(flow.balance),
-                        income_balance: ActiveValue::Set(flow.income_balance),
-                        ..Default::default()
-                    };
-                    flow_model.update(db_tx).await?;
-                }
+        // Persist denormalized balances.
+        for (wallet_id, wallet) in &wallets_by_id {
+            let wallet_model = wallets::ActiveModel {
+                id: ActiveValue::Set(*wallet_id),
+                balance: ActiveValue::Set(wallet.balance),
+                ..Default::default()
+            };
+            wallet_model.update(db_tx).await?;
+        }
 
-                Ok(())
-            })
-        })
-        .await
+        for (flow_id, flow) in &flows {
+            let flow_model = cash_flows::ActiveModel {
+                id: ActiveValue::Set(*flow_id),
+                balance: ActiveValue::Set(flow.balance),
+                income_balance: ActiveValue::Set(flow.income_balance),
+                ..Default::default()
+            };
+            flow_model.update(db_tx).await?;
+        }
+
+        Ok(())
     }
 }
+
+/// Audit snapshots of the wallets and flows of a vault, in a stable order.
+async fn balance_snapshots(
+    db_tx: &DatabaseTransaction,
+    vault_uuid: Uuid,
+) -> ResultEngine<Vec<(AuditEntity, Uuid, Option<Json>)>> {
+    let wallet_models = wallets::Entity::find()
+        .filter(wallets::Column::VaultId.eq(vault_uuid))
+        .order_by_asc(wallets::Column::Id)
+        .all(db_tx)
+        .await?;
+    let flow_models = cash_flows::Entity::find()
+        .filter(cash_flows::Column::VaultId.eq(vault_uuid))
+        .order_by_asc(cash_flows::Column::Id)
+        .all(db_tx)
+        .await?;
+    Ok(wallet_models
+        .iter()
+        .map(|model| (AuditEntity::Wallet, model.id, snapshot(model)))
+        .chain(
+            flow_models
+                .iter()
+                .map(|model| (AuditEntity::Flow, model.id, snapshot(model))),
+        )
+        .collect())
+}
+
+/// Sum of the legs of `target` that occurred before `at`.
+async fn balance_before(
+    db_tx: &DatabaseTransaction,
+    vault_uuid: Uuid,
+    target: BalanceTarget,
+    at: DateTime<Utc>,
+) -> ResultEngine<i64> {
+    let sum: Option<i64> = target_legs(vault_uuid, target)
+        .select_only()
+        .column_as(sum_minor((legs::Entity, legs::Column::AmountMinor)), "sum")
+        .filter(transactions::Column::OccurredAt.lt(at))
+        .into_tuple::<Option<i64>>()
+        .one(db_tx)
+        .await?
+        .flatten();
+    Ok(sum.unwrap_or(0))
+}
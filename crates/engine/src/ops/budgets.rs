@@ -0,0 +1,300 @@
+use chrono::{NaiveDate, NaiveTime};
+use sea_orm::{
+    Condition, DatabaseTransaction, JoinType, QueryFilter, QueryOrder, QuerySelect, prelude::*,
+};
+use uuid::Uuid;
+
+use crate::{
+    AuditEntity, Budget, BudgetStatus, CreateBudgetCmd, EngineError, ResultEngine, TransactionKind,
+    UpdateBudgetCmd, audit::AuditChange, budgets, legs, legs::LegTargetKind, transactions,
+};
+
+use super::{
+    Engine,
+    audit::{entity_snapshot, record_audit, snapshot},
+    parse_vault_uuid,
+};
+
+impl Engine {
+    async fn require_budget(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_id: &str,
+        budget_id: Uuid,
+    ) -> ResultEngine<Budget> {
+        let vault_uuid = parse_vault_uuid(vault_id)?;
+        let model = budgets::Entity::find_by_id(budget_id)
+            .filter(budgets::Column::VaultId.eq(vault_uuid))
+            .one(db_tx)
+            .await?
+            .ok_or_else(|| EngineError::KeyNotFound("budget not exists".to_string()))?;
+        Ok(Budget::from(model))
+    }
+
+    /// Computes the status of `budget` for the period containing `on`.
+    ///
+    /// Spending is the net of the flow legs of non-voided expenses and refunds
+    /// in the budget category. Split lines count towards their own category.
+    async fn budget_status_in_tx(
+        &self,
+        db_tx: &DatabaseTransaction,
+        budget: Budget,
+        on: NaiveDate,
+    ) -> ResultEngine<BudgetStatus> {
+        let vault_uuid = parse_vault_uuid(&budget.vault_id)?;
+        let (_, period_start, period_end) = budget.period_containing(on)?;
+        let from = if budget.rollover {
+            budget.starts_on
+        } else {
+            period_start
+        };
+
+        let rows: Vec<(DateTimeUtc, i64)> = legs::Entity::find()
+            .select_only()
+            .column(transactions::Column::OccurredAt)
+            .column(legs::Column::AmountMinor)
+            .join(JoinType::InnerJoin, legs::Relation::Transactions.def())
+            .filter(transactions::Column::VaultId.eq(vault_uuid))
+            .filter(
+                Condition::any()
+                    .add(legs::Column::CategoryId.eq(budget.category_id))
+                    .add(
+                        Condition::all()
+                            .add(legs::Column::CategoryId.is_null())
+                            .add(transactions::Column::CategoryId.eq(budget.category_id)),
+                    ),
+            )
+            .filter(
+                transactions::Column::Kind
+                    .is_in([TransactionKind::Expense, TransactionKind::Refund]),
+            )
+            .filter(transactions::Column::VoidedAt.is_null())
+            .filter(legs::Column::TargetKind.eq(LegTargetKind::Flow))
+            .filter(transactions::Column::OccurredAt.gte(from.and_time(NaiveTime::MIN).and_utc()))
+            .filter(
+                transactions::Column::OccurredAt.lt(period_end.and_time(NaiveTime::MIN).and_utc()),
+            )
+            .into_tuple()
+            .all(db_tx)
+            .await?;
+
+        // Flow legs of expenses are negative; spending is their opposite.
+        let entries: Vec<(NaiveDate, i64)> = rows
+            .into_iter()
+            .map(|(occurred_at, amount_minor)| (occurred_at.date_naive(), -amount_minor))
+            .collect();
+        BudgetStatus::compute(budget, on, &entries)
+    }
+
+    /// Creates a budget for a category. A category has at most one budget.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn create_budget(&self, cmd: CreateBudgetCmd) -> ResultEngine<Budget> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id_write(db_tx, &cmd.vault_id, &cmd.user_id)
+                    .await?;
+                let category = engine
+                    .resolve_category_input(db_tx, &cmd.vault_id, Some(cmd.category_id), None)
+                    .await?;
+                let vault_uuid = parse_vault_uuid(&cmd.vault_id)?;
+                let existing = budgets::Entity::find()
+                    .filter(budgets::Column::VaultId.eq(vault_uuid))
+                    .filter(budgets::Column::CategoryId.eq(category.id))
+                    .one(db_tx)
+                    .await?;
+                if existing.is_some() {
+                    return Err(EngineError::ExistingKey(
+                        "category already has a budget".to_string(),
+                    ));
+                }
+
+                let budget = Budget {
+                    id: Uuid::new_v4(),
+                    vault_id: cmd.vault_id,
+                    category_id: category.id,
+                    amount_minor: cmd.amount_minor,
+                    period: cmd.period,
+                    starts_on: cmd.starts_on,
+                    rollover: cmd.rollover,
+                };
+                budget.validate()?;
+                let model = budgets::ActiveModel::try_from(&budget)?
+                    .insert(db_tx)
+                    .await?;
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    &cmd.user_id,
+                    AuditChange::created(AuditEntity::Budget, budget.id, snapshot(&model)),
+                )
+                .await?;
+                Ok(budget)
+            })
+        })
+        .await
+    }
+
+    /// Lists the budgets of a vault.
+    ///
+    /// Authorization: requires vault read access.
+    pub async fn list_budgets(&self, vault_id: &str, user_id: &str) -> ResultEngine<Vec<Budget>> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id(db_tx, &vault_id, &user_id)
+                    .await?;
+                let vault_uuid = parse_vault_uuid(&vault_id)?;
+                Ok(budgets::Entity::find()
+                    .filter(budgets::Column::VaultId.eq(vault_uuid))
+                    .order_by_asc(budgets::Column::StartsOn)
+                    .order_by_asc(budgets::Column::Id)
+                    .all(db_tx)
+                    .await?
+                    .into_iter()
+                    .map(Budget::from)
+                    .collect())
+            })
+        })
+        .await
+    }
+
+    /// Updates limit, period, anchor or rollover of a budget.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn update_budget(&self, cmd: UpdateBudgetCmd) -> ResultEngine<Budget> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id_write(db_tx, &cmd.vault_id, &cmd.user_id)
+                    .await?;
+                let mut budget = engine
+                    .require_budget(db_tx, &cmd.vault_id, cmd.budget_id)
+                    .await?;
+                let before = entity_snapshot::<budgets::Entity>(db_tx, budget.id).await?;
+                if let Some(amount_minor) = cmd.amount_minor {
+                    budget.amount_minor = amount_minor;
+                }
+                if let Some(period) = cmd.period {
+                    budget.period = period;
+                }
+                if let Some(starts_on) = cmd.starts_on {
+                    budget.starts_on = starts_on;
+                }
+                if let Some(rollover) = cmd.rollover {
+                    budget.rollover = rollover;
+                }
+                budget.validate()?;
+                let model = budgets::ActiveModel::try_from(&budget)?
+                    .update(db_tx)
+                    .await?;
+                record_audit(
+                    db_tx,
+                    model.vault_id,
+                    &cmd.user_id,
+                    AuditChange::updated(AuditEntity::Budget, budget.id, before, snapshot(&model)),
+                )
+                .await?;
+                Ok(budget)
+            })
+        })
+        .await
+    }
+
+    /// Deletes a budget.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn delete_budget(
+        &self,
+        vault_id: &str,
+        budget_id: Uuid,
+        user_id: &str,
+    ) -> ResultEngine<()> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id_write(db_tx, &vault_id, &user_id)
+                    .await?;
+                engine.require_budget(db_tx, &vault_id, budget_id).await?;
+                let before = entity_snapshot::<budgets::Entity>(db_tx, budget_id).await?;
+                budgets::Entity::delete_by_id(budget_id).exec(db_tx).await?;
+                record_audit(
+                    db_tx,
+                    parse_vault_uuid(&vault_id)?,
+                    &user_id,
+                    AuditChange::deleted(AuditEntity::Budget, budget_id, before),
+                )
+                .await?;
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Returns spent vs. limit of a budget for the period containing `on`.
+    ///
+    /// Authorization: requires vault read access.
+    pub async fn budget_status(
+        &self,
+        vault_id: &str,
+        budget_id: Uuid,
+        user_id: &str,
+        on: NaiveDate,
+    ) -> ResultEngine<BudgetStatus> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id(db_tx, &vault_id, &user_id)
+                    .await?;
+                let budget = engine.require_budget(db_tx, &vault_id, budget_id).await?;
+                engine.budget_status_in_tx(db_tx, budget, on).await
+            })
+        })
+        .await
+    }
+
+    /// Returns spent vs. limit of every budget of a vault for the periods
+    /// containing `on`.
+    ///
+    /// Authorization: requires vault read access.
+    pub async fn budget_statuses(
+        &self,
+        vault_id: &str,
+        user_id: &str,
+        on: NaiveDate,
+    ) -> ResultEngine<Vec<BudgetStatus>> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id(db_tx, &vault_id, &user_id)
+                    .await?;
+                let vault_uuid = parse_vault_uuid(&vault_id)?;
+                let models = budgets::Entity::find()
+                    .filter(budgets::Column::VaultId.eq(vault_uuid))
+                    .order_by_asc(budgets::Column::StartsOn)
+                    .order_by_asc(budgets::Column::Id)
+                    .all(db_tx)
+                    .await?;
+                let mut statuses = Vec::with_capacity(models.len());
+                for model in models {
+                    statuses.push(
+                        engine
+                            .budget_status_in_tx(db_tx, Budget::from(model), on)
+                            .await?,
+                    );
+                }
+                Ok(statuses)
+            })
+        })
+        .await
+    }
+}
@@ -1,16 +1,24 @@
 use sea_orm::{
-    ActiveValue, DatabaseTransaction, QueryFilter, QueryOrder,
+    ActiveValue, Condition, DatabaseTransaction, QueryFilter, QueryOrder,
     prelude::*,
-    sea_query::{Expr, Value},
+    sea_query::{Expr, Query, Value},
 };
 use uuid::Uuid;
 
 use crate::{
-    Category, CategoryAlias, EngineError, ResultEngine, categories, category_aliases, transactions,
+    AuditAction, AuditEntity, Category, CategoryAlias, ClearingStatus, EngineError, ResultEngine,
+    audit::AuditChange,
+    budgets, categories, category_aliases, category_rules, legs, transactions,
     util::{normalize_category_display, normalize_category_key},
+    vault,
 };
 
-use super::{Engine, parse_vault_uuid};
+use super::{
+    Engine,
+    audit::{record_audit, snapshot},
+    parse_vault_uuid,
+    transactions::store_revision,
+};
 
 const UNCATEGORIZED_NAME: &str = "Uncategorized";
 const UNCATEGORIZED_NAME_NORM: &str = "uncategorized";
@@ -34,6 +42,10 @@ pub enum CategoryMergeConflictKind {
     TargetArchived,
     Alias,
     Name,
+    /// A transaction to move is reconciled.
+    TransactionLocked,
+    /// A transaction to move occurred in a closed period.
+    BooksClosed,
 }
 
 impl CategoryMergeConflictKind {
@@ -44,10 +56,37 @@ impl CategoryMergeConflictKind {
             Self::TargetArchived => "target_archived",
             Self::Alias => "alias_conflict",
             Self::Name => "name_conflict",
+            Self::TransactionLocked => "transaction_locked",
+            Self::BooksClosed => "books_closed",
         }
     }
 }
 
+/// Transactions of the vault booked on `category_id`, directly or on a split
+/// line.
+async fn transactions_in_category(
+    db_tx: &DatabaseTransaction,
+    vault_uuid: Uuid,
+    category_id: Uuid,
+) -> ResultEngine<Vec<transactions::Model>> {
+    let split_lines = Query::select()
+        .column(legs::Column::TransactionId)
+        .from(legs::Entity)
+        .and_where(legs::Column::CategoryId.eq(category_id))
+        .to_owned();
+    Ok(transactions::Entity::find()
+        .filter(transactions::Column::VaultId.eq(vault_uuid))
+        .filter(
+            Condition::any()
+                .add(transactions::Column::CategoryId.eq(category_id))
+                .add(transactions::Column::Id.in_subquery(split_lines)),
+        )
+        .order_by_asc(transactions::Column::OccurredAt)
+        .order_by_asc(transactions::Column::Id)
+        .all(db_tx)
+        .await?)
+}
+
 pub(super) struct CategorySelection {
     pub(super) id: Uuid,
     pub(super) name: Option<String>,
@@ -149,6 +188,13 @@ impl Engine {
                     is_system: ActiveValue::Set(false),
                 };
                 let model = active.insert(db_tx).await?;
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    user_id.as_str(),
+                    AuditChange::created(AuditEntity::Category, id, snapshot(&model)),
+                )
+                .await?;
                 Ok(Category::from(model))
             })
         })
@@ -227,7 +273,19 @@ impl Engine {
                     archived: ActiveValue::Set(archived),
                     ..Default::default()
                 };
-                active.update(db_tx).await?;
+                let updated = active.update(db_tx).await?;
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    user_id.as_str(),
+                    AuditChange::updated(
+                        AuditEntity::Category,
+                        category_id,
+                        snapshot(&model),
+                        snapshot(&updated),
+                    ),
+                )
+                .await?;
 
                 if name_display != model.name {
                     transactions::Entity::update_many()
@@ -352,6 +410,13 @@ impl Engine {
                     alias_norm: ActiveValue::Set(normalized),
                 };
                 let model = active.insert(db_tx).await?;
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    user_id.as_str(),
+                    AuditChange::created(AuditEntity::CategoryAlias, model.id, snapshot(&model)),
+                )
+                .await?;
                 Ok(CategoryAlias::from(model))
             })
         })
@@ -377,15 +442,22 @@ impl Engine {
                     .require_category_in_vault(db_tx, vault_uuid, category_id)
                     .await?;
 
-                let result = category_aliases::Entity::delete_many()
-                    .filter(category_aliases::Column::Id.eq(alias_id))
+                let model = category_aliases::Entity::find_by_id(alias_id)
                     .filter(category_aliases::Column::CategoryId.eq(category_id))
                     .filter(category_aliases::Column::VaultId.eq(vault_uuid))
+                    .one(db_tx)
+                    .await?
+                    .ok_or_else(|| EngineError::KeyNotFound("alias not exists".to_string()))?;
+                category_aliases::Entity::delete_by_id(alias_id)
                     .exec(db_tx)
                     .await?;
-                if result.rows_affected == 0 {
-                    return Err(EngineError::KeyNotFound("alias not exists".to_string()));
-                }
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    user_id.as_str(),
+                    AuditChange::deleted(AuditEntity::CategoryAlias, alias_id, snapshot(&model)),
+                )
+                .await?;
                 Ok(())
             })
         })
@@ -403,12 +475,11 @@ impl Engine {
         let user_id = user_id.to_string();
         self.with_tx(|engine, db_tx| {
             Box::pin(async move {
-                engine
+                let vault_model = engine
                     .require_vault_by_id_write(db_tx, vault_id.as_str(), user_id.as_str())
                     .await?;
-                let vault_uuid = parse_vault_uuid(vault_id.as_str())?;
                 let context = engine
-                    .merge_context(db_tx, vault_uuid, from_category_id, into_category_id)
+                    .merge_context(db_tx, &vault_model, from_category_id, into_category_id)
                     .await?;
                 Ok(CategoryMergePreview {
                     ok: context.conflicts.is_empty(),
@@ -430,13 +501,13 @@ impl Engine {
         let user_id = user_id.to_string();
         self.with_tx(|engine, db_tx| {
             Box::pin(async move {
-                engine
+                let vault_model = engine
                     .require_vault_by_id_write(db_tx, vault_id.as_str(), user_id.as_str())
                     .await?;
-                let vault_uuid = parse_vault_uuid(vault_id.as_str())?;
+                let vault_uuid = vault_model.id;
 
                 let context = engine
-                    .merge_context(db_tx, vault_uuid, from_category_id, into_category_id)
+                    .merge_context(db_tx, &vault_model, from_category_id, into_category_id)
                     .await?;
                 if let Some(conflict) = context.conflicts.first() {
                     return Err(Self::merge_conflict_error(conflict));
@@ -450,6 +521,12 @@ impl Engine {
                     Value::String(Some(Box::new(context.into.name.clone())))
                 };
 
+                // Keep the previous category of every moved transaction, split
+                // lines included, as a revision.
+                for tx_model in &context.moved {
+                    store_revision(db_tx, tx_model, user_id.as_str()).await?;
+                }
+
                 transactions::Entity::update_many()
                     .col_expr(
                         transactions::Column::CategoryId,
@@ -474,6 +551,42 @@ impl Engine {
                         .await?;
                 }
 
+                legs::Entity::update_many()
+                    .col_expr(legs::Column::CategoryId, Expr::value(into_category_id))
+                    .filter(legs::Column::CategoryId.eq(from_category_id))
+                    .exec(db_tx)
+                    .await?;
+
+                category_rules::Entity::update_many()
+                    .col_expr(
+                        category_rules::Column::CategoryId,
+                        Expr::value(into_category_id),
+                    )
+                    .filter(category_rules::Column::CategoryId.eq(from_category_id))
+                    .exec(db_tx)
+                    .await?;
+
+                // The budget follows the merged category unless the target
+                // already has one, which then wins.
+                let into_has_budget = budgets::Entity::find()
+                    .filter(budgets::Column::VaultId.eq(vault_uuid))
+                    .filter(budgets::Column::CategoryId.eq(into_category_id))
+                    .one(db_tx)
+                    .await?
+                    .is_some();
+                if into_has_budget {
+                    budgets::Entity::delete_many()
+                        .filter(budgets::Column::CategoryId.eq(from_category_id))
+                        .exec(db_tx)
+                        .await?;
+                } else {
+                    budgets::Entity::update_many()
+                        .col_expr(budgets::Column::CategoryId, Expr::value(into_category_id))
+                        .filter(budgets::Column::CategoryId.eq(from_category_id))
+                        .exec(db_tx)
+                        .await?;
+                }
+
                 if context.from.name_norm != context.into.name_norm {
                     let alias_active = category_aliases::ActiveModel {
                         id: ActiveValue::Set(Uuid::new_v4()),
@@ -490,7 +603,20 @@ impl Engine {
                     archived: ActiveValue::Set(true),
                     ..Default::default()
                 };
-                active.update(db_tx).await?;
+                let from = active.update(db_tx).await?;
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    user_id.as_str(),
+                    AuditChange::new(
+                        AuditAction::Merge,
+                        AuditEntity::Category,
+                        from_category_id,
+                        snapshot(&from),
+                        snapshot(&context.into),
+                    ),
+                )
+                .await?;
 
                 Ok(Category {
                     id: context.into.id,
@@ -506,10 +632,11 @@ impl Engine {
     async fn merge_context(
         &self,
         db_tx: &DatabaseTransaction,
-        vault_uuid: Uuid,
+        vault_model: &vault::Model,
         from_category_id: Uuid,
         into_category_id: Uuid,
     ) -> ResultEngine<MergeContext> {
+        let vault_uuid = vault_model.id;
         let from = self
             .require_category_in_vault(db_tx, vault_uuid, from_category_id)
             .await?;
@@ -537,6 +664,24 @@ impl Engine {
             });
         }
 
+        let moved = transactions_in_category(db_tx, vault_uuid, from_category_id).await?;
+        for tx_model in &moved {
+            if tx_model.clearing_status == ClearingStatus::Reconciled {
+                conflicts.push(CategoryMergeConflict {
+                    kind: CategoryMergeConflictKind::TransactionLocked,
+                    value: tx_model.id.to_string(),
+                });
+            }
+        }
+        if let Some(until) = vault_model.books_closed_until
+            && moved.iter().any(|tx_model| tx_model.occurred_at < until)
+        {
+            conflicts.push(CategoryMergeConflict {
+                kind: CategoryMergeConflictKind::BooksClosed,
+                value: until.to_rfc3339(),
+            });
+        }
+
         let mut reserved: std::collections::HashSet<String> = std::collections::HashSet::new();
         reserved.insert(into.name_norm.clone());
         let target_aliases = category_aliases::Entity::find()
@@ -573,6 +718,7 @@ impl Engine {
             from,
             into,
             from_aliases,
+            moved,
             conflicts,
         })
     }
@@ -591,6 +737,15 @@ impl Engine {
             CategoryMergeConflictKind::Alias | CategoryMergeConflictKind::Name => {
                 EngineError::ExistingKey(conflict.value.clone())
             }
+            CategoryMergeConflictKind::TransactionLocked => {
+                EngineError::TransactionLocked(format!(
+                    "transaction {} is reconciled, unlock it first",
+                    conflict.value
+                ))
+            }
+            CategoryMergeConflictKind::BooksClosed => {
+                EngineError::BooksClosed(format!("books are closed until {}", conflict.value))
+            }
         }
     }
 
@@ -672,6 +827,21 @@ impl Engine {
         })
     }
 
+    /// Id of the system "Uncategorized" category, if the vault has one yet.
+    pub(super) async fn uncategorized_category_id(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_id: &str,
+    ) -> ResultEngine<Option<Uuid>> {
+        let vault_uuid = parse_vault_uuid(vault_id)?;
+        Ok(categories::Entity::find()
+            .filter(categories::Column::VaultId.eq(vault_uuid))
+            .filter(categories::Column::NameNorm.eq(UNCATEGORIZED_NAME_NORM))
+            .one(db_tx)
+            .await?
+            .map(|model| model.id))
+    }
+
     async fn uncategorized_category(
         &self,
         db_tx: &DatabaseTransaction,
@@ -795,6 +965,8 @@ struct MergeContext {
     from: categories::Model,
     into: categories::Model,
     from_aliases: Vec<category_aliases::Model>,
+    /// Transactions moved to `into`, split lines included.
+    moved: Vec<transactions::Model>,
     conflicts: Vec<CategoryMergeConflict>,
 }
 
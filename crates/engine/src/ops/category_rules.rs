@@ -0,0 +1,485 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use sea_orm::{
+    ActiveValue, Condition, DatabaseTransaction, JoinType, QueryFilter, QueryOrder, QuerySelect,
+    prelude::*,
+};
+use uuid::Uuid;
+
+use crate::{
+    ApplyCategoryRulesCmd, AuditEntity, CategoryRule, CategoryRuleHit, CategoryRuleSkip,
+    ClearingStatus, CreateCategoryRuleCmd, EngineError, ResultEngine, TransactionKind,
+    UpdateCategoryRuleCmd,
+    audit::AuditChange,
+    categories,
+    category_rules::{self, CompiledRule, RuleSubject, first_match},
+    legs,
+    legs::LegTargetKind,
+    transactions, vault,
+};
+
+use super::{
+    Engine,
+    audit::{entity_snapshot, record_audit, snapshot, transaction_snapshot},
+    categories::CategorySelection,
+    parse_vault_uuid,
+    transactions::store_revision,
+};
+
+impl Engine {
+    async fn require_category_rule(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_id: &str,
+        rule_id: Uuid,
+    ) -> ResultEngine<CategoryRule> {
+        let vault_uuid = parse_vault_uuid(vault_id)?;
+        let model = category_rules::Entity::find_by_id(rule_id)
+            .filter(category_rules::Column::VaultId.eq(vault_uuid))
+            .one(db_tx)
+            .await?
+            .ok_or_else(|| EngineError::KeyNotFound("category rule not exists".to_string()))?;
+        Ok(CategoryRule::from(model))
+    }
+
+    /// Normalizes the conditions of `rule` and checks that its category,
+    /// wallet and flow belong to the vault.
+    async fn validate_category_rule(
+        &self,
+        db_tx: &DatabaseTransaction,
+        rule: &mut CategoryRule,
+    ) -> ResultEngine<()> {
+        rule.conditions = std::mem::take(&mut rule.conditions).normalize()?;
+        self.resolve_category_input(db_tx, &rule.vault_id, Some(rule.category_id), None)
+            .await?;
+        if let Some(wallet_id) = rule.conditions.wallet_id {
+            self.require_wallet_in_vault(db_tx, &rule.vault_id, wallet_id)
+                .await?;
+        }
+        if let Some(flow_id) = rule.conditions.flow_id {
+            self.require_flow_in_vault(db_tx, &rule.vault_id, flow_id)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Loads the rules of a vault in evaluation order. Rules targeting an
+    /// archived category are skipped.
+    pub(super) async fn load_category_rules(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_id: &str,
+    ) -> ResultEngine<Vec<CompiledRule>> {
+        let vault_uuid = parse_vault_uuid(vault_id)?;
+        category_rules::Entity::find()
+            .join(
+                JoinType::InnerJoin,
+                category_rules::Relation::Category.def(),
+            )
+            .filter(category_rules::Column::VaultId.eq(vault_uuid))
+            .filter(categories::Column::Archived.eq(false))
+            .order_by_asc(category_rules::Column::Priority)
+            .order_by_asc(category_rules::Column::CreatedAt)
+            .order_by_asc(category_rules::Column::Id)
+            .all(db_tx)
+            .await?
+            .into_iter()
+            .map(|model| CompiledRule::new(CategoryRule::from(model)))
+            .collect()
+    }
+
+    /// Category the first matching rule assigns to `subject`, if any.
+    pub(super) async fn categorize_by_rules(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_id: &str,
+        rules: &[CompiledRule],
+        subject: &RuleSubject<'_>,
+    ) -> ResultEngine<Option<CategorySelection>> {
+        let Some(rule) = first_match(rules, subject) else {
+            return Ok(None);
+        };
+        self.resolve_category_input(db_tx, vault_id, Some(rule.category_id), None)
+            .await
+            .map(Some)
+    }
+
+    /// Resolves the given category or, when neither `category_id` nor a
+    /// non-blank `category` is given, the one assigned by the first rule
+    /// matching `subject` (Uncategorized when none does).
+    pub(super) async fn resolve_category_with_rules(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_id: &str,
+        (category_id, category): (Option<Uuid>, Option<&str>),
+        rules: &[CompiledRule],
+        subject: &RuleSubject<'_>,
+    ) -> ResultEngine<CategorySelection> {
+        let given = category_id.is_some() || category.is_some_and(|name| !name.trim().is_empty());
+        if !given
+            && let Some(selection) = self
+                .categorize_by_rules(db_tx, vault_id, rules, subject)
+                .await?
+        {
+            return Ok(selection);
+        }
+        self.resolve_category_input(db_tx, vault_id, category_id, category)
+            .await
+    }
+
+    /// Matches the rules against non-voided incomes, expenses and refunds
+    /// (split expenses excluded) and returns the transactions whose category
+    /// would change.
+    async fn category_rule_hits_in_tx(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_model: &vault::Model,
+        cmd: &ApplyCategoryRulesCmd,
+    ) -> ResultEngine<Vec<CategoryRuleHit>> {
+        let rules = self.load_category_rules(db_tx, &cmd.vault_id).await?;
+        if rules.is_empty() {
+            return Ok(Vec::new());
+        }
+        let uncategorized = if cmd.include_categorized {
+            None
+        } else {
+            match self.uncategorized_category_id(db_tx, &cmd.vault_id).await? {
+                Some(id) => Some(id),
+                // Nothing was ever left uncategorized.
+                None => return Ok(Vec::new()),
+            }
+        };
+
+        let vault_uuid = parse_vault_uuid(&cmd.vault_id)?;
+        let mut condition = Condition::all()
+            .add(transactions::Column::VaultId.eq(vault_uuid))
+            .add(transactions::Column::Kind.is_in([
+                TransactionKind::Income,
+                TransactionKind::Expense,
+                TransactionKind::Refund,
+            ]))
+            .add(transactions::Column::VoidedAt.is_null());
+        if let Some(from) = cmd.from {
+            condition = condition.add(transactions::Column::OccurredAt.gte(from));
+        }
+        if let Some(to) = cmd.to {
+            condition = condition.add(transactions::Column::OccurredAt.lt(to));
+        }
+        if let Some(uncategorized) = uncategorized {
+            condition = condition.add(transactions::Column::CategoryId.eq(uncategorized));
+        }
+
+        let tx_models = transactions::Entity::find()
+            .filter(condition.clone())
+            .order_by_asc(transactions::Column::OccurredAt)
+            .order_by_asc(transactions::Column::Id)
+            .all(db_tx)
+            .await?;
+        let leg_rows: Vec<(Uuid, LegTargetKind, Uuid)> = legs::Entity::find()
+            .select_only()
+            .column(legs::Column::TransactionId)
+            .column(legs::Column::TargetKind)
+            .column(legs::Column::TargetId)
+            .join(JoinType::InnerJoin, legs::Relation::Transactions.def())
+            .filter(condition)
+            .into_tuple()
+            .all(db_tx)
+            .await?;
+        let mut targets: HashMap<Uuid, (Option<Uuid>, Option<Uuid>)> = HashMap::new();
+        // Split expenses are categorized per line, never by rules.
+        let mut splits: HashSet<Uuid> = HashSet::new();
+        for (tx_id, kind, target_id) in leg_rows {
+            let entry = targets.entry(tx_id).or_default();
+            match kind {
+                LegTargetKind::Wallet => entry.0 = Some(target_id),
+                LegTargetKind::Flow => {
+                    if entry.1.replace(target_id).is_some() {
+                        splits.insert(tx_id);
+                    }
+                }
+            }
+        }
+
+        let mut hits = Vec::new();
+        for model in tx_models {
+            if splits.contains(&model.id) {
+                continue;
+            }
+            let (wallet_id, flow_id) = targets.get(&model.id).copied().unwrap_or_default();
+            let subject = RuleSubject {
+                kind: model.kind,
+                amount_minor: model.amount_minor,
+                wallet_id,
+                flow_id,
+                note: model.note.as_deref(),
+            };
+            let Some(rule) = first_match(&rules, &subject) else {
+                continue;
+            };
+            if rule.category_id == model.category_id {
+                continue;
+            }
+            let skipped = if model.clearing_status == ClearingStatus::Reconciled {
+                Some(CategoryRuleSkip::Reconciled)
+            } else if vault_model.ensure_period_open(model.occurred_at).is_err() {
+                Some(CategoryRuleSkip::BooksClosed)
+            } else {
+                None
+            };
+            hits.push(CategoryRuleHit {
+                transaction_id: model.id,
+                occurred_at: model.occurred_at,
+                kind: model.kind,
+                amount_minor: model.amount_minor,
+                note: model.note,
+                previous_category_id: model.category_id,
+                rule_id: rule.id,
+                category_id: rule.category_id,
+                skipped,
+            });
+        }
+        Ok(hits)
+    }
+
+    /// Creates a categorization rule.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn create_category_rule(
+        &self,
+        cmd: CreateCategoryRuleCmd,
+    ) -> ResultEngine<CategoryRule> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id_write(db_tx, &cmd.vault_id, &cmd.user_id)
+                    .await?;
+                let mut rule = CategoryRule {
+                    id: Uuid::new_v4(),
+                    vault_id: cmd.vault_id,
+                    category_id: cmd.category_id,
+                    priority: cmd.priority,
+                    conditions: cmd.conditions,
+                    created_at: Utc::now(),
+                };
+                engine.validate_category_rule(db_tx, &mut rule).await?;
+                let model = category_rules::ActiveModel::try_from(&rule)?
+                    .insert(db_tx)
+                    .await?;
+                record_audit(
+                    db_tx,
+                    model.vault_id,
+                    &cmd.user_id,
+                    AuditChange::created(AuditEntity::CategoryRule, rule.id, snapshot(&model)),
+                )
+                .await?;
+                Ok(rule)
+            })
+        })
+        .await
+    }
+
+    /// Lists the categorization rules of a vault in evaluation order.
+    ///
+    /// Authorization: requires vault read access.
+    pub async fn list_category_rules(
+        &self,
+        vault_id: &str,
+        user_id: &str,
+    ) -> ResultEngine<Vec<CategoryRule>> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id(db_tx, &vault_id, &user_id)
+                    .await?;
+                let vault_uuid = parse_vault_uuid(&vault_id)?;
+                Ok(category_rules::Entity::find()
+                    .filter(category_rules::Column::VaultId.eq(vault_uuid))
+                    .order_by_asc(category_rules::Column::Priority)
+                    .order_by_asc(category_rules::Column::CreatedAt)
+                    .order_by_asc(category_rules::Column::Id)
+                    .all(db_tx)
+                    .await?
+                    .into_iter()
+                    .map(CategoryRule::from)
+                    .collect())
+            })
+        })
+        .await
+    }
+
+    /// Updates category, priority or conditions of a rule.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn update_category_rule(
+        &self,
+        cmd: UpdateCategoryRuleCmd,
+    ) -> ResultEngine<CategoryRule> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id_write(db_tx, &cmd.vault_id, &cmd.user_id)
+                    .await?;
+                let mut rule = engine
+                    .require_category_rule(db_tx, &cmd.vault_id, cmd.rule_id)
+                    .await?;
+                let before = entity_snapshot::<category_rules::Entity>(db_tx, rule.id).await?;
+                if let Some(category_id) = cmd.category_id {
+                    rule.category_id = category_id;
+                }
+                if let Some(priority) = cmd.priority {
+                    rule.priority = priority;
+                }
+                if let Some(conditions) = cmd.conditions {
+                    rule.conditions = conditions;
+                }
+                engine.validate_category_rule(db_tx, &mut rule).await?;
+                let model = category_rules::ActiveModel::try_from(&rule)?
+                    .update(db_tx)
+                    .await?;
+                record_audit(
+                    db_tx,
+                    model.vault_id,
+                    &cmd.user_id,
+                    AuditChange::updated(
+                        AuditEntity::CategoryRule,
+                        rule.id,
+                        before,
+                        snapshot(&model),
+                    ),
+                )
+                .await?;
+                Ok(rule)
+            })
+        })
+        .await
+    }
+
+    /// Deletes a categorization rule.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn delete_category_rule(
+        &self,
+        vault_id: &str,
+        rule_id: Uuid,
+        user_id: &str,
+    ) -> ResultEngine<()> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id_write(db_tx, &vault_id, &user_id)
+                    .await?;
+                engine
+                    .require_category_rule(db_tx, &vault_id, rule_id)
+                    .await?;
+                let before = entity_snapshot::<category_rules::Entity>(db_tx, rule_id).await?;
+                category_rules::Entity::delete_by_id(rule_id)
+                    .exec(db_tx)
+                    .await?;
+                record_audit(
+                    db_tx,
+                    parse_vault_uuid(&vault_id)?,
+                    &user_id,
+                    AuditChange::deleted(AuditEntity::CategoryRule, rule_id, before),
+                )
+                .await?;
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Dry run: returns the booked transactions the rules would
+    /// recategorize, flagging those that would be skipped. Nothing is written.
+    ///
+    /// Authorization: requires vault read access.
+    pub async fn preview_category_rules(
+        &self,
+        cmd: ApplyCategoryRulesCmd,
+    ) -> ResultEngine<Vec<CategoryRuleHit>> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let vault_model = engine
+                    .require_vault_by_id(db_tx, &cmd.vault_id, &cmd.user_id)
+                    .await?;
+                engine
+                    .category_rule_hits_in_tx(db_tx, &vault_model, &cmd)
+                    .await
+            })
+        })
+        .await
+    }
+
+    /// Recategorizes booked transactions with the rules, atomically, and
+    /// returns the hits. Skipped hits (see [`CategoryRuleSkip`]) are left
+    /// unchanged, so old locked or closed transactions don't block the rest. Balances
+    /// are unaffected; each change is kept as a transaction revision, like an
+    /// update.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn apply_category_rules(
+        &self,
+        cmd: ApplyCategoryRulesCmd,
+    ) -> ResultEngine<Vec<CategoryRuleHit>> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let vault_model = engine
+                    .require_vault_by_id_write(db_tx, &cmd.vault_id, &cmd.user_id)
+                    .await?;
+                let hits = engine
+                    .category_rule_hits_in_tx(db_tx, &vault_model, &cmd)
+                    .await?;
+                let vault_uuid = parse_vault_uuid(&cmd.vault_id)?;
+                let mut names: HashMap<Uuid, Option<String>> = HashMap::new();
+                for hit in hits.iter().filter(|hit| hit.skipped.is_none()) {
+                    if !names.contains_key(&hit.category_id) {
+                        let category = engine
+                            .resolve_category_input(
+                                db_tx,
+                                &cmd.vault_id,
+                                Some(hit.category_id),
+                                None,
+                            )
+                            .await?;
+                        names.insert(category.id, category.name);
+                    }
+                    let tx_model = transactions::Entity::find_by_id(hit.transaction_id)
+                        .one(db_tx)
+                        .await?
+                        .ok_or_else(|| {
+                            EngineError::KeyNotFound("transaction not exists".to_string())
+                        })?;
+                    let before = transaction_snapshot(db_tx, hit.transaction_id).await?;
+                    store_revision(db_tx, &tx_model, &cmd.user_id).await?;
+                    transactions::ActiveModel {
+                        id: ActiveValue::Set(hit.transaction_id),
+                        category_id: ActiveValue::Set(hit.category_id),
+                        category: ActiveValue::Set(names.get(&hit.category_id).cloned().flatten()),
+                        ..Default::default()
+                    }
+                    .update(db_tx)
+                    .await?;
+                    let after = transaction_snapshot(db_tx, hit.transaction_id).await?;
+                    record_audit(
+                        db_tx,
+                        vault_uuid,
+                        &cmd.user_id,
+                        AuditChange::updated(
+                            AuditEntity::Transaction,
+                            hit.transaction_id,
+                            before,
+                            after,
+                        ),
+                    )
+                    .await?;
+                }
+                Ok(hits)
+            })
+        })
+        .await
+    }
+}
@@ -1,15 +1,28 @@
-use chrono::Utc;
+use std::collections::HashMap;
+
+use chrono::{Months, NaiveDate, NaiveTime, Utc};
 use uuid::Uuid;
 
-use sea_orm::{ActiveValue, QueryFilter, Statement, prelude::*, sea_query::Expr};
+use sea_orm::{
+    ActiveValue, JoinType, QueryFilter, QueryOrder, QuerySelect,
+    prelude::*,
+    sea_query::{Expr, Query},
+};
 
 use crate::{
-    CashFlow, EngineError, ResultEngine, TransactionKind, cash_flows,
+    AuditEntity, CONTRIBUTION_WINDOW_MONTHS, CashFlow, EngineError, FlowLimit, GoalProgress,
+    ResultEngine, TransactionKind,
+    audit::AuditChange,
+    cash_flows, goals, legs, transactions,
     util::{normalize_required_name, validate_flow_mode_fields},
     vault,
 };
 
-use super::{Engine, build_transaction, parse_vault_uuid, transfer_flow_legs};
+use super::{
+    Engine,
+    audit::{record_audit, snapshot},
+    build_transaction, parse_vault_uuid, sum_minor, transfer_flow_legs,
+};
 
 impl Engine {
     /// Return a [`CashFlow`] (snapshot from DB).
@@ -120,18 +133,22 @@ impl Engine {
                     }));
                 }
 
-                if archive {
+                let before = snapshot(&flow_model);
+                let change = if archive {
                     let flow_model = cash_flows::ActiveModel {
                         id: ActiveValue::Set(cash_flow_id),
                         archived: ActiveValue::Set(true),
                         ..Default::default()
                     };
-                    flow_model.update(db_tx).await?;
+                    let model = flow_model.update(db_tx).await?;
+                    AuditChange::updated(AuditEntity::Flow, cash_flow_id, before, snapshot(&model))
                 } else {
                     cash_flows::Entity::delete_by_id(cash_flow_id)
                         .exec(db_tx)
                         .await?;
-                }
+                    AuditChange::deleted(AuditEntity::Flow, cash_flow_id, before)
+                };
+                record_audit(db_tx, vault_uuid, user_id.as_str(), change).await?;
 
                 Ok(())
             })
@@ -199,7 +216,14 @@ impl Engine {
                 let flow_id = flow.id;
                 let mut flow_model: cash_flows::ActiveModel = (&flow).into();
                 flow_model.vault_id = ActiveValue::Set(vault_uuid);
-                flow_model.insert(db_tx).await?;
+                let model = flow_model.insert(db_tx).await?;
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    user_id.as_str(),
+                    AuditChange::created(AuditEntity::Flow, flow_id, snapshot(&model)),
+                )
+                .await?;
 
                 if balance > 0 {
                     let unallocated_flow_id =
@@ -219,6 +243,7 @@ impl Engine {
                         created_by: user_id.as_str(),
                         idempotency_key: None,
                         refunded_transaction_id: None,
+                        conversion: None,
                     })?;
                     let legs = transfer_flow_legs(
                         tx.id,
@@ -290,7 +315,19 @@ impl Engine {
                     name: ActiveValue::Set(new_name),
                     ..Default::default()
                 };
-                active.update(db_tx).await?;
+                let model = active.update(db_tx).await?;
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    user_id.as_str(),
+                    AuditChange::updated(
+                        AuditEntity::Flow,
+                        flow_id,
+                        snapshot(&flow_model),
+                        snapshot(&model),
+                    ),
+                )
+                .await?;
                 Ok(())
             })
         })
@@ -325,7 +362,19 @@ impl Engine {
                     archived: ActiveValue::Set(archived),
                     ..Default::default()
                 };
-                active.update(db_tx).await?;
+                let model = active.update(db_tx).await?;
+                record_audit(
+                    db_tx,
+                    flow_model.vault_id,
+                    user_id.as_str(),
+                    AuditChange::updated(
+                        AuditEntity::Flow,
+                        flow_id,
+                        snapshot(&flow_model),
+                        snapshot(&model),
+                    ),
+                )
+                .await?;
                 Ok(())
             })
         })
@@ -379,35 +428,47 @@ impl Engine {
                     None => (None, None),
                     Some(cap_minor) if !income_capped => {
                         if flow_model.balance > cap_minor {
-                            return Err(EngineError::MaxBalanceReached(flow_name));
+                            return Err(EngineError::MaxBalanceReached(FlowLimit {
+                                flow_id,
+                                flow_name,
+                                currency: flow_model.currency,
+                                limit_minor: cap_minor,
+                                attempted_minor: flow_model.balance,
+                            }));
                         }
                         (Some(cap_minor), None)
                     }
                     Some(cap_minor) => {
                         let vault_uuid = parse_vault_uuid(vault_id.as_str())?;
-                        let vault_bytes: Vec<u8> = vault_uuid.as_bytes().to_vec();
-                        let flow_bytes: Vec<u8> = flow_id.as_bytes().to_vec();
-                        let stmt = Statement::from_sql_and_values(
-                            db_tx.get_database_backend(),
-                            "SELECT COALESCE(SUM(l.amount_minor), 0) AS sum \
-                         FROM legs l \
-                         JOIN transactions t ON t.id = l.transaction_id \
-                         WHERE t.vault_id = ? \
-                           AND t.voided_at IS NULL \
-                           AND l.target_kind = ? \
-                           AND l.target_id = ? \
-                           AND l.amount_minor > 0",
-                            vec![
-                                vault_bytes.into(),
-                                crate::legs::LegTargetKind::Flow.as_str().into(),
-                                flow_bytes.into(),
-                            ],
-                        );
-                        let row = db_tx.query_one(stmt).await?;
-                        let income_total_minor =
-                            row.and_then(|r| r.try_get("", "sum").ok()).unwrap_or(0);
+                        let income_total_minor: i64 = legs::Entity::find()
+                            .select_only()
+                            .column_as(sum_minor(legs::Column::AmountMinor), "sum")
+                            .filter(
+                                legs::Column::TransactionId.in_subquery(
+                                    Query::select()
+                                        .column(transactions::Column::Id)
+                                        .from(transactions::Entity)
+                                        .and_where(transactions::Column::VaultId.eq(vault_uuid))
+                                        .and_where(transactions::Column::VoidedAt.is_null())
+                                        .to_owned(),
+                                ),
+                            )
+                            .filter(legs::Column::TargetKind.eq(legs::LegTargetKind::Flow))
+                            .filter(legs::Column::TargetId.eq(flow_id))
+                            .filter(legs::Column::AmountMinor.gt(0))
+                            .into_tuple::<Option<i64>>()
+                            .one(db_tx)
+                            .await?
+                            .flatten()
+                            .unwrap_or(0);
                         if income_total_minor > cap_minor {
-                            return Err(EngineError::MaxBalanceReached(flow_name));
+                            return Err(EngineError::MaxBalanceReached(FlowLimit {
+                                flow_id,
+                                flow_name,
+                                currency: flow_model.currency,
+                                limit_minor: cap_minor,
+                                attempted_minor: income_total_minor,
+                            }));
                         }
                         (Some(cap_minor), Some(income_total_minor))
                     }
@@ -421,10 +482,147 @@ impl Engine {
                     income_balance: ActiveValue::Set(income_balance),
                     ..Default::default()
                 };
-                active.update(db_tx).await?;
+                let model = active.update(db_tx).await?;
+                record_audit(
+                    db_tx,
+                    flow_model.vault_id,
+                    user_id.as_str(),
+                    AuditChange::updated(
+                        AuditEntity::Flow,
+                        flow_id,
+                        snapshot(&flow_model),
+                        snapshot(&model),
+                    ),
+                )
+                .await?;
                 Ok(())
             })
         })
         .await
     }
+
+    /// Sets or clears the savings goal of a cash flow.
+    ///
+    /// `target_date` is only accepted together with `target_minor`.
+    ///
+    /// Authorization: requires flow write access.
+    pub async fn set_cash_flow_goal(
+        &self,
+        vault_id: &str,
+        flow_id: Uuid,
+        target_minor: Option<i64>,
+        target_date: Option<NaiveDate>,
+        user_id: &str,
+    ) -> ResultEngine<()> {
+        goals::validate_goal(target_minor, target_date)?;
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let flow_model = engine
+                    .require_flow_write(db_tx, vault_id.as_str(), flow_id, user_id.as_str())
+                    .await?;
+                if flow_model.system_kind.is_some() {
+                    return Err(EngineError::InvalidFlow(
+                        "cannot set a goal on system flow".to_string(),
+                    ));
+                }
+
+                let active = cash_flows::ActiveModel {
+                    id: ActiveValue::Set(flow_id),
+                    target_minor: ActiveValue::Set(target_minor),
+                    target_date: ActiveValue::Set(target_date),
+                    ..Default::default()
+                };
+                let model = active.update(db_tx).await?;
+                record_audit(
+                    db_tx,
+                    flow_model.vault_id,
+                    user_id.as_str(),
+                    AuditChange::updated(
+                        AuditEntity::Flow,
+                        flow_id,
+                        snapshot(&flow_model),
+                        snapshot(&model),
+                    ),
+                )
+                .await?;
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Returns the goal progress of every flow of a vault that has a goal,
+    /// as of the end of `on` (UTC).
+    ///
+    /// The monthly contribution averages the net change of each flow over
+    /// the [`CONTRIBUTION_WINDOW_MONTHS`] months up to `on`, voided
+    /// transactions excluded.
+    ///
+    /// Authorization: requires vault read access.
+    pub async fn flow_goals(
+        &self,
+        vault_id: &str,
+        user_id: &str,
+        on: NaiveDate,
+    ) -> ResultEngine<Vec<GoalProgress>> {
+        let out_of_range = || EngineError::InvalidAmount("date out of range".to_string());
+        let from = on
+            .checked_sub_months(Months::new(CONTRIBUTION_WINDOW_MONTHS))
+            .ok_or_else(out_of_range)?
+            .and_time(NaiveTime::MIN)
+            .and_utc();
+        let to = on
+            .succ_opt()
+            .ok_or_else(out_of_range)?
+            .and_time(NaiveTime::MIN)
+            .and_utc();
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let vault_model = engine
+                    .require_vault_by_id(db_tx, vault_id.as_str(), user_id.as_str())
+                    .await?;
+                let flow_models: Vec<cash_flows::Model> = cash_flows::Entity::find()
+                    .filter(cash_flows::Column::VaultId.eq(vault_model.id))
+                    .filter(cash_flows::Column::TargetMinor.is_not_null())
+                    .order_by_asc(cash_flows::Column::Name)
+                    .all(db_tx)
+                    .await?;
+                if flow_models.is_empty() {
+                    return Ok(Vec::new());
+                }
+
+                let changes: HashMap<Uuid, i64> = legs::Entity::find()
+                    .select_only()
+                    .column(legs::Column::TargetId)
+                    .column_as(sum_minor((legs::Entity, legs::Column::AmountMinor)), "sum")
+                    .join(JoinType::InnerJoin, legs::Relation::Transactions.def())
+                    .filter(transactions::Column::VaultId.eq(vault_model.id))
+                    .filter(transactions::Column::VoidedAt.is_null())
+                    .filter(transactions::Column::OccurredAt.gte(from))
+                    .filter(transactions::Column::OccurredAt.lt(to))
+                    .filter(legs::Column::TargetKind.eq(legs::LegTargetKind::Flow))
+                    .filter(legs::Column::TargetId.is_in(flow_models.iter().map(|model| model.id)))
+                    .group_by(legs::Column::TargetId)
+                    .into_tuple::<(Uuid, Option<i64>)>()
+                    .all(db_tx)
+                    .await?
+                    .into_iter()
+                    .map(|(flow_id, sum)| (flow_id, sum.unwrap_or(0)))
+                    .collect();
+
+                let mut progress = Vec::with_capacity(flow_models.len());
+                for model in flow_models {
+                    let flow = CashFlow::try_from((model, vault_model.currency))?;
+                    let change_minor = changes.get(&flow.id).copied().unwrap_or(0);
+                    progress.extend(GoalProgress::compute(&flow, on, change_minor));
+                }
+                Ok(progress)
+            })
+        })
+        .await
+    }
 }
@@ -0,0 +1,205 @@
+//! Currency conversion for transactions spanning two currencies.
+//!
+//! Flows are always denominated in the vault currency, wallets in their own
+//! currency. A transaction may involve at most two distinct currencies; the
+//! recorded exchange rate converts the *original* (entered) currency into the
+//! other one.
+
+use crate::{Currency, EngineError, ExchangeRate, ResultEngine};
+
+/// Conversion recorded on a transaction (`original_*` + `exchange_rate`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Conversion {
+    pub(super) amount_minor: i64,
+    pub(super) currency: Currency,
+    pub(super) rate: ExchangeRate,
+}
+
+/// Unsigned leg amounts for an income/expense/refund.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FlowWalletAmounts {
+    /// Flow-side amount (and transaction amount), in the vault currency.
+    pub(super) vault_minor: i64,
+    pub(super) vault_currency: Currency,
+    pub(super) wallet_minor: i64,
+    pub(super) wallet_currency: Currency,
+    pub(super) conversion: Option<Conversion>,
+}
+
+/// Unsigned leg amounts for a wallet-to-wallet transfer.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TransferWalletAmounts {
+    pub(super) from_minor: i64,
+    pub(super) from_currency: Currency,
+    pub(super) to_minor: i64,
+    pub(super) to_currency: Currency,
+    pub(super) conversion: Option<Conversion>,
+}
+
+fn missing_rate(from: Currency, to: Currency) -> EngineError {
+    EngineError::CurrencyMismatch(format!(
+        "exchange rate required to convert {} to {}",
+        from.code(),
+        to.code()
+    ))
+}
+
+/// Returns the currency an amount in `amount_currency` must be converted into,
+/// if any, for a flow/wallet transaction.
+pub(super) fn conversion_target(
+    amount_currency: Currency,
+    wallet_currency: Currency,
+    vault_currency: Currency,
+) -> ResultEngine<Option<Currency>> {
+    if amount_currency == wallet_currency && wallet_currency == vault_currency {
+        return Ok(None);
+    }
+    if amount_currency == vault_currency {
+        return Ok(Some(wallet_currency));
+    }
+    if amount_currency != wallet_currency && wallet_currency != vault_currency {
+        return Err(EngineError::CurrencyMismatch(format!(
+            "a transaction may involve at most two currencies, got {}, {} and {}",
+            amount_currency.code(),
+            wallet_currency.code(),
+            vault_currency.code()
+        )));
+    }
+    Ok(Some(vault_currency))
+}
+
+/// Computes wallet and flow amounts for an income/expense/refund.
+///
+/// `amount_minor` is expressed in `amount_currency`. An unused `rate` is
+/// dropped (the caller decides whether that is an error).
+pub(super) fn resolve_flow_wallet_amounts(
+    amount_minor: i64,
+    amount_currency: Currency,
+    rate: Option<ExchangeRate>,
+    wallet_currency: Currency,
+    vault_currency: Currency,
+) -> ResultEngine<FlowWalletAmounts> {
+    if amount_minor <= 0 {
+        return Err(EngineError::InvalidAmount(
+            "amount_minor must be > 0".to_string(),
+        ));
+    }
+    let Some(target) = conversion_target(amount_currency, wallet_currency, vault_currency)? else {
+        return Ok(FlowWalletAmounts {
+            vault_minor: amount_minor,
+            vault_currency,
+            wallet_minor: amount_minor,
+            wallet_currency,
+            conversion: None,
+        });
+    };
+    let rate = rate.ok_or_else(|| missing_rate(amount_currency, target))?;
+    let converted = rate.convert(amount_minor, amount_currency, target)?;
+    if converted <= 0 {
+        return Err(EngineError::InvalidAmount(
+            "converted amount must be > 0".to_string(),
+        ));
+    }
+
+    let vault_minor = if amount_currency == vault_currency {
+        amount_minor
+    } else {
+        converted
+    };
+    let wallet_minor = if amount_currency == wallet_currency {
+        amount_minor
+    } else {
+        converted
+    };
+    Ok(FlowWalletAmounts {
+        vault_minor,
+        vault_currency,
+        wallet_minor,
+        wallet_currency,
+        conversion: Some(Conversion {
+            amount_minor,
+            currency: amount_currency,
+            rate,
+        }),
+    })
+}
+
+/// Converts the lines of a split expense into the vault currency.
+///
+/// Each line gets the difference between the converted running totals, so the
+/// lines always sum to the converted total and rounding is spread over them.
+pub(super) fn split_vault_amounts(
+    lines: &[i64],
+    amount_currency: Currency,
+    rate: Option<ExchangeRate>,
+    wallet_currency: Currency,
+    vault_currency: Currency,
+) -> ResultEngine<Vec<i64>> {
+    let mut running_minor = 0i64;
+    let mut converted_before = 0i64;
+    let mut out = Vec::with_capacity(lines.len());
+    for &line_minor in lines {
+        running_minor = running_minor
+            .checked_add(line_minor)
+            .ok_or_else(|| EngineError::InvalidAmount("amount overflow".to_string()))?;
+        let converted = resolve_flow_wallet_amounts(
+            running_minor,
+            amount_currency,
+            rate,
+            wallet_currency,
+            vault_currency,
+        )?
+        .vault_minor;
+        if converted <= converted_before {
+            return Err(EngineError::InvalidAmount(
+                "split line converts to a zero amount".to_string(),
+            ));
+        }
+        out.push(converted - converted_before);
+        converted_before = converted;
+    }
+    Ok(out)
+}
+
+/// Computes both leg amounts for a wallet-to-wallet transfer.
+///
+/// `amount_minor` is expressed in the source wallet currency.
+pub(super) fn resolve_transfer_wallet_amounts(
+    amount_minor: i64,
+    from_currency: Currency,
+    to_currency: Currency,
+    rate: Option<ExchangeRate>,
+) -> ResultEngine<TransferWalletAmounts> {
+    if amount_minor <= 0 {
+        return Err(EngineError::InvalidAmount(
+            "amount_minor must be > 0".to_string(),
+        ));
+    }
+    if from_currency == to_currency {
+        return Ok(TransferWalletAmounts {
+            from_minor: amount_minor,
+            from_currency,
+            to_minor: amount_minor,
+            to_currency,
+            conversion: None,
+        });
+    }
+    let rate = rate.ok_or_else(|| missing_rate(from_currency, to_currency))?;
+    let to_minor = rate.convert(amount_minor, from_currency, to_currency)?;
+    if to_minor <= 0 {
+        return Err(EngineError::InvalidAmount(
+            "converted amount must be > 0".to_string(),
+        ));
+    }
+    Ok(TransferWalletAmounts {
+        from_minor: amount_minor,
+        from_currency,
+        to_minor,
+        to_currency,
+        conversion: Some(Conversion {
+            amount_minor,
+            currency: from_currency,
+            rate,
+        }),
+    })
+}
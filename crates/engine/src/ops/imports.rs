@@ -0,0 +1,395 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Duration, NaiveDate, NaiveTime};
+use sea_orm::{DatabaseTransaction, JoinType, QueryFilter, QueryOrder, QuerySelect, prelude::*};
+use uuid::Uuid;
+
+use crate::{
+    CsvImportCmd, EngineError, ImportDraft, ImportPreview, ImportReport, ImportRow, ImportRowError,
+    ResultEngine, StatementImportCmd, TransactionKind,
+    category_rules::RuleSubject,
+    imports::{DraftTarget, ParsedStatement, parse_csv, parse_statement},
+    legs,
+    legs::LegTargetKind,
+    transactions,
+    util::normalize_optional_text,
+};
+
+use super::{Engine, parse_vault_uuid, transactions::FlowWalletCmd};
+
+/// Duplicate key of a booked row: day, signed wallet amount and note.
+type DuplicateKey = (NaiveDate, i64, Option<String>);
+
+fn duplicate_key(date: NaiveDate, signed_minor: i64, note: Option<&str>) -> DuplicateKey {
+    (
+        date,
+        signed_minor,
+        normalize_optional_text(note).map(|note| note.to_lowercase()),
+    )
+}
+
+fn flow_wallet_cmd(draft: ImportDraft) -> FlowWalletCmd {
+    match draft {
+        ImportDraft::Income(cmd) => FlowWalletCmd {
+            vault_id: cmd.vault_id,
+            amount_minor: cmd.amount_minor,
+            flow_id: cmd.flow_id,
+            wallet_id: cmd.wallet_id,
+            currency: cmd.currency,
+            exchange_rate: cmd.exchange_rate,
+            meta: cmd.meta,
+            user_id: cmd.user_id,
+            kind: TransactionKind::Income,
+            refunded_transaction_id: None,
+        },
+        ImportDraft::Expense(cmd) => FlowWalletCmd {
+            vault_id: cmd.vault_id,
+            amount_minor: cmd.amount_minor,
+            flow_id: cmd.flow_id,
+            wallet_id: cmd.wallet_id,
+            currency: cmd.currency,
+            exchange_rate: cmd.exchange_rate,
+            meta: cmd.meta,
+            user_id: cmd.user_id,
+            kind: TransactionKind::Expense,
+            refunded_transaction_id: None,
+        },
+    }
+}
+
+impl Engine {
+    /// Resolves the wallet/flow an import books on. Requires write access.
+    async fn import_target(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_id: &str,
+        user_id: &str,
+        wallet_id: Option<Uuid>,
+        flow_id: Option<Uuid>,
+    ) -> ResultEngine<DraftTarget> {
+        self.require_vault_by_id_write(db_tx, vault_id, user_id)
+            .await?;
+        let wallet_id = self.resolve_wallet_id(db_tx, vault_id, wallet_id).await?;
+        let currency = self.wallet_currency(db_tx, vault_id, wallet_id).await?;
+        if let Some(flow_id) = flow_id {
+            self.require_flow_in_vault(db_tx, vault_id, flow_id).await?;
+        }
+        Ok(DraftTarget {
+            vault_id: vault_id.to_string(),
+            user_id: user_id.to_string(),
+            wallet_id,
+            flow_id,
+            currency,
+        })
+    }
+
+    /// Pairs drafts with the transactions they duplicate.
+    ///
+    /// A draft whose idempotency key is already used in the vault (voided or
+    /// not) is an exact match. Other drafts are paired with non-voided
+    /// incomes/expenses/refunds of the target wallet booked on the same day
+    /// with the same amount and note; each existing transaction matches at
+    /// most one draft, so a statement listing the same purchase twice only
+    /// flags as many rows as are booked.
+    async fn match_import_duplicates(
+        &self,
+        db_tx: &DatabaseTransaction,
+        target: &DraftTarget,
+        drafts: Vec<(u64, ImportDraft)>,
+    ) -> ResultEngine<Vec<ImportRow>> {
+        let (Some(first), Some(last)) = (
+            drafts.iter().map(|(_, draft)| draft.date()).min(),
+            drafts.iter().map(|(_, draft)| draft.date()).max(),
+        ) else {
+            return Ok(Vec::new());
+        };
+        let vault_uuid = parse_vault_uuid(&target.vault_id)?;
+
+        let keys: Vec<String> = drafts
+            .iter()
+            .filter_map(|(_, draft)| draft.meta().idempotency_key.clone())
+            .collect();
+        let imported: HashMap<String, Uuid> = if keys.is_empty() {
+            HashMap::new()
+        } else {
+            transactions::Entity::find()
+                .select_only()
+                .column(transactions::Column::IdempotencyKey)
+                .column(transactions::Column::Id)
+                .filter(transactions::Column::VaultId.eq(vault_uuid))
+                .filter(transactions::Column::IdempotencyKey.is_in(keys))
+                .into_tuple()
+                .all(db_tx)
+                .await?
+                .into_iter()
+                .collect()
+        };
+        let imported_ids: HashSet<Uuid> = imported.values().copied().collect();
+
+        let rows: Vec<(Uuid, DateTimeUtc, i64, Option<String>)> = legs::Entity::find()
+            .select_only()
+            .column(transactions::Column::Id)
+            .column(transactions::Column::OccurredAt)
+            .column(legs::Column::AmountMinor)
+            .column(transactions::Column::Note)
+            .join(JoinType::InnerJoin, legs::Relation::Transactions.def())
+            .filter(transactions::Column::VaultId.eq(vault_uuid))
+            .filter(transactions::Column::Kind.is_in([
+                TransactionKind::Income,
+                TransactionKind::Expense,
+                TransactionKind::Refund,
+            ]))
+            .filter(transactions::Column::VoidedAt.is_null())
+            .filter(legs::Column::TargetKind.eq(LegTargetKind::Wallet))
+            .filter(legs::Column::TargetId.eq(target.wallet_id))
+            .filter(transactions::Column::OccurredAt.gte(first.and_time(NaiveTime::MIN).and_utc()))
+            .filter(
+                transactions::Column::OccurredAt.lt((last + Duration::days(1))
+                    .and_time(NaiveTime::MIN)
+                    .and_utc()),
+            )
+            .order_by_asc(transactions::Column::OccurredAt)
+            .order_by_asc(transactions::Column::Id)
+            .into_tuple()
+            .all(db_tx)
+            .await?;
+
+        let mut booked: HashMap<DuplicateKey, Vec<Uuid>> = HashMap::new();
+        for (id, occurred_at, amount_minor, note) in rows {
+            if imported_ids.contains(&id) {
+                continue;
+            }
+            booked
+                .entry(duplicate_key(
+                    occurred_at.date_naive(),
+                    amount_minor,
+                    note.as_deref(),
+                ))
+                .or_default()
+                .push(id);
+        }
+        for ids in booked.values_mut() {
+            // Consume the oldest first.
+            ids.reverse();
+        }
+
+        Ok(drafts
+            .into_iter()
+            .map(|(line, draft)| {
+                if let Some(id) = draft
+                    .meta()
+                    .idempotency_key
+                    .as_ref()
+                    .and_then(|key| imported.get(key))
+                {
+                    return ImportRow {
+                        line,
+                        duplicate_of: Some(*id),
+                        already_imported: true,
+                        draft,
+                    };
+                }
+                let key = duplicate_key(
+                    draft.date(),
+                    draft.signed_amount_minor(),
+                    draft.meta().note.as_deref(),
+                );
+                let duplicate_of = booked.get_mut(&key).and_then(Vec::pop);
+                ImportRow {
+                    line,
+                    draft,
+                    duplicate_of,
+                    already_imported: false,
+                }
+            })
+            .collect())
+    }
+
+    /// Assigns rule categories to drafts the statement left uncategorized,
+    /// so the preview shows what will be booked.
+    async fn categorize_drafts(
+        &self,
+        db_tx: &DatabaseTransaction,
+        target: &DraftTarget,
+        drafts: &mut [(u64, ImportDraft)],
+    ) -> ResultEngine<()> {
+        let rules = self.load_category_rules(db_tx, &target.vault_id).await?;
+        if rules.is_empty() {
+            return Ok(());
+        }
+        let flow_id = match target.flow_id {
+            Some(flow_id) => flow_id,
+            None => self.unallocated_flow_id(db_tx, &target.vault_id).await?,
+        };
+        for (_, draft) in drafts.iter_mut() {
+            if draft.meta().category.is_some() || draft.meta().category_id.is_some() {
+                continue;
+            }
+            let subject = RuleSubject {
+                kind: draft.kind(),
+                amount_minor: draft.amount_minor(),
+                wallet_id: Some(target.wallet_id),
+                flow_id: Some(flow_id),
+                note: draft.meta().note.as_deref(),
+            };
+            let Some(category) = self
+                .categorize_by_rules(db_tx, &target.vault_id, &rules, &subject)
+                .await?
+            else {
+                continue;
+            };
+            let meta = draft.meta_mut();
+            meta.category_id = Some(category.id);
+            meta.category = category.name;
+        }
+        Ok(())
+    }
+
+    /// Resolves the import target, parses the statement with `parse` and
+    /// flags duplicates.
+    async fn preview_import_in_tx(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_id: &str,
+        user_id: &str,
+        (wallet_id, flow_id): (Option<Uuid>, Option<Uuid>),
+        parse: impl FnOnce(&DraftTarget) -> ResultEngine<ParsedStatement>,
+    ) -> ResultEngine<ImportPreview> {
+        let target = self
+            .import_target(db_tx, vault_id, user_id, wallet_id, flow_id)
+            .await?;
+        let (mut drafts, errors) = parse(&target)?;
+        self.categorize_drafts(db_tx, &target, &mut drafts).await?;
+        let rows = self.match_import_duplicates(db_tx, &target, drafts).await?;
+        Ok(ImportPreview {
+            currency: target.currency,
+            rows,
+            errors,
+        })
+    }
+
+    async fn preview_csv_import_in_tx(
+        &self,
+        db_tx: &DatabaseTransaction,
+        cmd: &CsvImportCmd,
+    ) -> ResultEngine<ImportPreview> {
+        self.preview_import_in_tx(
+            db_tx,
+            &cmd.vault_id,
+            &cmd.user_id,
+            (cmd.wallet_id, cmd.flow_id),
+            |target| parse_csv(&cmd.data, &cmd.mapping, target),
+        )
+        .await
+    }
+
+    async fn preview_statement_import_in_tx(
+        &self,
+        db_tx: &DatabaseTransaction,
+        cmd: &StatementImportCmd,
+    ) -> ResultEngine<ImportPreview> {
+        self.preview_import_in_tx(
+            db_tx,
+            &cmd.vault_id,
+            &cmd.user_id,
+            (cmd.wallet_id, cmd.flow_id),
+            |target| parse_statement(cmd.format, &cmd.data, target),
+        )
+        .await
+    }
+
+    /// Books previewed rows inside `db_tx`, skipping rows already imported
+    /// and, unless `include_duplicates` is set, likely duplicates.
+    ///
+    /// Rows that failed to parse abort the import.
+    async fn commit_import(
+        &self,
+        db_tx: &DatabaseTransaction,
+        preview: ImportPreview,
+        include_duplicates: bool,
+    ) -> ResultEngine<ImportReport> {
+        if let Some(ImportRowError { line, message }) = preview.errors.first() {
+            return Err(EngineError::InvalidAmount(format!(
+                "line {line}: {message}"
+            )));
+        }
+        let mut report = ImportReport::default();
+        for row in preview.rows {
+            if row.already_imported || (row.duplicate_of.is_some() && !include_duplicates) {
+                report.skipped_duplicates += 1;
+                continue;
+            }
+            let id = self
+                .create_flow_wallet_transaction_in_tx(db_tx, flow_wallet_cmd(row.draft))
+                .await
+                .map_err(|err| match err {
+                    EngineError::InvalidAmount(msg) => {
+                        EngineError::InvalidAmount(format!("line {}: {msg}", row.line))
+                    }
+                    other => other,
+                })?;
+            report.created.push(id);
+        }
+        Ok(report)
+    }
+
+    /// Parses a CSV statement and returns the drafts it would book, flagging
+    /// rows that look already booked. Nothing is written.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn preview_csv_import(&self, cmd: CsvImportCmd) -> ResultEngine<ImportPreview> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move { engine.preview_csv_import_in_tx(db_tx, &cmd).await })
+        })
+        .await
+    }
+
+    /// Imports a CSV statement atomically: either every row is booked (minus
+    /// skipped duplicates) or nothing is. Rows that fail to parse abort the
+    /// import.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn import_csv(&self, cmd: CsvImportCmd) -> ResultEngine<ImportReport> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let preview = engine.preview_csv_import_in_tx(db_tx, &cmd).await?;
+                engine
+                    .commit_import(db_tx, preview, cmd.include_duplicates)
+                    .await
+            })
+        })
+        .await
+    }
+
+    /// Parses an OFX, QIF or CAMT.053 statement and returns the drafts it
+    /// would book. Rows whose bank reference is already booked are flagged
+    /// `already_imported`. Nothing is written.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn preview_statement_import(
+        &self,
+        cmd: StatementImportCmd,
+    ) -> ResultEngine<ImportPreview> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move { engine.preview_statement_import_in_tx(db_tx, &cmd).await })
+        })
+        .await
+    }
+
+    /// Imports an OFX, QIF or CAMT.053 statement atomically. Each row is
+    /// booked with its bank reference as idempotency key, so importing the
+    /// same (or an overlapping) statement again never books a row twice.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn import_statement(&self, cmd: StatementImportCmd) -> ResultEngine<ImportReport> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let preview = engine.preview_statement_import_in_tx(db_tx, &cmd).await?;
+                engine
+                    .commit_import(db_tx, preview, cmd.include_duplicates)
+                    .await
+            })
+        })
+        .await
+    }
+}
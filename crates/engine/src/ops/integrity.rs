@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use sea_orm::{
+    Condition, DatabaseTransaction, JoinType, QueryFilter, QueryOrder, QuerySelect, prelude::*,
+    sea_query::Query,
+};
+use uuid::Uuid;
+
+use crate::{
+    CashFlow, EngineError, IntegrityIssue, IntegrityReport, Leg, LegTarget, ResultEngine,
+    cash_flows,
+    legs::{self, LegTargetKind},
+    transactions, wallets,
+};
+
+use super::Engine;
+
+impl Engine {
+    /// Checks the stored state of a vault against its ledger, without
+    /// changing anything.
+    ///
+    /// Replays the non-voided legs in chronological order, like
+    /// [`Engine::recompute_balances`], but collects every inconsistency into
+    /// the report instead of stopping at the first one.
+    ///
+    /// Authorization: requires vault read access.
+    pub async fn verify_vault(
+        &self,
+        vault_id: &str,
+        user_id: &str,
+    ) -> ResultEngine<IntegrityReport> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let vault_model = engine
+                    .require_vault_by_id(db_tx, &vault_id, &user_id)
+                    .await?;
+                verify_in_tx(db_tx, &vault_model).await
+            })
+        })
+        .await
+    }
+}
+
+async fn verify_in_tx(
+    db_tx: &DatabaseTransaction,
+    vault_model: &crate::vault::Model,
+) -> ResultEngine<IntegrityReport> {
+    let currency = vault_model.currency;
+    let mut issues = Vec::new();
+
+    let wallet_models: Vec<wallets::Model> = wallets::Entity::find()
+        .filter(wallets::Column::VaultId.eq(vault_model.id))
+        .all(db_tx)
+        .await?;
+    let flow_models: Vec<cash_flows::Model> = cash_flows::Entity::find()
+        .filter(cash_flows::Column::VaultId.eq(vault_model.id))
+        .all(db_tx)
+        .await?;
+
+    // Replay from zero on copies of the stored rows. Flows are built field by
+    // field: `CashFlow::try_from` would reject the very rows we report on.
+    let mut wallets_by_id: HashMap<Uuid, (wallets::Model, i64)> = wallet_models
+        .into_iter()
+        .map(|model| (model.id, (model, 0)))
+        .collect();
+    let mut flows: HashMap<Uuid, (cash_flows::Model, CashFlow)> = HashMap::new();
+    for model in flow_models {
+        if model.currency != currency {
+            issues.push(IntegrityIssue::FlowCurrencyMismatch {
+                flow_id: model.id,
+                expected: currency,
+                found: model.currency,
+            });
+        }
+        let flow = CashFlow {
+            id: model.id,
+            name: model.name.clone(),
+            system_kind: model.system_kind,
+            balance: 0,
+            max_balance: model.max_balance,
+            income_balance: model.income_balance.map(|_| 0),
+            currency: model.currency,
+            archived: model.archived,
+            target_minor: model.target_minor,
+            target_date: model.target_date,
+        };
+        flows.insert(model.id, (model, flow));
+    }
+
+    let rows: Vec<(legs::Model, Option<transactions::Model>)> = legs::Entity::find()
+        .find_also_related(transactions::Entity)
+        .filter(transactions::Column::VaultId.eq(vault_model.id))
+        .filter(transactions::Column::VoidedAt.is_null())
+        .order_by_asc(transactions::Column::OccurredAt)
+        .order_by_asc(legs::Column::Id)
+        .all(db_tx)
+        .await?;
+    let legs_checked = rows.len();
+
+    for (leg_model, transaction) in rows {
+        let Some(transaction) = transaction else {
+            continue;
+        };
+        let leg = Leg::try_from(leg_model)?;
+        match leg.target {
+            LegTarget::Wallet { wallet_id } => {
+                let Some((wallet, ledger_minor)) = wallets_by_id.get_mut(&wallet_id) else {
+                    issues.push(IntegrityIssue::MissingTarget {
+                        leg_id: leg.id,
+                        transaction_id: leg.transaction_id,
+                        target: leg.target,
+                    });
+                    continue;
+                };
+                if wallet.currency != leg.currency {
+                    issues.push(IntegrityIssue::LegCurrencyMismatch {
+                        leg_id: leg.id,
+                        transaction_id: leg.transaction_id,
+                        expected: wallet.currency,
+                        found: leg.currency,
+                    });
+                }
+                *ledger_minor += leg.amount_minor;
+            }
+            LegTarget::Flow { flow_id } => {
+                let Some((_, flow)) = flows.get_mut(&flow_id) else {
+                    issues.push(IntegrityIssue::MissingTarget {
+                        leg_id: leg.id,
+                        transaction_id: leg.transaction_id,
+                        target: leg.target,
+                    });
+                    continue;
+                };
+                if leg.currency != currency {
+                    issues.push(IntegrityIssue::LegCurrencyMismatch {
+                        leg_id: leg.id,
+                        transaction_id: leg.transaction_id,
+                        expected: currency,
+                        found: leg.currency,
+                    });
+                }
+                match flow.force_leg_change(0, leg.amount_minor) {
+                    Some(EngineError::MaxBalanceReached(limit)) => {
+                        issues.push(IntegrityIssue::CapExceeded {
+                            transaction_id: transaction.id,
+                            occurred_at: transaction.occurred_at,
+                            limit,
+                        });
+                    }
+                    Some(EngineError::InsufficientFunds(limit)) => {
+                        issues.push(IntegrityIssue::NegativeBalance {
+                            transaction_id: transaction.id,
+                            occurred_at: transaction.occurred_at,
+                            limit,
+                        });
+                    }
+                    Some(err) => return Err(err),
+                    None => {}
+                }
+            }
+        }
+    }
+
+    // Legs whose transaction is gone cannot be tied to a vault through the
+    // join above: match them by target instead.
+    let vault_wallets = Query::select()
+        .column(wallets::Column::Id)
+        .from(wallets::Entity)
+        .and_where(wallets::Column::VaultId.eq(vault_model.id))
+        .to_owned();
+    let vault_flows = Query::select()
+        .column(cash_flows::Column::Id)
+        .from(cash_flows::Entity)
+        .and_where(cash_flows::Column::VaultId.eq(vault_model.id))
+        .to_owned();
+    let orphans: Vec<legs::Model> = legs::Entity::find()
+        .join(JoinType::LeftJoin, legs::Relation::Transactions.def())
+        .filter(transactions::Column::Id.is_null())
+        .filter(
+            Condition::any()
+                .add(
+                    Condition::all()
+                        .add(legs::Column::TargetKind.eq(LegTargetKind::Wallet))
+                        .add(legs::Column::TargetId.in_subquery(vault_wallets)),
+                )
+                .add(
+                    Condition::all()
+                        .add(legs::Column::TargetKind.eq(LegTargetKind::Flow))
+                        .add(legs::Column::TargetId.in_subquery(vault_flows)),
+                ),
+        )
+        .order_by_asc(legs::Column::Id)
+        .all(db_tx)
+        .await?;
+    issues.extend(orphans.into_iter().map(|leg| IntegrityIssue::OrphanLeg {
+        leg_id: leg.id,
+        transaction_id: leg.transaction_id,
+    }));
+
+    let mut wallets_sorted: Vec<_> = wallets_by_id.into_values().collect();
+    wallets_sorted.sort_by_key(|(wallet, _)| wallet.id);
+    let wallets_checked = wallets_sorted.len();
+    for (wallet, ledger_minor) in wallets_sorted {
+        if wallet.balance != ledger_minor {
+            issues.push(IntegrityIssue::WalletBalanceMismatch {
+                wallet_id: wallet.id,
+                stored_minor: wallet.balance,
+                ledger_minor,
+            });
+        }
+    }
+
+    let mut flows_sorted: Vec<_> = flows.into_values().collect();
+    flows_sorted.sort_by_key(|(model, _)| model.id);
+    let flows_checked = flows_sorted.len();
+    for (model, flow) in flows_sorted {
+        if model.balance != flow.balance {
+            issues.push(IntegrityIssue::FlowBalanceMismatch {
+                flow_id: model.id,
+                stored_minor: model.balance,
+                ledger_minor: flow.balance,
+            });
+        }
+        if let (Some(stored_minor), Some(ledger_minor)) =
+            (model.income_balance, flow.income_balance)
+            && stored_minor != ledger_minor
+        {
+            issues.push(IntegrityIssue::FlowIncomeMismatch {
+                flow_id: model.id,
+                stored_minor,
+                ledger_minor,
+            });
+        }
+    }
+
+    Ok(IntegrityReport {
+        wallets_checked,
+        flows_checked,
+        legs_checked,
+        issues,
+    })
+}
@@ -2,9 +2,17 @@ use uuid::Uuid;
 
 use sea_orm::{ActiveValue, QueryFilter, prelude::*};
 
-use crate::{EngineError, ResultEngine, cash_flows, flow_memberships, vault_memberships};
+use crate::{
+    AuditEntity, EngineError, ResultEngine, audit::AuditChange, cash_flows, flow_memberships,
+    vault_memberships,
+};
 
-use super::{Engine, access::MembershipRole, parse_vault_uuid};
+use super::{
+    Engine,
+    access::MembershipRole,
+    audit::{record_audit, snapshot},
+    parse_vault_uuid,
+};
 
 impl Engine {
     /// Adds or updates a vault member (owner-only).
@@ -38,17 +46,32 @@ impl Engine {
                 };
 
                 // Upsert: insert if missing, otherwise update role.
-                match vault_memberships::Entity::find_by_id((vault_uuid, member_username.clone()))
-                    .one(db_tx)
-                    .await?
+                let change = match vault_memberships::Entity::find_by_id((
+                    vault_uuid,
+                    member_username.clone(),
+                ))
+                .one(db_tx)
+                .await?
                 {
-                    Some(_) => {
-                        active.update(db_tx).await?;
+                    Some(before) => {
+                        let model = active.update(db_tx).await?;
+                        AuditChange::updated(
+                            AuditEntity::VaultMember,
+                            &member_username,
+                            snapshot(&before),
+                            snapshot(&model),
+                        )
                     }
                     None => {
-                        active.insert(db_tx).await?;
+                        let model = active.insert(db_tx).await?;
+                        AuditChange::created(
+                            AuditEntity::VaultMember,
+                            &member_username,
+                            snapshot(&model),
+                        )
                     }
-                }
+                };
+                record_audit(db_tx, vault_uuid, user_id.as_str(), change).await?;
 
                 Ok(())
             })
@@ -78,9 +101,26 @@ impl Engine {
                 }
 
                 let vault_uuid = parse_vault_uuid(vault_id.as_str())?;
-                vault_memberships::Entity::delete_by_id((vault_uuid, member_username.clone()))
-                    .exec(db_tx)
+                let key = (vault_uuid, member_username.clone());
+                if let Some(before) = vault_memberships::Entity::find_by_id(key.clone())
+                    .one(db_tx)
+                    .await?
+                {
+                    vault_memberships::Entity::delete_by_id(key)
+                        .exec(db_tx)
+                        .await?;
+                    record_audit(
+                        db_tx,
+                        vault_uuid,
+                        user_id.as_str(),
+                        AuditChange::deleted(
+                            AuditEntity::VaultMember,
+                            &member_username,
+                            snapshot(&before),
+                        ),
+                    )
                     .await?;
+                }
 
                 Ok(())
             })
@@ -154,17 +194,30 @@ impl Engine {
                     role: ActiveValue::Set(role.clone()),
                 };
 
-                match flow_memberships::Entity::find_by_id((flow_id, member_username.clone()))
-                    .one(db_tx)
-                    .await?
-                {
-                    Some(_) => {
-                        active.update(db_tx).await?;
-                    }
-                    None => {
-                        active.insert(db_tx).await?;
-                    }
-                }
+                let change =
+                    match flow_memberships::Entity::find_by_id((flow_id, member_username.clone()))
+                        .one(db_tx)
+                        .await?
+                    {
+                        Some(before) => {
+                            let model = active.update(db_tx).await?;
+                            AuditChange::updated(
+                                AuditEntity::FlowMember,
+                                &member_username,
+                                snapshot(&before),
+                                snapshot(&model),
+                            )
+                        }
+                        None => {
+                            let model = active.insert(db_tx).await?;
+                            AuditChange::created(
+                                AuditEntity::FlowMember,
+                                &member_username,
+                                snapshot(&model),
+                            )
+                        }
+                    };
+                record_audit(db_tx, vault_uuid, user_id.as_str(), change).await?;
 
                 Ok(())
             })
@@ -190,13 +243,30 @@ impl Engine {
                     .await?;
 
                 // Ensure flow exists and belongs to vault.
-                engine
+                let flow = engine
                     .require_flow_read(db_tx, vault_id.as_str(), flow_id, user_id.as_str())
                     .await?;
 
-                flow_memberships::Entity::delete_by_id((flow_id, member_username.clone()))
-                    .exec(db_tx)
+                let key = (flow_id, member_username.clone());
+                if let Some(before) = flow_memberships::Entity::find_by_id(key.clone())
+                    .one(db_tx)
+                    .await?
+                {
+                    flow_memberships::Entity::delete_by_id(key)
+                        .exec(db_tx)
+                        .await?;
+                    record_audit(
+                        db_tx,
+                        flow.vault_id,
+                        user_id.as_str(),
+                        AuditChange::deleted(
+                            AuditEntity::FlowMember,
+                            &member_username,
+                            snapshot(&before),
+                        ),
+                    )
                     .await?;
+                }
                 Ok(())
             })
         })
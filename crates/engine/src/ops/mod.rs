@@ -1,5 +1,8 @@
 use chrono::{DateTime, Utc};
-use sea_orm::{DatabaseConnection, DatabaseTransaction, TransactionError, TransactionTrait};
+use sea_orm::{
+    DatabaseConnection, DatabaseTransaction, TransactionError, TransactionTrait,
+    sea_query::{Alias, Expr, Func, IntoColumnRef, SimpleExpr},
+};
 use std::{future::Future, pin::Pin};
 use uuid::Uuid;
 
@@ -9,15 +12,26 @@ use crate::{
 };
 
 mod access;
+mod access_tokens;
+mod audit;
+mod backup;
 mod balances;
+mod budgets;
 mod categories;
+mod category_rules;
 mod flows;
+mod fx;
+mod imports;
+mod integrity;
 mod memberships;
+mod recurring;
+mod reports;
 mod transactions;
 mod vaults;
 mod wallets;
 
 pub use categories::{CategoryMergeConflict, CategoryMergeConflictKind, CategoryMergePreview};
+pub use recurring::{RecurringFailure, RecurringRunReport};
 pub use transactions::TransactionListFilter;
 
 /// Parse a vault_id string into Uuid for DB queries.
@@ -25,6 +39,12 @@ pub(crate) fn parse_vault_uuid(vault_id: &str) -> ResultEngine<Uuid> {
     Uuid::parse_str(vault_id).map_err(|_| EngineError::KeyNotFound("vault not found".to_string()))
 }
 
+/// `SUM` of a minor-units column, cast back to `BIGINT`: Postgres widens
+/// integer sums to `numeric`.
+fn sum_minor(column: impl IntoColumnRef) -> SimpleExpr {
+    Func::cast_as(Func::sum(Expr::col(column)), Alias::new("BIGINT")).into()
+}
+
 #[derive(Clone, Debug)]
 pub struct Engine {
     database: DatabaseConnection,
@@ -79,6 +99,7 @@ pub(super) struct TransactionBuildInput<'a> {
     pub(super) created_by: &'a str,
     pub(super) idempotency_key: Option<String>,
     pub(super) refunded_transaction_id: Option<Uuid>,
+    pub(super) conversion: Option<fx::Conversion>,
 }
 
 fn build_transaction(input: TransactionBuildInput<'_>) -> ResultEngine<Transaction> {
@@ -94,6 +115,9 @@ fn build_transaction(input: TransactionBuildInput<'_>) -> ResultEngine<Transacti
         created_by: input.created_by.to_string(),
         idempotency_key: input.idempotency_key,
         refunded_transaction_id: input.refunded_transaction_id,
+        original_amount_minor: input.conversion.map(|c| c.amount_minor),
+        original_currency: input.conversion.map(|c| c.currency),
+        exchange_rate: input.conversion.map(|c| c.rate),
     })
 }
 
@@ -101,31 +125,30 @@ fn flow_wallet_legs(
     tx_id: Uuid,
     wallet_id: Uuid,
     flow_id: Uuid,
-    signed_amount_minor: i64,
-    currency: Currency,
-) -> Vec<Leg> {
-    vec![
+    kind: TransactionKind,
+    amounts: &fx::FlowWalletAmounts,
+) -> ResultEngine<Vec<Leg>> {
+    Ok(vec![
         Leg::new(
             tx_id,
             LegTarget::Wallet { wallet_id },
-            signed_amount_minor,
-            currency,
+            flow_wallet_signed_amount(kind, amounts.wallet_minor)?,
+            amounts.wallet_currency,
         ),
         Leg::new(
             tx_id,
             LegTarget::Flow { flow_id },
-            signed_amount_minor,
-            currency,
+            flow_wallet_signed_amount(kind, amounts.vault_minor)?,
+            amounts.vault_currency,
         ),
-    ]
+    ])
 }
 
 fn transfer_wallet_legs(
     tx_id: Uuid,
     from_wallet_id: Uuid,
     to_wallet_id: Uuid,
-    amount_minor: i64,
-    currency: Currency,
+    amounts: &fx::TransferWalletAmounts,
 ) -> Vec<Leg> {
     vec![
         Leg::new(
@@ -133,16 +156,16 @@ fn transfer_wallet_legs(
             LegTarget::Wallet {
                 wallet_id: from_wallet_id,
             },
-            -amount_minor,
-            currency,
+            -amounts.from_minor,
+            amounts.from_currency,
         ),
         Leg::new(
             tx_id,
             LegTarget::Wallet {
                 wallet_id: to_wallet_id,
             },
-            amount_minor,
-            currency,
+            amounts.to_minor,
+            amounts.to_currency,
         ),
     ]
 }
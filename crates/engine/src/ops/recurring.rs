@@ -0,0 +1,517 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{DatabaseTransaction, QueryFilter, QueryOrder, prelude::*, sea_query::Expr};
+use uuid::Uuid;
+
+use crate::{
+    AuditEntity, CreateRecurringRuleCmd, Currency, EngineError, ExpenseCmd, IncomeCmd,
+    RecurringRule, ResultEngine, TransactionKind, TransferFlowCmd, TxMeta, UpdateRecurringRuleCmd,
+    audit::AuditChange,
+    recurring_rules,
+    util::{apply_optional_text_patch, ensure_vault_currency, normalize_optional_text},
+};
+
+use super::{
+    Engine,
+    audit::{entity_snapshot, record_audit, snapshot},
+    parse_vault_uuid,
+};
+
+/// Upper bound of occurrences a single rule may post in one scheduler run, so
+/// a long outage is caught up over a few ticks instead of one huge burst.
+const MAX_CATCH_UP_PER_RUN: u32 = 500;
+
+/// Summary of a scheduler run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RecurringRunReport {
+    /// Occurrences posted (including ones found already posted).
+    pub posted: usize,
+    /// Rules that could not be advanced. They are paused with the error kept
+    /// in [`RecurringRule::last_error`], unless the database failed: those are
+    /// retried on the next run.
+    pub failures: Vec<RecurringFailure>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecurringFailure {
+    pub rule_id: Uuid,
+    pub vault_id: String,
+    pub occurrence_at: DateTime<Utc>,
+    pub error: String,
+}
+
+/// Validates the targets of a rule against its kind.
+///
+/// Occurrences are posted without an exchange rate, so Income/Expense rules
+/// must use a wallet in the vault currency.
+async fn validate_rule_targets(
+    engine: &Engine,
+    db_tx: &DatabaseTransaction,
+    vault_currency: Currency,
+    rule: &mut RecurringRule,
+) -> ResultEngine<()> {
+    match rule.kind {
+        TransactionKind::Income | TransactionKind::Expense => {
+            if rule.from_flow_id.is_some() || rule.to_flow_id.is_some() {
+                return Err(EngineError::InvalidFlow(
+                    "from_flow_id/to_flow_id are only valid for transfer_flow rules".to_string(),
+                ));
+            }
+            // Resolve defaults once, so the rule keeps posting to the same
+            // targets even if more wallets are added later.
+            let wallet_id = engine
+                .resolve_wallet_id(db_tx, &rule.vault_id, rule.wallet_id)
+                .await?;
+            ensure_vault_currency(
+                vault_currency,
+                engine
+                    .wallet_currency(db_tx, &rule.vault_id, wallet_id)
+                    .await?,
+            )?;
+            rule.wallet_id = Some(wallet_id);
+            rule.flow_id = Some(
+                engine
+                    .resolve_flow_id(db_tx, &rule.vault_id, rule.flow_id)
+                    .await?,
+            );
+        }
+        TransactionKind::TransferFlow => {
+            if rule.wallet_id.is_some() || rule.flow_id.is_some() {
+                return Err(EngineError::InvalidFlow(
+                    "wallet_id/flow_id are not valid for transfer_flow rules".to_string(),
+                ));
+            }
+            let (Some(from), Some(to)) = (rule.from_flow_id, rule.to_flow_id) else {
+                return Err(EngineError::InvalidFlow(
+                    "transfer_flow rules require from_flow_id and to_flow_id".to_string(),
+                ));
+            };
+            if from == to {
+                return Err(EngineError::InvalidFlow(
+                    "from_flow_id and to_flow_id must differ".to_string(),
+                ));
+            }
+            engine
+                .require_flow_in_vault(db_tx, &rule.vault_id, from)
+                .await?;
+            engine
+                .require_flow_in_vault(db_tx, &rule.vault_id, to)
+                .await?;
+        }
+        TransactionKind::TransferWallet | TransactionKind::Refund => {
+            return Err(EngineError::InvalidAmount(
+                "recurring rules support income, expense and transfer_flow".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl Engine {
+    async fn require_recurring_rule(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_id: &str,
+        rule_id: Uuid,
+    ) -> ResultEngine<RecurringRule> {
+        let vault_uuid = parse_vault_uuid(vault_id)?;
+        let model = recurring_rules::Entity::find_by_id(rule_id)
+            .filter(recurring_rules::Column::VaultId.eq(vault_uuid))
+            .one(db_tx)
+            .await?
+            .ok_or_else(|| EngineError::KeyNotFound("recurring rule not exists".to_string()))?;
+        RecurringRule::try_from(model)
+    }
+
+    /// Creates a recurring rule; the first occurrence is posted by the next
+    /// scheduler run once due.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn create_recurring_rule(
+        &self,
+        cmd: CreateRecurringRuleCmd,
+    ) -> ResultEngine<RecurringRule> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let vault_model = engine
+                    .require_vault_by_id_write(db_tx, &cmd.vault_id, &cmd.user_id)
+                    .await?;
+                if cmd.amount_minor <= 0 {
+                    return Err(EngineError::InvalidAmount(
+                        "amount_minor must be > 0".to_string(),
+                    ));
+                }
+                cmd.recurrence.validate()?;
+                let next_occurrence_at = cmd.recurrence.occurrence(0).ok_or_else(|| {
+                    EngineError::InvalidAmount("schedule has no occurrences".to_string())
+                })?;
+
+                let mut rule = RecurringRule {
+                    id: Uuid::new_v4(),
+                    vault_id: cmd.vault_id,
+                    kind: cmd.kind,
+                    amount_minor: cmd.amount_minor,
+                    wallet_id: cmd.wallet_id,
+                    flow_id: cmd.flow_id,
+                    from_flow_id: cmd.from_flow_id,
+                    to_flow_id: cmd.to_flow_id,
+                    category: normalize_optional_text(cmd.category.as_deref()),
+                    note: normalize_optional_text(cmd.note.as_deref()),
+                    recurrence: cmd.recurrence,
+                    occurrences: 0,
+                    next_occurrence_at: Some(next_occurrence_at),
+                    active: true,
+                    last_error: None,
+                    created_by: cmd.user_id,
+                };
+                validate_rule_targets(&engine, db_tx, vault_model.currency, &mut rule).await?;
+
+                let model = recurring_rules::ActiveModel::try_from(&rule)?
+                    .insert(db_tx)
+                    .await?;
+                record_audit(
+                    db_tx,
+                    model.vault_id,
+                    &rule.created_by,
+                    AuditChange::created(AuditEntity::RecurringRule, rule.id, snapshot(&model)),
+                )
+                .await?;
+                Ok(rule)
+            })
+        })
+        .await
+    }
+
+    /// Lists the recurring rules of a vault.
+    ///
+    /// Authorization: requires vault read access.
+    pub async fn list_recurring_rules(
+        &self,
+        vault_id: &str,
+        user_id: &str,
+    ) -> ResultEngine<Vec<RecurringRule>> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id(db_tx, &vault_id, &user_id)
+                    .await?;
+                let vault_uuid = parse_vault_uuid(&vault_id)?;
+                recurring_rules::Entity::find()
+                    .filter(recurring_rules::Column::VaultId.eq(vault_uuid))
+                    .order_by_asc(recurring_rules::Column::StartsAt)
+                    .order_by_asc(recurring_rules::Column::Id)
+                    .all(db_tx)
+                    .await?
+                    .into_iter()
+                    .map(RecurringRule::try_from)
+                    .collect()
+            })
+        })
+        .await
+    }
+
+    /// Returns a single recurring rule.
+    ///
+    /// Authorization: requires vault read access.
+    pub async fn recurring_rule(
+        &self,
+        vault_id: &str,
+        rule_id: Uuid,
+        user_id: &str,
+    ) -> ResultEngine<RecurringRule> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id(db_tx, &vault_id, &user_id)
+                    .await?;
+                engine
+                    .require_recurring_rule(db_tx, &vault_id, rule_id)
+                    .await
+            })
+        })
+        .await
+    }
+
+    /// Updates amount, targets, texts, end date or active state of a rule.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn update_recurring_rule(
+        &self,
+        cmd: UpdateRecurringRuleCmd,
+    ) -> ResultEngine<RecurringRule> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let vault_model = engine
+                    .require_vault_by_id_write(db_tx, &cmd.vault_id, &cmd.user_id)
+                    .await?;
+                let mut rule = engine
+                    .require_recurring_rule(db_tx, &cmd.vault_id, cmd.rule_id)
+                    .await?;
+                let before = entity_snapshot::<recurring_rules::Entity>(db_tx, rule.id).await?;
+
+                if let Some(amount_minor) = cmd.amount_minor {
+                    if amount_minor <= 0 {
+                        return Err(EngineError::InvalidAmount(
+                            "amount_minor must be > 0".to_string(),
+                        ));
+                    }
+                    rule.amount_minor = amount_minor;
+                }
+                rule.wallet_id = cmd.wallet_id.or(rule.wallet_id);
+                rule.flow_id = cmd.flow_id.or(rule.flow_id);
+                rule.from_flow_id = cmd.from_flow_id.or(rule.from_flow_id);
+                rule.to_flow_id = cmd.to_flow_id.or(rule.to_flow_id);
+                rule.category = apply_optional_text_patch(rule.category, cmd.category.as_deref());
+                rule.note = apply_optional_text_patch(rule.note, cmd.note.as_deref());
+                if let Some(active) = cmd.active {
+                    // Resuming a paused rule skips the occurrences that fell
+                    // inside the pause instead of backfilling them.
+                    if active && !rule.active {
+                        let now = Utc::now();
+                        while rule.next_occurrence_at.is_some_and(|at| at < now) {
+                            rule.occurrences += 1;
+                            rule.next_occurrence_at = rule.recurrence.occurrence(rule.occurrences);
+                        }
+                    }
+                    if active {
+                        rule.last_error = None;
+                    }
+                    rule.active = active;
+                }
+                if let Some(ends_at) = cmd.ends_at {
+                    rule.recurrence.ends_at = ends_at;
+                    rule.recurrence.validate()?;
+                    rule.next_occurrence_at = rule.recurrence.occurrence(rule.occurrences);
+                }
+                validate_rule_targets(&engine, db_tx, vault_model.currency, &mut rule).await?;
+
+                let model = recurring_rules::ActiveModel::try_from(&rule)?
+                    .update(db_tx)
+                    .await?;
+                record_audit(
+                    db_tx,
+                    model.vault_id,
+                    &cmd.user_id,
+                    AuditChange::updated(
+                        AuditEntity::RecurringRule,
+                        rule.id,
+                        before,
+                        snapshot(&model),
+                    ),
+                )
+                .await?;
+                Ok(rule)
+            })
+        })
+        .await
+    }
+
+    /// Deletes a recurring rule. Already posted transactions are kept.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn delete_recurring_rule(
+        &self,
+        vault_id: &str,
+        rule_id: Uuid,
+        user_id: &str,
+    ) -> ResultEngine<()> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id_write(db_tx, &vault_id, &user_id)
+                    .await?;
+                engine
+                    .require_recurring_rule(db_tx, &vault_id, rule_id)
+                    .await?;
+                let before = entity_snapshot::<recurring_rules::Entity>(db_tx, rule_id).await?;
+                recurring_rules::Entity::delete_by_id(rule_id)
+                    .exec(db_tx)
+                    .await?;
+                record_audit(
+                    db_tx,
+                    parse_vault_uuid(&vault_id)?,
+                    &user_id,
+                    AuditChange::deleted(AuditEntity::RecurringRule, rule_id, before),
+                )
+                .await?;
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Posts every occurrence due at `now` across all vaults, catching up
+    /// occurrences missed while the scheduler was not running.
+    ///
+    /// Each occurrence is created as the rule author through the regular
+    /// transaction commands (same authorization and validation), with a
+    /// deterministic idempotency key. A failing rule stops advancing and is
+    /// reported; other rules are unaffected. Unless the database failed, the
+    /// rule is also paused with the error recorded, so an occurrence that can
+    /// never be posted (e.g. in closed books) is not retried forever;
+    /// resuming it skips the failed occurrence.
+    pub async fn run_recurring_rules(
+        &self,
+        now: DateTime<Utc>,
+    ) -> ResultEngine<RecurringRunReport> {
+        let due = self
+            .with_tx(|_engine, db_tx| {
+                Box::pin(async move {
+                    recurring_rules::Entity::find()
+                        .filter(recurring_rules::Column::Active.eq(true))
+                        .filter(recurring_rules::Column::NextOccurrenceAt.lte(now))
+                        .order_by_asc(recurring_rules::Column::NextOccurrenceAt)
+                        .all(db_tx)
+                        .await?
+                        .into_iter()
+                        .map(RecurringRule::try_from)
+                        .collect::<ResultEngine<Vec<_>>>()
+                })
+            })
+            .await?;
+
+        let mut report = RecurringRunReport::default();
+        for mut rule in due {
+            for _ in 0..MAX_CATCH_UP_PER_RUN {
+                let Some(occurrence_at) = rule.next_occurrence_at.filter(|at| *at <= now) else {
+                    break;
+                };
+                let index = rule.occurrences;
+                if let Err(err) = self
+                    .post_recurring_occurrence(&rule, index, occurrence_at)
+                    .await
+                {
+                    report.failures.push(RecurringFailure {
+                        rule_id: rule.id,
+                        vault_id: rule.vault_id.clone(),
+                        occurrence_at,
+                        error: err.to_string(),
+                    });
+                    if !matches!(err, EngineError::Database(_)) {
+                        self.pause_recurring_rule(rule.id, err.to_string()).await?;
+                    }
+                    break;
+                }
+                report.posted += 1;
+
+                rule.occurrences = index + 1;
+                rule.next_occurrence_at = rule.recurrence.occurrence(rule.occurrences);
+                self.advance_recurring_rule(&rule).await?;
+            }
+        }
+        Ok(report)
+    }
+
+    async fn post_recurring_occurrence(
+        &self,
+        rule: &RecurringRule,
+        index: u32,
+        occurred_at: DateTime<Utc>,
+    ) -> ResultEngine<Uuid> {
+        let meta = TxMeta {
+            category_id: None,
+            category: rule.category.clone(),
+            note: rule.note.clone(),
+            idempotency_key: Some(rule.idempotency_key(index)),
+            occurred_at,
+        };
+        match rule.kind {
+            TransactionKind::Income => {
+                self.income(IncomeCmd {
+                    vault_id: rule.vault_id.clone(),
+                    amount_minor: rule.amount_minor,
+                    flow_id: rule.flow_id,
+                    wallet_id: rule.wallet_id,
+                    currency: None,
+                    exchange_rate: None,
+                    meta,
+                    user_id: rule.created_by.clone(),
+                })
+                .await
+            }
+            TransactionKind::Expense => {
+                self.expense(ExpenseCmd {
+                    vault_id: rule.vault_id.clone(),
+                    amount_minor: rule.amount_minor,
+                    flow_id: rule.flow_id,
+                    wallet_id: rule.wallet_id,
+                    currency: None,
+                    exchange_rate: None,
+                    meta,
+                    user_id: rule.created_by.clone(),
+                })
+                .await
+            }
+            TransactionKind::TransferFlow => {
+                let (Some(from_flow_id), Some(to_flow_id)) = (rule.from_flow_id, rule.to_flow_id)
+                else {
+                    return Err(EngineError::InvalidFlow(
+                        "transfer_flow rules require from_flow_id and to_flow_id".to_string(),
+                    ));
+                };
+                self.transfer_flow(TransferFlowCmd {
+                    vault_id: rule.vault_id.clone(),
+                    amount_minor: rule.amount_minor,
+                    from_flow_id,
+                    to_flow_id,
+                    note: meta.note,
+                    idempotency_key: meta.idempotency_key,
+                    occurred_at,
+                    user_id: rule.created_by.clone(),
+                })
+                .await
+            }
+            TransactionKind::TransferWallet | TransactionKind::Refund => {
+                Err(EngineError::InvalidAmount(
+                    "recurring rules support income, expense and transfer_flow".to_string(),
+                ))
+            }
+        }
+    }
+
+    async fn pause_recurring_rule(&self, rule_id: Uuid, error: String) -> ResultEngine<()> {
+        self.with_tx(|_engine, db_tx| {
+            Box::pin(async move {
+                recurring_rules::Entity::update_many()
+                    .col_expr(recurring_rules::Column::Active, Expr::value(false))
+                    .col_expr(recurring_rules::Column::LastError, Expr::value(error))
+                    .filter(recurring_rules::Column::Id.eq(rule_id))
+                    .exec(db_tx)
+                    .await?;
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn advance_recurring_rule(&self, rule: &RecurringRule) -> ResultEngine<()> {
+        let occurrences = i32::try_from(rule.occurrences)
+            .map_err(|_| EngineError::InvalidAmount("occurrences is too large".to_string()))?;
+        let id = rule.id;
+        let next_occurrence_at = rule.next_occurrence_at;
+        self.with_tx(|_engine, db_tx| {
+            Box::pin(async move {
+                // `update_many` tolerates a rule deleted while the run was in flight.
+                recurring_rules::Entity::update_many()
+                    .col_expr(
+                        recurring_rules::Column::Occurrences,
+                        Expr::value(occurrences),
+                    )
+                    .col_expr(
+                        recurring_rules::Column::NextOccurrenceAt,
+                        Expr::value(next_occurrence_at),
+                    )
+                    .filter(recurring_rules::Column::Id.eq(id))
+                    .exec(db_tx)
+                    .await?;
+                Ok(())
+            })
+        })
+        .await
+    }
+}
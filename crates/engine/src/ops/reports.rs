@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use sea_orm::{
+    DatabaseTransaction, FromQueryResult, JoinType, QueryFilter, QuerySelect, prelude::*,
+};
+use uuid::Uuid;
+
+use crate::{
+    EngineError, Report, ReportCmd, ReportGroupBy, ResultEngine, TransactionKind, cash_flows,
+    categories,
+    legs::{self, LegTargetKind},
+    reports::{self, ReportEntry},
+    transactions, wallets,
+};
+
+use super::{Engine, parse_vault_uuid};
+
+const REPORTED_KINDS: [TransactionKind; 3] = [
+    TransactionKind::Income,
+    TransactionKind::Expense,
+    TransactionKind::Refund,
+];
+
+/// A leg of a reported transaction.
+#[derive(FromQueryResult)]
+struct ReportLegRow {
+    transaction_id: Uuid,
+    target_kind: LegTargetKind,
+    target_id: Uuid,
+    amount_minor: i64,
+    leg_category_id: Option<Uuid>,
+    kind: TransactionKind,
+    occurred_at: DateTimeUtc,
+    category_id: Uuid,
+}
+
+fn validate_report_cmd(cmd: &ReportCmd) -> ResultEngine<()> {
+    reports::validate_group_by(&cmd.group_by)?;
+    if let (Some(from), Some(to)) = (cmd.from, cmd.to)
+        && from >= to
+    {
+        return Err(EngineError::InvalidAmount(
+            "invalid range: from must be < to".to_string(),
+        ));
+    }
+    if let Some(kinds) = &cmd.kinds {
+        if kinds.is_empty() {
+            return Err(EngineError::InvalidAmount(
+                "kinds must not be empty".to_string(),
+            ));
+        }
+        if kinds.iter().any(|kind| !REPORTED_KINDS.contains(kind)) {
+            return Err(EngineError::InvalidAmount(
+                "reports cover incomes, expenses and refunds only".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+async fn names_by_id<E>(
+    db_tx: &DatabaseTransaction,
+    vault_uuid: Uuid,
+    vault_column: E::Column,
+    id_column: E::Column,
+    name_column: E::Column,
+) -> ResultEngine<HashMap<Uuid, String>>
+where
+    E: EntityTrait,
+{
+    let rows: Vec<(Uuid, String)> = E::find()
+        .select_only()
+        .column(id_column)
+        .column(name_column)
+        .filter(vault_column.eq(vault_uuid))
+        .into_tuple()
+        .all(db_tx)
+        .await?;
+    Ok(rows.into_iter().collect())
+}
+
+impl Engine {
+    /// Aggregates the non-voided incomes, expenses and refunds of a vault
+    /// by category, flow, wallet and/or period. Amounts are summed from the
+    /// flow legs, in vault currency.
+    ///
+    /// Authorization: requires vault read access.
+    pub async fn report(&self, cmd: ReportCmd) -> ResultEngine<Report> {
+        validate_report_cmd(&cmd)?;
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let vault_model = engine
+                    .require_vault_by_id(db_tx, &cmd.vault_id, &cmd.user_id)
+                    .await?;
+                let vault_uuid = parse_vault_uuid(&cmd.vault_id)?;
+                let kinds = cmd.kinds.clone().unwrap_or_else(|| REPORTED_KINDS.to_vec());
+
+                let mut leg_query = legs::Entity::find()
+                    .select_only()
+                    .column(legs::Column::TransactionId)
+                    .column(legs::Column::TargetKind)
+                    .column(legs::Column::TargetId)
+                    .column(legs::Column::AmountMinor)
+                    .column_as(legs::Column::CategoryId, "leg_category_id")
+                    .column(transactions::Column::Kind)
+                    .column(transactions::Column::OccurredAt)
+                    .column(transactions::Column::CategoryId)
+                    .join(JoinType::InnerJoin, legs::Relation::Transactions.def())
+                    .filter(transactions::Column::VaultId.eq(vault_uuid))
+                    .filter(transactions::Column::Kind.is_in(kinds))
+                    .filter(transactions::Column::VoidedAt.is_null());
+                if let Some(from) = cmd.from {
+                    leg_query = leg_query.filter(transactions::Column::OccurredAt.gte(from));
+                }
+                if let Some(to) = cmd.to {
+                    leg_query = leg_query.filter(transactions::Column::OccurredAt.lt(to));
+                }
+                let rows = leg_query.into_model::<ReportLegRow>().all(db_tx).await?;
+
+                // Incomes, expenses and refunds have exactly one wallet leg.
+                let wallet_of: HashMap<Uuid, Uuid> = rows
+                    .iter()
+                    .filter(|row| row.target_kind == LegTargetKind::Wallet)
+                    .map(|row| (row.transaction_id, row.target_id))
+                    .collect();
+                let entries = rows
+                    .iter()
+                    .filter(|row| row.target_kind == LegTargetKind::Flow)
+                    .map(|row| ReportEntry {
+                        transaction_id: row.transaction_id,
+                        kind: row.kind,
+                        occurred_at: row.occurred_at,
+                        category_id: row.leg_category_id.unwrap_or(row.category_id),
+                        flow_id: row.target_id,
+                        wallet_id: wallet_of.get(&row.transaction_id).copied(),
+                        amount_minor: row.amount_minor,
+                    });
+                let mut rows = reports::aggregate(&cmd.group_by, cmd.utc_offset, entries)?;
+
+                if cmd.group_by.contains(&ReportGroupBy::Category) {
+                    let names = names_by_id::<categories::Entity>(
+                        db_tx,
+                        vault_uuid,
+                        categories::Column::VaultId,
+                        categories::Column::Id,
+                        categories::Column::Name,
+                    )
+                    .await?;
+                    for row in &mut rows {
+                        row.category = row.category_id.and_then(|id| names.get(&id).cloned());
+                    }
+                }
+                if cmd.group_by.contains(&ReportGroupBy::Flow) {
+                    let names = names_by_id::<cash_flows::Entity>(
+                        db_tx,
+                        vault_uuid,
+                        cash_flows::Column::VaultId,
+                        cash_flows::Column::Id,
+                        cash_flows::Column::Name,
+                    )
+                    .await?;
+                    for row in &mut rows {
+                        row.flow = row.flow_id.and_then(|id| names.get(&id).cloned());
+                    }
+                }
+                if cmd.group_by.contains(&ReportGroupBy::Wallet) {
+                    let names = names_by_id::<wallets::Entity>(
+                        db_tx,
+                        vault_uuid,
+                        wallets::Column::VaultId,
+                        wallets::Column::Id,
+                        wallets::Column::Name,
+                    )
+                    .await?;
+                    for row in &mut rows {
+                        row.wallet = row.wallet_id.and_then(|id| names.get(&id).cloned());
+                    }
+                }
+
+                Ok(Report {
+                    currency: vault_model.currency,
+                    group_by: cmd.group_by,
+                    rows,
+                })
+            })
+        })
+        .await
+    }
+}
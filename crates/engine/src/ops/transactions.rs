@@ -1,5 +1,7 @@
 mod helpers;
 mod list;
+mod refunds;
 mod write;
 
 pub use list::TransactionListFilter;
+pub(super) use write::{FlowWalletCmd, store_revision};
@@ -1,11 +1,11 @@
 use uuid::Uuid;
 
 use crate::{
-    Currency, EngineError, Leg, LegTarget, ResultEngine, TransactionKind, TxMeta,
+    Currency, EngineError, Leg, LegTarget, ResultEngine, Transaction, TransactionKind, TxMeta,
     util::normalize_optional_text,
 };
 
-use super::super::flow_wallet_signed_amount;
+use super::super::{flow_wallet_signed_amount, fx};
 
 pub(super) fn normalize_tx_note(meta: &TxMeta) -> Option<String> {
     normalize_optional_text(meta.note.as_deref())
@@ -15,7 +15,8 @@ pub(super) fn normalize_tx_note(meta: &TxMeta) -> Option<String> {
 
 pub(super) fn validate_transfer_legs<T, F>(
     legs: &[Leg],
-    amount_minor: i64,
+    from_amount_minor: i64,
+    to_amount_minor: i64,
     length_label: &str,
     kind_label: &str,
     target_label: &str,
@@ -43,9 +44,9 @@ where
         })?;
         targets.push(target);
 
-        if leg.amount_minor == -amount_minor {
+        if leg.amount_minor == -from_amount_minor {
             has_neg = true;
-        } else if leg.amount_minor == amount_minor {
+        } else if leg.amount_minor == to_amount_minor {
             has_pos = true;
         } else {
             return Err(EngineError::InvalidAmount(format!(
@@ -68,6 +69,39 @@ where
     Ok(())
 }
 
+/// Recomputes the expected wallet transfer amounts from a transaction header
+/// and the currencies of its legs.
+pub(super) fn transfer_wallet_amounts_for(
+    tx: &Transaction,
+    legs: &[Leg],
+) -> ResultEngine<fx::TransferWalletAmounts> {
+    let from_leg = legs
+        .iter()
+        .find(|leg| leg.amount_minor < 0)
+        .ok_or_else(|| {
+            EngineError::InvalidAmount("invalid transfer_wallet: missing negative leg".to_string())
+        })?;
+    let to_leg = legs
+        .iter()
+        .find(|leg| leg.amount_minor > 0)
+        .ok_or_else(|| {
+            EngineError::InvalidAmount("invalid transfer_wallet: missing positive leg".to_string())
+        })?;
+    if tx.currency != from_leg.currency {
+        return Err(EngineError::CurrencyMismatch(format!(
+            "transfer currency is {}, source leg currency is {}",
+            tx.currency.code(),
+            from_leg.currency.code()
+        )));
+    }
+    fx::resolve_transfer_wallet_amounts(
+        tx.amount_minor,
+        from_leg.currency,
+        to_leg.currency,
+        tx.exchange_rate,
+    )
+}
+
 pub(super) struct TransferLegInfo<T> {
     pub(super) from_target: T,
     pub(super) to_target: T,
@@ -148,17 +182,19 @@ pub(super) fn resolve_transfer_targets<T: Copy + Eq>(
 
 pub(super) struct TransferLegUpdateContext<'a, T> {
     pub(super) kind_label: &'a str,
-    pub(super) vault_currency: Currency,
     pub(super) from_leg_id: Uuid,
     pub(super) to_leg_id: Uuid,
     pub(super) new_from: T,
     pub(super) new_to: T,
-    pub(super) new_amount_minor: i64,
+    pub(super) from_amount_minor: i64,
+    pub(super) from_currency: Currency,
+    pub(super) to_amount_minor: i64,
+    pub(super) to_currency: Currency,
 }
 
 pub(super) struct TransferLegUpdateSink<'a> {
     pub(super) balance_updates: &'a mut Vec<(LegTarget, i64, i64)>,
-    pub(super) leg_updates: &'a mut Vec<(Uuid, LegTarget, i64)>,
+    pub(super) leg_updates: &'a mut Vec<(Uuid, LegTarget, i64, Currency)>,
 }
 
 pub(super) fn apply_transfer_leg_updates<T, F>(
@@ -172,18 +208,19 @@ where
     F: Fn(T) -> LegTarget,
 {
     for (model, leg) in leg_pairs {
-        if leg.currency != ctx.vault_currency {
-            return Err(EngineError::CurrencyMismatch(format!(
-                "vault currency is {}, got {}",
-                ctx.vault_currency.code(),
-                leg.currency.code()
-            )));
-        }
         let id = model.id;
-        let (new_target, new_amount) = if id == ctx.from_leg_id {
-            (make_target(ctx.new_from), -ctx.new_amount_minor)
+        let (new_target, new_amount, new_currency) = if id == ctx.from_leg_id {
+            (
+                make_target(ctx.new_from),
+                -ctx.from_amount_minor,
+                ctx.from_currency,
+            )
         } else if id == ctx.to_leg_id {
-            (make_target(ctx.new_to), ctx.new_amount_minor)
+            (
+                make_target(ctx.new_to),
+                ctx.to_amount_minor,
+                ctx.to_currency,
+            )
         } else {
             return Err(EngineError::InvalidAmount(format!(
                 "invalid {kind_label}: unexpected legs",
@@ -198,7 +235,8 @@ where
             sink.balance_updates.push((leg.target, leg.amount_minor, 0));
             sink.balance_updates.push((new_target, 0, new_amount));
         }
-        sink.leg_updates.push((model.id, new_target, new_amount));
+        sink.leg_updates
+            .push((model.id, new_target, new_amount, new_currency));
     }
 
     Ok(())
@@ -253,31 +291,52 @@ pub(super) fn validate_update_fields(
 }
 
 pub(super) fn validate_flow_wallet_legs(
-    kind: TransactionKind,
-    amount_minor: i64,
+    tx: &Transaction,
+    vault_currency: Currency,
     legs: &[Leg],
 ) -> ResultEngine<()> {
-    if legs.len() != 2 {
+    // One wallet leg and one flow leg, or several flow legs for a split.
+    if legs.len() < 2 {
         return Err(EngineError::InvalidAmount(
-            "invalid transaction: expected 2 legs".to_string(),
+            "invalid transaction: expected at least 2 legs".to_string(),
         ));
     }
-    let expected = flow_wallet_signed_amount(kind, amount_minor)?;
-    let (mut wallet_legs, mut flow_legs) = (0, 0);
-    for leg in legs {
-        match leg.target {
-            LegTarget::Wallet { .. } => wallet_legs += 1,
-            LegTarget::Flow { .. } => flow_legs += 1,
-        }
-        if leg.amount_minor != expected {
-            return Err(EngineError::InvalidAmount(
-                "invalid transaction: unexpected leg amount".to_string(),
-            ));
-        }
-    }
-    if wallet_legs != 1 || flow_legs != 1 {
+    let (wallet_legs, flow_legs): (Vec<&Leg>, Vec<&Leg>) = legs
+        .iter()
+        .partition(|leg| matches!(leg.target, LegTarget::Wallet { .. }));
+    let [wallet_leg] = wallet_legs[..] else {
+        return Err(EngineError::InvalidAmount(
+            "invalid transaction: expected one wallet leg and at least one flow leg".to_string(),
+        ));
+    };
+
+    let amounts = match (tx.original_amount_minor, tx.original_currency) {
+        (Some(original_minor), Some(original_currency)) => fx::resolve_flow_wallet_amounts(
+            original_minor,
+            original_currency,
+            tx.exchange_rate,
+            wallet_leg.currency,
+            vault_currency,
+        )?,
+        _ => fx::resolve_flow_wallet_amounts(
+            tx.amount_minor,
+            vault_currency,
+            None,
+            wallet_leg.currency,
+            vault_currency,
+        )?,
+    };
+    let flow_minor = flow_wallet_signed_amount(tx.kind, amounts.vault_minor)?;
+    let flow_sum: i64 = flow_legs.iter().map(|leg| leg.amount_minor).sum();
+    if amounts.vault_minor != tx.amount_minor
+        || wallet_leg.amount_minor != flow_wallet_signed_amount(tx.kind, amounts.wallet_minor)?
+        || flow_sum != flow_minor
+        || flow_legs
+            .iter()
+            .any(|leg| leg.amount_minor.signum() != flow_minor.signum())
+    {
         return Err(EngineError::InvalidAmount(
-            "invalid transaction: expected one wallet leg and one flow leg".to_string(),
+            "invalid transaction: unexpected leg amount".to_string(),
         ));
     }
     Ok(())
@@ -310,36 +369,34 @@ pub(super) fn extract_flow_wallet_targets(
     Ok((existing_wallet_id, existing_flow_id))
 }
 
+pub(super) struct FlowWalletLegUpdate {
+    pub(super) kind: TransactionKind,
+    pub(super) wallet_id: Uuid,
+    pub(super) flow_id: Uuid,
+    pub(super) amounts: fx::FlowWalletAmounts,
+}
+
 pub(super) fn apply_flow_wallet_leg_updates(
     leg_pairs: &[(crate::legs::Model, Leg)],
-    vault_currency: Currency,
-    new_wallet_id: Uuid,
-    new_flow_id: Uuid,
-    new_signed_amount: i64,
+    update: &FlowWalletLegUpdate,
     balance_updates: &mut Vec<(LegTarget, i64, i64)>,
-    leg_updates: &mut Vec<(Uuid, LegTarget, i64)>,
+    leg_updates: &mut Vec<(Uuid, LegTarget, i64, Currency)>,
 ) -> ResultEngine<()> {
     for (model, leg) in leg_pairs {
-        if leg.currency != vault_currency {
-            return Err(EngineError::CurrencyMismatch(format!(
-                "vault currency is {}, got {}",
-                vault_currency.code(),
-                leg.currency.code()
-            )));
-        }
-
-        let (new_target, new_amount) = match leg.target {
+        let (new_target, new_amount, new_currency) = match leg.target {
             LegTarget::Wallet { .. } => (
                 LegTarget::Wallet {
-                    wallet_id: new_wallet_id,
+                    wallet_id: update.wallet_id,
                 },
-                new_signed_amount,
+                flow_wallet_signed_amount(update.kind, update.amounts.wallet_minor)?,
+                update.amounts.wallet_currency,
             ),
             LegTarget::Flow { .. } => (
                 LegTarget::Flow {
-                    flow_id: new_flow_id,
+                    flow_id: update.flow_id,
                 },
-                new_signed_amount,
+                flow_wallet_signed_amount(update.kind, update.amounts.vault_minor)?,
+                update.amounts.vault_currency,
             ),
         };
 
@@ -349,7 +406,7 @@ pub(super) fn apply_flow_wallet_leg_updates(
             balance_updates.push((leg.target, leg.amount_minor, 0));
             balance_updates.push((new_target, 0, new_amount));
         }
-        leg_updates.push((model.id, new_target, new_amount));
+        leg_updates.push((model.id, new_target, new_amount, new_currency));
     }
 
     Ok(())
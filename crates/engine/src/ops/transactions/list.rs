@@ -3,11 +3,22 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use sea_orm::{Condition, QueryFilter, QueryOrder, QuerySelect, prelude::*};
+use std::collections::HashMap;
 
-use crate::{EngineError, ResultEngine, Transaction, TransactionKind, legs, transactions};
+use sea_orm::{
+    Condition, DatabaseTransaction, QueryFilter, QueryOrder, QuerySelect, prelude::*,
+    sea_query::Query,
+};
 
-use super::super::{Engine, parse_vault_uuid};
+use crate::{
+    EngineError, Leg, ResultEngine, Transaction, TransactionKind, legs, legs::LegTargetKind,
+    transactions,
+};
+
+use super::{
+    super::{Engine, parse_vault_uuid},
+    refunds::attach_refunded_amounts,
+};
 
 /// Filters for listing transactions.
 ///
@@ -76,7 +87,7 @@ where
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct TransactionsCursor {
     occurred_at: DateTime<Utc>,
-    transaction_id: String,
+    transaction_id: Uuid,
 }
 
 impl TransactionsCursor {
@@ -95,6 +106,39 @@ impl TransactionsCursor {
     }
 }
 
+/// Loads the legs of the split expenses among `txs`, so that list views can
+/// show their lines. Other transactions are left without legs.
+async fn attach_split_legs(
+    db_tx: &DatabaseTransaction,
+    mut txs: Vec<&mut Transaction>,
+) -> ResultEngine<()> {
+    if txs.is_empty() {
+        return Ok(());
+    }
+    let split_ids = Query::select()
+        .column(legs::Column::TransactionId)
+        .from(legs::Entity)
+        .and_where(legs::Column::TransactionId.is_in(txs.iter().map(|tx| tx.id)))
+        .and_where(legs::Column::CategoryId.is_not_null())
+        .to_owned();
+    let leg_models = legs::Entity::find()
+        .filter(legs::Column::TransactionId.in_subquery(split_ids))
+        .order_by_asc(legs::Column::Id)
+        .all(db_tx)
+        .await?;
+    let mut by_tx: HashMap<Uuid, Vec<Leg>> = HashMap::new();
+    for model in leg_models {
+        let leg = Leg::try_from(model)?;
+        by_tx.entry(leg.transaction_id).or_default().push(leg);
+    }
+    for tx in &mut txs {
+        if let Some(legs) = by_tx.remove(&tx.id) {
+            tx.legs = legs;
+        }
+    }
+    Ok(())
+}
+
 impl Engine {
     /// Lists recent transactions that affect a given flow.
     ///
@@ -140,12 +184,21 @@ impl Engine {
                 validate_list_filter(&filter)?;
                 let vault_uuid = parse_vault_uuid(vault_id.as_str())?;
 
+                // A split expense may charge the same flow on several lines: select
+                // transactions (not legs) and sum their legs on the flow.
                 let limit_plus_one = limit.saturating_add(1);
-                let mut query = legs::Entity::find()
-                    .filter(legs::Column::TargetKind.eq(crate::legs::LegTargetKind::Flow))
-                    .filter(legs::Column::TargetId.eq(flow_id))
-                    .find_also_related(transactions::Entity)
+                let mut query = transactions::Entity::find()
                     .filter(transactions::Column::VaultId.eq(vault_uuid))
+                    .filter(
+                        transactions::Column::Id.in_subquery(
+                            Query::select()
+                                .column(legs::Column::TransactionId)
+                                .from(legs::Entity)
+                                .and_where(legs::Column::TargetKind.eq(LegTargetKind::Flow))
+                                .and_where(legs::Column::TargetId.eq(flow_id))
+                                .to_owned(),
+                        ),
+                    )
                     .order_by_desc(transactions::Column::OccurredAt)
                     .order_by_desc(transactions::Column::Id)
                     .limit(limit_plus_one);
@@ -164,23 +217,41 @@ impl Engine {
                 }
                 query = query.apply_tx_filters(&filter);
 
-                let rows: Vec<(legs::Model, Option<transactions::Model>)> =
-                    query.all(db_tx).await?;
+                let rows: Vec<transactions::Model> = query.all(db_tx).await?;
                 let has_more = rows.len() > limit as usize;
-
-                let mut out: Vec<(Transaction, i64)> =
-                    Vec::with_capacity(rows.len().min(limit as usize));
-                for (leg_model, tx_model) in rows.into_iter().take(limit as usize) {
-                    let Some(tx_model) = tx_model else {
-                        continue;
-                    };
-                    let tx = Transaction::try_from(tx_model)?;
-                    out.push((tx, leg_model.amount_minor));
+                let mut txs = rows
+                    .into_iter()
+                    .take(limit as usize)
+                    .map(Transaction::try_from)
+                    .collect::<ResultEngine<Vec<_>>>()?;
+                attach_split_legs(db_tx, txs.iter_mut().collect()).await?;
+                attach_refunded_amounts(db_tx, txs.iter_mut().collect()).await?;
+
+                let flow_legs: Vec<(Uuid, i64)> = legs::Entity::find()
+                    .select_only()
+                    .column(legs::Column::TransactionId)
+                    .column(legs::Column::AmountMinor)
+                    .filter(legs::Column::TargetKind.eq(LegTargetKind::Flow))
+                    .filter(legs::Column::TargetId.eq(flow_id))
+                    .filter(legs::Column::TransactionId.is_in(txs.iter().map(|tx| tx.id)))
+                    .into_tuple()
+                    .all(db_tx)
+                    .await?;
+                let mut flow_amounts: HashMap<Uuid, i64> = HashMap::new();
+                for (tx_id, amount_minor) in flow_legs {
+                    *flow_amounts.entry(tx_id).or_default() += amount_minor;
                 }
+                let out: Vec<(Transaction, i64)> = txs
+                    .into_iter()
+                    .map(|tx| {
+                        let amount_minor = flow_amounts.get(&tx.id).copied().unwrap_or_default();
+                        (tx, amount_minor)
+                    })
+                    .collect();
 
                 let next_cursor = out.last().map(|(tx, _)| TransactionsCursor {
                     occurred_at: tx.occurred_at,
-                    transaction_id: tx.id.to_string(),
+                    transaction_id: tx.id,
                 });
                 let next_cursor = if has_more {
                     next_cursor.map(|c| c.encode()).transpose()?
@@ -247,10 +318,12 @@ impl Engine {
                 for tx_model in rows.into_iter().take(limit as usize) {
                     out.push(Transaction::try_from(tx_model)?);
                 }
+                attach_split_legs(db_tx, out.iter_mut().collect()).await?;
+                attach_refunded_amounts(db_tx, out.iter_mut().collect()).await?;
 
                 let next_cursor = out.last().map(|tx| TransactionsCursor {
                     occurred_at: tx.occurred_at,
-                    transaction_id: tx.id.to_string(),
+                    transaction_id: tx.id,
                 });
                 let next_cursor = if has_more {
                     next_cursor.map(|c| c.encode()).transpose()?
@@ -310,7 +383,7 @@ impl Engine {
 
                 let limit_plus_one = limit.saturating_add(1);
                 let mut query = legs::Entity::find()
-                    .filter(legs::Column::TargetKind.eq(crate::legs::LegTargetKind::Wallet))
+                    .filter(legs::Column::TargetKind.eq(LegTargetKind::Wallet))
                     .filter(legs::Column::TargetId.eq(wallet_id))
                     .find_also_related(transactions::Entity)
                     .filter(transactions::Column::VaultId.eq(vault_uuid))
@@ -345,10 +418,12 @@ impl Engine {
                     let tx = Transaction::try_from(tx_model)?;
                     out.push((tx, leg_model.amount_minor));
                 }
+                attach_split_legs(db_tx, out.iter_mut().map(|(tx, _)| tx).collect()).await?;
+                attach_refunded_amounts(db_tx, out.iter_mut().map(|(tx, _)| tx).collect()).await?;
 
                 let next_cursor = out.last().map(|(tx, _)| TransactionsCursor {
                     occurred_at: tx.occurred_at,
-                    transaction_id: tx.id.to_string(),
+                    transaction_id: tx.id,
                 });
                 let next_cursor = if has_more {
                     next_cursor.map(|c| c.encode()).transpose()?
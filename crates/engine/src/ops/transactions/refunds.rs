@@ -0,0 +1,197 @@
+//! Links between refunds and the expenses they pay back.
+//!
+//! Amounts are compared in vault currency (`transactions.amount_minor`).
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use sea_orm::{DatabaseTransaction, QueryFilter, QuerySelect, prelude::*};
+
+use crate::{
+    EngineError, Leg, LegTarget, ResultEngine, Transaction, TransactionKind, legs, transactions,
+};
+
+/// Expense referenced by a refund, with the targets it lends as defaults.
+pub(super) struct RefundedExpense {
+    pub(super) id: Uuid,
+    pub(super) amount_minor: i64,
+    pub(super) wallet_id: Uuid,
+    /// `None` when a split expense charged several flows.
+    pub(super) flow_id: Option<Uuid>,
+    pub(super) category_id: Uuid,
+}
+
+/// Loads the expense a refund points at.
+///
+/// Only non-voided expenses of the same vault can be refunded.
+pub(super) async fn load_refunded_expense(
+    db_tx: &DatabaseTransaction,
+    vault_uuid: Uuid,
+    expense_id: Uuid,
+) -> ResultEngine<RefundedExpense> {
+    let model = transactions::Entity::find_by_id(expense_id)
+        .one(db_tx)
+        .await?
+        .filter(|model| model.vault_id == vault_uuid)
+        .ok_or_else(|| EngineError::KeyNotFound("refunded transaction not exists".to_string()))?;
+    if model.kind != TransactionKind::Expense {
+        return Err(EngineError::InvalidAmount(
+            "only expenses can be refunded".to_string(),
+        ));
+    }
+    if model.voided_at.is_some() {
+        return Err(EngineError::InvalidAmount(
+            "cannot refund a voided transaction".to_string(),
+        ));
+    }
+
+    let mut wallet_id = None;
+    let mut flow_ids: Vec<Uuid> = Vec::new();
+    for leg_model in legs::Entity::find()
+        .filter(legs::Column::TransactionId.eq(expense_id))
+        .all(db_tx)
+        .await?
+    {
+        match Leg::try_from(leg_model)?.target {
+            LegTarget::Wallet { wallet_id: id } => wallet_id = Some(id),
+            LegTarget::Flow { flow_id } => {
+                if !flow_ids.contains(&flow_id) {
+                    flow_ids.push(flow_id);
+                }
+            }
+        }
+    }
+    let wallet_id = wallet_id.ok_or_else(|| {
+        EngineError::InvalidAmount("invalid transaction: missing wallet leg".to_string())
+    })?;
+
+    Ok(RefundedExpense {
+        id: model.id,
+        amount_minor: model.amount_minor,
+        wallet_id,
+        flow_id: (flow_ids.len() == 1).then(|| flow_ids[0]),
+        category_id: model.category_id,
+    })
+}
+
+/// Sums the non-voided refunds of each expense in `expense_ids`.
+async fn refunded_totals(
+    db_tx: &DatabaseTransaction,
+    expense_ids: Vec<Uuid>,
+    except_refund_id: Option<Uuid>,
+) -> ResultEngine<HashMap<Uuid, i64>> {
+    let mut totals: HashMap<Uuid, i64> = HashMap::new();
+    if expense_ids.is_empty() {
+        return Ok(totals);
+    }
+    let mut query = transactions::Entity::find()
+        .select_only()
+        .column(transactions::Column::RefundedTransactionId)
+        .column(transactions::Column::AmountMinor)
+        .filter(transactions::Column::Kind.eq(TransactionKind::Refund))
+        .filter(transactions::Column::VoidedAt.is_null())
+        .filter(transactions::Column::RefundedTransactionId.is_in(expense_ids));
+    if let Some(refund_id) = except_refund_id {
+        query = query.filter(transactions::Column::Id.ne(refund_id));
+    }
+    let rows: Vec<(Option<Uuid>, i64)> = query.into_tuple().all(db_tx).await?;
+    for (expense_id, amount_minor) in rows {
+        if let Some(expense_id) = expense_id {
+            *totals.entry(expense_id).or_default() += amount_minor;
+        }
+    }
+    Ok(totals)
+}
+
+/// Rejects a refund of `amount_minor` exceeding what is left of `expense`.
+///
+/// `refund_id` is the refund being edited or restored, if any, so that its
+/// current amount is not counted twice.
+pub(super) async fn ensure_refund_fits(
+    db_tx: &DatabaseTransaction,
+    expense: &RefundedExpense,
+    refund_id: Option<Uuid>,
+    amount_minor: i64,
+) -> ResultEngine<()> {
+    let refunded = refunded_totals(db_tx, vec![expense.id], refund_id)
+        .await?
+        .remove(&expense.id)
+        .unwrap_or_default();
+    let remaining = expense.amount_minor - refunded;
+    if amount_minor > remaining {
+        return Err(EngineError::InvalidAmount(format!(
+            "refund exceeds the unrefunded amount of the expense ({remaining})"
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that a transaction with the new `amount_minor` keeps its refund
+/// links consistent: a linked refund stays within its expense, and an expense
+/// stays at least as large as its refunds.
+pub(super) async fn ensure_refund_links(
+    db_tx: &DatabaseTransaction,
+    tx_model: &transactions::Model,
+    amount_minor: i64,
+) -> ResultEngine<()> {
+    match (tx_model.kind, tx_model.refunded_transaction_id) {
+        (TransactionKind::Refund, Some(expense_id)) => {
+            let expense = load_refunded_expense(db_tx, tx_model.vault_id, expense_id).await?;
+            ensure_refund_fits(db_tx, &expense, Some(tx_model.id), amount_minor).await
+        }
+        (TransactionKind::Expense, _) => {
+            let refunded = refunded_totals(db_tx, vec![tx_model.id], None)
+                .await?
+                .remove(&tx_model.id)
+                .unwrap_or_default();
+            if amount_minor < refunded {
+                return Err(EngineError::InvalidAmount(format!(
+                    "expense amount must cover its refunds ({refunded})"
+                )));
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Rejects voiding an expense that still has non-voided refunds: they could
+/// no longer be updated or restored.
+pub(super) async fn ensure_no_live_refunds(
+    db_tx: &DatabaseTransaction,
+    tx_model: &transactions::Model,
+) -> ResultEngine<()> {
+    if tx_model.kind != TransactionKind::Expense {
+        return Ok(());
+    }
+    let refunded = refunded_totals(db_tx, vec![tx_model.id], None)
+        .await?
+        .remove(&tx_model.id)
+        .unwrap_or_default();
+    if refunded != 0 {
+        return Err(EngineError::InvalidAmount(format!(
+            "expense has refunds ({refunded}), void them first"
+        )));
+    }
+    Ok(())
+}
+
+/// Fills `refunded_minor` on the expenses among `txs`, for read views.
+pub(super) async fn attach_refunded_amounts(
+    db_tx: &DatabaseTransaction,
+    mut txs: Vec<&mut Transaction>,
+) -> ResultEngine<()> {
+    let expense_ids: Vec<Uuid> = txs
+        .iter()
+        .filter(|tx| tx.kind == TransactionKind::Expense)
+        .map(|tx| tx.id)
+        .collect();
+    let totals = refunded_totals(db_tx, expense_ids, None).await?;
+    for tx in &mut txs {
+        if tx.kind == TransactionKind::Expense {
+            tx.refunded_minor = Some(totals.get(&tx.id).copied().unwrap_or_default());
+        }
+    }
+    Ok(())
+}
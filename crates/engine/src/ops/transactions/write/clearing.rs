@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use sea_orm::{ActiveValue, DatabaseTransaction, JoinType, QueryFilter, QuerySelect, prelude::*};
+
+use crate::{
+    AuditAction, AuditEntity, ClearingStatus, EngineError, ReconcileWalletCmd, Reconciliation,
+    ResultEngine,
+    audit::AuditChange,
+    legs::{self, LegTargetKind},
+    transactions,
+};
+
+use super::{
+    super::super::{
+        Engine,
+        audit::{record_audit, transaction_snapshot},
+        parse_vault_uuid,
+    },
+    revisions::require_transaction,
+};
+
+/// Sets the clearing status of a transaction and audits the change.
+async fn set_clearing_status(
+    db_tx: &DatabaseTransaction,
+    vault_uuid: Uuid,
+    user_id: &str,
+    action: AuditAction,
+    transaction_id: Uuid,
+    status: ClearingStatus,
+) -> ResultEngine<()> {
+    let before = transaction_snapshot(db_tx, transaction_id).await?;
+    transactions::ActiveModel {
+        id: ActiveValue::Set(transaction_id),
+        clearing_status: ActiveValue::Set(status),
+        ..Default::default()
+    }
+    .update(db_tx)
+    .await?;
+    let after = transaction_snapshot(db_tx, transaction_id).await?;
+    record_audit(
+        db_tx,
+        vault_uuid,
+        user_id,
+        AuditChange::new(
+            action,
+            AuditEntity::Transaction,
+            transaction_id,
+            before,
+            after,
+        ),
+    )
+    .await
+}
+
+impl Engine {
+    /// Marks a transaction as cleared (seen on a statement) or back as
+    /// uncleared.
+    ///
+    /// Reconciled transactions must be unlocked first.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn set_transaction_cleared(
+        &self,
+        vault_id: &str,
+        transaction_id: Uuid,
+        cleared: bool,
+        user_id: &str,
+    ) -> ResultEngine<()> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id_write(db_tx, vault_id.as_str(), user_id.as_str())
+                    .await?;
+                let vault_uuid = parse_vault_uuid(vault_id.as_str())?;
+                let tx_model = require_transaction(db_tx, vault_uuid, transaction_id).await?;
+                if tx_model.voided_at.is_some() {
+                    return Err(EngineError::InvalidAmount(
+                        "cannot clear a voided transaction".to_string(),
+                    ));
+                }
+                tx_model.ensure_unlocked()?;
+                let status = if cleared {
+                    ClearingStatus::Cleared
+                } else {
+                    ClearingStatus::Uncleared
+                };
+                if tx_model.clearing_status == status {
+                    return Ok(());
+                }
+                set_clearing_status(
+                    db_tx,
+                    vault_uuid,
+                    user_id.as_str(),
+                    AuditAction::Update,
+                    transaction_id,
+                    status,
+                )
+                .await
+            })
+        })
+        .await
+    }
+
+    /// Unlocks a reconciled transaction so it can be updated or voided
+    /// again. It goes back to cleared, and is reconciled again by the next
+    /// reconciliation that covers it.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn unlock_transaction(
+        &self,
+        vault_id: &str,
+        transaction_id: Uuid,
+        user_id: &str,
+    ) -> ResultEngine<()> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id_write(db_tx, vault_id.as_str(), user_id.as_str())
+                    .await?;
+                let vault_uuid = parse_vault_uuid(vault_id.as_str())?;
+                let tx_model = require_transaction(db_tx, vault_uuid, transaction_id).await?;
+                if tx_model.clearing_status != ClearingStatus::Reconciled {
+                    return Err(EngineError::InvalidAmount(
+                        "transaction is not reconciled".to_string(),
+                    ));
+                }
+                set_clearing_status(
+                    db_tx,
+                    vault_uuid,
+                    user_id.as_str(),
+                    AuditAction::Update,
+                    transaction_id,
+                    ClearingStatus::Cleared,
+                )
+                .await
+            })
+        })
+        .await
+    }
+
+    /// Compares the cleared balance of a wallet with a statement closing
+    /// balance. Nothing is written.
+    ///
+    /// Authorization: requires vault read access.
+    pub async fn preview_reconciliation(
+        &self,
+        cmd: ReconcileWalletCmd,
+    ) -> ResultEngine<Reconciliation> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id(db_tx, &cmd.vault_id, &cmd.user_id)
+                    .await?;
+                let (reconciliation, _) = engine.reconciliation_in_tx(db_tx, &cmd).await?;
+                Ok(reconciliation)
+            })
+        })
+        .await
+    }
+
+    /// Reconciles a wallet against a statement: when the cleared balance
+    /// matches the statement closing balance, every cleared transaction the
+    /// statement covers becomes reconciled and locked. A non-zero difference
+    /// is rejected and nothing is written.
+    ///
+    /// Authorization: requires vault write access.
+    pub async fn reconcile_wallet(&self, cmd: ReconcileWalletCmd) -> ResultEngine<Reconciliation> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id_write(db_tx, &cmd.vault_id, &cmd.user_id)
+                    .await?;
+                let (reconciliation, cleared) = engine.reconciliation_in_tx(db_tx, &cmd).await?;
+                if reconciliation.difference_minor != 0 {
+                    return Err(EngineError::InvalidAmount(format!(
+                        "statement balance differs from the cleared balance by {}",
+                        reconciliation.difference_minor
+                    )));
+                }
+                let vault_uuid = parse_vault_uuid(&cmd.vault_id)?;
+                for transaction_id in cleared {
+                    set_clearing_status(
+                        db_tx,
+                        vault_uuid,
+                        &cmd.user_id,
+                        AuditAction::Reconcile,
+                        transaction_id,
+                        ClearingStatus::Reconciled,
+                    )
+                    .await?;
+                }
+                Ok(reconciliation)
+            })
+        })
+        .await
+    }
+
+    /// Computes the reconciliation of `cmd` and returns it with the ids of
+    /// the cleared transactions it covers.
+    async fn reconciliation_in_tx(
+        &self,
+        db_tx: &DatabaseTransaction,
+        cmd: &ReconcileWalletCmd,
+    ) -> ResultEngine<(Reconciliation, Vec<Uuid>)> {
+        let currency = self
+            .wallet_currency(db_tx, &cmd.vault_id, cmd.wallet_id)
+            .await?;
+        let vault_uuid = parse_vault_uuid(&cmd.vault_id)?;
+        let rows: Vec<(Uuid, ClearingStatus, i64)> = legs::Entity::find()
+            .join(JoinType::InnerJoin, legs::Relation::Transactions.def())
+            .select_only()
+            .column(transactions::Column::Id)
+            .column(transactions::Column::ClearingStatus)
+            .column(legs::Column::AmountMinor)
+            .filter(transactions::Column::VaultId.eq(vault_uuid))
+            .filter(transactions::Column::VoidedAt.is_null())
+            .filter(transactions::Column::OccurredAt.lt(cmd.statement_date))
+            .filter(legs::Column::TargetKind.eq(LegTargetKind::Wallet))
+            .filter(legs::Column::TargetId.eq(cmd.wallet_id))
+            .into_tuple()
+            .all(db_tx)
+            .await?;
+
+        let overflow = || EngineError::InvalidAmount("amount overflow".to_string());
+        let mut cleared_balance_minor = 0i64;
+        let mut uncleared_minor = 0i64;
+        let mut statuses: HashMap<Uuid, ClearingStatus> = HashMap::new();
+        for (transaction_id, status, amount_minor) in rows {
+            let sum = if status == ClearingStatus::Uncleared {
+                &mut uncleared_minor
+            } else {
+                &mut cleared_balance_minor
+            };
+            *sum = sum.checked_add(amount_minor).ok_or_else(overflow)?;
+            statuses.insert(transaction_id, status);
+        }
+
+        let count =
+            |status: ClearingStatus| statuses.values().filter(|value| **value == status).count();
+        let mut cleared: Vec<Uuid> = statuses
+            .iter()
+            .filter(|(_, status)| **status == ClearingStatus::Cleared)
+            .map(|(id, _)| *id)
+            .collect();
+        cleared.sort_unstable();
+        let reconciliation = Reconciliation {
+            wallet_id: cmd.wallet_id,
+            currency,
+            statement_balance_minor: cmd.statement_balance_minor,
+            cleared_balance_minor,
+            difference_minor: cmd
+                .statement_balance_minor
+                .checked_sub(cleared_balance_minor)
+                .ok_or_else(overflow)?,
+            cleared_count: count(ClearingStatus::Cleared),
+            uncleared_count: count(ClearingStatus::Uncleared),
+            uncleared_minor,
+            reconciled_count: count(ClearingStatus::Reconciled),
+        };
+        Ok((reconciliation, cleared))
+    }
+}
@@ -5,21 +5,28 @@ use uuid::Uuid;
 use sea_orm::{ActiveValue, DatabaseTransaction, QueryFilter, prelude::*};
 
 use crate::{
-    Currency, EngineError, Leg, LegTarget, ResultEngine, Transaction, TransactionKind, TxMeta,
-    cash_flows, legs, transactions,
+    AuditEntity, Currency, EngineError, ExchangeRate, Leg, LegTarget, ResultEngine, Transaction,
+    TransactionKind, TxMeta,
+    audit::AuditChange,
+    cash_flows,
+    category_rules::RuleSubject,
+    legs, transactions,
     util::{ensure_vault_currency, validate_flow_mode_fields},
-    wallets,
+    vault, wallets,
 };
 
 use super::super::{
     super::{
-        Engine, TransactionBuildInput, build_transaction, flow_wallet_legs,
-        flow_wallet_signed_amount, parse_vault_uuid,
+        Engine, TransactionBuildInput,
+        audit::{record_audit, transaction_snapshot},
+        build_transaction, flow_wallet_legs, fx, parse_vault_uuid,
     },
     helpers::{
         apply_transfer_leg_updates, normalize_tx_note, parse_transfer_leg_pairs,
-        resolve_transfer_targets, validate_flow_wallet_legs, validate_transfer_legs,
+        resolve_transfer_targets, transfer_wallet_amounts_for, validate_flow_wallet_legs,
+        validate_transfer_legs,
     },
+    refunds::{ensure_refund_fits, load_refunded_expense},
 };
 
 #[derive(Clone, Copy, Debug)]
@@ -28,14 +35,18 @@ pub(super) enum TransferTargetKind {
     Flow,
 }
 
-pub(super) struct FlowWalletCmd {
-    pub(super) vault_id: String,
-    pub(super) amount_minor: i64,
-    pub(super) flow_id: Option<Uuid>,
-    pub(super) wallet_id: Option<Uuid>,
-    pub(super) meta: TxMeta,
-    pub(super) user_id: String,
-    pub(super) kind: TransactionKind,
+pub(in crate::ops) struct FlowWalletCmd {
+    pub(in crate::ops) vault_id: String,
+    pub(in crate::ops) amount_minor: i64,
+    pub(in crate::ops) flow_id: Option<Uuid>,
+    pub(in crate::ops) wallet_id: Option<Uuid>,
+    pub(in crate::ops) currency: Option<Currency>,
+    pub(in crate::ops) exchange_rate: Option<ExchangeRate>,
+    pub(in crate::ops) meta: TxMeta,
+    pub(in crate::ops) user_id: String,
+    pub(in crate::ops) kind: TransactionKind,
+    /// Expense paid back by a refund.
+    pub(in crate::ops) refunded_transaction_id: Option<Uuid>,
 }
 
 pub(super) struct TransferTransactionInput<'a> {
@@ -46,7 +57,11 @@ pub(super) struct TransferTransactionInput<'a> {
     pub(super) note: Option<String>,
     pub(super) idempotency_key: Option<String>,
     pub(super) kind: TransactionKind,
+    /// Currency of `amount_minor` (the source wallet currency for wallet
+    /// transfers, the vault currency for flow transfers).
     pub(super) currency: Currency,
+    pub(super) vault_currency: Currency,
+    pub(super) conversion: Option<fx::Conversion>,
 }
 
 pub(super) struct TransferUpdateInput<'a> {
@@ -57,12 +72,26 @@ pub(super) struct TransferUpdateInput<'a> {
     pub(super) to_override: Option<Uuid>,
     pub(super) kind: TransferTargetKind,
     pub(super) vault_currency: Currency,
-    pub(super) new_amount_minor: i64,
+    /// New amount in the source currency, if changed.
+    pub(super) amount_minor: Option<i64>,
+    /// Stored amount in the source currency.
+    pub(super) current_amount_minor: i64,
+    pub(super) current_currency: Currency,
+    /// Explicit new rate (falls back to `current_rate`).
+    pub(super) exchange_rate: Option<ExchangeRate>,
+    pub(super) current_rate: Option<ExchangeRate>,
 }
 
 pub(super) struct TransferUpdateOutput<'a> {
     pub(super) balance_updates: &'a mut Vec<(LegTarget, i64, i64)>,
-    pub(super) leg_updates: &'a mut Vec<(Uuid, LegTarget, i64)>,
+    pub(super) leg_updates: &'a mut Vec<(Uuid, LegTarget, i64, Currency)>,
+}
+
+/// Resulting transaction amount/currency after retargeting a transfer.
+pub(super) struct TransferUpdateResult {
+    pub(super) amount_minor: i64,
+    pub(super) currency: Currency,
+    pub(super) conversion: Option<fx::Conversion>,
 }
 
 struct FlowChangeInput<'a> {
@@ -82,56 +111,123 @@ impl Engine {
     ) -> ResultEngine<Uuid> {
         self.with_tx(|engine, db_tx| {
             Box::pin(async move {
-                let note = normalize_tx_note(&cmd.meta);
-                let vault_model = engine
-                    .require_vault_by_id_write(db_tx, &cmd.vault_id, &cmd.user_id)
-                    .await?;
-                let currency = vault_model.currency;
-                let category = engine
-                    .resolve_category_input(
-                        db_tx,
-                        &cmd.vault_id,
-                        cmd.meta.category_id,
-                        cmd.meta.category.as_deref(),
-                    )
-                    .await?;
-                let resolved_flow_id = engine
-                    .resolve_flow_id(db_tx, &cmd.vault_id, cmd.flow_id)
-                    .await?;
-                let resolved_wallet_id = engine
-                    .resolve_wallet_id(db_tx, &cmd.vault_id, cmd.wallet_id)
-                    .await?;
-                let leg_amount_minor = flow_wallet_signed_amount(cmd.kind, cmd.amount_minor)?;
-
-                let tx = build_transaction(TransactionBuildInput {
-                    vault_id: &cmd.vault_id,
-                    kind: cmd.kind,
-                    occurred_at: cmd.meta.occurred_at,
-                    amount_minor: cmd.amount_minor,
-                    currency,
-                    category_id: category.id,
-                    category: category.name,
-                    note,
-                    created_by: &cmd.user_id,
-                    idempotency_key: cmd.meta.idempotency_key.clone(),
-                    refunded_transaction_id: None,
-                })?;
-                let legs = flow_wallet_legs(
-                    tx.id,
-                    resolved_wallet_id,
-                    resolved_flow_id,
-                    leg_amount_minor,
-                    currency,
-                );
-
                 engine
-                    .create_transaction_with_legs(db_tx, &cmd.vault_id, currency, &tx, &legs)
+                    .create_flow_wallet_transaction_in_tx(db_tx, cmd)
                     .await
             })
         })
         .await
     }
 
+    /// Creates an income/expense/refund inside an existing DB transaction.
+    pub(in crate::ops) async fn create_flow_wallet_transaction_in_tx(
+        &self,
+        db_tx: &DatabaseTransaction,
+        cmd: FlowWalletCmd,
+    ) -> ResultEngine<Uuid> {
+        let note = normalize_tx_note(&cmd.meta);
+        let vault_model = self
+            .require_vault_by_id_write(db_tx, &cmd.vault_id, &cmd.user_id)
+            .await?;
+        let vault_currency = vault_model.currency;
+        let refunded = match cmd.refunded_transaction_id {
+            Some(expense_id) => Some(
+                load_refunded_expense(db_tx, parse_vault_uuid(&cmd.vault_id)?, expense_id).await?,
+            ),
+            None => None,
+        };
+        let mut flow_id = cmd.flow_id;
+        let mut wallet_id = cmd.wallet_id;
+        let mut category_id = cmd.meta.category_id;
+        if let Some(expense) = &refunded {
+            if flow_id.is_none() {
+                flow_id = Some(expense.flow_id.ok_or_else(|| {
+                    EngineError::InvalidAmount(
+                        "flow_id is required to refund an expense split across flows".to_string(),
+                    )
+                })?);
+            }
+            wallet_id = wallet_id.or(Some(expense.wallet_id));
+            if category_id.is_none() && cmd.meta.category.is_none() {
+                category_id = Some(expense.category_id);
+            }
+        }
+        let resolved_flow_id = self.resolve_flow_id(db_tx, &cmd.vault_id, flow_id).await?;
+        let resolved_wallet_id = self
+            .resolve_wallet_id(db_tx, &cmd.vault_id, wallet_id)
+            .await?;
+        let wallet_currency = self
+            .wallet_currency(db_tx, &cmd.vault_id, resolved_wallet_id)
+            .await?;
+        let amounts = fx::resolve_flow_wallet_amounts(
+            cmd.amount_minor,
+            cmd.currency.unwrap_or(wallet_currency),
+            cmd.exchange_rate,
+            wallet_currency,
+            vault_currency,
+        )?;
+        if cmd.exchange_rate.is_some() && amounts.conversion.is_none() {
+            return Err(EngineError::InvalidAmount(
+                "exchange_rate given but no currency conversion is needed".to_string(),
+            ));
+        }
+        if let Some(expense) = &refunded {
+            // A retried refund (same idempotency key) is already counted.
+            let retried = match cmd.meta.idempotency_key.as_deref() {
+                Some(key) => transactions::Entity::find()
+                    .filter(transactions::Column::VaultId.eq(parse_vault_uuid(&cmd.vault_id)?))
+                    .filter(transactions::Column::CreatedBy.eq(cmd.user_id.clone()))
+                    .filter(transactions::Column::IdempotencyKey.eq(key.to_string()))
+                    .one(db_tx)
+                    .await?
+                    .map(|model| model.id),
+                None => None,
+            };
+            ensure_refund_fits(db_tx, expense, retried, amounts.vault_minor).await?;
+        }
+        let rules = self.load_category_rules(db_tx, &cmd.vault_id).await?;
+        let category = self
+            .resolve_category_with_rules(
+                db_tx,
+                &cmd.vault_id,
+                (category_id, cmd.meta.category.as_deref()),
+                &rules,
+                &RuleSubject {
+                    kind: cmd.kind,
+                    amount_minor: amounts.vault_minor,
+                    wallet_id: Some(resolved_wallet_id),
+                    flow_id: Some(resolved_flow_id),
+                    note: note.as_deref(),
+                },
+            )
+            .await?;
+
+        let tx = build_transaction(TransactionBuildInput {
+            vault_id: &cmd.vault_id,
+            kind: cmd.kind,
+            occurred_at: cmd.meta.occurred_at,
+            amount_minor: amounts.vault_minor,
+            currency: vault_currency,
+            category_id: category.id,
+            category: category.name,
+            note,
+            created_by: &cmd.user_id,
+            idempotency_key: cmd.meta.idempotency_key.clone(),
+            refunded_transaction_id: cmd.refunded_transaction_id,
+            conversion: amounts.conversion,
+        })?;
+        let legs = flow_wallet_legs(
+            tx.id,
+            resolved_wallet_id,
+            resolved_flow_id,
+            cmd.kind,
+            &amounts,
+        )?;
+
+        self.create_transaction_with_legs(db_tx, &cmd.vault_id, vault_currency, &tx, &legs)
+            .await
+    }
+
     pub(super) async fn create_transfer_transaction(
         &self,
         db_tx: &DatabaseTransaction,
@@ -151,9 +247,10 @@ impl Engine {
             created_by: input.user_id,
             idempotency_key: input.idempotency_key,
             refunded_transaction_id: None,
+            conversion: input.conversion,
         })?;
         let legs = build_legs(tx.id);
-        self.create_transaction_with_legs(db_tx, input.vault_id, input.currency, &tx, &legs)
+        self.create_transaction_with_legs(db_tx, input.vault_id, input.vault_currency, &tx, &legs)
             .await
     }
 
@@ -161,7 +258,7 @@ impl Engine {
         &self,
         input: TransferUpdateInput<'_>,
         output: TransferUpdateOutput<'_>,
-    ) -> ResultEngine<()> {
+    ) -> ResultEngine<TransferUpdateResult> {
         let (kind_label, target_label, diff_error) = match input.kind {
             TransferTargetKind::Wallet => (
                 "transfer_wallet",
@@ -205,14 +302,53 @@ impl Engine {
             }
         }
 
+        let (from_currency, to_currency) = match input.kind {
+            TransferTargetKind::Wallet => (
+                self.wallet_currency(input.db_tx, input.vault_id, new_from)
+                    .await?,
+                self.wallet_currency(input.db_tx, input.vault_id, new_to)
+                    .await?,
+            ),
+            TransferTargetKind::Flow => (input.vault_currency, input.vault_currency),
+        };
+        let leg_currency = |leg_id: Uuid| {
+            input
+                .leg_pairs
+                .iter()
+                .find(|(model, _)| model.id == leg_id)
+                .map(|(_, leg)| leg.currency)
+        };
+        let current_pair = (leg_currency(info.from_leg_id), leg_currency(info.to_leg_id));
+        if input.amount_minor.is_none() && from_currency != input.current_currency {
+            return Err(EngineError::CurrencyMismatch(
+                "amount_minor is required when the source currency changes".to_string(),
+            ));
+        }
+        let amount_minor = input.amount_minor.unwrap_or(input.current_amount_minor);
+        // A stored rate only applies to the currency pair it was recorded for.
+        let rate = input.exchange_rate.or_else(|| {
+            (current_pair == (Some(from_currency), Some(to_currency)))
+                .then_some(input.current_rate)
+                .flatten()
+        });
+        let amounts =
+            fx::resolve_transfer_wallet_amounts(amount_minor, from_currency, to_currency, rate)?;
+        if input.exchange_rate.is_some() && amounts.conversion.is_none() {
+            return Err(EngineError::InvalidAmount(
+                "exchange_rate given but no currency conversion is needed".to_string(),
+            ));
+        }
+
         let ctx = super::super::helpers::TransferLegUpdateContext {
             kind_label,
-            vault_currency: input.vault_currency,
             from_leg_id: info.from_leg_id,
             to_leg_id: info.to_leg_id,
             new_from,
             new_to,
-            new_amount_minor: input.new_amount_minor,
+            from_amount_minor: amounts.from_minor,
+            from_currency,
+            to_amount_minor: amounts.to_minor,
+            to_currency,
         };
         let sink = super::super::helpers::TransferLegUpdateSink {
             balance_updates: output.balance_updates,
@@ -223,14 +359,17 @@ impl Engine {
             TransferTargetKind::Flow => LegTarget::Flow { flow_id: id },
         })?;
 
-        Ok(())
+        Ok(TransferUpdateResult {
+            amount_minor: amounts.from_minor,
+            currency: from_currency,
+            conversion: amounts.conversion,
+        })
     }
 
     async fn apply_wallet_delta(
         &self,
         db_tx: &DatabaseTransaction,
         vault_id: &str,
-        vault_currency: Currency,
         wallet_new_balances: &mut HashMap<Uuid, i64>,
         wallet_id: Uuid,
         delta_minor: i64,
@@ -241,7 +380,6 @@ impl Engine {
             .one(db_tx)
             .await?
             .ok_or_else(|| EngineError::KeyNotFound("wallet not exists".to_string()))?;
-        ensure_vault_currency(vault_currency, wallet_model.currency)?;
 
         let entry = wallet_new_balances
             .entry(wallet_id)
@@ -275,6 +413,8 @@ impl Engine {
                 income_balance: flow_model.income_balance,
                 currency: flow_model.currency,
                 archived: flow_model.archived,
+                target_minor: flow_model.target_minor,
+                target_date: flow_model.target_date,
             });
         entry.apply_leg_change(input.old_amount_minor, input.new_amount_minor)?;
         Ok(())
@@ -309,12 +449,14 @@ impl Engine {
         // Validate kind-specific invariants (kept strict for now).
         match tx.kind {
             TransactionKind::Income | TransactionKind::Expense | TransactionKind::Refund => {
-                validate_flow_wallet_legs(tx.kind, tx.amount_minor, legs)?;
+                validate_flow_wallet_legs(tx, vault_currency, legs)?;
             }
             TransactionKind::TransferWallet => {
+                let amounts = transfer_wallet_amounts_for(tx, legs)?;
                 validate_transfer_legs(
                     legs,
-                    tx.amount_minor,
+                    amounts.from_minor,
+                    amounts.to_minor,
                     "transfer",
                     "transfer_wallet",
                     "wallet",
@@ -328,6 +470,7 @@ impl Engine {
                 validate_transfer_legs(
                     legs,
                     tx.amount_minor,
+                    tx.amount_minor,
                     "transfer",
                     "transfer_flow",
                     "flow",
@@ -339,12 +482,10 @@ impl Engine {
             }
         }
 
-        if tx.currency != vault_currency {
-            return Err(EngineError::CurrencyMismatch(format!(
-                "vault currency is {}, got {}",
-                vault_currency.code(),
-                tx.currency.code()
-            )));
+        // Wallet transfers are denominated in the source wallet currency, every
+        // other transaction in the vault currency.
+        if tx.kind != TransactionKind::TransferWallet {
+            ensure_vault_currency(vault_currency, tx.currency)?;
         }
 
         let vault_uuid_early = parse_vault_uuid(vault_id)?;
@@ -359,18 +500,18 @@ impl Engine {
                 return Ok(existing.id);
             }
         }
+        vault::Entity::find_by_id(vault_uuid_early)
+            .one(db_tx)
+            .await?
+            .ok_or_else(|| EngineError::KeyNotFound("vault not exists".to_string()))?
+            .ensure_period_open(tx.occurred_at)?;
 
         // Validate currency and domain invariants by simulating balance changes, while
         // also computing the resulting denormalized balances to persist.
         let mut updates: Vec<(LegTarget, i64, i64)> = Vec::with_capacity(legs.len());
         for leg in legs {
-            if leg.currency != vault_currency {
-                return Err(EngineError::CurrencyMismatch(format!(
-                    "vault currency is {}, got {}",
-                    vault_currency.code(),
-                    leg.currency.code()
-                )));
-            }
+            self.ensure_leg_currency(db_tx, vault_id, vault_currency, leg.target, leg.currency)
+                .await?;
             updates.push((leg.target, 0, leg.amount_minor));
         }
         let (wallet_new_balances, flow_previews) = self
@@ -400,9 +541,44 @@ impl Engine {
         self.persist_targets(db_tx, wallet_new_balances, flow_previews)
             .await?;
 
+        let after = transaction_snapshot(db_tx, tx.id).await?;
+        record_audit(
+            db_tx,
+            vault_uuid,
+            &tx.created_by,
+            AuditChange::created(AuditEntity::Transaction, tx.id, after),
+        )
+        .await?;
+
         Ok(tx.id)
     }
 
+    /// Ensure a leg is denominated in its target currency (wallet currency for
+    /// wallet legs, vault currency for flow legs).
+    pub(super) async fn ensure_leg_currency(
+        &self,
+        db_tx: &DatabaseTransaction,
+        vault_id: &str,
+        vault_currency: Currency,
+        target: LegTarget,
+        currency: Currency,
+    ) -> ResultEngine<()> {
+        let expected = match target {
+            LegTarget::Wallet { wallet_id } => {
+                self.wallet_currency(db_tx, vault_id, wallet_id).await?
+            }
+            LegTarget::Flow { .. } => vault_currency,
+        };
+        if expected != currency {
+            return Err(EngineError::CurrencyMismatch(format!(
+                "leg currency is {}, target currency is {}",
+                currency.code(),
+                expected.code()
+            )));
+        }
+        Ok(())
+    }
+
     pub(super) async fn preview_apply_leg_updates(
         &self,
         db_tx: &DatabaseTransaction,
@@ -420,7 +596,6 @@ impl Engine {
                     self.apply_wallet_delta(
                         db_tx,
                         vault_id,
-                        vault_currency,
                         &mut wallet_new_balances,
                         wallet_id,
                         delta_minor,
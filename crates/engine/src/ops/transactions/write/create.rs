@@ -4,7 +4,7 @@ use crate::{ExpenseCmd, IncomeCmd, RefundCmd, ResultEngine, TransactionKind};
 
 use super::{super::super::Engine, common::FlowWalletCmd};
 
-/// Generates a flow+wallet transaction method (income, expense, refund).
+/// Generates a flow+wallet transaction method (income, expense).
 macro_rules! impl_flow_wallet_tx {
     ($(#[$meta:meta])* $fn_name:ident, $cmd_type:ty, $kind:expr) => {
         $(#[$meta])*
@@ -14,9 +14,12 @@ macro_rules! impl_flow_wallet_tx {
                 amount_minor: cmd.amount_minor,
                 flow_id: cmd.flow_id,
                 wallet_id: cmd.wallet_id,
+                currency: cmd.currency,
+                exchange_rate: cmd.exchange_rate,
                 meta: cmd.meta,
                 user_id: cmd.user_id,
                 kind: $kind,
+                refunded_transaction_id: None,
             })
             .await
         }
@@ -38,13 +41,27 @@ impl Engine {
         TransactionKind::Expense
     );
 
-    impl_flow_wallet_tx!(
-        /// Create a refund transaction (increases both wallet and flow).
-        ///
-        /// A refund is modeled as its own `TransactionKind::Refund` instead of a
-        /// negative expense, to keep reporting correct and explicit.
-        refund,
-        RefundCmd,
-        TransactionKind::Refund
-    );
+    /// Create a refund transaction (increases both wallet and flow).
+    ///
+    /// A refund is modeled as its own `TransactionKind::Refund` instead of a
+    /// negative expense, to keep reporting correct and explicit.
+    ///
+    /// When `refunded_transaction_id` is set, the refund defaults to the
+    /// expense's wallet, flow and category, and cannot exceed the part of the
+    /// expense not refunded yet.
+    pub async fn refund(&self, cmd: RefundCmd) -> ResultEngine<Uuid> {
+        self.create_flow_wallet_transaction_cmd(FlowWalletCmd {
+            vault_id: cmd.vault_id,
+            amount_minor: cmd.amount_minor,
+            flow_id: cmd.flow_id,
+            wallet_id: cmd.wallet_id,
+            currency: cmd.currency,
+            exchange_rate: cmd.exchange_rate,
+            meta: cmd.meta,
+            user_id: cmd.user_id,
+            kind: TransactionKind::Refund,
+            refunded_transaction_id: cmd.refunded_transaction_id,
+        })
+        .await
+    }
 }
@@ -6,7 +6,10 @@ use crate::{
     EngineError, Leg, ResultEngine, Transaction, legs, transactions, vault, vault_memberships,
 };
 
-use super::super::super::{Engine, parse_vault_uuid};
+use super::super::{
+    super::{Engine, parse_vault_uuid},
+    refunds::attach_refunded_amounts,
+};
 
 impl Engine {
     /// Returns a single transaction with all its legs (detail view).
@@ -61,6 +64,7 @@ impl Engine {
                     out.push(Leg::try_from(leg_model)?);
                 }
                 tx.legs = out;
+                attach_refunded_amounts(db_tx, vec![&mut tx]).await?;
 
                 Ok(tx)
             })
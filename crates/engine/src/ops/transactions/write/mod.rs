@@ -1,6 +1,12 @@
+mod clearing;
 mod common;
 mod create;
 mod detail;
+mod revisions;
+mod split;
 mod transfer;
 mod update;
 mod void;
+
+pub(in crate::ops) use common::FlowWalletCmd;
+pub(in crate::ops) use revisions::store_revision;
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use sea_orm::{ActiveValue, DatabaseTransaction, QueryFilter, QueryOrder, prelude::*};
+
+use crate::{
+    AuditEntity, EngineError, Leg, LegTarget, ResultEngine, TransactionRevision,
+    audit::AuditChange, legs, transaction_revisions, transactions,
+};
+
+use super::super::{
+    super::{
+        Engine,
+        audit::{record_audit, transaction_snapshot},
+        parse_vault_uuid,
+    },
+    refunds::ensure_refund_links,
+};
+
+/// Stores the current state of a transaction as its next revision.
+///
+/// Call it before changing the transaction, inside the same `with_tx`.
+pub(in crate::ops) async fn store_revision(
+    db_tx: &DatabaseTransaction,
+    tx_model: &transactions::Model,
+    user_id: &str,
+) -> ResultEngine<()> {
+    let last = transaction_revisions::Entity::find()
+        .filter(transaction_revisions::Column::TransactionId.eq(tx_model.id))
+        .order_by_desc(transaction_revisions::Column::Revision)
+        .one(db_tx)
+        .await?;
+    let leg_models = legs::Entity::find()
+        .filter(legs::Column::TransactionId.eq(tx_model.id))
+        .order_by_asc(legs::Column::Id)
+        .all(db_tx)
+        .await?;
+    let revision = last.map_or(1, |model| model.revision + 1);
+    transaction_revisions::ActiveModel::new(revision, tx_model, leg_models, user_id)?
+        .insert(db_tx)
+        .await?;
+    Ok(())
+}
+
+/// Loads a transaction of the vault, rejecting other vaults' transactions.
+pub(super) async fn require_transaction(
+    db_tx: &DatabaseTransaction,
+    vault_uuid: Uuid,
+    transaction_id: Uuid,
+) -> ResultEngine<transactions::Model> {
+    transactions::Entity::find_by_id(transaction_id)
+        .one(db_tx)
+        .await?
+        .filter(|model| model.vault_id == vault_uuid)
+        .ok_or_else(|| EngineError::KeyNotFound("transaction not exists".to_string()))
+}
+
+impl Engine {
+    /// Lists the previous states of a transaction, oldest first.
+    ///
+    /// Authorization: requires vault read access.
+    pub async fn transaction_revisions(
+        &self,
+        vault_id: &str,
+        transaction_id: Uuid,
+        user_id: &str,
+    ) -> ResultEngine<Vec<TransactionRevision>> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                engine
+                    .require_vault_by_id(db_tx, vault_id.as_str(), user_id.as_str())
+                    .await?;
+                let vault_uuid = parse_vault_uuid(vault_id.as_str())?;
+                require_transaction(db_tx, vault_uuid, transaction_id).await?;
+
+                transaction_revisions::Entity::find()
+                    .filter(transaction_revisions::Column::TransactionId.eq(transaction_id))
+                    .order_by_asc(transaction_revisions::Column::Revision)
+                    .all(db_tx)
+                    .await?
+                    .into_iter()
+                    .map(TransactionRevision::try_from)
+                    .collect()
+            })
+        })
+        .await
+    }
+
+    /// Restores a transaction to one of its revisions.
+    ///
+    /// The revision legs are re-applied like an update: balances are
+    /// validated (caps, non-negativity) against the current state, and the
+    /// current state is stored as a new revision first, so a revert can be
+    /// reverted too.
+    pub async fn revert_transaction(
+        &self,
+        vault_id: &str,
+        transaction_id: Uuid,
+        revision: u32,
+        user_id: &str,
+    ) -> ResultEngine<()> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let vault_id = vault_id.as_str();
+                let user_id = user_id.as_str();
+                let vault_model = engine
+                    .require_vault_by_id_write(db_tx, vault_id, user_id)
+                    .await?;
+                let vault_currency = vault_model.currency;
+                let vault_uuid = parse_vault_uuid(vault_id)?;
+                let tx_model = require_transaction(db_tx, vault_uuid, transaction_id).await?;
+                if tx_model.voided_at.is_some() {
+                    return Err(EngineError::InvalidAmount(
+                        "cannot revert a voided transaction".to_string(),
+                    ));
+                }
+                tx_model.ensure_unlocked()?;
+                let target = i32::try_from(revision).ok();
+                let revision_model = transaction_revisions::Entity::find()
+                    .filter(transaction_revisions::Column::TransactionId.eq(transaction_id))
+                    .filter(transaction_revisions::Column::Revision.eq(target))
+                    .one(db_tx)
+                    .await?
+                    .ok_or_else(|| EngineError::KeyNotFound("revision not exists".to_string()))?;
+                let target = TransactionRevision::try_from(revision_model)?;
+                vault_model.ensure_period_open(tx_model.occurred_at)?;
+                vault_model.ensure_period_open(target.occurred_at)?;
+                let before = transaction_snapshot(db_tx, transaction_id).await?;
+
+                let mut current: HashMap<Uuid, Leg> = HashMap::new();
+                for leg_model in legs::Entity::find()
+                    .filter(legs::Column::TransactionId.eq(transaction_id))
+                    .all(db_tx)
+                    .await?
+                {
+                    let leg = Leg::try_from(leg_model)?;
+                    current.insert(leg.id, leg);
+                }
+                if current.len() != target.legs.len()
+                    || target.legs.iter().any(|leg| !current.contains_key(&leg.id))
+                {
+                    return Err(EngineError::InvalidAmount(
+                        "revision legs do not match the transaction".to_string(),
+                    ));
+                }
+
+                let category = engine
+                    .resolve_category_input(db_tx, vault_id, Some(target.category_id), None)
+                    .await?;
+
+                let mut balance_updates: Vec<(LegTarget, i64, i64)> = Vec::new();
+                for leg in &target.legs {
+                    match leg.target {
+                        LegTarget::Wallet { wallet_id } => {
+                            engine
+                                .require_wallet_in_vault(db_tx, vault_id, wallet_id)
+                                .await?;
+                        }
+                        LegTarget::Flow { flow_id } => {
+                            engine
+                                .require_flow_in_vault(db_tx, vault_id, flow_id)
+                                .await?;
+                        }
+                    }
+                    engine
+                        .ensure_leg_currency(
+                            db_tx,
+                            vault_id,
+                            vault_currency,
+                            leg.target,
+                            leg.currency,
+                        )
+                        .await?;
+                    let Some(old) = current.get(&leg.id) else {
+                        continue;
+                    };
+                    if old.target == leg.target {
+                        balance_updates.push((leg.target, old.amount_minor, leg.amount_minor));
+                    } else {
+                        balance_updates.push((old.target, old.amount_minor, 0));
+                        balance_updates.push((leg.target, 0, leg.amount_minor));
+                    }
+                }
+                ensure_refund_links(db_tx, &tx_model, target.amount_minor).await?;
+                let (wallet_new_balances, flow_previews) = engine
+                    .preview_apply_leg_updates(db_tx, vault_id, vault_currency, &balance_updates)
+                    .await?;
+
+                store_revision(db_tx, &tx_model, user_id).await?;
+
+                let tx_active = transactions::ActiveModel {
+                    id: ActiveValue::Set(transaction_id),
+                    amount_minor: ActiveValue::Set(target.amount_minor),
+                    currency: ActiveValue::Set(target.currency),
+                    original_amount_minor: ActiveValue::Set(target.original_amount_minor),
+                    original_currency: ActiveValue::Set(target.original_currency),
+                    exchange_rate: ActiveValue::Set(
+                        target.exchange_rate.map(|rate| rate.to_string()),
+                    ),
+                    category_id: ActiveValue::Set(category.id),
+                    category: ActiveValue::Set(category.name),
+                    note: ActiveValue::Set(target.note),
+                    occurred_at: ActiveValue::Set(target.occurred_at),
+                    ..Default::default()
+                };
+                tx_active.update(db_tx).await?;
+                for leg in &target.legs {
+                    legs::ActiveModel::from(leg).update(db_tx).await?;
+                }
+
+                engine
+                    .persist_targets(db_tx, wallet_new_balances, flow_previews)
+                    .await?;
+
+                let after = transaction_snapshot(db_tx, transaction_id).await?;
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    user_id,
+                    AuditChange::updated(AuditEntity::Transaction, transaction_id, before, after),
+                )
+                .await?;
+
+                Ok(())
+            })
+        })
+        .await
+    }
+}
@@ -0,0 +1,171 @@
+use sea_orm::DatabaseTransaction;
+use uuid::Uuid;
+
+use crate::{
+    EngineError, Leg, LegTarget, ResultEngine, SplitExpenseCmd, TransactionKind,
+    category_rules::RuleSubject, util::normalize_optional_text,
+};
+
+use super::super::{
+    super::{Engine, TransactionBuildInput, build_transaction, fx},
+    helpers::normalize_tx_note,
+};
+
+fn validate_split_lines(cmd: &SplitExpenseCmd) -> ResultEngine<()> {
+    if cmd.meta.category_id.is_some() || cmd.meta.category.is_some() {
+        return Err(EngineError::InvalidName(
+            "split expenses take a category per line".to_string(),
+        ));
+    }
+    if cmd.splits.len() < 2 {
+        return Err(EngineError::InvalidAmount(
+            "a split expense needs at least 2 lines".to_string(),
+        ));
+    }
+    if cmd.splits.iter().any(|line| line.amount_minor <= 0) {
+        return Err(EngineError::InvalidAmount(
+            "split line amount_minor must be > 0".to_string(),
+        ));
+    }
+    let total = cmd
+        .splits
+        .iter()
+        .try_fold(0i64, |sum, line| sum.checked_add(line.amount_minor))
+        .ok_or_else(|| EngineError::InvalidAmount("amount overflow".to_string()))?;
+    if total != cmd.amount_minor {
+        return Err(EngineError::InvalidAmount(format!(
+            "split lines sum to {total}, expected {}",
+            cmd.amount_minor
+        )));
+    }
+    Ok(())
+}
+
+impl Engine {
+    /// Create an expense paid from one wallet and split across categories
+    /// and flows: one wallet leg plus one flow leg per line, each carrying
+    /// the line category and note.
+    ///
+    /// Lines without a category are categorized by the vault rules. The
+    /// transaction itself takes the category of its largest line (the first
+    /// one on ties).
+    pub async fn split_expense(&self, cmd: SplitExpenseCmd) -> ResultEngine<Uuid> {
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move { engine.split_expense_in_tx(db_tx, cmd).await })
+        })
+        .await
+    }
+
+    async fn split_expense_in_tx(
+        &self,
+        db_tx: &DatabaseTransaction,
+        cmd: SplitExpenseCmd,
+    ) -> ResultEngine<Uuid> {
+        validate_split_lines(&cmd)?;
+        let note = normalize_tx_note(&cmd.meta);
+        let vault_model = self
+            .require_vault_by_id_write(db_tx, &cmd.vault_id, &cmd.user_id)
+            .await?;
+        let vault_currency = vault_model.currency;
+        let wallet_id = self
+            .resolve_wallet_id(db_tx, &cmd.vault_id, cmd.wallet_id)
+            .await?;
+        let wallet_currency = self
+            .wallet_currency(db_tx, &cmd.vault_id, wallet_id)
+            .await?;
+        let amount_currency = cmd.currency.unwrap_or(wallet_currency);
+        let amounts = fx::resolve_flow_wallet_amounts(
+            cmd.amount_minor,
+            amount_currency,
+            cmd.exchange_rate,
+            wallet_currency,
+            vault_currency,
+        )?;
+        if cmd.exchange_rate.is_some() && amounts.conversion.is_none() {
+            return Err(EngineError::InvalidAmount(
+                "exchange_rate given but no currency conversion is needed".to_string(),
+            ));
+        }
+        let line_amounts: Vec<i64> = cmd.splits.iter().map(|line| line.amount_minor).collect();
+        let vault_amounts = fx::split_vault_amounts(
+            &line_amounts,
+            amount_currency,
+            cmd.exchange_rate,
+            wallet_currency,
+            vault_currency,
+        )?;
+
+        let rules = self.load_category_rules(db_tx, &cmd.vault_id).await?;
+        let mut flow_legs = Vec::with_capacity(cmd.splits.len());
+        let mut header: Option<(i64, Uuid, Option<String>)> = None;
+        for (line, vault_minor) in cmd.splits.iter().zip(vault_amounts) {
+            let flow_id = self
+                .resolve_flow_id(db_tx, &cmd.vault_id, line.flow_id)
+                .await?;
+            let line_note = normalize_optional_text(line.note.as_deref());
+            let category = self
+                .resolve_category_with_rules(
+                    db_tx,
+                    &cmd.vault_id,
+                    (line.category_id, line.category.as_deref()),
+                    &rules,
+                    &RuleSubject {
+                        kind: TransactionKind::Expense,
+                        amount_minor: vault_minor,
+                        wallet_id: Some(wallet_id),
+                        flow_id: Some(flow_id),
+                        note: line_note.as_deref().or(note.as_deref()),
+                    },
+                )
+                .await?;
+            if header
+                .as_ref()
+                .is_none_or(|(largest, _, _)| vault_minor > *largest)
+            {
+                header = Some((vault_minor, category.id, category.name.clone()));
+            }
+            flow_legs.push((flow_id, vault_minor, category.id, line_note));
+        }
+        let Some((_, category_id, category)) = header else {
+            return Err(EngineError::InvalidAmount(
+                "a split expense needs at least 2 lines".to_string(),
+            ));
+        };
+
+        let tx = build_transaction(TransactionBuildInput {
+            vault_id: &cmd.vault_id,
+            kind: TransactionKind::Expense,
+            occurred_at: cmd.meta.occurred_at,
+            amount_minor: amounts.vault_minor,
+            currency: vault_currency,
+            category_id,
+            category,
+            note,
+            created_by: &cmd.user_id,
+            idempotency_key: cmd.meta.idempotency_key.clone(),
+            refunded_transaction_id: None,
+            conversion: amounts.conversion,
+        })?;
+        let mut legs = Vec::with_capacity(flow_legs.len() + 1);
+        legs.push(Leg::new(
+            tx.id,
+            LegTarget::Wallet { wallet_id },
+            -amounts.wallet_minor,
+            wallet_currency,
+        ));
+        for (flow_id, vault_minor, category_id, line_note) in flow_legs {
+            let mut leg = Leg::new(
+                tx.id,
+                LegTarget::Flow { flow_id },
+                -vault_minor,
+                vault_currency,
+            );
+            leg.category_id = Some(category_id);
+            leg.note = line_note;
+            legs.push(leg);
+        }
+
+        self.create_transaction_with_legs(db_tx, &cmd.vault_id, vault_currency, &tx, &legs)
+            .await
+    }
+}
@@ -8,7 +8,7 @@ use crate::{
 };
 
 use super::{
-    super::super::{Engine, parse_vault_uuid, transfer_flow_legs, transfer_wallet_legs},
+    super::super::{Engine, fx, parse_vault_uuid, transfer_flow_legs, transfer_wallet_legs},
     common::TransferTransactionInput,
 };
 
@@ -24,6 +24,7 @@ impl Engine {
             amount_minor,
             from_wallet_id,
             to_wallet_id,
+            exchange_rate,
             note,
             idempotency_key,
             occurred_at,
@@ -35,14 +36,25 @@ impl Engine {
                 let vault_model = engine
                     .require_vault_by_id_write(db_tx, &vault_id, &user_id)
                     .await?;
-                let currency = vault_model.currency;
+                let vault_currency = vault_model.currency;
                 // Ensure wallets belong to the vault.
-                engine
-                    .resolve_wallet_id(db_tx, &vault_id, Some(from_wallet_id))
+                let from_currency = engine
+                    .wallet_currency(db_tx, &vault_id, from_wallet_id)
                     .await?;
-                engine
-                    .resolve_wallet_id(db_tx, &vault_id, Some(to_wallet_id))
+                let to_currency = engine
+                    .wallet_currency(db_tx, &vault_id, to_wallet_id)
                     .await?;
+                let amounts = fx::resolve_transfer_wallet_amounts(
+                    amount_minor,
+                    from_currency,
+                    to_currency,
+                    exchange_rate,
+                )?;
+                if exchange_rate.is_some() && amounts.conversion.is_none() {
+                    return Err(EngineError::InvalidAmount(
+                        "exchange_rate given but no currency conversion is needed".to_string(),
+                    ));
+                }
 
                 let id = engine
                     .create_transfer_transaction(
@@ -55,17 +67,11 @@ impl Engine {
                             note,
                             idempotency_key,
                             kind: TransactionKind::TransferWallet,
-                            currency,
-                        },
-                        |tx_id| {
-                            transfer_wallet_legs(
-                                tx_id,
-                                from_wallet_id,
-                                to_wallet_id,
-                                amount_minor,
-                                currency,
-                            )
+                            currency: from_currency,
+                            vault_currency,
+                            conversion: amounts.conversion,
                         },
+                        |tx_id| transfer_wallet_legs(tx_id, from_wallet_id, to_wallet_id, &amounts),
                     )
                     .await?;
                 Ok(id)
@@ -133,6 +139,8 @@ impl Engine {
                             idempotency_key,
                             kind: TransactionKind::TransferFlow,
                             currency,
+                            vault_currency: currency,
+                            conversion: None,
                         },
                         |tx_id| {
                             transfer_flow_legs(
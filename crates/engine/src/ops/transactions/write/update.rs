@@ -1,8 +1,10 @@
 use sea_orm::{ActiveValue, QueryFilter, prelude::*};
 
 use crate::{
-    EngineError, Leg, LegTarget, ResultEngine, TransactionKind, UpdateTransactionCmd, legs,
-    transactions,
+    AuditEntity, Currency, EngineError, ExchangeRate, Leg, LegTarget, ResultEngine,
+    TransactionKind, UpdateTransactionCmd,
+    audit::AuditChange,
+    legs, transactions,
     util::{apply_optional_datetime_patch, apply_optional_text_patch},
 };
 
@@ -10,12 +12,19 @@ use uuid::Uuid;
 
 use super::{
     super::{
-        super::{Engine, flow_wallet_signed_amount, parse_vault_uuid},
+        super::{
+            Engine,
+            audit::{record_audit, transaction_snapshot},
+            fx, parse_vault_uuid,
+        },
         helpers::{
-            apply_flow_wallet_leg_updates, extract_flow_wallet_targets, validate_update_fields,
+            FlowWalletLegUpdate, apply_flow_wallet_leg_updates, extract_flow_wallet_targets,
+            validate_update_fields,
         },
+        refunds::ensure_refund_links,
     },
     common::{TransferTargetKind, TransferUpdateInput, TransferUpdateOutput},
+    revisions::store_revision,
 };
 
 impl Engine {
@@ -25,11 +34,21 @@ impl Engine {
     /// - `Income`/`Expense`/`Refund`: wallet and/or flow can be changed
     /// - `TransferWallet`: from/to wallets can be changed
     /// - `TransferFlow`: from/to flows can be changed
+    ///
+    /// For transactions recorded with an exchange rate, `amount_minor` is the
+    /// original (entered) amount and the converted legs are recomputed.
+    ///
+    /// Split expenses only accept note and `occurred_at` changes; void and
+    /// re-create them to change amounts, targets or categories.
+    ///
+    /// The previous state is kept as a revision (see
+    /// [`Engine::transaction_revisions`]).
     pub async fn update_transaction(&self, cmd: UpdateTransactionCmd) -> ResultEngine<()> {
         let vault_id = cmd.vault_id;
         let transaction_id = cmd.transaction_id;
         let user_id = cmd.user_id;
         let amount_minor = cmd.amount_minor;
+        let exchange_rate = cmd.exchange_rate;
         let wallet_id = cmd.wallet_id;
         let flow_id = cmd.flow_id;
         let from_wallet_id = cmd.from_wallet_id;
@@ -67,17 +86,28 @@ impl Engine {
                         "cannot update a voided transaction".to_string(),
                     ));
                 }
+                tx_model.ensure_unlocked()?;
+                vault_model.ensure_period_open(tx_model.occurred_at)?;
+                let before = transaction_snapshot(db_tx, transaction_id).await?;
+                store_revision(db_tx, &tx_model, user_id).await?;
 
                 let kind = tx_model.kind;
-                let new_amount_minor = amount_minor.unwrap_or(tx_model.amount_minor);
-                if new_amount_minor <= 0 {
+                if let Some(new_amount_minor) = amount_minor
+                    && new_amount_minor <= 0
+                {
                     return Err(EngineError::InvalidAmount(
                         "amount_minor must be > 0".to_string(),
                     ));
                 }
+                let current_rate = tx_model
+                    .exchange_rate
+                    .as_deref()
+                    .map(ExchangeRate::parse)
+                    .transpose()?;
 
                 let new_occurred_at =
                     apply_optional_datetime_patch(tx_model.occurred_at, occurred_at);
+                vault_model.ensure_period_open(new_occurred_at)?;
                 let (new_category_id, new_category) = if category_id.is_some()
                     || category.as_deref().is_some()
                 {
@@ -101,10 +131,38 @@ impl Engine {
                     leg_pairs.push((leg_model, leg));
                 }
 
+                let is_split = leg_pairs.iter().any(|(_, leg)| leg.category_id.is_some());
+                if is_split
+                    && (amount_minor.is_some()
+                        || exchange_rate.is_some()
+                        || wallet_id.is_some()
+                        || flow_id.is_some()
+                        || category_id.is_some()
+                        || category.is_some())
+                {
+                    return Err(EngineError::InvalidAmount(
+                        "only note and occurred_at of a split transaction can be updated"
+                            .to_string(),
+                    ));
+                }
+
                 let mut balance_updates: Vec<(LegTarget, i64, i64)> = Vec::new();
-                let mut leg_updates: Vec<(Uuid, LegTarget, i64)> = Vec::new();
+                let mut leg_updates: Vec<(Uuid, LegTarget, i64, Currency)> = Vec::new();
 
-                match kind {
+                let (new_amount_minor, new_currency, new_conversion) = match kind {
+                    _ if is_split => (
+                        tx_model.amount_minor,
+                        tx_model.currency,
+                        tx_model
+                            .original_amount_minor
+                            .zip(tx_model.original_currency)
+                            .zip(current_rate)
+                            .map(|((amount_minor, currency), rate)| fx::Conversion {
+                                amount_minor,
+                                currency,
+                                rate,
+                            }),
+                    ),
                     TransactionKind::Income
                     | TransactionKind::Expense
                     | TransactionKind::Refund => {
@@ -119,20 +177,61 @@ impl Engine {
                             .require_flow_in_vault(db_tx, vault_id, new_flow_id)
                             .await?;
 
-                        let new_signed_amount = flow_wallet_signed_amount(kind, new_amount_minor)?;
+                        let current_wallet_currency = leg_pairs
+                            .iter()
+                            .find(|(_, leg)| matches!(leg.target, LegTarget::Wallet { .. }))
+                            .map_or(vault_currency, |(_, leg)| leg.currency);
+                        let new_wallet_currency = engine
+                            .wallet_currency(db_tx, vault_id, new_wallet_id)
+                            .await?;
+                        let (amount_currency, current_amount_minor) =
+                            match (tx_model.original_currency, tx_model.original_amount_minor) {
+                                (Some(currency), Some(original_minor)) => {
+                                    (currency, original_minor)
+                                }
+                                _ => (vault_currency, tx_model.amount_minor),
+                            };
+                        // A stored rate only applies to the currency pair it was recorded for.
+                        let same_pair = fx::conversion_target(
+                            amount_currency,
+                            current_wallet_currency,
+                            vault_currency,
+                        )? == fx::conversion_target(
+                            amount_currency,
+                            new_wallet_currency,
+                            vault_currency,
+                        )?;
+                        let rate =
+                            exchange_rate.or_else(|| same_pair.then_some(current_rate).flatten());
+                        let amounts = fx::resolve_flow_wallet_amounts(
+                            amount_minor.unwrap_or(current_amount_minor),
+                            amount_currency,
+                            rate,
+                            new_wallet_currency,
+                            vault_currency,
+                        )?;
+                        if exchange_rate.is_some() && amounts.conversion.is_none() {
+                            return Err(EngineError::InvalidAmount(
+                                "exchange_rate given but no currency conversion is needed"
+                                    .to_string(),
+                            ));
+                        }
 
                         apply_flow_wallet_leg_updates(
                             &leg_pairs,
-                            vault_currency,
-                            new_wallet_id,
-                            new_flow_id,
-                            new_signed_amount,
+                            &FlowWalletLegUpdate {
+                                kind,
+                                wallet_id: new_wallet_id,
+                                flow_id: new_flow_id,
+                                amounts,
+                            },
                             &mut balance_updates,
                             &mut leg_updates,
                         )?;
+                        (amounts.vault_minor, vault_currency, amounts.conversion)
                     }
                     TransactionKind::TransferWallet => {
-                        engine
+                        let result = engine
                             .update_transfer_targets(
                                 TransferUpdateInput {
                                     db_tx,
@@ -142,7 +241,11 @@ impl Engine {
                                     to_override: to_wallet_id,
                                     kind: TransferTargetKind::Wallet,
                                     vault_currency,
-                                    new_amount_minor,
+                                    amount_minor,
+                                    current_amount_minor: tx_model.amount_minor,
+                                    current_currency: tx_model.currency,
+                                    exchange_rate,
+                                    current_rate,
                                 },
                                 TransferUpdateOutput {
                                     balance_updates: &mut balance_updates,
@@ -150,9 +253,10 @@ impl Engine {
                                 },
                             )
                             .await?;
+                        (result.amount_minor, result.currency, result.conversion)
                     }
                     TransactionKind::TransferFlow => {
-                        engine
+                        let result = engine
                             .update_transfer_targets(
                                 TransferUpdateInput {
                                     db_tx,
@@ -162,7 +266,11 @@ impl Engine {
                                     to_override: to_flow_id,
                                     kind: TransferTargetKind::Flow,
                                     vault_currency,
-                                    new_amount_minor,
+                                    amount_minor,
+                                    current_amount_minor: tx_model.amount_minor,
+                                    current_currency: tx_model.currency,
+                                    exchange_rate,
+                                    current_rate,
                                 },
                                 TransferUpdateOutput {
                                     balance_updates: &mut balance_updates,
@@ -170,8 +278,9 @@ impl Engine {
                                 },
                             )
                             .await?;
+                        (result.amount_minor, result.currency, result.conversion)
                     }
-                }
+                };
 
                 // Reject unexpected target fields for this kind (avoid silent no-ops).
                 validate_update_fields(
@@ -184,6 +293,12 @@ impl Engine {
                     to_flow_id,
                 )?;
 
+                for (_, target, _, currency) in &leg_updates {
+                    engine
+                        .ensure_leg_currency(db_tx, vault_id, vault_currency, *target, *currency)
+                        .await?;
+                }
+                ensure_refund_links(db_tx, &tx_model, new_amount_minor).await?;
                 let (wallet_new_balances, flow_previews) = engine
                     .preview_apply_leg_updates(db_tx, vault_id, vault_currency, &balance_updates)
                     .await?;
@@ -191,6 +306,10 @@ impl Engine {
                 let tx_active = transactions::ActiveModel {
                     id: ActiveValue::Set(transaction_id),
                     amount_minor: ActiveValue::Set(new_amount_minor),
+                    currency: ActiveValue::Set(new_currency),
+                    original_amount_minor: ActiveValue::Set(new_conversion.map(|c| c.amount_minor)),
+                    original_currency: ActiveValue::Set(new_conversion.map(|c| c.currency)),
+                    exchange_rate: ActiveValue::Set(new_conversion.map(|c| c.rate.to_string())),
                     category_id: ActiveValue::Set(new_category_id),
                     category: ActiveValue::Set(new_category),
                     note: ActiveValue::Set(new_note),
@@ -199,7 +318,7 @@ impl Engine {
                 };
                 tx_active.update(db_tx).await?;
 
-                for (leg_id, new_target, new_amount_minor) in leg_updates {
+                for (leg_id, new_target, new_amount_minor, new_currency) in leg_updates {
                     let (target_kind, target_id) = match new_target {
                         LegTarget::Wallet { wallet_id } => (legs::LegTargetKind::Wallet, wallet_id),
                         LegTarget::Flow { flow_id } => (legs::LegTargetKind::Flow, flow_id),
@@ -209,6 +328,7 @@ impl Engine {
                         target_kind: ActiveValue::Set(target_kind),
                         target_id: ActiveValue::Set(target_id),
                         amount_minor: ActiveValue::Set(new_amount_minor),
+                        currency: ActiveValue::Set(new_currency),
                         ..Default::default()
                     };
                     leg_active.update(db_tx).await?;
@@ -218,6 +338,15 @@ impl Engine {
                     .persist_targets(db_tx, wallet_new_balances, flow_previews)
                     .await?;
 
+                let after = transaction_snapshot(db_tx, transaction_id).await?;
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    user_id,
+                    AuditChange::updated(AuditEntity::Transaction, transaction_id, before, after),
+                )
+                .await?;
+
                 Ok(())
             })
         })
@@ -3,9 +3,19 @@ use uuid::Uuid;
 
 use sea_orm::{ActiveValue, QueryFilter, prelude::*};
 
-use crate::{EngineError, Leg, LegTarget, ResultEngine, legs, transactions};
+use crate::{
+    AuditAction, AuditEntity, EngineError, Leg, LegTarget, ResultEngine, audit::AuditChange, legs,
+    transactions,
+};
 
-use super::super::super::{Engine, parse_vault_uuid};
+use super::super::{
+    super::{
+        Engine,
+        audit::{record_audit, transaction_snapshot},
+        parse_vault_uuid,
+    },
+    refunds::{ensure_no_live_refunds, ensure_refund_links},
+};
 
 impl Engine {
     /// Voids a transaction (soft delete).
@@ -14,6 +24,9 @@ impl Engine {
     /// - sets `voided_at`/`voided_by` on the transaction row
     /// - reverts all legs effects on wallet/flow balances
     ///
+    /// An expense with non-voided refunds cannot be voided: void the refunds
+    /// first.
+    ///
     /// Voided transactions are hidden by default in lists/reports.
     pub async fn void_transaction(
         &self,
@@ -48,6 +61,10 @@ impl Engine {
                         "transaction already voided".to_string(),
                     ));
                 }
+                tx_model.ensure_unlocked()?;
+                vault_model.ensure_period_open(tx_model.occurred_at)?;
+                ensure_no_live_refunds(db_tx, &tx_model).await?;
+                let before = transaction_snapshot(db_tx, transaction_id).await?;
 
                 let leg_models = legs::Entity::find()
                     .filter(legs::Column::TransactionId.eq(transaction_id))
@@ -76,6 +93,111 @@ impl Engine {
                     .persist_targets(db_tx, wallet_new_balances, flow_previews)
                     .await?;
 
+                let after = transaction_snapshot(db_tx, transaction_id).await?;
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    user_id.as_str(),
+                    AuditChange::new(
+                        AuditAction::Void,
+                        AuditEntity::Transaction,
+                        transaction_id,
+                        before,
+                        after,
+                    ),
+                )
+                .await?;
+
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Restores a voided transaction (undoes [`Engine::void_transaction`]).
+    ///
+    /// The legs are re-applied with the same flow checks as a new
+    /// transaction (caps, non-negativity), so a restore fails if the balances
+    /// moved since the void. The transaction keeps its id and refund links; a
+    /// refund is only restored if its expense still has room for it.
+    pub async fn unvoid_transaction(
+        &self,
+        vault_id: &str,
+        transaction_id: Uuid,
+        user_id: &str,
+    ) -> ResultEngine<()> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let vault_model = engine
+                    .require_vault_by_id_write(db_tx, vault_id.as_str(), user_id.as_str())
+                    .await?;
+                let vault_currency = vault_model.currency;
+
+                let vault_uuid = parse_vault_uuid(vault_id.as_str())?;
+                let tx_model = transactions::Entity::find_by_id(transaction_id)
+                    .one(db_tx)
+                    .await?
+                    .ok_or_else(|| {
+                        EngineError::KeyNotFound("transaction not exists".to_string())
+                    })?;
+                if tx_model.vault_id != vault_uuid {
+                    return Err(EngineError::KeyNotFound(
+                        "transaction not exists".to_string(),
+                    ));
+                }
+                if tx_model.voided_at.is_none() {
+                    return Err(EngineError::InvalidAmount(
+                        "transaction is not voided".to_string(),
+                    ));
+                }
+                vault_model.ensure_period_open(tx_model.occurred_at)?;
+                ensure_refund_links(db_tx, &tx_model, tx_model.amount_minor).await?;
+                let before = transaction_snapshot(db_tx, transaction_id).await?;
+
+                let leg_models = legs::Entity::find()
+                    .filter(legs::Column::TransactionId.eq(transaction_id))
+                    .all(db_tx)
+                    .await?;
+
+                let mut updates: Vec<(LegTarget, i64, i64)> = Vec::with_capacity(leg_models.len());
+                for leg_model in leg_models {
+                    let leg = Leg::try_from(leg_model)?;
+                    updates.push((leg.target, 0, leg.amount_minor));
+                }
+
+                let (wallet_new_balances, flow_previews) = engine
+                    .preview_apply_leg_updates(db_tx, vault_id.as_str(), vault_currency, &updates)
+                    .await?;
+
+                let tx_active = transactions::ActiveModel {
+                    id: ActiveValue::Set(transaction_id),
+                    voided_at: ActiveValue::Set(None),
+                    voided_by: ActiveValue::Set(None),
+                    ..Default::default()
+                };
+                tx_active.update(db_tx).await?;
+
+                engine
+                    .persist_targets(db_tx, wallet_new_balances, flow_previews)
+                    .await?;
+
+                let after = transaction_snapshot(db_tx, transaction_id).await?;
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    user_id.as_str(),
+                    AuditChange::new(
+                        AuditAction::Restore,
+                        AuditEntity::Transaction,
+                        transaction_id,
+                        before,
+                        after,
+                    ),
+                )
+                .await?;
+
                 Ok(())
             })
         })
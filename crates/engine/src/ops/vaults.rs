@@ -1,99 +1,141 @@
 use std::collections::HashMap;
 
-use sea_orm::{ActiveValue, QueryFilter, Statement, prelude::*, sea_query::Expr};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveValue, DatabaseTransaction, QueryFilter, QuerySelect,
+    prelude::*,
+    sea_query::{Expr, Query},
+};
 use uuid::Uuid;
 
 use crate::{
-    CashFlow, Currency, EngineError, ResultEngine, TransactionKind, Vault, Wallet, cash_flows,
-    categories,
+    AuditEntity, CashFlow, Currency, EngineError, ResultEngine, TransactionKind, Vault, Wallet,
+    audit::AuditChange,
+    budgets, cash_flows, categories, category_aliases, category_rules, legs, recurring_rules,
+    transaction_revisions, transactions,
     util::{normalize_category_key, normalize_required_name},
     vault, vault_memberships, wallets,
 };
 
-use super::{Engine, parse_vault_uuid};
+use super::{
+    Engine,
+    audit::{record_audit, snapshot},
+    parse_vault_uuid, sum_minor,
+};
 
 impl Engine {
     /// Delete or archive a vault
     pub async fn delete_vault(&self, vault_id: &str, user_id: &str) -> ResultEngine<()> {
         let vault_id = vault_id.to_string();
         let user_id = user_id.to_string();
-        self.with_tx(|engine, db_tx| Box::pin(async move {
-            let vault_model = engine
-                .require_vault_by_id_write(db_tx, vault_id.as_str(), user_id.as_str())
-                .await?;
-            let vault_db_id = vault_model.id;
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let vault_model = engine
+                    .require_vault_by_id_write(db_tx, vault_id.as_str(), user_id.as_str())
+                    .await?;
+                let vault_db_id = vault_model.id;
 
-            // Best-effort cascade delete within one DB transaction.
-            // (FKs currently don't declare ON DELETE CASCADE everywhere, and some
-            // relationships are not FK-backed, so we do it explicitly.)
-            let backend = engine.database.get_database_backend();
+                // Best-effort cascade delete within one DB transaction.
+                // (FKs currently don't declare ON DELETE CASCADE everywhere, and some
+                // relationships are not FK-backed, so we do it explicitly.)
 
-            let vault_db_id_bytes: Vec<u8> = vault_db_id.as_bytes().to_vec();
+                // 0) recurring rules (they reference wallets and flows), budgets and
+                // categorization rules (they reference categories)
+                recurring_rules::Entity::delete_many()
+                    .filter(recurring_rules::Column::VaultId.eq(vault_db_id))
+                    .exec(db_tx)
+                    .await?;
+                budgets::Entity::delete_many()
+                    .filter(budgets::Column::VaultId.eq(vault_db_id))
+                    .exec(db_tx)
+                    .await?;
+                category_rules::Entity::delete_many()
+                    .filter(category_rules::Column::VaultId.eq(vault_db_id))
+                    .exec(db_tx)
+                    .await?;
 
-            // 1) legs for transactions in this vault
-            db_tx
-                .execute(Statement::from_sql_and_values(
-                    backend,
-                    "DELETE FROM legs WHERE transaction_id IN (SELECT id FROM transactions WHERE vault_id = ?);",
-                    vec![vault_db_id_bytes.clone().into()],
-                ))
-                .await?;
+                // 1) revisions and legs for transactions in this vault
+                let vault_transaction_ids = Query::select()
+                    .column(transactions::Column::Id)
+                    .from(transactions::Entity)
+                    .and_where(transactions::Column::VaultId.eq(vault_db_id))
+                    .to_owned();
+                transaction_revisions::Entity::delete_many()
+                    .filter(
+                        transaction_revisions::Column::TransactionId
+                            .in_subquery(vault_transaction_ids.clone()),
+                    )
+                    .exec(db_tx)
+                    .await?;
+                legs::Entity::delete_many()
+                    .filter(legs::Column::TransactionId.in_subquery(vault_transaction_ids))
+                    .exec(db_tx)
+                    .await?;
 
-            // 2) transactions
-            db_tx
-                .execute(Statement::from_sql_and_values(
-                    backend,
-                    "DELETE FROM transactions WHERE vault_id = ?;",
-                    vec![vault_db_id_bytes.clone().into()],
-                ))
-                .await?;
+                // 2) transactions
+                transactions::Entity::delete_many()
+                    .filter(transactions::Column::VaultId.eq(vault_db_id))
+                    .exec(db_tx)
+                    .await?;
 
-            // 3) category aliases and categories
-            db_tx
-                .execute(Statement::from_sql_and_values(
-                    backend,
-                    "DELETE FROM category_aliases WHERE vault_id = ?;",
-                    vec![vault_db_id_bytes.clone().into()],
-                ))
-                .await?;
-            db_tx
-                .execute(Statement::from_sql_and_values(
-                    backend,
-                    "DELETE FROM categories WHERE vault_id = ?;",
-                    vec![vault_db_id_bytes.clone().into()],
-                ))
-                .await?;
+                // 3) category aliases and categories
+                category_aliases::Entity::delete_many()
+                    .filter(category_aliases::Column::VaultId.eq(vault_db_id))
+                    .exec(db_tx)
+                    .await?;
+                categories::Entity::delete_many()
+                    .filter(categories::Column::VaultId.eq(vault_db_id))
+                    .exec(db_tx)
+                    .await?;
 
-            // 4) flows and wallets (no more entries table)
-            db_tx
-                .execute(Statement::from_sql_and_values(
-                    backend,
-                    "DELETE FROM cash_flows WHERE vault_id = ?;",
-                    vec![vault_db_id_bytes.clone().into()],
-                ))
-                .await?;
-            db_tx
-                .execute(Statement::from_sql_and_values(
-                    backend,
-                    "DELETE FROM wallets WHERE vault_id = ?;",
-                    vec![vault_db_id_bytes.clone().into()],
-                ))
-                .await?;
+                // 4) flows and wallets (no more entries table)
+                cash_flows::Entity::delete_many()
+                    .filter(cash_flows::Column::VaultId.eq(vault_db_id))
+                    .exec(db_tx)
+                    .await?;
+                wallets::Entity::delete_many()
+                    .filter(wallets::Column::VaultId.eq(vault_db_id))
+                    .exec(db_tx)
+                    .await?;
 
-            // 5) vault
-            db_tx
-                .execute(Statement::from_sql_and_values(
-                    backend,
-                    "DELETE FROM vaults WHERE id = ?;",
-                    vec![vault_db_id_bytes.clone().into()],
-                ))
+                // 5) vault
+                vault::Entity::delete_by_id(vault_db_id).exec(db_tx).await?;
+
+                // The audit log is kept: it has no foreign key to the vault.
+                record_audit(
+                    db_tx,
+                    vault_db_id,
+                    user_id.as_str(),
+                    AuditChange::deleted(AuditEntity::Vault, vault_db_id, snapshot(&vault_model)),
+                )
                 .await?;
 
-            Ok(())
-        }))
+                Ok(())
+            })
+        })
         .await
     }
 
+    /// Enforces unique vault names per owner (case-insensitive) to avoid
+    /// ambiguous name lookups.
+    pub(super) async fn ensure_vault_name_available(
+        &self,
+        db_tx: &DatabaseTransaction,
+        user_id: &str,
+        name: &str,
+    ) -> ResultEngine<()> {
+        let exists = vault::Entity::find()
+            .filter(vault::Column::UserId.eq(user_id))
+            .filter(Expr::cust("LOWER(name)").eq(name.to_lowercase()))
+            .one(db_tx)
+            .await?
+            .is_some();
+        if exists {
+            return Err(EngineError::ExistingKey(name.to_string()));
+        }
+        Ok(())
+    }
+
     /// Add a new vault
     pub async fn new_vault(
         &self,
@@ -108,21 +150,24 @@ impl Engine {
         new_vault.currency = currency.unwrap_or_default();
         let new_vault_id = new_vault.id.clone();
         let vault_entry: vault::ActiveModel = (&new_vault).into();
-        self.with_tx(|_engine, db_tx| {
+        self.with_tx(|engine, db_tx| {
             Box::pin(async move {
-                // Enforce unique vault names per owner (case-insensitive) to avoid
-                // ambiguous name lookups.
-                let exists = vault::Entity::find()
-                    .filter(vault::Column::UserId.eq(user_id.clone()))
-                    .filter(Expr::cust("LOWER(name)").eq(name.to_lowercase()))
-                    .one(db_tx)
-                    .await?
-                    .is_some();
-                if exists {
-                    return Err(EngineError::ExistingKey(name));
-                }
+                engine
+                    .ensure_vault_name_available(db_tx, &user_id, &name)
+                    .await?;
 
-                vault_entry.insert(db_tx).await?;
+                let vault_model = vault_entry.insert(db_tx).await?;
+                record_audit(
+                    db_tx,
+                    vault_model.id,
+                    &user_id,
+                    AuditChange::created(
+                        AuditEntity::Vault,
+                        vault_model.id,
+                        snapshot(&vault_model),
+                    ),
+                )
+                .await?;
 
                 // Create the system flow "Unallocated".
                 let mut unallocated = CashFlow::new(
@@ -171,6 +216,45 @@ impl Engine {
         .await
     }
 
+    /// Closes the books of a vault until `until`: transactions that occurred
+    /// before it can no longer be created, updated, voided, restored or
+    /// reverted. `None` reopens every period.
+    ///
+    /// Authorization: requires vault ownership.
+    pub async fn set_books_closed_until(
+        &self,
+        vault_id: &str,
+        until: Option<DateTime<Utc>>,
+        user_id: &str,
+    ) -> ResultEngine<()> {
+        let vault_id = vault_id.to_string();
+        let user_id = user_id.to_string();
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let vault_model = engine
+                    .require_vault_owner(db_tx, vault_id.as_str(), user_id.as_str())
+                    .await?;
+                let vault_uuid = vault_model.id;
+                let before = snapshot(&vault_model);
+                let model = vault::ActiveModel {
+                    id: ActiveValue::Set(vault_uuid),
+                    books_closed_until: ActiveValue::Set(until),
+                    ..Default::default()
+                }
+                .update(db_tx)
+                .await?;
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    user_id.as_str(),
+                    AuditChange::updated(AuditEntity::Vault, vault_uuid, before, snapshot(&model)),
+                )
+                .await
+            })
+        })
+        .await
+    }
+
     /// Return a user `Vault`.
     /// Return a vault snapshot from DB, including all wallets and flows.
     pub async fn vault_snapshot(
@@ -219,7 +303,7 @@ impl Engine {
 
                 let mut wallets_map = HashMap::new();
                 for wallet_model in wallet_models {
-                    let wallet = Wallet::try_from((wallet_model, vault_currency))?;
+                    let wallet = Wallet::from(wallet_model);
                     wallets_map.insert(wallet.id, wallet);
                 }
 
@@ -230,6 +314,7 @@ impl Engine {
                     wallet: wallets_map,
                     user_id: vault_model.user_id,
                     currency: vault_currency,
+                    books_closed_until: vault_model.books_closed_until,
                 };
                 Ok(snapshot)
             })
@@ -240,7 +325,9 @@ impl Engine {
     /// Returns vault totals: `(currency, balance_minor, total_income_minor,
     /// total_expenses_minor)`.
     ///
-    /// Transfers are excluded from income/expense totals.
+    /// Transfers are excluded from income/expense totals. `balance_minor` only
+    /// sums wallets denominated in the vault currency; foreign-currency wallets
+    /// keep their own balance (see `vault_snapshot`).
     pub async fn vault_statistics(
         &self,
         vault_id: &str,
@@ -249,81 +336,64 @@ impl Engine {
     ) -> ResultEngine<(Currency, i64, i64, i64)> {
         let vault_id = vault_id.to_string();
         let user_id = user_id.to_string();
-        self.with_tx(|engine, db_tx| Box::pin(async move {
-            let vault_model = engine
-                .require_vault_by_id(db_tx, vault_id.as_str(), user_id.as_str())
-                .await?;
-            let currency = vault_model.currency;
-            let vault_uuid = parse_vault_uuid(vault_id.as_str())?;
-            let vault_bytes: Vec<u8> = vault_uuid.as_bytes().to_vec();
-
-            let backend = engine.database.get_database_backend();
-            let void_cond = if include_voided {
-                ""
-            } else {
-                " AND voided_at IS NULL"
-            };
-
-            let balance_minor: i64 = {
-                let stmt = Statement::from_sql_and_values(
-                    backend,
-                    "SELECT COALESCE(SUM(balance), 0) AS sum FROM wallets WHERE vault_id = ? AND archived = 0;"
-                        .to_string(),
-                    vec![vault_bytes.clone().into()],
-                );
-                let row = db_tx.query_one(stmt).await?;
-                row.and_then(|r| r.try_get("", "sum").ok()).unwrap_or(0)
-            };
-
-            let total_income_minor: i64 = {
-                let stmt = Statement::from_sql_and_values(
-                    backend,
-                    format!(
-                        "SELECT COALESCE(SUM(amount_minor), 0) AS sum \
-                         FROM transactions \
-                         WHERE vault_id = ? AND kind = ?{void_cond}"
-                    ),
-                    vec![vault_bytes.clone().into(), TransactionKind::Income.as_str().into()],
-                );
-                let row = db_tx.query_one(stmt).await?;
-                row.and_then(|r| r.try_get("", "sum").ok()).unwrap_or(0)
-            };
-
-            let total_expenses_minor: i64 = {
-                let stmt = Statement::from_sql_and_values(
-                    backend,
-                    format!(
-                        "SELECT COALESCE(SUM(amount_minor), 0) AS sum \
-                         FROM transactions \
-                         WHERE vault_id = ? AND kind = ?{void_cond}"
-                    ),
-                    vec![vault_bytes.clone().into(), TransactionKind::Expense.as_str().into()],
-                );
-                let row = db_tx.query_one(stmt).await?;
-                row.and_then(|r| r.try_get("", "sum").ok()).unwrap_or(0)
-            };
-
-            let total_refunds_minor: i64 = {
-                let stmt = Statement::from_sql_and_values(
-                    backend,
-                    format!(
-                        "SELECT COALESCE(SUM(amount_minor), 0) AS sum \
-                         FROM transactions \
-                         WHERE vault_id = ? AND kind = ?{void_cond}"
-                    ),
-                    vec![vault_bytes.clone().into(), TransactionKind::Refund.as_str().into()],
-                );
-                let row = db_tx.query_one(stmt).await?;
-                row.and_then(|r| r.try_get("", "sum").ok()).unwrap_or(0)
-            };
-
-            Ok((
-                currency,
-                balance_minor,
-                total_income_minor,
-                total_expenses_minor - total_refunds_minor,
-            ))
-        }))
+        self.with_tx(|engine, db_tx| {
+            Box::pin(async move {
+                let vault_model = engine
+                    .require_vault_by_id(db_tx, vault_id.as_str(), user_id.as_str())
+                    .await?;
+                let currency = vault_model.currency;
+                let vault_uuid = parse_vault_uuid(vault_id.as_str())?;
+
+                let balance_minor: i64 = wallets::Entity::find()
+                    .select_only()
+                    .column_as(sum_minor(wallets::Column::Balance), "sum")
+                    .filter(wallets::Column::VaultId.eq(vault_uuid))
+                    .filter(wallets::Column::Archived.eq(false))
+                    .filter(wallets::Column::Currency.eq(currency.code()))
+                    .into_tuple::<Option<i64>>()
+                    .one(db_tx)
+                    .await?
+                    .flatten()
+                    .unwrap_or(0);
+
+                let mut totals_query = transactions::Entity::find()
+                    .select_only()
+                    .column(transactions::Column::Kind)
+                    .column_as(sum_minor(transactions::Column::AmountMinor), "sum")
+                    .filter(transactions::Column::VaultId.eq(vault_uuid))
+                    .filter(transactions::Column::Kind.is_in([
+                        TransactionKind::Income,
+                        TransactionKind::Expense,
+                        TransactionKind::Refund,
+                    ]))
+                    .group_by(transactions::Column::Kind);
+                if !include_voided {
+                    totals_query = totals_query.filter(transactions::Column::VoidedAt.is_null());
+                }
+                let (mut total_income_minor, mut total_expenses_minor, mut total_refunds_minor) =
+                    (0, 0, 0);
+                for (kind, sum) in totals_query
+                    .into_tuple::<(TransactionKind, Option<i64>)>()
+                    .all(db_tx)
+                    .await?
+                {
+                    let sum = sum.unwrap_or(0);
+                    match kind {
+                        TransactionKind::Income => total_income_minor = sum,
+                        TransactionKind::Expense => total_expenses_minor = sum,
+                        TransactionKind::Refund => total_refunds_minor = sum,
+                        _ => {}
+                    }
+                }
+
+                Ok((
+                    currency,
+                    balance_minor,
+                    total_income_minor,
+                    total_expenses_minor - total_refunds_minor,
+                ))
+            })
+        })
         .await
     }
 }
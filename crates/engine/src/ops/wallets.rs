@@ -4,11 +4,14 @@ use uuid::Uuid;
 use sea_orm::{ActiveValue, QueryFilter, prelude::*, sea_query::Expr};
 
 use crate::{
-    EngineError, ResultEngine, TransactionKind, Wallet, util::normalize_required_name, wallets,
+    AuditEntity, Currency, EngineError, ResultEngine, TransactionKind, Wallet, audit::AuditChange,
+    util::normalize_required_name, wallets,
 };
 
 use super::{
-    Engine, build_transaction, flow_wallet_legs, flow_wallet_signed_amount, parse_vault_uuid,
+    Engine,
+    audit::{entity_snapshot, record_audit, snapshot},
+    build_transaction, flow_wallet_legs, fx, parse_vault_uuid,
 };
 
 impl Engine {
@@ -23,10 +26,9 @@ impl Engine {
         let user_id = user_id.to_string();
         self.with_tx(|engine, db_tx| {
             Box::pin(async move {
-                let vault_model = engine
+                engine
                     .require_vault_by_id(db_tx, vault_id.as_str(), user_id.as_str())
                     .await?;
-                let vault_currency = vault_model.currency;
                 let vault_uuid = parse_vault_uuid(vault_id.as_str())?;
 
                 let model = wallets::Entity::find_by_id(wallet_id)
@@ -35,8 +37,7 @@ impl Engine {
                     .await?
                     .ok_or_else(|| EngineError::KeyNotFound("wallet not exists".to_string()))?;
 
-                let wallet = Wallet::try_from((model, vault_currency))?;
-                Ok(wallet)
+                Ok(Wallet::from(model))
             })
         })
         .await
@@ -50,10 +51,15 @@ impl Engine {
     /// - if `balance_minor < 0`: an opening `Expense`
     ///
     /// The opening transaction uses `Utc::now()` as `occurred_at`.
+    ///
+    /// `currency` defaults to the vault currency. Opening balances of
+    /// foreign-currency wallets need an exchange rate, so record them as an
+    /// income instead (`balance_minor` must be 0).
     pub async fn new_wallet(
         &self,
         vault_id: &str,
         name: &str,
+        currency: Option<Currency>,
         balance_minor: i64,
         user_id: &str,
     ) -> ResultEngine<Uuid> {
@@ -66,7 +72,8 @@ impl Engine {
                 let vault_model = engine
                     .require_vault_by_id_write(db_tx, vault_id.as_str(), user_id.as_str())
                     .await?;
-                let currency = vault_model.currency;
+                let vault_currency = vault_model.currency;
+                let currency = currency.unwrap_or(vault_currency);
                 let vault_uuid = vault_model.id;
 
                 let exists = wallets::Entity::find()
@@ -86,7 +93,14 @@ impl Engine {
                 let wallet_id = wallet.id;
                 let mut wallet_model: wallets::ActiveModel = (&wallet).into();
                 wallet_model.vault_id = ActiveValue::Set(vault_uuid);
-                wallet_model.insert(db_tx).await?;
+                let model = wallet_model.insert(db_tx).await?;
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    user_id.as_str(),
+                    AuditChange::created(AuditEntity::Wallet, wallet_id, snapshot(&model)),
+                )
+                .await?;
 
                 if balance_minor != 0 {
                     let amount_minor = balance_minor.abs();
@@ -95,7 +109,13 @@ impl Engine {
                     } else {
                         TransactionKind::Expense
                     };
-                    let signed_amount = flow_wallet_signed_amount(kind, amount_minor)?;
+                    let amounts = fx::resolve_flow_wallet_amounts(
+                        amount_minor,
+                        currency,
+                        None,
+                        currency,
+                        vault_currency,
+                    )?;
                     let category = engine
                         .resolve_category(db_tx, vault_id.as_str(), Some("opening"))
                         .await?;
@@ -104,30 +124,26 @@ impl Engine {
                         vault_id: vault_id.as_str(),
                         kind,
                         occurred_at,
-                        amount_minor,
-                        currency,
+                        amount_minor: amounts.vault_minor,
+                        currency: vault_currency,
                         category_id: category.id,
                         category: category.name,
                         note: Some(format!("opening balance for wallet '{name}'")),
                         created_by: user_id.as_str(),
                         idempotency_key: None,
                         refunded_transaction_id: None,
+                        conversion: None,
                     })?;
 
                     let unallocated_flow_id =
                         engine.unallocated_flow_id(db_tx, vault_id.as_str()).await?;
-                    let legs = flow_wallet_legs(
-                        tx.id,
-                        wallet_id,
-                        unallocated_flow_id,
-                        signed_amount,
-                        currency,
-                    );
+                    let legs =
+                        flow_wallet_legs(tx.id, wallet_id, unallocated_flow_id, kind, &amounts)?;
                     engine
                         .create_transaction_with_legs(
                             db_tx,
                             vault_id.as_str(),
-                            currency,
+                            vault_currency,
                             &tx,
                             &legs,
                         )
@@ -174,12 +190,20 @@ impl Engine {
                     return Err(EngineError::ExistingKey(new_name.clone()));
                 }
 
+                let before = entity_snapshot::<wallets::Entity>(db_tx, wallet_id).await?;
                 let active = wallets::ActiveModel {
                     id: ActiveValue::Set(wallet_id),
                     name: ActiveValue::Set(new_name),
                     ..Default::default()
                 };
-                active.update(db_tx).await?;
+                let model = active.update(db_tx).await?;
+                record_audit(
+                    db_tx,
+                    vault_uuid,
+                    user_id.as_str(),
+                    AuditChange::updated(AuditEntity::Wallet, wallet_id, before, snapshot(&model)),
+                )
+                .await?;
                 Ok(())
             })
         })
@@ -207,12 +231,20 @@ impl Engine {
                     .require_wallet_in_vault(db_tx, vault_id.as_str(), wallet_id)
                     .await?;
 
+                let before = entity_snapshot::<wallets::Entity>(db_tx, wallet_id).await?;
                 let active = wallets::ActiveModel {
                     id: ActiveValue::Set(wallet_id),
                     archived: ActiveValue::Set(archived),
                     ..Default::default()
                 };
-                active.update(db_tx).await?;
+                let model = active.update(db_tx).await?;
+                record_audit(
+                    db_tx,
+                    model.vault_id,
+                    user_id.as_str(),
+                    AuditChange::updated(AuditEntity::Wallet, wallet_id, before, snapshot(&model)),
+                )
+                .await?;
                 Ok(())
             })
         })
@@ -0,0 +1,34 @@
+//! Reconciliation of wallets against bank statements.
+//!
+//! Each transaction carries a [`ClearingStatus`](crate::ClearingStatus):
+//! transactions are marked cleared once they show up on a statement, and a
+//! reconciliation compares the cleared balance of a wallet with the closing
+//! balance of the statement. When the two match, the cleared transactions
+//! become reconciled and are locked against updates, voids and reverts until
+//! explicitly unlocked.
+
+use uuid::Uuid;
+
+use crate::Currency;
+
+/// Outcome of reconciling a wallet against a statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reconciliation {
+    pub wallet_id: Uuid,
+    pub currency: Currency,
+    pub statement_balance_minor: i64,
+    /// Sum of the wallet legs of cleared and reconciled transactions before
+    /// the statement date.
+    pub cleared_balance_minor: i64,
+    /// `statement_balance_minor - cleared_balance_minor`: zero when the
+    /// wallet reconciles.
+    pub difference_minor: i64,
+    /// Cleared transactions the statement covers, reconciled on commit.
+    pub cleared_count: usize,
+    /// Transactions before the statement date that are not cleared yet.
+    pub uncleared_count: usize,
+    /// Sum of the wallet legs of those uncleared transactions.
+    pub uncleared_minor: i64,
+    /// Transactions already reconciled by earlier statements.
+    pub reconciled_count: usize,
+}
@@ -0,0 +1,425 @@
+//! Recurring rules that materialize scheduled postings.
+//!
+//! A rule describes an income, expense or flow transfer repeating on a
+//! calendar schedule. Each occurrence is posted with the idempotency key
+//! `recurring:<rule_id>:<index>`, so re-running the scheduler never creates
+//! duplicates.
+
+use chrono::{DateTime, Datelike, Duration, Utc};
+use sea_orm::{ActiveValue, entity::prelude::*};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{EngineError, ResultEngine, TransactionKind, util};
+
+/// How often a recurring rule repeats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
+pub enum RecurrenceFrequency {
+    #[sea_orm(string_value = "weekly")]
+    Weekly,
+    #[sea_orm(string_value = "monthly")]
+    Monthly,
+    #[sea_orm(string_value = "yearly")]
+    Yearly,
+}
+
+/// Calendar schedule of a recurring rule.
+///
+/// Occurrences keep the time of day of `starts_at`. Monthly rules fall on
+/// `day_of_month` (default: the day of `starts_at`), clamped to the last day of
+/// shorter months; yearly rules clamp Feb 29 to Feb 28.
+///
+/// # Examples
+///
+/// ```rust
+/// use chrono::{TimeZone, Utc};
+/// use engine::{Recurrence, RecurrenceFrequency};
+///
+/// let start = Utc.with_ymd_and_hms(2026, 1, 10, 9, 0, 0).unwrap();
+/// let rent = Recurrence::new(RecurrenceFrequency::Monthly, start).day_of_month(31);
+/// assert_eq!(
+///     rent.occurrence(1),
+///     Some(Utc.with_ymd_and_hms(2026, 2, 28, 9, 0, 0).unwrap())
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: RecurrenceFrequency,
+    /// Repeat every `interval` weeks/months/years (>= 1).
+    pub interval: u32,
+    /// Day of month for monthly rules (1..=31).
+    pub day_of_month: Option<u32>,
+    pub starts_at: DateTime<Utc>,
+    /// Inclusive end of the schedule.
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Maximum number of occurrences.
+    pub max_occurrences: Option<u32>,
+}
+
+impl Recurrence {
+    #[must_use]
+    pub fn new(frequency: RecurrenceFrequency, starts_at: DateTime<Utc>) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            day_of_month: None,
+            starts_at,
+            ends_at: None,
+            max_occurrences: None,
+        }
+    }
+
+    #[must_use]
+    pub fn interval(mut self, interval: u32) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    #[must_use]
+    pub fn day_of_month(mut self, day: u32) -> Self {
+        self.day_of_month = Some(day);
+        self
+    }
+
+    #[must_use]
+    pub fn ends_at(mut self, ends_at: DateTime<Utc>) -> Self {
+        self.ends_at = Some(ends_at);
+        self
+    }
+
+    #[must_use]
+    pub fn max_occurrences(mut self, count: u32) -> Self {
+        self.max_occurrences = Some(count);
+        self
+    }
+
+    pub(crate) fn validate(&self) -> ResultEngine<()> {
+        if self.interval == 0 {
+            return Err(EngineError::InvalidAmount(
+                "interval must be >= 1".to_string(),
+            ));
+        }
+        if let Some(day) = self.day_of_month {
+            if self.frequency != RecurrenceFrequency::Monthly {
+                return Err(EngineError::InvalidAmount(
+                    "day_of_month is only valid for monthly rules".to_string(),
+                ));
+            }
+            if !(1..=31).contains(&day) {
+                return Err(EngineError::InvalidAmount(
+                    "day_of_month must be between 1 and 31".to_string(),
+                ));
+            }
+        }
+        if self.max_occurrences == Some(0) {
+            return Err(EngineError::InvalidAmount(
+                "max_occurrences must be >= 1".to_string(),
+            ));
+        }
+        if self.ends_at.is_some_and(|end| end < self.starts_at) {
+            return Err(EngineError::InvalidAmount(
+                "ends_at must not be before starts_at".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the `index`-th occurrence (0-based), or `None` once the
+    /// schedule is exhausted.
+    #[must_use]
+    pub fn occurrence(&self, index: u32) -> Option<DateTime<Utc>> {
+        if self.max_occurrences.is_some_and(|max| index >= max) {
+            return None;
+        }
+        // A monthly day before the start day makes the first slot fall
+        // before `starts_at`; skip it.
+        let offset = u32::from(self.slot(0)? < self.starts_at);
+        let at = self.slot(index.checked_add(offset)?)?;
+        match self.ends_at {
+            Some(end) if at > end => None,
+            _ => Some(at),
+        }
+    }
+
+    fn slot(&self, step: u32) -> Option<DateTime<Utc>> {
+        let step = step.checked_mul(self.interval)?;
+        match self.frequency {
+            RecurrenceFrequency::Weekly => self
+                .starts_at
+                .checked_add_signed(Duration::weeks(i64::from(step))),
+            RecurrenceFrequency::Monthly => add_months_on_day(
+                self.starts_at,
+                step,
+                self.day_of_month.unwrap_or(self.starts_at.day()),
+            ),
+            RecurrenceFrequency::Yearly => {
+                add_months_on_day(self.starts_at, step.checked_mul(12)?, self.starts_at.day())
+            }
+        }
+    }
+}
+
+/// Moves `start` forward by `months` and places it on `day`, keeping the time
+/// of day.
+fn add_months_on_day(start: DateTime<Utc>, months: u32, day: u32) -> Option<DateTime<Utc>> {
+    let date = util::add_months_on_day(start.date_naive(), months, day)?;
+    Some(date.and_time(start.time()).and_utc())
+}
+
+/// Recurring posting template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecurringRule {
+    pub id: Uuid,
+    pub vault_id: String,
+    /// One of `Income`, `Expense` or `TransferFlow`.
+    pub kind: TransactionKind,
+    /// Amount in minor units of the wallet currency (vault currency for flow
+    /// transfers).
+    pub amount_minor: i64,
+    // Income/Expense targets.
+    pub wallet_id: Option<Uuid>,
+    pub flow_id: Option<Uuid>,
+    // TransferFlow targets.
+    pub from_flow_id: Option<Uuid>,
+    pub to_flow_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub note: Option<String>,
+    pub recurrence: Recurrence,
+    /// Number of occurrences already materialized.
+    pub occurrences: u32,
+    /// Next occurrence to materialize; `None` once the schedule is exhausted.
+    pub next_occurrence_at: Option<DateTime<Utc>>,
+    /// Paused rules are skipped by the scheduler.
+    pub active: bool,
+    /// Why the scheduler paused the rule; cleared when it is resumed.
+    pub last_error: Option<String>,
+    /// User the postings are created as.
+    pub created_by: String,
+}
+
+impl RecurringRule {
+    /// Idempotency key of the `index`-th occurrence.
+    #[must_use]
+    pub fn idempotency_key(&self, index: u32) -> String {
+        format!("recurring:{}:{index}", self.id)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "recurring_rules")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub vault_id: Uuid,
+    pub kind: TransactionKind,
+    pub amount_minor: i64,
+    pub wallet_id: Option<Uuid>,
+    pub flow_id: Option<Uuid>,
+    pub from_flow_id: Option<Uuid>,
+    pub to_flow_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub note: Option<String>,
+    pub frequency: RecurrenceFrequency,
+    pub interval: i32,
+    pub day_of_month: Option<i32>,
+    pub starts_at: DateTimeUtc,
+    pub ends_at: Option<DateTimeUtc>,
+    pub max_occurrences: Option<i32>,
+    pub occurrences: i32,
+    pub next_occurrence_at: Option<DateTimeUtc>,
+    pub active: bool,
+    pub last_error: Option<String>,
+    pub created_by: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::vault::Entity",
+        from = "Column::VaultId",
+        to = "super::vault::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Vault,
+}
+
+impl Related<super::vault::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vault.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+fn to_db_count(value: u32, label: &str) -> ResultEngine<i32> {
+    i32::try_from(value).map_err(|_| EngineError::InvalidAmount(format!("{label} is too large")))
+}
+
+fn from_db_count(value: i32, label: &str) -> ResultEngine<u32> {
+    u32::try_from(value).map_err(|_| EngineError::InvalidAmount(format!("invalid {label}")))
+}
+
+impl TryFrom<&RecurringRule> for ActiveModel {
+    type Error = EngineError;
+
+    fn try_from(rule: &RecurringRule) -> Result<Self, Self::Error> {
+        let vault_id = Uuid::parse_str(&rule.vault_id)
+            .map_err(|_| EngineError::KeyNotFound("vault not exists".to_string()))?;
+        Ok(Self {
+            id: ActiveValue::Set(rule.id),
+            vault_id: ActiveValue::Set(vault_id),
+            kind: ActiveValue::Set(rule.kind),
+            amount_minor: ActiveValue::Set(rule.amount_minor),
+            wallet_id: ActiveValue::Set(rule.wallet_id),
+            flow_id: ActiveValue::Set(rule.flow_id),
+            from_flow_id: ActiveValue::Set(rule.from_flow_id),
+            to_flow_id: ActiveValue::Set(rule.to_flow_id),
+            category: ActiveValue::Set(rule.category.clone()),
+            note: ActiveValue::Set(rule.note.clone()),
+            frequency: ActiveValue::Set(rule.recurrence.frequency),
+            interval: ActiveValue::Set(to_db_count(rule.recurrence.interval, "interval")?),
+            day_of_month: ActiveValue::Set(
+                rule.recurrence
+                    .day_of_month
+                    .map(|day| to_db_count(day, "day_of_month"))
+                    .transpose()?,
+            ),
+            starts_at: ActiveValue::Set(rule.recurrence.starts_at),
+            ends_at: ActiveValue::Set(rule.recurrence.ends_at),
+            max_occurrences: ActiveValue::Set(
+                rule.recurrence
+                    .max_occurrences
+                    .map(|count| to_db_count(count, "max_occurrences"))
+                    .transpose()?,
+            ),
+            occurrences: ActiveValue::Set(to_db_count(rule.occurrences, "occurrences")?),
+            next_occurrence_at: ActiveValue::Set(rule.next_occurrence_at),
+            active: ActiveValue::Set(rule.active),
+            last_error: ActiveValue::Set(rule.last_error.clone()),
+            created_by: ActiveValue::Set(rule.created_by.clone()),
+        })
+    }
+}
+
+impl TryFrom<Model> for RecurringRule {
+    type Error = EngineError;
+
+    fn try_from(model: Model) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: model.id,
+            vault_id: model.vault_id.to_string(),
+            kind: model.kind,
+            amount_minor: model.amount_minor,
+            wallet_id: model.wallet_id,
+            flow_id: model.flow_id,
+            from_flow_id: model.from_flow_id,
+            to_flow_id: model.to_flow_id,
+            category: model.category,
+            note: model.note,
+            recurrence: Recurrence {
+                frequency: model.frequency,
+                interval: from_db_count(model.interval, "interval")?,
+                day_of_month: model
+                    .day_of_month
+                    .map(|day| from_db_count(day, "day_of_month"))
+                    .transpose()?,
+                starts_at: model.starts_at,
+                ends_at: model.ends_at,
+                max_occurrences: model
+                    .max_occurrences
+                    .map(|count| from_db_count(count, "max_occurrences"))
+                    .transpose()?,
+            },
+            occurrences: from_db_count(model.occurrences, "occurrences")?,
+            next_occurrence_at: model.next_occurrence_at,
+            active: model.active,
+            last_error: model.last_error,
+            created_by: model.created_by,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, 8, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn weekly_respects_interval() {
+        let rule = Recurrence::new(RecurrenceFrequency::Weekly, at(2026, 1, 5)).interval(2);
+        assert_eq!(rule.occurrence(0), Some(at(2026, 1, 5)));
+        assert_eq!(rule.occurrence(1), Some(at(2026, 1, 19)));
+        assert_eq!(rule.occurrence(3), Some(at(2026, 2, 16)));
+    }
+
+    #[test]
+    fn monthly_clamps_day_and_skips_slot_before_start() {
+        let rule = Recurrence::new(RecurrenceFrequency::Monthly, at(2026, 1, 20)).day_of_month(5);
+        assert_eq!(rule.occurrence(0), Some(at(2026, 2, 5)));
+        assert_eq!(rule.occurrence(1), Some(at(2026, 3, 5)));
+
+        let rule = Recurrence::new(RecurrenceFrequency::Monthly, at(2026, 1, 31));
+        assert_eq!(rule.occurrence(0), Some(at(2026, 1, 31)));
+        assert_eq!(rule.occurrence(1), Some(at(2026, 2, 28)));
+        assert_eq!(rule.occurrence(2), Some(at(2026, 3, 31)));
+        assert_eq!(rule.occurrence(3), Some(at(2026, 4, 30)));
+    }
+
+    #[test]
+    fn yearly_clamps_leap_day() {
+        let rule = Recurrence::new(RecurrenceFrequency::Yearly, at(2028, 2, 29));
+        assert_eq!(rule.occurrence(1), Some(at(2029, 2, 28)));
+        assert_eq!(rule.occurrence(4), Some(at(2032, 2, 29)));
+    }
+
+    #[test]
+    fn end_date_and_count_limit_the_schedule() {
+        let rule = Recurrence::new(RecurrenceFrequency::Monthly, at(2026, 1, 1)).max_occurrences(2);
+        assert!(rule.occurrence(1).is_some());
+        assert_eq!(rule.occurrence(2), None);
+
+        let rule =
+            Recurrence::new(RecurrenceFrequency::Monthly, at(2026, 1, 1)).ends_at(at(2026, 3, 1));
+        assert_eq!(rule.occurrence(2), Some(at(2026, 3, 1)));
+        assert_eq!(rule.occurrence(3), None);
+    }
+
+    #[test]
+    fn validate_rejects_inconsistent_schedules() {
+        let start = at(2026, 1, 1);
+        assert!(
+            Recurrence::new(RecurrenceFrequency::Weekly, start)
+                .interval(0)
+                .validate()
+                .is_err()
+        );
+        assert!(
+            Recurrence::new(RecurrenceFrequency::Weekly, start)
+                .day_of_month(3)
+                .validate()
+                .is_err()
+        );
+        assert!(
+            Recurrence::new(RecurrenceFrequency::Monthly, start)
+                .day_of_month(32)
+                .validate()
+                .is_err()
+        );
+        assert!(
+            Recurrence::new(RecurrenceFrequency::Monthly, start)
+                .ends_at(at(2025, 12, 1))
+                .validate()
+                .is_err()
+        );
+    }
+}
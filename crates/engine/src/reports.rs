@@ -0,0 +1,294 @@
+//! Aggregated reports over booked transactions.
+//!
+//! A report sums the flow legs of non-voided incomes, expenses and refunds
+//! (transfers move money inside the vault and are never reported), so every
+//! amount is in the vault currency. Rows are keyed by the requested
+//! dimensions: category (split lines count towards their own category), flow,
+//! wallet and at most one calendar period — day, ISO week (starting on
+//! Monday), month or year — computed in the requested UTC offset.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{Currency, EngineError, ResultEngine, TransactionKind, util::CalendarPeriod};
+
+/// Dimension a report is grouped by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportGroupBy {
+    Category,
+    Flow,
+    Wallet,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl ReportGroupBy {
+    fn is_period(self) -> bool {
+        self.calendar_period().is_some()
+    }
+
+    fn calendar_period(self) -> Option<CalendarPeriod> {
+        match self {
+            Self::Category | Self::Flow | Self::Wallet => None,
+            Self::Day => Some(CalendarPeriod::Day),
+            Self::Week => Some(CalendarPeriod::Week),
+            Self::Month => Some(CalendarPeriod::Month),
+            Self::Year => Some(CalendarPeriod::Year),
+        }
+    }
+
+    /// First day of the period containing `date`, for period dimensions.
+    #[must_use]
+    pub fn period_start(self, date: NaiveDate) -> Option<NaiveDate> {
+        self.calendar_period()?.start(date)
+    }
+}
+
+/// Totals of one group. Dimensions the report is not grouped by are `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReportRow {
+    pub period_start: Option<NaiveDate>,
+    pub category_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub flow_id: Option<Uuid>,
+    pub flow: Option<String>,
+    pub wallet_id: Option<Uuid>,
+    pub wallet: Option<String>,
+    pub income_minor: i64,
+    /// Expenses as a positive amount.
+    pub expense_minor: i64,
+    pub refund_minor: i64,
+    /// Number of distinct transactions in the group.
+    pub transactions: u64,
+}
+
+impl ReportRow {
+    /// `income - expense + refund`.
+    #[must_use]
+    pub fn net_minor(&self) -> i64 {
+        self.income_minor
+            .saturating_sub(self.expense_minor)
+            .saturating_add(self.refund_minor)
+    }
+}
+
+/// Result of a report query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    pub currency: Currency,
+    pub group_by: Vec<ReportGroupBy>,
+    /// Sorted by period, then by expense (largest first), then by income.
+    pub rows: Vec<ReportRow>,
+}
+
+/// One flow leg to aggregate.
+#[derive(Clone, Debug)]
+pub(crate) struct ReportEntry {
+    pub transaction_id: Uuid,
+    pub kind: TransactionKind,
+    pub occurred_at: DateTime<Utc>,
+    pub category_id: Uuid,
+    pub flow_id: Uuid,
+    pub wallet_id: Option<Uuid>,
+    /// Signed flow leg amount.
+    pub amount_minor: i64,
+}
+
+type RowKey = (Option<NaiveDate>, Option<Uuid>, Option<Uuid>, Option<Uuid>);
+
+/// Checks that `group_by` has no duplicates and at most one period.
+pub(crate) fn validate_group_by(group_by: &[ReportGroupBy]) -> ResultEngine<()> {
+    let mut seen = HashSet::new();
+    if group_by.iter().any(|dimension| !seen.insert(*dimension)) {
+        return Err(EngineError::InvalidAmount(
+            "group_by must not repeat a dimension".to_string(),
+        ));
+    }
+    if group_by
+        .iter()
+        .filter(|dimension| dimension.is_period())
+        .count()
+        > 1
+    {
+        return Err(EngineError::InvalidAmount(
+            "group_by takes at most one period".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Sums `entries` into one row per group. Names are left empty.
+pub(crate) fn aggregate(
+    group_by: &[ReportGroupBy],
+    offset: FixedOffset,
+    entries: impl IntoIterator<Item = ReportEntry>,
+) -> ResultEngine<Vec<ReportRow>> {
+    let period = group_by
+        .iter()
+        .copied()
+        .find(|dimension| dimension.is_period());
+    let by_category = group_by.contains(&ReportGroupBy::Category);
+    let by_flow = group_by.contains(&ReportGroupBy::Flow);
+    let by_wallet = group_by.contains(&ReportGroupBy::Wallet);
+
+    let mut rows: HashMap<RowKey, ReportRow> = HashMap::new();
+    let mut counted: HashSet<(RowKey, Uuid)> = HashSet::new();
+    for entry in entries {
+        let period_start = match period {
+            Some(period) => Some(
+                period
+                    .period_start(entry.occurred_at.with_timezone(&offset).date_naive())
+                    .ok_or_else(|| EngineError::InvalidAmount("date out of range".to_string()))?,
+            ),
+            None => None,
+        };
+        let key = (
+            period_start,
+            by_category.then_some(entry.category_id),
+            by_flow.then_some(entry.flow_id),
+            by_wallet.then_some(entry.wallet_id).flatten(),
+        );
+        let row = rows.entry(key).or_insert_with(|| ReportRow {
+            period_start: key.0,
+            category_id: key.1,
+            flow_id: key.2,
+            wallet_id: key.3,
+            ..Default::default()
+        });
+        let (total, amount) = match entry.kind {
+            TransactionKind::Income => (&mut row.income_minor, entry.amount_minor),
+            TransactionKind::Expense => (&mut row.expense_minor, -entry.amount_minor),
+            TransactionKind::Refund => (&mut row.refund_minor, entry.amount_minor),
+            TransactionKind::TransferWallet | TransactionKind::TransferFlow => continue,
+        };
+        *total = total
+            .checked_add(amount)
+            .ok_or_else(|| EngineError::InvalidAmount("amount overflow".to_string()))?;
+        if counted.insert((key, entry.transaction_id)) {
+            row.transactions += 1;
+        }
+    }
+
+    let mut rows: Vec<ReportRow> = rows.into_values().collect();
+    rows.sort_by(|a, b| {
+        a.period_start
+            .cmp(&b.period_start)
+            .then(b.expense_minor.cmp(&a.expense_minor))
+            .then(b.income_minor.cmp(&a.income_minor))
+            .then(a.category_id.cmp(&b.category_id))
+            .then(a.flow_id.cmp(&b.flow_id))
+            .then(a.wallet_id.cmp(&b.wallet_id))
+    });
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn entry(
+        transaction_id: Uuid,
+        kind: TransactionKind,
+        occurred_at: DateTime<Utc>,
+        category_id: Uuid,
+        amount_minor: i64,
+    ) -> ReportEntry {
+        ReportEntry {
+            transaction_id,
+            kind,
+            occurred_at,
+            category_id,
+            flow_id: Uuid::nil(),
+            wallet_id: None,
+            amount_minor,
+        }
+    }
+
+    #[test]
+    fn periods_start_on_monday_first_of_month_and_new_year() {
+        // 2026-03-12 is a Thursday.
+        let date = day(2026, 3, 12);
+        assert_eq!(ReportGroupBy::Day.period_start(date), Some(date));
+        assert_eq!(
+            ReportGroupBy::Week.period_start(date),
+            Some(day(2026, 3, 9))
+        );
+        assert_eq!(
+            ReportGroupBy::Month.period_start(date),
+            Some(day(2026, 3, 1))
+        );
+        assert_eq!(
+            ReportGroupBy::Year.period_start(date),
+            Some(day(2026, 1, 1))
+        );
+        assert_eq!(ReportGroupBy::Category.period_start(date), None);
+    }
+
+    #[test]
+    fn group_by_rejects_duplicates_and_two_periods() {
+        assert!(validate_group_by(&[ReportGroupBy::Month, ReportGroupBy::Category]).is_ok());
+        assert!(validate_group_by(&[ReportGroupBy::Flow, ReportGroupBy::Flow]).is_err());
+        assert!(validate_group_by(&[ReportGroupBy::Day, ReportGroupBy::Month]).is_err());
+    }
+
+    #[test]
+    fn aggregates_per_month_and_category_in_the_given_offset() {
+        let food = Uuid::new_v4();
+        let rent = Uuid::new_v4();
+        let split = Uuid::new_v4();
+        // 23:30 UTC on Jan 31st is already February at +01:00.
+        let late = Utc.with_ymd_and_hms(2026, 1, 31, 23, 30, 0).unwrap();
+        let mid = Utc.with_ymd_and_hms(2026, 2, 10, 12, 0, 0).unwrap();
+        let entries = [
+            entry(split, TransactionKind::Expense, late, food, -3000),
+            entry(split, TransactionKind::Expense, late, food, -500),
+            entry(split, TransactionKind::Expense, late, rent, -1500),
+            entry(Uuid::new_v4(), TransactionKind::Refund, mid, food, 700),
+            entry(Uuid::new_v4(), TransactionKind::Income, mid, rent, 9000),
+        ];
+        let offset = FixedOffset::east_opt(3600).unwrap();
+        let rows = aggregate(
+            &[ReportGroupBy::Month, ReportGroupBy::Category],
+            offset,
+            entries.clone(),
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(
+            rows.iter()
+                .all(|row| row.period_start == Some(day(2026, 2, 1)))
+        );
+        assert_eq!(rows[0].category_id, Some(food));
+        assert_eq!(rows[0].expense_minor, 3500);
+        assert_eq!(rows[0].refund_minor, 700);
+        assert_eq!(rows[0].transactions, 2);
+        assert_eq!(rows[0].net_minor(), -2800);
+        assert_eq!(rows[1].income_minor, 9000);
+
+        let rows = aggregate(
+            &[ReportGroupBy::Month],
+            FixedOffset::east_opt(0).unwrap(),
+            entries,
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].period_start, Some(day(2026, 1, 1)));
+        assert_eq!(rows[0].expense_minor, 5000);
+        assert_eq!(rows[0].transactions, 1);
+        assert_eq!(rows[1].net_minor(), 9700);
+    }
+}
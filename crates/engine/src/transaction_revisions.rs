@@ -0,0 +1,140 @@
+//! Transaction edit history.
+//!
+//! Every update of a transaction first stores its current state (header and
+//! legs) as a numbered revision, so previous values can be listed and
+//! restored. Revision `1` is the state before the first update.
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveValue, entity::prelude::*};
+use uuid::Uuid;
+
+use crate::{Currency, EngineError, ExchangeRate, Leg, ResultEngine, legs, transactions};
+
+/// A previous state of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionRevision {
+    /// 1-based revision number, increasing with each update.
+    pub revision: u32,
+    pub transaction_id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    pub amount_minor: i64,
+    pub currency: Currency,
+    pub original_amount_minor: Option<i64>,
+    pub original_currency: Option<Currency>,
+    pub exchange_rate: Option<ExchangeRate>,
+    pub category_id: Uuid,
+    pub category: Option<String>,
+    pub note: Option<String>,
+    pub legs: Vec<Leg>,
+    /// User whose update replaced this state.
+    pub replaced_by: String,
+    pub replaced_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "transaction_revisions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub revision: i32,
+    pub occurred_at: DateTimeUtc,
+    pub amount_minor: i64,
+    pub currency: Currency,
+    pub original_amount_minor: Option<i64>,
+    pub original_currency: Option<Currency>,
+    pub exchange_rate: Option<String>,
+    pub category_id: Uuid,
+    pub category: Option<String>,
+    pub note: Option<String>,
+    /// The legs of the transaction, serialized as [`Leg`]s.
+    pub legs: Json,
+    pub replaced_by: String,
+    pub replaced_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::transactions::Entity",
+        from = "Column::TransactionId",
+        to = "super::transactions::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Transactions,
+}
+
+impl Related<super::transactions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Transactions.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    /// Revision `revision` of a transaction, from its current rows.
+    pub(crate) fn new(
+        revision: i32,
+        tx: &transactions::Model,
+        leg_models: Vec<legs::Model>,
+        replaced_by: &str,
+    ) -> ResultEngine<Self> {
+        let legs = leg_models
+            .into_iter()
+            .map(Leg::try_from)
+            .collect::<ResultEngine<Vec<_>>>()?;
+        let legs = serde_json::to_value(legs)
+            .map_err(|err| EngineError::InvalidAmount(format!("invalid legs: {err}")))?;
+        Ok(Self {
+            id: ActiveValue::Set(Uuid::now_v7()),
+            transaction_id: ActiveValue::Set(tx.id),
+            revision: ActiveValue::Set(revision),
+            occurred_at: ActiveValue::Set(tx.occurred_at),
+            amount_minor: ActiveValue::Set(tx.amount_minor),
+            currency: ActiveValue::Set(tx.currency),
+            original_amount_minor: ActiveValue::Set(tx.original_amount_minor),
+            original_currency: ActiveValue::Set(tx.original_currency),
+            exchange_rate: ActiveValue::Set(tx.exchange_rate.clone()),
+            category_id: ActiveValue::Set(tx.category_id),
+            category: ActiveValue::Set(tx.category.clone()),
+            note: ActiveValue::Set(tx.note.clone()),
+            legs: ActiveValue::Set(legs),
+            replaced_by: ActiveValue::Set(replaced_by.to_string()),
+            replaced_at: ActiveValue::Set(Utc::now()),
+        })
+    }
+}
+
+impl TryFrom<Model> for TransactionRevision {
+    type Error = EngineError;
+
+    fn try_from(model: Model) -> Result<Self, Self::Error> {
+        let revision = u32::try_from(model.revision)
+            .map_err(|_| EngineError::InvalidAmount("invalid revision number".to_string()))?;
+        let exchange_rate = model
+            .exchange_rate
+            .as_deref()
+            .map(ExchangeRate::parse)
+            .transpose()?;
+        let legs = serde_json::from_value(model.legs)
+            .map_err(|err| EngineError::InvalidAmount(format!("invalid revision legs: {err}")))?;
+        Ok(Self {
+            revision,
+            transaction_id: model.transaction_id,
+            occurred_at: model.occurred_at,
+            amount_minor: model.amount_minor,
+            currency: model.currency,
+            original_amount_minor: model.original_amount_minor,
+            original_currency: model.original_currency,
+            exchange_rate,
+            category_id: model.category_id,
+            category: model.category,
+            note: model.note,
+            legs,
+            replaced_by: model.replaced_by,
+            replaced_at: model.replaced_at,
+        })
+    }
+}
@@ -8,7 +8,7 @@ use sea_orm::{ActiveValue, entity::prelude::*};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{Currency, EngineError, ResultEngine};
+use crate::{Currency, EngineError, ExchangeRate, ResultEngine};
 
 use super::legs;
 
@@ -26,6 +26,9 @@ pub struct TransactionNew {
     pub created_by: String,
     pub idempotency_key: Option<String>,
     pub refunded_transaction_id: Option<Uuid>,
+    pub original_amount_minor: Option<i64>,
+    pub original_currency: Option<Currency>,
+    pub exchange_rate: Option<ExchangeRate>,
 }
 
 /// The type of a financial transaction.
@@ -74,6 +77,25 @@ impl TryFrom<&str> for TransactionKind {
     }
 }
 
+/// Whether a transaction has been matched against a bank statement.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, EnumIter, DeriveActiveEnum,
+)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
+pub enum ClearingStatus {
+    #[default]
+    #[sea_orm(string_value = "uncleared")]
+    Uncleared,
+    /// Seen on a statement, not reconciled yet.
+    #[sea_orm(string_value = "cleared")]
+    Cleared,
+    /// Part of a reconciled statement: locked against edits and voids until
+    /// unlocked.
+    #[sea_orm(string_value = "reconciled")]
+    Reconciled,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Transaction {
     pub id: Uuid,
@@ -90,8 +112,22 @@ pub struct Transaction {
     pub created_by: String,
     pub voided_at: Option<DateTime<Utc>>,
     pub voided_by: Option<String>,
+    pub clearing_status: ClearingStatus,
     pub refunded_transaction_id: Option<Uuid>,
+    /// Amount as entered, in `original_currency` minor units.
+    ///
+    /// Only set when a currency conversion happened; `amount_minor` is then
+    /// the converted amount in `currency`.
+    pub original_amount_minor: Option<i64>,
+    pub original_currency: Option<Currency>,
+    /// Rate converting `original_currency` into the other currency involved.
+    pub exchange_rate: Option<ExchangeRate>,
     pub legs: Vec<legs::Leg>,
+    /// Sum of the non-voided refunds linked to this expense (vault currency).
+    ///
+    /// Only filled for expenses by the list and detail reads.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refunded_minor: Option<i64>,
 }
 
 impl Transaction {
@@ -122,13 +158,18 @@ impl Transaction {
             created_by: input.created_by,
             voided_at: None,
             voided_by: None,
+            clearing_status: ClearingStatus::Uncleared,
             refunded_transaction_id: input.refunded_transaction_id,
+            original_amount_minor: input.original_amount_minor,
+            original_currency: input.original_currency,
+            exchange_rate: input.exchange_rate,
             legs: Vec::new(),
+            refunded_minor: None,
         })
     }
 }
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "transactions")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -146,6 +187,10 @@ pub struct Model {
     pub voided_by: Option<String>,
     pub refunded_transaction_id: Option<Uuid>,
     pub category_id: Uuid,
+    pub original_amount_minor: Option<i64>,
+    pub original_currency: Option<Currency>,
+    pub exchange_rate: Option<String>,
+    pub clearing_status: ClearingStatus,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -162,6 +207,19 @@ impl Related<super::legs::Entity> for Entity {
 
 impl ActiveModelBehavior for ActiveModel {}
 
+impl Model {
+    /// Rejects changes to a reconciled transaction.
+    pub(crate) fn ensure_unlocked(&self) -> ResultEngine<()> {
+        if self.clearing_status == ClearingStatus::Reconciled {
+            return Err(EngineError::TransactionLocked(format!(
+                "transaction {} is reconciled, unlock it first",
+                self.id
+            )));
+        }
+        Ok(())
+    }
+}
+
 impl From<&Transaction> for ActiveModel {
     fn from(tx: &Transaction) -> Self {
         Self {
@@ -181,6 +239,10 @@ impl From<&Transaction> for ActiveModel {
             voided_at: ActiveValue::Set(tx.voided_at),
             voided_by: ActiveValue::Set(tx.voided_by.clone()),
             refunded_transaction_id: ActiveValue::Set(tx.refunded_transaction_id),
+            original_amount_minor: ActiveValue::Set(tx.original_amount_minor),
+            original_currency: ActiveValue::Set(tx.original_currency),
+            exchange_rate: ActiveValue::Set(tx.exchange_rate.map(|rate| rate.to_string())),
+            clearing_status: ActiveValue::Set(tx.clearing_status),
         }
     }
 }
@@ -189,6 +251,11 @@ impl TryFrom<Model> for Transaction {
     type Error = EngineError;
 
     fn try_from(model: Model) -> Result<Self, Self::Error> {
+        let exchange_rate = model
+            .exchange_rate
+            .as_deref()
+            .map(ExchangeRate::parse)
+            .transpose()?;
         Ok(Self {
             id: model.id,
             vault_id: model.vault_id.to_string(),
@@ -203,8 +270,13 @@ impl TryFrom<Model> for Transaction {
             created_by: model.created_by,
             voided_at: model.voided_at,
             voided_by: model.voided_by,
+            clearing_status: model.clearing_status,
             refunded_transaction_id: model.refunded_transaction_id,
+            original_amount_minor: model.original_amount_minor,
+            original_currency: model.original_currency,
+            exchange_rate,
             legs: Vec::new(),
+            refunded_minor: None,
         })
     }
 }
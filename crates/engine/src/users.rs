@@ -12,6 +12,7 @@ pub struct Model {
     pub password: String,
     pub telegram_id: Option<String>,
     pub pair_code: Option<String>,
+    pub pair_code_expires_at: Option<DateTimeUtc>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
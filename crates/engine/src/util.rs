@@ -3,7 +3,7 @@
 //! These utilities are **not** part of the public API. They centralize
 //! validation and mapping logic so the engine enforces consistent invariants.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
 
 use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
 
@@ -159,3 +159,47 @@ pub(crate) fn ensure_vault_currency(
     }
     Ok(())
 }
+
+/// Calendar period used to bucket dates; weeks start on Monday (ISO).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CalendarPeriod {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl CalendarPeriod {
+    /// First day of the period containing `date`.
+    pub(crate) fn start(self, date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Self::Day => Some(date),
+            Self::Week => date.checked_sub_signed(Duration::days(i64::from(
+                date.weekday().num_days_from_monday(),
+            ))),
+            Self::Month => date.with_day(1),
+            Self::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1),
+        }
+    }
+
+    /// First day of the period after the one starting on `start`.
+    pub(crate) fn next_start(self, start: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Self::Day => start.succ_opt(),
+            Self::Week => start.checked_add_signed(Duration::days(7)),
+            Self::Month => start.checked_add_months(Months::new(1)),
+            Self::Year => start.checked_add_months(Months::new(12)),
+        }
+    }
+}
+
+/// Moves `date` forward by `months` and places it on `day`, clamped to the
+/// length of the target month.
+pub(crate) fn add_months_on_day(date: NaiveDate, months: u32, day: u32) -> Option<NaiveDate> {
+    let month_start = date.with_day(1)?.checked_add_months(Months::new(months))?;
+    let last_day = month_start
+        .checked_add_months(Months::new(1))?
+        .pred_opt()?
+        .day();
+    month_start.with_day(day.min(last_day))
+}
@@ -1,11 +1,13 @@
 //! The `Vault` holds the user's wallets and cash flows. The user can have
 //! multiple vaults.
 
+use chrono::{DateTime, Utc};
 use sea_orm::{ActiveValue, prelude::*};
+use serde::Serialize;
 use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::{CashFlow, Currency, Wallet};
+use crate::{CashFlow, Currency, EngineError, ResultEngine, Wallet};
 
 /// Holds wallets and cash flows
 #[derive(Debug)]
@@ -16,6 +18,9 @@ pub struct Vault {
     pub wallet: HashMap<Uuid, Wallet>,
     pub user_id: String,
     pub currency: Currency,
+    /// Transactions that occurred before this instant are closed: they can
+    /// no longer be created, changed or voided.
+    pub books_closed_until: Option<DateTime<Utc>>,
 }
 
 impl Vault {
@@ -27,11 +32,12 @@ impl Vault {
             wallet: HashMap::new(),
             user_id: user_id.to_string(),
             currency: Currency::Eur,
+            books_closed_until: None,
         }
     }
 }
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize)]
 #[sea_orm(table_name = "vaults")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -39,6 +45,7 @@ pub struct Model {
     pub name: String,
     pub user_id: String,
     pub currency: Currency,
+    pub books_closed_until: Option<DateTime<Utc>>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -63,6 +70,21 @@ impl Related<super::wallets::Entity> for Entity {
 
 impl ActiveModelBehavior for ActiveModel {}
 
+impl Model {
+    /// Rejects changes to transactions that occurred in a closed period.
+    pub(crate) fn ensure_period_open(&self, occurred_at: DateTime<Utc>) -> ResultEngine<()> {
+        if let Some(until) = self.books_closed_until
+            && occurred_at < until
+        {
+            return Err(EngineError::BooksClosed(format!(
+                "books are closed until {}",
+                until.to_rfc3339()
+            )));
+        }
+        Ok(())
+    }
+}
+
 impl From<&Vault> for ActiveModel {
     fn from(value: &Vault) -> Self {
         Self {
@@ -72,6 +94,7 @@ impl From<&Vault> for ActiveModel {
             name: ActiveValue::Set(value.name.clone()),
             user_id: ActiveValue::Set(value.user_id.clone()),
             currency: ActiveValue::Set(value.currency),
+            books_closed_until: ActiveValue::Set(value.books_closed_until),
         }
     }
 }
@@ -1,9 +1,10 @@
 //! Vault memberships scaffolding for future sharing.
 
 use sea_orm::entity::prelude::*;
+use serde::Serialize;
 use uuid::Uuid;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "vault_memberships")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
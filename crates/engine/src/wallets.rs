@@ -1,14 +1,18 @@
 //! Wallets.
 
 use sea_orm::entity::{ActiveValue, prelude::*};
+use serde::Serialize;
 use uuid::Uuid;
 
-use crate::{Currency, EngineError, ResultEngine, util::ensure_vault_currency};
+use crate::Currency;
 
 /// A wallet.
 ///
 /// A wallet is a representation of a real wallet, a bank account or anything
 /// else where money are kept. It is not a representation of a credit card.
+///
+/// Wallets are denominated in their own `currency`, which may differ from the
+/// vault currency; `balance` is always expressed in the wallet currency.
 #[derive(Debug)]
 pub struct Wallet {
     /// Stable identifier for this wallet.
@@ -34,7 +38,7 @@ impl Wallet {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "wallets")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -66,19 +70,16 @@ impl Related<super::vault::Entity> for Entity {
 
 impl ActiveModelBehavior for ActiveModel {}
 
-/// Convert a storage model into a domain `Wallet`, validating currency.
-impl TryFrom<(Model, Currency)> for Wallet {
-    type Error = EngineError;
-
-    fn try_from((model, vault_currency): (Model, Currency)) -> ResultEngine<Self> {
-        ensure_vault_currency(vault_currency, model.currency)?;
-        Ok(Self {
+/// Convert a storage model into a domain `Wallet`.
+impl From<Model> for Wallet {
+    fn from(model: Model) -> Self {
+        Self {
             id: model.id,
             name: model.name,
             balance: model.balance,
             currency: model.currency,
             archived: model.archived,
-        })
+        }
     }
 }
 
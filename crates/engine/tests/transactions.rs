@@ -1,21 +1,56 @@
 #![allow(clippy::expect_used, clippy::unwrap_used)]
 
-use chrono::{TimeZone, Utc};
-use sea_orm::{ConnectionTrait, Database, DatabaseConnection, Statement};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, DbBackend, Statement};
 
 use engine::{
-    CategoryMergeConflictKind, Currency, Engine, EngineError, TransactionKind,
-    TransactionListFilter,
+    AuditAction, AuditEntity, CategoryMergeConflictKind, ClearingStatus, Currency, Engine,
+    EngineError, FlowLimit, TransactionKind, TransactionListFilter,
 };
 use migration::MigratorTrait;
+use serde_json::json;
 use uuid::Uuid;
 
+/// Server URL of a Postgres instance to run the tests against instead of an
+/// in-memory SQLite database (e.g. `postgres://postgres@127.0.0.1`).
+const POSTGRES_URL_ENV: &str = "SPARAGNE_TEST_POSTGRES_URL";
+
 async fn engine_with_db() -> (Engine, DatabaseConnection) {
-    let db = Database::connect("sqlite::memory:").await.unwrap();
+    let db = match std::env::var(POSTGRES_URL_ENV) {
+        Ok(server_url) => fresh_postgres_db(&server_url).await,
+        Err(_) => Database::connect("sqlite::memory:").await.unwrap(),
+    };
     migration::Migrator::up(&db, None).await.unwrap();
+    seed_users(&db).await;
+    let engine = Engine::builder()
+        .database(db.clone())
+        .build()
+        .await
+        .unwrap();
+    (engine, db)
+}
+
+/// Creates an empty database on the Postgres server, one per test.
+async fn fresh_postgres_db(server_url: &str) -> DatabaseConnection {
+    let server_url = server_url.trim_end_matches('/');
+    let name = format!("sparagne_test_{}", Uuid::new_v4().simple());
+    let server = Database::connect(format!("{server_url}/postgres"))
+        .await
+        .unwrap();
+    server
+        .execute_unprepared(&format!("CREATE DATABASE {name}"))
+        .await
+        .unwrap();
+    server.close().await.unwrap();
+    Database::connect(format!("{server_url}/{name}"))
+        .await
+        .unwrap()
+}
+
+async fn seed_users(db: &DatabaseConnection) {
     let backend = db.get_database_backend();
     for username in ["alice", "bob", "charlie"] {
-        db.execute(Statement::from_sql_and_values(
+        db.execute(statement(
             backend,
             "INSERT INTO users (username, password) VALUES (?, ?)",
             vec![username.into(), "password".into()],
@@ -23,12 +58,24 @@ async fn engine_with_db() -> (Engine, DatabaseConnection) {
         .await
         .unwrap();
     }
-    let engine = Engine::builder()
-        .database(db.clone())
-        .build()
-        .await
-        .unwrap();
-    (engine, db)
+}
+
+/// Raw statement written with `?` placeholders, numbered for Postgres.
+fn statement(backend: DbBackend, sql: &str, values: Vec<sea_orm::Value>) -> Statement {
+    let sql = match backend {
+        DbBackend::Postgres => {
+            let mut numbered = String::with_capacity(sql.len());
+            for (i, part) in sql.split('?').enumerate() {
+                if i > 0 {
+                    numbered.push_str(&format!("${i}"));
+                }
+                numbered.push_str(part);
+            }
+            numbered
+        }
+        _ => sql.to_string(),
+    };
+    Statement::from_sql_and_values(backend, sql, values)
 }
 
 async fn engine_with_file_db() -> (Engine, DatabaseConnection, String, std::path::PathBuf) {
@@ -40,16 +87,7 @@ async fn engine_with_file_db() -> (Engine, DatabaseConnection, String, std::path
 
     let db = Database::connect(&url).await.unwrap();
     migration::Migrator::up(&db, None).await.unwrap();
-    let backend = db.get_database_backend();
-    for username in ["alice", "bob", "charlie"] {
-        db.execute(Statement::from_sql_and_values(
-            backend,
-            "INSERT INTO users (username, password) VALUES (?, ?)",
-            vec![username.into(), "password".into()],
-        ))
-        .await
-        .unwrap();
-    }
+    seed_users(&db).await;
     let engine = Engine::builder()
         .database(db.clone())
         .build()
@@ -75,6 +113,39 @@ fn unallocated_flow_id(vault: &engine::Vault) -> uuid::Uuid {
         .expect("unallocated flow missing")
 }
 
+/// Vault "Main" of alice in EUR, with the ids most tests start from.
+struct TestVault {
+    engine: Engine,
+    db: DatabaseConnection,
+    vault_id: String,
+    wallet_id: Uuid,
+    unallocated_id: Uuid,
+}
+
+async fn test_vault() -> TestVault {
+    let (engine, db) = engine_with_db().await;
+    let vault_id = engine
+        .new_vault("Main", "alice", Some(Currency::Eur))
+        .await
+        .unwrap();
+    let vault = engine
+        .vault_snapshot(Some(&vault_id), None, "alice")
+        .await
+        .unwrap();
+    TestVault {
+        wallet_id: default_wallet_id(&vault),
+        unallocated_id: unallocated_flow_id(&vault),
+        engine,
+        db,
+        vault_id,
+    }
+}
+
+/// Noon UTC of a day in 2026.
+fn noon(month: u32, day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, month, day, 12, 0, 0).unwrap()
+}
+
 #[tokio::test]
 async fn new_vault_creates_unallocated_and_default_wallet() {
     let (engine, _db) = engine_with_db().await;
@@ -156,6 +227,127 @@ async fn income_expense_void_reverts_balances() {
     assert_eq!(wallet.balance, 1000);
 }
 
+/// Flow capped at 100.
+async fn capped_flow(engine: &Engine, vault_id: &str) -> Uuid {
+    engine
+        .new_cash_flow(vault_id, "Capped", 0, Some(100), None, "alice")
+        .await
+        .unwrap()
+}
+
+fn income_on(
+    vault_id: &str,
+    wallet_id: Uuid,
+    flow_id: Uuid,
+    amount_minor: i64,
+) -> engine::IncomeCmd {
+    engine::IncomeCmd::new(vault_id, "alice", amount_minor, Utc::now())
+        .flow_id(flow_id)
+        .wallet_id(wallet_id)
+}
+
+#[tokio::test]
+async fn unvoid_is_rejected_when_it_would_exceed_a_cap() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let flow_id = capped_flow(&engine, &vault_id).await;
+    let first_id = engine
+        .income(income_on(&vault_id, wallet_id, flow_id, 80))
+        .await
+        .unwrap();
+    engine
+        .void_transaction(&vault_id, first_id, "alice", Utc::now())
+        .await
+        .unwrap();
+    engine
+        .income(income_on(&vault_id, wallet_id, flow_id, 50))
+        .await
+        .unwrap();
+
+    // 50 + 80 would exceed the cap: nothing changes.
+    let err = engine
+        .unvoid_transaction(&vault_id, first_id, "alice")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::MaxBalanceReached(FlowLimit {
+            flow_id,
+            flow_name: "Capped".to_string(),
+            currency: Currency::Eur,
+            limit_minor: 100,
+            attempted_minor: 130,
+        })
+    );
+    let tx = engine
+        .transaction_with_legs(&vault_id, first_id, "alice")
+        .await
+        .unwrap();
+    assert!(tx.voided_at.is_some());
+}
+
+#[tokio::test]
+async fn unvoid_reapplies_legs() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let flow_id = capped_flow(&engine, &vault_id).await;
+    let tx_id = engine
+        .income(income_on(&vault_id, wallet_id, flow_id, 80))
+        .await
+        .unwrap();
+    engine
+        .void_transaction(&vault_id, tx_id, "alice", Utc::now())
+        .await
+        .unwrap();
+
+    engine
+        .unvoid_transaction(&vault_id, tx_id, "alice")
+        .await
+        .unwrap();
+    let tx = engine
+        .transaction_with_legs(&vault_id, tx_id, "alice")
+        .await
+        .unwrap();
+    assert!(tx.voided_at.is_none());
+    assert!(tx.voided_by.is_none());
+    let flow = engine.cash_flow(flow_id, &vault_id, "alice").await.unwrap();
+    assert_eq!(flow.balance, 80);
+    let wallet = engine.wallet(wallet_id, &vault_id, "alice").await.unwrap();
+    assert_eq!(wallet.balance, 80);
+}
+
+#[tokio::test]
+async fn unvoid_requires_a_voided_transaction() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let flow_id = capped_flow(&engine, &vault_id).await;
+    let tx_id = engine
+        .income(income_on(&vault_id, wallet_id, flow_id, 80))
+        .await
+        .unwrap();
+
+    let err = engine
+        .unvoid_transaction(&vault_id, tx_id, "alice")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::InvalidAmount("transaction is not voided".to_string())
+    );
+}
+
 #[tokio::test]
 async fn refund_increases_balances() {
     let (engine, _db) = engine_with_db().await;
@@ -212,6 +404,262 @@ async fn refund_increases_balances() {
     assert_eq!(wallet.balance, 850);
 }
 
+/// Expense of 30 in "clothes" on a Shopping flow funded with 100. Returns
+/// the flow and the expense.
+async fn clothes_expense(engine: &Engine, vault_id: &str, wallet_id: Uuid) -> (Uuid, Uuid) {
+    let flow_id = engine
+        .new_cash_flow(vault_id, "Shopping", 0, None, None, "alice")
+        .await
+        .unwrap();
+    engine
+        .income(
+            engine::IncomeCmd::new(vault_id, "alice", 10_000, Utc::now())
+                .flow_id(flow_id)
+                .wallet_id(wallet_id),
+        )
+        .await
+        .unwrap();
+    let expense_id = engine
+        .expense(
+            engine::ExpenseCmd::new(vault_id, "alice", 3_000, Utc::now())
+                .flow_id(flow_id)
+                .wallet_id(wallet_id)
+                .category("clothes"),
+        )
+        .await
+        .unwrap();
+    (flow_id, expense_id)
+}
+
+fn refund_of(vault_id: &str, expense_id: Uuid, amount_minor: i64) -> engine::RefundCmd {
+    engine::RefundCmd::new(vault_id, "alice", amount_minor, Utc::now())
+        .refunded_transaction_id(expense_id)
+}
+
+#[tokio::test]
+async fn refund_linked_to_expense_defaults_to_its_category_flow_and_wallet() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let (flow_id, expense_id) = clothes_expense(&engine, &vault_id, wallet_id).await;
+
+    let refund_id = engine
+        .refund(refund_of(&vault_id, expense_id, 1_000))
+        .await
+        .unwrap();
+    let refund = engine
+        .transaction_with_legs(&vault_id, refund_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(refund.refunded_transaction_id, Some(expense_id));
+    assert_eq!(refund.category.as_deref(), Some("clothes"));
+    assert!(
+        refund
+            .legs
+            .iter()
+            .any(|leg| leg.target == engine::LegTarget::Flow { flow_id })
+    );
+    assert!(
+        refund
+            .legs
+            .iter()
+            .any(|leg| leg.target == engine::LegTarget::Wallet { wallet_id })
+    );
+}
+
+#[tokio::test]
+async fn linked_refunds_are_capped_at_the_expense() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let (flow_id, expense_id) = clothes_expense(&engine, &vault_id, wallet_id).await;
+    engine
+        .refund(refund_of(&vault_id, expense_id, 1_000))
+        .await
+        .unwrap();
+
+    let err = engine
+        .refund(refund_of(&vault_id, expense_id, 2_500))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::InvalidAmount(
+            "refund exceeds the unrefunded amount of the expense (2000)".to_string()
+        )
+    );
+    engine
+        .refund(refund_of(&vault_id, expense_id, 2_000))
+        .await
+        .unwrap();
+
+    let expense = engine
+        .transaction_with_legs(&vault_id, expense_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(expense.refunded_minor, Some(3_000));
+    let listed = engine
+        .list_transactions_for_flow(
+            &vault_id,
+            flow_id,
+            "alice",
+            10,
+            &TransactionListFilter::default(),
+        )
+        .await
+        .unwrap();
+    let (listed_expense, _) = listed.iter().find(|(tx, _)| tx.id == expense_id).unwrap();
+    assert_eq!(listed_expense.refunded_minor, Some(3_000));
+}
+
+#[tokio::test]
+async fn updates_cannot_refund_more_than_the_expense() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let (_, expense_id) = clothes_expense(&engine, &vault_id, wallet_id).await;
+    let refund_id = engine
+        .refund(refund_of(&vault_id, expense_id, 2_000))
+        .await
+        .unwrap();
+    engine
+        .refund(refund_of(&vault_id, expense_id, 1_000))
+        .await
+        .unwrap();
+
+    // Growing a refund or shrinking the expense past the other is rejected.
+    let update = |transaction_id, amount_minor| {
+        engine::UpdateTransactionCmd::new(&vault_id, transaction_id, "alice")
+            .amount_minor(amount_minor)
+    };
+    assert!(
+        engine
+            .update_transaction(update(refund_id, 2_100))
+            .await
+            .is_err()
+    );
+    assert!(
+        engine
+            .update_transaction(update(expense_id, 2_900))
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn voided_refund_frees_its_share_of_the_expense() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let (_, expense_id) = clothes_expense(&engine, &vault_id, wallet_id).await;
+    engine
+        .refund(refund_of(&vault_id, expense_id, 1_000))
+        .await
+        .unwrap();
+    let voided_id = engine
+        .refund(refund_of(&vault_id, expense_id, 2_000))
+        .await
+        .unwrap();
+
+    engine
+        .void_transaction(&vault_id, voided_id, "alice", Utc::now())
+        .await
+        .unwrap();
+    let expense = engine
+        .transaction_with_legs(&vault_id, expense_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(expense.refunded_minor, Some(1_000));
+
+    // Once the share is taken again, the voided refund no longer fits.
+    engine
+        .refund(refund_of(&vault_id, expense_id, 500))
+        .await
+        .unwrap();
+    assert!(
+        engine
+            .unvoid_transaction(&vault_id, voided_id, "alice")
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn expense_with_live_refunds_cannot_be_voided() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let (_, expense_id) = clothes_expense(&engine, &vault_id, wallet_id).await;
+    let refund_id = engine
+        .refund(refund_of(&vault_id, expense_id, 1_000))
+        .await
+        .unwrap();
+
+    let err = engine
+        .void_transaction(&vault_id, expense_id, "alice", Utc::now())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::InvalidAmount("expense has refunds (1000), void them first".to_string())
+    );
+    // The refund is still linked to a live expense and can change.
+    engine
+        .update_transaction(
+            engine::UpdateTransactionCmd::new(&vault_id, refund_id, "alice").amount_minor(1_500),
+        )
+        .await
+        .unwrap();
+
+    engine
+        .void_transaction(&vault_id, refund_id, "alice", Utc::now())
+        .await
+        .unwrap();
+    engine
+        .void_transaction(&vault_id, expense_id, "alice", Utc::now())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn only_expenses_can_be_refunded() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let (_, expense_id) = clothes_expense(&engine, &vault_id, wallet_id).await;
+    let refund_id = engine
+        .refund(refund_of(&vault_id, expense_id, 1_000))
+        .await
+        .unwrap();
+
+    let err = engine
+        .refund(refund_of(&vault_id, refund_id, 100))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::InvalidAmount("only expenses can be refunded".to_string())
+    );
+}
+
 #[tokio::test]
 async fn transfer_wallet_does_not_touch_flows() {
     let (engine, _db) = engine_with_db().await;
@@ -228,7 +676,7 @@ async fn transfer_wallet_does_not_touch_flows() {
         default_wallet_id(&vault)
     };
     let wallet_bank = engine
-        .new_wallet(&vault_id, "Bank", 0, "alice")
+        .new_wallet(&vault_id, "Bank", None, 0, "alice")
         .await
         .unwrap();
 
@@ -283,16 +731,242 @@ async fn transfer_wallet_does_not_touch_flows() {
 }
 
 #[tokio::test]
-async fn income_capped_counts_transfers_in() {
-    let (engine, _db) = engine_with_db().await;
-    let vault_id = engine
-        .new_vault("Main", "alice", Some(Currency::Eur))
+async fn foreign_wallet_income_requires_an_exchange_rate() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let wallet_chf = engine
+        .new_wallet(&vault_id, "Swiss", Some(Currency::Chf), 0, "alice")
         .await
         .unwrap();
 
-    let wallet_id = {
-        let vault = engine
-            .vault_snapshot(Some(&vault_id), None, "alice")
+    // Without a rate the CHF amount cannot be booked on the EUR flow.
+    let err = engine
+        .income(
+            engine::IncomeCmd::new(&vault_id, "alice", 10_000, Utc::now()).wallet_id(wallet_chf),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::CurrencyMismatch(_)));
+}
+
+#[tokio::test]
+async fn foreign_wallet_income_converts_flow_side_to_vault_currency() {
+    let TestVault {
+        engine,
+        vault_id,
+        unallocated_id,
+        ..
+    } = test_vault().await;
+    let wallet_chf = engine
+        .new_wallet(&vault_id, "Swiss", Some(Currency::Chf), 0, "alice")
+        .await
+        .unwrap();
+
+    let rate = engine::ExchangeRate::parse("1.05").unwrap();
+    let tx_id = engine
+        .income(
+            engine::IncomeCmd::new(&vault_id, "alice", 10_000, Utc::now())
+                .wallet_id(wallet_chf)
+                .exchange_rate(rate),
+        )
+        .await
+        .unwrap();
+
+    let wallet = engine.wallet(wallet_chf, &vault_id, "alice").await.unwrap();
+    assert_eq!(wallet.currency, Currency::Chf);
+    assert_eq!(wallet.balance, 10_000);
+    let flow = engine
+        .cash_flow(unallocated_id, &vault_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(flow.balance, 10_500);
+
+    let tx = engine
+        .transaction_with_legs(&vault_id, tx_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(tx.amount_minor, 10_500);
+    assert_eq!(tx.currency, Currency::Eur);
+    assert_eq!(tx.original_amount_minor, Some(10_000));
+    assert_eq!(tx.original_currency, Some(Currency::Chf));
+    assert_eq!(tx.exchange_rate, Some(rate));
+    for leg in &tx.legs {
+        match leg.target {
+            engine::LegTarget::Wallet { .. } => assert_eq!(leg.currency, Currency::Chf),
+            engine::LegTarget::Flow { .. } => assert_eq!(leg.currency, Currency::Eur),
+        }
+    }
+
+    // Rebuilding balances from legs yields the same result.
+    engine.recompute_balances(&vault_id, "alice").await.unwrap();
+    let wallet = engine.wallet(wallet_chf, &vault_id, "alice").await.unwrap();
+    assert_eq!(wallet.balance, 10_000);
+}
+
+#[tokio::test]
+async fn expense_in_foreign_currency_records_original_amount() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+
+    let rate = engine::ExchangeRate::parse("0.92").unwrap();
+    let tx_id = engine
+        .expense(
+            engine::ExpenseCmd::new(&vault_id, "alice", 50_00, Utc::now())
+                .wallet_id(wallet_id)
+                .currency(Currency::Usd)
+                .exchange_rate(rate),
+        )
+        .await
+        .unwrap();
+
+    let wallet = engine.wallet(wallet_id, &vault_id, "alice").await.unwrap();
+    assert_eq!(wallet.balance, -46_00);
+    let tx = engine
+        .transaction_with_legs(&vault_id, tx_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(tx.amount_minor, 46_00);
+    assert_eq!(tx.original_amount_minor, Some(50_00));
+    assert_eq!(tx.original_currency, Some(Currency::Usd));
+}
+
+#[tokio::test]
+async fn foreign_currency_update_reuses_the_stored_rate() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let tx_id = engine
+        .expense(
+            engine::ExpenseCmd::new(&vault_id, "alice", 50_00, Utc::now())
+                .wallet_id(wallet_id)
+                .currency(Currency::Usd)
+                .exchange_rate(engine::ExchangeRate::parse("0.92").unwrap()),
+        )
+        .await
+        .unwrap();
+
+    // The new amount is in the original currency.
+    engine
+        .update_transaction(
+            engine::UpdateTransactionCmd::new(&vault_id, tx_id, "alice").amount_minor(10_000),
+        )
+        .await
+        .unwrap();
+    let wallet = engine.wallet(wallet_id, &vault_id, "alice").await.unwrap();
+    assert_eq!(wallet.balance, -92_00);
+}
+
+#[tokio::test]
+async fn exchange_rate_on_a_same_currency_transaction_is_rejected() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+
+    let err = engine
+        .expense(
+            engine::ExpenseCmd::new(&vault_id, "alice", 10_00, Utc::now())
+                .wallet_id(wallet_id)
+                .exchange_rate(engine::ExchangeRate::parse("0.92").unwrap()),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::InvalidAmount(_)));
+}
+
+#[tokio::test]
+async fn transfer_between_wallets_in_different_currencies_requires_rate() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let wallet_jpy = engine
+        .new_wallet(&vault_id, "Tokyo", Some(Currency::Jpy), 0, "alice")
+        .await
+        .unwrap();
+
+    let err = engine
+        .transfer_wallet(engine::TransferWalletCmd::new(
+            &vault_id,
+            "alice",
+            10_00,
+            wallet_id,
+            wallet_jpy,
+            Utc::now(),
+        ))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::CurrencyMismatch(_)));
+}
+
+#[tokio::test]
+async fn transfer_between_wallets_in_different_currencies_converts_the_amount() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let wallet_jpy = engine
+        .new_wallet(&vault_id, "Tokyo", Some(Currency::Jpy), 0, "alice")
+        .await
+        .unwrap();
+
+    let tx_id = engine
+        .transfer_wallet(
+            engine::TransferWalletCmd::new(
+                &vault_id,
+                "alice",
+                10_00,
+                wallet_id,
+                wallet_jpy,
+                Utc::now(),
+            )
+            .exchange_rate(engine::ExchangeRate::parse("160").unwrap()),
+        )
+        .await
+        .unwrap();
+
+    let eur = engine.wallet(wallet_id, &vault_id, "alice").await.unwrap();
+    let jpy = engine.wallet(wallet_jpy, &vault_id, "alice").await.unwrap();
+    assert_eq!(eur.balance, -10_00);
+    assert_eq!(jpy.balance, 1600);
+
+    let tx = engine
+        .transaction_with_legs(&vault_id, tx_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(tx.currency, Currency::Eur);
+    assert_eq!(tx.original_currency, Some(Currency::Eur));
+
+    engine.recompute_balances(&vault_id, "alice").await.unwrap();
+    let jpy = engine.wallet(wallet_jpy, &vault_id, "alice").await.unwrap();
+    assert_eq!(jpy.balance, 1600);
+}
+
+#[tokio::test]
+async fn income_capped_counts_transfers_in() {
+    let (engine, _db) = engine_with_db().await;
+    let vault_id = engine
+        .new_vault("Main", "alice", Some(Currency::Eur))
+        .await
+        .unwrap();
+
+    let wallet_id = {
+        let vault = engine
+            .vault_snapshot(Some(&vault_id), None, "alice")
             .await
             .unwrap();
         default_wallet_id(&vault)
@@ -334,7 +1008,16 @@ async fn income_capped_counts_transfers_in() {
         .await
         .unwrap_err();
 
-    assert_eq!(err, EngineError::MaxBalanceReached("Capped".to_string()));
+    assert_eq!(
+        err,
+        EngineError::MaxBalanceReached(FlowLimit {
+            flow_id: capped_flow,
+            flow_name: "Capped".to_string(),
+            currency: Currency::Eur,
+            limit_minor: 500,
+            attempted_minor: 600,
+        })
+    );
 }
 
 #[tokio::test]
@@ -417,7 +1100,7 @@ async fn update_income_can_retarget_wallet_and_flow_and_keeps_metadata_when_omit
         default_wallet_id(&vault)
     };
     let wallet2 = engine
-        .new_wallet(&vault_id, "Bank", 0, "alice")
+        .new_wallet(&vault_id, "Bank", None, 0, "alice")
         .await
         .unwrap();
 
@@ -518,7 +1201,16 @@ async fn update_expense_retarget_flow_fails_if_insufficient_and_is_atomic() {
         )
         .await
         .unwrap_err();
-    assert_eq!(err, EngineError::InsufficientFunds("F2".to_string()));
+    assert_eq!(
+        err,
+        EngineError::InsufficientFunds(FlowLimit {
+            flow_id: flow2,
+            flow_name: "F2".to_string(),
+            currency: Currency::Eur,
+            limit_minor: 0,
+            attempted_minor: -80,
+        })
+    );
 
     // No state change on failure.
     let w1 = engine.wallet(wallet1, &vault_id, "alice").await.unwrap();
@@ -545,11 +1237,11 @@ async fn update_transfer_wallet_can_change_endpoints_and_amount() {
         default_wallet_id(&vault)
     };
     let wallet_bank = engine
-        .new_wallet(&vault_id, "Bank", 0, "alice")
+        .new_wallet(&vault_id, "Bank", None, 0, "alice")
         .await
         .unwrap();
     let wallet_card = engine
-        .new_wallet(&vault_id, "Card", 0, "alice")
+        .new_wallet(&vault_id, "Card", None, 0, "alice")
         .await
         .unwrap();
 
@@ -693,6 +1385,229 @@ async fn update_transfer_flow_can_change_endpoints_and_amount() {
     assert_eq!(f3m.balance, 10);
 }
 
+/// Expense with two revisions: booked as a 30 lunch on F1 (funded with 100),
+/// changed to a 50 dinner, then moved to F2 (funded with 60).
+struct RevisedExpense {
+    flow1: Uuid,
+    flow2: Uuid,
+    expense_id: Uuid,
+}
+
+async fn revised_expense(engine: &Engine, vault_id: &str, wallet_id: Uuid) -> RevisedExpense {
+    let flow1 = engine
+        .new_cash_flow(vault_id, "F1", 0, None, None, "alice")
+        .await
+        .unwrap();
+    let flow2 = engine
+        .new_cash_flow(vault_id, "F2", 0, None, None, "alice")
+        .await
+        .unwrap();
+    for (flow_id, amount_minor) in [(flow1, 100), (flow2, 60)] {
+        engine
+            .income(
+                engine::IncomeCmd::new(vault_id, "alice", amount_minor, Utc::now())
+                    .flow_id(flow_id)
+                    .wallet_id(wallet_id),
+            )
+            .await
+            .unwrap();
+    }
+    let expense_id = engine
+        .expense(
+            engine::ExpenseCmd::new(vault_id, "alice", 30, Utc::now())
+                .flow_id(flow1)
+                .wallet_id(wallet_id)
+                .note("lunch"),
+        )
+        .await
+        .unwrap();
+    engine
+        .update_transaction(
+            engine::UpdateTransactionCmd::new(vault_id, expense_id, "alice")
+                .amount_minor(50)
+                .note("dinner"),
+        )
+        .await
+        .unwrap();
+    engine
+        .update_transaction(
+            engine::UpdateTransactionCmd::new(vault_id, expense_id, "alice").flow_id(flow2),
+        )
+        .await
+        .unwrap();
+    RevisedExpense {
+        flow1,
+        flow2,
+        expense_id,
+    }
+}
+
+#[tokio::test]
+async fn updates_store_revisions_of_the_replaced_state() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let revised = revised_expense(&engine, &vault_id, wallet_id).await;
+
+    let revisions = engine
+        .transaction_revisions(&vault_id, revised.expense_id, "alice")
+        .await
+        .unwrap();
+    let summary: Vec<_> = revisions
+        .iter()
+        .map(|r| (r.revision, r.amount_minor, r.note.as_deref()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![(1, 30, Some("lunch")), (2, 50, Some("dinner"))]
+    );
+    assert_eq!(revisions[0].replaced_by, "alice");
+    assert!(revisions[0].legs.iter().any(|leg| {
+        leg.target
+            == engine::LegTarget::Flow {
+                flow_id: revised.flow1,
+            }
+            && leg.amount_minor == -30
+    }));
+}
+
+#[tokio::test]
+async fn reverting_restores_a_revision_and_keeps_the_replaced_state() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let revised = revised_expense(&engine, &vault_id, wallet_id).await;
+
+    engine
+        .revert_transaction(&vault_id, revised.expense_id, 1, "alice")
+        .await
+        .unwrap();
+    let tx = engine
+        .transaction_with_legs(&vault_id, revised.expense_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(tx.amount_minor, 30);
+    assert_eq!(tx.note.as_deref(), Some("lunch"));
+    assert_eq!(
+        engine
+            .cash_flow(revised.flow1, &vault_id, "alice")
+            .await
+            .unwrap()
+            .balance,
+        70
+    );
+    assert_eq!(
+        engine
+            .cash_flow(revised.flow2, &vault_id, "alice")
+            .await
+            .unwrap()
+            .balance,
+        60
+    );
+    assert_eq!(
+        engine
+            .wallet(wallet_id, &vault_id, "alice")
+            .await
+            .unwrap()
+            .balance,
+        130
+    );
+
+    let revisions = engine
+        .transaction_revisions(&vault_id, revised.expense_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(revisions.len(), 3);
+    assert_eq!(revisions[2].amount_minor, 50);
+}
+
+#[tokio::test]
+async fn revert_goes_through_the_update_balance_checks() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let revised = revised_expense(&engine, &vault_id, wallet_id).await;
+    // Leaves 30 on F1, less than the dinner of revision 2.
+    engine
+        .expense(
+            engine::ExpenseCmd::new(&vault_id, "alice", 70, Utc::now())
+                .flow_id(revised.flow1)
+                .wallet_id(wallet_id),
+        )
+        .await
+        .unwrap();
+
+    let err = engine
+        .revert_transaction(&vault_id, revised.expense_id, 2, "alice")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::InsufficientFunds(FlowLimit {
+            flow_id: revised.flow1,
+            flow_name: "F1".to_string(),
+            currency: Currency::Eur,
+            limit_minor: 0,
+            attempted_minor: -20,
+        })
+    );
+    assert_eq!(
+        engine
+            .transaction_revisions(&vault_id, revised.expense_id, "alice")
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn revert_to_an_unknown_revision_fails() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let revised = revised_expense(&engine, &vault_id, wallet_id).await;
+
+    let err = engine
+        .revert_transaction(&vault_id, revised.expense_id, 9, "alice")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::KeyNotFound("revision not exists".to_string())
+    );
+}
+
+#[tokio::test]
+async fn revisions_are_only_visible_to_vault_members() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let revised = revised_expense(&engine, &vault_id, wallet_id).await;
+
+    assert!(
+        engine
+            .transaction_revisions(&vault_id, revised.expense_id, "bob")
+            .await
+            .is_err()
+    );
+}
+
 #[tokio::test]
 async fn recompute_balances_restores_denormalized_state_and_ignores_voided() {
     let (engine, db) = engine_with_db().await;
@@ -767,8 +1682,8 @@ async fn recompute_balances_restores_denormalized_state_and_ignores_voided() {
         .unwrap();
 
     // Corrupt denormalized balances directly in DB.
-    let values: Vec<sea_orm::Value> = vec![999i64.into(), wallet_cash.as_bytes().to_vec().into()];
-    db.execute(Statement::from_sql_and_values(
+    let values: Vec<sea_orm::Value> = vec![999i64.into(), wallet_cash.into()];
+    db.execute(statement(
         backend,
         "UPDATE wallets SET balance = ? WHERE id = ?;",
         values,
@@ -777,12 +1692,8 @@ async fn recompute_balances_restores_denormalized_state_and_ignores_voided() {
     .unwrap();
     for flow_id in [unallocated_flow, capped_flow, vacanze_flow] {
         if flow_id == capped_flow {
-            let values: Vec<sea_orm::Value> = vec![
-                999i64.into(),
-                0i64.into(),
-                flow_id.as_bytes().to_vec().into(),
-            ];
-            db.execute(Statement::from_sql_and_values(
+            let values: Vec<sea_orm::Value> = vec![999i64.into(), 0i64.into(), flow_id.into()];
+            db.execute(statement(
                 backend,
                 "UPDATE cash_flows SET balance = ?, income_balance = ? WHERE id = ?;",
                 values,
@@ -790,9 +1701,8 @@ async fn recompute_balances_restores_denormalized_state_and_ignores_voided() {
             .await
             .unwrap();
         } else {
-            let values: Vec<sea_orm::Value> =
-                vec![999i64.into(), flow_id.as_bytes().to_vec().into()];
-            db.execute(Statement::from_sql_and_values(
+            let values: Vec<sea_orm::Value> = vec![999i64.into(), flow_id.into()];
+            db.execute(statement(
                 backend,
                 "UPDATE cash_flows SET balance = ?, income_balance = NULL WHERE id = ?;",
                 values,
@@ -836,10 +1746,10 @@ async fn recompute_balances_restores_denormalized_state_and_ignores_voided() {
 
     // Verify DB matches recompute results too.
     let row = db
-        .query_one(Statement::from_sql_and_values(
+        .query_one(statement(
             backend,
             "SELECT balance FROM wallets WHERE id = ?;",
-            vec![wallet_cash.as_bytes().to_vec().into()],
+            vec![wallet_cash.into()],
         ))
         .await
         .unwrap()
@@ -848,44 +1758,475 @@ async fn recompute_balances_restores_denormalized_state_and_ignores_voided() {
     assert_eq!(db_balance, 1000);
 }
 
-#[tokio::test]
-async fn expense_on_flow_without_balance_fails() {
-    let (engine, _db) = engine_with_db().await;
-    let vault_id = engine
-        .new_vault("Main", "alice", Some(Currency::Eur))
+/// Capped flow of 10 with an income of 8 and an expense of 1 on the default
+/// wallet. Returns the flow, the income and the expense.
+async fn capped_spending(engine: &Engine, vault_id: &str, cash: Uuid) -> (Uuid, Uuid, Uuid) {
+    let capped = engine
+        .new_cash_flow(vault_id, "Capped", 0, Some(1000), None, "alice")
         .await
         .unwrap();
-
-    let flow_id = engine
-        .new_cash_flow(&vault_id, "Vacanze", 0, None, None, "alice")
+    let income = engine
+        .income(
+            engine::IncomeCmd::new(vault_id, "alice", 800, noon(3, 1))
+                .flow_id(capped)
+                .wallet_id(cash),
+        )
         .await
         .unwrap();
-    let wallet_id = {
-        let vault = engine
-            .vault_snapshot(Some(&vault_id), None, "alice")
-            .await
-            .unwrap();
-        default_wallet_id(&vault)
-    };
-
-    let err = engine
+    let expense = engine
         .expense(
-            engine::ExpenseCmd::new(&vault_id, "alice", 1, Utc::now())
-                .flow_id(flow_id)
-                .wallet_id(wallet_id)
-                .category("food"),
+            engine::ExpenseCmd::new(vault_id, "alice", 100, Utc::now())
+                .flow_id(capped)
+                .wallet_id(cash),
         )
         .await
-        .unwrap_err();
-    assert_eq!(err, EngineError::InsufficientFunds("Vacanze".to_string()));
+        .unwrap();
+    (capped, income, expense)
 }
 
 #[tokio::test]
-async fn list_transactions_excludes_voided_and_transfers_by_default() {
-    let (engine, _db) = engine_with_db().await;
-    let vault_id = engine
-        .new_vault("Main", "alice", Some(Currency::Eur))
-        .await
+async fn verify_vault_passes_a_consistent_vault() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    capped_spending(&engine, &vault_id, wallet_id).await;
+
+    let report = engine.verify_vault(&vault_id, "alice").await.unwrap();
+    assert!(report.is_clean(), "{:?}", report.issues);
+    assert_eq!(
+        (
+            report.wallets_checked,
+            report.flows_checked,
+            report.legs_checked
+        ),
+        (1, 2, 4)
+    );
+}
+
+#[tokio::test]
+async fn verify_vault_requires_vault_access() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+
+    let err = engine.verify_vault(&vault_id, "bob").await.unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::KeyNotFound("vault not exists".to_string())
+    );
+}
+
+#[tokio::test]
+async fn verify_vault_reports_drift_without_repairing_it() {
+    let TestVault {
+        engine,
+        db,
+        vault_id,
+        wallet_id: cash,
+        ..
+    } = test_vault().await;
+    let backend = db.get_database_backend();
+    let (capped, income, expense) = capped_spending(&engine, &vault_id, cash).await;
+
+    let missing_flow = Uuid::new_v4();
+    for (sql, values) in [
+        (
+            "UPDATE wallets SET balance = ? WHERE id = ?;",
+            vec![999i64.into(), cash.into()],
+        ),
+        (
+            "UPDATE legs SET amount_minor = ? WHERE transaction_id = ? AND target_kind = 'flow';",
+            vec![1200i64.into(), income.into()],
+        ),
+        (
+            "UPDATE legs SET target_id = ? WHERE transaction_id = ? AND target_kind = 'flow';",
+            vec![missing_flow.into(), expense.into()],
+        ),
+        (
+            "UPDATE legs SET currency = 'USD' WHERE transaction_id = ? AND target_kind = 'wallet';",
+            vec![expense.into()],
+        ),
+    ] {
+        db.execute(statement(backend, sql, values)).await.unwrap();
+    }
+
+    let report = engine.verify_vault(&vault_id, "alice").await.unwrap();
+    let leg_id = |transaction_id: Uuid, target_kind: &'static str| {
+        let db = &db;
+        async move {
+            let row = db
+                .query_one(statement(
+                    backend,
+                    "SELECT id FROM legs WHERE transaction_id = ? AND target_kind = ?;",
+                    vec![transaction_id.into(), target_kind.into()],
+                ))
+                .await
+                .unwrap()
+                .unwrap();
+            row.try_get::<Uuid>("", "id").unwrap()
+        }
+    };
+    let mut expected = vec![
+        engine::IntegrityIssue::CapExceeded {
+            transaction_id: income,
+            occurred_at: noon(3, 1),
+            limit: FlowLimit {
+                flow_id: capped,
+                flow_name: "Capped".to_string(),
+                currency: Currency::Eur,
+                limit_minor: 1000,
+                attempted_minor: 1200,
+            },
+        },
+        engine::IntegrityIssue::LegCurrencyMismatch {
+            leg_id: leg_id(expense, "wallet").await,
+            transaction_id: expense,
+            expected: Currency::Eur,
+            found: Currency::Usd,
+        },
+        engine::IntegrityIssue::MissingTarget {
+            leg_id: leg_id(expense, "flow").await,
+            transaction_id: expense,
+            target: engine::LegTarget::Flow {
+                flow_id: missing_flow,
+            },
+        },
+        engine::IntegrityIssue::WalletBalanceMismatch {
+            wallet_id: cash,
+            stored_minor: 999,
+            ledger_minor: 700,
+        },
+        engine::IntegrityIssue::FlowBalanceMismatch {
+            flow_id: capped,
+            stored_minor: 700,
+            ledger_minor: 1200,
+        },
+    ];
+    let mut issues = report.issues;
+    let key = |issue: &engine::IntegrityIssue| issue.to_string();
+    issues.sort_by_key(key);
+    expected.sort_by_key(key);
+    assert_eq!(issues, expected);
+
+    // The check is read-only.
+    let wallet = engine.wallet(cash, &vault_id, "alice").await.unwrap();
+    assert_eq!(wallet.balance, 999);
+}
+
+#[tokio::test]
+async fn expense_on_flow_without_balance_fails() {
+    let (engine, _db) = engine_with_db().await;
+    let vault_id = engine
+        .new_vault("Main", "alice", Some(Currency::Eur))
+        .await
+        .unwrap();
+
+    let flow_id = engine
+        .new_cash_flow(&vault_id, "Vacanze", 0, None, None, "alice")
+        .await
+        .unwrap();
+    let wallet_id = {
+        let vault = engine
+            .vault_snapshot(Some(&vault_id), None, "alice")
+            .await
+            .unwrap();
+        default_wallet_id(&vault)
+    };
+
+    let err = engine
+        .expense(
+            engine::ExpenseCmd::new(&vault_id, "alice", 1, Utc::now())
+                .flow_id(flow_id)
+                .wallet_id(wallet_id)
+                .category("food"),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::InsufficientFunds(FlowLimit {
+            flow_id,
+            flow_name: "Vacanze".to_string(),
+            currency: Currency::Eur,
+            limit_minor: 0,
+            attempted_minor: -1,
+        })
+    );
+}
+
+/// Flow and categories of the split expense tests: 100 on Unallocated and 50
+/// on Casa, funded on March 1st 2026.
+struct Supermarket {
+    home: Uuid,
+    groceries: engine::Category,
+    household: engine::Category,
+}
+
+async fn supermarket(engine: &Engine, vault_id: &str) -> Supermarket {
+    let home = engine
+        .new_cash_flow(vault_id, "Casa", 0, None, None, "alice")
+        .await
+        .unwrap();
+    let groceries = engine
+        .create_category(vault_id, "Groceries", "alice")
+        .await
+        .unwrap();
+    let household = engine
+        .create_category(vault_id, "Household", "alice")
+        .await
+        .unwrap();
+    let funded_at = Utc.with_ymd_and_hms(2026, 3, 1, 9, 0, 0).unwrap();
+    engine
+        .income(engine::IncomeCmd::new(vault_id, "alice", 10_000, funded_at))
+        .await
+        .unwrap();
+    engine
+        .income(engine::IncomeCmd::new(vault_id, "alice", 5_000, funded_at).flow_id(home))
+        .await
+        .unwrap();
+    Supermarket {
+        home,
+        groceries,
+        household,
+    }
+}
+
+impl Supermarket {
+    /// Groceries on Unallocated, two lines on Casa.
+    fn lines(&self) -> Vec<engine::SplitLine> {
+        vec![
+            engine::SplitLine::new(4_000).category_id(self.groceries.id),
+            engine::SplitLine::new(2_500)
+                .flow_id(self.home)
+                .category_id(self.household.id)
+                .note("detergent"),
+            engine::SplitLine::new(1_500)
+                .flow_id(self.home)
+                .category("Gifts")
+                .note("birthday"),
+        ]
+    }
+}
+
+fn supermarket_split(
+    vault_id: &str,
+    wallet_id: Uuid,
+    lines: Vec<engine::SplitLine>,
+    total_minor: i64,
+) -> engine::SplitExpenseCmd {
+    engine::SplitExpenseCmd::new(vault_id, "alice", total_minor, noon(3, 10), lines)
+        .wallet_id(wallet_id)
+        .note("Supermarket")
+}
+
+#[tokio::test]
+async fn split_expense_lines_must_add_up_to_the_total() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let shop = supermarket(&engine, &vault_id).await;
+
+    let err = engine
+        .split_expense(supermarket_split(&vault_id, wallet_id, shop.lines(), 8_100))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::InvalidAmount(_)));
+    // A single line is not a split.
+    let err = engine
+        .split_expense(supermarket_split(
+            &vault_id,
+            wallet_id,
+            vec![engine::SplitLine::new(8_000)],
+            8_000,
+        ))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::InvalidAmount(_)));
+}
+
+#[tokio::test]
+async fn split_expense_books_one_wallet_leg_and_a_flow_leg_per_line() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        unallocated_id,
+        ..
+    } = test_vault().await;
+    let shop = supermarket(&engine, &vault_id).await;
+
+    let tx_id = engine
+        .split_expense(supermarket_split(&vault_id, wallet_id, shop.lines(), 8_000))
+        .await
+        .unwrap();
+    let tx = engine
+        .transaction_with_legs(&vault_id, tx_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(tx.kind, TransactionKind::Expense);
+    assert_eq!(tx.amount_minor, 8_000);
+    assert_eq!(tx.category_id, shop.groceries.id);
+    assert_eq!(tx.legs.len(), 4);
+    let notes: Vec<_> = tx
+        .legs
+        .iter()
+        .filter_map(|leg| leg.note.as_deref())
+        .collect();
+    assert_eq!(notes, ["detergent", "birthday"]);
+
+    let wallet = engine.wallet(wallet_id, &vault_id, "alice").await.unwrap();
+    assert_eq!(wallet.balance, 7_000);
+    let flow = engine
+        .cash_flow(unallocated_id, &vault_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(flow.balance, 6_000);
+    let flow = engine
+        .cash_flow(shop.home, &vault_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(flow.balance, 1_000);
+}
+
+#[tokio::test]
+async fn split_expense_lists_once_per_flow() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let shop = supermarket(&engine, &vault_id).await;
+    let tx_id = engine
+        .split_expense(supermarket_split(&vault_id, wallet_id, shop.lines(), 8_000))
+        .await
+        .unwrap();
+
+    // Both Casa lines show as one transaction, next to the funding income.
+    let (items, _) = engine
+        .list_transactions_for_flow_page(
+            &vault_id,
+            shop.home,
+            "alice",
+            10,
+            None,
+            &TransactionListFilter::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].0.id, tx_id);
+    assert_eq!(items[0].1, -4_000);
+    assert_eq!(items[0].0.legs.len(), 4);
+    assert!(items[1].0.legs.is_empty());
+}
+
+#[tokio::test]
+async fn budgets_count_each_split_line_in_its_category() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let shop = supermarket(&engine, &vault_id).await;
+    engine
+        .split_expense(supermarket_split(&vault_id, wallet_id, shop.lines(), 8_000))
+        .await
+        .unwrap();
+
+    let budget = engine
+        .create_budget(engine::CreateBudgetCmd::new(
+            &vault_id,
+            "alice",
+            shop.household.id,
+            10_000,
+            engine::BudgetPeriod::Monthly,
+            chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+        ))
+        .await
+        .unwrap();
+    let status = engine
+        .budget_status(&vault_id, budget.id, "alice", noon(3, 10).date_naive())
+        .await
+        .unwrap();
+    assert_eq!(status.spent_minor, 2_500);
+}
+
+#[tokio::test]
+async fn split_expense_amount_is_fixed_by_its_lines() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let shop = supermarket(&engine, &vault_id).await;
+    let tx_id = engine
+        .split_expense(supermarket_split(&vault_id, wallet_id, shop.lines(), 8_000))
+        .await
+        .unwrap();
+
+    let err = engine
+        .update_transaction(
+            engine::UpdateTransactionCmd::new(&vault_id, tx_id, "alice").amount_minor(9_000),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::InvalidAmount(_)));
+    engine
+        .update_transaction(
+            engine::UpdateTransactionCmd::new(&vault_id, tx_id, "alice").note("Market"),
+        )
+        .await
+        .unwrap();
+    let tx = engine
+        .transaction_with_legs(&vault_id, tx_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(tx.note.as_deref(), Some("Market"));
+    assert_eq!(tx.legs.len(), 4);
+}
+
+#[tokio::test]
+async fn voiding_a_split_expense_restores_every_flow() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let shop = supermarket(&engine, &vault_id).await;
+    let tx_id = engine
+        .split_expense(supermarket_split(&vault_id, wallet_id, shop.lines(), 8_000))
+        .await
+        .unwrap();
+
+    engine
+        .void_transaction(&vault_id, tx_id, "alice", Utc::now())
+        .await
+        .unwrap();
+    let wallet = engine.wallet(wallet_id, &vault_id, "alice").await.unwrap();
+    assert_eq!(wallet.balance, 15_000);
+    let flow = engine
+        .cash_flow(shop.home, &vault_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(flow.balance, 5_000);
+}
+
+#[tokio::test]
+async fn list_transactions_excludes_voided_and_transfers_by_default() {
+    let (engine, _db) = engine_with_db().await;
+    let vault_id = engine
+        .new_vault("Main", "alice", Some(Currency::Eur))
+        .await
         .unwrap();
     let wallet_id = {
         let vault = engine
@@ -919,7 +2260,7 @@ async fn list_transactions_excludes_voided_and_transfers_by_default() {
 
     // Transfers should be excluded when include_transfers=false.
     let other_wallet = engine
-        .new_wallet(&vault_id, "Bank", 0, "alice")
+        .new_wallet(&vault_id, "Bank", None, 0, "alice")
         .await
         .unwrap();
     engine
@@ -1140,12 +2481,12 @@ async fn names_are_trimmed_and_unique_case_insensitive() {
     assert_eq!(err, EngineError::ExistingKey("main".to_string()));
 
     let wallet_id = engine
-        .new_wallet(&vault_id, "  Bank  ", 0, "alice")
+        .new_wallet(&vault_id, "  Bank  ", None, 0, "alice")
         .await
         .unwrap();
 
     let err = engine
-        .new_wallet(&vault_id, "bank", 0, "alice")
+        .new_wallet(&vault_id, "bank", None, 0, "alice")
         .await
         .unwrap_err();
     assert_eq!(err, EngineError::ExistingKey("bank".to_string()));
@@ -1169,7 +2510,7 @@ async fn names_are_trimmed_and_unique_case_insensitive() {
 
     // Empty names are rejected.
     let err = engine
-        .new_wallet(&vault_id, "   ", 0, "alice")
+        .new_wallet(&vault_id, "   ", None, 0, "alice")
         .await
         .unwrap_err();
     assert_eq!(
@@ -1195,10 +2536,10 @@ async fn names_are_trimmed_and_unique_case_insensitive() {
         unallocated_flow_id(&vault)
     };
 
-    db.execute(Statement::from_sql_and_values(
+    db.execute(statement(
         backend,
         "UPDATE cash_flows SET income_balance = 0, max_balance = NULL WHERE id = ?;",
-        vec![unallocated_id.as_bytes().to_vec().into()],
+        vec![unallocated_id.into()],
     ))
     .await
     .unwrap();
@@ -1623,6 +2964,61 @@ async fn merge_category_moves_transactions_and_aliases() {
     assert!(txs.iter().all(|(tx, _)| tx.category_id == spese.id));
 }
 
+#[tokio::test]
+async fn merging_a_category_keeps_revisions_of_the_moved_transactions() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let food = engine
+        .create_category(&vault_id, "Food", "alice")
+        .await
+        .unwrap();
+    let spese = engine
+        .create_category(&vault_id, "Spese", "alice")
+        .await
+        .unwrap();
+    let expense_id = engine
+        .expense(engine::ExpenseCmd::new(&vault_id, "alice", 12, Utc::now()).category_id(food.id))
+        .await
+        .unwrap();
+    // Only the second line is on Food.
+    let split_id = engine
+        .split_expense(engine::SplitExpenseCmd::new(
+            &vault_id,
+            "alice",
+            30,
+            Utc::now(),
+            vec![
+                engine::SplitLine::new(20).category_id(spese.id),
+                engine::SplitLine::new(10).category_id(food.id),
+            ],
+        ))
+        .await
+        .unwrap();
+    let untouched_id = engine
+        .expense(engine::ExpenseCmd::new(&vault_id, "alice", 5, Utc::now()).category_id(spese.id))
+        .await
+        .unwrap();
+
+    engine
+        .merge_category(&vault_id, food.id, spese.id, "alice")
+        .await
+        .unwrap();
+    let revisions = |tx_id| engine.transaction_revisions(&vault_id, tx_id, "alice");
+    let expense_revisions = revisions(expense_id).await.unwrap();
+    assert_eq!(expense_revisions.len(), 1);
+    assert_eq!(expense_revisions[0].category_id, food.id);
+    let split_revisions = revisions(split_id).await.unwrap();
+    assert_eq!(split_revisions.len(), 1);
+    assert!(
+        split_revisions[0]
+            .legs
+            .iter()
+            .any(|leg| leg.category_id == Some(food.id))
+    );
+    assert!(revisions(untouched_id).await.unwrap().is_empty());
+}
+
 #[tokio::test]
 async fn preview_merge_reports_conflicts() {
     let (engine, _db) = engine_with_db().await;
@@ -1657,54 +3053,454 @@ async fn preview_merge_reports_conflicts() {
     );
 }
 
-#[tokio::test]
-async fn list_transactions_can_filter_by_date_range_and_kinds() {
-    let (engine, _db) = engine_with_db().await;
-    let vault_id = engine
-        .new_vault("Main", "alice", Some(Currency::Eur))
-        .await
-        .unwrap();
-    let wallet_id = {
-        let vault = engine
-            .vault_snapshot(Some(&vault_id), None, "alice")
-            .await
-            .unwrap();
-        default_wallet_id(&vault)
-    };
-
-    let t0 = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap();
-    let t1 = Utc.with_ymd_and_hms(2025, 1, 2, 10, 0, 0).unwrap();
-    let t2 = Utc.with_ymd_and_hms(2025, 1, 3, 10, 0, 0).unwrap();
+/// Categories and rules shared by the category rule tests.
+struct GroceryRules {
+    /// Esselunga or Conad expenses on the default wallet, priority 1.
+    groceries: engine::Category,
+    grocery_rule: engine::CategoryRule,
+    /// Expenses of 500 or more, priority 2.
+    big: engine::Category,
+}
 
-    let id0 = engine
-        .income(engine::IncomeCmd::new(&vault_id, "alice", 10, t0).wallet_id(wallet_id))
+async fn grocery_rules(engine: &Engine, vault_id: &str, wallet_id: Uuid) -> GroceryRules {
+    let groceries = engine
+        .create_category(vault_id, "Groceries", "alice")
         .await
         .unwrap();
-    let id1 = engine
-        .expense(engine::ExpenseCmd::new(&vault_id, "alice", 5, t1).wallet_id(wallet_id))
+    let big = engine
+        .create_category(vault_id, "Big spending", "alice")
         .await
         .unwrap();
-    let id2 = engine
-        .refund(engine::RefundCmd::new(&vault_id, "alice", 2, t2).wallet_id(wallet_id))
+    let grocery_rule = engine
+        .create_category_rule(
+            engine::CreateCategoryRuleCmd::new(
+                vault_id,
+                "alice",
+                groceries.id,
+                engine::CategoryRuleConditions {
+                    note_regex: Some("(?i)esselunga|conad".to_string()),
+                    wallet_id: Some(wallet_id),
+                    kind: Some(TransactionKind::Expense),
+                    ..Default::default()
+                },
+            )
+            .priority(1),
+        )
         .await
         .unwrap();
-    let _ = (id0, id1, id2);
-
-    // [t1, t2) includes only the expense at t1.
-    let filter = TransactionListFilter {
-        from: Some(t1),
-        to: Some(t2),
-        include_transfers: true,
-        ..Default::default()
-    };
-    let txs = engine
-        .list_transactions_for_wallet(&vault_id, wallet_id, "alice", 50, &filter)
+    engine
+        .create_category_rule(
+            engine::CreateCategoryRuleCmd::new(
+                vault_id,
+                "alice",
+                big.id,
+                engine::CategoryRuleConditions {
+                    min_amount_minor: Some(50_000),
+                    kind: Some(TransactionKind::Expense),
+                    ..Default::default()
+                },
+            )
+            .priority(2),
+        )
         .await
         .unwrap();
-    assert_eq!(txs.len(), 1);
-    assert_eq!(txs[0].0.kind, TransactionKind::Expense);
+    GroceryRules {
+        groceries,
+        grocery_rule,
+        big,
+    }
+}
 
-    // Kinds allow-list.
+/// Books an expense of alice with `note` and no category.
+async fn noted_expense(engine: &Engine, vault_id: &str, amount_minor: i64, note: &str) -> Uuid {
+    engine
+        .expense(engine::ExpenseCmd::new(vault_id, "alice", amount_minor, Utc::now()).note(note))
+        .await
+        .unwrap()
+}
+
+async fn category_of(engine: &Engine, vault_id: &str, tx_id: Uuid) -> Uuid {
+    engine
+        .transaction_with_legs(vault_id, tx_id, "alice")
+        .await
+        .unwrap()
+        .category_id
+}
+
+#[tokio::test]
+async fn category_rules_categorize_new_transactions_by_priority() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let rules = grocery_rules(&engine, &vault_id, wallet_id).await;
+
+    let conad = noted_expense(&engine, &vault_id, 2_500, "Conad City").await;
+    assert_eq!(
+        category_of(&engine, &vault_id, conad).await,
+        rules.groceries.id
+    );
+    let tv = noted_expense(&engine, &vault_id, 60_000, "TV").await;
+    assert_eq!(category_of(&engine, &vault_id, tv).await, rules.big.id);
+    let big_groceries = noted_expense(&engine, &vault_id, 60_000, "Esselunga").await;
+    assert_eq!(
+        category_of(&engine, &vault_id, big_groceries).await,
+        rules.groceries.id
+    );
+}
+
+#[tokio::test]
+async fn explicit_category_wins_over_category_rules() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let rules = grocery_rules(&engine, &vault_id, wallet_id).await;
+
+    let explicit = engine
+        .expense(
+            engine::ExpenseCmd::new(&vault_id, "alice", 1_000, Utc::now())
+                .note("Esselunga")
+                .category("Gifts"),
+        )
+        .await
+        .unwrap();
+    assert_ne!(
+        category_of(&engine, &vault_id, explicit).await,
+        rules.groceries.id
+    );
+}
+
+#[tokio::test]
+async fn viewers_cannot_manage_or_apply_category_rules() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let rules = grocery_rules(&engine, &vault_id, wallet_id).await;
+    engine
+        .upsert_vault_member(&vault_id, "charlie", "viewer", "alice")
+        .await
+        .unwrap();
+
+    let err = engine
+        .create_category_rule(engine::CreateCategoryRuleCmd::new(
+            &vault_id,
+            "charlie",
+            rules.groceries.id,
+            engine::CategoryRuleConditions::default(),
+        ))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::KeyNotFound(_)));
+    let err = engine
+        .apply_category_rules(engine::ApplyCategoryRulesCmd::new(&vault_id, "charlie"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::KeyNotFound(_)));
+
+    // Reading them is fine.
+    let listed = engine
+        .list_category_rules(&vault_id, "charlie")
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 2);
+}
+
+#[tokio::test]
+async fn invalid_category_rules_are_rejected() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let groceries = engine
+        .create_category(&vault_id, "Groceries", "alice")
+        .await
+        .unwrap();
+
+    let err = engine
+        .create_category_rule(engine::CreateCategoryRuleCmd::new(
+            &vault_id,
+            "alice",
+            groceries.id,
+            engine::CategoryRuleConditions {
+                note_regex: Some("(".to_string()),
+                ..Default::default()
+            },
+        ))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::InvalidRule(_)));
+}
+
+#[tokio::test]
+async fn category_rules_categorize_imported_rows() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let rules = grocery_rules(&engine, &vault_id, wallet_id).await;
+
+    let csv = "date,amount,description\n2026-01-05,-12.30,CONAD 123\n";
+    let mapping = engine::CsvMapping {
+        delimiter: ',',
+        has_headers: true,
+        date_column: engine::CsvColumn::Name("date".to_string()),
+        date_format: "%Y-%m-%d".to_string(),
+        amount: engine::CsvAmount::Signed {
+            column: engine::CsvColumn::Name("amount".to_string()),
+        },
+        decimal_separator: '.',
+        note_column: Some(engine::CsvColumn::Name("description".to_string())),
+        category_column: None,
+    };
+
+    // The preview shows the category already.
+    let preview = engine
+        .preview_csv_import(engine::CsvImportCmd::new(
+            &vault_id,
+            "alice",
+            mapping.clone(),
+            csv,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(
+        preview.rows[0].draft.meta().category_id,
+        Some(rules.groceries.id)
+    );
+    let report = engine
+        .import_csv(engine::CsvImportCmd::new(&vault_id, "alice", mapping, csv))
+        .await
+        .unwrap();
+    assert_eq!(
+        category_of(&engine, &vault_id, report.created[0]).await,
+        rules.groceries.id
+    );
+}
+
+#[tokio::test]
+async fn category_rules_preview_lists_old_transactions_without_touching_them() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let old_id = noted_expense(&engine, &vault_id, 4_000, "ESSELUNGA").await;
+    let rules = grocery_rules(&engine, &vault_id, wallet_id).await;
+
+    let hits = engine
+        .preview_category_rules(engine::ApplyCategoryRulesCmd::new(&vault_id, "alice"))
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].transaction_id, old_id);
+    assert_eq!(hits[0].rule_id, rules.grocery_rule.id);
+    assert_ne!(
+        category_of(&engine, &vault_id, old_id).await,
+        rules.groceries.id
+    );
+}
+
+#[tokio::test]
+async fn applying_category_rules_categorizes_old_transactions() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let old_id = noted_expense(&engine, &vault_id, 4_000, "ESSELUNGA").await;
+    grocery_rules(&engine, &vault_id, wallet_id).await;
+    let apply = || engine::ApplyCategoryRulesCmd::new(&vault_id, "alice");
+
+    let hits = engine.preview_category_rules(apply()).await.unwrap();
+    let applied = engine.apply_category_rules(apply()).await.unwrap();
+    assert_eq!(applied, hits);
+    let tx = engine
+        .transaction_with_legs(&vault_id, old_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(tx.category.as_deref(), Some("Groceries"));
+    assert!(
+        engine
+            .preview_category_rules(apply())
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn applied_category_rules_are_kept_as_revisions() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let old_id = noted_expense(&engine, &vault_id, 4_000, "ESSELUNGA").await;
+    let rules = grocery_rules(&engine, &vault_id, wallet_id).await;
+    let uncategorized = category_of(&engine, &vault_id, old_id).await;
+
+    engine
+        .apply_category_rules(engine::ApplyCategoryRulesCmd::new(&vault_id, "alice"))
+        .await
+        .unwrap();
+    let revisions = engine
+        .transaction_revisions(&vault_id, old_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0].category_id, uncategorized);
+
+    engine
+        .revert_transaction(&vault_id, old_id, 1, "alice")
+        .await
+        .unwrap();
+    assert_ne!(
+        category_of(&engine, &vault_id, old_id).await,
+        rules.groceries.id
+    );
+}
+
+#[tokio::test]
+async fn reapplying_category_rules_only_reports_real_changes() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    grocery_rules(&engine, &vault_id, wallet_id).await;
+    noted_expense(&engine, &vault_id, 2_500, "Conad City").await;
+    let explicit = engine
+        .expense(
+            engine::ExpenseCmd::new(&vault_id, "alice", 1_000, Utc::now())
+                .note("Esselunga")
+                .category("Gifts"),
+        )
+        .await
+        .unwrap();
+
+    let hits = engine
+        .preview_category_rules(
+            engine::ApplyCategoryRulesCmd::new(&vault_id, "alice").include_categorized(true),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        hits.iter()
+            .map(|hit| hit.transaction_id)
+            .collect::<Vec<_>>(),
+        vec![explicit]
+    );
+}
+
+#[tokio::test]
+async fn deleted_category_rule_is_no_longer_listed() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let rules = grocery_rules(&engine, &vault_id, wallet_id).await;
+
+    engine
+        .delete_category_rule(&vault_id, rules.grocery_rule.id, "alice")
+        .await
+        .unwrap();
+    let listed = engine
+        .list_category_rules(&vault_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].category_id, rules.big.id);
+}
+
+#[tokio::test]
+async fn category_rules_follow_a_merged_category() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let rules = grocery_rules(&engine, &vault_id, wallet_id).await;
+    let large = engine
+        .create_category(&vault_id, "Large purchases", "alice")
+        .await
+        .unwrap();
+
+    engine
+        .merge_category(&vault_id, rules.big.id, large.id, "alice")
+        .await
+        .unwrap();
+    let listed = engine
+        .list_category_rules(&vault_id, "alice")
+        .await
+        .unwrap();
+    assert!(listed.iter().any(|rule| rule.category_id == large.id));
+    assert!(listed.iter().all(|rule| rule.category_id != rules.big.id));
+}
+
+#[tokio::test]
+async fn list_transactions_can_filter_by_date_range_and_kinds() {
+    let (engine, _db) = engine_with_db().await;
+    let vault_id = engine
+        .new_vault("Main", "alice", Some(Currency::Eur))
+        .await
+        .unwrap();
+    let wallet_id = {
+        let vault = engine
+            .vault_snapshot(Some(&vault_id), None, "alice")
+            .await
+            .unwrap();
+        default_wallet_id(&vault)
+    };
+
+    let t0 = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap();
+    let t1 = Utc.with_ymd_and_hms(2025, 1, 2, 10, 0, 0).unwrap();
+    let t2 = Utc.with_ymd_and_hms(2025, 1, 3, 10, 0, 0).unwrap();
+
+    let id0 = engine
+        .income(engine::IncomeCmd::new(&vault_id, "alice", 10, t0).wallet_id(wallet_id))
+        .await
+        .unwrap();
+    let id1 = engine
+        .expense(engine::ExpenseCmd::new(&vault_id, "alice", 5, t1).wallet_id(wallet_id))
+        .await
+        .unwrap();
+    let id2 = engine
+        .refund(engine::RefundCmd::new(&vault_id, "alice", 2, t2).wallet_id(wallet_id))
+        .await
+        .unwrap();
+    let _ = (id0, id1, id2);
+
+    // [t1, t2) includes only the expense at t1.
+    let filter = TransactionListFilter {
+        from: Some(t1),
+        to: Some(t2),
+        include_transfers: true,
+        ..Default::default()
+    };
+    let txs = engine
+        .list_transactions_for_wallet(&vault_id, wallet_id, "alice", 50, &filter)
+        .await
+        .unwrap();
+    assert_eq!(txs.len(), 1);
+    assert_eq!(txs[0].0.kind, TransactionKind::Expense);
+
+    // Kinds allow-list.
     let filter = TransactionListFilter {
         kinds: Some(vec![TransactionKind::Income]),
         include_transfers: true,
@@ -1827,91 +3623,520 @@ async fn vault_statistics_treats_refunds_as_expense_reduction() {
     assert_eq!(total_expenses_minor, 250);
 }
 
-#[tokio::test]
-async fn flow_membership_allows_reading_flow_without_vault_access() {
-    let (engine, db) = engine_with_db().await;
-    let backend = db.get_database_backend();
-
-    let vault_id = engine
-        .new_vault("Main", "alice", Some(Currency::Eur))
+/// Books the transactions of the report tests, mostly on a Bank wallet:
+/// January salary and groceries, a February split expense shared with Casa, a
+/// refund and a voided expense. Returns the Casa flow.
+async fn book_report_transactions(engine: &Engine, vault_id: &str, unallocated: Uuid) -> Uuid {
+    let bank = engine
+        .new_wallet(vault_id, "Bank", None, 0, "alice")
         .await
         .unwrap();
-
-    let flow_id = engine
-        .new_cash_flow(&vault_id, "Shared", 0, None, None, "alice")
+    let home = engine
+        .new_cash_flow(vault_id, "Casa", 0, None, None, "alice")
         .await
         .unwrap();
 
-    // "bob" is not a vault member, but is a flow viewer.
-    db.execute(Statement::from_sql_and_values(
-        backend,
-        "INSERT INTO flow_memberships (flow_id, user_id, role) VALUES (?, ?, ?);",
-        vec![
-            flow_id.as_bytes().to_vec().into(),
-            "bob".into(),
-            "viewer".into(),
-        ],
-    ))
-    .await
-    .unwrap();
-
-    let shared = engine.cash_flow(flow_id, &vault_id, "bob").await.unwrap();
-    assert_eq!(shared.name, "Shared");
-
-    let err = engine
-        .vault_snapshot(Some(&vault_id), None, "bob")
+    engine
+        .income(
+            engine::IncomeCmd::new(vault_id, "alice", 200_000, noon(1, 2))
+                .wallet_id(bank)
+                .category("Salary"),
+        )
         .await
-        .unwrap_err();
-    assert_eq!(
-        err,
-        EngineError::KeyNotFound("vault not exists".to_string())
-    );
-}
-
-#[tokio::test]
-async fn flow_member_cannot_access_transaction_detail() {
-    let (engine, db) = engine_with_db().await;
-    let backend = db.get_database_backend();
-
-    let vault_id = engine
-        .new_vault("Main", "alice", Some(Currency::Eur))
+        .unwrap();
+    engine
+        .transfer_flow(engine::TransferFlowCmd::new(
+            vault_id,
+            "alice",
+            50_000,
+            unallocated,
+            home,
+            noon(1, 3),
+        ))
         .await
         .unwrap();
-
-    let flow_id = engine
-        .new_cash_flow(&vault_id, "Shared", 0, None, None, "alice")
+    engine
+        .expense(
+            engine::ExpenseCmd::new(vault_id, "alice", 12_000, noon(1, 10))
+                .wallet_id(bank)
+                .category("Groceries"),
+        )
         .await
         .unwrap();
-
-    let wallet_id = {
-        let vault = engine
-            .vault_snapshot(Some(&vault_id), None, "alice")
-            .await
-            .unwrap();
-        default_wallet_id(&vault)
-    };
-
-    let tx_id = engine
-        .income(
-            engine::IncomeCmd::new(&vault_id, "alice", 100, Utc::now())
-                .flow_id(flow_id)
-                .wallet_id(wallet_id),
+    engine
+        .split_expense(
+            engine::SplitExpenseCmd::new(
+                vault_id,
+                "alice",
+                9_000,
+                noon(2, 4),
+                vec![
+                    engine::SplitLine::new(6_000).category("Groceries"),
+                    engine::SplitLine::new(3_000)
+                        .flow_id(home)
+                        .category("Household"),
+                ],
+            )
+            .wallet_id(bank),
         )
         .await
         .unwrap();
-
-    // "bob" is not a vault member, but is a flow viewer.
-    db.execute(Statement::from_sql_and_values(
-        backend,
-        "INSERT INTO flow_memberships (flow_id, user_id, role) VALUES (?, ?, ?);",
-        vec![
-            flow_id.as_bytes().to_vec().into(),
-            "bob".into(),
-            "viewer".into(),
-        ],
-    ))
-    .await
-    .unwrap();
+    engine
+        .refund(
+            engine::RefundCmd::new(vault_id, "alice", 1_000, noon(2, 6))
+                .wallet_id(bank)
+                .category("Groceries"),
+        )
+        .await
+        .unwrap();
+    let voided = engine
+        .expense(
+            engine::ExpenseCmd::new(vault_id, "alice", 5_000, noon(2, 7))
+                .wallet_id(bank)
+                .category("Groceries"),
+        )
+        .await
+        .unwrap();
+    engine
+        .void_transaction(vault_id, voided, "alice", Utc::now())
+        .await
+        .unwrap();
+    home
+}
+
+#[tokio::test]
+async fn report_groups_by_month_and_category() {
+    let TestVault {
+        engine,
+        vault_id,
+        unallocated_id,
+        ..
+    } = test_vault().await;
+    book_report_transactions(&engine, &vault_id, unallocated_id).await;
+
+    let report = engine
+        .report(engine::ReportCmd::new(
+            &vault_id,
+            "alice",
+            vec![
+                engine::ReportGroupBy::Month,
+                engine::ReportGroupBy::Category,
+            ],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(report.currency, Currency::Eur);
+    let rows: Vec<_> = report
+        .rows
+        .iter()
+        .map(|row| {
+            (
+                row.period_start.unwrap().to_string(),
+                row.category.clone().unwrap(),
+                row.income_minor,
+                row.expense_minor,
+                row.refund_minor,
+                row.transactions,
+            )
+        })
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            (
+                "2026-01-01".to_string(),
+                "Groceries".to_string(),
+                0,
+                12_000,
+                0,
+                1
+            ),
+            (
+                "2026-01-01".to_string(),
+                "Salary".to_string(),
+                200_000,
+                0,
+                0,
+                1
+            ),
+            (
+                "2026-02-01".to_string(),
+                "Groceries".to_string(),
+                0,
+                6_000,
+                1_000,
+                2
+            ),
+            (
+                "2026-02-01".to_string(),
+                "Household".to_string(),
+                0,
+                3_000,
+                0,
+                1
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn report_groups_by_flow_and_wallet_within_filters() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        unallocated_id,
+        ..
+    } = test_vault().await;
+    book_report_transactions(&engine, &vault_id, unallocated_id).await;
+
+    let report = engine
+        .report(
+            engine::ReportCmd::new(
+                &vault_id,
+                "alice",
+                vec![engine::ReportGroupBy::Flow, engine::ReportGroupBy::Wallet],
+            )
+            .from(noon(2, 1))
+            .kinds(vec![TransactionKind::Expense]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(report.rows.len(), 2);
+    assert_eq!(report.rows[0].flow_id, Some(unallocated_id));
+    assert_eq!(report.rows[0].wallet.as_deref(), Some("Bank"));
+    assert_eq!(report.rows[0].expense_minor, 6_000);
+    assert_eq!(report.rows[1].flow.as_deref(), Some("Casa"));
+    assert_eq!(report.rows[1].expense_minor, 3_000);
+    assert!(
+        report
+            .rows
+            .iter()
+            .all(|row| row.wallet_id != Some(wallet_id))
+    );
+}
+
+#[tokio::test]
+async fn report_without_grouping_totals_the_vault() {
+    let TestVault {
+        engine,
+        vault_id,
+        unallocated_id,
+        ..
+    } = test_vault().await;
+    book_report_transactions(&engine, &vault_id, unallocated_id).await;
+
+    let total = engine
+        .report(engine::ReportCmd::new(&vault_id, "alice", Vec::new()))
+        .await
+        .unwrap();
+    assert_eq!(total.rows.len(), 1);
+    assert_eq!(total.rows[0].net_minor(), 200_000 - 12_000 - 9_000 + 1_000);
+    assert_eq!(total.rows[0].transactions, 4);
+}
+
+#[tokio::test]
+async fn report_rejects_transfers_and_non_members() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+
+    let err = engine
+        .report(
+            engine::ReportCmd::new(&vault_id, "alice", Vec::new())
+                .kinds(vec![TransactionKind::TransferFlow]),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::InvalidAmount(_)));
+    let err = engine
+        .report(engine::ReportCmd::new(&vault_id, "bob", Vec::new()))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::KeyNotFound(_)));
+}
+
+/// Books the history of the balance tests: 100 income on January 5th 2026,
+/// 40 moved to Vacanze on January 20th, 15 spent from it on February 10th
+/// and a voided expense on February 12th. Returns the Vacanze flow.
+async fn vacation_history(
+    engine: &Engine,
+    vault_id: &str,
+    wallet_id: Uuid,
+    unallocated: Uuid,
+) -> Uuid {
+    let flow_id = engine
+        .new_cash_flow(vault_id, "Vacanze", 0, None, None, "alice")
+        .await
+        .unwrap();
+    engine
+        .income(engine::IncomeCmd::new(
+            vault_id,
+            "alice",
+            10_000,
+            noon(1, 5),
+        ))
+        .await
+        .unwrap();
+    engine
+        .transfer_flow(engine::TransferFlowCmd::new(
+            vault_id,
+            "alice",
+            4_000,
+            unallocated,
+            flow_id,
+            noon(1, 20),
+        ))
+        .await
+        .unwrap();
+    engine
+        .expense(
+            engine::ExpenseCmd::new(vault_id, "alice", 1_500, noon(2, 10))
+                .flow_id(flow_id)
+                .wallet_id(wallet_id),
+        )
+        .await
+        .unwrap();
+    let voided = engine
+        .expense(engine::ExpenseCmd::new(vault_id, "alice", 700, noon(2, 12)))
+        .await
+        .unwrap();
+    engine
+        .void_transaction(vault_id, voided, "alice", noon(2, 13))
+        .await
+        .unwrap();
+    flow_id
+}
+
+async fn balance_on(
+    engine: &Engine,
+    vault_id: &str,
+    target: engine::BalanceTarget,
+    at: DateTime<Utc>,
+) -> i64 {
+    engine
+        .balance_at(vault_id, target, at, "alice")
+        .await
+        .unwrap()
+        .balance_minor
+}
+
+#[tokio::test]
+async fn balance_at_counts_transactions_before_the_date() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        unallocated_id,
+        ..
+    } = test_vault().await;
+    let flow_id = vacation_history(&engine, &vault_id, wallet_id, unallocated_id).await;
+
+    let vault = engine::BalanceTarget::Vault;
+    let wallet = engine::BalanceTarget::Wallet { wallet_id };
+    let flow = engine::BalanceTarget::Flow { flow_id };
+    assert_eq!(balance_on(&engine, &vault_id, vault, noon(1, 5)).await, 0);
+    assert_eq!(
+        balance_on(&engine, &vault_id, vault, noon(1, 6)).await,
+        10_000
+    );
+    assert_eq!(
+        balance_on(&engine, &vault_id, flow, noon(1, 31)).await,
+        4_000
+    );
+    assert_eq!(
+        balance_on(&engine, &vault_id, wallet, noon(3, 1)).await,
+        8_500
+    );
+    assert_eq!(
+        balance_on(&engine, &vault_id, flow, noon(3, 1)).await,
+        2_500
+    );
+}
+
+#[tokio::test]
+async fn balance_history_has_a_point_per_month() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        unallocated_id,
+        ..
+    } = test_vault().await;
+    let flow_id = vacation_history(&engine, &vault_id, wallet_id, unallocated_id).await;
+
+    let history = engine
+        .balance_history(engine::BalanceHistoryCmd::new(
+            &vault_id,
+            "alice",
+            engine::BalanceTarget::Flow { flow_id },
+            noon(1, 1),
+            noon(3, 1),
+            engine::BalanceInterval::Month,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(history.currency, Currency::Eur);
+    assert_eq!(history.opening_minor, 0);
+    let points: Vec<i64> = history
+        .points
+        .iter()
+        .map(|point| point.balance_minor)
+        .collect();
+    assert_eq!(points, [4_000, 2_500, 2_500]);
+}
+
+#[tokio::test]
+async fn balance_history_has_a_point_per_day() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        unallocated_id,
+        ..
+    } = test_vault().await;
+    vacation_history(&engine, &vault_id, wallet_id, unallocated_id).await;
+
+    let history = engine
+        .balance_history(engine::BalanceHistoryCmd::new(
+            &vault_id,
+            "alice",
+            engine::BalanceTarget::Vault,
+            noon(2, 9),
+            noon(2, 12),
+            engine::BalanceInterval::Day,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(history.opening_minor, 10_000);
+    let points: Vec<(u32, i64)> = history
+        .points
+        .iter()
+        .map(|point| (point.period_start.day(), point.balance_minor))
+        .collect();
+    assert_eq!(points, [(9, 10_000), (10, 8_500), (11, 8_500), (12, 8_500)]);
+}
+
+#[tokio::test]
+async fn flow_members_read_the_flow_balance_only() {
+    let TestVault {
+        engine,
+        db,
+        vault_id,
+        wallet_id,
+        unallocated_id,
+    } = test_vault().await;
+    let flow_id = vacation_history(&engine, &vault_id, wallet_id, unallocated_id).await;
+    db.execute(statement(
+        db.get_database_backend(),
+        "INSERT INTO flow_memberships (flow_id, user_id, role) VALUES (?, ?, ?);",
+        vec![flow_id.into(), "bob".into(), "viewer".into()],
+    ))
+    .await
+    .unwrap();
+
+    let shared = engine
+        .balance_at(
+            &vault_id,
+            engine::BalanceTarget::Flow { flow_id },
+            noon(3, 1),
+            "bob",
+        )
+        .await
+        .unwrap();
+    assert_eq!(shared.balance_minor, 2_500);
+    let err = engine
+        .balance_at(
+            &vault_id,
+            engine::BalanceTarget::Wallet { wallet_id },
+            noon(3, 1),
+            "bob",
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::KeyNotFound("vault not exists".to_string())
+    );
+}
+
+#[tokio::test]
+async fn flow_membership_allows_reading_flow_without_vault_access() {
+    let (engine, db) = engine_with_db().await;
+    let backend = db.get_database_backend();
+
+    let vault_id = engine
+        .new_vault("Main", "alice", Some(Currency::Eur))
+        .await
+        .unwrap();
+
+    let flow_id = engine
+        .new_cash_flow(&vault_id, "Shared", 0, None, None, "alice")
+        .await
+        .unwrap();
+
+    // "bob" is not a vault member, but is a flow viewer.
+    db.execute(statement(
+        backend,
+        "INSERT INTO flow_memberships (flow_id, user_id, role) VALUES (?, ?, ?);",
+        vec![flow_id.into(), "bob".into(), "viewer".into()],
+    ))
+    .await
+    .unwrap();
+
+    let shared = engine.cash_flow(flow_id, &vault_id, "bob").await.unwrap();
+    assert_eq!(shared.name, "Shared");
+
+    let err = engine
+        .vault_snapshot(Some(&vault_id), None, "bob")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::KeyNotFound("vault not exists".to_string())
+    );
+}
+
+#[tokio::test]
+async fn flow_member_cannot_access_transaction_detail() {
+    let (engine, db) = engine_with_db().await;
+    let backend = db.get_database_backend();
+
+    let vault_id = engine
+        .new_vault("Main", "alice", Some(Currency::Eur))
+        .await
+        .unwrap();
+
+    let flow_id = engine
+        .new_cash_flow(&vault_id, "Shared", 0, None, None, "alice")
+        .await
+        .unwrap();
+
+    let wallet_id = {
+        let vault = engine
+            .vault_snapshot(Some(&vault_id), None, "alice")
+            .await
+            .unwrap();
+        default_wallet_id(&vault)
+    };
+
+    let tx_id = engine
+        .income(
+            engine::IncomeCmd::new(&vault_id, "alice", 100, Utc::now())
+                .flow_id(flow_id)
+                .wallet_id(wallet_id),
+        )
+        .await
+        .unwrap();
+
+    // "bob" is not a vault member, but is a flow viewer.
+    db.execute(statement(
+        backend,
+        "INSERT INTO flow_memberships (flow_id, user_id, role) VALUES (?, ?, ?);",
+        vec![flow_id.into(), "bob".into(), "viewer".into()],
+    ))
+    .await
+    .unwrap();
 
     let err = engine
         .transaction_with_legs(&vault_id, tx_id, "bob")
@@ -1940,14 +4165,10 @@ async fn flow_membership_editor_can_transfer_between_shared_flows_without_vault_
         .unwrap();
 
     for fid in [f1, f2] {
-        db.execute(Statement::from_sql_and_values(
+        db.execute(statement(
             backend,
             "INSERT INTO flow_memberships (flow_id, user_id, role) VALUES (?, ?, ?);",
-            vec![
-                fid.as_bytes().to_vec().into(),
-                "bob".into(),
-                "editor".into(),
-            ],
+            vec![fid.into(), "bob".into(), "editor".into()],
         ))
         .await
         .unwrap();
@@ -1956,16 +4177,15 @@ async fn flow_membership_editor_can_transfer_between_shared_flows_without_vault_
     // Allocate funds via owner first (Unallocated -> F1).
     let vault_uuid = Uuid::parse_str(&vault_id).unwrap();
     let unallocated_row = db
-        .query_one(Statement::from_sql_and_values(
+        .query_one(statement(
             backend,
             "SELECT id FROM cash_flows WHERE vault_id = ? AND system_kind = 'unallocated';",
-            vec![vault_uuid.as_bytes().to_vec().into()],
+            vec![vault_uuid.into()],
         ))
         .await
         .unwrap()
         .unwrap();
-    let unallocated_id_bytes: Vec<u8> = unallocated_row.try_get("", "id").unwrap();
-    let unallocated_id = Uuid::from_slice(&unallocated_id_bytes).unwrap();
+    let unallocated_id: Uuid = unallocated_row.try_get("", "id").unwrap();
 
     engine
         .transfer_flow(
@@ -2112,3 +4332,2121 @@ async fn vault_owner_can_manage_flow_members_and_unallocated_is_not_shareable()
         EngineError::InvalidFlow("cannot share Unallocated".to_string())
     );
 }
+
+/// Write token of alice limited to `vault_id`, expiring in 30 days.
+async fn ci_token(engine: &Engine, vault_id: &str) -> (engine::AccessToken, String) {
+    engine
+        .create_access_token(
+            engine::CreateAccessTokenCmd::new("alice", " ci ", engine::TokenScope::Write)
+                .vault_id(vault_id.to_string())
+                .expires_at(Utc::now() + chrono::Duration::days(30)),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn access_tokens_store_only_a_digest() {
+    let TestVault {
+        engine,
+        db,
+        vault_id,
+        ..
+    } = test_vault().await;
+    let (token, secret) = ci_token(&engine, &vault_id).await;
+    assert_eq!(token.name, "ci");
+    assert!(token.allows(true, Some(&vault_id)));
+
+    let stored = db
+        .query_one(statement(
+            db.get_database_backend(),
+            "SELECT token_hash FROM access_tokens WHERE id = ?",
+            vec![token.id.into()],
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let token_hash: String = stored.try_get("", "token_hash").unwrap();
+    assert_ne!(token_hash, secret);
+    assert!(!token_hash.contains(&secret));
+}
+
+#[tokio::test]
+async fn access_tokens_authenticate_only_with_the_exact_secret() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let (token, secret) = ci_token(&engine, &vault_id).await;
+
+    let found = engine.authenticate_access_token(&secret).await.unwrap();
+    assert_eq!(found, Some(token));
+    assert_eq!(
+        engine
+            .authenticate_access_token(&secret[..secret.len() - 1])
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn access_tokens_are_limited_to_accessible_vaults_and_future_expiry() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+
+    let err = engine
+        .create_access_token(
+            engine::CreateAccessTokenCmd::new("bob", "ci", engine::TokenScope::Read)
+                .vault_id(vault_id.clone()),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        EngineError::Forbidden(_) | EngineError::KeyNotFound(_)
+    ));
+    let err = engine
+        .create_access_token(
+            engine::CreateAccessTokenCmd::new("alice", "old", engine::TokenScope::Read)
+                .expires_at(Utc::now() - chrono::Duration::minutes(1)),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::InvalidAmount(_)));
+}
+
+#[tokio::test]
+async fn only_the_owner_lists_and_revokes_an_access_token() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let (token, secret) = ci_token(&engine, &vault_id).await;
+
+    assert!(engine.list_access_tokens("bob").await.unwrap().is_empty());
+    let err = engine
+        .revoke_access_token(token.id, "bob")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::KeyNotFound("token not exists".to_string())
+    );
+    assert_eq!(
+        engine.list_access_tokens("alice").await.unwrap(),
+        vec![token.clone()]
+    );
+    engine.revoke_access_token(token.id, "alice").await.unwrap();
+    assert_eq!(
+        engine.authenticate_access_token(&secret).await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn recurring_rule_catches_up_missed_occurrences_once() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+
+    let starts_at = Utc.with_ymd_and_hms(2025, 1, 31, 9, 0, 0).unwrap();
+    let rule = engine
+        .create_recurring_rule(
+            engine::CreateRecurringRuleCmd::new(
+                &vault_id,
+                "alice",
+                TransactionKind::Expense,
+                12_00,
+                engine::Recurrence::new(engine::RecurrenceFrequency::Monthly, starts_at)
+                    .max_occurrences(12),
+            )
+            .category("rent"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(rule.wallet_id, Some(wallet_id));
+    assert_eq!(rule.next_occurrence_at, Some(starts_at));
+
+    // Jan 31, Feb 28, Mar 31, Apr 30 are due.
+    let now = Utc.with_ymd_and_hms(2025, 5, 1, 0, 0, 0).unwrap();
+    let report = engine.run_recurring_rules(now).await.unwrap();
+    assert_eq!(report.posted, 4);
+    assert!(report.failures.is_empty());
+
+    // A second run at the same instant posts nothing new.
+    let report = engine.run_recurring_rules(now).await.unwrap();
+    assert_eq!(report.posted, 0);
+
+    let wallet = engine.wallet(wallet_id, &vault_id, "alice").await.unwrap();
+    assert_eq!(wallet.balance, -48_00);
+
+    let txs = engine
+        .list_transactions_for_wallet(
+            &vault_id,
+            wallet_id,
+            "alice",
+            10,
+            &TransactionListFilter::default(),
+        )
+        .await
+        .unwrap();
+    let mut dates: Vec<_> = txs.iter().map(|(tx, _)| tx.occurred_at).collect();
+    dates.sort();
+    assert_eq!(
+        dates,
+        vec![
+            starts_at,
+            Utc.with_ymd_and_hms(2025, 2, 28, 9, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2025, 3, 31, 9, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2025, 4, 30, 9, 0, 0).unwrap(),
+        ]
+    );
+    assert!(
+        txs.iter()
+            .all(|(tx, _)| tx.category.as_deref() == Some("rent"))
+    );
+
+    let rule = engine
+        .recurring_rule(&vault_id, rule.id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(rule.occurrences, 4);
+    assert_eq!(
+        rule.next_occurrence_at,
+        Some(Utc.with_ymd_and_hms(2025, 5, 31, 9, 0, 0).unwrap())
+    );
+}
+
+/// Weekly income rule of alice that started three weeks ago.
+async fn weekly_income_rule(engine: &Engine, vault_id: &str) -> engine::RecurringRule {
+    let starts_at = Utc::now() - chrono::Duration::weeks(3);
+    engine
+        .create_recurring_rule(engine::CreateRecurringRuleCmd::new(
+            vault_id,
+            "alice",
+            TransactionKind::Income,
+            1_00,
+            engine::Recurrence::new(engine::RecurrenceFrequency::Weekly, starts_at),
+        ))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn recurring_rule_stops_after_max_occurrences() {
+    let TestVault {
+        engine,
+        vault_id,
+        unallocated_id,
+        ..
+    } = test_vault().await;
+    let savings = engine
+        .new_cash_flow(&vault_id, "Savings", 0, None, None, "alice")
+        .await
+        .unwrap();
+    engine
+        .income(engine::IncomeCmd::new(
+            &vault_id,
+            "alice",
+            10_000,
+            Utc::now(),
+        ))
+        .await
+        .unwrap();
+
+    let starts_at = Utc::now() - chrono::Duration::weeks(3);
+    let rule = engine
+        .create_recurring_rule(
+            engine::CreateRecurringRuleCmd::new(
+                &vault_id,
+                "alice",
+                TransactionKind::TransferFlow,
+                5_00,
+                engine::Recurrence::new(engine::RecurrenceFrequency::Weekly, starts_at)
+                    .max_occurrences(2),
+            )
+            .from_flow_id(unallocated_id)
+            .to_flow_id(savings),
+        )
+        .await
+        .unwrap();
+
+    let report = engine.run_recurring_rules(Utc::now()).await.unwrap();
+    assert_eq!(report.posted, 2);
+    let flow = engine.cash_flow(savings, &vault_id, "alice").await.unwrap();
+    assert_eq!(flow.balance, 10_00);
+    let rule = engine
+        .recurring_rule(&vault_id, rule.id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(rule.next_occurrence_at, None);
+}
+
+#[tokio::test]
+async fn resumed_recurring_rule_does_not_backfill_the_pause() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let rule = weekly_income_rule(&engine, &vault_id).await;
+
+    engine
+        .update_recurring_rule(
+            engine::UpdateRecurringRuleCmd::new(&vault_id, rule.id, "alice").active(false),
+        )
+        .await
+        .unwrap();
+    let report = engine.run_recurring_rules(Utc::now()).await.unwrap();
+    assert_eq!(report.posted, 0);
+
+    let rule = engine
+        .update_recurring_rule(
+            engine::UpdateRecurringRuleCmd::new(&vault_id, rule.id, "alice").active(true),
+        )
+        .await
+        .unwrap();
+    assert!(rule.next_occurrence_at.is_some_and(|at| at >= Utc::now()));
+    let report = engine.run_recurring_rules(Utc::now()).await.unwrap();
+    assert_eq!(report.posted, 0);
+}
+
+#[tokio::test]
+async fn recurring_rule_end_date_can_be_removed() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let rule = weekly_income_rule(&engine, &vault_id).await;
+    let update = || engine::UpdateRecurringRuleCmd::new(&vault_id, rule.id, "alice");
+
+    // The schedule ends after its first occurrence.
+    let ends_at = rule.recurrence.starts_at + chrono::Duration::days(1);
+    let ended = engine
+        .update_recurring_rule(update().ends_at(ends_at))
+        .await
+        .unwrap();
+    assert_eq!(ended.recurrence.ends_at, Some(ends_at));
+    let report = engine.run_recurring_rules(Utc::now()).await.unwrap();
+    assert_eq!(report.posted, 1);
+
+    let reopened = engine
+        .update_recurring_rule(update().clear_ends_at())
+        .await
+        .unwrap();
+    assert_eq!(reopened.recurrence.ends_at, None);
+    assert!(reopened.next_occurrence_at.is_some());
+    let report = engine.run_recurring_rules(Utc::now()).await.unwrap();
+    assert!(report.posted > 0);
+    let stored = engine
+        .recurring_rule(&vault_id, rule.id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(stored.recurrence.ends_at, None);
+}
+
+#[tokio::test]
+async fn recurring_rule_failing_for_good_is_paused_with_its_error() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let rule = weekly_income_rule(&engine, &vault_id).await;
+    let closed_until = rule.recurrence.starts_at + chrono::Duration::days(1);
+    engine
+        .set_books_closed_until(&vault_id, Some(closed_until), "alice")
+        .await
+        .unwrap();
+
+    let report = engine.run_recurring_rules(Utc::now()).await.unwrap();
+    assert_eq!(report.posted, 0);
+    assert_eq!(report.failures.len(), 1);
+    let paused = engine
+        .recurring_rule(&vault_id, rule.id, "alice")
+        .await
+        .unwrap();
+    assert!(!paused.active);
+    assert_eq!(paused.occurrences, 0);
+    assert!(
+        paused
+            .last_error
+            .is_some_and(|error| error.starts_with("Books closed:"))
+    );
+
+    // The next run leaves the paused rule alone.
+    let report = engine.run_recurring_rules(Utc::now()).await.unwrap();
+    assert_eq!(report.posted, 0);
+    assert!(report.failures.is_empty());
+
+    // Resuming skips the failed occurrence and clears the error.
+    let resumed = engine
+        .update_recurring_rule(
+            engine::UpdateRecurringRuleCmd::new(&vault_id, rule.id, "alice").active(true),
+        )
+        .await
+        .unwrap();
+    assert!(resumed.active);
+    assert_eq!(resumed.last_error, None);
+    assert!(
+        resumed
+            .next_occurrence_at
+            .is_some_and(|at| at >= Utc::now())
+    );
+}
+
+#[tokio::test]
+async fn recurring_rule_on_a_foreign_currency_wallet_is_rejected() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let swiss = engine
+        .new_wallet(&vault_id, "Swiss", Some(Currency::Chf), 0, "alice")
+        .await
+        .unwrap();
+    let cmd = || {
+        engine::CreateRecurringRuleCmd::new(
+            &vault_id,
+            "alice",
+            TransactionKind::Expense,
+            12_00,
+            engine::Recurrence::new(engine::RecurrenceFrequency::Monthly, Utc::now()),
+        )
+    };
+
+    let err = engine
+        .create_recurring_rule(cmd().wallet_id(swiss))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::CurrencyMismatch(_)));
+
+    let rule = engine
+        .create_recurring_rule(cmd().wallet_id(wallet_id))
+        .await
+        .unwrap();
+    let err = engine
+        .update_recurring_rule(
+            engine::UpdateRecurringRuleCmd::new(&vault_id, rule.id, "alice").wallet_id(swiss),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::CurrencyMismatch(_)));
+}
+
+#[tokio::test]
+async fn recurring_rules_are_only_visible_to_vault_members() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    weekly_income_rule(&engine, &vault_id).await;
+
+    let err = engine
+        .list_recurring_rules(&vault_id, "bob")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::KeyNotFound(_)));
+}
+
+#[tokio::test]
+async fn deleted_recurring_rule_is_no_longer_listed() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let kept = weekly_income_rule(&engine, &vault_id).await;
+    let deleted = weekly_income_rule(&engine, &vault_id).await;
+
+    engine
+        .delete_recurring_rule(&vault_id, deleted.id, "alice")
+        .await
+        .unwrap();
+    let rules = engine
+        .list_recurring_rules(&vault_id, "alice")
+        .await
+        .unwrap();
+    assert_eq!(
+        rules.iter().map(|rule| rule.id).collect::<Vec<_>>(),
+        vec![kept.id]
+    );
+}
+
+/// Groceries budget of 400 a month from January 2026, with 150 spent on
+/// groceries in January and a net 60 in February (a voided expense and a
+/// refund included), plus 99 spent on Fun.
+async fn groceries_budget(
+    engine: &Engine,
+    vault_id: &str,
+) -> (engine::Category, engine::Category, engine::Budget) {
+    let groceries = engine
+        .create_category(vault_id, "Groceries", "alice")
+        .await
+        .unwrap();
+    let other = engine
+        .create_category(vault_id, "Fun", "alice")
+        .await
+        .unwrap();
+    engine
+        .income(engine::IncomeCmd::new(
+            vault_id,
+            "alice",
+            500_000,
+            Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+        ))
+        .await
+        .unwrap();
+
+    for (amount, at, category) in [
+        (12_000, noon(1, 10), groceries.id),
+        (30_00, noon(1, 20), groceries.id),
+        (99_00, noon(1, 21), other.id),
+        (80_00, noon(2, 3), groceries.id),
+    ] {
+        engine
+            .expense(engine::ExpenseCmd::new(vault_id, "alice", amount, at).category_id(category))
+            .await
+            .unwrap();
+    }
+    let voided = engine
+        .expense(
+            engine::ExpenseCmd::new(vault_id, "alice", 100_000, noon(2, 4))
+                .category_id(groceries.id),
+        )
+        .await
+        .unwrap();
+    engine
+        .void_transaction(vault_id, voided, "alice", Utc::now())
+        .await
+        .unwrap();
+    engine
+        .refund(
+            engine::RefundCmd::new(vault_id, "alice", 20_00, noon(2, 5)).category_id(groceries.id),
+        )
+        .await
+        .unwrap();
+
+    let budget = engine
+        .create_budget(engine::CreateBudgetCmd::new(
+            vault_id,
+            "alice",
+            groceries.id,
+            40_000,
+            engine::BudgetPeriod::Monthly,
+            chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+        ))
+        .await
+        .unwrap();
+    (groceries, other, budget)
+}
+
+#[tokio::test]
+async fn budget_reports_spent_vs_limit_in_the_current_period() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let (_, _, budget) = groceries_budget(&engine, &vault_id).await;
+
+    let feb_10 = chrono::NaiveDate::from_ymd_opt(2026, 2, 10).unwrap();
+    let status = engine
+        .budget_status(&vault_id, budget.id, "alice", feb_10)
+        .await
+        .unwrap();
+    assert_eq!(
+        status.period_start,
+        chrono::NaiveDate::from_ymd_opt(2026, 2, 1).unwrap()
+    );
+    assert_eq!(status.spent_minor, 60_00);
+    assert_eq!(status.carried_minor, 0);
+    assert_eq!(status.remaining_minor, 34_000);
+}
+
+#[tokio::test]
+async fn budget_rollover_carries_the_previous_leftover() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let (_, _, budget) = groceries_budget(&engine, &vault_id).await;
+
+    // January's 250 leftover is carried into February.
+    engine
+        .update_budget(engine::UpdateBudgetCmd::new(&vault_id, budget.id, "alice").rollover(true))
+        .await
+        .unwrap();
+    let feb_10 = chrono::NaiveDate::from_ymd_opt(2026, 2, 10).unwrap();
+    let statuses = engine
+        .budget_statuses(&vault_id, "alice", feb_10)
+        .await
+        .unwrap();
+    assert_eq!(statuses.len(), 1);
+    assert_eq!(statuses[0].carried_minor, 25_000);
+    assert_eq!(statuses[0].available_minor, 65_000);
+    assert_eq!(statuses[0].remaining_minor, 59_000);
+}
+
+#[tokio::test]
+async fn category_has_at_most_one_budget() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let (groceries, _, _) = groceries_budget(&engine, &vault_id).await;
+
+    let err = engine
+        .create_budget(engine::CreateBudgetCmd::new(
+            &vault_id,
+            "alice",
+            groceries.id,
+            10_000,
+            engine::BudgetPeriod::Weekly,
+            chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+        ))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::ExistingKey(_)));
+}
+
+#[tokio::test]
+async fn merging_a_category_moves_its_budget() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let (groceries, other, _) = groceries_budget(&engine, &vault_id).await;
+
+    engine
+        .merge_category(&vault_id, groceries.id, other.id, "alice")
+        .await
+        .unwrap();
+    let budgets = engine.list_budgets(&vault_id, "alice").await.unwrap();
+    assert_eq!(budgets[0].category_id, other.id);
+}
+
+#[tokio::test]
+async fn deleted_budget_is_no_longer_listed() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let (_, _, budget) = groceries_budget(&engine, &vault_id).await;
+
+    engine
+        .delete_budget(&vault_id, budget.id, "alice")
+        .await
+        .unwrap();
+    assert!(
+        engine
+            .list_budgets(&vault_id, "alice")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+/// Bank CSV export with debit and credit columns and Italian number format.
+const CSV_STATEMENT: &str = "Date;Description;Out;In\n\
+                             02/03/2026;coffee ;3,50;\n\
+                             03/03/2026;Salary;;1.200,00\n\
+                             04/03/2026;Lunch;12,00;\n";
+
+fn csv_mapping() -> engine::CsvMapping {
+    serde_json::from_str(
+        r#"{
+            "delimiter": ";",
+            "date_column": "Date",
+            "date_format": "%d/%m/%Y",
+            "amount": { "mode": "debit_credit", "debit": "Out", "credit": "In" },
+            "decimal_separator": ",",
+            "note_column": "Description"
+        }"#,
+    )
+    .unwrap()
+}
+
+/// Books the coffee of the first [`CSV_STATEMENT`] row.
+async fn book_coffee(engine: &Engine, vault_id: &str) -> Uuid {
+    engine
+        .expense(
+            engine::ExpenseCmd::new(
+                vault_id,
+                "alice",
+                3_50,
+                Utc.with_ymd_and_hms(2026, 3, 2, 8, 30, 0).unwrap(),
+            )
+            .note("Coffee"),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn csv_import_preview_flags_duplicates() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let booked = book_coffee(&engine, &vault_id).await;
+
+    let preview = engine
+        .preview_csv_import(engine::CsvImportCmd::new(
+            &vault_id,
+            "alice",
+            csv_mapping(),
+            CSV_STATEMENT,
+        ))
+        .await
+        .unwrap();
+    assert!(preview.errors.is_empty());
+    assert_eq!(preview.currency, Currency::Eur);
+    let rows: Vec<_> = preview
+        .rows
+        .iter()
+        .map(|row| (row.line, row.draft.signed_amount_minor(), row.duplicate_of))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            (2, -3_50, Some(booked)),
+            (3, 120_000, None),
+            (4, -12_00, None)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn csv_import_skips_duplicates_and_reimport_books_nothing() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    book_coffee(&engine, &vault_id).await;
+    let cmd = || engine::CsvImportCmd::new(&vault_id, "alice", csv_mapping(), CSV_STATEMENT);
+
+    let report = engine.import_csv(cmd()).await.unwrap();
+    assert_eq!(report.created.len(), 2);
+    assert_eq!(report.skipped_duplicates, 1);
+
+    let report = engine.import_csv(cmd()).await.unwrap();
+    assert!(report.created.is_empty());
+    assert_eq!(report.skipped_duplicates, 3);
+
+    let wallet = engine.wallet(wallet_id, &vault_id, "alice").await.unwrap();
+    assert_eq!(wallet.balance, 120_000 - 3_50 - 12_00);
+}
+
+#[tokio::test]
+async fn csv_import_with_a_malformed_row_books_nothing() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+
+    let broken = "Date;Description;Out;In\n\
+                  05/03/2026;Bakery;2,00;\n\
+                  not a date;Cinema;9,00;\n";
+    let err = engine
+        .import_csv(engine::CsvImportCmd::new(
+            &vault_id,
+            "alice",
+            csv_mapping(),
+            broken,
+        ))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::InvalidAmount(msg) if msg.starts_with("line 3:")));
+
+    let listed = engine
+        .list_transactions_for_wallet(
+            &vault_id,
+            wallet_id,
+            "alice",
+            10,
+            &TransactionListFilter::default(),
+        )
+        .await
+        .unwrap();
+    assert!(listed.is_empty());
+}
+
+#[tokio::test]
+async fn viewers_cannot_import_csv() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    engine
+        .upsert_vault_member(&vault_id, "bob", "viewer", "alice")
+        .await
+        .unwrap();
+
+    let err = engine
+        .preview_csv_import(engine::CsvImportCmd::new(
+            &vault_id,
+            "bob",
+            csv_mapping(),
+            CSV_STATEMENT,
+        ))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::KeyNotFound(_)));
+}
+
+fn ofx_entry(fitid: &str, date: &str, amount: &str, name: &str) -> String {
+    format!(
+        "<STMTTRN><TRNTYPE>OTHER<DTPOSTED>{date}<TRNAMT>{amount}<FITID>{fitid}<NAME>{name}</STMTTRN>\n"
+    )
+}
+
+fn ofx_statement(entries: &[String]) -> String {
+    format!(
+        "OFXHEADER:100\n\n<OFX><BANKMSGSRSV1><STMTTRNRS><STMTRS><CURDEF>EUR<BANKTRANLIST>\n{}</BANKTRANLIST></STMTRS></STMTTRNRS></BANKMSGSRSV1></OFX>\n",
+        entries.concat()
+    )
+}
+
+fn january_ofx() -> String {
+    ofx_statement(&[
+        ofx_entry("T1", "20260105", "-12.00", "Lunch"),
+        ofx_entry("T2", "20260106", "1500.00", "Salary"),
+    ])
+}
+
+fn ofx_import(vault_id: &str, user: &str, data: &str) -> engine::StatementImportCmd {
+    engine::StatementImportCmd::new(vault_id, user, engine::StatementFormat::Ofx, data)
+}
+
+#[tokio::test]
+async fn statement_import_never_books_a_bank_reference_twice() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    engine
+        .upsert_vault_member(&vault_id, "bob", "editor", "alice")
+        .await
+        .unwrap();
+
+    let report = engine
+        .import_statement(ofx_import(&vault_id, "alice", &january_ofx()))
+        .await
+        .unwrap();
+    assert_eq!(report.created.len(), 2);
+
+    // An overlapping statement imported by another member only books the new
+    // rows, even when duplicates are explicitly included.
+    let overlapping = ofx_statement(&[
+        ofx_entry("T2", "20260106", "1500.00", "Salary"),
+        ofx_entry("T3", "20260107", "-12.00", "Lunch"),
+    ]);
+    let preview = engine
+        .preview_statement_import(ofx_import(&vault_id, "bob", &overlapping))
+        .await
+        .unwrap();
+    let flags: Vec<_> = preview
+        .rows
+        .iter()
+        .map(|row| (row.duplicate_of.is_some(), row.already_imported))
+        .collect();
+    assert_eq!(flags, vec![(true, true), (false, false)]);
+    let report = engine
+        .import_statement(ofx_import(&vault_id, "bob", &overlapping).include_duplicates(true))
+        .await
+        .unwrap();
+    assert_eq!(report.created.len(), 1);
+    assert_eq!(report.skipped_duplicates, 1);
+
+    let wallet = engine.wallet(wallet_id, &vault_id, "alice").await.unwrap();
+    assert_eq!(wallet.balance, 150_000 - 12_00 - 12_00);
+}
+
+#[tokio::test]
+async fn statement_import_rejects_other_currencies_and_unknown_formats() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+
+    let err = engine
+        .preview_statement_import(ofx_import(
+            &vault_id,
+            "alice",
+            &january_ofx().replace("EUR", "USD"),
+        ))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::CurrencyMismatch(_)));
+    let err = engine
+        .preview_statement_import(ofx_import(&vault_id, "alice", "Date,Amount\n"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::InvalidAmount(_)));
+}
+
+/// Fills the vault with a second wallet, a capped flow, an aliased category,
+/// a member and four transactions (one voided). Returns the category.
+async fn populate_for_export(engine: &Engine, vault_id: &str, cash: Uuid) -> engine::Category {
+    let bank = engine
+        .new_wallet(vault_id, "Bank", None, 0, "alice")
+        .await
+        .unwrap();
+    let trips = engine
+        .new_cash_flow(vault_id, "Trips", 0, Some(150_000), Some(true), "alice")
+        .await
+        .unwrap();
+    let food = engine
+        .create_category(vault_id, "Food", "alice")
+        .await
+        .unwrap();
+    engine
+        .create_category_alias(vault_id, food.id, "groceries", "alice")
+        .await
+        .unwrap();
+    engine
+        .upsert_vault_member(vault_id, "bob", "editor", "alice")
+        .await
+        .unwrap();
+
+    let at = |d| Utc.with_ymd_and_hms(2026, 4, d, 9, 0, 0).unwrap();
+    engine
+        .income(
+            engine::IncomeCmd::new(vault_id, "alice", 100_000, at(1))
+                .wallet_id(bank)
+                .flow_id(trips),
+        )
+        .await
+        .unwrap();
+    engine
+        .transfer_wallet(engine::TransferWalletCmd::new(
+            vault_id,
+            "alice",
+            20_000,
+            bank,
+            cash,
+            at(2),
+        ))
+        .await
+        .unwrap();
+    engine
+        .expense(
+            engine::ExpenseCmd::new(vault_id, "alice", 4_500, at(3))
+                .wallet_id(cash)
+                .flow_id(trips)
+                .category_id(food.id)
+                .note("market"),
+        )
+        .await
+        .unwrap();
+    let voided = engine
+        .expense(engine::ExpenseCmd::new(vault_id, "alice", 1_000, at(4)).wallet_id(cash))
+        .await
+        .unwrap();
+    engine
+        .void_transaction(vault_id, voided, "alice", at(5))
+        .await
+        .unwrap();
+    food
+}
+
+#[tokio::test]
+async fn vault_export_holds_the_whole_vault_and_survives_json() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    populate_for_export(&engine, &vault_id, wallet_id).await;
+
+    let export = engine.export_vault(&vault_id, "alice").await.unwrap();
+    assert_eq!(export.format_version, engine::VAULT_EXPORT_VERSION);
+    assert_eq!(export.transactions.len(), 4);
+    assert_eq!(export.members.len(), 1);
+    assert!(
+        export
+            .flows
+            .iter()
+            .any(|f| f.mode == engine::FlowModeRecord::IncomeCapped { cap_minor: 150_000 })
+    );
+
+    let json = serde_json::to_string(&export).unwrap();
+    let parsed: engine::VaultExport = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, export);
+}
+
+#[tokio::test]
+async fn only_the_owner_can_export_a_vault() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    populate_for_export(&engine, &vault_id, wallet_id).await;
+
+    let err = engine.export_vault(&vault_id, "bob").await.unwrap_err();
+    assert!(matches!(
+        err,
+        EngineError::Forbidden(_) | EngineError::KeyNotFound(_)
+    ));
+}
+
+#[tokio::test]
+async fn vault_import_rejects_an_existing_owner_and_name() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let export = engine.export_vault(&vault_id, "alice").await.unwrap();
+
+    let err = engine
+        .import_vault(engine::ImportVaultCmd::new(export).new_ids(true))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::ExistingKey(_)));
+}
+
+#[tokio::test]
+async fn vault_import_rejects_tampered_balances_and_leaves_nothing_behind() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    populate_for_export(&engine, &vault_id, wallet_id).await;
+    let export = engine.export_vault(&vault_id, "alice").await.unwrap();
+
+    let mut tampered = export.clone();
+    tampered.wallets[0].balance_minor += 1;
+    let err = engine
+        .import_vault(
+            engine::ImportVaultCmd::new(tampered)
+                .owner("charlie")
+                .new_ids(true),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::InvalidExport(_)));
+
+    // The same name is still free for charlie.
+    engine
+        .import_vault(
+            engine::ImportVaultCmd::new(export)
+                .owner("charlie")
+                .new_ids(true),
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn vault_export_round_trips_through_import() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let food = populate_for_export(&engine, &vault_id, wallet_id).await;
+    let export = engine.export_vault(&vault_id, "alice").await.unwrap();
+
+    let report = engine
+        .import_vault(
+            engine::ImportVaultCmd::new(export.clone())
+                .owner("charlie")
+                .name("Copy")
+                .new_ids(true),
+        )
+        .await
+        .unwrap();
+    assert!(report.skipped_members.is_empty());
+    assert_ne!(report.vault_id, vault_id);
+
+    let copy = engine
+        .export_vault(&report.vault_id, "charlie")
+        .await
+        .unwrap();
+    assert_eq!(copy.vault.name, "Copy");
+    assert_eq!(copy.vault.owner, "charlie");
+    let balances = |export: &engine::VaultExport| {
+        let mut wallets: Vec<_> = export
+            .wallets
+            .iter()
+            .map(|w| (w.name.clone(), w.balance_minor))
+            .collect();
+        let mut flows: Vec<_> = export
+            .flows
+            .iter()
+            .map(|f| (f.name.clone(), f.mode, f.balance_minor))
+            .collect();
+        wallets.sort();
+        flows.sort_by(|a, b| a.0.cmp(&b.0));
+        (wallets, flows)
+    };
+    assert_eq!(balances(&copy), balances(&export));
+    assert_eq!(copy.transactions.len(), 4);
+    assert_eq!(
+        copy.transactions
+            .iter()
+            .filter(|tx| tx.voided_at.is_some())
+            .count(),
+        1
+    );
+    assert_eq!(copy.category_aliases[0].alias, "groceries");
+    assert_eq!(copy.members[0].user_id, "bob");
+    let copied_food = copy.categories.iter().find(|c| c.name == "Food").unwrap();
+    assert_ne!(copied_food.id, food.id);
+    assert!(
+        copy.transactions
+            .iter()
+            .any(|tx| tx.category_id == copied_food.id)
+    );
+}
+
+#[tokio::test]
+async fn vault_import_reads_older_format_versions() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let mut older = engine.export_vault(&vault_id, "alice").await.unwrap();
+    older.format_version = 1;
+
+    engine
+        .import_vault(
+            engine::ImportVaultCmd::new(older)
+                .owner("bob")
+                .new_ids(true),
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn vault_import_rejects_newer_format_versions() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let mut newer = engine.export_vault(&vault_id, "alice").await.unwrap();
+    newer.format_version = engine::VAULT_EXPORT_VERSION + 1;
+
+    let err = engine
+        .import_vault(
+            engine::ImportVaultCmd::new(newer)
+                .owner("bob")
+                .new_ids(true),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::InvalidExport(_)));
+}
+
+/// Makes bob an editor, who creates a flow and books, updates and voids an
+/// income on it; alice then caps the flow. Returns the income.
+async fn audited_history(engine: &Engine, vault_id: &str, wallet_id: Uuid) -> Uuid {
+    engine
+        .upsert_vault_member(vault_id, "bob", "editor", "alice")
+        .await
+        .unwrap();
+    let flow_id = engine
+        .new_cash_flow(vault_id, "Food", 0, None, None, "bob")
+        .await
+        .unwrap();
+    let income_id = engine
+        .income(
+            engine::IncomeCmd::new(vault_id, "bob", 200, Utc::now())
+                .flow_id(flow_id)
+                .wallet_id(wallet_id),
+        )
+        .await
+        .unwrap();
+    engine
+        .update_transaction(
+            engine::UpdateTransactionCmd::new(vault_id, income_id, "bob").amount_minor(250),
+        )
+        .await
+        .unwrap();
+    engine
+        .void_transaction(vault_id, income_id, "bob", Utc::now())
+        .await
+        .unwrap();
+    engine
+        .set_cash_flow_mode(vault_id, flow_id, Some(1_000), false, "alice")
+        .await
+        .unwrap();
+    income_id
+}
+
+async fn full_audit(engine: &Engine, vault_id: &str) -> Vec<engine::AuditEntry> {
+    let (entries, next) = engine
+        .list_audit(engine::AuditListCmd::new(vault_id, "alice", 50))
+        .await
+        .unwrap();
+    assert!(next.is_none());
+    entries
+}
+
+#[tokio::test]
+async fn audit_log_records_every_mutation_for_the_owner() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    audited_history(&engine, &vault_id, wallet_id).await;
+
+    let entries = full_audit(&engine, &vault_id).await;
+    let summary: Vec<_> = entries
+        .iter()
+        .map(|e| (e.actor.as_str(), e.action, e.entity))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("alice", AuditAction::Update, AuditEntity::Flow),
+            ("bob", AuditAction::Void, AuditEntity::Transaction),
+            ("bob", AuditAction::Update, AuditEntity::Transaction),
+            ("bob", AuditAction::Create, AuditEntity::Transaction),
+            ("bob", AuditAction::Create, AuditEntity::Flow),
+            ("alice", AuditAction::Create, AuditEntity::VaultMember),
+            ("alice", AuditAction::Create, AuditEntity::Vault),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn audit_entries_snapshot_what_changed() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    audited_history(&engine, &vault_id, wallet_id).await;
+    let entries = full_audit(&engine, &vault_id).await;
+
+    let mode = &entries[0];
+    assert_eq!(mode.before.as_ref().unwrap()["max_balance"], json!(null));
+    assert_eq!(mode.after.as_ref().unwrap()["max_balance"], json!(1_000));
+    let update = &entries[2];
+    assert_eq!(update.before.as_ref().unwrap()["amount_minor"], json!(200));
+    assert_eq!(update.after.as_ref().unwrap()["amount_minor"], json!(250));
+    assert_eq!(
+        update.after.as_ref().unwrap()["legs"]
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+    let void = &entries[1];
+    assert_eq!(void.before.as_ref().unwrap()["voided_by"], json!(null));
+    assert_eq!(void.after.as_ref().unwrap()["voided_by"], json!("bob"));
+}
+
+#[tokio::test]
+async fn audit_log_filters_by_actor_and_entity() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let income_id = audited_history(&engine, &vault_id, wallet_id).await;
+
+    let (by_entity, _) = engine
+        .list_audit(
+            engine::AuditListCmd::new(&vault_id, "alice", 50)
+                .actor("bob")
+                .entity(AuditEntity::Transaction)
+                .entity_id(income_id.to_string()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(by_entity.len(), 3);
+}
+
+async fn token_audit(engine: &Engine, vault_id: &str) -> Vec<engine::AuditEntry> {
+    let (entries, _) = engine
+        .list_audit(
+            engine::AuditListCmd::new(vault_id, "alice", 50).entity(AuditEntity::AccessToken),
+        )
+        .await
+        .unwrap();
+    entries
+}
+
+#[tokio::test]
+async fn access_tokens_are_audited_in_the_vaults_they_reach() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let other_vault = engine
+        .new_vault("Side", "alice", Some(Currency::Eur))
+        .await
+        .unwrap();
+    let (limited, _) = ci_token(&engine, &vault_id).await;
+    let (unlimited, _) = engine
+        .create_access_token(engine::CreateAccessTokenCmd::new(
+            "alice",
+            "cli",
+            engine::TokenScope::Read,
+        ))
+        .await
+        .unwrap();
+    engine
+        .revoke_access_token(limited.id, "alice")
+        .await
+        .unwrap();
+
+    let entries = token_audit(&engine, &vault_id).await;
+    let summary: Vec<_> = entries
+        .iter()
+        .map(|e| (e.action, e.entity_id.clone()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (AuditAction::Delete, limited.id.to_string()),
+            (AuditAction::Create, unlimited.id.to_string()),
+            (AuditAction::Create, limited.id.to_string()),
+        ]
+    );
+    let created = entries[2].after.as_ref().unwrap();
+    assert_eq!(created["name"], json!("ci"));
+    assert!(created.get("token_hash").is_none());
+
+    // Only the unlimited token reaches the other vault.
+    let entries = token_audit(&engine, &other_vault).await;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].entity_id, unlimited.id.to_string());
+}
+
+#[tokio::test]
+async fn recomputed_balances_are_audited() {
+    let TestVault {
+        engine,
+        db,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    engine
+        .income(engine::IncomeCmd::new(&vault_id, "alice", 500, Utc::now()))
+        .await
+        .unwrap();
+    let values: Vec<sea_orm::Value> = vec![999i64.into(), wallet_id.into()];
+    db.execute(statement(
+        db.get_database_backend(),
+        "UPDATE wallets SET balance = ? WHERE id = ?;",
+        values,
+    ))
+    .await
+    .unwrap();
+
+    engine.recompute_balances(&vault_id, "alice").await.unwrap();
+
+    // Only the drifted wallet changed.
+    let entries = full_audit(&engine, &vault_id).await;
+    let recompute = &entries[0];
+    assert_eq!(recompute.action, AuditAction::Update);
+    assert_eq!(recompute.entity, AuditEntity::Wallet);
+    assert_eq!(recompute.entity_id, wallet_id.to_string());
+    assert_eq!(recompute.before.as_ref().unwrap()["balance"], json!(999));
+    assert_eq!(recompute.after.as_ref().unwrap()["balance"], json!(500));
+    assert_eq!(entries[1].entity, AuditEntity::Transaction);
+}
+
+#[tokio::test]
+async fn audit_log_pages_with_a_cursor() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    audited_history(&engine, &vault_id, wallet_id).await;
+    let entries = full_audit(&engine, &vault_id).await;
+
+    let (page, cursor) = engine
+        .list_audit(engine::AuditListCmd::new(&vault_id, "alice", 4))
+        .await
+        .unwrap();
+    assert_eq!(page, entries[..4]);
+    let (rest, cursor) = engine
+        .list_audit(engine::AuditListCmd::new(&vault_id, "alice", 4).cursor(cursor.unwrap()))
+        .await
+        .unwrap();
+    assert_eq!(rest, entries[4..]);
+    assert!(cursor.is_none());
+    assert!(matches!(
+        engine
+            .list_audit(engine::AuditListCmd::new(&vault_id, "alice", 4).cursor("nope"))
+            .await
+            .unwrap_err(),
+        EngineError::InvalidCursor(_)
+    ));
+}
+
+#[tokio::test]
+async fn only_the_owner_reads_the_audit_log() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    audited_history(&engine, &vault_id, wallet_id).await;
+
+    assert!(
+        engine
+            .list_audit(engine::AuditListCmd::new(&vault_id, "bob", 50))
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn failed_writes_leave_no_audit_entry() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let income_id = audited_history(&engine, &vault_id, wallet_id).await;
+
+    // The income is voided: updating it fails.
+    engine
+        .update_transaction(
+            engine::UpdateTransactionCmd::new(&vault_id, income_id, "bob").amount_minor(300),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(full_audit(&engine, &vault_id).await.len(), 7);
+}
+
+#[tokio::test]
+async fn audit_entries_outlive_the_vault() {
+    let TestVault {
+        engine,
+        db,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    audited_history(&engine, &vault_id, wallet_id).await;
+
+    engine.delete_vault(&vault_id, "alice").await.unwrap();
+    let row = db
+        .query_one(statement(
+            db.get_database_backend(),
+            "SELECT COUNT(*) AS n FROM audit_log WHERE vault_id = ?",
+            vec![Uuid::parse_str(&vault_id).unwrap().into()],
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let count: i64 = row.try_get("", "n").unwrap();
+    assert_eq!(count, 8);
+}
+
+/// Transactions of the reconciliation tests, in March 2026: a cleared 100
+/// salary and 40 rent, an uncleared 3 expense before the statement date (the
+/// 15th) and a cleared 2 expense after it.
+struct MarchStatement {
+    rent: Uuid,
+    after_statement: Uuid,
+}
+
+async fn march_statement(engine: &Engine, vault_id: &str) -> MarchStatement {
+    let salary = engine
+        .income(engine::IncomeCmd::new(
+            vault_id,
+            "alice",
+            10_000,
+            noon(3, 1),
+        ))
+        .await
+        .unwrap();
+    let rent = engine
+        .expense(engine::ExpenseCmd::new(
+            vault_id,
+            "alice",
+            4_000,
+            noon(3, 5),
+        ))
+        .await
+        .unwrap();
+    engine
+        .expense(engine::ExpenseCmd::new(vault_id, "alice", 300, noon(3, 9)))
+        .await
+        .unwrap();
+    let after_statement = engine
+        .expense(engine::ExpenseCmd::new(vault_id, "alice", 200, noon(3, 20)))
+        .await
+        .unwrap();
+    for id in [salary, rent, after_statement] {
+        engine
+            .set_transaction_cleared(vault_id, id, true, "alice")
+            .await
+            .unwrap();
+    }
+    MarchStatement {
+        rent,
+        after_statement,
+    }
+}
+
+fn reconcile_march(
+    vault_id: &str,
+    wallet_id: Uuid,
+    statement_balance_minor: i64,
+) -> engine::ReconcileWalletCmd {
+    engine::ReconcileWalletCmd::new(
+        vault_id,
+        "alice",
+        wallet_id,
+        noon(3, 15),
+        statement_balance_minor,
+    )
+}
+
+async fn clearing_status(engine: &Engine, vault_id: &str, tx_id: Uuid) -> ClearingStatus {
+    engine
+        .transaction_with_legs(vault_id, tx_id, "alice")
+        .await
+        .unwrap()
+        .clearing_status
+}
+
+#[tokio::test]
+async fn reconciliation_preview_compares_the_cleared_balance() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    march_statement(&engine, &vault_id).await;
+
+    let preview = engine
+        .preview_reconciliation(reconcile_march(&vault_id, wallet_id, 6_000))
+        .await
+        .unwrap();
+    assert_eq!(preview.cleared_balance_minor, 6_000);
+    assert_eq!(preview.difference_minor, 0);
+    assert_eq!(preview.cleared_count, 2);
+    assert_eq!(preview.uncleared_count, 1);
+    assert_eq!(preview.uncleared_minor, -300);
+}
+
+#[tokio::test]
+async fn reconciliation_rejects_a_statement_that_differs() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    march_statement(&engine, &vault_id).await;
+
+    let err = engine
+        .reconcile_wallet(reconcile_march(&vault_id, wallet_id, 5_700))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::InvalidAmount(
+            "statement balance differs from the cleared balance by -300".to_string()
+        )
+    );
+}
+
+#[tokio::test]
+async fn reconciling_locks_cleared_transactions_up_to_the_statement_date() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let march = march_statement(&engine, &vault_id).await;
+    let cmd = reconcile_march(&vault_id, wallet_id, 6_000);
+
+    let preview = engine.preview_reconciliation(cmd.clone()).await.unwrap();
+    let reconciliation = engine.reconcile_wallet(cmd).await.unwrap();
+    assert_eq!(reconciliation, preview);
+    assert_eq!(
+        clearing_status(&engine, &vault_id, march.rent).await,
+        ClearingStatus::Reconciled
+    );
+    assert_eq!(
+        clearing_status(&engine, &vault_id, march.after_statement).await,
+        ClearingStatus::Cleared
+    );
+}
+
+#[tokio::test]
+async fn reconciled_transactions_reject_changes() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let MarchStatement { rent, .. } = march_statement(&engine, &vault_id).await;
+    engine
+        .reconcile_wallet(reconcile_march(&vault_id, wallet_id, 6_000))
+        .await
+        .unwrap();
+
+    let locked = EngineError::TransactionLocked(format!(
+        "transaction {rent} is reconciled, unlock it first"
+    ));
+    let err = engine
+        .update_transaction(
+            engine::UpdateTransactionCmd::new(&vault_id, rent, "alice").amount_minor(4_100),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, locked);
+    let err = engine
+        .void_transaction(&vault_id, rent, "alice", noon(3, 16))
+        .await
+        .unwrap_err();
+    assert_eq!(err, locked);
+    let err = engine
+        .set_transaction_cleared(&vault_id, rent, false, "alice")
+        .await
+        .unwrap_err();
+    assert_eq!(err, locked);
+}
+
+#[tokio::test]
+async fn applying_category_rules_skips_reconciled_transactions() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let march = march_statement(&engine, &vault_id).await;
+    engine
+        .reconcile_wallet(reconcile_march(&vault_id, wallet_id, 6_000))
+        .await
+        .unwrap();
+    let uncategorized = category_of(&engine, &vault_id, march.rent).await;
+    let spending = engine
+        .create_category(&vault_id, "Spending", "alice")
+        .await
+        .unwrap();
+    engine
+        .create_category_rule(engine::CreateCategoryRuleCmd::new(
+            &vault_id,
+            "alice",
+            spending.id,
+            engine::CategoryRuleConditions {
+                kind: Some(TransactionKind::Expense),
+                ..Default::default()
+            },
+        ))
+        .await
+        .unwrap();
+
+    let hits = engine
+        .apply_category_rules(engine::ApplyCategoryRulesCmd::new(&vault_id, "alice"))
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 3);
+    for hit in &hits {
+        let skipped =
+            (hit.transaction_id == march.rent).then_some(engine::CategoryRuleSkip::Reconciled);
+        assert_eq!(hit.skipped, skipped);
+    }
+    assert_eq!(
+        category_of(&engine, &vault_id, march.rent).await,
+        uncategorized
+    );
+    assert_eq!(
+        category_of(&engine, &vault_id, march.after_statement).await,
+        spending.id
+    );
+}
+
+#[tokio::test]
+async fn merging_a_category_of_reconciled_transactions_is_rejected() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let march = march_statement(&engine, &vault_id).await;
+    let housing = engine
+        .create_category(&vault_id, "Housing", "alice")
+        .await
+        .unwrap();
+    let home = engine
+        .create_category(&vault_id, "Home", "alice")
+        .await
+        .unwrap();
+    engine
+        .update_transaction(
+            engine::UpdateTransactionCmd::new(&vault_id, march.rent, "alice")
+                .category_id(housing.id),
+        )
+        .await
+        .unwrap();
+    engine
+        .reconcile_wallet(reconcile_march(&vault_id, wallet_id, 6_000))
+        .await
+        .unwrap();
+
+    let preview = engine
+        .preview_category_merge(&vault_id, housing.id, home.id, "alice")
+        .await
+        .unwrap();
+    assert!(!preview.ok);
+    assert!(preview.conflicts.iter().any(|c| {
+        c.kind == CategoryMergeConflictKind::TransactionLocked && c.value == march.rent.to_string()
+    }));
+    let err = engine
+        .merge_category(&vault_id, housing.id, home.id, "alice")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::TransactionLocked(format!(
+            "transaction {} is reconciled, unlock it first",
+            march.rent
+        ))
+    );
+    assert_eq!(
+        category_of(&engine, &vault_id, march.rent).await,
+        housing.id
+    );
+}
+
+#[tokio::test]
+async fn reconciling_the_same_statement_again_covers_nothing_new() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    march_statement(&engine, &vault_id).await;
+    let cmd = reconcile_march(&vault_id, wallet_id, 6_000);
+    engine.reconcile_wallet(cmd.clone()).await.unwrap();
+
+    let again = engine.reconcile_wallet(cmd).await.unwrap();
+    assert_eq!(again.cleared_count, 0);
+    assert_eq!(again.reconciled_count, 2);
+}
+
+#[tokio::test]
+async fn unlocked_transactions_can_change_again() {
+    let TestVault {
+        engine,
+        vault_id,
+        wallet_id,
+        ..
+    } = test_vault().await;
+    let MarchStatement { rent, .. } = march_statement(&engine, &vault_id).await;
+    engine
+        .reconcile_wallet(reconcile_march(&vault_id, wallet_id, 6_000))
+        .await
+        .unwrap();
+
+    engine
+        .unlock_transaction(&vault_id, rent, "alice")
+        .await
+        .unwrap();
+    assert_eq!(
+        clearing_status(&engine, &vault_id, rent).await,
+        ClearingStatus::Cleared
+    );
+    engine
+        .update_transaction(
+            engine::UpdateTransactionCmd::new(&vault_id, rent, "alice").amount_minor(4_100),
+        )
+        .await
+        .unwrap();
+    let err = engine
+        .unlock_transaction(&vault_id, rent, "alice")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::InvalidAmount("transaction is not reconciled".to_string())
+    );
+}
+
+/// Books a 100 income on January 10th 2026 and a 5 expense on March 10th,
+/// then closes the books until February 1st. Returns both transactions.
+async fn close_january(engine: &Engine, vault_id: &str) -> (Uuid, Uuid) {
+    let old = engine
+        .income(engine::IncomeCmd::new(
+            vault_id,
+            "alice",
+            10_000,
+            noon(1, 10),
+        ))
+        .await
+        .unwrap();
+    let recent = engine
+        .expense(engine::ExpenseCmd::new(vault_id, "alice", 500, noon(3, 10)))
+        .await
+        .unwrap();
+    engine
+        .set_books_closed_until(vault_id, Some(books_closed_until()), "alice")
+        .await
+        .unwrap();
+    (old, recent)
+}
+
+fn books_closed_until() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 2, 1, 0, 0, 0).unwrap()
+}
+
+fn books_closed() -> EngineError {
+    EngineError::BooksClosed("books are closed until 2026-02-01T00:00:00+00:00".into())
+}
+
+#[tokio::test]
+async fn only_the_owner_closes_the_books() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+
+    let err = engine
+        .set_books_closed_until(&vault_id, Some(books_closed_until()), "bob")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::KeyNotFound("vault not exists".to_string())
+    );
+}
+
+#[tokio::test]
+async fn closed_books_reject_new_transactions_before_the_lock_date() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    close_january(&engine, &vault_id).await;
+
+    let err = engine
+        .expense(engine::ExpenseCmd::new(
+            &vault_id,
+            "alice",
+            100,
+            noon(1, 20),
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(err, books_closed());
+}
+
+#[tokio::test]
+async fn closed_books_reject_changes_before_the_lock_date() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let (old, _) = close_january(&engine, &vault_id).await;
+
+    let err = engine
+        .update_transaction(engine::UpdateTransactionCmd::new(&vault_id, old, "alice").note("late"))
+        .await
+        .unwrap_err();
+    assert_eq!(err, books_closed());
+    let err = engine
+        .void_transaction(&vault_id, old, "alice", noon(3, 11))
+        .await
+        .unwrap_err();
+    assert_eq!(err, books_closed());
+}
+
+#[tokio::test]
+async fn closed_books_reject_moving_a_transaction_into_the_closed_period() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let (_, recent) = close_january(&engine, &vault_id).await;
+
+    let err = engine
+        .update_transaction(
+            engine::UpdateTransactionCmd::new(&vault_id, recent, "alice").occurred_at(noon(1, 15)),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, books_closed());
+}
+
+#[tokio::test]
+async fn closed_books_allow_changes_from_the_lock_date() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let (_, recent) = close_january(&engine, &vault_id).await;
+
+    engine
+        .expense(engine::ExpenseCmd::new(&vault_id, "alice", 100, noon(2, 1)))
+        .await
+        .unwrap();
+    engine
+        .update_transaction(
+            engine::UpdateTransactionCmd::new(&vault_id, recent, "alice").amount_minor(600),
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn reopened_books_allow_changes_again() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let (old, _) = close_january(&engine, &vault_id).await;
+
+    engine
+        .set_books_closed_until(&vault_id, None, "alice")
+        .await
+        .unwrap();
+    engine
+        .void_transaction(&vault_id, old, "alice", noon(3, 11))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn applying_category_rules_skips_transactions_in_closed_books() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let (old, recent) = close_january(&engine, &vault_id).await;
+    let uncategorized = category_of(&engine, &vault_id, old).await;
+    let ruled = engine
+        .create_category(&vault_id, "Ruled", "alice")
+        .await
+        .unwrap();
+    engine
+        .create_category_rule(engine::CreateCategoryRuleCmd::new(
+            &vault_id,
+            "alice",
+            ruled.id,
+            engine::CategoryRuleConditions {
+                min_amount_minor: Some(100),
+                ..Default::default()
+            },
+        ))
+        .await
+        .unwrap();
+
+    let hits = engine
+        .apply_category_rules(engine::ApplyCategoryRulesCmd::new(&vault_id, "alice"))
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 2);
+    for hit in &hits {
+        let skipped = (hit.transaction_id == old).then_some(engine::CategoryRuleSkip::BooksClosed);
+        assert_eq!(hit.skipped, skipped);
+    }
+    assert_eq!(category_of(&engine, &vault_id, old).await, uncategorized);
+    assert_eq!(category_of(&engine, &vault_id, recent).await, ruled.id);
+}
+
+#[tokio::test]
+async fn merging_a_category_across_closed_books_is_rejected() {
+    let TestVault {
+        engine, vault_id, ..
+    } = test_vault().await;
+    let salary = engine
+        .create_category(&vault_id, "Salary", "alice")
+        .await
+        .unwrap();
+    let earnings = engine
+        .create_category(&vault_id, "Earnings", "alice")
+        .await
+        .unwrap();
+    let old = engine
+        .income(
+            engine::IncomeCmd::new(&vault_id, "alice", 10_000, noon(1, 10)).category_id(salary.id),
+        )
+        .await
+        .unwrap();
+    engine
+        .income(
+            engine::IncomeCmd::new(&vault_id, "alice", 10_000, noon(3, 10)).category_id(salary.id),
+        )
+        .await
+        .unwrap();
+    engine
+        .set_books_closed_until(&vault_id, Some(books_closed_until()), "alice")
+        .await
+        .unwrap();
+
+    let preview = engine
+        .preview_category_merge(&vault_id, salary.id, earnings.id, "alice")
+        .await
+        .unwrap();
+    assert!(!preview.ok);
+    assert!(preview.conflicts.iter().any(|c| {
+        c.kind == CategoryMergeConflictKind::BooksClosed
+            && c.value == books_closed_until().to_rfc3339()
+    }));
+    let err = engine
+        .merge_category(&vault_id, salary.id, earnings.id, "alice")
+        .await
+        .unwrap_err();
+    assert_eq!(err, books_closed());
+    assert_eq!(category_of(&engine, &vault_id, old).await, salary.id);
+}
+
+/// Holiday flow with a goal of 3000 by June 1st 2026. Allocations, made from
+/// Unallocated in 2025: 300 in September, 300 a month from October to
+/// December and a voided 500. Returns the flow.
+async fn holiday_goal(engine: &Engine, vault_id: &str, unallocated: Uuid) -> Uuid {
+    let holiday = engine
+        .new_cash_flow(vault_id, "Holiday", 0, None, None, "alice")
+        .await
+        .unwrap();
+    engine
+        .set_cash_flow_goal(
+            vault_id,
+            holiday,
+            Some(300_000),
+            chrono::NaiveDate::from_ymd_opt(2026, 6, 1),
+            "alice",
+        )
+        .await
+        .unwrap();
+
+    let allocate = |amount_minor: i64, month: u32, day: u32| {
+        let occurred_at = Utc.with_ymd_and_hms(2025, month, day, 12, 0, 0).unwrap();
+        engine.transfer_flow(engine::TransferFlowCmd::new(
+            vault_id,
+            "alice",
+            amount_minor,
+            unallocated,
+            holiday,
+            occurred_at,
+        ))
+    };
+    allocate(30_000, 9, 1).await.unwrap();
+    for month in [10, 11, 12] {
+        allocate(30_000, month, 20).await.unwrap();
+    }
+    let voided = allocate(50_000, 12, 21).await.unwrap();
+    engine
+        .void_transaction(vault_id, voided, "alice", Utc::now())
+        .await
+        .unwrap();
+    holiday
+}
+
+fn goals_day() -> chrono::NaiveDate {
+    chrono::NaiveDate::from_ymd_opt(2026, 1, 15).unwrap()
+}
+
+#[tokio::test]
+async fn flow_goal_needs_a_positive_target_on_a_user_flow() {
+    let TestVault {
+        engine,
+        vault_id,
+        unallocated_id,
+        ..
+    } = test_vault().await;
+    let holiday = engine
+        .new_cash_flow(&vault_id, "Holiday", 0, None, None, "alice")
+        .await
+        .unwrap();
+
+    let err = engine
+        .set_cash_flow_goal(&vault_id, holiday, Some(0), None, "alice")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::InvalidFlow("goal target must be > 0".to_string())
+    );
+    let err = engine
+        .set_cash_flow_goal(&vault_id, unallocated_id, Some(100), None, "alice")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::InvalidFlow("cannot set a goal on system flow".to_string())
+    );
+}
+
+#[tokio::test]
+async fn flow_goals_report_progress_from_recent_contributions() {
+    let TestVault {
+        engine,
+        vault_id,
+        unallocated_id,
+        ..
+    } = test_vault().await;
+    let holiday = holiday_goal(&engine, &vault_id, unallocated_id).await;
+
+    // September falls out of the contribution window.
+    let goals = engine
+        .flow_goals(&vault_id, "alice", goals_day())
+        .await
+        .unwrap();
+    assert_eq!(
+        goals,
+        vec![engine::GoalProgress {
+            flow_id: holiday,
+            target_minor: 300_000,
+            target_date: chrono::NaiveDate::from_ymd_opt(2026, 6, 1),
+            saved_minor: 120_000,
+            remaining_minor: 180_000,
+            progress_bps: 4000,
+            monthly_contribution_minor: 30_000,
+            required_monthly_minor: Some(36_000),
+            projected_completion: chrono::NaiveDate::from_ymd_opt(2026, 7, 15),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn flow_goals_require_vault_access() {
+    let TestVault {
+        engine,
+        vault_id,
+        unallocated_id,
+        ..
+    } = test_vault().await;
+    holiday_goal(&engine, &vault_id, unallocated_id).await;
+
+    let err = engine
+        .flow_goals(&vault_id, "bob", goals_day())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::KeyNotFound("vault not exists".to_string())
+    );
+}
+
+#[tokio::test]
+async fn cleared_flow_goal_is_no_longer_reported() {
+    let TestVault {
+        engine,
+        vault_id,
+        unallocated_id,
+        ..
+    } = test_vault().await;
+    let holiday = holiday_goal(&engine, &vault_id, unallocated_id).await;
+
+    engine
+        .set_cash_flow_goal(&vault_id, holiday, None, None, "alice")
+        .await
+        .unwrap();
+    assert!(
+        engine
+            .flow_goals(&vault_id, "alice", goals_day())
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
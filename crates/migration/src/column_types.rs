@@ -0,0 +1,31 @@
+//! Column types that differ between backends.
+//!
+//! SQLite keeps its original layout (UUIDs as 16-byte blobs, timestamps as
+//! text). Postgres gets native `uuid` and `timestamptz` columns, which is what
+//! the engine entities (`Uuid`, `DateTime<Utc>`) bind to there.
+
+use sea_orm::DbBackend;
+use sea_orm_migration::prelude::*;
+
+pub(crate) trait BackendColumnDef {
+    /// A UUID column.
+    fn uuid_for(&mut self, backend: DbBackend) -> &mut Self;
+    /// A UTC timestamp column.
+    fn timestamp_for(&mut self, backend: DbBackend) -> &mut Self;
+}
+
+impl BackendColumnDef for ColumnDef {
+    fn uuid_for(&mut self, backend: DbBackend) -> &mut Self {
+        match backend {
+            DbBackend::Sqlite => self.blob(),
+            DbBackend::Postgres | DbBackend::MySql => self.uuid(),
+        }
+    }
+
+    fn timestamp_for(&mut self, backend: DbBackend) -> &mut Self {
+        match backend {
+            DbBackend::Sqlite => self.timestamp(),
+            DbBackend::Postgres | DbBackend::MySql => self.timestamp_with_time_zone(),
+        }
+    }
+}
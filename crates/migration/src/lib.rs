@@ -1,7 +1,22 @@
 pub use sea_orm_migration::prelude::*;
 
+mod column_types;
 mod m20251230_000000_init;
 mod m20260115_000001_categories;
+mod m20260201_000002_multi_currency;
+mod m20260210_000003_recurring_rules;
+mod m20260220_000004_budgets;
+mod m20260301_000005_category_rules;
+mod m20260310_000006_split_legs;
+mod m20260320_000007_hash_passwords;
+mod m20260401_000008_access_tokens;
+mod m20260410_000009_audit_log;
+mod m20260420_000010_transaction_revisions;
+mod m20260501_000011_clearing_status;
+mod m20260510_000012_books_closed_until;
+mod m20260520_000013_flow_goals;
+mod m20260601_000014_pair_code_expiry;
+mod m20260610_000015_recurring_rule_errors;
 
 pub struct Migrator;
 
@@ -11,6 +26,20 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20251230_000000_init::Migration),
             Box::new(m20260115_000001_categories::Migration),
+            Box::new(m20260201_000002_multi_currency::Migration),
+            Box::new(m20260210_000003_recurring_rules::Migration),
+            Box::new(m20260220_000004_budgets::Migration),
+            Box::new(m20260301_000005_category_rules::Migration),
+            Box::new(m20260310_000006_split_legs::Migration),
+            Box::new(m20260320_000007_hash_passwords::Migration),
+            Box::new(m20260401_000008_access_tokens::Migration),
+            Box::new(m20260410_000009_audit_log::Migration),
+            Box::new(m20260420_000010_transaction_revisions::Migration),
+            Box::new(m20260501_000011_clearing_status::Migration),
+            Box::new(m20260510_000012_books_closed_until::Migration),
+            Box::new(m20260520_000013_flow_goals::Migration),
+            Box::new(m20260601_000014_pair_code_expiry::Migration),
+            Box::new(m20260610_000015_recurring_rule_errors::Migration),
         ]
     }
 }
@@ -14,6 +14,8 @@
 
 use sea_orm_migration::prelude::*;
 
+use crate::column_types::BackendColumnDef;
+
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -117,6 +119,7 @@ enum FlowMemberships {
 #[async_trait::async_trait]
 impl MigrationTrait for Migration {
     async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let backend = manager.get_database_backend();
         // ───────────────────────────────────────────────────────────────────
         // 1. Users
         // ───────────────────────────────────────────────────────────────────
@@ -146,7 +149,12 @@ impl MigrationTrait for Migration {
                 Table::create()
                     .table(Vaults::Table)
                     .if_not_exists()
-                    .col(ColumnDef::new(Vaults::Id).blob().not_null().primary_key())
+                    .col(
+                        ColumnDef::new(Vaults::Id)
+                            .uuid_for(backend)
+                            .not_null()
+                            .primary_key(),
+                    )
                     .col(ColumnDef::new(Vaults::Name).string().not_null())
                     .col(ColumnDef::new(Vaults::UserId).string().not_null())
                     .col(
@@ -173,7 +181,12 @@ impl MigrationTrait for Migration {
                 Table::create()
                     .table(Wallets::Table)
                     .if_not_exists()
-                    .col(ColumnDef::new(Wallets::Id).blob().not_null().primary_key())
+                    .col(
+                        ColumnDef::new(Wallets::Id)
+                            .uuid_for(backend)
+                            .not_null()
+                            .primary_key(),
+                    )
                     .col(ColumnDef::new(Wallets::Name).string().not_null())
                     .col(ColumnDef::new(Wallets::Balance).big_integer().not_null())
                     .col(
@@ -183,7 +196,11 @@ impl MigrationTrait for Migration {
                             .default("EUR"),
                     )
                     .col(ColumnDef::new(Wallets::Archived).boolean().not_null())
-                    .col(ColumnDef::new(Wallets::VaultId).blob().not_null())
+                    .col(
+                        ColumnDef::new(Wallets::VaultId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
                     .foreign_key(
                         ForeignKey::create()
                             .name("fk-wallets-vault_id")
@@ -216,7 +233,7 @@ impl MigrationTrait for Migration {
                     .if_not_exists()
                     .col(
                         ColumnDef::new(CashFlows::Id)
-                            .blob()
+                            .uuid_for(backend)
                             .not_null()
                             .primary_key(),
                     )
@@ -232,7 +249,11 @@ impl MigrationTrait for Migration {
                             .default("EUR"),
                     )
                     .col(ColumnDef::new(CashFlows::Archived).boolean().not_null())
-                    .col(ColumnDef::new(CashFlows::VaultId).blob().not_null())
+                    .col(
+                        ColumnDef::new(CashFlows::VaultId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
                     .foreign_key(
                         ForeignKey::create()
                             .name("fk-cash_flows-vault_id")
@@ -265,15 +286,19 @@ impl MigrationTrait for Migration {
                     .if_not_exists()
                     .col(
                         ColumnDef::new(Transactions::Id)
-                            .blob()
+                            .uuid_for(backend)
                             .not_null()
                             .primary_key(),
                     )
-                    .col(ColumnDef::new(Transactions::VaultId).blob().not_null())
+                    .col(
+                        ColumnDef::new(Transactions::VaultId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
                     .col(ColumnDef::new(Transactions::Kind).string().not_null())
                     .col(
                         ColumnDef::new(Transactions::OccurredAt)
-                            .timestamp()
+                            .timestamp_for(backend)
                             .not_null(),
                     )
                     .col(
@@ -285,9 +310,9 @@ impl MigrationTrait for Migration {
                     .col(ColumnDef::new(Transactions::Category).string())
                     .col(ColumnDef::new(Transactions::Note).string())
                     .col(ColumnDef::new(Transactions::CreatedBy).string().not_null())
-                    .col(ColumnDef::new(Transactions::VoidedAt).timestamp())
+                    .col(ColumnDef::new(Transactions::VoidedAt).timestamp_for(backend))
                     .col(ColumnDef::new(Transactions::VoidedBy).string())
-                    .col(ColumnDef::new(Transactions::RefundedTransactionId).blob())
+                    .col(ColumnDef::new(Transactions::RefundedTransactionId).uuid_for(backend))
                     .col(ColumnDef::new(Transactions::IdempotencyKey).string())
                     .foreign_key(
                         ForeignKey::create()
@@ -341,10 +366,19 @@ impl MigrationTrait for Migration {
                 Table::create()
                     .table(Legs::Table)
                     .if_not_exists()
-                    .col(ColumnDef::new(Legs::Id).blob().not_null().primary_key())
-                    .col(ColumnDef::new(Legs::TransactionId).blob().not_null())
+                    .col(
+                        ColumnDef::new(Legs::Id)
+                            .uuid_for(backend)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Legs::TransactionId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
                     .col(ColumnDef::new(Legs::TargetKind).string().not_null())
-                    .col(ColumnDef::new(Legs::TargetId).blob().not_null())
+                    .col(ColumnDef::new(Legs::TargetId).uuid_for(backend).not_null())
                     .col(ColumnDef::new(Legs::AmountMinor).big_integer().not_null())
                     .col(ColumnDef::new(Legs::Currency).string().not_null())
                     .col(ColumnDef::new(Legs::AttributedUserId).string())
@@ -398,7 +432,11 @@ impl MigrationTrait for Migration {
                 Table::create()
                     .table(VaultMemberships::Table)
                     .if_not_exists()
-                    .col(ColumnDef::new(VaultMemberships::VaultId).blob().not_null())
+                    .col(
+                        ColumnDef::new(VaultMemberships::VaultId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
                     .col(ColumnDef::new(VaultMemberships::UserId).string().not_null())
                     .col(ColumnDef::new(VaultMemberships::Role).string().not_null())
                     .primary_key(
@@ -442,7 +480,11 @@ impl MigrationTrait for Migration {
                 Table::create()
                     .table(FlowMemberships::Table)
                     .if_not_exists()
-                    .col(ColumnDef::new(FlowMemberships::FlowId).blob().not_null())
+                    .col(
+                        ColumnDef::new(FlowMemberships::FlowId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
                     .col(ColumnDef::new(FlowMemberships::UserId).string().not_null())
                     .col(ColumnDef::new(FlowMemberships::Role).string().not_null())
                     .primary_key(
@@ -1,10 +1,12 @@
 use std::collections::{HashMap, HashSet};
 
-use sea_orm::{ConnectionTrait, DbBackend, QueryResult, Statement, Value, prelude::DateTimeUtc};
+use sea_orm::{ConnectionTrait, DbBackend, prelude::DateTimeUtc};
 use sea_orm_migration::{SchemaManagerConnection, prelude::*};
 use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
 use uuid::Uuid;
 
+use crate::column_types::BackendColumnDef;
+
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -32,6 +34,10 @@ enum CategoryAliases {
 #[derive(Iden)]
 enum Transactions {
     Table,
+    Id,
+    VaultId,
+    Category,
+    OccurredAt,
     CategoryId,
 }
 
@@ -44,6 +50,7 @@ enum Vaults {
 #[async_trait::async_trait]
 impl MigrationTrait for Migration {
     async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let backend = manager.get_database_backend();
         manager
             .create_table(
                 Table::create()
@@ -51,11 +58,15 @@ impl MigrationTrait for Migration {
                     .if_not_exists()
                     .col(
                         ColumnDef::new(Categories::Id)
-                            .blob()
+                            .uuid_for(backend)
                             .not_null()
                             .primary_key(),
                     )
-                    .col(ColumnDef::new(Categories::VaultId).blob().not_null())
+                    .col(
+                        ColumnDef::new(Categories::VaultId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
                     .col(ColumnDef::new(Categories::Name).string().not_null())
                     .col(ColumnDef::new(Categories::NameNorm).string().not_null())
                     .col(
@@ -99,14 +110,18 @@ impl MigrationTrait for Migration {
                     .if_not_exists()
                     .col(
                         ColumnDef::new(CategoryAliases::Id)
-                            .blob()
+                            .uuid_for(backend)
                             .not_null()
                             .primary_key(),
                     )
-                    .col(ColumnDef::new(CategoryAliases::VaultId).blob().not_null())
+                    .col(
+                        ColumnDef::new(CategoryAliases::VaultId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
                     .col(
                         ColumnDef::new(CategoryAliases::CategoryId)
-                            .blob()
+                            .uuid_for(backend)
                             .not_null(),
                     )
                     .col(ColumnDef::new(CategoryAliases::Alias).string().not_null())
@@ -147,7 +162,7 @@ impl MigrationTrait for Migration {
             .alter_table(
                 Table::alter()
                     .table(Transactions::Table)
-                    .add_column(ColumnDef::new(Transactions::CategoryId).blob())
+                    .add_column(ColumnDef::new(Transactions::CategoryId).uuid_for(backend))
                     .to_owned(),
             )
             .await?;
@@ -200,11 +215,11 @@ async fn backfill_categories(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
     let backend = db.get_database_backend();
 
     let vault_rows = db
-        .query_all(Statement::from_string(backend, "SELECT id FROM vaults;"))
+        .query_all(backend.build(Query::select().column(Vaults::Id).from(Vaults::Table)))
         .await?;
     let mut vault_ids = Vec::with_capacity(vault_rows.len());
     for row in vault_rows {
-        let vault_id = uuid_from_row(&row, "id")?;
+        let vault_id: Uuid = row.try_get("", "id")?;
         vault_ids.push(vault_id);
     }
 
@@ -225,18 +240,26 @@ async fn backfill_categories(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
     }
 
     let tx_rows = db
-        .query_all(Statement::from_string(
-            backend,
-            "SELECT id, vault_id, category, occurred_at FROM transactions;",
-        ))
+        .query_all(
+            backend.build(
+                Query::select()
+                    .columns([
+                        Transactions::Id,
+                        Transactions::VaultId,
+                        Transactions::Category,
+                        Transactions::OccurredAt,
+                    ])
+                    .from(Transactions::Table),
+            ),
+        )
         .await?;
 
     let mut variants_by_vault: HashMap<Uuid, HashMap<String, CategoryVariant>> = HashMap::new();
     let mut tx_by_vault: HashMap<Uuid, Vec<TxRecord>> = HashMap::new();
 
     for row in tx_rows {
-        let tx_id = uuid_from_row(&row, "id")?;
-        let vault_id = uuid_from_row(&row, "vault_id")?;
+        let tx_id: Uuid = row.try_get("", "id")?;
+        let vault_id: Uuid = row.try_get("", "vault_id")?;
         let category: Option<String> = row.try_get("", "category")?;
         let occurred_at: DateTimeUtc = row.try_get("", "occurred_at")?;
 
@@ -374,20 +397,28 @@ async fn insert_category(
     name_norm: &str,
     is_system: bool,
 ) -> Result<(), DbErr> {
-    let values = vec![
-        id.as_bytes().to_vec().into(),
-        vault_id.as_bytes().to_vec().into(),
-        name.to_string().into(),
-        name_norm.to_string().into(),
-        Value::Bool(Some(false)),
-        Value::Bool(Some(is_system)),
-    ];
-    db.execute(Statement::from_sql_and_values(
-        backend,
-        "INSERT INTO categories (id, vault_id, name, name_norm, archived, is_system) \
-         VALUES (?, ?, ?, ?, ?, ?);",
-        values,
-    ))
+    db.execute(
+        backend.build(
+            Query::insert()
+                .into_table(Categories::Table)
+                .columns([
+                    Categories::Id,
+                    Categories::VaultId,
+                    Categories::Name,
+                    Categories::NameNorm,
+                    Categories::Archived,
+                    Categories::IsSystem,
+                ])
+                .values_panic([
+                    id.into(),
+                    vault_id.into(),
+                    name.into(),
+                    name_norm.into(),
+                    false.into(),
+                    is_system.into(),
+                ]),
+        ),
+    )
     .await?;
     Ok(())
 }
@@ -401,19 +432,26 @@ async fn insert_alias(
     alias: &str,
     alias_norm: &str,
 ) -> Result<(), DbErr> {
-    let values = vec![
-        id.as_bytes().to_vec().into(),
-        vault_id.as_bytes().to_vec().into(),
-        category_id.as_bytes().to_vec().into(),
-        alias.to_string().into(),
-        alias_norm.to_string().into(),
-    ];
-    db.execute(Statement::from_sql_and_values(
-        backend,
-        "INSERT INTO category_aliases (id, vault_id, category_id, alias, alias_norm) \
-         VALUES (?, ?, ?, ?, ?);",
-        values,
-    ))
+    db.execute(
+        backend.build(
+            Query::insert()
+                .into_table(CategoryAliases::Table)
+                .columns([
+                    CategoryAliases::Id,
+                    CategoryAliases::VaultId,
+                    CategoryAliases::CategoryId,
+                    CategoryAliases::Alias,
+                    CategoryAliases::AliasNorm,
+                ])
+                .values_panic([
+                    id.into(),
+                    vault_id.into(),
+                    category_id.into(),
+                    alias.into(),
+                    alias_norm.into(),
+                ]),
+        ),
+    )
     .await?;
     Ok(())
 }
@@ -425,29 +463,24 @@ async fn update_transaction_category(
     category_id: Uuid,
     category_display: Option<&str>,
 ) -> Result<(), DbErr> {
-    let values = vec![
-        category_id.as_bytes().to_vec().into(),
-        match category_display {
-            Some(value) => value.to_string().into(),
-            None => Value::String(None),
-        },
-        transaction_id.as_bytes().to_vec().into(),
-    ];
-    db.execute(Statement::from_sql_and_values(
-        backend,
-        "UPDATE transactions SET category_id = ?, category = ? WHERE id = ?;",
-        values,
-    ))
+    db.execute(
+        backend.build(
+            Query::update()
+                .table(Transactions::Table)
+                .values([
+                    (Transactions::CategoryId, category_id.into()),
+                    (
+                        Transactions::Category,
+                        category_display.map(str::to_string).into(),
+                    ),
+                ])
+                .and_where(Expr::col(Transactions::Id).eq(transaction_id)),
+        ),
+    )
     .await?;
     Ok(())
 }
 
-fn uuid_from_row(row: &QueryResult, column: &str) -> Result<Uuid, DbErr> {
-    let bytes: Vec<u8> = row.try_get("", column)?;
-    Uuid::from_slice(&bytes)
-        .map_err(|err| DbErr::Custom(format!("invalid UUID in column {column}: {err}")))
-}
-
 fn normalize_display(input: &str) -> Option<String> {
     let trimmed = input.trim();
     if trimmed.is_empty() {
@@ -0,0 +1,146 @@
+use sea_orm_migration::prelude::*;
+
+use crate::column_types::BackendColumnDef;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum RecurringRules {
+    Table,
+    Id,
+    VaultId,
+    Kind,
+    AmountMinor,
+    WalletId,
+    FlowId,
+    FromFlowId,
+    ToFlowId,
+    Category,
+    Note,
+    Frequency,
+    Interval,
+    DayOfMonth,
+    StartsAt,
+    EndsAt,
+    MaxOccurrences,
+    Occurrences,
+    NextOccurrenceAt,
+    Active,
+    CreatedBy,
+}
+
+#[derive(Iden)]
+enum Vaults {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let backend = manager.get_database_backend();
+        manager
+            .create_table(
+                Table::create()
+                    .table(RecurringRules::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RecurringRules::Id)
+                            .uuid_for(backend)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(RecurringRules::VaultId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(RecurringRules::Kind).string().not_null())
+                    .col(
+                        ColumnDef::new(RecurringRules::AmountMinor)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(RecurringRules::WalletId).uuid_for(backend))
+                    .col(ColumnDef::new(RecurringRules::FlowId).uuid_for(backend))
+                    .col(ColumnDef::new(RecurringRules::FromFlowId).uuid_for(backend))
+                    .col(ColumnDef::new(RecurringRules::ToFlowId).uuid_for(backend))
+                    .col(ColumnDef::new(RecurringRules::Category).string())
+                    .col(ColumnDef::new(RecurringRules::Note).string())
+                    .col(
+                        ColumnDef::new(RecurringRules::Frequency)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RecurringRules::Interval)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .col(ColumnDef::new(RecurringRules::DayOfMonth).integer())
+                    .col(
+                        ColumnDef::new(RecurringRules::StartsAt)
+                            .timestamp_for(backend)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(RecurringRules::EndsAt).timestamp_for(backend))
+                    .col(ColumnDef::new(RecurringRules::MaxOccurrences).integer())
+                    .col(
+                        ColumnDef::new(RecurringRules::Occurrences)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(RecurringRules::NextOccurrenceAt).timestamp_for(backend))
+                    .col(
+                        ColumnDef::new(RecurringRules::Active)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(RecurringRules::CreatedBy)
+                            .string()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-recurring_rules-vault_id")
+                            .from(RecurringRules::Table, RecurringRules::VaultId)
+                            .to(Vaults::Table, Vaults::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-recurring_rules-active-next_occurrence_at")
+                    .table(RecurringRules::Table)
+                    .col(RecurringRules::Active)
+                    .col(RecurringRules::NextOccurrenceAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-recurring_rules-vault_id")
+                    .table(RecurringRules::Table)
+                    .col(RecurringRules::VaultId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RecurringRules::Table).to_owned())
+            .await
+    }
+}
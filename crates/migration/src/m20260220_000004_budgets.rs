@@ -0,0 +1,107 @@
+use sea_orm_migration::prelude::*;
+
+use crate::column_types::BackendColumnDef;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum Budgets {
+    Table,
+    Id,
+    VaultId,
+    CategoryId,
+    AmountMinor,
+    Period,
+    StartsOn,
+    Rollover,
+}
+
+#[derive(Iden)]
+enum Vaults {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Categories {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let backend = manager.get_database_backend();
+        manager
+            .create_table(
+                Table::create()
+                    .table(Budgets::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Budgets::Id)
+                            .uuid_for(backend)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Budgets::VaultId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Budgets::CategoryId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Budgets::AmountMinor)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Budgets::Period).string().not_null())
+                    .col(ColumnDef::new(Budgets::StartsOn).date().not_null())
+                    .col(
+                        ColumnDef::new(Budgets::Rollover)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-budgets-vault_id")
+                            .from(Budgets::Table, Budgets::VaultId)
+                            .to(Vaults::Table, Vaults::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-budgets-category_id")
+                            .from(Budgets::Table, Budgets::CategoryId)
+                            .to(Categories::Table, Categories::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // One budget per category.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-budgets-vault_id-category_id")
+                    .table(Budgets::Table)
+                    .col(Budgets::VaultId)
+                    .col(Budgets::CategoryId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Budgets::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,131 @@
+use sea_orm_migration::prelude::*;
+
+use crate::column_types::BackendColumnDef;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum CategoryRules {
+    Table,
+    Id,
+    VaultId,
+    CategoryId,
+    Priority,
+    NoteContains,
+    NoteRegex,
+    MinAmountMinor,
+    MaxAmountMinor,
+    WalletId,
+    FlowId,
+    Kind,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum Vaults {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Categories {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let backend = manager.get_database_backend();
+        manager
+            .create_table(
+                Table::create()
+                    .table(CategoryRules::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CategoryRules::Id)
+                            .uuid_for(backend)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CategoryRules::VaultId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CategoryRules::CategoryId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CategoryRules::Priority)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(CategoryRules::NoteContains).string().null())
+                    .col(ColumnDef::new(CategoryRules::NoteRegex).string().null())
+                    .col(
+                        ColumnDef::new(CategoryRules::MinAmountMinor)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(CategoryRules::MaxAmountMinor)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(CategoryRules::WalletId)
+                            .uuid_for(backend)
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(CategoryRules::FlowId)
+                            .uuid_for(backend)
+                            .null(),
+                    )
+                    .col(ColumnDef::new(CategoryRules::Kind).string().null())
+                    .col(
+                        ColumnDef::new(CategoryRules::CreatedAt)
+                            .timestamp_for(backend)
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-category_rules-vault_id")
+                            .from(CategoryRules::Table, CategoryRules::VaultId)
+                            .to(Vaults::Table, Vaults::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-category_rules-category_id")
+                            .from(CategoryRules::Table, CategoryRules::CategoryId)
+                            .to(Categories::Table, Categories::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-category_rules-vault_id-priority")
+                    .table(CategoryRules::Table)
+                    .col(CategoryRules::VaultId)
+                    .col(CategoryRules::Priority)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CategoryRules::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::*;
+
+use crate::column_types::BackendColumnDef;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum Legs {
+    Table,
+    CategoryId,
+    Note,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let backend = manager.get_database_backend();
+        // Split lines: flow legs of a split expense carry their own category
+        // and note. SQLite only supports one column per ALTER TABLE statement.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Legs::Table)
+                    .add_column(ColumnDef::new(Legs::CategoryId).uuid_for(backend))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Legs::Table)
+                    .add_column(ColumnDef::new(Legs::Note).string())
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [Legs::Note, Legs::CategoryId] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Legs::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
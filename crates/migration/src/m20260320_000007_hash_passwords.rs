@@ -0,0 +1,68 @@
+use argon2::{
+    Argon2,
+    password_hash::{PasswordHasher, SaltString, rand_core::OsRng},
+};
+use sea_orm::ConnectionTrait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Username,
+    Password,
+}
+
+fn hash_password(password: &str) -> Result<String, DbErr> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|err| DbErr::Migration(format!("failed to hash password: {err}")))
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Passwords used to be stored in clear text. Replace every value that
+        // is not already an Argon2 PHC string with its Argon2id hash, so the
+        // migration also copes with rows created by an updated admin CLI.
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+
+        let rows = db
+            .query_all(
+                backend.build(
+                    Query::select()
+                        .columns([Users::Username, Users::Password])
+                        .from(Users::Table),
+                ),
+            )
+            .await?;
+        for row in rows {
+            let username: String = row.try_get("", "username")?;
+            let password: String = row.try_get("", "password")?;
+            if password.starts_with("$argon2") {
+                continue;
+            }
+            db.execute(
+                backend.build(
+                    Query::update()
+                        .table(Users::Table)
+                        .value(Users::Password, hash_password(&password)?)
+                        .and_where(Expr::col(Users::Username).eq(username)),
+                ),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // Hashes cannot be turned back into passwords; the previous schema
+        // stores them as opaque strings, so there is nothing to undo.
+        Ok(())
+    }
+}
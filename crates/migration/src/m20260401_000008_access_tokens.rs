@@ -0,0 +1,98 @@
+use sea_orm_migration::prelude::*;
+
+use crate::column_types::BackendColumnDef;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum AccessTokens {
+    Table,
+    Id,
+    Username,
+    Name,
+    TokenHash,
+    Scope,
+    VaultId,
+    CreatedAt,
+    ExpiresAt,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Username,
+}
+
+#[derive(Iden)]
+enum Vaults {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let backend = manager.get_database_backend();
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessTokens::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccessTokens::Id)
+                            .uuid_for(backend)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AccessTokens::Username).string().not_null())
+                    .col(ColumnDef::new(AccessTokens::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(AccessTokens::TokenHash)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(AccessTokens::Scope).string().not_null())
+                    .col(ColumnDef::new(AccessTokens::VaultId).uuid_for(backend))
+                    .col(
+                        ColumnDef::new(AccessTokens::CreatedAt)
+                            .timestamp_for(backend)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AccessTokens::ExpiresAt).timestamp_for(backend))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-access_tokens-username")
+                            .from(AccessTokens::Table, AccessTokens::Username)
+                            .to(Users::Table, Users::Username)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-access_tokens-vault_id")
+                            .from(AccessTokens::Table, AccessTokens::VaultId)
+                            .to(Vaults::Table, Vaults::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-access_tokens-username")
+                    .table(AccessTokens::Table)
+                    .col(AccessTokens::Username)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccessTokens::Table).to_owned())
+            .await
+    }
+}
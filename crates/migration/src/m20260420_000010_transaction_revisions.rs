@@ -0,0 +1,127 @@
+use sea_orm_migration::prelude::*;
+
+use crate::column_types::BackendColumnDef;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum TransactionRevisions {
+    Table,
+    Id,
+    TransactionId,
+    Revision,
+    OccurredAt,
+    AmountMinor,
+    Currency,
+    OriginalAmountMinor,
+    OriginalCurrency,
+    ExchangeRate,
+    CategoryId,
+    Category,
+    Note,
+    Legs,
+    ReplacedBy,
+    ReplacedAt,
+}
+
+#[derive(Iden)]
+enum Transactions {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let backend = manager.get_database_backend();
+        manager
+            .create_table(
+                Table::create()
+                    .table(TransactionRevisions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TransactionRevisions::Id)
+                            .uuid_for(backend)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TransactionRevisions::TransactionId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TransactionRevisions::Revision)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TransactionRevisions::OccurredAt)
+                            .timestamp_for(backend)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TransactionRevisions::AmountMinor)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TransactionRevisions::Currency)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TransactionRevisions::OriginalAmountMinor).big_integer())
+                    .col(ColumnDef::new(TransactionRevisions::OriginalCurrency).string())
+                    .col(ColumnDef::new(TransactionRevisions::ExchangeRate).string())
+                    .col(
+                        ColumnDef::new(TransactionRevisions::CategoryId)
+                            .uuid_for(backend)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TransactionRevisions::Category).string())
+                    .col(ColumnDef::new(TransactionRevisions::Note).string())
+                    .col(ColumnDef::new(TransactionRevisions::Legs).json().not_null())
+                    .col(
+                        ColumnDef::new(TransactionRevisions::ReplacedBy)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TransactionRevisions::ReplacedAt)
+                            .timestamp_for(backend)
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-transaction_revisions-transaction_id")
+                            .from(
+                                TransactionRevisions::Table,
+                                TransactionRevisions::TransactionId,
+                            )
+                            .to(Transactions::Table, Transactions::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-transaction_revisions-transaction_id-revision")
+                    .table(TransactionRevisions::Table)
+                    .col(TransactionRevisions::TransactionId)
+                    .col(TransactionRevisions::Revision)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TransactionRevisions::Table).to_owned())
+            .await
+    }
+}
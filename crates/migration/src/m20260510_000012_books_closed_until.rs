@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+use crate::column_types::BackendColumnDef;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum Vaults {
+    Table,
+    BooksClosedUntil,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let backend = manager.get_database_backend();
+        // Transactions that occurred before it can no longer be created,
+        // changed or voided. `NULL` keeps every period open.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Vaults::Table)
+                    .add_column(
+                        ColumnDef::new(Vaults::BooksClosedUntil)
+                            .timestamp_for(backend)
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Vaults::Table)
+                    .drop_column(Vaults::BooksClosedUntil)
+                    .to_owned(),
+            )
+            .await
+    }
+}
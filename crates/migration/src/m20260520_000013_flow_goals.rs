@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum CashFlows {
+    Table,
+    TargetMinor,
+    TargetDate,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Optional savings goal: an amount to reach, optionally by a date.
+        // SQLite only accepts one column per `ALTER TABLE`.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CashFlows::Table)
+                    .add_column(ColumnDef::new(CashFlows::TargetMinor).big_integer().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CashFlows::Table)
+                    .add_column(ColumnDef::new(CashFlows::TargetDate).date().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [CashFlows::TargetDate, CashFlows::TargetMinor] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(CashFlows::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use sea_orm_migration::prelude::*;
+
+use crate::column_types::BackendColumnDef;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    PairCode,
+    PairCodeExpiresAt,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let backend = manager.get_database_backend();
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(
+                        ColumnDef::new(Users::PairCodeExpiresAt)
+                            .timestamp_for(backend)
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // `pair_code` now holds the digest of a generated code. Codes typed in
+        // by an admin would never match it, and have no expiry: drop them.
+        manager
+            .get_connection()
+            .execute(
+                backend.build(
+                    Query::update()
+                        .table(Users::Table)
+                        .value(Users::PairCode, Option::<String>::None),
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::PairCodeExpiresAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum RecurringRules {
+    Table,
+    LastError,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Why the scheduler paused the rule; cleared when it is resumed.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RecurringRules::Table)
+                    .add_column(ColumnDef::new(RecurringRules::LastError).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RecurringRules::Table)
+                    .drop_column(RecurringRules::LastError)
+                    .to_owned(),
+            )
+            .await
+    }
+}
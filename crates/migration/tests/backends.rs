@@ -0,0 +1,229 @@
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+//! Runs the migrations on SQLite and, when `SPARAGNE_TEST_POSTGRES_URL` points
+//! at a Postgres server (e.g. `postgres://postgres@127.0.0.1`), on Postgres.
+
+use migration::{Alias, Expr, MigratorTrait, Query};
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, DbBackend, prelude::DateTimeUtc};
+use uuid::Uuid;
+
+const POSTGRES_URL_ENV: &str = "SPARAGNE_TEST_POSTGRES_URL";
+
+async fn postgres_db() -> Option<DatabaseConnection> {
+    let server_url = std::env::var(POSTGRES_URL_ENV).ok()?;
+    let server_url = server_url.trim_end_matches('/');
+    let name = format!("sparagne_test_{}", Uuid::new_v4().simple());
+    let server = Database::connect(format!("{server_url}/postgres"))
+        .await
+        .unwrap();
+    server
+        .execute_unprepared(&format!("CREATE DATABASE {name}"))
+        .await
+        .unwrap();
+    server.close().await.unwrap();
+    Some(
+        Database::connect(format!("{server_url}/{name}"))
+            .await
+            .unwrap(),
+    )
+}
+
+async fn column_type(db: &DatabaseConnection, table: &str, column: &str) -> String {
+    let query = Query::select()
+        .column(Alias::new("data_type"))
+        .from((Alias::new("information_schema"), Alias::new("columns")))
+        .and_where(Expr::col(Alias::new("table_name")).eq(table))
+        .and_where(Expr::col(Alias::new("column_name")).eq(column))
+        .to_owned();
+    db.query_one(db.get_database_backend().build(&query))
+        .await
+        .unwrap()
+        .expect("column missing")
+        .try_get("", "data_type")
+        .unwrap()
+}
+
+/// Applies every migration, rolls them all back and applies them again.
+async fn round_trip(db: &DatabaseConnection) {
+    migration::Migrator::up(db, None).await.unwrap();
+    assert!(
+        migration::Migrator::get_pending_migrations(db)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    migration::Migrator::down(db, None).await.unwrap();
+    assert_eq!(
+        migration::Migrator::get_applied_migrations(db)
+            .await
+            .unwrap()
+            .len(),
+        0
+    );
+    migration::Migrator::up(db, None).await.unwrap();
+}
+
+/// Data migrations must rewrite existing rows: plain passwords get hashed.
+async fn hashes_existing_passwords(db: &DatabaseConnection) {
+    let backend = db.get_database_backend();
+    let before_hashing = migration::Migrator::migrations()
+        .iter()
+        .position(|m| m.name() == "m20260320_000007_hash_passwords")
+        .unwrap();
+    migration::Migrator::up(db, Some(before_hashing as u32))
+        .await
+        .unwrap();
+    let insert = Query::insert()
+        .into_table(Alias::new("users"))
+        .columns([Alias::new("username"), Alias::new("password")])
+        .values_panic(["alice".into(), "secret".into()])
+        .to_owned();
+    db.execute(backend.build(&insert)).await.unwrap();
+
+    migration::Migrator::up(db, None).await.unwrap();
+
+    let select = Query::select()
+        .column(Alias::new("password"))
+        .from(Alias::new("users"))
+        .and_where(Expr::col(Alias::new("username")).eq("alice"))
+        .to_owned();
+    let password: String = db
+        .query_one(backend.build(&select))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get("", "password")
+        .unwrap();
+    assert!(password.starts_with("$argon2"));
+    assert!(verify_password_hash(&password, "secret"));
+}
+
+/// Free-text categories of existing transactions become category rows.
+async fn backfills_categories(db: &DatabaseConnection) {
+    let backend = db.get_database_backend();
+    migration::Migrator::up(db, Some(1)).await.unwrap();
+    let vault_id = Uuid::new_v4();
+    let tx_id = Uuid::new_v4();
+    let occurred_at: DateTimeUtc = "2026-01-10T12:00:00Z".parse().unwrap();
+    let inserts = [
+        Query::insert()
+            .into_table(Alias::new("users"))
+            .columns([Alias::new("username"), Alias::new("password")])
+            .values_panic(["alice".into(), "secret".into()])
+            .to_owned(),
+        Query::insert()
+            .into_table(Alias::new("vaults"))
+            .columns([
+                Alias::new("id"),
+                Alias::new("name"),
+                Alias::new("user_id"),
+                Alias::new("currency"),
+            ])
+            .values_panic([vault_id.into(), "Main".into(), "alice".into(), "EUR".into()])
+            .to_owned(),
+        Query::insert()
+            .into_table(Alias::new("transactions"))
+            .columns([
+                Alias::new("id"),
+                Alias::new("vault_id"),
+                Alias::new("kind"),
+                Alias::new("occurred_at"),
+                Alias::new("amount_minor"),
+                Alias::new("currency"),
+                Alias::new("category"),
+                Alias::new("created_by"),
+            ])
+            .values_panic([
+                tx_id.into(),
+                vault_id.into(),
+                "expense".into(),
+                occurred_at.into(),
+                1200i64.into(),
+                "EUR".into(),
+                " Food ".into(),
+                "alice".into(),
+            ])
+            .to_owned(),
+    ];
+    for insert in &inserts {
+        db.execute(backend.build(insert)).await.unwrap();
+    }
+
+    migration::Migrator::up(db, None).await.unwrap();
+
+    let select = Query::select()
+        .column((Alias::new("categories"), Alias::new("name")))
+        .from(Alias::new("transactions"))
+        .inner_join(
+            Alias::new("categories"),
+            Expr::col((Alias::new("categories"), Alias::new("id")))
+                .equals((Alias::new("transactions"), Alias::new("category_id"))),
+        )
+        .and_where(Expr::col((Alias::new("transactions"), Alias::new("id"))).eq(tx_id))
+        .to_owned();
+    let name: String = db
+        .query_one(backend.build(&select))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get("", "name")
+        .unwrap();
+    assert_eq!(name, "Food");
+}
+
+fn verify_password_hash(hash: &str, password: &str) -> bool {
+    use argon2::{Argon2, PasswordHash, PasswordVerifier};
+    let hash = PasswordHash::new(hash).unwrap();
+    Argon2::default()
+        .verify_password(password.as_bytes(), &hash)
+        .is_ok()
+}
+
+#[tokio::test]
+async fn migrations_round_trip_on_sqlite() {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    round_trip(&db).await;
+}
+
+#[tokio::test]
+async fn migrations_hash_existing_passwords_on_sqlite() {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    hashes_existing_passwords(&db).await;
+}
+
+#[tokio::test]
+async fn migrations_backfill_categories_on_sqlite() {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    backfills_categories(&db).await;
+}
+
+#[tokio::test]
+async fn migrations_round_trip_on_postgres() {
+    let Some(db) = postgres_db().await else {
+        return;
+    };
+    assert_eq!(db.get_database_backend(), DbBackend::Postgres);
+    round_trip(&db).await;
+
+    assert_eq!(column_type(&db, "transactions", "id").await, "uuid");
+    assert_eq!(
+        column_type(&db, "transactions", "occurred_at").await,
+        "timestamp with time zone"
+    );
+}
+
+#[tokio::test]
+async fn migrations_hash_existing_passwords_on_postgres() {
+    let Some(db) = postgres_db().await else {
+        return;
+    };
+    hashes_existing_passwords(&db).await;
+}
+
+#[tokio::test]
+async fn migrations_backfill_categories_on_postgres() {
+    let Some(db) = postgres_db().await else {
+        return;
+    };
+    backfills_categories(&db).await;
+}
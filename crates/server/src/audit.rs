@@ -0,0 +1,91 @@
+//! Vault audit log endpoint (owner-only).
+
+use api_types::audit::{AuditAction, AuditEntity, AuditEntryView, AuditListResponse, AuditQuery};
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+};
+use chrono::FixedOffset;
+
+use crate::{ServerError, server::ServerState, user};
+
+fn action_to_api(action: engine::AuditAction) -> AuditAction {
+    match action {
+        engine::AuditAction::Create => AuditAction::Create,
+        engine::AuditAction::Update => AuditAction::Update,
+        engine::AuditAction::Delete => AuditAction::Delete,
+        engine::AuditAction::Void => AuditAction::Void,
+        engine::AuditAction::Restore => AuditAction::Restore,
+        engine::AuditAction::Merge => AuditAction::Merge,
+        engine::AuditAction::Import => AuditAction::Import,
+        engine::AuditAction::Reconcile => AuditAction::Reconcile,
+    }
+}
+
+fn entity_to_api(entity: engine::AuditEntity) -> AuditEntity {
+    match entity {
+        engine::AuditEntity::Vault => AuditEntity::Vault,
+        engine::AuditEntity::Wallet => AuditEntity::Wallet,
+        engine::AuditEntity::Flow => AuditEntity::Flow,
+        engine::AuditEntity::Transaction => AuditEntity::Transaction,
+        engine::AuditEntity::Category => AuditEntity::Category,
+        engine::AuditEntity::CategoryAlias => AuditEntity::CategoryAlias,
+        engine::AuditEntity::CategoryRule => AuditEntity::CategoryRule,
+        engine::AuditEntity::Budget => AuditEntity::Budget,
+        engine::AuditEntity::RecurringRule => AuditEntity::RecurringRule,
+        engine::AuditEntity::VaultMember => AuditEntity::VaultMember,
+        engine::AuditEntity::FlowMember => AuditEntity::FlowMember,
+        engine::AuditEntity::AccessToken => AuditEntity::AccessToken,
+    }
+}
+
+fn entity_from_api(entity: AuditEntity) -> engine::AuditEntity {
+    match entity {
+        AuditEntity::Vault => engine::AuditEntity::Vault,
+        AuditEntity::Wallet => engine::AuditEntity::Wallet,
+        AuditEntity::Flow => engine::AuditEntity::Flow,
+        AuditEntity::Transaction => engine::AuditEntity::Transaction,
+        AuditEntity::Category => engine::AuditEntity::Category,
+        AuditEntity::CategoryAlias => engine::AuditEntity::CategoryAlias,
+        AuditEntity::CategoryRule => engine::AuditEntity::CategoryRule,
+        AuditEntity::Budget => engine::AuditEntity::Budget,
+        AuditEntity::RecurringRule => engine::AuditEntity::RecurringRule,
+        AuditEntity::VaultMember => engine::AuditEntity::VaultMember,
+        AuditEntity::FlowMember => engine::AuditEntity::FlowMember,
+        AuditEntity::AccessToken => engine::AuditEntity::AccessToken,
+    }
+}
+
+pub async fn list(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(vault_id): Path<String>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<AuditListResponse>, ServerError> {
+    let mut cmd = engine::AuditListCmd::new(vault_id, user.username, query.limit.unwrap_or(50));
+    cmd.actor = query.actor;
+    cmd.entity = query.entity.map(entity_from_api);
+    cmd.entity_id = query.entity_id;
+    cmd.cursor = query.cursor;
+
+    let (entries, next_cursor) = state.engine.list_audit(cmd).await?;
+    let utc = FixedOffset::east_opt(0)
+        .ok_or_else(|| ServerError::Generic("invalid UTC offset".to_string()))?;
+    let entries = entries
+        .into_iter()
+        .map(|entry| AuditEntryView {
+            id: entry.id,
+            actor: entry.actor,
+            action: action_to_api(entry.action),
+            entity: entity_to_api(entry.entity),
+            entity_id: entry.entity_id,
+            before: entry.before,
+            after: entry.after,
+            created_at: entry.created_at.with_timezone(&utc),
+        })
+        .collect();
+    Ok(Json(AuditListResponse {
+        entries,
+        next_cursor,
+    }))
+}
@@ -0,0 +1,100 @@
+//! Point-in-time balance endpoints.
+
+use api_types::balance::{
+    BalanceHistoryQuery, BalanceHistoryResponse, BalanceInterval, BalancePointView, BalanceQuery,
+    BalanceResponse,
+};
+use axum::{Extension, Json, extract::State};
+use chrono::{FixedOffset, Utc};
+use uuid::Uuid;
+
+use crate::{ServerError, currency, server::ServerState, user};
+
+fn target(
+    wallet_id: Option<Uuid>,
+    flow_id: Option<Uuid>,
+) -> Result<engine::BalanceTarget, ServerError> {
+    match (wallet_id, flow_id) {
+        (None, None) => Ok(engine::BalanceTarget::Vault),
+        (Some(wallet_id), None) => Ok(engine::BalanceTarget::Wallet { wallet_id }),
+        (None, Some(flow_id)) => Ok(engine::BalanceTarget::Flow { flow_id }),
+        (Some(_), Some(_)) => Err(ServerError::Generic(
+            "set at most one of wallet_id and flow_id".to_string(),
+        )),
+    }
+}
+
+fn interval_from_api(interval: BalanceInterval) -> engine::BalanceInterval {
+    match interval {
+        BalanceInterval::Day => engine::BalanceInterval::Day,
+        BalanceInterval::Week => engine::BalanceInterval::Week,
+        BalanceInterval::Month => engine::BalanceInterval::Month,
+    }
+}
+
+fn map_interval(interval: engine::BalanceInterval) -> BalanceInterval {
+    match interval {
+        engine::BalanceInterval::Day => BalanceInterval::Day,
+        engine::BalanceInterval::Week => BalanceInterval::Week,
+        engine::BalanceInterval::Month => BalanceInterval::Month,
+    }
+}
+
+pub async fn balance(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<BalanceQuery>,
+) -> Result<Json<BalanceResponse>, ServerError> {
+    let target = target(payload.wallet_id, payload.flow_id)?;
+    let balance = state
+        .engine
+        .balance_at(
+            &payload.vault_id,
+            target,
+            payload.at.with_timezone(&Utc),
+            &user.username,
+        )
+        .await?;
+    Ok(Json(BalanceResponse {
+        currency: currency::to_api(balance.currency),
+        balance_minor: balance.balance_minor,
+    }))
+}
+
+pub async fn history(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<BalanceHistoryQuery>,
+) -> Result<Json<BalanceHistoryResponse>, ServerError> {
+    let utc_offset = payload
+        .utc_offset_minutes
+        .unwrap_or(0)
+        .checked_mul(60)
+        .and_then(FixedOffset::east_opt)
+        .ok_or_else(|| ServerError::Generic("invalid utc_offset_minutes".to_string()))?;
+
+    let cmd = engine::BalanceHistoryCmd::new(
+        payload.vault_id,
+        user.username,
+        target(payload.wallet_id, payload.flow_id)?,
+        payload.from.with_timezone(&Utc),
+        payload.to.with_timezone(&Utc),
+        interval_from_api(payload.interval),
+    )
+    .utc_offset(utc_offset);
+
+    let history = state.engine.balance_history(cmd).await?;
+    Ok(Json(BalanceHistoryResponse {
+        currency: currency::to_api(history.currency),
+        interval: map_interval(history.interval),
+        opening_minor: history.opening_minor,
+        points: history
+            .points
+            .into_iter()
+            .map(|point| BalancePointView {
+                period_start: point.period_start,
+                balance_minor: point.balance_minor,
+            })
+            .collect(),
+    }))
+}
@@ -0,0 +1,136 @@
+//! Budget API endpoints.
+
+use api_types::budget::{
+    BudgetDelete, BudgetList, BudgetListResponse, BudgetNew, BudgetPeriod, BudgetUpdate, BudgetView,
+};
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::{NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::{ServerError, server::ServerState, user};
+
+fn map_period(period: engine::BudgetPeriod) -> BudgetPeriod {
+    match period {
+        engine::BudgetPeriod::Weekly => BudgetPeriod::Weekly,
+        engine::BudgetPeriod::Monthly => BudgetPeriod::Monthly,
+        engine::BudgetPeriod::Yearly => BudgetPeriod::Yearly,
+    }
+}
+
+fn period_from_api(period: BudgetPeriod) -> engine::BudgetPeriod {
+    match period {
+        BudgetPeriod::Weekly => engine::BudgetPeriod::Weekly,
+        BudgetPeriod::Monthly => engine::BudgetPeriod::Monthly,
+        BudgetPeriod::Yearly => engine::BudgetPeriod::Yearly,
+    }
+}
+
+fn map_status(status: engine::BudgetStatus) -> BudgetView {
+    BudgetView {
+        id: status.budget.id,
+        category_id: status.budget.category_id,
+        amount_minor: status.budget.amount_minor,
+        period: map_period(status.budget.period),
+        starts_on: status.budget.starts_on,
+        rollover: status.budget.rollover,
+        period_start: status.period_start,
+        period_end: status.period_end,
+        spent_minor: status.spent_minor,
+        carried_minor: status.carried_minor,
+        available_minor: status.available_minor,
+        remaining_minor: status.remaining_minor,
+    }
+}
+
+fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+pub async fn list(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<BudgetList>,
+) -> Result<Json<BudgetListResponse>, ServerError> {
+    let budgets = state
+        .engine
+        .budget_statuses(
+            &payload.vault_id,
+            &user.username,
+            payload.on.unwrap_or_else(today),
+        )
+        .await?
+        .into_iter()
+        .map(map_status)
+        .collect();
+    Ok(Json(BudgetListResponse { budgets }))
+}
+
+pub async fn create(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<BudgetNew>,
+) -> Result<(StatusCode, Json<BudgetView>), ServerError> {
+    let vault_id = payload.vault_id.clone();
+    let budget = state
+        .engine
+        .create_budget(
+            engine::CreateBudgetCmd::new(
+                payload.vault_id,
+                user.username.clone(),
+                payload.category_id,
+                payload.amount_minor,
+                period_from_api(payload.period),
+                payload.starts_on,
+            )
+            .rollover(payload.rollover),
+        )
+        .await?;
+    let status = state
+        .engine
+        .budget_status(&vault_id, budget.id, &user.username, today())
+        .await?;
+    Ok((StatusCode::CREATED, Json(map_status(status))))
+}
+
+pub async fn update(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(budget_id): Path<Uuid>,
+    Json(payload): Json<BudgetUpdate>,
+) -> Result<Json<BudgetView>, ServerError> {
+    let vault_id = payload.vault_id.clone();
+    state
+        .engine
+        .update_budget(engine::UpdateBudgetCmd {
+            vault_id: payload.vault_id,
+            budget_id,
+            user_id: user.username.clone(),
+            amount_minor: payload.amount_minor,
+            period: payload.period.map(period_from_api),
+            starts_on: payload.starts_on,
+            rollover: payload.rollover,
+        })
+        .await?;
+    let status = state
+        .engine
+        .budget_status(&vault_id, budget_id, &user.username, today())
+        .await?;
+    Ok(Json(map_status(status)))
+}
+
+pub async fn delete(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(budget_id): Path<Uuid>,
+    Json(payload): Json<BudgetDelete>,
+) -> Result<StatusCode, ServerError> {
+    state
+        .engine
+        .delete_budget(&payload.vault_id, budget_id, &user.username)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,16 +1,15 @@
 //! CashFlow API endpoints
 
-use api_types::cash_flow::CashFlowGet;
+use api_types::cash_flow::{CashFlowGet, CashFlowView};
 use axum::{Extension, Json, extract::State};
-use engine::CashFlow;
 
-use crate::{ServerError, server::ServerState, user};
+use crate::{ServerError, currency, server::ServerState, user};
 
 pub async fn get(
     Extension(user): Extension<user::Model>,
     State(state): State<ServerState>,
     Json(payload): Json<CashFlowGet>,
-) -> Result<Json<CashFlow>, ServerError> {
+) -> Result<Json<CashFlowView>, ServerError> {
     let flow = match (payload.id, payload.name.as_deref()) {
         (Some(id), _) => {
             state
@@ -31,5 +30,15 @@ pub async fn get(
         }
     };
 
-    Ok(Json(flow))
+    Ok(Json(CashFlowView {
+        id: flow.id,
+        name: flow.name,
+        balance: flow.balance,
+        max_balance: flow.max_balance,
+        income_balance: flow.income_balance,
+        currency: currency::to_api(flow.currency),
+        archived: flow.archived,
+        target_minor: flow.target_minor,
+        target_date: flow.target_date,
+    }))
 }
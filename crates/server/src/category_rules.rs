@@ -0,0 +1,185 @@
+//! Auto-categorization rule API endpoints.
+
+use api_types::category_rule::{
+    CategoryRuleApply, CategoryRuleApplyResponse, CategoryRuleConditions, CategoryRuleDelete,
+    CategoryRuleHitView, CategoryRuleList, CategoryRuleListResponse, CategoryRuleNew,
+    CategoryRuleSkip, CategoryRuleUpdate, CategoryRuleView,
+};
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::{FixedOffset, Utc};
+use uuid::Uuid;
+
+use crate::{
+    ServerError,
+    server::ServerState,
+    transactions::{kind_from_api, map_kind},
+    user,
+};
+
+fn utc_offset() -> Result<FixedOffset, ServerError> {
+    FixedOffset::east_opt(0).ok_or_else(|| ServerError::Generic("invalid UTC offset".to_string()))
+}
+
+fn conditions_from_api(conditions: CategoryRuleConditions) -> engine::CategoryRuleConditions {
+    engine::CategoryRuleConditions {
+        note_contains: conditions.note_contains,
+        note_regex: conditions.note_regex,
+        min_amount_minor: conditions.min_amount_minor,
+        max_amount_minor: conditions.max_amount_minor,
+        wallet_id: conditions.wallet_id,
+        flow_id: conditions.flow_id,
+        kind: conditions.kind.map(kind_from_api),
+    }
+}
+
+fn map_rule(rule: engine::CategoryRule) -> Result<CategoryRuleView, ServerError> {
+    let conditions = rule.conditions;
+    Ok(CategoryRuleView {
+        id: rule.id,
+        category_id: rule.category_id,
+        priority: rule.priority,
+        conditions: CategoryRuleConditions {
+            note_contains: conditions.note_contains,
+            note_regex: conditions.note_regex,
+            min_amount_minor: conditions.min_amount_minor,
+            max_amount_minor: conditions.max_amount_minor,
+            wallet_id: conditions.wallet_id,
+            flow_id: conditions.flow_id,
+            kind: conditions.kind.map(map_kind),
+        },
+        created_at: rule.created_at.with_timezone(&utc_offset()?),
+    })
+}
+
+fn apply_cmd(user: user::Model, payload: CategoryRuleApply) -> engine::ApplyCategoryRulesCmd {
+    let mut cmd = engine::ApplyCategoryRulesCmd::new(payload.vault_id, user.username)
+        .include_categorized(payload.include_categorized);
+    cmd.from = payload.from.map(|at| at.with_timezone(&Utc));
+    cmd.to = payload.to.map(|at| at.with_timezone(&Utc));
+    cmd
+}
+
+fn map_skip(skip: engine::CategoryRuleSkip) -> CategoryRuleSkip {
+    match skip {
+        engine::CategoryRuleSkip::Reconciled => CategoryRuleSkip::Reconciled,
+        engine::CategoryRuleSkip::BooksClosed => CategoryRuleSkip::BooksClosed,
+    }
+}
+
+fn apply_response(
+    hits: Vec<engine::CategoryRuleHit>,
+    applied: bool,
+) -> Result<CategoryRuleApplyResponse, ServerError> {
+    let utc = utc_offset()?;
+    let hits = hits
+        .into_iter()
+        .map(|hit| CategoryRuleHitView {
+            transaction_id: hit.transaction_id,
+            occurred_at: hit.occurred_at.with_timezone(&utc),
+            kind: map_kind(hit.kind),
+            amount_minor: hit.amount_minor,
+            note: hit.note,
+            previous_category_id: hit.previous_category_id,
+            rule_id: hit.rule_id,
+            category_id: hit.category_id,
+            skipped: hit.skipped.map(map_skip),
+        })
+        .collect();
+    Ok(CategoryRuleApplyResponse { applied, hits })
+}
+
+pub async fn list(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<CategoryRuleList>,
+) -> Result<Json<CategoryRuleListResponse>, ServerError> {
+    let rules = state
+        .engine
+        .list_category_rules(&payload.vault_id, &user.username)
+        .await?
+        .into_iter()
+        .map(map_rule)
+        .collect::<Result<_, _>>()?;
+    Ok(Json(CategoryRuleListResponse { rules }))
+}
+
+pub async fn create(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<CategoryRuleNew>,
+) -> Result<(StatusCode, Json<CategoryRuleView>), ServerError> {
+    let rule = state
+        .engine
+        .create_category_rule(
+            engine::CreateCategoryRuleCmd::new(
+                payload.vault_id,
+                user.username,
+                payload.category_id,
+                conditions_from_api(payload.conditions),
+            )
+            .priority(payload.priority),
+        )
+        .await?;
+    Ok((StatusCode::CREATED, Json(map_rule(rule)?)))
+}
+
+pub async fn update(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(rule_id): Path<Uuid>,
+    Json(payload): Json<CategoryRuleUpdate>,
+) -> Result<Json<CategoryRuleView>, ServerError> {
+    let rule = state
+        .engine
+        .update_category_rule(engine::UpdateCategoryRuleCmd {
+            vault_id: payload.vault_id,
+            rule_id,
+            user_id: user.username,
+            category_id: payload.category_id,
+            priority: payload.priority,
+            conditions: payload.conditions.map(conditions_from_api),
+        })
+        .await?;
+    Ok(Json(map_rule(rule)?))
+}
+
+pub async fn delete(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(rule_id): Path<Uuid>,
+    Json(payload): Json<CategoryRuleDelete>,
+) -> Result<StatusCode, ServerError> {
+    state
+        .engine
+        .delete_category_rule(&payload.vault_id, rule_id, &user.username)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn preview(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<CategoryRuleApply>,
+) -> Result<Json<CategoryRuleApplyResponse>, ServerError> {
+    let hits = state
+        .engine
+        .preview_category_rules(apply_cmd(user, payload))
+        .await?;
+    Ok(Json(apply_response(hits, false)?))
+}
+
+pub async fn apply(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<CategoryRuleApply>,
+) -> Result<Json<CategoryRuleApplyResponse>, ServerError> {
+    let hits = state
+        .engine
+        .apply_category_rules(apply_cmd(user, payload))
+        .await?;
+    Ok(Json(apply_response(hits, true)?))
+}
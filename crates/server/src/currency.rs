@@ -0,0 +1,31 @@
+//! Currency mapping between engine and API types.
+
+use crate::ServerError;
+
+/// Generates the engine <-> API currency mappings from a single variant list,
+/// so a variant missing on either side fails to compile.
+macro_rules! currency_mapping {
+    ($($variant:ident),+ $(,)?) => {
+        pub(crate) fn to_api(currency: engine::Currency) -> api_types::Currency {
+            match currency {
+                $(engine::Currency::$variant => api_types::Currency::$variant,)+
+            }
+        }
+
+        pub(crate) fn from_api(currency: api_types::Currency) -> engine::Currency {
+            match currency {
+                $(api_types::Currency::$variant => engine::Currency::$variant,)+
+            }
+        }
+    };
+}
+
+currency_mapping!(
+    Eur, Usd, Gbp, Chf, Jpy, Cad, Aud, Nzd, Sek, Nok, Dkk, Pln, Czk, Huf, Ron, Cny, Inr, Krw, Brl,
+    Mxn, Bhd, Kwd, Omr, Jod, Tnd,
+);
+
+/// Parses an optional decimal exchange rate from a request payload.
+pub(crate) fn parse_rate(rate: Option<&str>) -> Result<Option<engine::ExchangeRate>, ServerError> {
+    Ok(rate.map(engine::ExchangeRate::parse).transpose()?)
+}
@@ -1,12 +1,12 @@
 //! Flows API endpoints.
 
-use api_types::flow::{FlowCreated, FlowMode, FlowNew, FlowUpdate};
+use api_types::flow::{FlowCreated, FlowGoal, FlowMode, FlowNew, FlowUpdate};
 use axum::{
     Extension, Json,
     extract::{Path, State},
     http::StatusCode,
 };
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 use uuid::Uuid;
 
 use crate::{ServerError, server::ServerState, user};
@@ -19,6 +19,16 @@ fn map_mode(mode: FlowMode) -> Result<(Option<i64>, Option<bool>), ServerError>
     }
 }
 
+fn map_goal(goal: FlowGoal) -> (Option<i64>, Option<NaiveDate>) {
+    match goal {
+        FlowGoal::Unset => (None, None),
+        FlowGoal::Target {
+            target_minor,
+            target_date,
+        } => (Some(target_minor), target_date),
+    }
+}
+
 pub async fn flow_new(
     Extension(user): Extension<user::Model>,
     State(state): State<ServerState>,
@@ -45,6 +55,20 @@ pub async fn flow_new(
         )
         .await?;
 
+    if let Some(goal) = payload.goal {
+        let (target_minor, target_date) = map_goal(goal);
+        state
+            .engine
+            .set_cash_flow_goal(
+                &payload.vault_id,
+                flow_id,
+                target_minor,
+                target_date,
+                &user.username,
+            )
+            .await?;
+    }
+
     if payload.opening_balance_minor > 0 {
         let vault = state
             .engine
@@ -81,9 +105,13 @@ pub async fn flow_update(
     Path(flow_id): Path<Uuid>,
     Json(payload): Json<FlowUpdate>,
 ) -> Result<StatusCode, ServerError> {
-    if payload.name.is_none() && payload.archived.is_none() && payload.mode.is_none() {
+    if payload.name.is_none()
+        && payload.archived.is_none()
+        && payload.mode.is_none()
+        && payload.goal.is_none()
+    {
         return Err(ServerError::Generic(
-            "provide at least one of name, archived, or mode".to_string(),
+            "provide at least one of name, archived, mode, or goal".to_string(),
         ));
     }
 
@@ -112,6 +140,19 @@ pub async fn flow_update(
             )
             .await?;
     }
+    if let Some(goal) = payload.goal {
+        let (target_minor, target_date) = map_goal(goal);
+        state
+            .engine
+            .set_cash_flow_goal(
+                &payload.vault_id,
+                flow_id,
+                target_minor,
+                target_date,
+                &user.username,
+            )
+            .await?;
+    }
 
     Ok(StatusCode::OK)
 }
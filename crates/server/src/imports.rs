@@ -0,0 +1,159 @@
+//! Statement import API endpoints.
+
+use api_types::import::{
+    CsvAmount, CsvColumn, CsvImport, CsvMapping, ImportCommitResponse, ImportPreviewResponse,
+    ImportRowError, ImportRowView, StatementFormat, StatementImport,
+};
+use axum::{Extension, Json, extract::State, http::StatusCode};
+use chrono::FixedOffset;
+
+use crate::{ServerError, server::ServerState, transactions::map_kind, user};
+
+fn column_from_api(column: CsvColumn) -> engine::CsvColumn {
+    match column {
+        CsvColumn::Index(index) => engine::CsvColumn::Index(index),
+        CsvColumn::Name(name) => engine::CsvColumn::Name(name),
+    }
+}
+
+fn mapping_from_api(mapping: CsvMapping) -> engine::CsvMapping {
+    let amount = match mapping.amount {
+        CsvAmount::Signed { column } => engine::CsvAmount::Signed {
+            column: column_from_api(column),
+        },
+        CsvAmount::DebitCredit { debit, credit } => engine::CsvAmount::DebitCredit {
+            debit: column_from_api(debit),
+            credit: column_from_api(credit),
+        },
+    };
+    engine::CsvMapping {
+        delimiter: mapping.delimiter.unwrap_or(','),
+        has_headers: mapping.has_headers.unwrap_or(true),
+        date_column: column_from_api(mapping.date_column),
+        date_format: mapping.date_format,
+        amount,
+        decimal_separator: mapping.decimal_separator.unwrap_or('.'),
+        note_column: mapping.note_column.map(column_from_api),
+        category_column: mapping.category_column.map(column_from_api),
+    }
+}
+
+fn import_cmd(user: user::Model, payload: CsvImport) -> engine::CsvImportCmd {
+    let mut cmd = engine::CsvImportCmd::new(
+        payload.vault_id,
+        user.username,
+        mapping_from_api(payload.mapping),
+        payload.data,
+    )
+    .include_duplicates(payload.include_duplicates);
+    cmd.wallet_id = payload.wallet_id;
+    cmd.flow_id = payload.flow_id;
+    cmd
+}
+
+fn format_from_api(format: StatementFormat) -> engine::StatementFormat {
+    match format {
+        StatementFormat::Ofx => engine::StatementFormat::Ofx,
+        StatementFormat::Qif => engine::StatementFormat::Qif,
+        StatementFormat::Camt053 => engine::StatementFormat::Camt053,
+    }
+}
+
+fn statement_cmd(user: user::Model, payload: StatementImport) -> engine::StatementImportCmd {
+    let mut cmd = engine::StatementImportCmd::new(
+        payload.vault_id,
+        user.username,
+        format_from_api(payload.format),
+        payload.data,
+    )
+    .include_duplicates(payload.include_duplicates);
+    cmd.wallet_id = payload.wallet_id;
+    cmd.flow_id = payload.flow_id;
+    cmd
+}
+
+fn preview_response(preview: engine::ImportPreview) -> Result<ImportPreviewResponse, ServerError> {
+    let utc = FixedOffset::east_opt(0)
+        .ok_or_else(|| ServerError::Generic("invalid UTC offset".to_string()))?;
+    let rows = preview
+        .rows
+        .into_iter()
+        .map(|row| {
+            let meta = row.draft.meta();
+            ImportRowView {
+                line: row.line,
+                kind: map_kind(row.draft.kind()),
+                amount_minor: row.draft.amount_minor(),
+                occurred_at: meta.occurred_at.with_timezone(&utc),
+                category: meta.category.clone(),
+                note: meta.note.clone(),
+                duplicate_of: row.duplicate_of,
+                already_imported: row.already_imported,
+            }
+        })
+        .collect();
+    let errors = preview
+        .errors
+        .into_iter()
+        .map(|err| ImportRowError {
+            line: err.line,
+            message: err.message,
+        })
+        .collect();
+    Ok(ImportPreviewResponse { rows, errors })
+}
+
+fn commit_response(report: engine::ImportReport) -> (StatusCode, Json<ImportCommitResponse>) {
+    (
+        StatusCode::CREATED,
+        Json(ImportCommitResponse {
+            created: report.created,
+            skipped_duplicates: report.skipped_duplicates,
+        }),
+    )
+}
+
+pub async fn preview_csv(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<CsvImport>,
+) -> Result<Json<ImportPreviewResponse>, ServerError> {
+    let preview = state
+        .engine
+        .preview_csv_import(import_cmd(user, payload))
+        .await?;
+    Ok(Json(preview_response(preview)?))
+}
+
+pub async fn commit_csv(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<CsvImport>,
+) -> Result<(StatusCode, Json<ImportCommitResponse>), ServerError> {
+    let report = state.engine.import_csv(import_cmd(user, payload)).await?;
+    Ok(commit_response(report))
+}
+
+pub async fn preview_statement(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<StatementImport>,
+) -> Result<Json<ImportPreviewResponse>, ServerError> {
+    let preview = state
+        .engine
+        .preview_statement_import(statement_cmd(user, payload))
+        .await?;
+    Ok(Json(preview_response(preview)?))
+}
+
+pub async fn commit_statement(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<StatementImport>,
+) -> Result<(StatusCode, Json<ImportCommitResponse>), ServerError> {
+    let report = state
+        .engine
+        .import_statement(statement_cmd(user, payload))
+        .await?;
+    Ok(commit_response(report))
+}
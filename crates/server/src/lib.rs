@@ -1,17 +1,32 @@
-use axum::{Json, http::StatusCode, response::IntoResponse};
+use api_types::error::{ErrorCode, ErrorDetails, ErrorResponse};
+use axum::{
+    Json,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
 use engine::EngineError;
 
-use serde::Serialize;
-pub use server::{run, run_with_listener, spawn_with_listener};
+pub use server::{openapi, run, run_with_listener, spawn_with_listener};
 
+mod audit;
+mod balances;
+mod budgets;
 mod cash_flow;
 mod categories;
+mod category_rules;
+mod currency;
 mod flows;
+mod imports;
 mod memberships;
+mod openapi;
+mod recurring;
+mod reports;
 mod server;
 mod statistics;
+mod tokens;
 mod transactions;
 mod user;
+mod v2;
 mod vault;
 mod wallets;
 
@@ -26,15 +41,28 @@ pub mod types {
     }
 
     pub mod user {
-        pub use api_types::user::PairUser;
+        pub use api_types::user::{PairUser, PairUserResponse};
+    }
+
+    pub mod audit {
+        pub use api_types::audit::{
+            AuditAction, AuditEntity, AuditEntryView, AuditListResponse, AuditQuery,
+        };
+    }
+
+    pub mod access_token {
+        pub use api_types::access_token::{
+            AccessTokenCreated, AccessTokenListResponse, AccessTokenNew, AccessTokenScope,
+            AccessTokenView,
+        };
     }
 
     pub mod transaction {
         pub use api_types::transaction::{
-            ExpenseNew, IncomeNew, LegTarget, TransactionCreated, TransactionDetailResponse,
-            TransactionGet, TransactionHeaderView, TransactionLegView, TransactionList,
-            TransactionListResponse, TransactionUpdate, TransactionView, TransactionVoid,
-            TransferFlowNew, TransferWalletNew,
+            ExpenseNew, IncomeNew, LegTarget, SplitExpenseNew, SplitLineNew, TransactionCreated,
+            TransactionDetailResponse, TransactionGet, TransactionHeaderView, TransactionLegView,
+            TransactionList, TransactionListResponse, TransactionSplitView, TransactionUpdate,
+            TransactionView, TransactionVoid, TransferFlowNew, TransferWalletNew,
         };
     }
 
@@ -42,6 +70,10 @@ pub mod types {
         pub use api_types::stats::Statistic;
     }
 
+    pub mod report {
+        pub use api_types::report::{ReportGroupBy, ReportQuery, ReportResponse, ReportRowView};
+    }
+
     pub mod category {
         pub use api_types::category::{
             CategoryAliasCreate, CategoryAliasCreated, CategoryAliasDelete, CategoryAliasList,
@@ -58,60 +90,217 @@ pub mod types {
     pub mod flow {
         pub use api_types::flow::{FlowCreated, FlowMode, FlowNew, FlowUpdate};
     }
+
+    pub mod budget {
+        pub use api_types::budget::{
+            BudgetDelete, BudgetList, BudgetListResponse, BudgetNew, BudgetPeriod, BudgetUpdate,
+            BudgetView,
+        };
+    }
+
+    pub mod category_rule {
+        pub use api_types::category_rule::{
+            CategoryRuleApply, CategoryRuleApplyResponse, CategoryRuleConditions,
+            CategoryRuleDelete, CategoryRuleHitView, CategoryRuleList, CategoryRuleListResponse,
+            CategoryRuleNew, CategoryRuleSkip, CategoryRuleUpdate, CategoryRuleView,
+        };
+    }
+
+    pub mod import {
+        pub use api_types::import::{
+            CsvAmount, CsvColumn, CsvImport, CsvMapping, ImportCommitResponse,
+            ImportPreviewResponse, ImportRowError, ImportRowView, StatementFormat, StatementImport,
+        };
+    }
+
+    pub mod recurring {
+        pub use api_types::recurring::{
+            RecurrenceFrequency, RecurringRuleDelete, RecurringRuleList, RecurringRuleListResponse,
+            RecurringRuleNew, RecurringRuleUpdate, RecurringRuleView,
+        };
+    }
 }
 
 pub enum ServerError {
     Engine(EngineError),
     Generic(String),
+    /// `POST /user/pair` with a code no user holds, or an expired one.
+    InvalidPairCode,
+    /// `POST /user/pair` while failed attempts are over the limit.
+    TooManyPairAttempts,
 }
 
-//TODO: Find a better solution
-#[derive(Serialize)]
-struct Error {
-    error: String,
-}
-
-fn status_for_engine_error(err: &EngineError) -> StatusCode {
-    match err {
-        EngineError::Forbidden(_) => StatusCode::FORBIDDEN,
-        EngineError::KeyNotFound(_) => StatusCode::NOT_FOUND,
-        EngineError::ExistingKey(_) => StatusCode::CONFLICT,
-        EngineError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
-        EngineError::MaxBalanceReached(_)
-        | EngineError::InsufficientFunds(_)
-        | EngineError::InvalidAmount(_)
-        | EngineError::InvalidName(_)
-        | EngineError::InvalidId(_)
-        | EngineError::InvalidCursor(_)
-        | EngineError::InvalidFlow(_)
-        | EngineError::InvalidRole(_)
-        | EngineError::CurrencyMismatch(_) => StatusCode::UNPROCESSABLE_ENTITY,
+fn flow_limit_details(limit: &engine::FlowLimit) -> ErrorDetails {
+    ErrorDetails::FlowLimit {
+        flow_id: limit.flow_id,
+        flow_name: limit.flow_name.clone(),
+        currency: currency::to_api(limit.currency),
+        limit_minor: limit.limit_minor,
+        attempted_minor: limit.attempted_minor,
+        excess_minor: limit.excess_minor(),
     }
 }
 
-fn message_for_engine_error(err: EngineError) -> String {
-    match err {
+fn response_for_engine_error(err: EngineError) -> (StatusCode, ErrorResponse) {
+    let (status, code, details) = match &err {
+        EngineError::MaxBalanceReached(limit) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::MaxBalanceReached,
+            Some(flow_limit_details(limit)),
+        ),
+        EngineError::InsufficientFunds(limit) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::InsufficientFunds,
+            Some(flow_limit_details(limit)),
+        ),
+        EngineError::KeyNotFound(_) => (StatusCode::NOT_FOUND, ErrorCode::NotFound, None),
+        EngineError::ExistingKey(_) => (StatusCode::CONFLICT, ErrorCode::AlreadyExists, None),
+        EngineError::InvalidAmount(_) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::InvalidAmount,
+            None,
+        ),
+        EngineError::InvalidName(_) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::InvalidName,
+            None,
+        ),
+        EngineError::InvalidId(_) => (StatusCode::UNPROCESSABLE_ENTITY, ErrorCode::InvalidId, None),
+        EngineError::InvalidCursor(_) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::InvalidCursor,
+            None,
+        ),
+        EngineError::InvalidFlow(_) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::InvalidFlow,
+            None,
+        ),
+        EngineError::InvalidRole(_) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::InvalidRole,
+            None,
+        ),
+        EngineError::CurrencyMismatch(_) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::CurrencyMismatch,
+            None,
+        ),
+        EngineError::InvalidExport(_) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::InvalidExport,
+            None,
+        ),
+        EngineError::InvalidRule(_) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::InvalidRule,
+            None,
+        ),
+        EngineError::InvalidPassword(_) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::InvalidPassword,
+            None,
+        ),
+        EngineError::Forbidden(_) => (StatusCode::FORBIDDEN, ErrorCode::Forbidden, None),
+        EngineError::TransactionLocked(_) => {
+            (StatusCode::CONFLICT, ErrorCode::TransactionLocked, None)
+        }
+        EngineError::BooksClosed(_) => (StatusCode::CONFLICT, ErrorCode::BooksClosed, None),
+        EngineError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Internal, None),
+    };
+    let message = match err {
         EngineError::Database(db_err) => {
             tracing::error!("database error: {db_err}");
             "internal server error".to_string()
         }
         other => other.to_string(),
-    }
+    };
+    (
+        status,
+        ErrorResponse {
+            code,
+            message,
+            details,
+        },
+    )
 }
 
 impl IntoResponse for ServerError {
-    fn into_response(self) -> axum::response::Response {
+    fn into_response(self) -> Response {
         let (status, error) = match self {
-            ServerError::Engine(err) => {
-                (status_for_engine_error(&err), message_for_engine_error(err))
-            }
-            ServerError::Generic(err) => (StatusCode::BAD_REQUEST, err),
+            ServerError::Engine(err) => response_for_engine_error(err),
+            ServerError::Generic(message) => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    code: ErrorCode::BadRequest,
+                    message,
+                    details: None,
+                },
+            ),
+            ServerError::InvalidPairCode => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    code: ErrorCode::InvalidPairCode,
+                    message: "user not found".to_string(),
+                    details: None,
+                },
+            ),
+            ServerError::TooManyPairAttempts => (
+                StatusCode::TOO_MANY_REQUESTS,
+                ErrorResponse {
+                    code: ErrorCode::TooManyRequests,
+                    message: "too many failed pairing attempts, retry later".to_string(),
+                    details: None,
+                },
+            ),
         };
 
-        (status, Json(Error { error })).into_response()
+        (status, Json(error)).into_response()
+    }
+}
+
+/// Code of an error response that did not come from a handler: extractor
+/// rejections, the `auth` middleware and unknown routes.
+fn code_for_status(status: StatusCode) -> ErrorCode {
+    match status {
+        StatusCode::UNAUTHORIZED => ErrorCode::Unauthorized,
+        StatusCode::FORBIDDEN => ErrorCode::Forbidden,
+        StatusCode::NOT_FOUND => ErrorCode::NotFound,
+        StatusCode::METHOD_NOT_ALLOWED => ErrorCode::MethodNotAllowed,
+        StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+        StatusCode::TOO_MANY_REQUESTS => ErrorCode::TooManyRequests,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => ErrorCode::UnsupportedMediaType,
+        status if status.is_server_error() => ErrorCode::Internal,
+        _ => ErrorCode::BadRequest,
     }
 }
 
+/// Rewrites error responses without a JSON body (extractor rejections are
+/// plain text, the `auth` middleware sends none) into an [`ErrorResponse`].
+async fn error_envelope(response: Response) -> Response {
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if !(status.is_client_error() || status.is_server_error()) || is_json {
+        return response;
+    }
+    let body = axum::body::to_bytes(response.into_body(), 64 * 1024)
+        .await
+        .unwrap_or_default();
+    let message = match String::from_utf8_lossy(&body).trim() {
+        "" => status.canonical_reason().unwrap_or_default().to_string(),
+        text => text.to_string(),
+    };
+    let error = ErrorResponse {
+        code: code_for_status(status),
+        message,
+        details: None,
+    };
+    (status, Json(error)).into_response()
+}
+
 impl From<EngineError> for ServerError {
     fn from(value: EngineError) -> Self {
         Self::Engine(value)
@@ -152,4 +341,71 @@ mod tests {
         let res = ServerError::Generic("bad".to_string()).into_response();
         assert_eq!(res.status(), StatusCode::BAD_REQUEST);
     }
+
+    async fn body(res: Response) -> ErrorResponse {
+        use http_body_util::BodyExt as _;
+        let bytes = res.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn engine_cap_errors_carry_the_flow_limit() {
+        let flow_id = uuid::Uuid::new_v4();
+        let err = EngineError::MaxBalanceReached(engine::FlowLimit {
+            flow_id,
+            flow_name: "Holidays".to_string(),
+            currency: engine::Currency::Eur,
+            limit_minor: 50_000,
+            attempted_minor: 51_200,
+        });
+        let res = ServerError::from(err).into_response();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let error = body(res).await;
+        assert_eq!(error.code, ErrorCode::MaxBalanceReached);
+        assert_eq!(
+            error.details,
+            Some(ErrorDetails::FlowLimit {
+                flow_id,
+                flow_name: "Holidays".to_string(),
+                currency: api_types::Currency::Eur,
+                limit_minor: 50_000,
+                attempted_minor: 51_200,
+                excess_minor: 1_200,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn engine_errors_have_a_code_and_message() {
+        let res = ServerError::from(EngineError::KeyNotFound("wallet".to_string())).into_response();
+        let error = body(res).await;
+        assert_eq!(error.code, ErrorCode::NotFound);
+        assert_eq!(error.message, "\"wallet\" key not found!");
+        assert_eq!(error.details, None);
+
+        let res = ServerError::from(EngineError::Database(sea_orm::DbErr::Custom(
+            "secret".to_string(),
+        )))
+        .into_response();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let error = body(res).await;
+        assert_eq!(error.code, ErrorCode::Internal);
+        assert_eq!(error.message, "internal server error");
+    }
+
+    #[tokio::test]
+    async fn plain_error_responses_get_the_envelope() {
+        let res = error_envelope((StatusCode::UNAUTHORIZED, "").into_response()).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let error = body(res).await;
+        assert_eq!(error.code, ErrorCode::Unauthorized);
+        assert_eq!(error.message, "Unauthorized");
+
+        let res =
+            error_envelope((StatusCode::UNPROCESSABLE_ENTITY, "missing field").into_response())
+                .await;
+        let error = body(res).await;
+        assert_eq!(error.code, ErrorCode::BadRequest);
+        assert_eq!(error.message, "missing field");
+    }
 }
@@ -0,0 +1,530 @@
+//! OpenAPI 3.1 document of the HTTP API.
+//!
+//! Routes are registered through [`ApiRouter`], which adds them to the axum
+//! router and documents them in the same step. Parameters, request bodies and
+//! responses are read off each handler's signature (`Path`, `Query`, `Json`
+//! extractors and return type), and payload schemas come from the
+//! `api_types` `JsonSchema` impls, so the document follows the code.
+//!
+//! Response statuses cannot be read from the types: `Json<T>` is documented as
+//! `200`, `(StatusCode, Json<T>)` as `201` (it is only used for creations) and
+//! a bare `StatusCode` as `2XX`.
+
+use std::{any::type_name, collections::BTreeMap, future::Future};
+
+use api_types::error::ErrorResponse;
+use axum::{
+    Extension, Json, Router,
+    extract::{ConnectInfo, Path, Query, State},
+    handler::Handler,
+    http::StatusCode,
+    routing::{MethodFilter, get, on},
+};
+use schemars::{JsonSchema, Schema, SchemaGenerator, generate::SchemaSettings};
+use serde_json::{Map, Value, json};
+use uuid::Uuid;
+
+use crate::{ServerError, server::ServerState};
+
+/// Axum router that also collects the OpenAPI operations of its routes.
+pub(crate) struct ApiRouter {
+    authenticated: Router<ServerState>,
+    public: Router<ServerState>,
+    generator: SchemaGenerator,
+    paths: BTreeMap<&'static str, Map<String, Value>>,
+}
+
+impl ApiRouter {
+    pub(crate) fn new() -> Self {
+        let settings = SchemaSettings::draft2020_12().with(|settings| {
+            settings.definitions_path = "/components/schemas".into();
+            settings.meta_schema = None;
+        });
+        Self {
+            authenticated: Router::new(),
+            public: Router::new(),
+            generator: settings.into_generator(),
+            paths: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn get<H, T>(self, path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, ServerState> + DescribeHandler<T>,
+        T: 'static,
+    {
+        self.add(MethodFilter::GET, "get", path, handler, false)
+    }
+
+    pub(crate) fn post<H, T>(self, path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, ServerState> + DescribeHandler<T>,
+        T: 'static,
+    {
+        self.add(MethodFilter::POST, "post", path, handler, false)
+    }
+
+    pub(crate) fn patch<H, T>(self, path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, ServerState> + DescribeHandler<T>,
+        T: 'static,
+    {
+        self.add(MethodFilter::PATCH, "patch", path, handler, false)
+    }
+
+    pub(crate) fn delete<H, T>(self, path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, ServerState> + DescribeHandler<T>,
+        T: 'static,
+    {
+        self.add(MethodFilter::DELETE, "delete", path, handler, false)
+    }
+
+    /// A `POST` route that skips the `Authorization` check.
+    pub(crate) fn public_post<H, T>(self, path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, ServerState> + DescribeHandler<T>,
+        T: 'static,
+    {
+        self.add(MethodFilter::POST, "post", path, handler, true)
+    }
+
+    fn add<H, T>(
+        mut self,
+        filter: MethodFilter,
+        method: &'static str,
+        path: &'static str,
+        handler: H,
+        public: bool,
+    ) -> Self
+    where
+        H: Handler<T, ServerState> + DescribeHandler<T>,
+        T: 'static,
+    {
+        let mut operation = Operation::default();
+        H::describe(&mut operation, &mut self.generator);
+        let document = operation.into_document(path, type_name::<H>(), public);
+        self.paths
+            .entry(path)
+            .or_default()
+            .insert(method.to_string(), Value::Object(document));
+
+        if public {
+            self.public = self.public.route(path, on(filter, handler));
+        } else {
+            self.authenticated = self.authenticated.route(path, on(filter, handler));
+        }
+        self
+    }
+
+    /// The OpenAPI document of the routes registered so far.
+    pub(crate) fn openapi(&mut self) -> Value {
+        let error = self.generator.subschema_for::<ErrorResponse>();
+        let schemas = self.generator.take_definitions(true);
+        json!({
+            "openapi": "3.1.0",
+            "info": {
+                "title": "Sparagne",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "security": [{ "basic": [] }, { "bearer": [] }],
+            "paths": self.paths,
+            "components": {
+                "schemas": schemas,
+                "securitySchemes": {
+                    "basic": { "type": "http", "scheme": "basic" },
+                    "bearer": {
+                        "type": "http",
+                        "scheme": "bearer",
+                        "description": "Personal access token.",
+                    },
+                },
+                "responses": {
+                    "Error": {
+                        "description": "The request failed.",
+                        "content": {
+                            "application/json": { "schema": error },
+                        },
+                    },
+                },
+            },
+        })
+    }
+
+    /// The axum router: `authenticate` wraps the non-public routes, and the
+    /// document is served at `GET /openapi.json`.
+    pub(crate) fn into_router(
+        mut self,
+        authenticate: impl FnOnce(Router<ServerState>) -> Router<ServerState>,
+    ) -> Router<ServerState> {
+        let document = Json(self.openapi());
+        authenticate(self.authenticated)
+            .merge(self.public)
+            .route("/openapi.json", get(move || async move { document }))
+    }
+}
+
+/// Whether `schema` is an array, or an optional one.
+fn is_array(schema: &Value) -> bool {
+    match schema.get("type") {
+        Some(Value::String(ty)) => ty == "array",
+        Some(Value::Array(types)) => types.iter().any(|ty| ty == "array"),
+        _ => false,
+    }
+}
+
+/// What a handler contributes to its operation.
+#[derive(Default)]
+pub(crate) struct Operation {
+    path_parameters: Vec<Schema>,
+    query: Option<Schema>,
+    request_body: Option<Schema>,
+    response: Option<(&'static str, Option<Schema>)>,
+    fallible: bool,
+}
+
+impl Operation {
+    fn into_document(self, path: &str, handler: &str, public: bool) -> Map<String, Value> {
+        // `server::transactions::list` → tag `transactions`, id `transactions_list`.
+        let handler = handler.strip_prefix("server::").unwrap_or(handler);
+        let tag = handler.split("::").next().unwrap_or(handler);
+        let mut document = Map::new();
+        document.insert("operationId".into(), handler.replace("::", "_").into());
+        document.insert("tags".into(), json!([tag]));
+
+        let mut parameters = Vec::new();
+        let names = path
+            .split('/')
+            .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'));
+        for (name, schema) in names.zip(self.path_parameters) {
+            parameters.push(json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": schema,
+            }));
+        }
+        if let Some(query) = self.query {
+            let required = query.get("required").cloned().unwrap_or_else(|| json!([]));
+            if let Some(Value::Object(properties)) = query.get("properties") {
+                for (name, schema) in properties {
+                    let mut parameter = json!({
+                        "name": name,
+                        "in": "query",
+                        "required": required.as_array().is_some_and(|names| names.contains(&json!(name))),
+                        "schema": schema,
+                    });
+                    // `api_types::CommaSeparated`: `kinds=income,expense`.
+                    if is_array(schema) {
+                        parameter["style"] = "form".into();
+                        parameter["explode"] = false.into();
+                    }
+                    parameters.push(parameter);
+                }
+            }
+        }
+        if !parameters.is_empty() {
+            document.insert("parameters".into(), parameters.into());
+        }
+
+        if let Some(body) = self.request_body {
+            document.insert(
+                "requestBody".into(),
+                json!({
+                    "required": true,
+                    "content": { "application/json": { "schema": body } },
+                }),
+            );
+        }
+
+        let mut responses = Map::new();
+        if let Some((status, body)) = self.response {
+            let mut response = json!({ "description": "Success." });
+            if let Some(body) = body {
+                response["content"] = json!({ "application/json": { "schema": body } });
+            }
+            responses.insert(status.into(), response);
+        }
+        if self.fallible {
+            responses.insert(
+                "default".into(),
+                json!({ "$ref": "#/components/responses/Error" }),
+            );
+        }
+        document.insert("responses".into(), responses.into());
+
+        if public {
+            document.insert("security".into(), json!([]));
+        }
+        document
+    }
+}
+
+/// Implemented for handlers whose extractors and response are documented.
+pub(crate) trait DescribeHandler<T> {
+    fn describe(operation: &mut Operation, generator: &mut SchemaGenerator);
+}
+
+macro_rules! impl_describe_handler {
+    ($($extractor:ident),+) => {
+        impl<F, Fut, M, $($extractor,)+> DescribeHandler<(M, $($extractor,)+)> for F
+        where
+            F: FnOnce($($extractor,)+) -> Fut,
+            Fut: Future,
+            Fut::Output: DescribeResponse,
+            $($extractor: DescribeExtractor,)+
+        {
+            fn describe(operation: &mut Operation, generator: &mut SchemaGenerator) {
+                $($extractor::describe(operation, generator);)+
+                Fut::Output::describe(operation, generator);
+            }
+        }
+    };
+}
+
+impl_describe_handler!(T1);
+impl_describe_handler!(T1, T2);
+impl_describe_handler!(T1, T2, T3);
+impl_describe_handler!(T1, T2, T3, T4);
+impl_describe_handler!(T1, T2, T3, T4, T5);
+
+pub(crate) trait DescribeExtractor {
+    fn describe(operation: &mut Operation, generator: &mut SchemaGenerator);
+}
+
+impl<S> DescribeExtractor for State<S> {
+    fn describe(_: &mut Operation, _: &mut SchemaGenerator) {}
+}
+
+impl<T> DescribeExtractor for Extension<T> {
+    fn describe(_: &mut Operation, _: &mut SchemaGenerator) {}
+}
+
+impl<T> DescribeExtractor for ConnectInfo<T> {
+    fn describe(_: &mut Operation, _: &mut SchemaGenerator) {}
+}
+
+impl<T: JsonSchema> DescribeExtractor for Json<T> {
+    fn describe(operation: &mut Operation, generator: &mut SchemaGenerator) {
+        operation.request_body = Some(generator.subschema_for::<T>());
+    }
+}
+
+impl<T: JsonSchema> DescribeExtractor for Query<T> {
+    fn describe(operation: &mut Operation, generator: &mut SchemaGenerator) {
+        // Inline, so that each property becomes a query parameter.
+        operation.query = Some(T::json_schema(generator));
+    }
+}
+
+impl<T: PathParameters> DescribeExtractor for Path<T> {
+    fn describe(operation: &mut Operation, generator: &mut SchemaGenerator) {
+        operation.path_parameters = T::schemas(generator);
+    }
+}
+
+/// Path segments captured by a `Path` extractor, in path order.
+pub(crate) trait PathParameters {
+    fn schemas(generator: &mut SchemaGenerator) -> Vec<Schema>;
+}
+
+impl PathParameters for Uuid {
+    fn schemas(generator: &mut SchemaGenerator) -> Vec<Schema> {
+        vec![generator.subschema_for::<Self>()]
+    }
+}
+
+impl PathParameters for String {
+    fn schemas(generator: &mut SchemaGenerator) -> Vec<Schema> {
+        vec![generator.subschema_for::<Self>()]
+    }
+}
+
+impl<A: JsonSchema, B: JsonSchema> PathParameters for (A, B) {
+    fn schemas(generator: &mut SchemaGenerator) -> Vec<Schema> {
+        vec![
+            generator.subschema_for::<A>(),
+            generator.subschema_for::<B>(),
+        ]
+    }
+}
+
+impl<A: JsonSchema, B: JsonSchema, C: JsonSchema> PathParameters for (A, B, C) {
+    fn schemas(generator: &mut SchemaGenerator) -> Vec<Schema> {
+        vec![
+            generator.subschema_for::<A>(),
+            generator.subschema_for::<B>(),
+            generator.subschema_for::<C>(),
+        ]
+    }
+}
+
+pub(crate) trait DescribeResponse {
+    fn describe(operation: &mut Operation, generator: &mut SchemaGenerator);
+}
+
+impl<T: JsonSchema> DescribeResponse for Json<T> {
+    fn describe(operation: &mut Operation, generator: &mut SchemaGenerator) {
+        operation.response = Some(("200", Some(generator.subschema_for::<T>())));
+    }
+}
+
+impl<T: JsonSchema> DescribeResponse for (StatusCode, Json<T>) {
+    fn describe(operation: &mut Operation, generator: &mut SchemaGenerator) {
+        operation.response = Some(("201", Some(generator.subschema_for::<T>())));
+    }
+}
+
+impl DescribeResponse for StatusCode {
+    fn describe(operation: &mut Operation, _: &mut SchemaGenerator) {
+        operation.response = Some(("2XX", None));
+    }
+}
+
+impl<T: DescribeResponse> DescribeResponse for Result<T, ServerError> {
+    fn describe(operation: &mut Operation, generator: &mut SchemaGenerator) {
+        T::describe(operation, generator);
+        operation.fallible = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::unwrap_used)]
+
+    use super::*;
+
+    /// Checked-in copy of the document, for client generators.
+    const SPEC_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
+    fn refs(value: &Value, found: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    match (key.as_str(), value) {
+                        ("$ref", Value::String(target)) => found.push(target.clone()),
+                        _ => refs(value, found),
+                    }
+                }
+            }
+            Value::Array(values) => values.iter().for_each(|value| refs(value, found)),
+            _ => {}
+        }
+    }
+
+    /// Fails when a route or payload changes without regenerating the
+    /// checked-in spec: `SPARAGNE_UPDATE_OPENAPI=1 cargo test -p server openapi`.
+    #[test]
+    fn checked_in_spec_matches_routes_and_payloads() {
+        let document = crate::server::openapi();
+        let generated = format!("{}\n", serde_json::to_string_pretty(&document).unwrap());
+        if std::env::var_os("SPARAGNE_UPDATE_OPENAPI").is_some() {
+            std::fs::write(SPEC_PATH, &generated).unwrap();
+        }
+        let checked_in = std::fs::read_to_string(SPEC_PATH).unwrap_or_default();
+        assert!(
+            checked_in == generated,
+            "crates/server/openapi.json is out of date; regenerate it with \
+             `SPARAGNE_UPDATE_OPENAPI=1 cargo test -p server openapi`"
+        );
+    }
+
+    #[test]
+    fn every_reference_resolves() {
+        let document = crate::server::openapi();
+        let mut found = Vec::new();
+        refs(&document, &mut found);
+        assert!(!found.is_empty());
+        for target in found {
+            let pointer = target.strip_prefix('#').expect("local reference");
+            assert!(
+                document.pointer(pointer).is_some(),
+                "dangling reference {target}"
+            );
+        }
+    }
+
+    #[test]
+    fn operations_follow_handler_signatures() {
+        let document = crate::server::openapi();
+
+        let list = &document["paths"]["/transactions"]["post"];
+        assert_eq!(list["operationId"], "transactions_list");
+        assert_eq!(
+            list["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/TransactionList"
+        );
+        assert_eq!(
+            list["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/TransactionListResponse"
+        );
+
+        let alias = &document["paths"]["/categories/{category_id}/aliases/{alias_id}"]["delete"];
+        let names: Vec<&str> = alias["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|parameter| parameter["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["category_id", "alias_id"]);
+
+        let audit = &document["paths"]["/vault/{vault_id}/audit"]["get"];
+        assert!(
+            audit["parameters"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|parameter| parameter["in"] == "query")
+        );
+
+        assert_eq!(
+            document["paths"]["/user/pair"]["post"]["security"],
+            json!([])
+        );
+        assert!(document["paths"]["/user/pair"]["delete"]["security"].is_null());
+
+        let kinds = document["paths"]["/v2/vaults/{vault_id}/transactions"]["get"]["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|parameter| parameter["name"] == "kinds")
+            .unwrap();
+        assert_eq!(kinds["explode"], false);
+        assert_eq!(
+            kinds["schema"]["items"]["$ref"],
+            "#/components/schemas/TransactionKind"
+        );
+    }
+
+    #[test]
+    fn operation_ids_are_unique() {
+        let document = crate::server::openapi();
+        let mut ids = std::collections::BTreeSet::new();
+        for operations in document["paths"].as_object().unwrap().values() {
+            for operation in operations.as_object().unwrap().values() {
+                let id = operation["operationId"].as_str().unwrap();
+                assert!(ids.insert(id), "duplicate operationId {id}");
+            }
+        }
+    }
+
+    #[test]
+    fn flattened_and_tagged_payloads_match_serde() {
+        let document = crate::server::openapi();
+        let schemas = &document["components"]["schemas"];
+
+        let leg = &schemas["TransactionLegView"];
+        assert!(leg["properties"]["amount_minor"].is_object());
+        let targets = leg["oneOf"].as_array().unwrap();
+        assert_eq!(targets[0]["properties"]["target"]["const"], "wallet");
+
+        let rule = &schemas["CategoryRuleView"];
+        assert!(rule["properties"]["note_contains"].is_object());
+
+        let mode = schemas["FlowMode"]["oneOf"].as_array().unwrap();
+        assert_eq!(mode[1]["properties"]["mode"]["const"], "net_capped");
+        assert_eq!(mode[1]["required"], json!(["mode", "cap_minor"]));
+
+        assert_eq!(schemas["Currency"]["enum"][0], "EUR");
+    }
+}
@@ -0,0 +1,158 @@
+//! Recurring rules API endpoints.
+
+use api_types::recurring::{
+    RecurrenceFrequency, RecurringRuleDelete, RecurringRuleList, RecurringRuleListResponse,
+    RecurringRuleNew, RecurringRuleUpdate, RecurringRuleView,
+};
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::{FixedOffset, Utc};
+use uuid::Uuid;
+
+use crate::{
+    ServerError,
+    server::ServerState,
+    transactions::{kind_from_api, map_kind},
+    user,
+};
+
+fn map_frequency(frequency: engine::RecurrenceFrequency) -> RecurrenceFrequency {
+    match frequency {
+        engine::RecurrenceFrequency::Weekly => RecurrenceFrequency::Weekly,
+        engine::RecurrenceFrequency::Monthly => RecurrenceFrequency::Monthly,
+        engine::RecurrenceFrequency::Yearly => RecurrenceFrequency::Yearly,
+    }
+}
+
+fn frequency_from_api(frequency: RecurrenceFrequency) -> engine::RecurrenceFrequency {
+    match frequency {
+        RecurrenceFrequency::Weekly => engine::RecurrenceFrequency::Weekly,
+        RecurrenceFrequency::Monthly => engine::RecurrenceFrequency::Monthly,
+        RecurrenceFrequency::Yearly => engine::RecurrenceFrequency::Yearly,
+    }
+}
+
+fn map_rule(rule: engine::RecurringRule) -> Result<RecurringRuleView, ServerError> {
+    let utc = FixedOffset::east_opt(0)
+        .ok_or_else(|| ServerError::Generic("invalid UTC offset".to_string()))?;
+    Ok(RecurringRuleView {
+        id: rule.id,
+        kind: map_kind(rule.kind),
+        amount_minor: rule.amount_minor,
+        wallet_id: rule.wallet_id,
+        flow_id: rule.flow_id,
+        from_flow_id: rule.from_flow_id,
+        to_flow_id: rule.to_flow_id,
+        category: rule.category,
+        note: rule.note,
+        frequency: map_frequency(rule.recurrence.frequency),
+        interval: rule.recurrence.interval,
+        day_of_month: rule.recurrence.day_of_month,
+        starts_at: rule.recurrence.starts_at.with_timezone(&utc),
+        ends_at: rule.recurrence.ends_at.map(|at| at.with_timezone(&utc)),
+        max_occurrences: rule.recurrence.max_occurrences,
+        occurrences: rule.occurrences,
+        next_occurrence_at: rule.next_occurrence_at.map(|at| at.with_timezone(&utc)),
+        active: rule.active,
+        last_error: rule.last_error,
+    })
+}
+
+pub async fn list(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<RecurringRuleList>,
+) -> Result<Json<RecurringRuleListResponse>, ServerError> {
+    let rules = state
+        .engine
+        .list_recurring_rules(&payload.vault_id, &user.username)
+        .await?
+        .into_iter()
+        .map(map_rule)
+        .collect::<Result<_, _>>()?;
+    Ok(Json(RecurringRuleListResponse { rules }))
+}
+
+pub async fn create(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<RecurringRuleNew>,
+) -> Result<(StatusCode, Json<RecurringRuleView>), ServerError> {
+    let mut recurrence = engine::Recurrence::new(
+        frequency_from_api(payload.frequency),
+        payload.starts_at.with_timezone(&Utc),
+    );
+    recurrence.interval = payload.interval.unwrap_or(1);
+    recurrence.day_of_month = payload.day_of_month;
+    recurrence.ends_at = payload.ends_at.map(|at| at.with_timezone(&Utc));
+    recurrence.max_occurrences = payload.max_occurrences;
+
+    let rule = state
+        .engine
+        .create_recurring_rule(engine::CreateRecurringRuleCmd {
+            vault_id: payload.vault_id,
+            user_id: user.username,
+            kind: kind_from_api(payload.kind),
+            amount_minor: payload.amount_minor,
+            recurrence,
+            wallet_id: payload.wallet_id,
+            flow_id: payload.flow_id,
+            from_flow_id: payload.from_flow_id,
+            to_flow_id: payload.to_flow_id,
+            category: payload.category,
+            note: payload.note,
+        })
+        .await?;
+    Ok((StatusCode::CREATED, Json(map_rule(rule)?)))
+}
+
+pub async fn update(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(rule_id): Path<Uuid>,
+    Json(payload): Json<RecurringRuleUpdate>,
+) -> Result<Json<RecurringRuleView>, ServerError> {
+    let ends_at = match (payload.clear_ends_at, payload.ends_at) {
+        (false, ends_at) => ends_at.map(|at| Some(at.with_timezone(&Utc))),
+        (true, None) => Some(None),
+        (true, Some(_)) => {
+            return Err(ServerError::Generic(
+                "ends_at and clear_ends_at are mutually exclusive".to_string(),
+            ));
+        }
+    };
+    let rule = state
+        .engine
+        .update_recurring_rule(engine::UpdateRecurringRuleCmd {
+            vault_id: payload.vault_id,
+            rule_id,
+            user_id: user.username,
+            amount_minor: payload.amount_minor,
+            wallet_id: payload.wallet_id,
+            flow_id: payload.flow_id,
+            from_flow_id: payload.from_flow_id,
+            to_flow_id: payload.to_flow_id,
+            category: payload.category,
+            note: payload.note,
+            ends_at,
+            active: payload.active,
+        })
+        .await?;
+    Ok(Json(map_rule(rule)?))
+}
+
+pub async fn delete(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(rule_id): Path<Uuid>,
+    Json(payload): Json<RecurringRuleDelete>,
+) -> Result<StatusCode, ServerError> {
+    state
+        .engine
+        .delete_recurring_rule(&payload.vault_id, rule_id, &user.username)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,86 @@
+//! Reporting API endpoints.
+
+use api_types::report::{ReportGroupBy, ReportQuery, ReportResponse, ReportRowView};
+use axum::{Extension, Json, extract::State};
+use chrono::{FixedOffset, Utc};
+
+use crate::{ServerError, currency, server::ServerState, transactions::kind_from_api, user};
+
+fn group_by_from_api(group_by: ReportGroupBy) -> engine::ReportGroupBy {
+    match group_by {
+        ReportGroupBy::Category => engine::ReportGroupBy::Category,
+        ReportGroupBy::Flow => engine::ReportGroupBy::Flow,
+        ReportGroupBy::Wallet => engine::ReportGroupBy::Wallet,
+        ReportGroupBy::Day => engine::ReportGroupBy::Day,
+        ReportGroupBy::Week => engine::ReportGroupBy::Week,
+        ReportGroupBy::Month => engine::ReportGroupBy::Month,
+        ReportGroupBy::Year => engine::ReportGroupBy::Year,
+    }
+}
+
+fn map_group_by(group_by: engine::ReportGroupBy) -> ReportGroupBy {
+    match group_by {
+        engine::ReportGroupBy::Category => ReportGroupBy::Category,
+        engine::ReportGroupBy::Flow => ReportGroupBy::Flow,
+        engine::ReportGroupBy::Wallet => ReportGroupBy::Wallet,
+        engine::ReportGroupBy::Day => ReportGroupBy::Day,
+        engine::ReportGroupBy::Week => ReportGroupBy::Week,
+        engine::ReportGroupBy::Month => ReportGroupBy::Month,
+        engine::ReportGroupBy::Year => ReportGroupBy::Year,
+    }
+}
+
+pub async fn report(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<ReportQuery>,
+) -> Result<Json<ReportResponse>, ServerError> {
+    let utc_offset = payload
+        .utc_offset_minutes
+        .unwrap_or(0)
+        .checked_mul(60)
+        .and_then(FixedOffset::east_opt)
+        .ok_or_else(|| ServerError::Generic("invalid utc_offset_minutes".to_string()))?;
+
+    let mut cmd = engine::ReportCmd::new(
+        payload.vault_id,
+        user.username,
+        payload
+            .group_by
+            .into_iter()
+            .map(group_by_from_api)
+            .collect(),
+    )
+    .utc_offset(utc_offset);
+    cmd.from = payload.from.map(|at| at.with_timezone(&Utc));
+    cmd.to = payload.to.map(|at| at.with_timezone(&Utc));
+    cmd.kinds = payload
+        .kinds
+        .map(|kinds| kinds.into_iter().map(kind_from_api).collect());
+
+    let report = state.engine.report(cmd).await?;
+    let rows = report
+        .rows
+        .into_iter()
+        .map(|row| ReportRowView {
+            net_minor: row.net_minor(),
+            period_start: row.period_start,
+            category_id: row.category_id,
+            category: row.category,
+            flow_id: row.flow_id,
+            flow: row.flow,
+            wallet_id: row.wallet_id,
+            wallet: row.wallet,
+            income_minor: row.income_minor,
+            expense_minor: row.expense_minor,
+            refund_minor: row.refund_minor,
+            transactions: row.transactions,
+        })
+        .collect();
+
+    Ok(Json(ReportResponse {
+        currency: currency::to_api(report.currency),
+        group_by: report.group_by.into_iter().map(map_group_by).collect(),
+        rows,
+    }))
+}
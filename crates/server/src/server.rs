@@ -4,165 +4,218 @@ use axum::{
     http::StatusCode,
     middleware::{self, Next},
     response::Response,
-    routing::{delete, get, post},
 };
-use axum_extra::{
-    TypedHeader,
-    headers::{Authorization, Error as AxumError, Header, authorization::Basic},
+use axum_extra::headers::{
+    Authorization, Header,
+    authorization::{Basic, Bearer, Credentials},
 };
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use sea_orm::{DatabaseConnection, EntityTrait};
 
 use std::sync::Arc;
 
 use crate::{
-    cash_flow, categories, flows, memberships, statistics, transactions, user, vault, wallets,
+    audit, balances, budgets, cash_flow, categories, category_rules, error_envelope, flows,
+    imports, memberships, openapi::ApiRouter, recurring, reports, statistics, tokens, transactions,
+    user, v2, vault, wallets,
 };
 use engine::Engine;
 
-static TELEGRAM_HEADER: axum::http::HeaderName =
-    axum::http::HeaderName::from_static("telegram-user-id");
-
 #[derive(Clone)]
 pub struct ServerState {
     pub engine: Arc<Engine>,
     pub db: DatabaseConnection,
+    pub pair_attempts: Arc<user::PairAttempts>,
 }
 
-/// `TypedHeader` for custom telegram header
-///
-/// Telegram requests must contain "telegram-user-id" entry in the header.
-#[derive(Debug)]
-struct TelegramHeader(String);
-
-impl Header for TelegramHeader {
-    fn name() -> &'static axum::http::HeaderName {
-        &TELEGRAM_HEADER
-    }
-
-    fn decode<'i, I>(values: &mut I) -> Result<Self, AxumError>
-    where
-        Self: Sized,
-        I: Iterator<Item = &'i axum::http::HeaderValue>,
-    {
-        let value = values.next().ok_or_else(AxumError::invalid)?;
-        let Ok(value) = value.to_str() else {
-            return Err(AxumError::invalid());
-        };
-        Ok(TelegramHeader(value.to_string()))
+/// Argon2id hash, with the cost parameters of `engine::hash_password`, checked
+/// when the user does not exist: unknown usernames then take as long to reject
+/// as wrong passwords, and cannot be told apart by timing.
+const MISSING_USER_PASSWORD_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$LPm7fzJXPSNlvqkZz8mExQ$zsJScLRePSxyprlW0SN/7Zt6wMli+fdcJhpIQ1LNe2o";
+
+/// Loads the user named in Basic credentials and checks the password.
+async fn basic_user(state: &ServerState, credentials: &Basic) -> Result<user::Model, StatusCode> {
+    if credentials.username().is_empty() || credentials.password().is_empty() {
+        return Err(StatusCode::UNAUTHORIZED);
     }
 
-    fn encode<E: Extend<axum::http::HeaderValue>>(&self, values: &mut E) {
-        match axum::http::HeaderValue::from_str(&self.0) {
-            Ok(value) => values.extend(std::iter::once(value)),
-            Err(_) => tracing::error!("failed to encode telegram-user-id header"),
-        }
+    let user: Option<user::Model> = user::Entity::find_by_id(credentials.username())
+        .one(&state.db)
+        .await
+        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+    // Argon2 verification is CPU-bound: keep it off the async workers.
+    let password = credentials.password().to_string();
+    let hash = user.as_ref().map_or_else(
+        || MISSING_USER_PASSWORD_HASH.to_string(),
+        |user| user.password.clone(),
+    );
+    let verified = tokio::task::spawn_blocking(move || engine::verify_password(&password, &hash))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    match user {
+        Some(user) if verified => Ok(user),
+        _ => Err(StatusCode::UNAUTHORIZED),
     }
 }
 
+/// Resolves a personal access token to the token and its owner.
+async fn bearer_user(
+    state: &ServerState,
+    credentials: &Bearer,
+) -> Result<(user::Model, engine::AccessToken), StatusCode> {
+    let token = state
+        .engine
+        .authenticate_access_token(credentials.token())
+        .await
+        .map_err(|_| StatusCode::UNAUTHORIZED)?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let user = user::Entity::find_by_id(token.user_id.as_str())
+        .one(&state.db)
+        .await
+        .map_err(|_| StatusCode::UNAUTHORIZED)?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    Ok((user, token))
+}
+
+/// Decodes the `Authorization` header with the given scheme, if it uses it.
+fn authorization<C: Credentials>(request: &Request) -> Option<C> {
+    let value = request.headers().get(axum::http::header::AUTHORIZATION)?;
+    Authorization::<C>::decode(&mut std::iter::once(value))
+        .ok()
+        .map(|Authorization(credentials)| credentials)
+}
+
 async fn auth(
-    auth_header: TypedHeader<Authorization<Basic>>,
-    telegram_header: Option<TypedHeader<TelegramHeader>>,
     State(state): State<ServerState>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    if auth_header.username().is_empty() || auth_header.password().is_empty() {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
-
-    let user: Option<user::Model> = user::Entity::find()
-        .filter(user::Column::Username.contains(auth_header.username()))
-        .filter(user::Column::Password.contains(auth_header.password()))
-        .one(&state.db)
-        .await
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
-
-    let mut user = if let Some(user) = user {
-        user
+    let (user, token) = if let Some(basic) = authorization::<Basic>(&request) {
+        (basic_user(&state, &basic).await?, None)
+    } else if let Some(bearer) = authorization::<Bearer>(&request) {
+        let (user, token) = bearer_user(&state, &bearer).await?;
+        (user, Some(token))
     } else {
         return Err(StatusCode::UNAUTHORIZED);
     };
 
-    if let Some(header) = telegram_header {
-        let header = header.0;
-        let user_entry = user::Entity::find()
-            .filter(user::Column::TelegramId.eq(header.0))
-            .one(&state.db)
-            .await
-            .map_err(|_| StatusCode::UNAUTHORIZED)?;
-
-        user = if let Some(user) = user_entry {
-            user
-        } else {
-            return Err(StatusCode::UNAUTHORIZED);
-        };
+    if let Some(token) = token {
+        request = tokens::authorize(&token, request).await?;
     }
 
     request.extensions_mut().insert(user);
     Ok(next.run(request).await)
 }
 
-fn router(state: ServerState) -> Router {
-    Router::new()
-        .route("/cashFlow/get", post(cash_flow::get))
-        .route("/transactions", post(transactions::list))
-        .route("/transactions/get", post(transactions::get_detail))
-        .route("/wallets", post(wallets::wallet_new))
-        .route(
-            "/wallets/{id}",
-            axum::routing::patch(wallets::wallet_update),
+/// Every API route; see [`ApiRouter`] for how they are documented.
+fn api() -> ApiRouter {
+    let v1 = ApiRouter::new()
+        .post("/cashFlow/get", cash_flow::get)
+        .post("/transactions", transactions::list)
+        .post("/transactions/get", transactions::get_detail)
+        .post("/wallets", wallets::wallet_new)
+        .patch("/wallets/{id}", wallets::wallet_update)
+        .post(
+            "/wallets/{id}/reconcile/preview",
+            wallets::reconcile_preview,
         )
-        .route("/flows", post(flows::flow_new))
-        .route("/flows/{id}", axum::routing::patch(flows::flow_update))
-        .route("/categories/list", post(categories::list))
-        .route("/categories", post(categories::create))
-        .route("/categories/{id}", axum::routing::patch(categories::update))
-        .route(
-            "/categories/{id}/aliases/list",
-            post(categories::list_aliases),
-        )
-        .route("/categories/{id}/aliases", post(categories::create_alias))
-        .route(
+        .post("/wallets/{id}/reconcile", wallets::reconcile)
+        .post("/flows", flows::flow_new)
+        .patch("/flows/{id}", flows::flow_update)
+        .post("/categories/list", categories::list)
+        .post("/categories", categories::create)
+        .patch("/categories/{id}", categories::update)
+        .post("/categories/{id}/aliases/list", categories::list_aliases)
+        .post("/categories/{id}/aliases", categories::create_alias)
+        .delete(
             "/categories/{category_id}/aliases/{alias_id}",
-            delete(categories::delete_alias),
-        )
-        .route(
-            "/categories/{id}/merge/preview",
-            post(categories::preview_merge),
+            categories::delete_alias,
         )
-        .route("/categories/{id}/merge", post(categories::merge))
-        .route("/income", post(transactions::income_new))
-        .route("/expense", post(transactions::expense_new))
-        .route("/refund", post(transactions::refund_new))
-        .route("/transferWallet", post(transactions::transfer_wallet_new))
-        .route("/transferFlow", post(transactions::transfer_flow_new))
-        .route(
-            "/transactions/{id}",
-            axum::routing::patch(transactions::update),
+        .post("/categories/{id}/merge/preview", categories::preview_merge)
+        .post("/categories/{id}/merge", categories::merge)
+        .post("/income", transactions::income_new)
+        .post("/expense", transactions::expense_new)
+        .post("/expense/split", transactions::split_expense_new)
+        .post("/refund", transactions::refund_new)
+        .post("/transferWallet", transactions::transfer_wallet_new)
+        .post("/transferFlow", transactions::transfer_flow_new)
+        .patch("/transactions/{id}", transactions::update)
+        .post("/transactions/{id}/void", transactions::void_tx)
+        .post("/transactions/{id}/restore", transactions::restore)
+        .post("/transactions/{id}/clear", transactions::clear)
+        .post("/transactions/{id}/unlock", transactions::unlock)
+        .post(
+            "/transactions/{id}/revisions/list",
+            transactions::list_revisions,
         )
-        .route("/transactions/{id}/void", post(transactions::void_tx))
-        .route("/vault/new", post(vault::vault_new))
-        .route("/vault/get", post(vault::get))
-        .route("/vault/snapshot", post(vault::snapshot))
-        .route(
+        .post("/transactions/{id}/revert", transactions::revert)
+        .post("/vault/new", vault::vault_new)
+        .post("/vault/get", vault::get)
+        .post("/vault/snapshot", vault::snapshot)
+        .get("/vault/{vault_id}/audit", audit::list)
+        .get("/vault/{vault_id}/members", memberships::list_vault_members)
+        .post(
             "/vault/{vault_id}/members",
-            get(memberships::list_vault_members).post(memberships::upsert_vault_member),
+            memberships::upsert_vault_member,
         )
-        .route(
+        .delete(
             "/vault/{vault_id}/members/{username}",
-            axum::routing::delete(memberships::remove_vault_member),
+            memberships::remove_vault_member,
+        )
+        .get(
+            "/vault/{vault_id}/flows/{flow_id}/members",
+            memberships::list_flow_members,
         )
-        .route(
+        .post(
             "/vault/{vault_id}/flows/{flow_id}/members",
-            get(memberships::list_flow_members).post(memberships::upsert_flow_member),
+            memberships::upsert_flow_member,
         )
-        .route(
+        .delete(
             "/vault/{vault_id}/flows/{flow_id}/members/{username}",
-            axum::routing::delete(memberships::remove_flow_member),
+            memberships::remove_flow_member,
         )
-        .route("/user/pair", post(user::pair).delete(user::unpair))
-        .route("/stats/get", post(statistics::get_stats))
-        .route_layer(middleware::from_fn_with_state(state.clone(), auth))
+        .delete("/user/pair", user::unpair)
+        .post("/stats/get", statistics::get_stats)
+        .post("/reports", reports::report)
+        .post("/balances", balances::balance)
+        .post("/balances/history", balances::history)
+        .post("/budgets/list", budgets::list)
+        .post("/budgets", budgets::create)
+        .patch("/budgets/{id}", budgets::update)
+        .delete("/budgets/{id}", budgets::delete)
+        .post("/category-rules/list", category_rules::list)
+        .post("/category-rules", category_rules::create)
+        .post("/category-rules/preview", category_rules::preview)
+        .post("/category-rules/apply", category_rules::apply)
+        .patch("/category-rules/{id}", category_rules::update)
+        .delete("/category-rules/{id}", category_rules::delete)
+        .post("/recurring/list", recurring::list)
+        .post("/recurring", recurring::create)
+        .patch("/recurring/{id}", recurring::update)
+        .delete("/recurring/{id}", recurring::delete)
+        .post("/import/csv/preview", imports::preview_csv)
+        .post("/import/csv", imports::commit_csv)
+        .post("/import/statement/preview", imports::preview_statement)
+        .post("/import/statement", imports::commit_statement)
+        .get("/tokens", tokens::list)
+        .post("/tokens", tokens::create)
+        .delete("/tokens/{id}", tokens::revoke)
+        // Pairing is authenticated by the one-time pair code.
+        .public_post("/user/pair", user::pair);
+    v2::routes(v1)
+}
+
+/// OpenAPI 3.1 document of the HTTP API, as served at `/openapi.json`.
+pub fn openapi() -> serde_json::Value {
+    api().openapi()
+}
+
+fn router(state: ServerState) -> Router {
+    api()
+        .into_router(|routes| {
+            routes.route_layer(middleware::from_fn_with_state(state.clone(), auth))
+        })
+        .layer(middleware::map_response(error_envelope))
         .with_state(state)
 }
 
@@ -190,9 +243,14 @@ pub async fn run_with_listener(
     let state = ServerState {
         engine: Arc::new(engine),
         db,
+        pair_attempts: Arc::default(),
     };
 
-    axum::serve(listener, router(state)).await
+    axum::serve(
+        listener,
+        router(state).into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
 }
 
 pub fn spawn_with_listener(
@@ -218,12 +276,14 @@ mod http_tests {
     use super::*;
 
     use api_types::{
-        category, flow,
+        access_token, audit, balance, budget, category, category_rule,
+        error::{ErrorCode, ErrorDetails, ErrorResponse},
+        flow, import, recurring, report, transaction,
         transaction::{TransactionDetailResponse, TransactionGet, TransactionList},
         wallet,
     };
     use base64::Engine as _;
-    use chrono::{FixedOffset, Utc};
+    use chrono::{FixedOffset, TimeZone, Utc};
     use http_body_util::BodyExt as _;
     use migration::{Migrator, MigratorTrait};
     use sea_orm::{ActiveModelTrait, ActiveValue, Database};
@@ -240,20 +300,40 @@ mod http_tests {
         format!("Basic {encoded}")
     }
 
+    async fn insert_user(db: &sea_orm::DatabaseConnection, username: &str, password: &str) {
+        let active = crate::user::ActiveModel {
+            username: ActiveValue::Set(username.to_string()),
+            password: ActiveValue::Set(engine::hash_password(password).unwrap()),
+            telegram_id: ActiveValue::Set(None),
+            pair_code: ActiveValue::Set(None),
+            pair_code_expires_at: ActiveValue::Set(None),
+        };
+        active.insert(db).await.unwrap();
+    }
+
+    /// Gives `username` a fresh pair code and returns it.
+    async fn set_pair_code(
+        db: &sea_orm::DatabaseConnection,
+        username: &str,
+        expires_at: chrono::DateTime<Utc>,
+    ) -> String {
+        let code = engine::generate_pair_code();
+        let mut user: crate::user::ActiveModel = crate::user::Entity::find_by_id(username)
+            .one(db)
+            .await
+            .unwrap()
+            .unwrap()
+            .into();
+        user.pair_code = ActiveValue::Set(Some(engine::hash_pair_code(&code)));
+        user.pair_code_expires_at = ActiveValue::Set(Some(expires_at));
+        user.update(db).await.unwrap();
+        code
+    }
+
     async fn setup() -> (Router, Arc<Engine>, sea_orm::DatabaseConnection) {
         let db = Database::connect("sqlite::memory:").await.unwrap();
         Migrator::up(&db, None).await.unwrap();
 
-        async fn insert_user(db: &sea_orm::DatabaseConnection, username: &str, password: &str) {
-            let active = crate::user::ActiveModel {
-                username: ActiveValue::Set(username.to_string()),
-                password: ActiveValue::Set(password.to_string()),
-                telegram_id: ActiveValue::Set(None),
-                pair_code: ActiveValue::Set(None),
-            };
-            active.insert(db).await.unwrap();
-        }
-
         insert_user(&db, OWNER, OWNER_PW).await;
         insert_user(&db, FLOW_MEMBER, FLOW_MEMBER_PW).await;
 
@@ -268,9 +348,532 @@ mod http_tests {
         let state = ServerState {
             engine: engine.clone(),
             db: db.clone(),
+            pair_attempts: Arc::default(),
+        };
+
+        let client = axum::extract::connect_info::MockConnectInfo(std::net::SocketAddr::from((
+            [127, 0, 0, 1],
+            40_000,
+        )));
+        (router(state).layer(client), engine, db)
+    }
+
+    #[tokio::test]
+    async fn auth_requires_exact_username_and_password() {
+        let (app, engine, db) = setup().await;
+        insert_user(&db, "owner2", "pw-long").await;
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+
+        let get_vault = |username: &str, password: &str| {
+            axum::http::Request::builder()
+                .method("POST")
+                .uri("/vault/get")
+                .header(
+                    axum::http::header::AUTHORIZATION,
+                    basic_auth(username, password),
+                )
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(
+                    serde_json::to_vec(&serde_json::json!({ "id": vault_id })).unwrap(),
+                ))
+                .unwrap()
+        };
+
+        // A substring of another user's password, a username prefix and the
+        // stored hash itself are all rejected.
+        for (username, password) in [
+            (OWNER, "p"),
+            ("owner2", OWNER_PW),
+            ("own", OWNER_PW),
+            (OWNER, "pw "),
+        ] {
+            let res = app
+                .clone()
+                .oneshot(get_vault(username, password))
+                .await
+                .unwrap();
+            assert_eq!(
+                res.status(),
+                StatusCode::UNAUTHORIZED,
+                "{username}:{password}"
+            );
+        }
+        let hash: String = crate::user::Entity::find_by_id(OWNER)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap()
+            .password;
+        assert!(hash.starts_with("$argon2id$"));
+        let res = app.clone().oneshot(get_vault(OWNER, &hash)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = app.oneshot(get_vault(OWNER, OWNER_PW)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn unknown_users_are_checked_against_a_real_hash() {
+        // The dummy hash must cost as much as a stored one to verify.
+        let params = |hash: &str| hash.rsplitn(3, '$').nth(2).unwrap().to_string();
+        let stored = engine::hash_password("pw").unwrap();
+        assert_eq!(params(MISSING_USER_PASSWORD_HASH), params(&stored));
+        assert!(engine::verify_password(
+            "sparagne-missing-user",
+            MISSING_USER_PASSWORD_HASH
+        ));
+
+        // Knowing its password does not log anyone in.
+        let (app, _engine, _db) = setup().await;
+        let res = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .method("GET")
+                    .uri("/tokens")
+                    .header(
+                        axum::http::header::AUTHORIZATION,
+                        basic_auth("ghost", "sparagne-missing-user"),
+                    )
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn openapi_document_is_served_without_auth() {
+        let (app, _engine, _db) = setup().await;
+        let res = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .method("GET")
+                    .uri("/openapi.json")
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let document: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(document, openapi());
+    }
+
+    #[tokio::test]
+    async fn errors_are_returned_as_a_json_envelope() {
+        let (app, engine, _db) = setup().await;
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        let flow_id = engine
+            .new_cash_flow(&vault_id, "Vacanze", 0, None, None, OWNER)
+            .await
+            .unwrap();
+        let request = |auth: Option<String>, body: Vec<u8>| {
+            let mut request = axum::http::Request::builder()
+                .method("POST")
+                .uri("/expense")
+                .header(axum::http::header::CONTENT_TYPE, "application/json");
+            if let Some(auth) = auth {
+                request = request.header(axum::http::header::AUTHORIZATION, auth);
+            }
+            request.body(axum::body::Body::from(body)).unwrap()
+        };
+        let error_of = |res: axum::response::Response| async move {
+            let status = res.status();
+            let body = res.into_body().collect().await.unwrap().to_bytes();
+            let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+            (status, error)
+        };
+        let expense = serde_json::json!({
+            "vault_id": vault_id,
+            "amount_minor": 1250,
+            "flow_id": flow_id,
+            "occurred_at": "2026-01-10T12:00:00+01:00",
+        });
+
+        let res = app
+            .clone()
+            .oneshot(request(None, serde_json::to_vec(&expense).unwrap()))
+            .await
+            .unwrap();
+        let (status, error) = error_of(res).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(error.code, ErrorCode::Unauthorized);
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                Some(basic_auth(OWNER, OWNER_PW)),
+                b"{\"vault_id\":".to_vec(),
+            ))
+            .await
+            .unwrap();
+        let (status, error) = error_of(res).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.code, ErrorCode::BadRequest);
+        assert!(!error.message.is_empty());
+
+        let res = app
+            .oneshot(request(
+                Some(basic_auth(OWNER, OWNER_PW)),
+                serde_json::to_vec(&expense).unwrap(),
+            ))
+            .await
+            .unwrap();
+        let (status, error) = error_of(res).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error.code, ErrorCode::InsufficientFunds);
+        let Some(ErrorDetails::FlowLimit {
+            flow_name,
+            limit_minor,
+            attempted_minor,
+            excess_minor,
+            ..
+        }) = error.details
+        else {
+            panic!("missing flow limit: {error:?}");
+        };
+        assert_eq!(flow_name, "Vacanze");
+        assert_eq!((limit_minor, attempted_minor), (0, -1250));
+        assert_eq!(excess_minor, 1250);
+    }
+
+    #[tokio::test]
+    async fn access_tokens_authenticate_within_their_scope() {
+        let (app, engine, _db) = setup().await;
+        let main = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        let other = engine
+            .new_vault("Other", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+
+        let request = |method: &str, uri: &str, auth: &str, body: serde_json::Value| {
+            axum::http::Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(axum::http::header::AUTHORIZATION, auth)
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap()
+        };
+        let owner = basic_auth(OWNER, OWNER_PW);
+        let mut secrets = Vec::new();
+        for payload in [
+            access_token::AccessTokenNew {
+                name: "dashboard".to_string(),
+                scope: access_token::AccessTokenScope::Read,
+                vault_id: None,
+                expires_at: None,
+            },
+            access_token::AccessTokenNew {
+                name: "ci".to_string(),
+                scope: access_token::AccessTokenScope::Write,
+                vault_id: Some(main.clone()),
+                expires_at: None,
+            },
+        ] {
+            let res = app
+                .clone()
+                .oneshot(request(
+                    "POST",
+                    "/tokens",
+                    &owner,
+                    serde_json::to_value(&payload).unwrap(),
+                ))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::CREATED);
+            let body = res.into_body().collect().await.unwrap().to_bytes();
+            let created: access_token::AccessTokenCreated = serde_json::from_slice(&body).unwrap();
+            assert!(created.secret.starts_with("spg_"));
+            secrets.push((created.token.id, format!("Bearer {}", created.secret)));
+        }
+        let (read_id, read) = secrets[0].clone();
+        let (_, ci) = secrets[1].clone();
+
+        let cases = [
+            // Read tokens read anything the owner can read, but never write.
+            (
+                &read,
+                "POST",
+                "/vault/get",
+                serde_json::json!({ "id": other }),
+                StatusCode::OK,
+            ),
+            (
+                &read,
+                "POST",
+                "/categories",
+                serde_json::json!({ "vault_id": main, "name": "Books" }),
+                StatusCode::FORBIDDEN,
+            ),
+            // Vault-limited tokens only reach their vault, in body or path.
+            (
+                &ci,
+                "POST",
+                "/categories",
+                serde_json::json!({ "vault_id": main, "name": "Books" }),
+                StatusCode::CREATED,
+            ),
+            (
+                &ci,
+                "POST",
+                "/categories/list",
+                serde_json::json!({ "vault_id": other }),
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                &ci,
+                "POST",
+                "/vault/get",
+                serde_json::json!({ "id": other }),
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                &ci,
+                "GET",
+                &format!("/vault/{main}/members"),
+                serde_json::json!({}),
+                StatusCode::OK,
+            ),
+            (
+                &ci,
+                "POST",
+                "/vault/new",
+                serde_json::json!({ "name": "New" }),
+                StatusCode::FORBIDDEN,
+            ),
+            // Tokens cannot manage tokens; unknown tokens are rejected.
+            (
+                &read,
+                "GET",
+                "/tokens",
+                serde_json::json!({}),
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                &"Bearer spg_unknown".to_string(),
+                "POST",
+                "/vault/get",
+                serde_json::json!({ "id": main }),
+                StatusCode::UNAUTHORIZED,
+            ),
+        ];
+        for (auth, method, uri, body, status) in cases {
+            let res = app
+                .clone()
+                .oneshot(request(method, uri, auth, body))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), status, "{method} {uri}");
+        }
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                "DELETE",
+                &format!("/tokens/{read_id}"),
+                &owner,
+                serde_json::json!({}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        let res = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/vault/get",
+                &read,
+                serde_json::json!({ "id": main }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = app
+            .oneshot(request("GET", "/tokens", &owner, serde_json::json!({})))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let list: access_token::AccessTokenListResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(list.tokens.len(), 1);
+        assert_eq!(list.tokens[0].name, "ci");
+        assert_eq!(list.tokens[0].vault_id.as_deref(), Some(main.as_str()));
+    }
+
+    #[tokio::test]
+    async fn pairing_mints_a_per_user_token_and_unpair_revokes_it() {
+        let (app, engine, db) = setup().await;
+        let alice_vault = engine
+            .new_vault("Main", FLOW_MEMBER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        let code =
+            set_pair_code(&db, FLOW_MEMBER, Utc::now() + chrono::Duration::minutes(15)).await;
+
+        let request = |method: &str, uri: &str, auth: Option<&str>, body: serde_json::Value| {
+            let mut builder = axum::http::Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("telegram-user-id", "42")
+                .header(axum::http::header::CONTENT_TYPE, "application/json");
+            if let Some(auth) = auth {
+                builder = builder.header(axum::http::header::AUTHORIZATION, auth);
+            }
+            builder
+                .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap()
+        };
+        let pair = serde_json::to_value(api_types::user::PairUser {
+            code,
+            telegram_id: "42".to_string(),
+        })
+        .unwrap();
+
+        // Pairing needs no account: the code is the credential, once.
+        let res = app
+            .clone()
+            .oneshot(request("POST", "/user/pair", None, pair.clone()))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let paired: api_types::user::PairUserResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(paired.username, FLOW_MEMBER);
+        let alice_auth = format!("Bearer {}", paired.secret);
+        let res = app
+            .clone()
+            .oneshot(request("POST", "/user/pair", None, pair))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let get_alice_vault = serde_json::json!({ "id": alice_vault });
+        let res = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/vault/get",
+                Some(&alice_auth),
+                get_alice_vault.clone(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // The telegram-user-id header no longer swaps identity.
+        let res = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/vault/get",
+                Some(&basic_auth(OWNER, OWNER_PW)),
+                get_alice_vault.clone(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                "DELETE",
+                "/user/pair",
+                Some(&alice_auth),
+                serde_json::json!({}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        let res = app
+            .oneshot(request(
+                "POST",
+                "/vault/get",
+                Some(&alice_auth),
+                get_alice_vault,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let alice = crate::user::Entity::find_by_id(FLOW_MEMBER)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(alice.telegram_id, None);
+    }
+
+    #[tokio::test]
+    async fn pairing_rejects_expired_codes_and_limits_failed_attempts() {
+        let (app, _engine, db) = setup().await;
+        let attacker = std::net::SocketAddr::from(([192, 0, 2, 1], 50_000));
+        let pair = |client: std::net::SocketAddr, code: String| {
+            let mut req = axum::http::Request::builder()
+                .method("POST")
+                .uri("/user/pair")
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(
+                    serde_json::to_vec(&api_types::user::PairUser {
+                        code,
+                        telegram_id: "42".to_string(),
+                    })
+                    .unwrap(),
+                ))
+                .unwrap();
+            req.extensions_mut()
+                .insert(axum::extract::ConnectInfo(client));
+            req
         };
 
-        (router(state), engine, db)
+        // An expired code is refused, and consumed.
+        let expired =
+            set_pair_code(&db, FLOW_MEMBER, Utc::now() - chrono::Duration::minutes(1)).await;
+        let res = app.clone().oneshot(pair(attacker, expired)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let alice = crate::user::Entity::find_by_id(FLOW_MEMBER)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(alice.pair_code, None);
+        assert_eq!(alice.telegram_id, None);
+
+        // Once the failures of a client reach the limit, even a valid code
+        // from it has to wait; other clients are not affected.
+        for _ in 1..10 {
+            let res = app
+                .clone()
+                .oneshot(pair(attacker, engine::generate_pair_code()))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        }
+        let code =
+            set_pair_code(&db, FLOW_MEMBER, Utc::now() + chrono::Duration::minutes(15)).await;
+        let res = app
+            .clone()
+            .oneshot(pair(attacker, code.clone()))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error.code, ErrorCode::TooManyRequests);
+        let other = std::net::SocketAddr::from(([198, 51, 100, 7], 50_000));
+        let res = app.oneshot(pair(other, code)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
     }
 
     #[tokio::test]
@@ -307,6 +910,8 @@ mod http_tests {
                 amount_minor: 1000,
                 flow_id: Some(flow_id),
                 wallet_id: Some(wallet_id),
+                currency: None,
+                exchange_rate: None,
                 meta: engine::TxMeta {
                     category_id: None,
                     category: None,
@@ -396,6 +1001,8 @@ mod http_tests {
                 amount_minor: 1000,
                 flow_id: Some(flow_id),
                 wallet_id: Some(wallet_id),
+                currency: None,
+                exchange_rate: None,
                 meta: engine::TxMeta {
                     category_id: None,
                     category: None,
@@ -479,6 +1086,8 @@ mod http_tests {
                 amount_minor: 1000,
                 flow_id: Some(flow_id),
                 wallet_id: Some(wallet_id),
+                currency: None,
+                exchange_rate: None,
                 meta: engine::TxMeta {
                     category_id: None,
                     category: None,
@@ -757,7 +1366,9 @@ mod http_tests {
                 serde_json::to_vec(&wallet::WalletNew {
                     vault_id: vault_id.clone(),
                     name: "Bank".to_string(),
+                    currency: None,
                     opening_balance_minor: 1234,
+                    exchange_rate: None,
                     occurred_at,
                 })
                 .unwrap(),
@@ -823,6 +1434,10 @@ mod http_tests {
                     vault_id: vault_id.clone(),
                     name: "Vacanze".to_string(),
                     mode: flow::FlowMode::NetCapped { cap_minor: 10_000 },
+                    goal: Some(flow::FlowGoal::Target {
+                        target_minor: 2_000,
+                        target_date: None,
+                    }),
                     opening_balance_minor: 500,
                     occurred_at,
                 })
@@ -835,6 +1450,38 @@ mod http_tests {
         let body = res.into_body().collect().await.unwrap().to_bytes();
         let created: flow::FlowCreated = serde_json::from_slice(&body).unwrap();
 
+        let req = axum::http::Request::builder()
+            .method("GET")
+            .uri(format!("/v2/vaults/{vault_id}/snapshot"))
+            .header(
+                axum::http::header::AUTHORIZATION,
+                basic_auth(OWNER, OWNER_PW),
+            )
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let snapshot: api_types::vault::VaultSnapshot = serde_json::from_slice(&body).unwrap();
+        let goal = snapshot
+            .flows
+            .iter()
+            .find(|flow| flow.id == created.id)
+            .and_then(|flow| flow.goal.as_ref())
+            .unwrap();
+        assert_eq!(
+            (goal.saved_minor, goal.remaining_minor, goal.progress_bps),
+            (500, 1_500, 2_500)
+        );
+        assert_eq!(goal.required_monthly_minor, None);
+        assert!(
+            snapshot
+                .flows
+                .iter()
+                .filter(|flow| flow.id != created.id)
+                .all(|flow| flow.goal.is_none())
+        );
+
         let req = axum::http::Request::builder()
             .method("PATCH")
             .uri(format!("/flows/{}", created.id))
@@ -849,6 +1496,7 @@ mod http_tests {
                     name: Some("Vacanze 2026".to_string()),
                     archived: Some(true),
                     mode: Some(flow::FlowMode::IncomeCapped { cap_minor: 20_000 }),
+                    goal: Some(flow::FlowGoal::Unset),
                 })
                 .unwrap(),
             ))
@@ -865,5 +1513,1184 @@ mod http_tests {
         assert!(flow.archived);
         assert_eq!(flow.max_balance, Some(20_000));
         assert!(flow.income_balance.is_some());
+        assert_eq!(flow.target_minor, None);
+    }
+
+    #[tokio::test]
+    async fn vault_owner_can_manage_recurring_rules() {
+        let (app, engine, _db) = setup().await;
+
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let starts_at = Utc::now().with_timezone(&utc) + chrono::Duration::days(1);
+
+        let req = axum::http::Request::builder()
+            .method("POST")
+            .uri("/recurring")
+            .header(
+                axum::http::header::AUTHORIZATION,
+                basic_auth(OWNER, OWNER_PW),
+            )
+            .header(axum::http::header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(
+                serde_json::to_vec(&recurring::RecurringRuleNew {
+                    vault_id: vault_id.clone(),
+                    kind: transaction::TransactionKind::Expense,
+                    amount_minor: 900,
+                    wallet_id: None,
+                    flow_id: None,
+                    from_flow_id: None,
+                    to_flow_id: None,
+                    category: Some("gym".to_string()),
+                    note: None,
+                    frequency: recurring::RecurrenceFrequency::Monthly,
+                    interval: None,
+                    day_of_month: None,
+                    starts_at,
+                    ends_at: None,
+                    max_occurrences: None,
+                })
+                .unwrap(),
+            ))
+            .unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let created: recurring::RecurringRuleView = serde_json::from_slice(&body).unwrap();
+        assert_eq!(created.interval, 1);
+        assert!(created.wallet_id.is_some());
+        assert!(created.active);
+
+        let req = axum::http::Request::builder()
+            .method("PATCH")
+            .uri(format!("/recurring/{}", created.id))
+            .header(
+                axum::http::header::AUTHORIZATION,
+                basic_auth(OWNER, OWNER_PW),
+            )
+            .header(axum::http::header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(
+                serde_json::to_vec(&recurring::RecurringRuleUpdate {
+                    vault_id: vault_id.clone(),
+                    amount_minor: Some(1200),
+                    wallet_id: None,
+                    flow_id: None,
+                    from_flow_id: None,
+                    to_flow_id: None,
+                    category: None,
+                    note: None,
+                    ends_at: None,
+                    clear_ends_at: false,
+                    active: Some(false),
+                })
+                .unwrap(),
+            ))
+            .unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = axum::http::Request::builder()
+            .method("POST")
+            .uri("/recurring/list")
+            .header(
+                axum::http::header::AUTHORIZATION,
+                basic_auth(OWNER, OWNER_PW),
+            )
+            .header(axum::http::header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(
+                serde_json::to_vec(&recurring::RecurringRuleList {
+                    vault_id: vault_id.clone(),
+                })
+                .unwrap(),
+            ))
+            .unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let list: recurring::RecurringRuleListResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(list.rules.len(), 1);
+        assert_eq!(list.rules[0].amount_minor, 1200);
+        assert!(!list.rules[0].active);
+
+        let req = axum::http::Request::builder()
+            .method("DELETE")
+            .uri(format!("/recurring/{}", created.id))
+            .header(
+                axum::http::header::AUTHORIZATION,
+                basic_auth(OWNER, OWNER_PW),
+            )
+            .header(axum::http::header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(
+                serde_json::to_vec(&recurring::RecurringRuleDelete {
+                    vault_id: vault_id.clone(),
+                })
+                .unwrap(),
+            ))
+            .unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+        let rules = engine.list_recurring_rules(&vault_id, OWNER).await.unwrap();
+        assert!(rules.is_empty());
+    }
+
+    #[tokio::test]
+    async fn vault_owner_can_create_and_list_budgets() {
+        let (app, engine, _db) = setup().await;
+
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        let category = engine
+            .create_category(&vault_id, "Groceries", OWNER)
+            .await
+            .unwrap();
+        let today = Utc::now().date_naive();
+
+        let req = axum::http::Request::builder()
+            .method("POST")
+            .uri("/budgets")
+            .header(
+                axum::http::header::AUTHORIZATION,
+                basic_auth(OWNER, OWNER_PW),
+            )
+            .header(axum::http::header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(
+                serde_json::to_vec(&budget::BudgetNew {
+                    vault_id: vault_id.clone(),
+                    category_id: category.id,
+                    amount_minor: 40_000,
+                    period: budget::BudgetPeriod::Weekly,
+                    starts_on: today,
+                    rollover: false,
+                })
+                .unwrap(),
+            ))
+            .unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        engine
+            .expense(
+                engine::ExpenseCmd::new(&vault_id, OWNER, 1500, Utc::now())
+                    .category_id(category.id),
+            )
+            .await
+            .unwrap();
+
+        let req = axum::http::Request::builder()
+            .method("POST")
+            .uri("/budgets/list")
+            .header(
+                axum::http::header::AUTHORIZATION,
+                basic_auth(OWNER, OWNER_PW),
+            )
+            .header(axum::http::header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(
+                serde_json::to_vec(&budget::BudgetList {
+                    vault_id: vault_id.clone(),
+                    on: Some(today),
+                })
+                .unwrap(),
+            ))
+            .unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let list: budget::BudgetListResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(list.budgets.len(), 1);
+        assert_eq!(list.budgets[0].period_start, today);
+        assert_eq!(list.budgets[0].spent_minor, 1500);
+        assert_eq!(list.budgets[0].remaining_minor, 38_500);
+    }
+
+    #[tokio::test]
+    async fn csv_import_preview_then_commit() {
+        let (app, engine, _db) = setup().await;
+
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        let payload = |include_duplicates| import::CsvImport {
+            vault_id: vault_id.clone(),
+            wallet_id: None,
+            flow_id: None,
+            mapping: import::CsvMapping {
+                delimiter: None,
+                has_headers: None,
+                date_column: import::CsvColumn::Name("date".to_string()),
+                date_format: "%Y-%m-%d".to_string(),
+                amount: import::CsvAmount::Signed {
+                    column: import::CsvColumn::Index(1),
+                },
+                decimal_separator: None,
+                note_column: Some(import::CsvColumn::Name("note".to_string())),
+                category_column: None,
+            },
+            data: "date,amount,note\n2026-03-01,-4.20,Bus\n2026-03-02,oops,Train\n".to_string(),
+            include_duplicates,
+        };
+        let request = |uri: &str, body: &import::CsvImport| {
+            axum::http::Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header(
+                    axum::http::header::AUTHORIZATION,
+                    basic_auth(OWNER, OWNER_PW),
+                )
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(serde_json::to_vec(body).unwrap()))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(request("/import/csv/preview", &payload(false)))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let preview: import::ImportPreviewResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(preview.rows.len(), 1);
+        assert_eq!(preview.rows[0].kind, transaction::TransactionKind::Expense);
+        assert_eq!(preview.rows[0].amount_minor, 420);
+        assert_eq!(preview.rows[0].note.as_deref(), Some("Bus"));
+        assert_eq!(preview.errors.len(), 1);
+        assert_eq!(preview.errors[0].line, 3);
+
+        // Unparsable rows reject the commit.
+        let res = app
+            .clone()
+            .oneshot(request("/import/csv", &payload(false)))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let mut fixed = payload(false);
+        fixed.data = "date,amount,note\n2026-03-01,-4.20,Bus\n".to_string();
+        let res = app
+            .clone()
+            .oneshot(request("/import/csv", &fixed))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let report: import::ImportCommitResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report.created.len(), 1);
+        assert_eq!(report.skipped_duplicates, 0);
+    }
+
+    #[tokio::test]
+    async fn statement_import_is_idempotent() {
+        let (app, engine, _db) = setup().await;
+
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        let payload = import::StatementImport {
+            vault_id: vault_id.clone(),
+            wallet_id: None,
+            flow_id: None,
+            format: import::StatementFormat::Qif,
+            data: "!Type:Bank\nD03/01/2026\nT-4.20\nPBus\n^\n".to_string(),
+            include_duplicates: false,
+        };
+        let request = |uri: &str| {
+            axum::http::Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header(
+                    axum::http::header::AUTHORIZATION,
+                    basic_auth(OWNER, OWNER_PW),
+                )
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(
+                    serde_json::to_vec(&payload).unwrap(),
+                ))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(request("/import/statement"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let report: import::ImportCommitResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report.created.len(), 1);
+
+        let res = app
+            .clone()
+            .oneshot(request("/import/statement/preview"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let preview: import::ImportPreviewResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(preview.rows.len(), 1);
+        assert!(preview.rows[0].already_imported);
+        assert_eq!(preview.rows[0].duplicate_of, Some(report.created[0]));
+
+        let res = app
+            .clone()
+            .oneshot(request("/import/statement"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let report: import::ImportCommitResponse = serde_json::from_slice(&body).unwrap();
+        assert!(report.created.is_empty());
+        assert_eq!(report.skipped_duplicates, 1);
+    }
+
+    #[tokio::test]
+    async fn category_rules_preview_then_apply() {
+        let (app, engine, _db) = setup().await;
+
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        engine
+            .income(engine::IncomeCmd::new(&vault_id, OWNER, 10_000, Utc::now()))
+            .await
+            .unwrap();
+        let expense_id = engine
+            .expense(engine::ExpenseCmd::new(&vault_id, OWNER, 1500, Utc::now()).note("Netflix"))
+            .await
+            .unwrap();
+        let category = engine
+            .create_category(&vault_id, "Subscriptions", OWNER)
+            .await
+            .unwrap();
+        let request = |uri: &str, body: Vec<u8>| {
+            axum::http::Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header(
+                    axum::http::header::AUTHORIZATION,
+                    basic_auth(OWNER, OWNER_PW),
+                )
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(body))
+                .unwrap()
+        };
+
+        let rule = category_rule::CategoryRuleNew {
+            vault_id: vault_id.clone(),
+            category_id: category.id,
+            priority: 0,
+            conditions: category_rule::CategoryRuleConditions {
+                note_contains: Some("netflix".to_string()),
+                ..Default::default()
+            },
+        };
+        let res = app
+            .clone()
+            .oneshot(request(
+                "/category-rules",
+                serde_json::to_vec(&rule).unwrap(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let created: category_rule::CategoryRuleView = serde_json::from_slice(&body).unwrap();
+        assert_eq!(created.conditions.note_contains.as_deref(), Some("netflix"));
+
+        let invalid = serde_json::json!({
+            "vault_id": vault_id,
+            "category_id": category.id,
+            "note_regex": "(",
+        });
+        let res = app
+            .clone()
+            .oneshot(request(
+                "/category-rules",
+                serde_json::to_vec(&invalid).unwrap(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let apply = serde_json::to_vec(&category_rule::CategoryRuleApply {
+            vault_id: vault_id.clone(),
+            from: None,
+            to: None,
+            include_categorized: false,
+        })
+        .unwrap();
+        for (uri, applied) in [
+            ("/category-rules/preview", false),
+            ("/category-rules/apply", true),
+        ] {
+            let res = app
+                .clone()
+                .oneshot(request(uri, apply.clone()))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            let body = res.into_body().collect().await.unwrap().to_bytes();
+            let response: category_rule::CategoryRuleApplyResponse =
+                serde_json::from_slice(&body).unwrap();
+            assert_eq!(response.applied, applied);
+            assert_eq!(response.hits.len(), 1);
+            assert_eq!(response.hits[0].transaction_id, expense_id);
+            assert_eq!(response.hits[0].rule_id, created.id);
+        }
+
+        let tx = engine
+            .transaction_with_legs(&vault_id, expense_id, OWNER)
+            .await
+            .unwrap();
+        assert_eq!(tx.category_id, category.id);
+    }
+
+    #[tokio::test]
+    async fn split_expense_shows_lines_in_list_and_detail() {
+        let (app, engine, _db) = setup().await;
+
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        engine
+            .income(engine::IncomeCmd::new(&vault_id, OWNER, 10_000, Utc::now()))
+            .await
+            .unwrap();
+        let request = |uri: &str, body: Vec<u8>| {
+            axum::http::Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header(
+                    axum::http::header::AUTHORIZATION,
+                    basic_auth(OWNER, OWNER_PW),
+                )
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(body))
+                .unwrap()
+        };
+        let split = |total: i64| transaction::SplitExpenseNew {
+            vault_id: vault_id.clone(),
+            amount_minor: total,
+            wallet_id: None,
+            currency: None,
+            exchange_rate: None,
+            splits: vec![
+                transaction::SplitLineNew {
+                    amount_minor: 3000,
+                    flow_id: None,
+                    category_id: None,
+                    category: Some("Groceries".to_string()),
+                    note: None,
+                },
+                transaction::SplitLineNew {
+                    amount_minor: 1200,
+                    flow_id: None,
+                    category_id: None,
+                    category: Some("Household".to_string()),
+                    note: Some("soap".to_string()),
+                },
+            ],
+            note: Some("Supermarket".to_string()),
+            idempotency_key: None,
+            occurred_at: Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap()),
+        };
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                "/expense/split",
+                serde_json::to_vec(&split(5000)).unwrap(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                "/expense/split",
+                serde_json::to_vec(&split(4200)).unwrap(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let created: transaction::TransactionCreated = serde_json::from_slice(&body).unwrap();
+
+        let list = TransactionList {
+            vault_id: vault_id.clone(),
+            flow_id: None,
+            wallet_id: None,
+            limit: None,
+            cursor: None,
+            from: None,
+            to: None,
+            kinds: None,
+            include_voided: None,
+            include_transfers: None,
+        };
+        let res = app
+            .clone()
+            .oneshot(request("/transactions", serde_json::to_vec(&list).unwrap()))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let listed: transaction::TransactionListResponse = serde_json::from_slice(&body).unwrap();
+        let view = listed
+            .transactions
+            .iter()
+            .find(|tx| tx.id == created.id)
+            .unwrap();
+        assert_eq!(view.amount_minor, -4200);
+        assert_eq!(view.category.as_deref(), Some("Groceries"));
+        let amounts: Vec<i64> = view.splits.iter().map(|line| line.amount_minor).collect();
+        assert_eq!(amounts, [3000, 1200]);
+
+        let get = TransactionGet {
+            vault_id: vault_id.clone(),
+            id: created.id,
+        };
+        let res = app
+            .oneshot(request(
+                "/transactions/get",
+                serde_json::to_vec(&get).unwrap(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let detail: TransactionDetailResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(detail.legs.len(), 3);
+        assert_eq!(detail.splits.len(), 2);
+        assert_eq!(detail.splits[1].note.as_deref(), Some("soap"));
+    }
+
+    #[tokio::test]
+    async fn reports_group_by_month_and_category() {
+        let (app, engine, _db) = setup().await;
+
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        let at = Utc.with_ymd_and_hms(2026, 3, 31, 23, 30, 0).unwrap();
+        engine
+            .income(engine::IncomeCmd::new(&vault_id, OWNER, 10_000, at).category("Salary"))
+            .await
+            .unwrap();
+        engine
+            .expense(engine::ExpenseCmd::new(&vault_id, OWNER, 2500, at).category("Food"))
+            .await
+            .unwrap();
+        let request = |query: &report::ReportQuery| {
+            axum::http::Request::builder()
+                .method("POST")
+                .uri("/reports")
+                .header(
+                    axum::http::header::AUTHORIZATION,
+                    basic_auth(OWNER, OWNER_PW),
+                )
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(serde_json::to_vec(query).unwrap()))
+                .unwrap()
+        };
+
+        let mut query = report::ReportQuery {
+            vault_id: vault_id.clone(),
+            group_by: vec![
+                report::ReportGroupBy::Month,
+                report::ReportGroupBy::Category,
+            ],
+            from: None,
+            to: None,
+            kinds: None,
+            utc_offset_minutes: Some(120),
+        };
+        let res = app.clone().oneshot(request(&query)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let response: report::ReportResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response.rows.len(), 2);
+        // 23:30 UTC on March 31st is April at +02:00.
+        assert_eq!(
+            response.rows[0].period_start,
+            chrono::NaiveDate::from_ymd_opt(2026, 4, 1)
+        );
+        assert_eq!(response.rows[0].category.as_deref(), Some("Food"));
+        assert_eq!(response.rows[0].expense_minor, 2500);
+        assert_eq!(response.rows[1].net_minor, 10_000);
+
+        query.group_by = vec![report::ReportGroupBy::Day, report::ReportGroupBy::Week];
+        let res = app.oneshot(request(&query)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn balance_history_charts_the_vault_over_time() {
+        let (app, engine, _db) = setup().await;
+
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        let at = |day: u32| Utc.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap();
+        engine
+            .income(engine::IncomeCmd::new(&vault_id, OWNER, 10_000, at(2)))
+            .await
+            .unwrap();
+        engine
+            .expense(engine::ExpenseCmd::new(&vault_id, OWNER, 2500, at(10)))
+            .await
+            .unwrap();
+        let request = |uri: &str, body: serde_json::Value| {
+            axum::http::Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header(
+                    axum::http::header::AUTHORIZATION,
+                    basic_auth(OWNER, OWNER_PW),
+                )
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap()
+        };
+        let utc = FixedOffset::east_opt(0).unwrap();
+
+        let query = balance::BalanceHistoryQuery {
+            vault_id: vault_id.clone(),
+            wallet_id: None,
+            flow_id: None,
+            from: at(1).with_timezone(&utc),
+            to: at(31).with_timezone(&utc),
+            interval: balance::BalanceInterval::Week,
+            utc_offset_minutes: None,
+        };
+        let res = app
+            .clone()
+            .oneshot(request(
+                "/balances/history",
+                serde_json::to_value(&query).unwrap(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let history: balance::BalanceHistoryResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(history.opening_minor, 0);
+        let points: Vec<i64> = history
+            .points
+            .iter()
+            .map(|point| point.balance_minor)
+            .collect();
+        // Weeks of 2026-02-23, 03-02, 03-09, 03-16, 03-23 and 03-30.
+        assert_eq!(points, [0, 10_000, 7500, 7500, 7500, 7500]);
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                "/balances",
+                serde_json::json!({ "vault_id": vault_id, "at": at(5) }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let balance: balance::BalanceResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(balance.balance_minor, 10_000);
+
+        let res = app
+            .oneshot(request(
+                "/balances",
+                serde_json::json!({
+                    "vault_id": vault_id,
+                    "wallet_id": uuid::Uuid::new_v4(),
+                    "flow_id": uuid::Uuid::new_v4(),
+                    "at": at(5),
+                }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn reconciled_transactions_reject_voids_until_unlocked() {
+        let (app, engine, _db) = setup().await;
+
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        let vault = engine
+            .vault_snapshot(Some(&vault_id), None, OWNER)
+            .await
+            .unwrap();
+        let wallet_id = vault
+            .wallet
+            .values()
+            .find(|w| w.name.eq_ignore_ascii_case("Cash"))
+            .unwrap()
+            .id;
+        let at = |day: u32| Utc.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap();
+        let income = engine
+            .income(engine::IncomeCmd::new(&vault_id, OWNER, 10_000, at(2)))
+            .await
+            .unwrap();
+        let expense = engine
+            .expense(engine::ExpenseCmd::new(&vault_id, OWNER, 2500, at(10)))
+            .await
+            .unwrap();
+        let request = |uri: String, body: serde_json::Value| {
+            axum::http::Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header(
+                    axum::http::header::AUTHORIZATION,
+                    basic_auth(OWNER, OWNER_PW),
+                )
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap()
+        };
+        let utc = FixedOffset::east_opt(0).unwrap();
+
+        for id in [income, expense] {
+            let res = app
+                .clone()
+                .oneshot(request(
+                    format!("/transactions/{id}/clear"),
+                    serde_json::json!({ "vault_id": vault_id, "cleared": true }),
+                ))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+
+        let statement = serde_json::to_value(wallet::WalletReconcile {
+            vault_id: vault_id.clone(),
+            statement_date: at(15).with_timezone(&utc),
+            statement_balance_minor: 7500,
+        })
+        .unwrap();
+        let res = app
+            .clone()
+            .oneshot(request(
+                format!("/wallets/{wallet_id}/reconcile/preview"),
+                statement.clone(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let preview: wallet::ReconciliationView = serde_json::from_slice(&body).unwrap();
+        assert_eq!(preview.cleared_balance_minor, 7500);
+        assert_eq!(preview.difference_minor, 0);
+        assert_eq!(preview.cleared_count, 2);
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                format!("/wallets/{wallet_id}/reconcile"),
+                statement,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let void = || {
+            request(
+                format!("/transactions/{expense}/void"),
+                serde_json::json!({ "vault_id": vault_id }),
+            )
+        };
+        let res = app.clone().oneshot(void()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error.code, ErrorCode::TransactionLocked);
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                "/transactions/get".to_string(),
+                serde_json::to_value(TransactionGet {
+                    vault_id: vault_id.clone(),
+                    id: expense,
+                })
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let detail: TransactionDetailResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            detail.transaction.clearing_status,
+            transaction::ClearingStatus::Reconciled
+        );
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                format!("/transactions/{expense}/unlock"),
+                serde_json::json!({ "vault_id": vault_id }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = app.oneshot(void()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn v2_reads_match_the_v1_routes() {
+        let (app, engine, _db) = setup().await;
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        let other_vault_id = engine
+            .new_vault("Other", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        let at = Utc.with_ymd_and_hms(2026, 3, 31, 23, 30, 0).unwrap();
+        engine
+            .income(engine::IncomeCmd::new(&vault_id, OWNER, 10_000, at).category("Salary"))
+            .await
+            .unwrap();
+        engine
+            .expense(engine::ExpenseCmd::new(&vault_id, OWNER, 2500, at).category("Food"))
+            .await
+            .unwrap();
+        let mut cmd =
+            engine::CreateAccessTokenCmd::new(OWNER, "dashboard", engine::TokenScope::Read);
+        cmd.vault_id = Some(vault_id.clone());
+        let (_, secret) = engine.create_access_token(cmd).await.unwrap();
+
+        let send = |method: &str, uri: String, auth: String, body: serde_json::Value| {
+            let request = axum::http::Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(axum::http::header::AUTHORIZATION, auth)
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let res = app.oneshot(request).await.unwrap();
+                let status = res.status();
+                let body = res.into_body().collect().await.unwrap().to_bytes();
+                (
+                    status,
+                    serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default(),
+                )
+            }
+        };
+        let owner = || basic_auth(OWNER, OWNER_PW);
+        let get = |uri: String| send("GET", uri, owner(), serde_json::Value::Null);
+        let post =
+            |uri: &str, body: serde_json::Value| send("POST", uri.to_string(), owner(), body);
+
+        let (status, vault) = get(format!("/v2/vaults/{vault_id}")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            vault,
+            post("/vault/get", serde_json::json!({ "id": vault_id }))
+                .await
+                .1
+        );
+
+        let (status, expenses) = get(format!(
+            "/v2/vaults/{vault_id}/transactions?kinds=expense,refund&from=2026-01-01T00:00:00%2B01:00"
+        ))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(expenses["transactions"].as_array().unwrap().len(), 1);
+        let v1 = post(
+            "/transactions",
+            serde_json::json!({
+                "vault_id": vault_id,
+                "kinds": ["expense", "refund"],
+                "from": "2026-01-01T00:00:00+01:00",
+            }),
+        )
+        .await
+        .1;
+        assert_eq!(expenses, v1);
+
+        let (status, report) = get(format!(
+            "/v2/vaults/{vault_id}/reports?group_by=month,category&utc_offset_minutes=120"
+        ))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["rows"].as_array().unwrap().len(), 2);
+        let v1 = post(
+            "/reports",
+            serde_json::json!({
+                "vault_id": vault_id,
+                "group_by": ["month", "category"],
+                "utc_offset_minutes": 120,
+            }),
+        )
+        .await
+        .1;
+        assert_eq!(report, v1);
+
+        let (status, history) = get(format!(
+            "/v2/vaults/{vault_id}/balances/history?from=2026-03-01T00:00:00Z&to=2026-05-01T00:00:00Z&interval=month"
+        ))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(history["points"][1]["balance_minor"], 7500);
+        let v1 = post(
+            "/balances/history",
+            serde_json::json!({
+                "vault_id": vault_id,
+                "from": "2026-03-01T00:00:00Z",
+                "to": "2026-05-01T00:00:00Z",
+                "interval": "month",
+            }),
+        )
+        .await
+        .1;
+        assert_eq!(history, v1);
+
+        let (status, _) = get(format!("/v2/vaults/{vault_id}/transactions?kinds=bogus")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Read tokens may use the v2 reads, within their vault.
+        let bearer = || format!("Bearer {secret}");
+        let (status, _) = send(
+            "GET",
+            format!("/v2/vaults/{vault_id}/categories?include_archived=true"),
+            bearer(),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(
+            "GET",
+            format!("/v2/vaults/{other_vault_id}/categories"),
+            bearer(),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn audit_log_is_owner_only_and_filterable() {
+        let (app, engine, _db) = setup().await;
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        engine
+            .upsert_vault_member(&vault_id, FLOW_MEMBER, "editor", OWNER)
+            .await
+            .unwrap();
+        let flow_id = engine
+            .new_cash_flow(&vault_id, "Food", 0, None, None, FLOW_MEMBER)
+            .await
+            .unwrap();
+        engine
+            .set_cash_flow_mode(&vault_id, flow_id, Some(10_000), false, FLOW_MEMBER)
+            .await
+            .unwrap();
+
+        let request = |uri: String, username: &str, password: &str| {
+            axum::http::Request::builder()
+                .method("GET")
+                .uri(uri)
+                .header(
+                    axum::http::header::AUTHORIZATION,
+                    basic_auth(username, password),
+                )
+                .body(axum::body::Body::empty())
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                format!("/vault/{vault_id}/audit?actor={FLOW_MEMBER}&entity=flow&limit=1"),
+                OWNER,
+                OWNER_PW,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let page: audit::AuditListResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(page.entries.len(), 1);
+        let entry = &page.entries[0];
+        assert_eq!(entry.actor, FLOW_MEMBER);
+        assert_eq!(entry.action, audit::AuditAction::Update);
+        assert_eq!(entry.entity, audit::AuditEntity::Flow);
+        assert_eq!(entry.entity_id, flow_id.to_string());
+        assert_eq!(entry.after.as_ref().unwrap()["max_balance"], 10_000);
+
+        let cursor = page.next_cursor.unwrap();
+        let res = app
+            .clone()
+            .oneshot(request(
+                format!(
+                    "/vault/{vault_id}/audit?actor={FLOW_MEMBER}&entity=flow&limit=1&cursor={cursor}"
+                ),
+                OWNER,
+                OWNER_PW,
+            ))
+            .await
+            .unwrap();
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let page: audit::AuditListResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.entries[0].action, audit::AuditAction::Create);
+        assert!(page.next_cursor.is_none());
+
+        // Editors can write but not read the log.
+        let res = app
+            .oneshot(request(
+                format!("/vault/{vault_id}/audit"),
+                FLOW_MEMBER,
+                FLOW_MEMBER_PW,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn transaction_revisions_can_be_listed_and_reverted() {
+        let (app, engine, _db) = setup().await;
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        let income_id = engine
+            .income(engine::IncomeCmd::new(&vault_id, OWNER, 1_000, Utc::now()).note("salary"))
+            .await
+            .unwrap();
+        engine
+            .update_transaction(
+                engine::UpdateTransactionCmd::new(&vault_id, income_id, OWNER).amount_minor(1_500),
+            )
+            .await
+            .unwrap();
+
+        let request = |uri: String, body: serde_json::Value| {
+            axum::http::Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header(
+                    axum::http::header::AUTHORIZATION,
+                    basic_auth(OWNER, OWNER_PW),
+                )
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                format!("/transactions/{income_id}/revisions/list"),
+                serde_json::json!({ "vault_id": vault_id }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let list: transaction::TransactionRevisionListResponse =
+            serde_json::from_slice(&body).unwrap();
+        assert_eq!(list.revisions.len(), 1);
+        let revision = &list.revisions[0];
+        assert_eq!(revision.revision, 1);
+        assert_eq!(revision.amount_minor, 1_000);
+        assert_eq!(revision.note.as_deref(), Some("salary"));
+        assert_eq!(revision.legs.len(), 2);
+        assert_eq!(revision.replaced_by, OWNER);
+
+        let res = app
+            .clone()
+            .oneshot(request(
+                format!("/transactions/{income_id}/revert"),
+                serde_json::json!({ "vault_id": vault_id, "revision": 1 }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let tx = engine
+            .transaction_with_legs(&vault_id, income_id, OWNER)
+            .await
+            .unwrap();
+        assert_eq!(tx.amount_minor, 1_000);
+
+        let res = app
+            .oneshot(request(
+                format!("/transactions/{income_id}/revert"),
+                serde_json::json!({ "vault_id": vault_id, "revision": 7 }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn voided_transaction_can_be_restored() {
+        let (app, engine, _db) = setup().await;
+        let vault_id = engine
+            .new_vault("Main", OWNER, Some(engine::Currency::Eur))
+            .await
+            .unwrap();
+        let income_id = engine
+            .income(engine::IncomeCmd::new(&vault_id, OWNER, 1_000, Utc::now()))
+            .await
+            .unwrap();
+        engine
+            .void_transaction(&vault_id, income_id, OWNER, Utc::now())
+            .await
+            .unwrap();
+
+        let request = || {
+            axum::http::Request::builder()
+                .method("POST")
+                .uri(format!("/transactions/{income_id}/restore"))
+                .header(
+                    axum::http::header::AUTHORIZATION,
+                    basic_auth(OWNER, OWNER_PW),
+                )
+                .header(axum::http::header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(
+                    serde_json::to_vec(&transaction::TransactionRestore {
+                        vault_id: vault_id.clone(),
+                    })
+                    .unwrap(),
+                ))
+                .unwrap()
+        };
+
+        let res = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let tx = engine
+            .transaction_with_legs(&vault_id, income_id, OWNER)
+            .await
+            .unwrap();
+        assert!(tx.voided_at.is_none());
+
+        // Restoring a transaction that is not voided is rejected.
+        let res = app.oneshot(request()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
     }
 }
@@ -3,7 +3,7 @@
 use api_types::{stats::Statistic, vault::Vault};
 use axum::{Extension, Json, extract::State};
 
-use crate::{ServerError, server::ServerState, user};
+use crate::{ServerError, currency, server::ServerState, user};
 
 /// Handle requests for user statistics
 pub async fn get_stats(
@@ -25,9 +25,7 @@ pub async fn get_stats(
         .await?;
 
     Ok(Json(Statistic {
-        currency: match currency {
-            engine::Currency::Eur => api_types::Currency::Eur,
-        },
+        currency: currency::to_api(currency),
         balance_minor,
         total_income_minor,
         total_expenses_minor,
@@ -0,0 +1,195 @@
+//! Personal access token endpoints and the scope checks applied to requests
+//! authenticated with `Authorization: Bearer`.
+
+use api_types::access_token::{
+    AccessTokenCreated, AccessTokenListResponse, AccessTokenNew, AccessTokenScope, AccessTokenView,
+};
+use axum::{
+    Extension, Json,
+    body::Body,
+    extract::{FromRequestParts, MatchedPath, Path, RawPathParams, Request, State},
+    http::{Method, StatusCode},
+};
+use chrono::{FixedOffset, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{ServerError, server::ServerState, user};
+
+/// `POST` routes that only read. Every `GET` route reads too; anything else
+/// is a write.
+const READ_ROUTES: &[&str] = &[
+    "/cashFlow/get",
+    "/transactions",
+    "/transactions/get",
+    "/transactions/{id}/revisions/list",
+    "/categories/list",
+    "/categories/{id}/aliases/list",
+    "/categories/{id}/merge/preview",
+    "/vault/get",
+    "/vault/snapshot",
+    "/stats/get",
+    "/reports",
+    "/balances",
+    "/balances/history",
+    "/budgets/list",
+    "/category-rules/list",
+    "/category-rules/preview",
+    "/recurring/list",
+    "/import/csv/preview",
+    "/import/statement/preview",
+    "/wallets/{id}/reconcile/preview",
+];
+
+/// Routes whose body names the vault in `id` rather than `vault_id`.
+const VAULT_ID_AS_ID_ROUTES: &[&str] = &["/vault/get", "/vault/snapshot", "/stats/get"];
+
+/// Largest body buffered to find the vault of a vault-limited token.
+const MAX_SCOPED_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Deserialize)]
+struct VaultIdField {
+    vault_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct IdField {
+    id: Option<String>,
+}
+
+fn utc_offset() -> Result<FixedOffset, ServerError> {
+    FixedOffset::east_opt(0).ok_or_else(|| ServerError::Generic("invalid UTC offset".to_string()))
+}
+
+fn scope_from_api(scope: AccessTokenScope) -> engine::TokenScope {
+    match scope {
+        AccessTokenScope::Read => engine::TokenScope::Read,
+        AccessTokenScope::Write => engine::TokenScope::Write,
+    }
+}
+
+fn map_scope(scope: engine::TokenScope) -> AccessTokenScope {
+    match scope {
+        engine::TokenScope::Read => AccessTokenScope::Read,
+        engine::TokenScope::Write => AccessTokenScope::Write,
+    }
+}
+
+fn map_token(token: engine::AccessToken) -> Result<AccessTokenView, ServerError> {
+    let utc = utc_offset()?;
+    Ok(AccessTokenView {
+        id: token.id,
+        name: token.name,
+        scope: map_scope(token.scope),
+        vault_id: token.vault_id,
+        created_at: token.created_at.with_timezone(&utc),
+        expires_at: token.expires_at.map(|at| at.with_timezone(&utc)),
+    })
+}
+
+/// Returns the vault a request targets: the `vault_id` path parameter, or
+/// the vault named in the JSON body. The body is buffered and put back.
+async fn requested_vault_id(
+    request: Request,
+    path: &str,
+) -> Result<(Request, Option<String>), StatusCode> {
+    let (mut parts, body) = request.into_parts();
+    let params = RawPathParams::from_request_parts(&mut parts, &())
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    if let Some((_, vault_id)) = params.iter().find(|(key, _)| *key == "vault_id") {
+        let vault_id = vault_id.to_string();
+        return Ok((Request::from_parts(parts, body), Some(vault_id)));
+    }
+
+    let bytes = axum::body::to_bytes(body, MAX_SCOPED_BODY_BYTES)
+        .await
+        .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE)?;
+    let vault_id = if VAULT_ID_AS_ID_ROUTES.contains(&path) {
+        Json::<IdField>::from_bytes(&bytes)
+            .ok()
+            .and_then(|Json(field)| field.id)
+    } else {
+        Json::<VaultIdField>::from_bytes(&bytes)
+            .ok()
+            .and_then(|Json(field)| field.vault_id)
+    };
+    Ok((Request::from_parts(parts, Body::from(bytes)), vault_id))
+}
+
+/// Checks a bearer-authenticated request against the token scope.
+///
+/// Read tokens may only call read routes; vault-limited tokens may only call
+/// routes that name their vault. Tokens cannot manage tokens.
+pub(crate) async fn authorize(
+    token: &engine::AccessToken,
+    request: Request,
+) -> Result<Request, StatusCode> {
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+    if path == "/tokens" || path.starts_with("/tokens/") {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let write = request.method() != Method::GET && !READ_ROUTES.contains(&path.as_str());
+    let (request, vault_id) = if token.vault_id.is_some() {
+        requested_vault_id(request, &path).await?
+    } else {
+        (request, None)
+    };
+    if token.allows(write, vault_id.as_deref()) {
+        Ok(request)
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+pub async fn create(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<AccessTokenNew>,
+) -> Result<(StatusCode, Json<AccessTokenCreated>), ServerError> {
+    let mut cmd = engine::CreateAccessTokenCmd::new(
+        user.username,
+        payload.name,
+        scope_from_api(payload.scope),
+    );
+    cmd.vault_id = payload.vault_id;
+    cmd.expires_at = payload.expires_at.map(|at| at.with_timezone(&Utc));
+    let (token, secret) = state.engine.create_access_token(cmd).await?;
+    Ok((
+        StatusCode::CREATED,
+        Json(AccessTokenCreated {
+            token: map_token(token)?,
+            secret,
+        }),
+    ))
+}
+
+pub async fn list(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+) -> Result<Json<AccessTokenListResponse>, ServerError> {
+    let tokens = state
+        .engine
+        .list_access_tokens(&user.username)
+        .await?
+        .into_iter()
+        .map(map_token)
+        .collect::<Result<_, _>>()?;
+    Ok(Json(AccessTokenListResponse { tokens }))
+}
+
+pub async fn revoke(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(token_id): Path<Uuid>,
+) -> Result<StatusCode, ServerError> {
+    state
+        .engine
+        .revoke_access_token(token_id, &user.username)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
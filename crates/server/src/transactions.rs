@@ -1,10 +1,13 @@
 //! Transactions API endpoints
 
 use api_types::transaction::{
-    ExpenseNew, IncomeNew, LegTarget, Refund, TransactionCreated, TransactionDetailResponse,
-    TransactionGet, TransactionHeaderView, TransactionKind as ApiKind, TransactionLegView,
-    TransactionList, TransactionListResponse, TransactionUpdate, TransactionView, TransactionVoid,
-    TransferFlowNew, TransferWalletNew,
+    ClearingStatus, ExpenseNew, IncomeNew, LegTarget, Refund, RefundStatusView, SplitExpenseNew,
+    TransactionClear, TransactionCreated, TransactionDetailResponse, TransactionGet,
+    TransactionHeaderView, TransactionKind as ApiKind, TransactionLegView, TransactionList,
+    TransactionListResponse, TransactionRestore, TransactionRevert, TransactionRevisionList,
+    TransactionRevisionListResponse, TransactionRevisionView, TransactionSplitView,
+    TransactionUnlock, TransactionUpdate, TransactionView, TransactionVoid, TransferFlowNew,
+    TransferWalletNew,
 };
 use axum::{
     Extension, Json,
@@ -14,9 +17,9 @@ use axum::{
 use chrono::{FixedOffset, Utc};
 use uuid::Uuid;
 
-use crate::{ServerError, server::ServerState, user};
+use crate::{ServerError, currency, server::ServerState, user};
 
-fn map_kind(kind: engine::TransactionKind) -> ApiKind {
+pub(crate) fn map_kind(kind: engine::TransactionKind) -> ApiKind {
     match kind {
         engine::TransactionKind::Income => ApiKind::Income,
         engine::TransactionKind::Expense => ApiKind::Expense,
@@ -26,9 +29,21 @@ fn map_kind(kind: engine::TransactionKind) -> ApiKind {
     }
 }
 
-fn map_currency(currency: engine::Currency) -> api_types::Currency {
-    match currency {
-        engine::Currency::Eur => api_types::Currency::Eur,
+pub(crate) fn kind_from_api(kind: ApiKind) -> engine::TransactionKind {
+    match kind {
+        ApiKind::Income => engine::TransactionKind::Income,
+        ApiKind::Expense => engine::TransactionKind::Expense,
+        ApiKind::TransferWallet => engine::TransactionKind::TransferWallet,
+        ApiKind::TransferFlow => engine::TransactionKind::TransferFlow,
+        ApiKind::Refund => engine::TransactionKind::Refund,
+    }
+}
+
+fn map_clearing_status(status: engine::ClearingStatus) -> ClearingStatus {
+    match status {
+        engine::ClearingStatus::Uncleared => ClearingStatus::Uncleared,
+        engine::ClearingStatus::Cleared => ClearingStatus::Cleared,
+        engine::ClearingStatus::Reconciled => ClearingStatus::Reconciled,
     }
 }
 
@@ -39,6 +54,43 @@ fn map_leg_target(target: engine::LegTarget) -> LegTarget {
     }
 }
 
+fn map_leg(leg: engine::Leg) -> TransactionLegView {
+    TransactionLegView {
+        target: map_leg_target(leg.target),
+        amount_minor: leg.amount_minor,
+        attributed_user_id: leg.attributed_user_id,
+        currency: currency::to_api(leg.currency),
+        category_id: leg.category_id,
+        note: leg.note,
+    }
+}
+
+/// Split lines are the flow legs carrying a category.
+fn map_splits(legs: &[engine::Leg]) -> Vec<TransactionSplitView> {
+    legs.iter()
+        .filter_map(|leg| match (leg.target, leg.category_id) {
+            (engine::LegTarget::Flow { flow_id }, Some(category_id)) => {
+                Some(TransactionSplitView {
+                    flow_id,
+                    amount_minor: -leg.amount_minor,
+                    category_id,
+                    note: leg.note.clone(),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// "Refunded X of Y" for expenses, in the transaction (vault) currency.
+fn map_refund_status(tx: &engine::Transaction) -> Option<RefundStatusView> {
+    tx.refunded_minor.map(|refunded_minor| RefundStatusView {
+        refunded_minor,
+        total_minor: tx.amount_minor,
+        currency: currency::to_api(tx.currency),
+    })
+}
+
 pub async fn list(
     Extension(user): Extension<user::Model>,
     State(state): State<ServerState>,
@@ -51,18 +103,9 @@ pub async fn list(
     let include_transfers = payload.include_transfers.unwrap_or(false);
     let from = payload.from.map(|dt| dt.with_timezone(&Utc));
     let to = payload.to.map(|dt| dt.with_timezone(&Utc));
-    let kinds = payload.kinds.map(|kinds| {
-        kinds
-            .into_iter()
-            .map(|k| match k {
-                ApiKind::Income => engine::TransactionKind::Income,
-                ApiKind::Expense => engine::TransactionKind::Expense,
-                ApiKind::TransferWallet => engine::TransactionKind::TransferWallet,
-                ApiKind::TransferFlow => engine::TransactionKind::TransferFlow,
-                ApiKind::Refund => engine::TransactionKind::Refund,
-            })
-            .collect::<Vec<_>>()
-    });
+    let kinds = payload
+        .kinds
+        .map(|kinds| kinds.into_iter().map(kind_from_api).collect::<Vec<_>>());
 
     let filter = engine::TransactionListFilter {
         from,
@@ -72,6 +115,18 @@ pub async fn list(
         include_transfers,
     };
 
+    // Wallet legs are denominated in the wallet currency, every other amount in
+    // the transaction currency.
+    let wallet_currency = match payload.wallet_id {
+        Some(wallet_id) => Some(
+            engine
+                .wallet(wallet_id, &payload.vault_id, &user.username)
+                .await?
+                .currency,
+        ),
+        None => None,
+    };
+
     let (txs, next_cursor): (Vec<(engine::Transaction, i64)>, Option<String>) =
         match (payload.flow_id, payload.wallet_id) {
             (Some(flow_id), None) => {
@@ -137,14 +192,19 @@ pub async fn list(
     let transactions = txs
         .into_iter()
         .map(|(tx, amount_minor)| TransactionView {
+            splits: map_splits(&tx.legs),
+            refunded_transaction_id: tx.refunded_transaction_id,
+            refund_status: map_refund_status(&tx),
             id: tx.id,
             kind: map_kind(tx.kind),
             occurred_at: tx.occurred_at.with_timezone(&utc),
             amount_minor,
+            currency: currency::to_api(wallet_currency.unwrap_or(tx.currency)),
             category_id: tx.category_id,
             category: tx.category,
             note: tx.note,
             voided: tx.voided_at.is_some(),
+            clearing_status: map_clearing_status(tx.clearing_status),
         })
         .collect();
 
@@ -167,30 +227,33 @@ pub async fn get_detail(
     let utc = FixedOffset::east_opt(0)
         .ok_or_else(|| ServerError::Generic("invalid UTC offset".to_string()))?;
 
+    let refund_status = map_refund_status(&tx);
     let transaction = TransactionHeaderView {
         id: tx.id,
         kind: map_kind(tx.kind),
         occurred_at: tx.occurred_at.with_timezone(&utc),
         amount_minor: tx.amount_minor,
-        currency: map_currency(tx.currency),
+        currency: currency::to_api(tx.currency),
+        original_amount_minor: tx.original_amount_minor,
+        original_currency: tx.original_currency.map(currency::to_api),
+        exchange_rate: tx.exchange_rate.map(|rate| rate.to_string()),
         category_id: tx.category_id,
         category: tx.category,
         note: tx.note,
         voided: tx.voided_at.is_some(),
+        clearing_status: map_clearing_status(tx.clearing_status),
+        refunded_transaction_id: tx.refunded_transaction_id,
+        refund_status,
     };
 
-    let legs = tx
-        .legs
-        .into_iter()
-        .map(|leg| TransactionLegView {
-            target: map_leg_target(leg.target),
-            amount_minor: leg.amount_minor,
-            attributed_user_id: leg.attributed_user_id,
-            currency: map_currency(leg.currency),
-        })
-        .collect();
+    let splits = map_splits(&tx.legs);
+    let legs = tx.legs.into_iter().map(map_leg).collect();
 
-    Ok(Json(TransactionDetailResponse { transaction, legs }))
+    Ok(Json(TransactionDetailResponse {
+        transaction,
+        legs,
+        splits,
+    }))
 }
 
 pub async fn income_new(
@@ -205,6 +268,8 @@ pub async fn income_new(
             amount_minor: payload.amount_minor,
             flow_id: payload.flow_id,
             wallet_id: payload.wallet_id,
+            currency: payload.currency.map(currency::from_api),
+            exchange_rate: currency::parse_rate(payload.exchange_rate.as_deref())?,
             meta: engine::TxMeta {
                 category_id: payload.category_id,
                 category: payload.category,
@@ -231,6 +296,8 @@ pub async fn expense_new(
             amount_minor: payload.amount_minor,
             flow_id: payload.flow_id,
             wallet_id: payload.wallet_id,
+            currency: payload.currency.map(currency::from_api),
+            exchange_rate: currency::parse_rate(payload.exchange_rate.as_deref())?,
             meta: engine::TxMeta {
                 category_id: payload.category_id,
                 category: payload.category,
@@ -245,6 +312,45 @@ pub async fn expense_new(
     Ok((StatusCode::CREATED, Json(TransactionCreated { id })))
 }
 
+pub async fn split_expense_new(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Json(payload): Json<SplitExpenseNew>,
+) -> Result<(StatusCode, Json<TransactionCreated>), ServerError> {
+    let splits = payload
+        .splits
+        .into_iter()
+        .map(|line| engine::SplitLine {
+            amount_minor: line.amount_minor,
+            flow_id: line.flow_id,
+            category_id: line.category_id,
+            category: line.category,
+            note: line.note,
+        })
+        .collect();
+    let id = state
+        .engine
+        .split_expense(engine::SplitExpenseCmd {
+            vault_id: payload.vault_id,
+            amount_minor: payload.amount_minor,
+            wallet_id: payload.wallet_id,
+            currency: payload.currency.map(currency::from_api),
+            exchange_rate: currency::parse_rate(payload.exchange_rate.as_deref())?,
+            splits,
+            meta: engine::TxMeta {
+                category_id: None,
+                category: None,
+                note: payload.note,
+                idempotency_key: payload.idempotency_key,
+                occurred_at: payload.occurred_at.with_timezone(&Utc),
+            },
+            user_id: user.username.clone(),
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(TransactionCreated { id })))
+}
+
 pub async fn refund_new(
     Extension(user): Extension<user::Model>,
     State(state): State<ServerState>,
@@ -255,8 +361,11 @@ pub async fn refund_new(
         .refund(engine::RefundCmd {
             vault_id: payload.vault_id,
             amount_minor: payload.amount_minor,
+            refunded_transaction_id: payload.refunded_transaction_id,
             flow_id: payload.flow_id,
             wallet_id: payload.wallet_id,
+            currency: payload.currency.map(currency::from_api),
+            exchange_rate: currency::parse_rate(payload.exchange_rate.as_deref())?,
             meta: engine::TxMeta {
                 category_id: payload.category_id,
                 category: payload.category,
@@ -283,6 +392,7 @@ pub async fn transfer_wallet_new(
             amount_minor: payload.amount_minor,
             from_wallet_id: payload.from_wallet_id,
             to_wallet_id: payload.to_wallet_id,
+            exchange_rate: currency::parse_rate(payload.exchange_rate.as_deref())?,
             note: payload.note,
             idempotency_key: payload.idempotency_key,
             occurred_at: payload.occurred_at.with_timezone(&Utc),
@@ -329,6 +439,7 @@ pub async fn update(
             transaction_id: id,
             user_id: user.username.clone(),
             amount_minor: payload.amount_minor,
+            exchange_rate: currency::parse_rate(payload.exchange_rate.as_deref())?,
             wallet_id: payload.wallet_id,
             flow_id: payload.flow_id,
             from_wallet_id: payload.from_wallet_id,
@@ -362,3 +473,92 @@ pub async fn void_tx(
 
     Ok(StatusCode::OK)
 }
+
+pub async fn restore(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<TransactionRestore>,
+) -> Result<StatusCode, ServerError> {
+    state
+        .engine
+        .unvoid_transaction(&payload.vault_id, id, &user.username)
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn clear(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<TransactionClear>,
+) -> Result<StatusCode, ServerError> {
+    state
+        .engine
+        .set_transaction_cleared(&payload.vault_id, id, payload.cleared, &user.username)
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn unlock(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<TransactionUnlock>,
+) -> Result<StatusCode, ServerError> {
+    state
+        .engine
+        .unlock_transaction(&payload.vault_id, id, &user.username)
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn list_revisions(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<TransactionRevisionList>,
+) -> Result<Json<TransactionRevisionListResponse>, ServerError> {
+    let utc = FixedOffset::east_opt(0)
+        .ok_or_else(|| ServerError::Generic("invalid UTC offset".to_string()))?;
+    let revisions = state
+        .engine
+        .transaction_revisions(&payload.vault_id, id, &user.username)
+        .await?
+        .into_iter()
+        .map(|revision| TransactionRevisionView {
+            revision: revision.revision,
+            occurred_at: revision.occurred_at.with_timezone(&utc),
+            amount_minor: revision.amount_minor,
+            currency: currency::to_api(revision.currency),
+            original_amount_minor: revision.original_amount_minor,
+            original_currency: revision.original_currency.map(currency::to_api),
+            exchange_rate: revision.exchange_rate.map(|rate| rate.to_string()),
+            category_id: revision.category_id,
+            category: revision.category,
+            note: revision.note,
+            legs: revision.legs.into_iter().map(map_leg).collect(),
+            replaced_by: revision.replaced_by,
+            replaced_at: revision.replaced_at.with_timezone(&utc),
+        })
+        .collect();
+
+    Ok(Json(TransactionRevisionListResponse { revisions }))
+}
+
+pub async fn revert(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<TransactionRevert>,
+) -> Result<StatusCode, ServerError> {
+    state
+        .engine
+        .revert_transaction(&payload.vault_id, id, payload.revision, &user.username)
+        .await?;
+
+    Ok(StatusCode::OK)
+}
@@ -1,11 +1,43 @@
-//! The module contains the definition of a user and its
+//! The module contains the definition of a user and the Telegram pairing
+//! endpoints.
+//!
+//! Pairing trades the one-time code generated for a user (see the admin CLI)
+//! for a personal access token owned by that user. The bot stores the token
+//! and presents it on behalf of the Telegram account, so it never needs a
+//! service account of its own.
+//!
+//! Codes are random, stored as digests and expire. Failed attempts are
+//! limited per client address, since a wrong code cannot be tied to a user.
 
-use api_types::user::PairUser;
-use axum::{Extension, Json, extract::State, http::StatusCode};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use api_types::user::{PairUser, PairUserResponse};
+use axum::{
+    Extension, Json,
+    extract::{ConnectInfo, State},
+    http::StatusCode,
+};
+use chrono::Utc;
 use sea_orm::{ActiveValue, entity::prelude::*};
 
 use crate::{ServerError, server::ServerState};
 
+/// Name prefix of the tokens minted by pairing.
+const TELEGRAM_TOKEN_PREFIX: &str = "telegram:";
+
+/// Failed pairing attempts allowed per [`PAIR_ATTEMPT_WINDOW`].
+const MAX_FAILED_PAIR_ATTEMPTS: u32 = 10;
+
+const PAIR_ATTEMPT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Clients tracked at once; beyond it the stalest window is dropped.
+const MAX_TRACKED_CLIENTS: usize = 1024;
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "users")]
 pub struct Model {
@@ -14,6 +46,7 @@ pub struct Model {
     pub password: String,
     pub telegram_id: Option<String>,
     pub pair_code: Option<String>,
+    pub pair_code_expires_at: Option<DateTimeUtc>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -21,51 +54,139 @@ pub enum Relation {}
 
 impl ActiveModelBehavior for ActiveModel {}
 
-/// Function to pair a user with its telegram id
+/// Counts failed pairing attempts per client address, in fixed windows of
+/// [`PAIR_ATTEMPT_WINDOW`].
+#[derive(Debug, Default)]
+pub struct PairAttempts {
+    /// Start of the current window of each client and failures seen in it.
+    windows: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+impl PairAttempts {
+    /// Fails once [`MAX_FAILED_PAIR_ATTEMPTS`] attempts of `client` failed in
+    /// its current window.
+    fn ensure_allowed(&self, client: IpAddr, now: Instant) -> Result<(), ServerError> {
+        let windows = self.windows.lock().unwrap_or_else(PoisonError::into_inner);
+        match windows.get(&client) {
+            Some(&(start, failures))
+                if now.duration_since(start) < PAIR_ATTEMPT_WINDOW
+                    && failures >= MAX_FAILED_PAIR_ATTEMPTS =>
+            {
+                Err(ServerError::TooManyPairAttempts)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn record_failure(&self, client: IpAddr, now: Instant) {
+        let mut windows = self.windows.lock().unwrap_or_else(PoisonError::into_inner);
+        if !windows.contains_key(&client) && windows.len() >= MAX_TRACKED_CLIENTS {
+            windows.retain(|_, (start, _)| now.duration_since(*start) < PAIR_ATTEMPT_WINDOW);
+            let stalest = windows
+                .iter()
+                .min_by_key(|(_, (start, _))| *start)
+                .map(|(client, _)| *client);
+            if windows.len() >= MAX_TRACKED_CLIENTS
+                && let Some(stalest) = stalest
+            {
+                windows.remove(&stalest);
+            }
+        }
+        let window = windows.entry(client).or_insert((now, 0));
+        if now.duration_since(window.0) >= PAIR_ATTEMPT_WINDOW {
+            *window = (now, 0);
+        }
+        window.1 += 1;
+    }
+}
+
+/// Revokes the Telegram tokens of `username`.
+async fn revoke_telegram_tokens(state: &ServerState, username: &str) -> Result<(), ServerError> {
+    for token in state.engine.list_access_tokens(username).await? {
+        if token.name.starts_with(TELEGRAM_TOKEN_PREFIX) {
+            state.engine.revoke_access_token(token.id, username).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Function to pair a user with its telegram id.
+///
+/// Unauthenticated: the pair code is the credential. It is consumed (expired
+/// codes too), and any token of a previous pairing is revoked.
 pub async fn pair(
-    _: Extension<Model>,
     State(state): State<ServerState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
     Json(payload): Json<PairUser>,
-) -> Result<StatusCode, ServerError> {
-    if let Some(user) = Entity::find()
-        .filter(Column::PairCode.eq(payload.code))
+) -> Result<(StatusCode, Json<PairUserResponse>), ServerError> {
+    let client = peer.ip();
+    state.pair_attempts.ensure_allowed(client, Instant::now())?;
+    let code = payload.code.trim();
+    let telegram_id = payload.telegram_id.trim();
+    if code.is_empty() || telegram_id.is_empty() {
+        return Err(ServerError::InvalidPairCode);
+    }
+
+    let Some(user) = Entity::find()
+        .filter(Column::PairCode.eq(engine::hash_pair_code(code)))
         .one(&state.db)
         .await
         .map_err(|err| ServerError::Generic(err.to_string()))?
-    {
-        let mut user: ActiveModel = user.into();
-        user.telegram_id = ActiveValue::Set(Some(payload.telegram_id));
-        user.pair_code = ActiveValue::Set(None);
-
-        user.update(&state.db)
-            .await
-            .map_err(|err| ServerError::Generic(err.to_string()))?;
-    } else {
-        return Err(ServerError::Generic("user not found".to_string()));
+    else {
+        state.pair_attempts.record_failure(client, Instant::now());
+        return Err(ServerError::InvalidPairCode);
+    };
+
+    let username = user.username.clone();
+    let expired = user
+        .pair_code_expires_at
+        .is_none_or(|expires_at| expires_at <= Utc::now());
+    let mut user: ActiveModel = user.into();
+    if !expired {
+        user.telegram_id = ActiveValue::Set(Some(telegram_id.to_string()));
+    }
+    user.pair_code = ActiveValue::Set(None);
+    user.pair_code_expires_at = ActiveValue::Set(None);
+    user.update(&state.db)
+        .await
+        .map_err(|err| ServerError::Generic(err.to_string()))?;
+    if expired {
+        state.pair_attempts.record_failure(client, Instant::now());
+        return Err(ServerError::InvalidPairCode);
     }
 
-    Ok(StatusCode::CREATED)
+    revoke_telegram_tokens(&state, &username).await?;
+    let (_, secret) = state
+        .engine
+        .create_access_token(engine::CreateAccessTokenCmd::new(
+            username.clone(),
+            format!("{TELEGRAM_TOKEN_PREFIX}{telegram_id}"),
+            engine::TokenScope::Write,
+        ))
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(PairUserResponse { username, secret }),
+    ))
 }
 
-/// Function to unpair the user with its teleram id
+/// Function to unpair the authenticated user from its telegram id. Revokes
+/// the tokens minted by pairing.
 pub async fn unpair(
     Extension(user): Extension<Model>,
     State(state): State<ServerState>,
 ) -> Result<StatusCode, ServerError> {
-    if let Some(user) = Entity::find()
-        .filter(Column::TelegramId.eq(user.telegram_id))
-        .one(&state.db)
-        .await
-        .map_err(|err| ServerError::Generic(err.to_string()))?
-    {
-        let mut user: ActiveModel = user.into();
-        user.telegram_id = ActiveValue::Set(None);
-        user.update(&state.db)
-            .await
-            .map_err(|err| ServerError::Generic(err.to_string()))?;
-    } else {
+    if user.telegram_id.is_none() {
         return Err(ServerError::Generic("user not found".to_string()));
     }
 
+    revoke_telegram_tokens(&state, &user.username).await?;
+    let mut user: ActiveModel = user.into();
+    user.telegram_id = ActiveValue::Set(None);
+    user.update(&state.db)
+        .await
+        .map_err(|err| ServerError::Generic(err.to_string()))?;
+
     Ok(StatusCode::ACCEPTED)
 }
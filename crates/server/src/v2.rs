@@ -0,0 +1,288 @@
+//! Resource-oriented read routes under `/v2`.
+//!
+//! The v1 reads take their arguments as a `POST` body. Here the same reads
+//! are `GET`s below `/v2/vaults/{vault_id}`, with filters in the query string,
+//! and each handler forwards to its v1 counterpart so both return the same
+//! `api_types` views. Writes are only available through the v1 routes.
+
+use api_types::{
+    audit::{AuditListResponse, AuditQuery},
+    balance::{
+        BalanceHistoryQuery, BalanceHistoryQueryParams, BalanceHistoryResponse, BalanceQuery,
+        BalanceQueryParams, BalanceResponse,
+    },
+    budget::{BudgetList, BudgetListQuery, BudgetListResponse},
+    cash_flow::{CashFlowGet, CashFlowView},
+    category::{
+        CategoryAliasList, CategoryAliasListResponse, CategoryList, CategoryListQuery,
+        CategoryListResponse,
+    },
+    category_rule::{CategoryRuleList, CategoryRuleListResponse},
+    membership::MembersResponse,
+    recurring::{RecurringRuleList, RecurringRuleListResponse},
+    report::{ReportQuery, ReportQueryParams, ReportResponse},
+    stats::Statistic,
+    transaction::{
+        TransactionDetailResponse, TransactionGet, TransactionList, TransactionListQuery,
+        TransactionListResponse, TransactionRevisionList, TransactionRevisionListResponse,
+    },
+    vault::{Vault, VaultSnapshot},
+};
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+};
+use uuid::Uuid;
+
+use crate::{
+    ServerError, audit, balances, budgets, cash_flow, categories, category_rules, memberships,
+    openapi::ApiRouter, recurring, reports, server::ServerState, statistics, transactions, user,
+    vault,
+};
+
+/// Adds the `/v2` routes to `router`.
+pub(crate) fn routes(router: ApiRouter) -> ApiRouter {
+    router
+        .get("/v2/vaults/{vault_id}", get_vault)
+        .get("/v2/vaults/{vault_id}/snapshot", get_snapshot)
+        .get("/v2/vaults/{vault_id}/stats", get_stats)
+        .get("/v2/vaults/{vault_id}/audit", list_audit)
+        .get("/v2/vaults/{vault_id}/members", list_vault_members)
+        .get("/v2/vaults/{vault_id}/flows/{flow_id}", get_flow)
+        .get(
+            "/v2/vaults/{vault_id}/flows/{flow_id}/members",
+            list_flow_members,
+        )
+        .get("/v2/vaults/{vault_id}/transactions", list_transactions)
+        .get(
+            "/v2/vaults/{vault_id}/transactions/{transaction_id}",
+            get_transaction,
+        )
+        .get(
+            "/v2/vaults/{vault_id}/transactions/{transaction_id}/revisions",
+            list_revisions,
+        )
+        .get("/v2/vaults/{vault_id}/categories", list_categories)
+        .get(
+            "/v2/vaults/{vault_id}/categories/{category_id}/aliases",
+            list_aliases,
+        )
+        .get("/v2/vaults/{vault_id}/budgets", list_budgets)
+        .get("/v2/vaults/{vault_id}/category-rules", list_category_rules)
+        .get("/v2/vaults/{vault_id}/recurring", list_recurring)
+        .get("/v2/vaults/{vault_id}/reports", report)
+        .get("/v2/vaults/{vault_id}/balances", balance)
+        .get("/v2/vaults/{vault_id}/balances/history", balance_history)
+}
+
+fn vault_by_id(vault_id: String) -> Json<Vault> {
+    Json(Vault {
+        id: Some(vault_id),
+        name: None,
+        currency: None,
+    })
+}
+
+pub async fn get_vault(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path(vault_id): Path<String>,
+) -> Result<Json<Vault>, ServerError> {
+    vault::get(user, state, vault_by_id(vault_id)).await
+}
+
+pub async fn get_snapshot(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path(vault_id): Path<String>,
+) -> Result<Json<VaultSnapshot>, ServerError> {
+    vault::snapshot(user, state, vault_by_id(vault_id)).await
+}
+
+pub async fn get_stats(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path(vault_id): Path<String>,
+) -> Result<Json<Statistic>, ServerError> {
+    statistics::get_stats(user, state, vault_by_id(vault_id)).await
+}
+
+pub async fn list_audit(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    path: Path<String>,
+    query: Query<AuditQuery>,
+) -> Result<Json<AuditListResponse>, ServerError> {
+    audit::list(user, state, path, query).await
+}
+
+pub async fn list_vault_members(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    path: Path<String>,
+) -> Result<Json<MembersResponse>, ServerError> {
+    memberships::list_vault_members(user, state, path).await
+}
+
+pub async fn get_flow(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path((vault_id, flow_id)): Path<(String, Uuid)>,
+) -> Result<Json<CashFlowView>, ServerError> {
+    let payload = CashFlowGet {
+        vault_id,
+        id: Some(flow_id),
+        name: None,
+    };
+    cash_flow::get(user, state, Json(payload)).await
+}
+
+pub async fn list_flow_members(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    path: Path<(String, Uuid)>,
+) -> Result<Json<MembersResponse>, ServerError> {
+    memberships::list_flow_members(user, state, path).await
+}
+
+pub async fn list_transactions(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path(vault_id): Path<String>,
+    Query(query): Query<TransactionListQuery>,
+) -> Result<Json<TransactionListResponse>, ServerError> {
+    let payload = TransactionList {
+        vault_id,
+        flow_id: query.flow_id,
+        wallet_id: query.wallet_id,
+        limit: query.limit,
+        cursor: query.cursor,
+        from: query.from,
+        to: query.to,
+        kinds: query.kinds.map(|kinds| kinds.0),
+        include_voided: query.include_voided,
+        include_transfers: query.include_transfers,
+    };
+    transactions::list(user, state, Json(payload)).await
+}
+
+pub async fn get_transaction(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path((vault_id, id)): Path<(String, Uuid)>,
+) -> Result<Json<TransactionDetailResponse>, ServerError> {
+    transactions::get_detail(user, state, Json(TransactionGet { vault_id, id })).await
+}
+
+pub async fn list_revisions(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path((vault_id, id)): Path<(String, Uuid)>,
+) -> Result<Json<TransactionRevisionListResponse>, ServerError> {
+    let payload = TransactionRevisionList { vault_id };
+    transactions::list_revisions(user, state, Path(id), Json(payload)).await
+}
+
+pub async fn list_categories(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path(vault_id): Path<String>,
+    Query(query): Query<CategoryListQuery>,
+) -> Result<Json<CategoryListResponse>, ServerError> {
+    let payload = CategoryList {
+        vault_id,
+        include_archived: query.include_archived,
+    };
+    categories::list(user, state, Json(payload)).await
+}
+
+pub async fn list_aliases(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path((vault_id, category_id)): Path<(String, Uuid)>,
+) -> Result<Json<CategoryAliasListResponse>, ServerError> {
+    let payload = CategoryAliasList { vault_id };
+    categories::list_aliases(user, state, Path(category_id), Json(payload)).await
+}
+
+pub async fn list_budgets(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path(vault_id): Path<String>,
+    Query(query): Query<BudgetListQuery>,
+) -> Result<Json<BudgetListResponse>, ServerError> {
+    let payload = BudgetList {
+        vault_id,
+        on: query.on,
+    };
+    budgets::list(user, state, Json(payload)).await
+}
+
+pub async fn list_category_rules(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path(vault_id): Path<String>,
+) -> Result<Json<CategoryRuleListResponse>, ServerError> {
+    category_rules::list(user, state, Json(CategoryRuleList { vault_id })).await
+}
+
+pub async fn list_recurring(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path(vault_id): Path<String>,
+) -> Result<Json<RecurringRuleListResponse>, ServerError> {
+    recurring::list(user, state, Json(RecurringRuleList { vault_id })).await
+}
+
+pub async fn report(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path(vault_id): Path<String>,
+    Query(query): Query<ReportQueryParams>,
+) -> Result<Json<ReportResponse>, ServerError> {
+    let payload = ReportQuery {
+        vault_id,
+        group_by: query
+            .group_by
+            .map(|group_by| group_by.0)
+            .unwrap_or_default(),
+        from: query.from,
+        to: query.to,
+        kinds: query.kinds.map(|kinds| kinds.0),
+        utc_offset_minutes: query.utc_offset_minutes,
+    };
+    reports::report(user, state, Json(payload)).await
+}
+
+pub async fn balance(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path(vault_id): Path<String>,
+    Query(query): Query<BalanceQueryParams>,
+) -> Result<Json<BalanceResponse>, ServerError> {
+    let payload = BalanceQuery {
+        vault_id,
+        wallet_id: query.wallet_id,
+        flow_id: query.flow_id,
+        at: query.at,
+    };
+    balances::balance(user, state, Json(payload)).await
+}
+
+pub async fn balance_history(
+    user: Extension<user::Model>,
+    state: State<ServerState>,
+    Path(vault_id): Path<String>,
+    Query(query): Query<BalanceHistoryQueryParams>,
+) -> Result<Json<BalanceHistoryResponse>, ServerError> {
+    let payload = BalanceHistoryQuery {
+        vault_id,
+        wallet_id: query.wallet_id,
+        flow_id: query.flow_id,
+        from: query.from,
+        to: query.to,
+        interval: query.interval,
+        utc_offset_minutes: query.utc_offset_minutes,
+    };
+    balances::history(user, state, Json(payload)).await
+}
@@ -1,9 +1,12 @@
 //! Vault API endpoints
 
-use api_types::vault::{FlowView, Vault, VaultNew, VaultSnapshot, WalletView};
+use std::collections::HashMap;
+
+use api_types::vault::{FlowGoalView, FlowView, Vault, VaultNew, VaultSnapshot, WalletView};
 use axum::{Extension, Json, extract::State};
+use chrono::Utc;
 
-use crate::{ServerError, server::ServerState, user};
+use crate::{ServerError, currency, server::ServerState, user};
 
 /// Handle requests for creating new `Vault`
 pub async fn vault_new(
@@ -11,22 +14,20 @@ pub async fn vault_new(
     State(state): State<ServerState>,
     Json(payload): Json<VaultNew>,
 ) -> Result<Json<Vault>, ServerError> {
-    let currency = payload.currency.unwrap_or(api_types::Currency::Eur);
+    let payload_currency = payload.currency.unwrap_or_default();
     let vault_id = state
         .engine
         .new_vault(
             &payload.name,
             &user.username,
-            Some(match currency {
-                api_types::Currency::Eur => engine::Currency::Eur,
-            }),
+            Some(currency::from_api(payload_currency)),
         )
         .await?;
 
     Ok(Json(Vault {
         id: Some(vault_id),
         name: Some(payload.name),
-        currency: Some(currency),
+        currency: Some(payload_currency),
     }))
 }
 
@@ -48,9 +49,7 @@ pub async fn get(
     Ok(Json(Vault {
         id: Some(vault.id.clone()),
         name: Some(vault.name.clone()),
-        currency: Some(match vault.currency {
-            engine::Currency::Eur => api_types::Currency::Eur,
-        }),
+        currency: Some(currency::to_api(vault.currency)),
     }))
 }
 
@@ -76,11 +75,19 @@ pub async fn snapshot(
             id,
             name: wallet.name,
             balance_minor: wallet.balance,
+            currency: currency::to_api(wallet.currency),
             archived: wallet.archived,
         })
         .collect::<Vec<_>>();
-    wallets.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    wallets.sort_by_key(|a| a.name.to_lowercase());
 
+    let mut goals = state
+        .engine
+        .flow_goals(&vault.id, &user.username, Utc::now().date_naive())
+        .await?
+        .into_iter()
+        .map(|goal| (goal.flow_id, goal))
+        .collect::<HashMap<_, _>>();
     let mut flows = vault
         .cash_flow
         .into_iter()
@@ -92,6 +99,16 @@ pub async fn snapshot(
                 balance_minor: flow.balance,
                 archived: flow.archived,
                 is_unallocated,
+                goal: goals.remove(&id).map(|goal| FlowGoalView {
+                    target_minor: goal.target_minor,
+                    target_date: goal.target_date,
+                    saved_minor: goal.saved_minor,
+                    remaining_minor: goal.remaining_minor,
+                    progress_bps: goal.progress_bps,
+                    monthly_contribution_minor: goal.monthly_contribution_minor,
+                    required_monthly_minor: goal.required_monthly_minor,
+                    projected_completion: goal.projected_completion,
+                }),
             }
         })
         .collect::<Vec<_>>();
@@ -109,9 +126,7 @@ pub async fn snapshot(
     Ok(Json(VaultSnapshot {
         id: vault.id,
         name: vault.name,
-        currency: match vault.currency {
-            engine::Currency::Eur => api_types::Currency::Eur,
-        },
+        currency: currency::to_api(vault.currency),
         wallets,
         flows,
         unallocated_flow_id,
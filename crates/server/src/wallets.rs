@@ -1,6 +1,8 @@
 //! Wallets API endpoints.
 
-use api_types::wallet::{WalletCreated, WalletNew, WalletUpdate};
+use api_types::wallet::{
+    ReconciliationView, WalletCreated, WalletNew, WalletReconcile, WalletUpdate,
+};
 use axum::{
     Extension, Json,
     extract::{Path, State},
@@ -9,7 +11,7 @@ use axum::{
 use chrono::Utc;
 use uuid::Uuid;
 
-use crate::{ServerError, server::ServerState, user};
+use crate::{ServerError, currency, server::ServerState, user};
 
 pub async fn wallet_new(
     Extension(user): Extension<user::Model>,
@@ -19,7 +21,13 @@ pub async fn wallet_new(
     let name_trimmed = payload.name.trim();
     let wallet_id = state
         .engine
-        .new_wallet(&payload.vault_id, name_trimmed, 0, &user.username)
+        .new_wallet(
+            &payload.vault_id,
+            name_trimmed,
+            payload.currency.map(currency::from_api),
+            0,
+            &user.username,
+        )
         .await?;
 
     if payload.opening_balance_minor != 0 {
@@ -35,6 +43,9 @@ pub async fn wallet_new(
             .ok_or_else(|| ServerError::Generic("missing Unallocated flow".to_string()))?;
 
         let occurred_at = payload.occurred_at.with_timezone(&Utc);
+        // The opening balance is in the wallet currency; a foreign wallet needs
+        // a rate to book it against the (vault currency) Unallocated flow.
+        let exchange_rate = currency::parse_rate(payload.exchange_rate.as_deref())?;
         let opening_abs = payload.opening_balance_minor.abs();
 
         if payload.opening_balance_minor > 0 {
@@ -45,6 +56,8 @@ pub async fn wallet_new(
                     amount_minor: opening_abs,
                     flow_id: Some(unallocated_flow_id),
                     wallet_id: Some(wallet_id),
+                    currency: None,
+                    exchange_rate,
                     meta: engine::TxMeta {
                         category_id: None,
                         category: Some("opening".to_string()),
@@ -63,6 +76,8 @@ pub async fn wallet_new(
                     amount_minor: opening_abs,
                     flow_id: Some(unallocated_flow_id),
                     wallet_id: Some(wallet_id),
+                    currency: None,
+                    exchange_rate,
                     meta: engine::TxMeta {
                         category_id: None,
                         category: Some("opening".to_string()),
@@ -106,3 +121,57 @@ pub async fn wallet_update(
 
     Ok(StatusCode::OK)
 }
+
+fn reconcile_cmd(
+    user: &user::Model,
+    wallet_id: Uuid,
+    payload: WalletReconcile,
+) -> engine::ReconcileWalletCmd {
+    engine::ReconcileWalletCmd::new(
+        payload.vault_id,
+        user.username.clone(),
+        wallet_id,
+        payload.statement_date.with_timezone(&Utc),
+        payload.statement_balance_minor,
+    )
+}
+
+fn map_reconciliation(reconciliation: engine::Reconciliation) -> ReconciliationView {
+    ReconciliationView {
+        wallet_id: reconciliation.wallet_id,
+        currency: currency::to_api(reconciliation.currency),
+        statement_balance_minor: reconciliation.statement_balance_minor,
+        cleared_balance_minor: reconciliation.cleared_balance_minor,
+        difference_minor: reconciliation.difference_minor,
+        cleared_count: reconciliation.cleared_count,
+        uncleared_count: reconciliation.uncleared_count,
+        uncleared_minor: reconciliation.uncleared_minor,
+        reconciled_count: reconciliation.reconciled_count,
+    }
+}
+
+pub async fn reconcile_preview(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(wallet_id): Path<Uuid>,
+    Json(payload): Json<WalletReconcile>,
+) -> Result<Json<ReconciliationView>, ServerError> {
+    let reconciliation = state
+        .engine
+        .preview_reconciliation(reconcile_cmd(&user, wallet_id, payload))
+        .await?;
+    Ok(Json(map_reconciliation(reconciliation)))
+}
+
+pub async fn reconcile(
+    Extension(user): Extension<user::Model>,
+    State(state): State<ServerState>,
+    Path(wallet_id): Path<Uuid>,
+    Json(payload): Json<WalletReconcile>,
+) -> Result<Json<ReconciliationView>, ServerError> {
+    let reconciliation = state
+        .engine
+        .reconcile_wallet(reconcile_cmd(&user, wallet_id, payload))
+        .await?;
+    Ok(Json(map_reconciliation(reconciliation)))
+}
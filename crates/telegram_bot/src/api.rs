@@ -1,38 +1,65 @@
 use api_types::{
+    error::{ErrorCode, ErrorResponse},
+    report::{ReportQuery, ReportResponse},
     stats::Statistic,
     transaction::{
         ExpenseNew, IncomeNew, Refund, TransactionCreated, TransactionDetailResponse,
-        TransactionGet, TransactionList, TransactionListResponse, TransactionUpdate,
-        TransactionVoid,
+        TransactionGet, TransactionList, TransactionListResponse, TransactionRestore,
+        TransactionUpdate, TransactionVoid,
     },
-    user::PairUser,
+    user::{PairUser, PairUserResponse},
     vault::{Vault, VaultSnapshot},
 };
-use reqwest::{Client, StatusCode};
-use serde::Deserialize;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
 
-#[derive(Clone, Debug)]
+use crate::state::PrefsStore;
+
+/// Server API client. Requests on behalf of a Telegram user carry the access
+/// token minted when that user paired.
+#[derive(Clone)]
 pub(crate) struct ApiClient {
     client: Client,
     base_url: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct ErrorBody {
-    error: String,
+    prefs: PrefsStore,
 }
 
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum ApiError {
     #[error("network error: {0}")]
     Network(#[from] reqwest::Error),
-    #[error("{status}: {message}")]
-    Server { status: StatusCode, message: String },
+    #[error("telegram user not paired")]
+    NotPaired,
+    #[error("{}", .0.message)]
+    Server(ErrorResponse),
+}
+
+impl ApiError {
+    /// A failure on the bot side, reported like a server error.
+    pub(crate) fn internal(message: String) -> Self {
+        Self::Server(ErrorResponse {
+            code: ErrorCode::Internal,
+            message,
+            details: None,
+        })
+    }
+
+    /// Whether the user has to pair (again) before using the bot.
+    pub(crate) fn needs_pairing(&self) -> bool {
+        match self {
+            Self::NotPaired => true,
+            Self::Server(err) => matches!(err.code, ErrorCode::Unauthorized | ErrorCode::Forbidden),
+            Self::Network(_) => false,
+        }
+    }
 }
 
 impl ApiClient {
-    pub(crate) fn new(client: Client, base_url: String) -> Self {
-        Self { client, base_url }
+    pub(crate) fn new(client: Client, base_url: String, prefs: PrefsStore) -> Self {
+        Self {
+            client,
+            base_url,
+            prefs,
+        }
     }
 
     fn url(&self, path: &str) -> String {
@@ -43,28 +70,56 @@ impl ApiClient {
         )
     }
 
-    async fn post_json<TReq: serde::Serialize + ?Sized, TResp: for<'de> serde::Deserialize<'de>>(
+    /// Sends `req` with the credential of `telegram_user_id`, if any. A
+    /// rejected credential (e.g. revoked by unpairing) is forgotten.
+    async fn send(
         &self,
         telegram_user_id: Option<u64>,
-        path: &str,
-        body: &TReq,
-    ) -> Result<TResp, ApiError> {
-        let mut req = self.client.post(self.url(path)).json(body);
-        if let Some(id) = telegram_user_id {
-            req = req.header("telegram-user-id", id.to_string());
-        }
+        req: RequestBuilder,
+    ) -> Result<Response, ApiError> {
+        let req = match telegram_user_id {
+            Some(id) => {
+                let secret = self.prefs.credential(id).await.ok_or(ApiError::NotPaired)?;
+                req.bearer_auth(secret)
+            }
+            None => req,
+        };
 
         let resp = req.send().await?;
-        let status = resp.status();
-        if status.is_success() {
-            return Ok(resp.json::<TResp>().await?);
+        if resp.status() == StatusCode::UNAUTHORIZED
+            && let Some(id) = telegram_user_id
+            && let Err(err) = self.prefs.set_credential(id, None).await
+        {
+            tracing::warn!("{err}");
+        }
+        if resp.status().is_success() {
+            return Ok(resp);
         }
 
-        let message = match resp.json::<ErrorBody>().await {
-            Ok(err) => err.error,
-            Err(_) => "server error".to_string(),
-        };
-        Err(ApiError::Server { status, message })
+        let status = resp.status();
+        Err(match resp.json::<ErrorResponse>().await {
+            Ok(err) => ApiError::Server(err),
+            Err(_) => ApiError::Server(ErrorResponse {
+                code: if status.is_server_error() {
+                    ErrorCode::Internal
+                } else {
+                    ErrorCode::BadRequest
+                },
+                message: "server error".to_string(),
+                details: None,
+            }),
+        })
+    }
+
+    async fn post_json<TReq: serde::Serialize + ?Sized, TResp: for<'de> serde::Deserialize<'de>>(
+        &self,
+        telegram_user_id: Option<u64>,
+        path: &str,
+        body: &TReq,
+    ) -> Result<TResp, ApiError> {
+        let req = self.client.post(self.url(path)).json(body);
+        let resp = self.send(telegram_user_id, req).await?;
+        Ok(resp.json::<TResp>().await?)
     }
 
     async fn post_json_unit<TReq: serde::Serialize + ?Sized>(
@@ -73,37 +128,32 @@ impl ApiClient {
         path: &str,
         body: &TReq,
     ) -> Result<(), ApiError> {
-        let mut req = self.client.post(self.url(path)).json(body);
-        if let Some(id) = telegram_user_id {
-            req = req.header("telegram-user-id", id.to_string());
-        }
-
-        let resp = req.send().await?;
-        let status = resp.status();
-        if status.is_success() {
-            return Ok(());
-        }
-        let message = match resp.json::<ErrorBody>().await {
-            Ok(err) => err.error,
-            Err(_) => "server error".to_string(),
-        };
-        Err(ApiError::Server { status, message })
+        let req = self.client.post(self.url(path)).json(body);
+        self.send(telegram_user_id, req).await?;
+        Ok(())
     }
 
+    /// Pairs the Telegram user with the account holding `code` and stores
+    /// the credential the server mints for it.
     pub(crate) async fn pair_user(
         &self,
         telegram_user_id: u64,
         code: &str,
     ) -> Result<(), ApiError> {
-        self.post_json_unit(
-            None,
-            "/user/pair",
-            &PairUser {
-                code: code.to_string(),
-                telegram_id: telegram_user_id.to_string(),
-            },
-        )
-        .await
+        let paired: PairUserResponse = self
+            .post_json(
+                None,
+                "/user/pair",
+                &PairUser {
+                    code: code.to_string(),
+                    telegram_id: telegram_user_id.to_string(),
+                },
+            )
+            .await?;
+        self.prefs
+            .set_credential(telegram_user_id, Some(paired.secret))
+            .await
+            .map_err(ApiError::internal)
     }
 
     pub(crate) async fn vault_get_main(&self, telegram_user_id: u64) -> Result<Vault, ApiError> {
@@ -151,6 +201,15 @@ impl ApiClient {
         .await
     }
 
+    pub(crate) async fn report(
+        &self,
+        telegram_user_id: u64,
+        payload: &ReportQuery,
+    ) -> Result<ReportResponse, ApiError> {
+        self.post_json(Some(telegram_user_id), "/reports", payload)
+            .await
+    }
+
     pub(crate) async fn transactions_list(
         &self,
         telegram_user_id: u64,
@@ -210,6 +269,20 @@ impl ApiClient {
         .await
     }
 
+    pub(crate) async fn restore_transaction(
+        &self,
+        telegram_user_id: u64,
+        tx_id: uuid::Uuid,
+        payload: &TransactionRestore,
+    ) -> Result<(), ApiError> {
+        self.post_json_unit(
+            Some(telegram_user_id),
+            &format!("/transactions/{tx_id}/restore"),
+            payload,
+        )
+        .await
+    }
+
     pub(crate) async fn update_transaction(
         &self,
         telegram_user_id: u64,
@@ -219,18 +292,8 @@ impl ApiClient {
         let req = self
             .client
             .patch(self.url(&format!("/transactions/{tx_id}")))
-            .header("telegram-user-id", telegram_user_id.to_string())
             .json(payload);
-
-        let resp = req.send().await?;
-        let status = resp.status();
-        if status.is_success() {
-            return Ok(());
-        }
-        let message = match resp.json::<ErrorBody>().await {
-            Ok(err) => err.error,
-            Err(_) => "server error".to_string(),
-        };
-        Err(ApiError::Server { status, message })
+        self.send(Some(telegram_user_id), req).await?;
+        Ok(())
     }
 }
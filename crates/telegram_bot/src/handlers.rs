@@ -1,7 +1,7 @@
-use chrono::{DateTime, FixedOffset, Utc};
+use api_types::error::{ErrorCode, ErrorDetails};
+use chrono::{DateTime, Datelike, FixedOffset, Utc};
 use chrono_tz::Europe::Rome;
 use engine::{Currency as EngineCurrency, Money};
-use reqwest::StatusCode;
 use teloxide::{
     prelude::*,
     types::{CallbackQuery, ChatId, InlineKeyboardButton, InlineKeyboardMarkup, User},
@@ -449,6 +449,37 @@ pub(crate) async fn handle_callback(
         bot.send_message(chat_id, "✅ Voce annullata (void).")
             .await?;
         show_list(&bot, chat_id, user_id, &cfg).await?;
+    } else if let Some(tx_id) = data.strip_prefix("tx:restore:") {
+        let Ok(tx_id) = Uuid::parse_str(tx_id) else {
+            bot.send_message(chat_id, "Transazione non valida.").await?;
+            return Ok(());
+        };
+
+        let vault_id = match resolve_main_vault_id(&cfg.api, user_id).await {
+            Ok(vault_id) => vault_id,
+            Err(err) => {
+                bot.send_message(chat_id, user_message_for_api_error(err))
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let restored = cfg
+            .api
+            .restore_transaction(
+                user_id,
+                tx_id,
+                &api_types::transaction::TransactionRestore { vault_id },
+            )
+            .await;
+        if let Err(err) = restored {
+            bot.send_message(chat_id, user_message_for_api_error(err))
+                .await?;
+            return Ok(());
+        }
+
+        bot.send_message(chat_id, "✅ Voce ripristinata.").await?;
+        show_detail(&bot, chat_id, user_id, &cfg, tx_id).await?;
     } else if let Some(tx_id) = data.strip_prefix("tx:edit:") {
         let Ok(tx_id) = Uuid::parse_str(tx_id) else {
             bot.send_message(chat_id, "Transazione non valida.").await?;
@@ -584,6 +615,8 @@ async fn handle_pending_message(
                                 amount_minor: parsed.amount_minor,
                                 flow_id,
                                 wallet_id: Some(wallet_id),
+                                currency: None,
+                                exchange_rate: None,
                                 category_id: None,
                                 category,
                                 note: parsed.note,
@@ -602,6 +635,8 @@ async fn handle_pending_message(
                                 amount_minor: parsed.amount_minor,
                                 flow_id,
                                 wallet_id: Some(wallet_id),
+                                currency: None,
+                                exchange_rate: None,
                                 category_id: None,
                                 category,
                                 note: parsed.note,
@@ -618,8 +653,11 @@ async fn handle_pending_message(
                             &api_types::transaction::Refund {
                                 vault_id: snapshot.id.clone(),
                                 amount_minor: parsed.amount_minor,
+                                refunded_transaction_id: None,
                                 flow_id,
                                 wallet_id: Some(wallet_id),
+                                currency: None,
+                                exchange_rate: None,
                                 category_id: None,
                                 category,
                                 note: parsed.note,
@@ -658,7 +696,7 @@ async fn handle_pending_message(
                         .reply_markup(kb)
                         .await?;
                 }
-                Err(ApiError::Server { status, .. }) if status == StatusCode::CONFLICT => {
+                Err(ApiError::Server(err)) if err.code == ErrorCode::AlreadyExists => {
                     bot.send_message(chat_id, "✅ Già salvato.").await?;
                 }
                 Err(err) => {
@@ -711,6 +749,7 @@ async fn handle_pending_message(
                     &api_types::transaction::TransactionUpdate {
                         vault_id,
                         amount_minor: Some(amount_minor),
+                        exchange_rate: None,
                         wallet_id: None,
                         flow_id: None,
                         from_wallet_id: None,
@@ -757,6 +796,7 @@ async fn handle_pending_message(
                     &api_types::transaction::TransactionUpdate {
                         vault_id,
                         amount_minor: None,
+                        exchange_rate: None,
                         wallet_id: None,
                         flow_id: None,
                         from_wallet_id: None,
@@ -873,6 +913,8 @@ async fn finalize_quick_add(
                         amount_minor: draft.amount_minor,
                         flow_id: Some(flow_id),
                         wallet_id: Some(wallet_id),
+                        currency: None,
+                        exchange_rate: None,
                         category_id: None,
                         category: draft.category.clone(),
                         note: draft.note.clone(),
@@ -891,6 +933,8 @@ async fn finalize_quick_add(
                         amount_minor: draft.amount_minor,
                         flow_id: Some(flow_id),
                         wallet_id: Some(wallet_id),
+                        currency: None,
+                        exchange_rate: None,
                         category_id: None,
                         category: draft.category.clone(),
                         note: draft.note.clone(),
@@ -907,8 +951,11 @@ async fn finalize_quick_add(
                     &api_types::transaction::Refund {
                         vault_id,
                         amount_minor: draft.amount_minor,
+                        refunded_transaction_id: None,
                         flow_id: Some(flow_id),
                         wallet_id: Some(wallet_id),
+                        currency: None,
+                        exchange_rate: None,
                         category_id: None,
                         category: draft.category.clone(),
                         note: draft.note.clone(),
@@ -963,7 +1010,7 @@ async fn finalize_quick_add(
                 .reply_markup(kb)
                 .await?;
         }
-        Err(ApiError::Server { status, .. }) if status == StatusCode::CONFLICT => {
+        Err(ApiError::Server(err)) if err.code == ErrorCode::AlreadyExists => {
             bot.send_message(chat_id, "✅ Già salvato.").await?;
         }
         Err(err) => {
@@ -984,11 +1031,7 @@ async fn show_home(
     let snapshot = match cfg.api.vault_snapshot_main(user_id).await {
         Ok(s) => s,
         Err(err) => {
-            let needs_pairing = matches!(
-                err,
-                ApiError::Server { status, .. }
-                    if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN
-            );
+            let needs_pairing = err.needs_pairing();
             if needs_pairing {
                 bot.send_message(chat_id, "Per fare pairing: /start <codice>")
                     .await?;
@@ -1042,11 +1085,7 @@ async fn show_wallet_picker(
     let snapshot = match cfg.api.vault_snapshot_main(user_id).await {
         Ok(s) => s,
         Err(err) => {
-            let needs_pairing = matches!(
-                err,
-                ApiError::Server { status, .. }
-                    if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN
-            );
+            let needs_pairing = err.needs_pairing();
             if needs_pairing {
                 cfg.sessions
                     .update(chat_id, |s| s.pending = Some(PendingAction::PairCode))
@@ -1078,11 +1117,7 @@ async fn show_flow_picker(
     let snapshot = match cfg.api.vault_snapshot_main(user_id).await {
         Ok(s) => s,
         Err(err) => {
-            let needs_pairing = matches!(
-                err,
-                ApiError::Server { status, .. }
-                    if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN
-            );
+            let needs_pairing = err.needs_pairing();
             if needs_pairing {
                 cfg.sessions
                     .update(chat_id, |s| s.pending = Some(PendingAction::PairCode))
@@ -1109,11 +1144,7 @@ async fn show_list(
     let snapshot = match cfg.api.vault_snapshot_main(user_id).await {
         Ok(s) => s,
         Err(err) => {
-            let needs_pairing = matches!(
-                err,
-                ApiError::Server { status, .. }
-                    if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN
-            );
+            let needs_pairing = err.needs_pairing();
             if needs_pairing {
                 cfg.sessions
                     .update(chat_id, |s| s.pending = Some(PendingAction::PairCode))
@@ -1247,8 +1278,39 @@ async fn show_stats(
             return Ok(());
         }
     };
+    let snapshot = match cfg.api.vault_snapshot_main(user_id).await {
+        Ok(s) => s,
+        Err(err) => {
+            bot.send_message(chat_id, user_message_for_api_error(err))
+                .await?;
+            return Ok(());
+        }
+    };
+    let now = now_rome();
+    let month_start = now
+        .date_naive()
+        .with_day(1)
+        .and_then(|day| day.and_hms_opt(0, 0, 0))
+        .and_then(|start| start.and_local_timezone(Rome).single())
+        .map(|start| start.fixed_offset());
+    let query = api_types::report::ReportQuery {
+        vault_id: snapshot.id,
+        group_by: vec![api_types::report::ReportGroupBy::Category],
+        from: month_start,
+        to: None,
+        kinds: Some(vec![api_types::transaction::TransactionKind::Expense]),
+        utc_offset_minutes: Some(now.offset().local_minus_utc() / 60),
+    };
+    let report = match cfg.api.report(user_id, &query).await {
+        Ok(r) => r,
+        Err(err) => {
+            bot.send_message(chat_id, user_message_for_api_error(err))
+                .await?;
+            return Ok(());
+        }
+    };
     let currency = engine_currency(stats.currency);
-    let (text, kb) = ui::render_stats(currency, &stats);
+    let (text, kb) = ui::render_stats(currency, &stats, &report.rows);
     edit_or_send(bot, chat_id, cfg, text, kb).await
 }
 
@@ -1315,6 +1377,8 @@ async fn repeat_transaction(
                         amount_minor: detail.transaction.amount_minor,
                         flow_id,
                         wallet_id: Some(wallet_id),
+                        currency: None,
+                        exchange_rate: None,
                         category_id: Some(detail.transaction.category_id),
                         category: detail.transaction.category.clone(),
                         note: detail.transaction.note.clone(),
@@ -1333,6 +1397,8 @@ async fn repeat_transaction(
                         amount_minor: detail.transaction.amount_minor,
                         flow_id,
                         wallet_id: Some(wallet_id),
+                        currency: None,
+                        exchange_rate: None,
                         category_id: Some(detail.transaction.category_id),
                         category: detail.transaction.category.clone(),
                         note: detail.transaction.note.clone(),
@@ -1349,8 +1415,11 @@ async fn repeat_transaction(
                     &api_types::transaction::Refund {
                         vault_id,
                         amount_minor: detail.transaction.amount_minor,
+                        refunded_transaction_id: detail.transaction.refunded_transaction_id,
                         flow_id,
                         wallet_id: Some(wallet_id),
+                        currency: None,
+                        exchange_rate: None,
                         category_id: Some(detail.transaction.category_id),
                         category: detail.transaction.category.clone(),
                         note: detail.transaction.note.clone(),
@@ -1369,7 +1438,7 @@ async fn repeat_transaction(
 
     match created {
         Ok(_) => bot.send_message(chat_id, "✅ Ripetuta.").await?,
-        Err(ApiError::Server { status, .. }) if status == StatusCode::CONFLICT => {
+        Err(ApiError::Server(err)) if err.code == ErrorCode::AlreadyExists => {
             bot.send_message(chat_id, "✅ Già salvato.").await?
         }
         Err(err) => {
@@ -1408,10 +1477,9 @@ async fn edit_or_send(
 
 async fn resolve_main_vault_id(api: &ApiClient, telegram_user_id: u64) -> Result<String, ApiError> {
     let vault = api.vault_get_main(telegram_user_id).await?;
-    vault.id.ok_or(ApiError::Server {
-        status: StatusCode::INTERNAL_SERVER_ERROR,
-        message: "vault id missing".to_string(),
-    })
+    vault
+        .id
+        .ok_or_else(|| ApiError::internal("vault id missing".to_string()))
 }
 
 fn is_allowed(cfg: &ConfigParameters, from: Option<&User>) -> bool {
@@ -1451,29 +1519,51 @@ fn looks_like_quick_add(text: &str) -> bool {
 }
 
 fn user_message_for_api_error(err: ApiError) -> String {
-    match err {
+    let err = match err {
         ApiError::Network(_) => {
-            "Problemi di connessione con il server. Riprova più tardi!".to_string()
+            return "Problemi di connessione con il server. Riprova più tardi!".to_string();
+        }
+        ApiError::NotPaired => {
+            return "Non autorizzato. Usa /start per fare il pairing.".to_string();
         }
-        ApiError::Server { status, message } => match status {
-            reqwest::StatusCode::UNAUTHORIZED => {
-                "Non autorizzato. Usa /start per fare il pairing.".to_string()
+        ApiError::Server(err) => err,
+    };
+    if let Some(ErrorDetails::FlowLimit {
+        flow_name,
+        currency,
+        excess_minor,
+        ..
+    }) = &err.details
+    {
+        let excess = Money::new(*excess_minor).format(engine_currency(*currency));
+        match err.code {
+            ErrorCode::MaxBalanceReached => {
+                return format!("Il tetto di {flow_name} verrebbe superato di {excess}.");
             }
-            reqwest::StatusCode::FORBIDDEN => "Operazione non permessa.".to_string(),
-            reqwest::StatusCode::NOT_FOUND => {
-                "Risorsa non trovata. Prova a reimpostare i default.".to_string()
+            ErrorCode::InsufficientFunds => {
+                return format!("Fondi insufficienti in {flow_name}: mancano {excess}.");
             }
-            reqwest::StatusCode::CONFLICT => "Richiesta duplicata (già salvata).".to_string(),
-            reqwest::StatusCode::BAD_REQUEST => {
-                if message == "user not found" {
-                    "Codice di pairing non valido (o stai usando un database diverso da quello del server).".to_string()
-                } else {
-                    message
-                }
-            }
-            reqwest::StatusCode::UNPROCESSABLE_ENTITY => message,
-            _ => "Errore server.".to_string(),
-        },
+            _ => {}
+        }
+    }
+    match err.code {
+        ErrorCode::Unauthorized => "Non autorizzato. Usa /start per fare il pairing.".to_string(),
+        ErrorCode::InvalidPairCode => {
+            "Codice di pairing non valido o scaduto (o stai usando un database diverso da quello del server)."
+                .to_string()
+        }
+        ErrorCode::TooManyRequests => {
+            "Troppi tentativi di pairing falliti: riprova tra un minuto.".to_string()
+        }
+        ErrorCode::Forbidden => "Operazione non permessa.".to_string(),
+        ErrorCode::NotFound => "Risorsa non trovata. Prova a reimpostare i default.".to_string(),
+        ErrorCode::AlreadyExists => "Richiesta duplicata (già salvata).".to_string(),
+        ErrorCode::TransactionLocked => {
+            "Transazione riconciliata: sbloccala prima di modificarla.".to_string()
+        }
+        ErrorCode::BooksClosed => "Periodo chiuso: la contabilità è bloccata.".to_string(),
+        ErrorCode::Internal => "Errore server.".to_string(),
+        _ => err.message,
     }
 }
 
@@ -1484,6 +1574,30 @@ fn now_rome() -> DateTime<FixedOffset> {
 fn engine_currency(currency: api_types::Currency) -> EngineCurrency {
     match currency {
         api_types::Currency::Eur => EngineCurrency::Eur,
+        api_types::Currency::Usd => EngineCurrency::Usd,
+        api_types::Currency::Gbp => EngineCurrency::Gbp,
+        api_types::Currency::Chf => EngineCurrency::Chf,
+        api_types::Currency::Jpy => EngineCurrency::Jpy,
+        api_types::Currency::Cad => EngineCurrency::Cad,
+        api_types::Currency::Aud => EngineCurrency::Aud,
+        api_types::Currency::Nzd => EngineCurrency::Nzd,
+        api_types::Currency::Sek => EngineCurrency::Sek,
+        api_types::Currency::Nok => EngineCurrency::Nok,
+        api_types::Currency::Dkk => EngineCurrency::Dkk,
+        api_types::Currency::Pln => EngineCurrency::Pln,
+        api_types::Currency::Czk => EngineCurrency::Czk,
+        api_types::Currency::Huf => EngineCurrency::Huf,
+        api_types::Currency::Ron => EngineCurrency::Ron,
+        api_types::Currency::Cny => EngineCurrency::Cny,
+        api_types::Currency::Inr => EngineCurrency::Inr,
+        api_types::Currency::Krw => EngineCurrency::Krw,
+        api_types::Currency::Brl => EngineCurrency::Brl,
+        api_types::Currency::Mxn => EngineCurrency::Mxn,
+        api_types::Currency::Bhd => EngineCurrency::Bhd,
+        api_types::Currency::Kwd => EngineCurrency::Kwd,
+        api_types::Currency::Omr => EngineCurrency::Omr,
+        api_types::Currency::Jod => EngineCurrency::Jod,
+        api_types::Currency::Tnd => EngineCurrency::Tnd,
     }
 }
 
@@ -5,8 +5,7 @@
 
 use std::path::PathBuf;
 
-use base64::Engine;
-use reqwest::{Client, header};
+use reqwest::Client;
 use teloxide::prelude::*;
 
 mod api;
@@ -38,24 +37,11 @@ impl Bot {
         token: &str,
         allowed_users: Option<Vec<UserId>>,
         server: &str,
-        username: &str,
-        password: &str,
         state_path: PathBuf,
     ) -> Result<Self, String> {
-        // Basic authorization is in the form "Basic `secret`" where `secret` is
-        // the base64 of the string "username:password".
-        let secret = format!("{username}:{password}");
-        let secret = format!("Basic {}", base64::prelude::BASE64_STANDARD.encode(secret));
-
-        let mut auth = header::HeaderValue::try_from(secret)
-            .map_err(|err| format!("invalid auth header value: {err}"))?;
-        auth.set_sensitive(true);
-
-        let mut headers = header::HeaderMap::new();
-        headers.insert(header::AUTHORIZATION, auth);
-
+        // No default credentials: every request carries the access token of
+        // the Telegram user it is made for.
         let client = Client::builder()
-            .default_headers(headers)
             .build()
             .map_err(|err| format!("failed to build http client: {err}"))?;
 
@@ -80,7 +66,7 @@ impl Bot {
 
         let parameters = ConfigParameters {
             allowed_users: self.allowed_users.clone(),
-            api: api::ApiClient::new(self.client.clone(), self.server.clone()),
+            api: api::ApiClient::new(self.client.clone(), self.server.clone(), prefs.clone()),
             prefs,
             sessions: state::SessionStore::default(),
         };
@@ -109,8 +95,6 @@ pub struct BotBuilder {
     token: String,
     allowed_users: Option<Vec<UserId>>,
     server: String,
-    username: String,
-    password: String,
     state_path: Option<PathBuf>,
 }
 
@@ -127,10 +111,8 @@ impl BotBuilder {
         self
     }
 
-    pub fn server(mut self, server: &str, username: &str, password: &str) -> BotBuilder {
+    pub fn server(mut self, server: &str) -> BotBuilder {
         self.server = server.to_string();
-        self.username = username.to_string();
-        self.password = password.to_string();
         self
     }
 
@@ -144,13 +126,6 @@ impl BotBuilder {
         let state_path = self
             .state_path
             .unwrap_or_else(|| PathBuf::from(DEFAULT_STATE_PATH));
-        Bot::new(
-            &self.token,
-            self.allowed_users,
-            &self.server,
-            &self.username,
-            &self.password,
-            state_path,
-        )
+        Bot::new(&self.token, self.allowed_users, &self.server, state_path)
     }
 }
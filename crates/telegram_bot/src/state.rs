@@ -1,6 +1,7 @@
 use std::{
     collections::HashMap,
     fs,
+    io::Write as _,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -36,6 +37,9 @@ impl Default for UserPrefs {
 #[derive(Debug, Default, Serialize, Deserialize)]
 struct PrefsFile {
     users: HashMap<String, UserPrefs>,
+    /// Access token secrets minted by pairing, per Telegram user.
+    #[serde(default)]
+    credentials: HashMap<String, String>,
 }
 
 #[derive(Clone)]
@@ -73,6 +77,31 @@ impl PrefsStore {
         write_json_file(&self.path, &guard).map_err(|e| format!("state save failed: {e}"))?;
         Ok(snapshot)
     }
+
+    /// Returns the API credential stored when the Telegram user paired.
+    pub(crate) async fn credential(&self, telegram_user_id: u64) -> Option<String> {
+        let guard = self.inner.lock().await;
+        guard
+            .credentials
+            .get(&telegram_user_id.to_string())
+            .cloned()
+    }
+
+    /// Stores (or, with `None`, forgets) the API credential of a Telegram
+    /// user.
+    pub(crate) async fn set_credential(
+        &self,
+        telegram_user_id: u64,
+        secret: Option<String>,
+    ) -> Result<(), String> {
+        let key = telegram_user_id.to_string();
+        let mut guard = self.inner.lock().await;
+        match secret {
+            Some(secret) => guard.credentials.insert(key, secret),
+            None => guard.credentials.remove(&key),
+        };
+        write_json_file(&self.path, &guard).map_err(|e| format!("state save failed: {e}"))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -157,6 +186,9 @@ fn read_json_file(path: &Path) -> Option<PrefsFile> {
     serde_json::from_str(&raw).ok()
 }
 
+/// Writes `prefs` to a temporary file readable by the owner only, then
+/// renames it over `path`: the file holds bearer tokens, and a crash must not
+/// leave it truncated.
 fn write_json_file(path: &Path, prefs: &PrefsFile) -> Result<(), std::io::Error> {
     let Some(parent) = path.parent() else {
         return Ok(());
@@ -167,13 +199,51 @@ fn write_json_file(path: &Path, prefs: &PrefsFile) -> Result<(), std::io::Error>
         .map_err(|_| std::io::Error::other("serialize failed"))?;
 
     let tmp = path.with_extension("tmp");
-    fs::write(&tmp, json)?;
-    match fs::rename(&tmp, path) {
-        Ok(()) => Ok(()),
-        Err(_) => {
-            fs::copy(&tmp, path)?;
-            let _ = fs::remove_file(&tmp);
-            Ok(())
+    // A leftover temporary file keeps its mode when reopened: start afresh.
+    match fs::remove_file(&tmp) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&tmp)?;
+    file.write_all(json.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefs_file_is_private_and_replaced_whole() {
+        let dir = std::env::temp_dir().join(format!("sparagne-prefs-{}", Uuid::new_v4()));
+        let path = dir.join("prefs.json");
+        let mut prefs = PrefsFile::default();
+        prefs
+            .credentials
+            .insert("42".to_string(), "spg_secret".to_string());
+
+        write_json_file(&path, &prefs).unwrap();
+        prefs.credentials.clear();
+        write_json_file(&path, &prefs).unwrap();
+
+        let stored = read_json_file(&path).unwrap();
+        assert!(stored.credentials.is_empty());
+        assert!(!path.with_extension("tmp").exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
         }
+        fs::remove_dir_all(dir).unwrap();
     }
 }
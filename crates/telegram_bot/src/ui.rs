@@ -1,4 +1,5 @@
 use api_types::{
+    report::ReportRowView,
     stats::Statistic,
     transaction::{
         TransactionDetailResponse, TransactionKind, TransactionListResponse, TransactionView,
@@ -205,6 +206,13 @@ pub(crate) fn render_list(
                 .unwrap_or_default(),
             if tx.voided { " • void" } else { "" }
         ));
+        if let Some(status) = tx.refund_status.as_ref().filter(|s| s.refunded_minor > 0) {
+            text.push_str(&format!(
+                " • rimborsato {} di {}",
+                Money::new(status.refunded_minor).format(currency),
+                Money::new(status.total_minor).format(currency),
+            ));
+        }
     }
 
     let mut rows: Vec<Vec<InlineKeyboardButton>> = Vec::new();
@@ -252,6 +260,13 @@ pub(crate) fn render_detail(
         tx.note.as_deref().unwrap_or("-"),
         if tx.voided { "sì" } else { "no" }
     );
+    if let Some(status) = &tx.refund_status {
+        text.push_str(&format!(
+            "\nRimborsato: {} di {}",
+            Money::new(status.refunded_minor).format(currency),
+            Money::new(status.total_minor).format(currency),
+        ));
+    }
 
     text.push_str("\n\nLegs:");
     for leg in &detail.legs {
@@ -262,9 +277,14 @@ pub(crate) fn render_detail(
         ));
     }
 
+    let void_or_restore = if tx.voided {
+        InlineKeyboardButton::callback("♻️ Ripristina", format!("tx:restore:{id}", id = tx.id))
+    } else {
+        InlineKeyboardButton::callback("↩ Void", format!("tx:void:{id}", id = tx.id))
+    };
     let kb = InlineKeyboardMarkup::new(vec![
         vec![
-            InlineKeyboardButton::callback("↩ Void", format!("tx:void:{id}", id = tx.id)),
+            void_or_restore,
             InlineKeyboardButton::callback("✏️ Edit", format!("tx:edit:{id}", id = tx.id)),
             InlineKeyboardButton::callback("📌 Ripeti", format!("tx:repeat:{id}", id = tx.id)),
         ],
@@ -293,13 +313,30 @@ pub(crate) fn render_edit_menu(tx_id: Uuid) -> (String, InlineKeyboardMarkup) {
 pub(crate) fn render_stats(
     currency: EngineCurrency,
     stats: &Statistic,
+    month_by_category: &[ReportRowView],
 ) -> (String, InlineKeyboardMarkup) {
-    let text = format!(
+    let mut text = format!(
         "Stats\n\nBilancio: {}\nTotale entrate: {}\nTotale uscite: {}",
         Money::new(stats.balance_minor).format(currency),
         Money::new(stats.total_income_minor).format(currency),
         Money::new(stats.total_expenses_minor).format(currency),
     );
+    // Rows come sorted by expense, largest first.
+    let top = month_by_category
+        .iter()
+        .filter(|row| row.expense_minor > 0)
+        .take(5)
+        .collect::<Vec<_>>();
+    if !top.is_empty() {
+        text.push_str("\n\nUscite del mese:");
+        for row in top {
+            text.push_str(&format!(
+                "\n- {}: {}",
+                row.category.as_deref().unwrap_or("Altro"),
+                Money::new(row.expense_minor).format(currency),
+            ));
+        }
+    }
     let kb = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
         "⬅️ Home",
         "nav:home",
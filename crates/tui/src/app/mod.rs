@@ -8,21 +8,27 @@ use crate::{
     error::{AppError, Result},
     local_state::{LocalState, default_state_path},
     quick_add::QuickAddKind,
-    ui,
+    ui::{self, components::money::map_currency},
 };
 
 use api_types::{
+    balance::{BalanceHistoryQuery, BalanceHistoryResponse, BalanceInterval, BalancePointView},
+    error::{ErrorCode, ErrorDetails, ErrorResponse},
     flow::{FlowMode, FlowNew, FlowUpdate},
+    report::{ReportGroupBy, ReportQuery, ReportResponse, ReportRowView},
     stats::Statistic,
     transaction::{
         ExpenseNew, IncomeNew, Refund, TransactionDetailResponse, TransactionGet, TransactionKind,
-        TransactionList, TransactionListResponse, TransactionUpdate, TransactionView,
-        TransactionVoid, TransferFlowNew, TransferWalletNew,
+        TransactionList, TransactionListResponse, TransactionRestore, TransactionUpdate,
+        TransactionView, TransactionVoid, TransferFlowNew, TransferWalletNew,
     },
     vault::{Vault, VaultNew, VaultSnapshot},
     wallet::{WalletNew, WalletUpdate},
 };
-use chrono::{DateTime, Datelike, Duration as ChronoDuration, FixedOffset, Offset, TimeZone, Utc};
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, FixedOffset, Months, NaiveDate, NaiveTime,
+    Offset, TimeZone, Utc,
+};
 use chrono_tz::Tz;
 use engine::Money;
 use std::str::FromStr;
@@ -193,11 +199,7 @@ impl App {
                 }
             }
             crate::ui::keymap::AppAction::Quit => {
-                if self.state.screen == Screen::Login {
-                    self.should_quit = true;
-                } else {
-                    self.should_quit = true;
-                }
+                self.should_quit = true;
             }
             crate::ui::keymap::AppAction::Cancel => {
                 if self.state.screen == Screen::Login {
@@ -849,11 +851,22 @@ impl App {
                 return Ok(());
             }
             'v' | 'V' => {
-                // In transaction detail, 'v' voids the transaction
+                // In transaction detail, 'v' voids the transaction (or restores
+                // a voided one)
                 if self.state.section == Section::Transactions
                     && self.state.transactions.mode == TransactionsMode::Detail
                 {
-                    self.void_transaction().await?;
+                    let voided = self
+                        .state
+                        .transactions
+                        .detail
+                        .as_ref()
+                        .is_some_and(|detail| detail.transaction.voided);
+                    if voided {
+                        self.restore_transaction().await?;
+                    } else {
+                        self.void_transaction().await?;
+                    }
                 } else {
                     self.state.section = Section::Vault;
                     self.state.transactions.mode = TransactionsMode::List;
@@ -1154,11 +1167,9 @@ impl App {
                     return true;
                 }
             }
-            Section::Vault => {
-                if self.state.vault_ui.mode == VaultMode::Create {
-                    self.state.vault_ui.form.name.push(ch);
-                    return true;
-                }
+            Section::Vault if self.state.vault_ui.mode == VaultMode::Create => {
+                self.state.vault_ui.form.name.push(ch);
+                return true;
             }
             _ => {}
         }
@@ -1333,10 +1344,10 @@ impl App {
     }
 
     fn expire_toast(&mut self) {
-        if let Some(toast) = &self.state.toast {
-            if std::time::Instant::now() >= toast.expires_at {
-                self.state.toast = None;
-            }
+        if let Some(toast) = &self.state.toast
+            && std::time::Instant::now() >= toast.expires_at
+        {
+            self.state.toast = None;
         }
     }
 
@@ -1360,7 +1371,7 @@ impl App {
     }
 
     fn handle_auth_error(&mut self, err: &ClientError) -> bool {
-        if matches!(err, ClientError::Unauthorized | ClientError::Forbidden) {
+        if matches!(err, ClientError::Unauthorized) {
             self.state.screen = Screen::Login;
             self.state.login.password.clear();
             self.state.login.message = Some("Credenziali errate o pairing mancante.".to_string());
@@ -2216,6 +2227,7 @@ impl App {
                     TransactionUpdate {
                         vault_id: vault_id.to_string(),
                         amount_minor: Some(amount_minor),
+                        exchange_rate: None,
                         wallet_id: Some(wallet_id),
                         flow_id: Some(flow_id),
                         from_wallet_id: None,
@@ -2258,6 +2270,8 @@ impl App {
                                 amount_minor,
                                 flow_id: Some(flow_id),
                                 wallet_id: Some(wallet_id),
+                                currency: None,
+                                exchange_rate: None,
                                 category_id: None,
                                 category,
                                 note,
@@ -2277,6 +2291,8 @@ impl App {
                                 amount_minor,
                                 flow_id: Some(flow_id),
                                 wallet_id: Some(wallet_id),
+                                currency: None,
+                                exchange_rate: None,
                                 category_id: None,
                                 category,
                                 note,
@@ -2293,9 +2309,12 @@ impl App {
                             self.state.login.password.as_str(),
                             Refund {
                                 vault_id: vault_id.to_string(),
+                                refunded_transaction_id: None,
                                 amount_minor,
                                 flow_id: Some(flow_id),
                                 wallet_id: Some(wallet_id),
+                                currency: None,
+                                exchange_rate: None,
                                 category_id: None,
                                 category,
                                 note,
@@ -2454,6 +2473,7 @@ impl App {
                     TransactionUpdate {
                         vault_id,
                         amount_minor: Some(amount),
+                        exchange_rate: None,
                         wallet_id: None,
                         flow_id: None,
                         from_wallet_id: Some(from_id),
@@ -2492,6 +2512,7 @@ impl App {
                     TransferWalletNew {
                         vault_id,
                         amount_minor: amount,
+                        exchange_rate: None,
                         from_wallet_id: from_id,
                         to_wallet_id: to_id,
                         note: if note.is_empty() {
@@ -2581,6 +2602,7 @@ impl App {
                     TransactionUpdate {
                         vault_id,
                         amount_minor: Some(amount),
+                        exchange_rate: None,
                         wallet_id: None,
                         flow_id: None,
                         from_wallet_id: None,
@@ -3359,6 +3381,48 @@ impl App {
         Ok(())
     }
 
+    async fn restore_transaction(&mut self) -> Result<()> {
+        let vault_id = self
+            .state
+            .vault
+            .as_ref()
+            .and_then(|v| v.id.as_deref())
+            .ok_or_else(|| AppError::Terminal("missing vault id".to_string()))?;
+        let Some(detail) = self.state.transactions.detail.as_ref() else {
+            return Ok(());
+        };
+
+        let res = self
+            .client
+            .transaction_restore(
+                self.state.login.username.as_str(),
+                self.state.login.password.as_str(),
+                detail.transaction.id,
+                TransactionRestore {
+                    vault_id: vault_id.to_string(),
+                },
+            )
+            .await;
+
+        match res {
+            Ok(()) => {
+                self.state.transactions.mode = TransactionsMode::List;
+                self.state.transactions.detail = None;
+                self.set_toast("Transazione ripristinata.", ToastLevel::Success);
+                self.load_transactions(true).await?;
+            }
+            Err(err) => {
+                if self.handle_auth_error(&err) {
+                    return Ok(());
+                }
+                self.state.transactions.error = Some(login_message_for_error(err));
+                self.set_toast("Errore durante il ripristino.", ToastLevel::Error);
+            }
+        }
+
+        Ok(())
+    }
+
     async fn repeat_transaction(&mut self) -> Result<()> {
         let vault_id = self
             .state
@@ -3382,9 +3446,19 @@ impl App {
                         self.state.login.password.as_str(),
                         IncomeNew {
                             vault_id: vault_id.to_string(),
-                            amount_minor: detail.transaction.amount_minor,
+                            amount_minor: detail
+                                .transaction
+                                .original_amount_minor
+                                .unwrap_or(detail.transaction.amount_minor),
                             flow_id,
                             wallet_id,
+                            currency: Some(
+                                detail
+                                    .transaction
+                                    .original_currency
+                                    .unwrap_or(detail.transaction.currency),
+                            ),
+                            exchange_rate: detail.transaction.exchange_rate.clone(),
                             category_id: Some(detail.transaction.category_id),
                             category: detail.transaction.category.clone(),
                             note: detail.transaction.note.clone(),
@@ -3403,9 +3477,19 @@ impl App {
                         self.state.login.password.as_str(),
                         ExpenseNew {
                             vault_id: vault_id.to_string(),
-                            amount_minor: detail.transaction.amount_minor,
+                            amount_minor: detail
+                                .transaction
+                                .original_amount_minor
+                                .unwrap_or(detail.transaction.amount_minor),
                             flow_id,
                             wallet_id,
+                            currency: Some(
+                                detail
+                                    .transaction
+                                    .original_currency
+                                    .unwrap_or(detail.transaction.currency),
+                            ),
+                            exchange_rate: detail.transaction.exchange_rate.clone(),
                             category_id: Some(detail.transaction.category_id),
                             category: detail.transaction.category.clone(),
                             note: detail.transaction.note.clone(),
@@ -3424,9 +3508,20 @@ impl App {
                         self.state.login.password.as_str(),
                         Refund {
                             vault_id: vault_id.to_string(),
-                            amount_minor: detail.transaction.amount_minor,
+                            refunded_transaction_id: detail.transaction.refunded_transaction_id,
+                            amount_minor: detail
+                                .transaction
+                                .original_amount_minor
+                                .unwrap_or(detail.transaction.amount_minor),
                             flow_id,
                             wallet_id,
+                            currency: Some(
+                                detail
+                                    .transaction
+                                    .original_currency
+                                    .unwrap_or(detail.transaction.currency),
+                            ),
+                            exchange_rate: detail.transaction.exchange_rate.clone(),
                             category_id: Some(detail.transaction.category_id),
                             category: detail.transaction.category.clone(),
                             note: detail.transaction.note.clone(),
@@ -3445,6 +3540,7 @@ impl App {
                         TransferWalletNew {
                             vault_id: vault_id.to_string(),
                             amount_minor: detail.transaction.amount_minor,
+                            exchange_rate: detail.transaction.exchange_rate.clone(),
                             from_wallet_id,
                             to_wallet_id,
                             note: detail.transaction.note.clone(),
@@ -3548,6 +3644,8 @@ impl App {
                             amount_minor: parsed.amount_minor,
                             flow_id: Some(flow_id),
                             wallet_id: Some(wallet_id),
+                            currency: None,
+                            exchange_rate: None,
                             category_id: None,
                             category: parsed.category.clone(),
                             note: parsed.note.clone(),
@@ -3567,6 +3665,8 @@ impl App {
                             amount_minor: parsed.amount_minor,
                             flow_id: Some(flow_id),
                             wallet_id: Some(wallet_id),
+                            currency: None,
+                            exchange_rate: None,
                             category_id: None,
                             category: parsed.category.clone(),
                             note: parsed.note.clone(),
@@ -3583,9 +3683,12 @@ impl App {
                         self.state.login.password.as_str(),
                         Refund {
                             vault_id: vault_id.to_string(),
+                            refunded_transaction_id: None,
                             amount_minor: parsed.amount_minor,
                             flow_id: Some(flow_id),
                             wallet_id: Some(wallet_id),
+                            currency: None,
+                            exchange_rate: None,
                             category_id: None,
                             category: parsed.category.clone(),
                             note: parsed.note.clone(),
@@ -3701,7 +3804,9 @@ impl App {
                 WalletNew {
                     vault_id,
                     name: name.to_string(),
+                    currency: None,
                     opening_balance_minor: opening,
+                    exchange_rate: None,
                     occurred_at: self.now_in_timezone(),
                 },
             )
@@ -3946,6 +4051,7 @@ impl App {
                     vault_id,
                     name,
                     mode,
+                    goal: None,
                     opening_balance_minor: opening,
                     occurred_at: self.now_in_timezone(),
                 },
@@ -3998,6 +4104,7 @@ impl App {
                     name: Some(name.to_string()),
                     archived: None,
                     mode: None,
+                    goal: None,
                 },
             )
             .await;
@@ -4041,6 +4148,7 @@ impl App {
                     name: None,
                     archived: Some(!flow.archived),
                     mode: None,
+                    goal: None,
                 },
             )
             .await;
@@ -4139,149 +4247,171 @@ impl App {
     async fn load_stats_series(&mut self) -> Result<()> {
         let vault_id = self.current_vault_id()?;
         let to = self.now_in_timezone();
-        let from = to - chrono::Duration::days(180);
+        let offset = *to.offset();
+        let midnight = |date: NaiveDate| {
+            offset
+                .from_local_datetime(&date.and_time(NaiveTime::MIN))
+                .single()
+        };
 
-        let mut cursor = None;
-        let mut transactions = Vec::new();
-        loop {
-            let payload = TransactionList {
+        let start_day = (to - ChronoDuration::days(29)).date_naive();
+        let months = Self::build_last_months(to, 6);
+        let (current_year, current_month) = self.state.stats.current_month;
+        let month_start = NaiveDate::from_ymd_opt(current_year, current_month, 1);
+        let ranges = (
+            midnight(start_day),
+            months
+                .first()
+                .and_then(|(year, month, _)| NaiveDate::from_ymd_opt(*year, *month, 1))
+                .and_then(midnight),
+            month_start.and_then(midnight),
+            month_start
+                .and_then(|day| day.checked_add_months(Months::new(1)))
+                .and_then(midnight),
+        );
+        let (Some(days_from), Some(months_from), Some(breakdown_from), Some(breakdown_to)) = ranges
+        else {
+            self.state.stats.error = Some("Intervallo date non valido".to_string());
+            return Ok(());
+        };
+
+        let Some(net_worth) = self
+            .stats_balance_history(BalanceHistoryQuery {
                 vault_id: vault_id.clone(),
-                flow_id: None,
                 wallet_id: None,
-                limit: Some(200),
-                cursor,
-                from: Some(from),
-                to: Some(to),
-                kinds: None,
-                include_voided: Some(false),
-                include_transfers: Some(false),
-            };
-
-            let res = self
-                .client
-                .transactions_list(
-                    self.state.login.username.as_str(),
-                    self.state.login.password.as_str(),
-                    payload,
-                )
-                .await;
+                flow_id: None,
+                from: days_from,
+                to,
+                interval: BalanceInterval::Day,
+                utc_offset_minutes: Some(offset.local_minus_utc() / 60),
+            })
+            .await
+        else {
+            return Ok(());
+        };
 
-            match res {
-                Ok(list) => {
-                    transactions.extend(list.transactions);
-                    if let Some(next) = list.next_cursor {
-                        cursor = Some(next);
-                    } else {
-                        break;
-                    }
-                }
-                Err(err) => {
-                    if self.handle_auth_error(&err) {
-                        return Ok(());
-                    }
-                    self.state.stats.error = Some(login_message_for_error(err));
-                    return Ok(());
-                }
-            }
-        }
+        let query = |group_by, from, to, kinds| ReportQuery {
+            vault_id: vault_id.clone(),
+            group_by: vec![group_by],
+            from: Some(from),
+            to: Some(to),
+            kinds,
+            utc_offset_minutes: Some(offset.local_minus_utc() / 60),
+        };
+        let Some(monthly) = self
+            .stats_report(query(ReportGroupBy::Month, months_from, to, None))
+            .await
+        else {
+            return Ok(());
+        };
+        let Some(breakdown) = self
+            .stats_report(query(
+                ReportGroupBy::Category,
+                breakdown_from,
+                breakdown_to,
+                Some(vec![TransactionKind::Expense]),
+            ))
+            .await
+        else {
+            return Ok(());
+        };
 
-        self.compute_stats_series(&transactions, to);
+        self.compute_stats_series(&net_worth.points, &monthly.rows, &breakdown.rows, months);
         Ok(())
     }
 
-    fn compute_stats_series(
+    /// Fetches a balance series for the stats screen; on failure records the
+    /// error and returns `None`.
+    async fn stats_balance_history(
         &mut self,
-        transactions: &[TransactionView],
-        to: DateTime<FixedOffset>,
-    ) {
-        use api_types::transaction::TransactionKind;
-        use std::collections::HashMap;
-
-        let tz = Tz::from_str(self.config.timezone.as_str()).unwrap_or(Tz::UTC);
-        let start_day = (to - chrono::Duration::days(29))
-            .with_timezone(&tz)
-            .date_naive();
-        let end_day = to.with_timezone(&tz).date_naive();
-        let days_count = (end_day - start_day).num_days().max(0) as usize + 1;
-        let mut daily_net = vec![0i64; days_count];
-
-        let mut category_breakdown: HashMap<String, i64> = HashMap::new();
-        let mut monthly_income: HashMap<(i32, u32), i64> = HashMap::new();
-        let mut monthly_expense: HashMap<(i32, u32), (i64, i64)> = HashMap::new(); // (expense, refund)
-
-        let (current_year, current_month) = self.state.stats.current_month;
-
-        for tx in transactions {
-            if tx.voided {
-                continue;
-            }
-
-            let local = tx.occurred_at.with_timezone(&tz);
-            let date = local.date_naive();
-            let year = date.year();
-            let month = date.month();
-
-            match tx.kind {
-                TransactionKind::Income => {
-                    if date >= start_day && date <= end_day {
-                        let idx = (date - start_day).num_days() as usize;
-                        daily_net[idx] += tx.amount_minor.abs();
-                    }
-                    *monthly_income.entry((year, month)).or_insert(0) += tx.amount_minor.abs();
-                }
-                TransactionKind::Expense => {
-                    if date >= start_day && date <= end_day {
-                        let idx = (date - start_day).num_days() as usize;
-                        daily_net[idx] -= tx.amount_minor.abs();
-                    }
-                    let entry = monthly_expense.entry((year, month)).or_insert((0, 0));
-                    entry.0 += tx.amount_minor.abs();
-
-                    if year == current_year && month == current_month {
-                        let category = tx.category.clone().unwrap_or_else(|| "Other".to_string());
-                        *category_breakdown.entry(category).or_insert(0) += tx.amount_minor.abs();
-                    }
-                }
-                TransactionKind::Refund => {
-                    if date >= start_day && date <= end_day {
-                        let idx = (date - start_day).num_days() as usize;
-                        daily_net[idx] += tx.amount_minor.abs();
-                    }
-                    let entry = monthly_expense.entry((year, month)).or_insert((0, 0));
-                    entry.1 += tx.amount_minor.abs();
+        payload: BalanceHistoryQuery,
+    ) -> Option<BalanceHistoryResponse> {
+        let res = self
+            .client
+            .balance_history(
+                self.state.login.username.as_str(),
+                self.state.login.password.as_str(),
+                payload,
+            )
+            .await;
+        match res {
+            Ok(history) => Some(history),
+            Err(err) => {
+                if !self.handle_auth_error(&err) {
+                    self.state.stats.error = Some(login_message_for_error(err));
                 }
-                TransactionKind::TransferWallet | TransactionKind::TransferFlow => {}
+                None
             }
         }
+    }
 
-        let mut cumulative = Vec::with_capacity(daily_net.len());
-        let mut running = 0i64;
-        for delta in daily_net {
-            running += delta;
-            cumulative.push(running);
+    /// Fetches a report for the stats screen; on failure records the error
+    /// and returns `None`.
+    async fn stats_report(&mut self, payload: ReportQuery) -> Option<ReportResponse> {
+        let res = self
+            .client
+            .report(
+                self.state.login.username.as_str(),
+                self.state.login.password.as_str(),
+                payload,
+            )
+            .await;
+        match res {
+            Ok(report) => Some(report),
+            Err(err) => {
+                if !self.handle_auth_error(&err) {
+                    self.state.stats.error = Some(login_message_for_error(err));
+                }
+                None
+            }
         }
+    }
 
-        let min = cumulative.iter().copied().min().unwrap_or(0);
-        let max = cumulative.iter().copied().max().unwrap_or(0);
+    fn compute_stats_series(
+        &mut self,
+        net_worth: &[BalancePointView],
+        monthly: &[ReportRowView],
+        breakdown: &[ReportRowView],
+        months: Vec<(i32, u32, String)>,
+    ) {
+        // Closing net worth of each day, oldest first.
+        let min = net_worth
+            .iter()
+            .map(|point| point.balance_minor)
+            .min()
+            .unwrap_or(0);
+        let max = net_worth
+            .iter()
+            .map(|point| point.balance_minor)
+            .max()
+            .unwrap_or(0);
         let shift = if min < 0 { -min } else { 0 };
-        let sparkline = cumulative
+        let sparkline = net_worth
             .iter()
-            .map(|value| (value + shift) as u64)
+            .map(|point| (point.balance_minor + shift) as u64)
             .collect::<Vec<_>>();
 
-        let mut breakdown = category_breakdown.into_iter().collect::<Vec<_>>();
-        breakdown.sort_by(|a, b| b.1.cmp(&a.1));
+        // Rows come sorted by expense, largest first.
+        let breakdown = breakdown
+            .iter()
+            .filter(|row| row.expense_minor > 0)
+            .map(|row| {
+                (
+                    row.category.clone().unwrap_or_else(|| "Other".to_string()),
+                    row.expense_minor,
+                )
+            })
+            .collect::<Vec<_>>();
 
-        let months = Self::build_last_months(to, 6);
         let mut monthly_expenses_vec = Vec::new();
         let mut monthly_income_vec = Vec::new();
         for (year, month, label) in months {
-            let income = monthly_income.get(&(year, month)).copied().unwrap_or(0);
-            let (expense, refund) = monthly_expense
-                .get(&(year, month))
-                .copied()
-                .unwrap_or((0, 0));
-            let net_expense = (expense - refund).max(0);
+            let row = monthly.iter().find(|row| {
+                row.period_start
+                    .is_some_and(|day| day.year() == year && day.month() == month)
+            });
+            let income = row.map_or(0, |row| row.income_minor);
+            let net_expense = row.map_or(0, |row| (row.expense_minor - row.refund_minor).max(0));
             monthly_income_vec.push((label.clone(), income));
             monthly_expenses_vec.push((label, net_expense));
         }
@@ -4745,23 +4875,13 @@ pub struct ConnectionState {
     pub message: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct CommandPaletteState {
     pub active: bool,
     pub query: String,
     pub selected: usize,
 }
 
-impl Default for CommandPaletteState {
-    fn default() -> Self {
-        Self {
-            active: false,
-            query: String::new(),
-            selected: 0,
-        }
-    }
-}
-
 #[derive(Debug)]
 pub struct WalletsState {
     pub selected: usize,
@@ -4860,25 +4980,14 @@ pub enum FlowsMode {
     Rename,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct FlowDetailState {
     pub flow_id: Option<uuid::Uuid>,
     pub transactions: Vec<TransactionView>,
-    pub detail: Option<engine::CashFlow>,
+    pub detail: Option<api_types::cash_flow::CashFlowView>,
     pub error: Option<String>,
 }
 
-impl Default for FlowDetailState {
-    fn default() -> Self {
-        Self {
-            flow_id: None,
-            transactions: Vec::new(),
-            detail: None,
-            error: None,
-        }
-    }
-}
-
 #[derive(Debug)]
 pub struct FlowFormState {
     pub name: String,
@@ -4953,21 +5062,12 @@ pub enum VaultMode {
     Defaults,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct VaultFormState {
     pub name: String,
     pub error: Option<String>,
 }
 
-impl Default for VaultFormState {
-    fn default() -> Self {
-        Self {
-            name: String::new(),
-            error: None,
-        }
-    }
-}
-
 #[derive(Debug)]
 pub struct DefaultsFormState {
     pub wallet_index: usize,
@@ -5005,7 +5105,7 @@ pub struct StatsState {
     pub monthly_trend: Vec<(String, i64)>,
     /// Monthly trend data (last 6 months of income)
     pub monthly_income: Vec<(String, i64)>,
-    /// Sparkline data for last 30 days (shifted to >= 0)
+    /// Daily net worth over the last 30 days (shifted to >= 0)
     pub sparkline: Vec<u64>,
     pub sparkline_min: i64,
     pub sparkline_max: i64,
@@ -5097,29 +5197,52 @@ fn fuzzy_score(label: &str, query: &str) -> Option<usize> {
     let mut score = 0usize;
     let mut pos = 0usize;
     for ch in query.chars() {
-        if let Some(idx) = label[pos..].find(ch) {
-            score += idx;
-            pos += idx + 1;
-        } else {
-            return None;
-        }
+        let idx = label[pos..].find(ch)?;
+        score += idx;
+        pos += idx + 1;
     }
     Some(score)
 }
 
 fn login_message_for_error(err: ClientError) -> String {
     match err {
-        ClientError::Unauthorized | ClientError::Forbidden => {
-            "Credenziali errate o pairing mancante.".to_string()
-        }
-        ClientError::NotFound => "Vault non trovato.".to_string(),
-        ClientError::Conflict(message) => format!("Conflitto: {message}"),
-        ClientError::Validation(message) => format!("Errore di validazione: {message}"),
+        ClientError::Unauthorized => "Credenziali errate o pairing mancante.".to_string(),
+        ClientError::Api(err) => message_for_api_error(err),
         ClientError::Server(message) => format!("Errore server: {message}"),
         ClientError::Transport(err) => format!("Server non raggiungibile: {err}"),
     }
 }
 
+fn message_for_api_error(err: ErrorResponse) -> String {
+    if let Some(ErrorDetails::FlowLimit {
+        flow_name,
+        currency,
+        excess_minor,
+        ..
+    }) = &err.details
+    {
+        let excess = Money::new(*excess_minor).format(map_currency(currency));
+        match err.code {
+            ErrorCode::MaxBalanceReached => {
+                return format!("Il tetto di {flow_name} verrebbe superato di {excess}.");
+            }
+            ErrorCode::InsufficientFunds => {
+                return format!("Fondi insufficienti in {flow_name}: mancano {excess}.");
+            }
+            _ => {}
+        }
+    }
+    let message = err.message;
+    match err.code {
+        ErrorCode::Unauthorized => "Credenziali errate o pairing mancante.".to_string(),
+        ErrorCode::Forbidden => "Operazione non permessa.".to_string(),
+        ErrorCode::NotFound => format!("Non trovato: {message}"),
+        ErrorCode::AlreadyExists => format!("Conflitto: {message}"),
+        ErrorCode::Internal => format!("Errore server: {message}"),
+        _ => format!("Errore di validazione: {message}"),
+    }
+}
+
 fn extract_wallet_flow(
     detail: &TransactionDetailResponse,
 ) -> (Option<uuid::Uuid>, Option<uuid::Uuid>) {
@@ -5182,12 +5305,6 @@ fn extract_flow_transfer(
     }
 }
 
-fn map_currency(currency: &api_types::Currency) -> engine::Currency {
-    match currency {
-        api_types::Currency::Eur => engine::Currency::Eur,
-    }
-}
-
 fn month_label(month: u32) -> String {
     let label = match month {
         1 => "Jan",
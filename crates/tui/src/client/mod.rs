@@ -1,37 +1,33 @@
 use api_types::{
+    balance::{BalanceHistoryQuery, BalanceHistoryResponse},
     cash_flow::CashFlowGet,
+    error::{ErrorCode, ErrorResponse},
     flow::{FlowCreated, FlowNew, FlowUpdate},
+    report::{ReportQuery, ReportResponse},
     stats::Statistic,
     transaction::{
         ExpenseNew, IncomeNew, Refund, TransactionCreated, TransactionDetailResponse,
-        TransactionGet, TransactionList, TransactionListResponse, TransactionUpdate,
-        TransactionVoid, TransferFlowNew, TransferWalletNew,
+        TransactionGet, TransactionList, TransactionListResponse, TransactionRestore,
+        TransactionUpdate, TransactionVoid, TransferFlowNew, TransferWalletNew,
     },
     vault::{Vault, VaultNew, VaultSnapshot},
     wallet::{WalletCreated, WalletNew, WalletUpdate},
 };
 use reqwest::Url;
 
-use serde::{Deserialize, de::DeserializeOwned};
+use serde::de::DeserializeOwned;
 
 use crate::error::{AppError, Result};
 
 #[derive(Debug)]
 pub enum ClientError {
     Unauthorized,
-    Forbidden,
-    NotFound,
-    Conflict(String),
-    Validation(String),
+    /// The server rejected the request; react to [`ErrorResponse::code`].
+    Api(ErrorResponse),
     Server(String),
     Transport(reqwest::Error),
 }
 
-#[derive(Debug, Deserialize)]
-struct ErrorResponse {
-    error: String,
-}
-
 #[derive(Debug, Clone)]
 pub struct Client {
     base_url: Url,
@@ -152,6 +148,52 @@ impl Client {
         handle_json(res).await
     }
 
+    pub async fn report(
+        &self,
+        username: &str,
+        password: &str,
+        payload: ReportQuery,
+    ) -> std::result::Result<ReportResponse, ClientError> {
+        let endpoint = self
+            .base_url
+            .join("reports")
+            .map_err(|err| ClientError::Server(format!("invalid base_url: {err}")))?;
+
+        let res = self
+            .http
+            .post(endpoint)
+            .basic_auth(username, Some(password))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(ClientError::Transport)?;
+
+        handle_json(res).await
+    }
+
+    pub async fn balance_history(
+        &self,
+        username: &str,
+        password: &str,
+        payload: BalanceHistoryQuery,
+    ) -> std::result::Result<BalanceHistoryResponse, ClientError> {
+        let endpoint = self
+            .base_url
+            .join("balances/history")
+            .map_err(|err| ClientError::Server(format!("invalid base_url: {err}")))?;
+
+        let res = self
+            .http
+            .post(endpoint)
+            .basic_auth(username, Some(password))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(ClientError::Transport)?;
+
+        handle_json(res).await
+    }
+
     pub async fn transactions_list(
         &self,
         username: &str,
@@ -222,6 +264,30 @@ impl Client {
         handle_empty(res).await
     }
 
+    pub async fn transaction_restore(
+        &self,
+        username: &str,
+        password: &str,
+        transaction_id: uuid::Uuid,
+        payload: TransactionRestore,
+    ) -> std::result::Result<(), ClientError> {
+        let endpoint = self
+            .base_url
+            .join(&format!("transactions/{transaction_id}/restore"))
+            .map_err(|err| ClientError::Server(format!("invalid base_url: {err}")))?;
+
+        let res = self
+            .http
+            .post(endpoint)
+            .basic_auth(username, Some(password))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(ClientError::Transport)?;
+
+        handle_empty(res).await
+    }
+
     pub async fn transaction_update(
         &self,
         username: &str,
@@ -390,7 +456,7 @@ impl Client {
         username: &str,
         password: &str,
         payload: CashFlowGet,
-    ) -> std::result::Result<engine::CashFlow, ClientError> {
+    ) -> std::result::Result<api_types::cash_flow::CashFlowView, ClientError> {
         let endpoint = self
             .base_url
             .join("cashFlow/get")
@@ -440,36 +506,32 @@ async fn handle_json<T: DeserializeOwned>(
         return res.json::<T>().await.map_err(ClientError::Transport);
     }
 
-    let status = res.status();
-    let body = res
-        .json::<ErrorResponse>()
-        .await
-        .map(|err| err.error)
-        .unwrap_or_else(|_| "unknown error".to_string());
-
-    Err(map_error(status.as_u16(), body))
+    Err(map_error(res).await)
 }
 
 async fn handle_empty(res: reqwest::Response) -> std::result::Result<(), ClientError> {
     if res.status().is_success() {
         return Ok(());
     }
+    Err(map_error(res).await)
+}
+
+async fn map_error(res: reqwest::Response) -> ClientError {
     let status = res.status();
-    let body = res
+    let error = res
         .json::<ErrorResponse>()
         .await
-        .map(|err| err.error)
-        .unwrap_or_else(|_| "unknown error".to_string());
-    Err(map_error(status.as_u16(), body))
-}
-
-fn map_error(status: u16, body: String) -> ClientError {
-    match status {
-        401 => ClientError::Unauthorized,
-        403 => ClientError::Forbidden,
-        404 => ClientError::NotFound,
-        409 => ClientError::Conflict(body),
-        422 => ClientError::Validation(body),
-        _ => ClientError::Server(body),
+        .unwrap_or_else(|_| ErrorResponse {
+            code: if status.is_server_error() {
+                ErrorCode::Internal
+            } else {
+                ErrorCode::BadRequest
+            },
+            message: "unknown error".to_string(),
+            details: None,
+        });
+    match error.code {
+        ErrorCode::Unauthorized => ClientError::Unauthorized,
+        _ => ClientError::Api(error),
     }
 }
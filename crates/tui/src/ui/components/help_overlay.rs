@@ -106,7 +106,7 @@ fn help_lines(state: &AppState, theme: &Theme) -> Vec<Line<'static>> {
                         Span::styled("r", Style::default().fg(theme.accent)),
                         Span::raw(" repeat  "),
                         Span::styled("v", Style::default().fg(theme.accent)),
-                        Span::raw(" void"),
+                        Span::raw(" void/restore"),
                     ]));
                 }
                 TransactionsMode::TransferWallet | TransactionsMode::TransferFlow => {
@@ -7,6 +7,38 @@ use ratatui::{
 
 use crate::ui::theme::Theme;
 
+/// Maps an API currency to the engine currency used for formatting.
+#[must_use]
+pub fn map_currency(currency: &api_types::Currency) -> Currency {
+    match currency {
+        api_types::Currency::Eur => Currency::Eur,
+        api_types::Currency::Usd => Currency::Usd,
+        api_types::Currency::Gbp => Currency::Gbp,
+        api_types::Currency::Chf => Currency::Chf,
+        api_types::Currency::Jpy => Currency::Jpy,
+        api_types::Currency::Cad => Currency::Cad,
+        api_types::Currency::Aud => Currency::Aud,
+        api_types::Currency::Nzd => Currency::Nzd,
+        api_types::Currency::Sek => Currency::Sek,
+        api_types::Currency::Nok => Currency::Nok,
+        api_types::Currency::Dkk => Currency::Dkk,
+        api_types::Currency::Pln => Currency::Pln,
+        api_types::Currency::Czk => Currency::Czk,
+        api_types::Currency::Huf => Currency::Huf,
+        api_types::Currency::Ron => Currency::Ron,
+        api_types::Currency::Cny => Currency::Cny,
+        api_types::Currency::Inr => Currency::Inr,
+        api_types::Currency::Krw => Currency::Krw,
+        api_types::Currency::Brl => Currency::Brl,
+        api_types::Currency::Mxn => Currency::Mxn,
+        api_types::Currency::Bhd => Currency::Bhd,
+        api_types::Currency::Kwd => Currency::Kwd,
+        api_types::Currency::Omr => Currency::Omr,
+        api_types::Currency::Jod => Currency::Jod,
+        api_types::Currency::Tnd => Currency::Tnd,
+    }
+}
+
 /// Creates a styled span for a money amount with semantic coloring.
 ///
 /// - Positive amounts: green with `+` prefix
@@ -184,6 +216,43 @@ pub fn styled_progress_bar(
     Span::styled(bar, Style::default().fg(color))
 }
 
+/// Creates a line gauge for savings goal progress.
+///
+/// Unlike caps, filling up is good: the gauge turns positive once the goal
+/// is reached.
+#[must_use]
+pub fn goal_line_gauge(saved: i64, target: i64, theme: &Theme) -> Option<LineGauge<'static>> {
+    if target <= 0 {
+        return None;
+    }
+    let ratio = (saved as f64 / target as f64).clamp(0.0, 1.0);
+    let gauge_color = if ratio >= 1.0 {
+        theme.positive
+    } else {
+        theme.accent
+    };
+
+    Some(
+        LineGauge::default()
+            .filled_style(Style::default().fg(gauge_color))
+            .line_set(ratatui::symbols::line::THICK)
+            .ratio(ratio),
+    )
+}
+
+/// Creates a styled inline progress bar for savings goal progress.
+#[must_use]
+pub fn styled_goal_bar(saved: i64, target: i64, width: usize, theme: &Theme) -> Span<'static> {
+    let bar = inline_progress_bar(saved, Some(target), width);
+    let color = if target > 0 && saved >= target {
+        theme.positive
+    } else {
+        theme.accent
+    };
+
+    Span::styled(bar, Style::default().fg(color))
+}
+
 /// Formats a percentage change with appropriate styling.
 ///
 /// Returns something like `▲ +2.3%` (green) or `▼ -1.5%` (red).
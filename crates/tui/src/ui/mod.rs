@@ -188,7 +188,15 @@ fn get_transactions_hints(state: &AppState) -> Vec<components::hints::KeyHint> {
         crate::app::TransactionsMode::Detail => {
             let mut hints = components::hints::common::detail_view();
             hints.push(components::hints::KeyHint::new("e", "edit"));
-            hints.push(components::hints::KeyHint::new("v", "void"));
+            let voided = state
+                .transactions
+                .detail
+                .as_ref()
+                .is_some_and(|detail| detail.transaction.voided);
+            hints.push(components::hints::KeyHint::new(
+                "v",
+                if voided { "restore" } else { "void" },
+            ));
             hints.push(components::hints::KeyHint::new("r", "repeat"));
             hints
         }
@@ -12,7 +12,10 @@ use engine::{Currency, Money};
 use crate::{
     app::{AppState, FlowFormField, FlowModeChoice, FlowsMode, flows_visible_indices},
     ui::{
-        components::money::{flow_cap_line_gauge, styled_amount_no_sign, styled_progress_bar},
+        components::money::{
+            flow_cap_line_gauge, goal_line_gauge, map_currency, styled_amount_no_sign,
+            styled_goal_bar, styled_progress_bar,
+        },
         theme::Theme,
     },
 };
@@ -143,6 +146,15 @@ fn render_list(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Them
             }
             spans.push(Span::raw("  "));
             spans.push(balance_span(flow.balance_minor, currency, theme));
+            if let Some(goal) = flow.goal.as_ref() {
+                spans.push(Span::raw(" "));
+                spans.push(styled_goal_bar(
+                    goal.saved_minor,
+                    goal.target_minor,
+                    10,
+                    theme,
+                ));
+            }
             ListItem::new(Line::from(spans))
         })
         .collect::<Vec<_>>();
@@ -281,11 +293,19 @@ fn render_detail(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Th
         .detail
         .as_ref()
         .and_then(|detail| cap_line_gauge(detail, theme));
-    let header_height = if cap_line.is_some() || cap_gauge.is_some() {
-        6
-    } else {
-        5
-    };
+    let goal_lines = flow
+        .goal
+        .as_ref()
+        .map(|goal| goal_progress_lines(goal, currency, theme))
+        .unwrap_or_default();
+    let goal_gauge = flow
+        .goal
+        .as_ref()
+        .and_then(|goal| goal_line_gauge(goal.saved_minor, goal.target_minor, theme));
+    let gauges: Vec<_> = cap_gauge.into_iter().chain(goal_gauge).collect();
+    let header_height = 5
+        + u16::from(cap_line.is_some())
+        + u16::try_from(goal_lines.len() + gauges.len()).unwrap_or(0);
 
     let layout = Layout::default()
         .direction(Direction::Vertical)
@@ -322,6 +342,7 @@ fn render_detail(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Th
     if let Some(line) = cap_line {
         header_lines.push(line);
     }
+    header_lines.extend(goal_lines);
     let header_block = Block::default()
         .title("Flow Detail")
         .borders(Borders::ALL)
@@ -329,15 +350,15 @@ fn render_detail(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Th
         .border_style(Style::default().fg(theme.accent));
     let header_inner = header_block.inner(layout[0]);
     frame.render_widget(header_block, layout[0]);
-    if let Some(gauge) = cap_gauge {
-        let split = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Min(0), Constraint::Length(1)])
-            .split(header_inner);
-        frame.render_widget(Paragraph::new(header_lines), split[0]);
-        frame.render_widget(gauge, split[1]);
-    } else {
-        frame.render_widget(Paragraph::new(header_lines), header_inner);
+    let split = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            std::iter::once(Constraint::Min(0)).chain(gauges.iter().map(|_| Constraint::Length(1))),
+        )
+        .split(header_inner);
+    frame.render_widget(Paragraph::new(header_lines), split[0]);
+    for (gauge, area) in gauges.into_iter().zip(split.iter().skip(1)) {
+        frame.render_widget(gauge, *area);
     }
 
     if let Some(err) = state.flows.detail.error.as_ref() {
@@ -459,14 +480,8 @@ fn kind_chip(kind: TransactionKind, theme: &Theme) -> Span<'static> {
     )
 }
 
-fn map_currency(currency: &api_types::Currency) -> Currency {
-    match currency {
-        api_types::Currency::Eur => Currency::Eur,
-    }
-}
-
 fn cap_progress_line(
-    detail: &engine::CashFlow,
+    detail: &api_types::cash_flow::CashFlowView,
     currency: Currency,
     theme: &Theme,
 ) -> Option<Line<'static>> {
@@ -498,7 +513,7 @@ fn cap_progress_line(
 }
 
 fn cap_line_gauge(
-    detail: &engine::CashFlow,
+    detail: &api_types::cash_flow::CashFlowView,
     theme: &Theme,
 ) -> Option<ratatui::widgets::LineGauge<'static>> {
     let cap = detail.max_balance?;
@@ -512,3 +527,45 @@ fn cap_line_gauge(
     };
     flow_cap_line_gauge(current.max(0), Some(cap), theme)
 }
+
+fn goal_progress_lines(
+    goal: &api_types::vault::FlowGoalView,
+    currency: Currency,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let mut progress = vec![
+        Span::styled("Goal", Style::default().fg(theme.dim)),
+        Span::raw(": "),
+        styled_amount_no_sign(goal.saved_minor, currency, theme),
+        Span::raw(" / "),
+        styled_amount_no_sign(goal.target_minor, currency, theme),
+        Span::raw(format!(" ({}%)", goal.progress_bps / 100)),
+    ];
+    if let Some(date) = goal.target_date {
+        progress.push(Span::raw(format!(" by {}", date.format("%d %b %Y"))));
+    }
+
+    let mut pace = vec![
+        Span::styled("Monthly", Style::default().fg(theme.dim)),
+        Span::raw(": "),
+    ];
+    if let Some(required) = goal.required_monthly_minor {
+        pace.push(Span::raw("need "));
+        pace.push(styled_amount_no_sign(required, currency, theme));
+        pace.push(Span::raw(" • "));
+    }
+    pace.push(Span::raw("avg "));
+    pace.push(signed_amount_span(
+        goal.monthly_contribution_minor,
+        currency,
+        theme,
+    ));
+    if goal.remaining_minor == 0 {
+        pace.push(Span::raw(" • "));
+        pace.push(Span::styled("reached", Style::default().fg(theme.positive)));
+    } else if let Some(date) = goal.projected_completion {
+        pace.push(Span::raw(format!(" • ETA {}", date.format("%b %Y"))));
+    }
+
+    vec![Line::from(progress), Line::from(pace)]
+}
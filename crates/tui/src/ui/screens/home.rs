@@ -15,7 +15,7 @@ use crate::{
         components::{
             card::{Card, StatCard},
             charts::{ascii_bar, mini_bar_chart},
-            money::{inline_progress_bar, styled_amount},
+            money::{inline_progress_bar, map_currency, styled_amount},
         },
         theme::Theme,
     },
@@ -44,11 +44,17 @@ pub fn render(frame: &mut Frame<'_>, area: Rect, state: &AppState) {
 fn render_quick_stats(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Theme) {
     let currency = get_currency(state);
 
-    // Calculate totals
+    // Calculate totals (only wallets denominated in the vault currency add up)
     let total_balance: i64 = state
         .snapshot
         .as_ref()
-        .map(|snap| snap.wallets.iter().map(|w| w.balance_minor).sum())
+        .map(|snap| {
+            snap.wallets
+                .iter()
+                .filter(|w| map_currency(&w.currency) == currency)
+                .map(|w| w.balance_minor)
+                .sum()
+        })
         .unwrap_or(0);
 
     let (income, expenses) = state
@@ -152,8 +158,6 @@ fn render_wallets_flows(frame: &mut Frame<'_>, area: Rect, state: &AppState, the
 }
 
 fn render_wallets_panel(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Theme) {
-    let currency = get_currency(state);
-
     let card = Card::new("Wallets", theme);
     let inner = card.inner(area);
     card.render_frame(frame, area);
@@ -167,7 +171,8 @@ fn render_wallets_panel(frame: &mut Frame<'_>, area: Rect, state: &AppState, the
                 .filter(|w| !w.archived)
                 .take(inner.height as usize)
                 .map(|wallet| {
-                    let balance = styled_amount(wallet.balance_minor, currency, theme);
+                    let balance =
+                        styled_amount(wallet.balance_minor, map_currency(&wallet.currency), theme);
                     let name = Span::styled(&wallet.name, Style::default().fg(theme.text));
 
                     ListItem::new(Line::from(vec![name, Span::raw("  "), balance]))
@@ -254,8 +259,6 @@ fn render_flows_panel(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme
 }
 
 fn render_recent_transactions(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Theme) {
-    let currency = get_currency(state);
-
     let card = Card::new("Recent Transactions", theme);
     let inner = card.inner(area);
     card.render_frame(frame, area);
@@ -274,7 +277,7 @@ fn render_recent_transactions(frame: &mut Frame<'_>, area: Rect, state: &AppStat
                 } else {
                     tx.amount_minor
                 },
-                currency,
+                map_currency(&tx.currency),
                 theme,
             );
 
@@ -346,12 +349,6 @@ fn get_currency(state: &AppState) -> Currency {
         .unwrap_or(Currency::Eur)
 }
 
-fn map_currency(currency: &api_types::Currency) -> Currency {
-    match currency {
-        api_types::Currency::Eur => Currency::Eur,
-    }
-}
-
 fn kind_label(kind: TransactionKind) -> &'static str {
     match kind {
         TransactionKind::Income => "▲ Income",
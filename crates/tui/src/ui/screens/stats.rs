@@ -18,7 +18,8 @@ use crate::{
                 render_inline_sparkline, render_sparkline as render_sparkline_card,
             },
             money::{
-                flow_cap_gauge, styled_amount_bold, styled_amount_no_sign, styled_percentage_change,
+                flow_cap_gauge, map_currency, styled_amount_bold, styled_amount_no_sign,
+                styled_percentage_change,
             },
         },
         theme::Theme,
@@ -257,6 +258,7 @@ fn render_month_summary(frame: &mut Frame<'_>, area: Rect, state: &AppState, the
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn render_stat_row(
     frame: &mut Frame<'_>,
     area: Rect,
@@ -485,7 +487,7 @@ fn render_monthly_trend(frame: &mut Frame<'_>, area: Rect, state: &AppState, the
 
 fn render_sparkline(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Theme) {
     if state.stats.sparkline.is_empty() {
-        let card = Card::new("Balance Trend (30d)", theme);
+        let card = Card::new("Net Worth (30d)", theme);
         let inner = card.inner(area);
         card.render_frame(frame, area);
         frame.render_widget(
@@ -502,7 +504,7 @@ fn render_sparkline(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme:
     render_sparkline_card(
         frame,
         area,
-        "Balance Trend (30d)",
+        "Net Worth (30d)",
         &state.stats.sparkline,
         theme,
     );
@@ -517,12 +519,6 @@ fn get_currency(state: &AppState) -> Currency {
         .unwrap_or(Currency::Eur)
 }
 
-fn map_currency(currency: &api_types::Currency) -> Currency {
-    match currency {
-        api_types::Currency::Eur => Currency::Eur,
-    }
-}
-
 fn percentage_change(series: &[(String, i64)]) -> Option<f64> {
     if series.len() < 2 {
         return None;
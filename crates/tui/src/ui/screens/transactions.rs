@@ -15,7 +15,10 @@ use crate::{
         AppState, FilterField, TransactionFormField, TransactionsMode, TransferField,
         ordered_flow_ids_from_state, ordered_wallet_ids_from_state, transactions_visible_indices,
     },
-    ui::{components::centered_rect, theme::Theme},
+    ui::{
+        components::{centered_rect, money::map_currency},
+        theme::Theme,
+    },
 };
 
 pub fn render(frame: &mut Frame<'_>, area: Rect, state: &AppState) {
@@ -150,13 +153,6 @@ fn render_list(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Them
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(theme.border));
 
-    let currency = state
-        .vault
-        .as_ref()
-        .and_then(|v| v.currency.as_ref())
-        .map(map_currency)
-        .unwrap_or(Currency::Eur);
-
     let mut rows = Vec::new();
     let mut selected_row = None;
     let mut last_day = None;
@@ -210,19 +206,36 @@ fn render_list(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Them
             .map(|c| format!("#{c}"))
             .unwrap_or_default();
 
-        let mut spans = Vec::new();
-        spans.push(Span::styled(
-            tx.occurred_at.format("%H:%M").to_string(),
-            Style::default().fg(theme.dim),
-        ));
-        spans.push(Span::raw("  "));
-        spans.push(kind_chip(tx.kind, theme));
-        spans.push(Span::raw(" "));
+        let mut spans = vec![
+            Span::styled(
+                tx.occurred_at.format("%H:%M").to_string(),
+                Style::default().fg(theme.dim),
+            ),
+            Span::raw("  "),
+            kind_chip(tx.kind, theme),
+            Span::raw(" "),
+        ];
         if let Some(voided) = void_chip(tx.voided, theme) {
             spans.push(voided);
             spans.push(Span::raw(" "));
         }
-        spans.push(amount_span(tx.kind, tx.amount_minor, currency, theme));
+        spans.push(amount_span(
+            tx.kind,
+            tx.amount_minor,
+            map_currency(&tx.currency),
+            theme,
+        ));
+        if let Some(status) = tx.refund_status.as_ref().filter(|s| s.refunded_minor > 0) {
+            let currency = map_currency(&status.currency);
+            spans.push(Span::styled(
+                format!(
+                    " ↩ {}/{}",
+                    Money::new(status.refunded_minor).format(currency),
+                    Money::new(status.total_minor).format(currency),
+                ),
+                Style::default().fg(theme.dim),
+            ));
+        }
         spans.push(Span::raw("  "));
         if !category.is_empty() {
             spans.push(Span::styled(category, Style::default().fg(theme.accent)));
@@ -975,16 +988,25 @@ fn render_detail(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Th
         .constraints([Constraint::Length(7), Constraint::Min(0)])
         .split(area);
 
-    let currency = state
-        .vault
-        .as_ref()
-        .and_then(|v| v.currency.as_ref())
-        .map(map_currency)
-        .unwrap_or(Currency::Eur);
-
     let header = &detail.transaction;
     let occurred_at = header.occurred_at.format("%d %b %Y %H:%M").to_string();
-    let amount = Money::new(header.amount_minor).format(currency);
+    let mut amount = Money::new(header.amount_minor).format(map_currency(&header.currency));
+    if let (Some(original_minor), Some(original_currency), Some(rate)) = (
+        header.original_amount_minor,
+        header.original_currency.as_ref(),
+        header.exchange_rate.as_deref(),
+    ) {
+        let original = Money::new(original_minor).format(map_currency(original_currency));
+        amount = format!("{amount} ({original} @ {rate})");
+    }
+    if let Some(status) = &header.refund_status {
+        let currency = map_currency(&status.currency);
+        amount = format!(
+            "{amount}  refunded {} of {}",
+            Money::new(status.refunded_minor).format(currency),
+            Money::new(status.total_minor).format(currency),
+        );
+    }
     let category = header
         .category
         .as_deref()
@@ -1047,7 +1069,7 @@ fn render_detail(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Th
                 LegTarget::Wallet { .. } => "Wallet",
                 LegTarget::Flow { .. } => "Flow",
             };
-            let amount = leg_amount_span(leg.amount_minor, currency, theme);
+            let amount = leg_amount_span(leg.amount_minor, map_currency(&leg.currency), theme);
             ListItem::new(Line::from(vec![
                 Span::styled(format!("{label:<6}"), Style::default().fg(theme.dim)),
                 Span::raw(": "),
@@ -1127,12 +1149,6 @@ fn leg_amount_span(amount_minor: i64, currency: Currency, theme: &Theme) -> Span
     Span::styled(amount, Style::default().fg(color))
 }
 
-fn map_currency(currency: &api_types::Currency) -> Currency {
-    match currency {
-        api_types::Currency::Eur => Currency::Eur,
-    }
-}
-
 fn resolve_wallet_name(state: &AppState, wallet_id: Uuid) -> String {
     state
         .snapshot
@@ -1333,21 +1349,21 @@ fn filter_summary(state: &AppState) -> Option<String> {
     if let Some(to) = state.transactions.filter_to {
         parts.push(format!("to {}", to.format("%Y-%m-%d")));
     }
-    if let Some(kinds) = state.transactions.filter_kinds.as_ref() {
-        if !kinds.is_empty() {
-            let labels = kinds
-                .iter()
-                .map(|kind| match kind {
-                    TransactionKind::Income => "inc",
-                    TransactionKind::Expense => "exp",
-                    TransactionKind::Refund => "ref",
-                    TransactionKind::TransferWallet => "tw",
-                    TransactionKind::TransferFlow => "tf",
-                })
-                .collect::<Vec<_>>()
-                .join(",");
-            parts.push(format!("kinds {labels}"));
-        }
+    if let Some(kinds) = state.transactions.filter_kinds.as_ref()
+        && !kinds.is_empty()
+    {
+        let labels = kinds
+            .iter()
+            .map(|kind| match kind {
+                TransactionKind::Income => "inc",
+                TransactionKind::Expense => "exp",
+                TransactionKind::Refund => "ref",
+                TransactionKind::TransferWallet => "tw",
+                TransactionKind::TransferFlow => "tf",
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        parts.push(format!("kinds {labels}"));
     }
     if parts.is_empty() {
         None
@@ -11,7 +11,7 @@ use engine::{Currency, Money};
 
 use crate::{
     app::{AppState, WalletFormField, WalletsMode, wallets_visible_indices},
-    ui::theme::Theme,
+    ui::{components::money::map_currency, theme::Theme},
 };
 
 pub fn render(frame: &mut Frame<'_>, area: Rect, state: &AppState) {
@@ -114,13 +114,6 @@ fn render_list(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Them
         return;
     };
 
-    let currency = state
-        .vault
-        .as_ref()
-        .and_then(|v| v.currency.as_ref())
-        .map(map_currency)
-        .unwrap_or(Currency::Eur);
-
     let visible = wallets_visible_indices(state);
     let items = visible
         .iter()
@@ -134,7 +127,7 @@ fn render_list(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Them
             let mut spans = vec![
                 Span::styled(wallet.name.clone(), name_style),
                 Span::raw("  "),
-                balance_span(wallet.balance_minor, currency, theme),
+                balance_span(wallet.balance_minor, map_currency(&wallet.currency), theme),
             ];
             if wallet.archived {
                 spans.push(Span::raw(" "));
@@ -256,12 +249,7 @@ fn render_detail(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Th
         .constraints([Constraint::Length(5), Constraint::Min(0)])
         .split(area);
 
-    let currency = state
-        .vault
-        .as_ref()
-        .and_then(|v| v.currency.as_ref())
-        .map(map_currency)
-        .unwrap_or(Currency::Eur);
+    let currency = map_currency(&wallet.currency);
 
     let header_lines = vec![
         Line::from(vec![
@@ -321,7 +309,7 @@ fn render_detail(frame: &mut Frame<'_>, area: Rect, state: &AppState, theme: &Th
                 Span::raw(" "),
                 kind_chip(tx.kind, theme),
                 Span::raw(" "),
-                signed_amount_span(tx.amount_minor, currency, theme),
+                signed_amount_span(tx.amount_minor, map_currency(&tx.currency), theme),
                 Span::raw(" "),
                 Span::raw(note),
             ]);
@@ -408,9 +396,3 @@ fn kind_chip(kind: TransactionKind, theme: &Theme) -> Span<'static> {
         Style::default().fg(color).add_modifier(Modifier::BOLD),
     )
 }
-
-fn map_currency(currency: &api_types::Currency) -> Currency {
-    match currency {
-        api_types::Currency::Eur => Currency::Eur,
-    }
-}